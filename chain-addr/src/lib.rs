@@ -33,10 +33,11 @@
 use bech32::{self, FromBase32, ToBase32};
 use std::string::ToString;
 
-use chain_crypto::{Ed25519, PublicKey, PublicKeyError};
+use chain_crypto::{AsymmetricPublicKey, Ed25519, PublicKey, PublicKeyError};
 
-use chain_core::mempack::{ReadBuf, ReadError, Readable};
-use chain_core::property::{self, Serialize as PropertySerialize};
+use chain_core::mempack::ReadError;
+use chain_core::packer::Codec;
+use chain_core::property::{ByteSource, Pack, Serialize as PropertySerialize, Unpack};
 
 #[cfg(any(test, feature = "property-test-api"))]
 mod testing;
@@ -157,6 +158,7 @@ pub enum Error {
     InvalidInternalEncoding,
     InvalidPrefix,
     MismatchPrefix,
+    UnsupportedKind(KindType),
 }
 
 impl std::fmt::Display for Error {
@@ -168,6 +170,13 @@ impl std::fmt::Display for Error {
             Error::InvalidInternalEncoding => write!(f, "invalid internal encoding"),
             Error::InvalidPrefix => write!(f, "invalid prefix"),
             Error::MismatchPrefix => write!(f, "mismatch prefix"),
+            Error::UnsupportedKind(kind) => {
+                write!(
+                    f,
+                    "unsupported kind of address {:?} for this operation",
+                    kind
+                )
+            }
         }
     }
 }
@@ -254,6 +263,36 @@ impl Address {
             .expect("expect in memory allocation to always work")
     }
 
+    /// Serialize an address at the start of the buffer, which fits the
+    /// largest kind of address
+    fn write_bytes<'a>(&self, buf: &'a mut [u8; ADDR_SIZE_GROUP]) -> &'a [u8] {
+        let size = self.to_size();
+        self.serialize(&mut buf[..size])
+            .expect("the buffer fits the serialized address");
+        &buf[..size]
+    }
+
+    /// Write the human readable format of the address with the given
+    /// prefix, the same as [`AddressReadable::from_address`], without
+    /// allocating
+    pub fn write_bech32<W: std::fmt::Write>(
+        &self,
+        prefix: &str,
+        writer: &mut W,
+    ) -> std::fmt::Result {
+        let mut buf = [0; ADDR_SIZE_GROUP];
+        chain_crypto::bech32::write_bech32(prefix, self.write_bytes(&mut buf), writer)
+    }
+
+    /// `Display` adapter writing the human readable format of the address
+    /// with [`write_bech32`](Self::write_bech32)
+    pub fn display_bech32<'a>(&'a self, prefix: &'a str) -> AddressBech32Display<'a> {
+        AddressBech32Display {
+            address: self,
+            prefix,
+        }
+    }
+
     /// create a base32 encoding of the byte serialization
     ///
     /// This is not the official normal human representation
@@ -277,6 +316,64 @@ impl Address {
             Kind::Script(_) => None,
         }
     }
+
+    /// The spending key of a single or group address
+    pub fn spending_key(&self) -> Option<&PublicKey<Ed25519>> {
+        match self.1 {
+            Kind::Single(ref pk) | Kind::Group(ref pk, _) => Some(pk),
+            Kind::Account(_) | Kind::Multisig(_) | Kind::Script(_) => None,
+        }
+    }
+
+    /// The key the funds of the address are staked with: the group key of
+    /// a group address or the key of an account address
+    pub fn stake_key(&self) -> Option<&PublicKey<Ed25519>> {
+        match self.1 {
+            Kind::Group(_, ref pk) | Kind::Account(ref pk) => Some(pk),
+            Kind::Single(_) | Kind::Multisig(_) | Kind::Script(_) => None,
+        }
+    }
+
+    /// Whether the address spends from an account instead of the UTxOs
+    pub fn is_account(&self) -> bool {
+        match self.1 {
+            Kind::Account(_) | Kind::Multisig(_) => true,
+            Kind::Single(_) | Kind::Group(_, _) | Kind::Script(_) => false,
+        }
+    }
+
+    /// Add the stake key to a single address, making it a group address. The
+    /// stake key of a group address is replaced.
+    pub fn with_stake_key(&self, stake_key: PublicKey<Ed25519>) -> Result<Self, Error> {
+        match &self.1 {
+            Kind::Single(spending) | Kind::Group(spending, _) => {
+                Ok(Address(self.0, Kind::Group(spending.clone(), stake_key)))
+            }
+            _ => Err(Error::UnsupportedKind(self.to_kind_type())),
+        }
+    }
+
+    /// Strip the stake key of a group address, making it a single address.
+    /// A single address is left as is.
+    pub fn without_stake_key(&self) -> Result<Self, Error> {
+        match &self.1 {
+            Kind::Single(spending) | Kind::Group(spending, _) => {
+                Ok(Address(self.0, Kind::Single(spending.clone())))
+            }
+            _ => Err(Error::UnsupportedKind(self.to_kind_type())),
+        }
+    }
+
+    /// The account address of the stake key of a group address, with the
+    /// same discrimination
+    pub fn to_stake_account(&self) -> Result<Self, Error> {
+        match &self.1 {
+            Kind::Group(_, stake_key) | Kind::Account(stake_key) => {
+                Ok(Address(self.0, Kind::Account(stake_key.clone())))
+            }
+            _ => Err(Error::UnsupportedKind(self.to_kind_type())),
+        }
+    }
 }
 
 fn get_kind_value(first_byte: u8) -> u8 {
@@ -401,6 +498,43 @@ impl AddressReadable {
         let dat = Vec::from_base32(&data).unwrap();
         Address::from_bytes(&dat[..]).unwrap()
     }
+
+    /// Decode every string of the batch into an address with the expected
+    /// prefix. The results are in the order of the strings, an invalid
+    /// string does not prevent decoding the other ones.
+    pub fn parse_batch<'a, I>(expected_prefix: &str, strings: I) -> Vec<Result<Address, Error>>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        strings
+            .into_iter()
+            .map(|s| Self::from_string(expected_prefix, s).map(|readable| readable.to_address()))
+            .collect()
+    }
+
+    /// Same as [`parse_batch`](Self::parse_batch), accepting any prefix
+    pub fn parse_batch_anyprefix<'a, I>(strings: I) -> Vec<Result<Address, Error>>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        strings
+            .into_iter()
+            .map(|s| Self::from_str_anyprefix(s).map(|readable| readable.to_address()))
+            .collect()
+    }
+}
+
+/// See [`Address::display_bech32`]
+#[derive(Debug, Clone, Copy)]
+pub struct AddressBech32Display<'a> {
+    address: &'a Address,
+    prefix: &'a str,
+}
+
+impl<'a> std::fmt::Display for AddressBech32Display<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.address.write_bech32(self.prefix, f)
+    }
 }
 
 impl std::fmt::Display for AddressReadable {
@@ -416,139 +550,85 @@ impl std::str::FromStr for AddressReadable {
     }
 }
 
-impl PropertySerialize for Address {
-    type Error = std::io::Error;
-
-    fn serialize<W: std::io::Write>(&self, writer: W) -> Result<(), Self::Error> {
-        use chain_core::packer::*;
-        use std::io::Write;
-        let mut codec = Codec::new(writer);
-
+impl Pack for Address {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
         let first_byte = match self.0 {
             Discrimination::Production => self.to_kind_value(),
             Discrimination::Test => self.to_kind_value() | 0b1000_0000,
         };
         codec.put_u8(first_byte)?;
         match &self.1 {
-            Kind::Single(spend) => codec.write_all(spend.as_ref())?,
+            Kind::Single(spend) => codec.put_bytes(spend.as_ref()),
             Kind::Group(spend, group) => {
-                codec.write_all(spend.as_ref())?;
-                codec.write_all(group.as_ref())?;
+                codec.put_bytes(spend.as_ref())?;
+                codec.put_bytes(group.as_ref())
             }
-            Kind::Account(stake_key) => codec.write_all(stake_key.as_ref())?,
-            Kind::Multisig(hash) => codec.write_all(&hash[..])?,
-            Kind::Script(hash) => codec.write_all(&hash[..])?,
-        };
-
-        Ok(())
-    }
-
-    fn serialize_as_vec(&self) -> Result<Vec<u8>, Self::Error> {
-        let mut data = Vec::with_capacity(self.to_size());
-        self.serialize(&mut data)?;
-        Ok(data)
-    }
-}
-impl property::Deserialize for Address {
-    type Error = std::io::Error;
-
-    fn deserialize<R: std::io::BufRead>(reader: R) -> Result<Self, Self::Error> {
-        use chain_core::packer::*;
-        use std::io::Read;
-        let mut codec = Codec::new(reader);
-        // is_valid_data(bytes)?;
-
-        let byte = codec.get_u8()?;
-
-        let discr = get_discrimination_value(byte);
-        let kind = match get_kind_value(byte) {
-            ADDR_KIND_SINGLE => {
-                let mut bytes = [0u8; 32];
-                codec.read_exact(&mut bytes)?;
-                let spending = PublicKey::from_binary(&bytes[..]).map_err(|err| {
-                    std::io::Error::new(std::io::ErrorKind::InvalidData, Box::new(err))
-                })?;
-                Kind::Single(spending)
-            }
-            ADDR_KIND_GROUP => {
-                let mut bytes = [0u8; 32];
-                codec.read_exact(&mut bytes)?;
-                let spending = PublicKey::from_binary(&bytes[..]).map_err(|err| {
-                    std::io::Error::new(std::io::ErrorKind::InvalidData, Box::new(err))
-                })?;
-                let mut bytes = [0u8; 32];
-                codec.read_exact(&mut bytes)?;
-                let group = PublicKey::from_binary(&bytes[..]).map_err(|err| {
-                    std::io::Error::new(std::io::ErrorKind::InvalidData, Box::new(err))
-                })?;
-                Kind::Group(spending, group)
-            }
-            ADDR_KIND_ACCOUNT => {
-                let mut bytes = [0u8; 32];
-                codec.read_exact(&mut bytes)?;
-                let stake_key = PublicKey::from_binary(&bytes[..]).map_err(|err| {
-                    std::io::Error::new(std::io::ErrorKind::InvalidData, Box::new(err))
-                })?;
-                Kind::Account(stake_key)
-            }
-            ADDR_KIND_MULTISIG => {
-                let mut bytes = [0u8; 32];
-                codec.read_exact(&mut bytes)?;
-                Kind::Multisig(bytes)
-            }
-            _ => unreachable!(),
-        };
-        Ok(Address(discr, kind))
+            Kind::Account(stake_key) => codec.put_bytes(stake_key.as_ref()),
+            Kind::Multisig(hash) => codec.put_bytes(&hash[..]),
+            Kind::Script(hash) => codec.put_bytes(&hash[..]),
+        }
     }
 }
 
-fn chain_crypto_err(e: chain_crypto::PublicKeyError) -> ReadError {
+fn chain_crypto_err(e: chain_crypto::PublicKeyError, size: usize) -> ReadError {
     match e {
-        PublicKeyError::SizeInvalid => {
-            ReadError::StructureInvalid("publickey size invalid".to_string())
-        }
+        PublicKeyError::SizeInvalid => ReadError::SizeMismatch {
+            expected: Ed25519::PUBLIC_KEY_SIZE,
+            actual: size,
+        },
         PublicKeyError::StructureInvalid => {
-            ReadError::StructureInvalid("publickey structure invalid".to_string())
+            ReadError::StructureInvalid("publickey structure invalid".into())
         }
     }
 }
 
-impl Readable for Address {
-    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        let byte = buf.get_u8()?;
+impl Unpack for Address {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let byte = source.take_u8()?;
         let discr = get_discrimination_value(byte);
         let kind = match get_kind_value(byte) {
             ADDR_KIND_SINGLE => {
-                let bytes = <[u8; 32]>::read(buf)?;
-                let spending = PublicKey::from_binary(&bytes[..]).map_err(chain_crypto_err)?;
+                let bytes = <[u8; 32]>::unpack(source)?;
+                let spending = PublicKey::from_binary(&bytes[..])
+                    .map_err(|e| chain_crypto_err(e, bytes.len()))?;
                 Kind::Single(spending)
             }
             ADDR_KIND_GROUP => {
-                let bytes = <[u8; 32]>::read(buf)?;
-                let spending = PublicKey::from_binary(&bytes[..]).map_err(chain_crypto_err)?;
-                let bytes = <[u8; 32]>::read(buf)?;
-                let group = PublicKey::from_binary(&bytes[..]).map_err(chain_crypto_err)?;
+                let bytes = <[u8; 32]>::unpack(source)?;
+                let spending = PublicKey::from_binary(&bytes[..])
+                    .map_err(|e| chain_crypto_err(e, bytes.len()))?;
+                let bytes = <[u8; 32]>::unpack(source)?;
+                let group = PublicKey::from_binary(&bytes[..])
+                    .map_err(|e| chain_crypto_err(e, bytes.len()))?;
                 Kind::Group(spending, group)
             }
             ADDR_KIND_ACCOUNT => {
-                let bytes = <[u8; 32]>::read(buf)?;
-                let stake_key = PublicKey::from_binary(&bytes[..]).map_err(chain_crypto_err)?;
+                let bytes = <[u8; 32]>::unpack(source)?;
+                let stake_key = PublicKey::from_binary(&bytes[..])
+                    .map_err(|e| chain_crypto_err(e, bytes.len()))?;
                 Kind::Account(stake_key)
             }
             ADDR_KIND_MULTISIG => {
-                let bytes = <[u8; 32]>::read(buf)?;
+                let bytes = <[u8; 32]>::unpack(source)?;
                 Kind::Multisig(bytes)
             }
             ADDR_KIND_SCRIPT => {
-                let bytes = <[u8; 32]>::read(buf)?;
+                let bytes = <[u8; 32]>::unpack(source)?;
                 Kind::Script(bytes)
             }
-            n => return Err(ReadError::UnknownTag(n as u32)),
+            n => {
+                return Err(ReadError::UnknownTag {
+                    kind: "address kind",
+                    tag: n as u32,
+                })
+            }
         };
         Ok(Address(discr, kind))
     }
 }
 
+chain_ser::impl_legacy_codecs!(Address);
+
 /// error that can happen when parsing the Discrimination
 /// from a string
 #[derive(Debug)]
@@ -607,6 +687,7 @@ mod test {
             AddressReadable::from_address(TEST_PREFIX, addr),
             AddressReadable(expected.to_string())
         );
+        assert_eq!(addr.display_bech32(TEST_PREFIX).to_string(), expected);
     }
 
     fn property_readable(addr: &Address) {
@@ -616,6 +697,7 @@ mod test {
             .expect("address is readable from string");
         assert_eq!(addr, &a);
         assert_eq!(ar, ar2);
+        assert_eq!(addr.display_bech32(TEST_PREFIX).to_string(), ar.as_string());
     }
 
     #[proptest]
@@ -626,6 +708,21 @@ mod test {
         prop_assert_eq!(address, decoded);
     }
 
+    #[proptest]
+    fn stream_and_buffer_decoding_agree(address: Address) {
+        use chain_core::{
+            mempack::{ReadBuf, Readable as _},
+            property::Deserialize as _,
+        };
+
+        let bytes = address.to_bytes();
+        let streamed = Address::deserialize(&bytes[..]).unwrap();
+        let read = Address::read(&mut ReadBuf::from(&bytes)).unwrap();
+
+        prop_assert_eq!(&address, &streamed);
+        prop_assert_eq!(address, read);
+    }
+
     #[proptest]
     fn to_bytes_from_bytes(address: Address) {
         let readable = address.to_bytes();
@@ -634,6 +731,94 @@ mod test {
         prop_assert_eq!(address, decoded);
     }
 
+    #[proptest]
+    fn stake_key_round_trip(
+        discrimination: Discrimination,
+        #[strategy(public_key_strategy::<Ed25519>())] spending: PublicKey<Ed25519>,
+        #[strategy(public_key_strategy::<Ed25519>())] stake: PublicKey<Ed25519>,
+    ) {
+        let single = Address(discrimination, Kind::Single(spending.clone()));
+        let group = single.with_stake_key(stake.clone()).unwrap();
+
+        prop_assert_eq!(group.to_kind_type(), KindType::Group);
+        prop_assert_eq!(group.discrimination(), discrimination);
+        prop_assert_eq!(group.spending_key(), Some(&spending));
+        prop_assert_eq!(group.stake_key(), Some(&stake));
+        prop_assert_eq!(
+            group.to_stake_account().unwrap(),
+            Address(discrimination, Kind::Account(stake))
+        );
+        prop_assert_eq!(group.without_stake_key().unwrap(), single.clone());
+        prop_assert_eq!(single.without_stake_key().unwrap(), single);
+    }
+
+    #[proptest]
+    fn stake_key_is_replaced(
+        #[strategy(public_key_strategy::<Ed25519>())] spending: PublicKey<Ed25519>,
+        #[strategy(public_key_strategy::<Ed25519>())] stake: PublicKey<Ed25519>,
+        #[strategy(public_key_strategy::<Ed25519>())] other_stake: PublicKey<Ed25519>,
+    ) {
+        let group = Address(Discrimination::Test, Kind::Group(spending.clone(), stake));
+        prop_assert_eq!(
+            group.with_stake_key(other_stake.clone()).unwrap(),
+            Address(Discrimination::Test, Kind::Group(spending, other_stake))
+        );
+    }
+
+    #[proptest]
+    fn stake_conversions_of_accounts_and_scripts(
+        address: Address,
+        #[strategy(public_key_strategy::<Ed25519>())] stake: PublicKey<Ed25519>,
+    ) {
+        let kind_type = address.to_kind_type();
+        match kind_type {
+            KindType::Single | KindType::Group => {
+                prop_assert!(address.spending_key().is_some());
+                prop_assert!(!address.is_account());
+            }
+            KindType::Account | KindType::Multisig | KindType::Script => {
+                prop_assert!(address.spending_key().is_none());
+                prop_assert!(matches!(
+                    address.with_stake_key(stake),
+                    Err(Error::UnsupportedKind(k)) if k == kind_type
+                ));
+                prop_assert!(matches!(
+                    address.without_stake_key(),
+                    Err(Error::UnsupportedKind(k)) if k == kind_type
+                ));
+            }
+        }
+    }
+
+    #[proptest]
+    fn parse_batch_keeps_the_order(addresses: Vec<Address>) {
+        let mut strings: Vec<String> = addresses
+            .iter()
+            .map(|address| AddressReadable::from_address(TEST_PREFIX, address).to_string())
+            .collect();
+        strings.insert(strings.len() / 2, "not an address".to_string());
+
+        let parsed = AddressReadable::parse_batch(TEST_PREFIX, strings.iter().map(String::as_str));
+        prop_assert_eq!(parsed.len(), addresses.len() + 1);
+        let (valid, invalid): (Vec<_>, Vec<_>) = parsed.into_iter().partition(Result::is_ok);
+        prop_assert_eq!(invalid.len(), 1);
+        prop_assert_eq!(
+            valid.into_iter().map(Result::unwrap).collect::<Vec<_>>(),
+            addresses
+        );
+
+        let other_prefix =
+            AddressReadable::parse_batch("other", strings.iter().map(String::as_str));
+        prop_assert!(other_prefix.iter().all(Result::is_err));
+        prop_assert_eq!(
+            AddressReadable::parse_batch_anyprefix(strings.iter().map(String::as_str))
+                .iter()
+                .filter(|result| result.is_ok())
+                .count(),
+            addresses.len()
+        );
+    }
+
     #[test]
     fn unit_tests() {
         let fake_spendingkey: PublicKey<Ed25519> = PublicKey::from_binary(&[
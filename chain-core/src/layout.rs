@@ -0,0 +1,551 @@
+//! Machine readable description of the wire formats.
+//!
+//! A type implementing [`DescribeLayout`] describes the bytes written by its
+//! [`Serialize`] implementation: the fields in order with their size, and
+//! the tags of its variants. The description is not trusted: [`check_layout`]
+//! walks the bytes actually written for a value through the description, so
+//! a test serializing sample values fails as soon as the code and the
+//! description drift apart.
+//!
+//! [`Specification`] renders the descriptions of a set of types, and of the
+//! types they refer to, as a markdown wire format specification for the
+//! external implementers.
+
+use crate::mempack::{ReadBuf, ReadError};
+use crate::property::Serialize;
+use std::fmt::{self, Write};
+
+/// A big endian unsigned integer, used for the tags, sizes and counts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Int {
+    U8,
+    U16,
+    U32,
+    U64,
+}
+
+impl Int {
+    pub fn size(self) -> usize {
+        match self {
+            Int::U8 => 1,
+            Int::U16 => 2,
+            Int::U32 => 4,
+            Int::U64 => 8,
+        }
+    }
+
+    fn read(self, buf: &mut ReadBuf) -> Result<u64, ReadError> {
+        match self {
+            Int::U8 => buf.get_u8().map(u64::from),
+            Int::U16 => buf.get_u16().map(u64::from),
+            Int::U32 => buf.get_u32().map(u64::from),
+            Int::U64 => buf.get_u64(),
+        }
+    }
+}
+
+impl fmt::Display for Int {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Int::U8 => f.write_str("u8"),
+            Int::U16 => f.write_str("u16"),
+            Int::U32 => f.write_str("u32"),
+            Int::U64 => f.write_str("u64"),
+        }
+    }
+}
+
+/// A reference to a type describing its layout, resolved lazily so the
+/// layouts can refer to each other
+#[derive(Clone, Copy)]
+pub struct TypeRef {
+    pub name: &'static str,
+    layout: fn() -> Layout,
+}
+
+impl TypeRef {
+    pub fn of<T: DescribeLayout>() -> Self {
+        TypeRef {
+            name: T::NAME,
+            layout: T::layout,
+        }
+    }
+
+    pub fn layout(&self) -> Layout {
+        (self.layout)()
+    }
+}
+
+impl fmt::Debug for TypeRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name)
+    }
+}
+
+impl PartialEq for TypeRef {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Eq for TypeRef {}
+
+/// The size of a field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Size {
+    /// a fixed number of bytes
+    Fixed(usize),
+    /// bytes prefixed with their length, laid out as `content` if any
+    Prefixed {
+        length: Int,
+        content: Option<TypeRef>,
+    },
+    /// items laid out as `item`, prefixed with their number
+    Repeated { count: Int, item: TypeRef },
+    /// laid out as another type
+    Type(TypeRef),
+    /// all the bytes left in the enclosing data
+    Remaining,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+    pub name: &'static str,
+    pub size: Size,
+    pub description: &'static str,
+}
+
+impl Field {
+    pub fn fixed(name: &'static str, size: usize) -> Self {
+        Self::new(name, Size::Fixed(size))
+    }
+
+    pub fn int(name: &'static str, int: Int) -> Self {
+        Self::new(name, Size::Fixed(int.size()))
+    }
+
+    pub fn prefixed(name: &'static str, length: Int) -> Self {
+        Self::new(
+            name,
+            Size::Prefixed {
+                length,
+                content: None,
+            },
+        )
+    }
+
+    pub fn prefixed_of<T: DescribeLayout>(name: &'static str, length: Int) -> Self {
+        Self::new(
+            name,
+            Size::Prefixed {
+                length,
+                content: Some(TypeRef::of::<T>()),
+            },
+        )
+    }
+
+    pub fn repeated<T: DescribeLayout>(name: &'static str, count: Int) -> Self {
+        Self::new(
+            name,
+            Size::Repeated {
+                count,
+                item: TypeRef::of::<T>(),
+            },
+        )
+    }
+
+    pub fn of<T: DescribeLayout>(name: &'static str) -> Self {
+        Self::new(name, Size::Type(TypeRef::of::<T>()))
+    }
+
+    pub fn remaining(name: &'static str) -> Self {
+        Self::new(name, Size::Remaining)
+    }
+
+    fn new(name: &'static str, size: Size) -> Self {
+        Field {
+            name,
+            size,
+            description: "",
+        }
+    }
+
+    pub fn describe(self, description: &'static str) -> Self {
+        Field {
+            description,
+            ..self
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Variant {
+    pub tag: u64,
+    pub name: &'static str,
+    pub fields: Vec<Field>,
+}
+
+impl Variant {
+    pub fn new(tag: u64, name: &'static str, fields: Vec<Field>) -> Self {
+        Variant { tag, name, fields }
+    }
+}
+
+/// The layout of the serialization of a type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Layout {
+    /// the fields, one after the other
+    Fields(Vec<Field>),
+    /// a tag selecting the variant laying out the rest of the bytes
+    Tagged { tag: Int, variants: Vec<Variant> },
+}
+
+/// A type whose serialization is described, see the module documentation
+pub trait DescribeLayout {
+    /// the name of the type in the specification
+    const NAME: &'static str;
+
+    fn layout() -> Layout;
+}
+
+/// The bytes of a value do not follow the layout of its type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayoutMismatch {
+    /// the types and fields leading to the mismatch, outermost first
+    pub path: Vec<&'static str>,
+    /// the offset of the mismatch in the bytes, relative to the content of
+    /// the innermost length prefixed field if any
+    pub offset: usize,
+    pub error: ReadError,
+}
+
+impl fmt::Display for LayoutMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "layout mismatch in {} at offset {}: {}",
+            self.path.join("."),
+            self.offset,
+            self.error
+        )
+    }
+}
+
+impl std::error::Error for LayoutMismatch {}
+
+/// Check the bytes written by the serialization of the value follow the
+/// layout of its type, to the last byte
+pub fn check_layout<T>(value: &T) -> Result<(), LayoutMismatch>
+where
+    T: DescribeLayout + Serialize,
+{
+    let bytes = value
+        .serialize_as_vec()
+        .expect("memory serialize is expected to just work");
+    check_bytes::<T>(&bytes)
+}
+
+/// Check the bytes follow the layout of the type, to the last byte
+pub fn check_bytes<T: DescribeLayout>(bytes: &[u8]) -> Result<(), LayoutMismatch> {
+    let mut buf = ReadBuf::from(bytes);
+    let mut path = Vec::new();
+    walk_type(&TypeRef::of::<T>(), &mut buf, &mut path)?;
+    buf.expect_end().map_err(|error| LayoutMismatch {
+        path: vec![T::NAME],
+        offset: buf.position(),
+        error,
+    })
+}
+
+fn walk_type(
+    type_ref: &TypeRef,
+    buf: &mut ReadBuf,
+    path: &mut Vec<&'static str>,
+) -> Result<(), LayoutMismatch> {
+    path.push(type_ref.name);
+    match type_ref.layout() {
+        Layout::Fields(fields) => walk_fields(&fields, buf, path)?,
+        Layout::Tagged { tag, variants } => {
+            let offset = buf.position();
+            let value = tag.read(buf).map_err(|error| LayoutMismatch {
+                path: path.clone(),
+                offset,
+                error,
+            })?;
+            let variant = variants
+                .iter()
+                .find(|variant| variant.tag == value)
+                .ok_or_else(|| LayoutMismatch {
+                    path: path.clone(),
+                    offset,
+                    error: ReadError::UnknownTag {
+                        kind: type_ref.name,
+                        tag: value as u32,
+                    },
+                })?;
+            path.push(variant.name);
+            walk_fields(&variant.fields, buf, path)?;
+            path.pop();
+        }
+    }
+    path.pop();
+    Ok(())
+}
+
+fn walk_fields(
+    fields: &[Field],
+    buf: &mut ReadBuf,
+    path: &mut Vec<&'static str>,
+) -> Result<(), LayoutMismatch> {
+    for field in fields {
+        path.push(field.name);
+        let offset = buf.position();
+        let mismatch = |path: &Vec<&'static str>, error| LayoutMismatch {
+            path: path.clone(),
+            offset,
+            error,
+        };
+        match &field.size {
+            Size::Fixed(size) => buf.skip_bytes(*size).map_err(|e| mismatch(path, e))?,
+            Size::Prefixed { length, content } => {
+                let length = length.read(buf).map_err(|e| mismatch(path, e))? as usize;
+                let mut content_buf = buf.split_to(length).map_err(|e| mismatch(path, e))?;
+                if let Some(content) = content {
+                    walk_type(content, &mut content_buf, path)?;
+                    content_buf.expect_end().map_err(|e| mismatch(path, e))?;
+                }
+            }
+            Size::Repeated { count, item } => {
+                let count = count.read(buf).map_err(|e| mismatch(path, e))?;
+                for _ in 0..count {
+                    walk_type(item, buf, path)?;
+                }
+            }
+            Size::Type(type_ref) => walk_type(type_ref, buf, path)?,
+            Size::Remaining => {
+                buf.get_slice_end();
+            }
+        }
+        path.pop();
+    }
+    Ok(())
+}
+
+/// The wire format specification of a set of types, see the module
+/// documentation
+#[derive(Debug, Clone)]
+pub struct Specification {
+    title: &'static str,
+    types: Vec<TypeRef>,
+}
+
+impl Specification {
+    pub fn new(title: &'static str) -> Self {
+        Specification {
+            title,
+            types: Vec::new(),
+        }
+    }
+
+    /// Specify the type, and the types it refers to
+    pub fn with<T: DescribeLayout>(mut self) -> Self {
+        self.add(TypeRef::of::<T>());
+        self
+    }
+
+    fn add(&mut self, type_ref: TypeRef) {
+        if self.types.contains(&type_ref) {
+            return;
+        }
+        self.types.push(type_ref);
+        let layout = type_ref.layout();
+        let fields: Vec<&Field> = match &layout {
+            Layout::Fields(fields) => fields.iter().collect(),
+            Layout::Tagged { variants, .. } => variants
+                .iter()
+                .flat_map(|variant| variant.fields.iter())
+                .collect(),
+        };
+        for field in fields {
+            match &field.size {
+                Size::Prefixed {
+                    content: Some(type_ref),
+                    ..
+                }
+                | Size::Repeated { item: type_ref, .. }
+                | Size::Type(type_ref) => self.add(*type_ref),
+                Size::Fixed(_) | Size::Prefixed { content: None, .. } | Size::Remaining => {}
+            }
+        }
+    }
+
+    /// The names of the specified types, in the order of the specification
+    pub fn type_names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.types.iter().map(|type_ref| type_ref.name)
+    }
+
+    /// Render the specification as markdown, the integers being big endian
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "# {}\n", self.title).unwrap();
+        out.push_str("All the integers are big endian.\n");
+        for type_ref in &self.types {
+            writeln!(out, "\n## {}\n", type_ref.name).unwrap();
+            match type_ref.layout() {
+                Layout::Fields(fields) => render_fields(&mut out, 0, &fields),
+                Layout::Tagged { tag, variants } => {
+                    writeln!(out, "A {} tag followed by the fields of its variant.", tag).unwrap();
+                    for variant in variants {
+                        writeln!(out, "\n### {} (tag {})\n", variant.name, variant.tag).unwrap();
+                        render_fields(&mut out, tag.size(), &variant.fields);
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+fn render_fields(out: &mut String, start: usize, fields: &[Field]) {
+    out.push_str("| offset | field | size | description |\n");
+    out.push_str("|--------|-------|------|-------------|\n");
+    let mut offset = Some(start);
+    for field in fields {
+        let offset_text = offset.map_or_else(|| "-".to_owned(), |offset| offset.to_string());
+        writeln!(
+            out,
+            "| {} | {} | {} | {} |",
+            offset_text,
+            field.name,
+            SizeText(&field.size),
+            field.description
+        )
+        .unwrap();
+        offset = match field.size {
+            Size::Fixed(size) => offset.map(|offset| offset + size),
+            _ => None,
+        };
+    }
+}
+
+struct SizeText<'a>(&'a Size);
+
+impl fmt::Display for SizeText<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Size::Fixed(size) => write!(f, "{}", size),
+            Size::Prefixed {
+                length,
+                content: None,
+            } => write!(f, "{} length, then the bytes", length),
+            Size::Prefixed {
+                length,
+                content: Some(content),
+            } => write!(f, "{} length, then {}", length, TypeLink(content)),
+            Size::Repeated { count, item } => {
+                write!(f, "{} count, then each {}", count, TypeLink(item))
+            }
+            Size::Type(type_ref) => write!(f, "{}", TypeLink(type_ref)),
+            Size::Remaining => f.write_str("the rest"),
+        }
+    }
+}
+
+struct TypeLink<'a>(&'a TypeRef);
+
+impl fmt::Display for TypeLink<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}](#", self.0.name)?;
+        for c in self.0.name.chars() {
+            if c.is_alphanumeric() {
+                f.write_char(c.to_ascii_lowercase())?;
+            } else if c == ' ' || c == '-' {
+                f.write_char('-')?;
+            }
+        }
+        f.write_char(')')
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Entry;
+
+    impl DescribeLayout for Entry {
+        const NAME: &'static str = "Entry";
+
+        fn layout() -> Layout {
+            Layout::Tagged {
+                tag: Int::U8,
+                variants: vec![
+                    Variant::new(1, "Value", vec![Field::int("value", Int::U64)]),
+                    Variant::new(2, "Name", vec![Field::prefixed("name", Int::U8)]),
+                ],
+            }
+        }
+    }
+
+    struct Entries;
+
+    impl DescribeLayout for Entries {
+        const NAME: &'static str = "Entries";
+
+        fn layout() -> Layout {
+            Layout::Fields(vec![
+                Field::fixed("magic", 2).describe("always `ce`"),
+                Field::repeated::<Entry>("entries", Int::U16),
+                Field::remaining("padding"),
+            ])
+        }
+    }
+
+    #[test]
+    fn bytes_are_walked_through_the_layout() {
+        let mut bytes = vec![0xc, 0xe, 0, 2];
+        bytes.extend([1, 0, 0, 0, 0, 0, 0, 0, 42]);
+        bytes.extend([2, 3, b'a', b'b', b'c']);
+        assert_eq!(check_bytes::<Entries>(&bytes), Ok(()));
+        bytes.push(0);
+        assert_eq!(check_bytes::<Entries>(&bytes), Ok(()));
+
+        assert_eq!(
+            check_bytes::<Entries>(&bytes[..12]),
+            Err(LayoutMismatch {
+                path: vec!["Entries", "entries", "Entry", "Value", "value"],
+                offset: 5,
+                error: ReadError::NotEnoughBytes {
+                    offset: 5,
+                    left: 7,
+                    demanded: 8
+                },
+            })
+        );
+        bytes[13] = 3;
+        assert_eq!(
+            check_bytes::<Entries>(&bytes),
+            Err(LayoutMismatch {
+                path: vec!["Entries", "entries", "Entry"],
+                offset: 13,
+                error: ReadError::UnknownTag {
+                    kind: "Entry",
+                    tag: 3
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn specification_includes_the_referred_types() {
+        let specification = Specification::new("Entries format").with::<Entries>();
+        assert_eq!(
+            specification.type_names().collect::<Vec<_>>(),
+            vec!["Entries", "Entry"]
+        );
+        let rendered = specification.render();
+        assert!(rendered.contains("| 2 | entries | u16 count, then each [Entry](#entry) |  |"));
+        assert!(rendered.contains("### Name (tag 2)"));
+        assert!(rendered.contains("| 1 | name | u8 length, then the bytes |  |"));
+    }
+}
@@ -1,4 +1,6 @@
 pub use chain_ser::abor;
 pub use chain_ser::mempack;
 pub use chain_ser::packer;
+pub use chain_ser::shared;
+pub mod layout;
 pub mod property;
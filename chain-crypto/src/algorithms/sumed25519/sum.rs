@@ -1,6 +1,7 @@
 use super::common::{self, Depth, Seed};
 use ed25519_dalek as ed25519;
 use ed25519_dalek::{Signer as _, Verifier as _};
+use subtle::{Choice, ConstantTimeEq};
 
 #[derive(Debug)]
 pub enum Error {
@@ -419,6 +420,12 @@ impl AsRef<[u8]> for PublicKey {
     }
 }
 
+impl ConstantTimeEq for PublicKey {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
 /// Signature using the repetitive MMM sum composition
 ///
 /// Serialization:
@@ -740,10 +747,7 @@ pub fn verify(pk: &PublicKey, m: &[u8], sig: &Signature) -> bool {
             got = hash(&got, &pk_combi);
         }
     }
-    if &got != pk {
-        return false;
-    }
-    true
+    got.ct_eq(pk).into()
 }
 
 pub fn update(secret: &mut SecretKey) -> Result<(), Error> {
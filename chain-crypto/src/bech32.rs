@@ -1,4 +1,4 @@
-use bech32::{self, Error as Bech32Error, FromBase32, ToBase32};
+use bech32::{self, Bech32Writer, Error as Bech32Error, FromBase32, ToBase32};
 use std::error::Error as StdError;
 use std::fmt;
 use std::result::Result as StdResult;
@@ -37,6 +37,36 @@ pub fn to_bech32_from_bytes<B: Bech32>(bytes: &[u8]) -> String {
         .unwrap_or_else(|e| panic!("Failed to build bech32: {}", e))
 }
 
+/// Write the Bech32 representation of `bytes` with the prefix `hrp`, without
+/// allocating. Unlike the functions building a string, the prefix is not
+/// checked: it is expected to be a valid lowercase prefix.
+pub fn write_bech32<W: fmt::Write>(hrp: &str, bytes: &[u8], writer: &mut W) -> fmt::Result {
+    // Use the original Bech32 format from BIP-0173, like `to_bech32_from_bytes`
+    let mut bech32_writer = Bech32Writer::new(hrp, bech32::Variant::Bech32, writer)?;
+    bytes.write_base32(&mut bech32_writer)?;
+    bech32_writer.finalize()
+}
+
+/// [`Display`](fmt::Display) adapter writing the Bech32 representation of
+/// the bytes with [`write_bech32`]
+#[derive(Debug, Clone, Copy)]
+pub struct Bech32Display<'a> {
+    hrp: &'a str,
+    bytes: &'a [u8],
+}
+
+impl<'a> Bech32Display<'a> {
+    pub fn new(hrp: &'a str, bytes: &'a [u8]) -> Self {
+        Bech32Display { hrp, bytes }
+    }
+}
+
+impl<'a> fmt::Display for Bech32Display<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_bech32(self.hrp, self.bytes, f)
+    }
+}
+
 pub fn try_from_bech32_to_bytes<B: Bech32>(bech32_str: &str) -> Result<Vec<u8>> {
     let (hrp, data, _variant) = bech32::decode(bech32_str)?;
     if hrp != B::BECH32_HRP {
@@ -8,6 +8,7 @@ use std::{error, fmt, result};
 use cryptoxide::blake2b::Blake2b;
 use cryptoxide::digest::Digest as _;
 use hex::FromHexError;
+use subtle::{Choice, ConstantTimeEq};
 
 use typed_bytes::ByteSlice;
 
@@ -197,6 +198,12 @@ impl<H: DigestAlg> PartialEq for Digest<H> {
 
 impl<H: DigestAlg> Eq for Digest<H> {}
 
+impl<H: DigestAlg> ConstantTimeEq for Digest<H> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.as_ref().ct_eq(other.0.as_ref())
+    }
+}
+
 impl<H: DigestAlg> PartialOrd for Digest<H> {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         Some(self.cmp(other))
@@ -303,6 +310,12 @@ impl<H: DigestAlg, T> PartialEq for DigestOf<H, T> {
 
 impl<H: DigestAlg, T> Eq for DigestOf<H, T> {}
 
+impl<H: DigestAlg, T> ConstantTimeEq for DigestOf<H, T> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.inner.ct_eq(&other.inner)
+    }
+}
+
 impl<H: DigestAlg, T> PartialOrd for DigestOf<H, T> {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         self.inner.partial_cmp(&other.inner)
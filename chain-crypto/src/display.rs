@@ -0,0 +1,63 @@
+//! Formatting of binary values without intermediate allocation.
+//!
+//! Formatting an identifier with `hex::encode` builds a `String` before
+//! writing it, which shows up in the paths formatting many identifiers
+//! (logging, indexing). The functions of this module write directly into
+//! the destination instead.
+
+use std::fmt;
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+/// bytes encoded per write into the destination
+const HEX_CHUNK: usize = 32;
+
+/// Write the bytes in lowercase hexadecimal
+pub fn write_hex<W: fmt::Write + ?Sized>(bytes: &[u8], writer: &mut W) -> fmt::Result {
+    let mut buf = [0u8; HEX_CHUNK * 2];
+    for chunk in bytes.chunks(HEX_CHUNK) {
+        for (byte, digits) in chunk.iter().zip(buf.chunks_exact_mut(2)) {
+            digits[0] = HEX_DIGITS[(byte >> 4) as usize];
+            digits[1] = HEX_DIGITS[(byte & 0x0f) as usize];
+        }
+        let encoded = std::str::from_utf8(&buf[..chunk.len() * 2]).map_err(|_| fmt::Error)?;
+        writer.write_str(encoded)?;
+    }
+    Ok(())
+}
+
+/// [`Display`](fmt::Display) adapter writing the bytes in lowercase
+/// hexadecimal with [`write_hex`]
+#[derive(Debug, Clone, Copy)]
+pub struct Hex<'a>(pub &'a [u8]);
+
+impl<'a> fmt::Display for Hex<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_hex(self.0, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bech32::{to_bech32_from_bytes, Bech32, Bech32Display};
+    use crate::hash::Blake2b256;
+
+    #[test]
+    fn hex_is_the_one_of_the_hex_crate() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        for len in [0, 1, HEX_CHUNK - 1, HEX_CHUNK, HEX_CHUNK + 1, bytes.len()] {
+            assert_eq!(Hex(&bytes[..len]).to_string(), hex::encode(&bytes[..len]));
+        }
+    }
+
+    #[test]
+    fn bech32_is_the_one_of_the_string_encoding() {
+        let hash = Blake2b256::new(b"some bytes");
+        assert_eq!(
+            Bech32Display::new(Blake2b256::BECH32_HRP, hash.as_ref()).to_string(),
+            to_bech32_from_bytes::<Blake2b256>(hash.as_ref())
+        );
+        assert_eq!(hash.to_string(), hex::encode(hash.as_ref()));
+    }
+}
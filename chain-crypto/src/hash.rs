@@ -9,6 +9,7 @@ use cryptoxide::digest::Digest as _;
 use hex::FromHexError;
 
 use crate::bech32::{self, Bech32};
+use crate::display::write_hex;
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Error {
@@ -76,6 +77,18 @@ macro_rules! define_hash_object {
                 buf[0..Self::HASH_SIZE].clone_from_slice(slice);
                 Ok(Self::from(buf))
             }
+
+            /// Write the hash in lowercase hexadecimal, like the `Display`
+            /// implementation, without allocating
+            pub fn write_hex<W: fmt::Write + ?Sized>(&self, writer: &mut W) -> fmt::Result {
+                write_hex(self.as_ref(), writer)
+            }
+
+            /// Write the Bech32 representation of the hash without
+            /// allocating
+            pub fn write_bech32<W: fmt::Write>(&self, writer: &mut W) -> fmt::Result {
+                bech32::write_bech32(Self::BECH32_HRP, self.as_ref(), writer)
+            }
         }
         impl AsRef<[u8]> for $hash_ty {
             fn as_ref(&self) -> &[u8] {
@@ -106,13 +119,13 @@ macro_rules! define_hash_object {
         }
         impl fmt::Display for $hash_ty {
             fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-                write!(f, "{}", hex::encode(self.as_ref()))
+                self.write_hex(f)
             }
         }
         impl fmt::Debug for $hash_ty {
             fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
                 f.write_str(concat!(stringify!($hash_ty), "(0x"))?;
-                write!(f, "{}", hex::encode(self.as_ref()))?;
+                self.write_hex(f)?;
                 f.write_str(")")
             }
         }
@@ -4,6 +4,7 @@ use rand_core::{CryptoRng, RngCore};
 use std::fmt;
 use std::hash::Hash;
 use std::str::FromStr;
+use subtle::{Choice, ConstantTimeEq};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum SecretKeyError {
@@ -203,9 +204,23 @@ impl<A: AsymmetricKey> Clone for KeyPair<A> {
     }
 }
 
+impl<A: AsymmetricPublicKey> ConstantTimeEq for PublicKey<A> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.as_ref().ct_eq(other.0.as_ref())
+    }
+}
+
+impl<A: AsymmetricKey> ConstantTimeEq for SecretKey<A> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.as_ref().ct_eq(other.0.as_ref())
+    }
+}
+
+/// public keys are often the identifiers of secret material (accounts,
+/// committee members), they are compared in constant time
 impl<A: AsymmetricPublicKey> std::cmp::PartialEq<Self> for PublicKey<A> {
     fn eq(&self, other: &Self) -> bool {
-        self.0.as_ref().eq(other.0.as_ref())
+        self.ct_eq(other).into()
     }
 }
 
@@ -266,7 +281,9 @@ mod test {
 
     use crate::testing::TestCryptoGen;
 
+    use crate::Ed25519;
     use proptest::prelude::*;
+    use test_strategy::proptest;
 
     // ONLY ALLOWED WHEN TESTING
     impl<A> std::fmt::Debug for SecretKey<A>
@@ -288,4 +305,20 @@ mod test {
                 .boxed()
         }
     }
+
+    #[proptest]
+    fn ct_eq_matches_eq(a: KeyPair<Ed25519>, b: KeyPair<Ed25519>) {
+        let (a_sk, a_pk) = a.into_keys();
+        let (b_sk, b_pk) = b.into_keys();
+        prop_assert!(bool::from(a_pk.ct_eq(&a_pk.clone())));
+        prop_assert!(bool::from(a_sk.ct_eq(&a_sk.clone())));
+        prop_assert_eq!(
+            bool::from(a_pk.ct_eq(&b_pk)),
+            a_pk.as_ref() == b_pk.as_ref()
+        );
+        prop_assert_eq!(
+            bool::from(a_sk.ct_eq(&b_sk)),
+            a_sk.0.as_ref() == b_sk.0.as_ref()
+        );
+    }
 }
@@ -5,6 +5,7 @@ pub mod algorithms;
 pub mod asymlock;
 pub mod bech32;
 pub mod digest;
+pub mod display;
 mod evolving;
 #[macro_use]
 pub mod ec;
@@ -34,3 +35,4 @@ pub use vrf::{
 
 pub use algorithms::*;
 pub use hash::Blake2b256;
+pub use subtle::{Choice, ConstantTimeEq};
@@ -0,0 +1,73 @@
+use chain_addr::{AddressReadable, Discrimination};
+use chain_impl_mockchain::{
+    fragment::FragmentId,
+    testing::{data::AddressData, TestGen},
+};
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::fmt::Write;
+
+const IDS: usize = 1_000;
+const ADDRESS_PREFIX: &str = "ca";
+
+fn format_ids(c: &mut Criterion) {
+    let ids: Vec<FragmentId> = (0..IDS).map(|_| TestGen::hash()).collect();
+    let mut group = c.benchmark_group("format_ids");
+
+    group.bench_function("to_string", |b| {
+        b.iter(|| {
+            for id in ids.iter() {
+                criterion::black_box(id.to_string());
+            }
+        })
+    });
+    group.bench_function("write_hex", |b| {
+        let mut out = String::with_capacity(64);
+        b.iter(|| {
+            for id in ids.iter() {
+                out.clear();
+                id.write_hex(&mut out).unwrap();
+                criterion::black_box(&out);
+            }
+        })
+    });
+    group.finish();
+}
+
+fn format_addresses(c: &mut Criterion) {
+    let addresses: Vec<_> = (0..IDS)
+        .map(|_| AddressData::account(Discrimination::Test).address)
+        .collect();
+    let mut group = c.benchmark_group("format_addresses");
+
+    group.bench_function("address_readable", |b| {
+        b.iter(|| {
+            for address in addresses.iter() {
+                criterion::black_box(AddressReadable::from_address(ADDRESS_PREFIX, address));
+            }
+        })
+    });
+    group.bench_function("write_bech32", |b| {
+        let mut out = String::with_capacity(128);
+        b.iter(|| {
+            for address in addresses.iter() {
+                out.clear();
+                address.write_bech32(ADDRESS_PREFIX, &mut out).unwrap();
+                criterion::black_box(&out);
+            }
+        })
+    });
+    group.bench_function("display_bech32", |b| {
+        let mut out = String::with_capacity(128);
+        b.iter(|| {
+            for address in addresses.iter() {
+                out.clear();
+                write!(out, "{}", address.display_bech32(ADDRESS_PREFIX)).unwrap();
+                criterion::black_box(&out);
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(formatting, format_ids, format_addresses);
+criterion_main!(formatting);
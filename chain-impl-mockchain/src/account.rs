@@ -2,12 +2,15 @@ use crate::accounting::account;
 use crate::key::{deserialize_public_key, serialize_public_key};
 use crate::transaction::WitnessAccountData;
 use chain_core::{
-    mempack::{ReadBuf, ReadError, Readable},
-    property,
+    mempack::ReadError,
+    packer::Codec,
+    property::{ByteSource, Pack, Unpack},
 };
-use chain_crypto::{Ed25519, PublicKey, Signature};
+use chain_crypto::{AsymmetricPublicKey, Choice, ConstantTimeEq, Ed25519, PublicKey, Signature};
 
-pub use account::{DelegationRatio, DelegationType, LedgerError, SpendingCounter};
+pub use account::{
+    DelegationAmounts, DelegationRatio, DelegationType, LedgerError, SpendingCounter, SpendingCycle,
+};
 
 pub type AccountAlg = Ed25519;
 
@@ -29,25 +32,34 @@ impl From<Identifier> for PublicKey<AccountAlg> {
     }
 }
 
+impl ConstantTimeEq for Identifier {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
 impl AsRef<PublicKey<AccountAlg>> for Identifier {
     fn as_ref(&self) -> &PublicKey<AccountAlg> {
         &self.0
     }
 }
 
-impl property::Serialize for Identifier {
-    type Error = std::io::Error;
-    fn serialize<W: std::io::Write>(&self, writer: W) -> Result<(), Self::Error> {
-        serialize_public_key(&self.0, writer)
+impl Pack for Identifier {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        serialize_public_key(&self.0, codec)
     }
 }
 
-impl Readable for Identifier {
-    fn read(reader: &mut ReadBuf) -> Result<Self, ReadError> {
-        deserialize_public_key(reader).map(Identifier)
+impl Unpack for Identifier {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        source
+            .with_slice(AccountAlg::PUBLIC_KEY_SIZE, deserialize_public_key)
+            .map(Identifier)
     }
 }
 
+chain_ser::impl_legacy_codecs!(Identifier);
+
 /// The public ledger of all accounts associated with their current state
 pub type Ledger = account::Ledger<Identifier, ()>;
 
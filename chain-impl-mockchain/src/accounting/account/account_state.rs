@@ -12,6 +12,10 @@ use super::{LastRewards, LedgerError};
 /// * No delegation
 /// * Full delegation of this account to a specific pool
 /// * Ratio of stake to multiple pools
+///
+/// The delegation of explicit amounts of stake is not one of them, it is only
+/// declared with an `AmountStakeDelegation` certificate, see
+/// [`AccountState::delegation_amounts`].
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub enum DelegationType {
     NonDelegated,
@@ -77,13 +81,72 @@ impl DelegationRatio {
     }
 }
 
+/// Delegation Amounts type express an explicit value of stake
+/// associated with each pool
+///
+/// E.g. pools: [(A,100), (B,50)] with a balance of 200 at the time of the
+/// stake snapshot means that A is associated with 100 of the stake, B with 50
+/// and the remaining 50 are not delegated. If the balance does not cover the
+/// total of the amounts at the time of the snapshot, none of the stake is
+/// delegated.
+///
+/// It's invalid to have no element in the array, a zero amount, or the same
+/// pool twice.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DelegationAmounts {
+    pub(crate) pools: Box<[(PoolId, Value)]>,
+}
+
+/// The maximum number of pools
+pub const DELEGATION_AMOUNTS_MAX_DECLS: usize = 8;
+
+impl DelegationAmounts {
+    pub fn new(pools: Vec<(PoolId, Value)>) -> Option<DelegationAmounts> {
+        let has_no_zero = !pools.iter().any(|x| x.1 == Value::zero());
+        let has_no_duplicate = pools
+            .iter()
+            .enumerate()
+            .all(|(i, x)| !pools[..i].iter().any(|y| y.0 == x.0));
+        if has_no_zero
+            && has_no_duplicate
+            && !pools.is_empty()
+            && pools.len() <= DELEGATION_AMOUNTS_MAX_DECLS
+            && Value::sum(pools.iter().map(|x| x.1)).is_ok()
+        {
+            Some(Self {
+                pools: pools.into(),
+            })
+        } else {
+            None
+        }
+    }
+
+    pub fn pools(&self) -> &[(PoolId, Value)] {
+        &self.pools
+    }
+
+    /// The value needed to cover all the amounts
+    pub fn total(&self) -> Value {
+        Value::sum(self.pools.iter().map(|x| x.1))
+            .expect("the total of the amounts is checked at creation")
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct AccountState<Extra> {
     pub spending: SpendingCounterIncreasing,
     pub delegation: DelegationType,
+    /// `Some` when the account delegates explicit amounts of its stake, its
+    /// `delegation` is then `NonDelegated`. Whatever its form, a new
+    /// delegation of the account replaces the previous one.
+    pub delegation_amounts: Option<DelegationAmounts>,
     pub value: Value,
     pub tokens: Hamt<DefaultHasher, TokenIdentifier, Value>,
     pub last_rewards: LastRewards,
+    /// `Some` once the redelegation of the rewards is enabled with a
+    /// `RewardsRedelegation` certificate, with the rewards received since
+    /// the redelegation was enabled or the delegation last changed
+    pub redelegated_rewards: Option<Value>,
     pub extra: Extra,
 }
 
@@ -93,9 +156,11 @@ impl<Extra> AccountState<Extra> {
         Self {
             spending: SpendingCounterIncreasing::default(),
             delegation: DelegationType::NonDelegated,
+            delegation_amounts: None,
             value: v,
             tokens: Hamt::new(),
             last_rewards: LastRewards::default(),
+            redelegated_rewards: None,
             extra: e,
         }
     }
@@ -111,6 +176,12 @@ impl<Extra> AccountState<Extra> {
         &self.delegation
     }
 
+    /// The explicit amounts of stake delegated by the account, if it
+    /// delegates amounts instead of its whole stake
+    pub fn delegation_amounts(&self) -> Option<&DelegationAmounts> {
+        self.delegation_amounts.as_ref()
+    }
+
     pub fn value(&self) -> Value {
         self.value
     }
@@ -141,6 +212,9 @@ impl<Extra: Clone> AccountState<Extra> {
         let mut st = self.clone();
         st.value = new_value;
         st.last_rewards.add_for(e, v);
+        if let Some(redelegated) = st.redelegated_rewards {
+            st.redelegated_rewards = Some((redelegated + v)?);
+        }
         Ok(st)
     }
 
@@ -169,10 +243,35 @@ impl<Extra: Clone> AccountState<Extra> {
         })
     }
 
-    /// Set delegation
+    /// Set delegation, the rewards redelegated so far are expected to be
+    /// covered by the new delegation
     pub fn set_delegation(&self, delegation: DelegationType) -> Self {
         let mut st = self.clone();
         st.delegation = delegation;
+        st.delegation_amounts = None;
+        if st.redelegated_rewards.is_some() {
+            st.redelegated_rewards = Some(Value::zero());
+        }
+        st
+    }
+
+    /// Set the delegation of explicit amounts, replacing the delegation of
+    /// the account like `set_delegation`
+    pub fn set_delegation_amounts(&self, amounts: DelegationAmounts) -> Self {
+        let mut st = self.set_delegation(DelegationType::NonDelegated);
+        st.delegation_amounts = Some(amounts);
+        st
+    }
+
+    /// Enable or disable the redelegation of the rewards, enabling it again
+    /// keeps the rewards redelegated so far
+    pub fn set_rewards_redelegation(&self, enabled: bool) -> Self {
+        let mut st = self.clone();
+        st.redelegated_rewards = match (enabled, self.redelegated_rewards) {
+            (true, Some(redelegated)) => Some(redelegated),
+            (true, None) => Some(Value::zero()),
+            (false, _) => None,
+        };
         st
     }
 }
@@ -190,8 +289,8 @@ impl<'a, ID, Extra> Iterator for Iter<'a, ID, Extra> {
 #[cfg(test)]
 mod tests {
     use super::{
-        AccountState, DelegationRatio, DelegationType, LastRewards, SpendingCounter,
-        SpendingCounterIncreasing, DELEGATION_RATIO_MAX_DECLS,
+        AccountState, DelegationAmounts, DelegationRatio, DelegationType, LastRewards,
+        SpendingCounter, SpendingCounterIncreasing, DELEGATION_RATIO_MAX_DECLS,
     };
     use crate::{
         certificate::PoolId, testing::builders::StakePoolBuilder, testing::TestGen, value::Value,
@@ -323,9 +422,11 @@ mod tests {
             AccountState {
                 spending: spending_strat,
                 delegation,
+                delegation_amounts: None,
                 value: result_value,
                 tokens: Hamt::new(),
                 last_rewards: LastRewards::default(),
+                redelegated_rewards: None,
                 extra: (),
             }
         }
@@ -484,6 +585,25 @@ mod tests {
         assert!(DelegationRatio::new(parts, pools).is_none());
     }
 
+    #[test]
+    pub fn delegation_replaces_delegation_amounts() {
+        let fake_pool_id = StakePoolBuilder::new().build().id();
+        let amounts = DelegationAmounts::new(vec![(fake_pool_id.clone(), Value(10))]).unwrap();
+        let account_state = AccountState::new(Value(100), ())
+            .set_delegation(DelegationType::Full(fake_pool_id.clone()))
+            .set_delegation_amounts(amounts.clone());
+        assert_eq!(account_state.delegation(), &DelegationType::NonDelegated);
+        assert_eq!(account_state.delegation_amounts(), Some(&amounts));
+
+        let account_state =
+            account_state.set_delegation(DelegationType::Full(fake_pool_id.clone()));
+        assert_eq!(
+            account_state.delegation(),
+            &DelegationType::Full(fake_pool_id)
+        );
+        assert_eq!(account_state.delegation_amounts(), None);
+    }
+
     #[quickcheck]
     pub fn add_rewards(account_state_no_reward: AccountState<()>, value: Value) -> TestResult {
         let initial_value = account_state_no_reward.value();
@@ -18,7 +18,7 @@ use thiserror::Error;
 
 pub use account_state::*;
 pub use last_rewards::LastRewards;
-pub use spending::{SpendingCounter, SpendingCounterIncreasing};
+pub use spending::{SpendingCounter, SpendingCounterIncreasing, SpendingCycle};
 
 #[cfg(any(test, feature = "property-test-api"))]
 pub mod test;
@@ -33,6 +33,8 @@ pub enum LedgerError {
     NonZero,
     #[error("Spending credential invalid")]
     SpendingCredentialInvalid,
+    #[error("Spending counter exhausted, all the cycles of the lane are used")]
+    SpendingCounterExhausted,
     #[error("Value calculation failed")]
     ValueError(#[from] ValueError),
 }
@@ -99,6 +101,35 @@ impl<ID: Clone + Eq + Hash, Extra: Clone> Ledger<ID, Extra> {
             .map_err(|e| e.into())
     }
 
+    /// Set the delegation of explicit amounts of an account in this ledger
+    pub fn set_delegation_amounts(
+        &self,
+        identifier: &ID,
+        amounts: &DelegationAmounts,
+    ) -> Result<Self, LedgerError> {
+        self.0
+            .update(identifier, |st| {
+                Ok(Some(st.set_delegation_amounts(amounts.clone())))
+            })
+            .map(Ledger)
+            .map_err(|e| e.into())
+    }
+
+    /// Enable or disable the redelegation of the rewards of an existing
+    /// account
+    pub fn set_rewards_redelegation(
+        &self,
+        identifier: &ID,
+        enabled: bool,
+    ) -> Result<Self, LedgerError> {
+        self.0
+            .update(identifier, |st| {
+                Ok(Some(st.set_rewards_redelegation(enabled)))
+            })
+            .map(Ledger)
+            .map_err(|e| e.into())
+    }
+
     /// check if an account already exist
     #[inline]
     pub fn exists(&self, identifier: &ID) -> bool {
@@ -112,6 +143,18 @@ impl<ID: Clone + Eq + Hash, Extra: Clone> Ledger<ID, Extra> {
         self.0.lookup(account).ok_or(LedgerError::NonExistent)
     }
 
+    /// Get the cycle of the lane of the spending counter of an account
+    ///
+    /// If the identifier does not match any account, error out
+    pub fn get_spending_cycle(
+        &self,
+        account: &ID,
+        spending_counter: SpendingCounter,
+    ) -> Result<SpendingCycle, LedgerError> {
+        self.get_state(account)
+            .map(|state| state.spending.get_cycle(spending_counter))
+    }
+
     /// Remove an account from this ledger
     ///
     /// If the account still have value > 0, then error
@@ -188,12 +231,14 @@ impl<ID: Clone + Eq + Hash, Extra: Clone> Ledger<ID, Extra> {
             .map_err(|e| e.into())
     }
 
-    pub fn get_total_value(&self) -> Result<Value, ValueError> {
+    /// Sum the values of the accounts, the index of an overflow is the one
+    /// of the account in the iteration order of the ledger
+    pub fn get_total_value(&self) -> Result<Value, SumOverflow> {
         let values = self
             .0
             .iter()
             .map(|(_, account_state)| account_state.value());
-        Value::sum(values)
+        Value::checked_sum(values)
     }
 
     pub fn token_add(
@@ -426,8 +471,10 @@ mod tests {
                         reward: value,
                     },
                     delegation: DelegationType::Full(stake_pool_id),
+                    delegation_amounts: None,
                     value: value_after_reward,
                     tokens: Hamt::new(),
+                    redelegated_rewards: None,
                     extra: (),
                 };
 
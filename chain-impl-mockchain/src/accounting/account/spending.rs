@@ -2,11 +2,20 @@
 use super::LedgerError;
 
 /// Simple strategy to spend from multiple increasing counters
+///
+/// The counter of a lane wraps around once all its values are used, the
+/// lane then starts a new cycle. The cycle is part of the data signed by the
+/// witnesses of the spendings after the first cycle, so that the witnesses
+/// of a previous cycle cannot be replayed.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SpendingCounterIncreasing {
     nexts: Vec<SpendingCounter>,
+    cycles: Vec<SpendingCycle>,
 }
 
+/// Number of times the counter of a lane wrapped around
+pub type SpendingCycle = u32;
+
 // number of bits reserved for lanes
 const LANES_BITS: usize = 3;
 // number of bits reserved for counter (unrespective of the lane)
@@ -23,13 +32,20 @@ impl SpendingCounterIncreasing {
     }
 
     pub fn new_from_counters(set: Vec<SpendingCounter>) -> Option<Self> {
-        if set.len() == Self::LANES {
+        Self::new_from_counters_and_cycles(set, vec![0; Self::LANES])
+    }
+
+    pub fn new_from_counters_and_cycles(
+        set: Vec<SpendingCounter>,
+        cycles: Vec<SpendingCycle>,
+    ) -> Option<Self> {
+        if set.len() == Self::LANES && cycles.len() == Self::LANES {
             for (i, i_set_value) in set.iter().enumerate() {
                 if i_set_value.lane() != i {
                     return None;
                 }
             }
-            Some(SpendingCounterIncreasing { nexts: set })
+            Some(SpendingCounterIncreasing { nexts: set, cycles })
         } else {
             None
         }
@@ -43,19 +59,39 @@ impl SpendingCounterIncreasing {
         self.nexts.clone()
     }
 
+    /// the current cycle of the lane of the counter
+    pub fn get_cycle(&self, counter: SpendingCounter) -> SpendingCycle {
+        self.cycles[counter.lane()]
+    }
+
+    pub fn get_cycles(&self) -> Vec<SpendingCycle> {
+        self.cycles.clone()
+    }
+
+    /// whether the counter of any of the lanes wrapped around
+    pub fn has_wrapped(&self) -> bool {
+        self.cycles.iter().any(|cycle| *cycle != 0)
+    }
+
     /// try to match the lane of the counter in argument, if it doesn't match
     /// a ledger error reported.
     ///
-    /// If the counter match succesfully, then the counter at this lane is incremented by one.
+    /// If the counter match succesfully, then the counter at this lane is incremented by one,
+    /// starting a new cycle of the lane if it wraps around.
     pub fn next_verify(&mut self, counter: SpendingCounter) -> Result<(), LedgerError> {
-        let actual_counter = self.nexts[counter.lane()];
+        let lane = counter.lane();
+        let actual_counter = self.nexts[lane];
 
         if actual_counter != counter {
-            Err(LedgerError::SpendingCredentialInvalid)
-        } else {
-            self.nexts[counter.lane()] = actual_counter.increment();
-            Ok(())
+            return Err(LedgerError::SpendingCredentialInvalid);
+        }
+        if actual_counter.is_last_of_lane() {
+            self.cycles[lane] = self.cycles[lane]
+                .checked_add(1)
+                .ok_or(LedgerError::SpendingCounterExhausted)?;
         }
+        self.nexts[lane] = actual_counter.increment();
+        Ok(())
     }
 }
 
@@ -75,7 +111,10 @@ impl Default for SpendingCounterIncreasing {
         for i in 0..Self::LANES {
             nexts.push(SpendingCounter::new(i, 0));
         }
-        SpendingCounterIncreasing { nexts }
+        SpendingCounterIncreasing {
+            nexts,
+            cycles: vec![0; Self::LANES],
+        }
     }
 }
 
@@ -116,6 +155,12 @@ impl SpendingCounter {
         SpendingCounter(0)
     }
 
+    /// whether the counter is the last one of its lane, the next counter
+    /// of the lane wrapping around
+    pub fn is_last_of_lane(self) -> bool {
+        self.unlaned_counter() == Self::UNLANED_MASK
+    }
+
     /// Increment the counter within it own lane. the lane of where this counter apply, cannot change
     /// through the incrementation procedure
     ///
@@ -269,6 +314,78 @@ mod tests {
         assert!(sc_increasing.next_verify(incorrect_sc).is_err());
     }
 
+    #[quickcheck_macros::quickcheck]
+    pub fn spending_counter_wraps_around_into_a_new_cycle(
+        lane: usize,
+        before_end: u8,
+        after_end: u8,
+    ) -> TestResult {
+        let lane = lane % SpendingCounterIncreasing::LANES;
+        let start = SpendingCounter::new(lane, SpendingCounter::UNLANED_MASK - before_end as u32);
+        let mut sc_increasing = SpendingCounterIncreasing::new_from_counter(start);
+
+        let mut counter = start;
+        for _ in 0..=before_end {
+            assert_eq!(sc_increasing.get_cycle(counter), 0);
+            sc_increasing.next_verify(counter).unwrap();
+            counter = counter.increment();
+        }
+        for _ in 0..after_end {
+            sc_increasing.next_verify(counter).unwrap();
+            counter = counter.increment();
+        }
+
+        let counters = sc_increasing.get_valid_counters();
+        assert_eq!(counters[lane].lane(), lane);
+        assert_eq!(counters[lane].unlaned_counter(), after_end as u32);
+        assert!(sc_increasing.has_wrapped());
+
+        // only the lane of the counter starts a new cycle
+        let cycles = sc_increasing.get_cycles();
+        TestResult::from_bool(cycles.iter().enumerate().all(|(i, cycle)| {
+            if i == lane {
+                *cycle == 1
+            } else {
+                *cycle == 0
+            }
+        }))
+    }
+
+    #[quickcheck_macros::quickcheck]
+    pub fn spending_counter_of_previous_cycle_is_rejected(
+        lane: usize,
+        before_end: u8,
+    ) -> TestResult {
+        let lane = lane % SpendingCounterIncreasing::LANES;
+        let end = SpendingCounter::new(lane, SpendingCounter::UNLANED_MASK);
+        let start = SpendingCounter::new(lane, SpendingCounter::UNLANED_MASK - before_end as u32);
+        let mut sc_increasing = SpendingCounterIncreasing::new_from_counter(start);
+
+        let mut counter = start;
+        for _ in 0..=before_end {
+            sc_increasing.next_verify(counter).unwrap();
+            counter = counter.increment();
+        }
+        assert!(sc_increasing.next_verify(end).is_err());
+        assert!(sc_increasing.next_verify(start).is_err());
+        TestResult::from_bool(sc_increasing.next_verify(counter).is_ok())
+    }
+
+    #[test]
+    pub fn spending_cycle_exhausted() {
+        let mut counters = SpendingCounterIncreasing::default().get_valid_counters();
+        counters[0] = SpendingCounter::new(0, SpendingCounter::UNLANED_MASK);
+        let mut cycles = vec![0; SpendingCounterIncreasing::LANES];
+        cycles[0] = SpendingCycle::MAX;
+        let mut sc_increasing =
+            SpendingCounterIncreasing::new_from_counters_and_cycles(counters, cycles).unwrap();
+
+        assert_eq!(
+            sc_increasing.next_verify(SpendingCounter::new(0, SpendingCounter::UNLANED_MASK)),
+            Err(LedgerError::SpendingCounterExhausted)
+        );
+    }
+
     #[test]
     #[should_panic]
     pub fn spending_counter_increasing_wrong_lane() {
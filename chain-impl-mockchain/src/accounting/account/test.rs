@@ -22,9 +22,11 @@ impl Arbitrary for AccountState<()> {
         AccountState {
             spending: Arbitrary::arbitrary(gen),
             delegation: DelegationType::Full(Arbitrary::arbitrary(gen)),
+            delegation_amounts: None,
             value: Arbitrary::arbitrary(gen),
             tokens: Hamt::new(),
             last_rewards: LastRewards::default(),
+            redelegated_rewards: None,
             extra: (),
         }
     }
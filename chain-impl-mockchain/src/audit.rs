@@ -0,0 +1,558 @@
+//! Audit of a chain: full re-validation of the ledger from a block stream.
+//!
+//! Every block is checked as if it was received from the network: its
+//! parent, the leadership of its header and every fragment of its content
+//! are verified against the state built from the previous blocks. The
+//! digest of the ledger state is compared against a list of checkpoints, to
+//! find where a stored ledger started to diverge from the chain.
+//!
+//! The verification of a header only depends on the leadership of its
+//! epoch, so the headers of a same epoch are verified together, in parallel
+//! on the rayon thread pool when the `rayon` feature is enabled, by
+//! batches of [`AUDIT_VERIFICATION_BATCH`] blocks, before the contents of
+//! the blocks are applied in order.
+//!
+//! The outcome is an [`AuditReport`], its `Display` implementation is a
+//! line oriented format meant to be parsed by tools:
+//!
+//! ```text
+//! start <header id> <chain length> <date>
+//! tip <header id> <chain length> <date>
+//! blocks <number of blocks applied>
+//! fragments <number of fragments applied>
+//! digest <ledger digest at the tip, or - if it could not be computed>
+//! checkpoint <chain length> <header id> <expected digest> <actual digest> <match|mismatch>
+//! unreached <chain length> <expected digest>
+//! failure <chain length> <date> <header id> <reason>
+//! result <passed|failed>
+//! ```
+
+use crate::{
+    block::Block,
+    chaintypes::{ChainLength, HeaderId},
+    date::{BlockDate, Epoch},
+    key::Hash,
+    leadership::{self, Leadership},
+    ledger::{Error as LedgerError, Ledger, RewardsInfoParameters},
+};
+use chain_core::property::Serialize;
+use cryptoxide::{blake2b::Blake2b, digest::Digest};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+use std::fmt;
+use thiserror::Error;
+
+/// maximum number of headers verified in parallel
+pub const AUDIT_VERIFICATION_BATCH: usize = 64;
+
+/// Digest of the state of a ledger, the Blake2b-256 hash of its
+/// serialization
+pub type LedgerDigest = Hash;
+
+/// Expected digest of the ledger state once the block at `chain_length`
+/// is applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub chain_length: ChainLength,
+    pub digest: LedgerDigest,
+}
+
+/// Digest of the ledger state compared against a checkpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CheckpointOutcome {
+    pub chain_length: ChainLength,
+    pub block: HeaderId,
+    pub expected: LedgerDigest,
+    pub actual: LedgerDigest,
+}
+
+#[derive(Debug, Error)]
+pub enum AuditError {
+    #[error("Invalid block0")]
+    Block0(#[source] LedgerError),
+    #[error("The parent of the block is {actual}, expected the previous block {expected}")]
+    InvalidParent {
+        expected: HeaderId,
+        actual: HeaderId,
+    },
+    #[error("Invalid leadership of the block header")]
+    Leadership(#[source] leadership::Error),
+    #[error("Cannot process the epoch transition")]
+    EpochTransition(#[source] LedgerError),
+    #[error("Invalid block")]
+    Block(#[source] LedgerError),
+    #[error("Cannot compute the digest of the ledger")]
+    Digest(#[source] std::io::Error),
+}
+
+/// Block at which the audit stopped
+#[derive(Debug)]
+pub struct AuditFailure {
+    pub chain_length: ChainLength,
+    pub date: BlockDate,
+    pub block: HeaderId,
+    pub error: AuditError,
+}
+
+#[derive(Debug)]
+pub struct AuditReport {
+    /// block the audit started from, block0 or the tip of a trusted ledger
+    pub start: HeaderId,
+    pub start_chain_length: ChainLength,
+    pub start_date: BlockDate,
+    /// last block successfully applied
+    pub tip: HeaderId,
+    pub tip_chain_length: ChainLength,
+    pub tip_date: BlockDate,
+    pub blocks_applied: u64,
+    pub fragments_applied: u64,
+    /// digest of the ledger at the tip, `None` if it could not be computed
+    pub digest: Option<LedgerDigest>,
+    pub checkpoints: Vec<CheckpointOutcome>,
+    /// checkpoints beyond the tip, or not reached because of a failure
+    pub unreached_checkpoints: Vec<Checkpoint>,
+    pub failure: Option<AuditFailure>,
+}
+
+impl CheckpointOutcome {
+    pub fn matches(&self) -> bool {
+        self.expected == self.actual
+    }
+}
+
+impl AuditReport {
+    fn new(start: HeaderId, chain_length: ChainLength, date: BlockDate) -> Self {
+        AuditReport {
+            start,
+            start_chain_length: chain_length,
+            start_date: date,
+            tip: start,
+            tip_chain_length: chain_length,
+            tip_date: date,
+            blocks_applied: 0,
+            fragments_applied: 0,
+            digest: None,
+            checkpoints: Vec::new(),
+            unreached_checkpoints: Vec::new(),
+            failure: None,
+        }
+    }
+
+    pub fn mismatched_checkpoints(&self) -> impl Iterator<Item = &CheckpointOutcome> {
+        self.checkpoints
+            .iter()
+            .filter(|checkpoint| !checkpoint.matches())
+    }
+
+    /// true if every block was applied and every checkpoint was reached
+    /// with the expected digest
+    pub fn passed(&self) -> bool {
+        self.failure.is_none()
+            && self.unreached_checkpoints.is_empty()
+            && self.mismatched_checkpoints().next().is_none()
+    }
+}
+
+impl fmt::Display for AuditReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "start {} {} {}",
+            self.start, self.start_chain_length, self.start_date
+        )?;
+        writeln!(
+            f,
+            "tip {} {} {}",
+            self.tip, self.tip_chain_length, self.tip_date
+        )?;
+        writeln!(f, "blocks {}", self.blocks_applied)?;
+        writeln!(f, "fragments {}", self.fragments_applied)?;
+        match &self.digest {
+            Some(digest) => writeln!(f, "digest {}", digest)?,
+            None => writeln!(f, "digest -")?,
+        }
+        for checkpoint in &self.checkpoints {
+            writeln!(
+                f,
+                "checkpoint {} {} {} {} {}",
+                checkpoint.chain_length,
+                checkpoint.block,
+                checkpoint.expected,
+                checkpoint.actual,
+                if checkpoint.matches() {
+                    "match"
+                } else {
+                    "mismatch"
+                }
+            )?;
+        }
+        for checkpoint in &self.unreached_checkpoints {
+            writeln!(
+                f,
+                "unreached {} {}",
+                checkpoint.chain_length, checkpoint.digest
+            )?;
+        }
+        if let Some(failure) = &self.failure {
+            writeln!(
+                f,
+                "failure {} {} {} {}",
+                failure.chain_length, failure.date, failure.block, failure.error
+            )?;
+        }
+        writeln!(
+            f,
+            "result {}",
+            if self.passed() { "passed" } else { "failed" }
+        )
+    }
+}
+
+struct DigestWriter(Blake2b);
+
+impl std::io::Write for DigestWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.input(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// compute the digest of the state of the ledger, over its serialization in
+/// the canonical order so the digest only depends on the state
+pub fn ledger_digest(ledger: &Ledger) -> Result<LedgerDigest, std::io::Error> {
+    let mut writer = DigestWriter(Blake2b::new(32));
+    ledger.serialize(&mut writer)?;
+    let mut digest = [0; 32];
+    writer.0.result(&mut digest);
+    Ok(Hash::from(digest))
+}
+
+struct Auditor {
+    ledger: Ledger,
+    leadership: Option<Leadership>,
+    checkpoints: BTreeMap<ChainLength, LedgerDigest>,
+    report: AuditReport,
+}
+
+impl Auditor {
+    /// process the epoch transition if the block is the first of a new
+    /// epoch and get the leadership of the block's epoch
+    fn enter_epoch(&mut self, epoch: Epoch) -> Result<(), AuditError> {
+        if self.leadership.as_ref().map(Leadership::epoch) == Some(epoch) {
+            return Ok(());
+        }
+
+        if epoch > self.ledger.date().epoch {
+            let ledger = self
+                .ledger
+                .apply_protocol_changes()
+                .map_err(AuditError::EpochTransition)?;
+            self.ledger = if ledger.can_distribute_reward() {
+                ledger
+                    .distribute_rewards(
+                        &ledger.get_stake_distribution(),
+                        &ledger.get_ledger_parameters(),
+                        RewardsInfoParameters::default(),
+                    )
+                    .map_err(AuditError::EpochTransition)?
+                    .0
+            } else {
+                ledger
+            };
+        }
+
+        self.leadership = Some(Leadership::new(epoch, &self.ledger));
+        Ok(())
+    }
+
+    fn apply(&mut self, block: &Block) -> Result<(), AuditError> {
+        let header = block.header();
+        if header.block_parent_hash() != self.report.tip {
+            return Err(AuditError::InvalidParent {
+                expected: self.report.tip,
+                actual: header.block_parent_hash(),
+            });
+        }
+
+        self.ledger = self
+            .ledger
+            .apply_block(
+                self.ledger.get_ledger_parameters(),
+                block.contents(),
+                &header.get_content_eval_context(),
+            )
+            .map_err(AuditError::Block)?;
+
+        self.report.tip = header.id();
+        self.report.tip_chain_length = header.chain_length();
+        self.report.tip_date = header.block_date();
+        self.report.blocks_applied += 1;
+        self.report.fragments_applied += block.contents().iter().count() as u64;
+        self.check_checkpoint()
+    }
+
+    fn check_checkpoint(&mut self) -> Result<(), AuditError> {
+        if let Some(expected) = self.checkpoints.remove(&self.report.tip_chain_length) {
+            let actual = ledger_digest(&self.ledger).map_err(AuditError::Digest)?;
+            self.report.checkpoints.push(CheckpointOutcome {
+                chain_length: self.report.tip_chain_length,
+                block: self.report.tip,
+                expected,
+                actual,
+            });
+        }
+        Ok(())
+    }
+
+    fn fail(&mut self, block: &Block, error: AuditError) {
+        let header = block.header();
+        self.report.failure = Some(AuditFailure {
+            chain_length: header.chain_length(),
+            date: header.block_date(),
+            block: header.id(),
+            error,
+        });
+    }
+
+    fn finish(mut self) -> AuditReport {
+        self.report.digest = ledger_digest(&self.ledger).ok();
+        self.report.unreached_checkpoints = unreached(self.checkpoints);
+        self.report
+    }
+}
+
+fn unreached(checkpoints: BTreeMap<ChainLength, LedgerDigest>) -> Vec<Checkpoint> {
+    checkpoints
+        .into_iter()
+        .map(|(chain_length, digest)| Checkpoint {
+            chain_length,
+            digest,
+        })
+        .collect()
+}
+
+/// Re-validate the chain starting at `block0`, see [`audit_from`].
+pub fn audit<I>(block0: &Block, blocks: I, checkpoints: &[Checkpoint]) -> AuditReport
+where
+    I: IntoIterator<Item = Block>,
+{
+    let header = block0.header();
+    match Ledger::new(header.id(), block0.contents().iter()) {
+        Ok(ledger) => audit_from(ledger, header.id(), blocks, checkpoints),
+        Err(error) => {
+            let mut report =
+                AuditReport::new(header.id(), header.chain_length(), header.block_date());
+            report.failure = Some(AuditFailure {
+                chain_length: header.chain_length(),
+                date: header.block_date(),
+                block: header.id(),
+                error: AuditError::Block0(error),
+            });
+            report.unreached_checkpoints = unreached(
+                checkpoints
+                    .iter()
+                    .map(|checkpoint| (checkpoint.chain_length, checkpoint.digest))
+                    .collect(),
+            );
+            report
+        }
+    }
+}
+
+/// Re-validate the chain of `blocks` on top of the given `ledger`, whose
+/// last applied block is `tip`.
+///
+/// The blocks are applied with all the checks until the first invalid
+/// one. The digest of the ledger is computed only at the checkpoints and
+/// at the end of the audit, so checkpoints should not be too dense on
+/// large ledgers.
+pub fn audit_from<I>(
+    ledger: Ledger,
+    tip: HeaderId,
+    blocks: I,
+    checkpoints: &[Checkpoint],
+) -> AuditReport
+where
+    I: IntoIterator<Item = Block>,
+{
+    let mut auditor = Auditor {
+        report: AuditReport::new(tip, ledger.chain_length(), ledger.date()),
+        ledger,
+        leadership: None,
+        checkpoints: checkpoints
+            .iter()
+            .map(|checkpoint| (checkpoint.chain_length, checkpoint.digest))
+            .collect(),
+    };
+
+    if let Err(error) = auditor.check_checkpoint() {
+        auditor.report.failure = Some(AuditFailure {
+            chain_length: auditor.report.tip_chain_length,
+            date: auditor.report.tip_date,
+            block: tip,
+            error,
+        });
+        return auditor.finish();
+    }
+
+    let mut blocks = blocks.into_iter().peekable();
+    while let Some(first) = blocks.next() {
+        let epoch = first.header().block_date().epoch;
+        if let Err(error) = auditor.enter_epoch(epoch) {
+            auditor.fail(&first, error);
+            break;
+        }
+
+        let mut batch = vec![first];
+        while batch.len() < AUDIT_VERIFICATION_BATCH {
+            match blocks.next_if(|block| block.header().block_date().epoch == epoch) {
+                Some(block) => batch.push(block),
+                None => break,
+            }
+        }
+
+        let leadership = auditor.leadership.as_ref().unwrap();
+        let verify = |block: &Block| leadership.verify(block.header()).into_error();
+        #[cfg(feature = "rayon")]
+        let verifications: Vec<_> = batch.par_iter().map(verify).collect();
+        #[cfg(not(feature = "rayon"))]
+        let verifications: Vec<_> = batch.iter().map(verify).collect();
+
+        let failed = batch
+            .iter()
+            .zip(verifications)
+            .find_map(|(block, verification)| {
+                verification
+                    .map_err(AuditError::Leadership)
+                    .and_then(|()| auditor.apply(block))
+                    .err()
+                    .map(|error| (block, error))
+            });
+        if let Some((block, error)) = failed {
+            auditor.fail(block, error);
+            break;
+        }
+    }
+
+    auditor.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{
+        builders::BftBlockBuilder,
+        data::LeaderPair,
+        ledger::{ConfigBuilder, LedgerBuilder, TestLedger},
+        Fork, TestGen,
+    };
+    use crate::value::Value;
+
+    fn bft_chain(leader: &LeaderPair, length: usize) -> (TestLedger, Vec<Block>) {
+        let ledger = LedgerBuilder::from_config(
+            ConfigBuilder::new()
+                .with_slots_per_epoch(4)
+                .with_leaders(&[leader.id()]),
+        )
+        .faucet_value(Value(1000))
+        .build()
+        .unwrap();
+
+        let mut branch = Fork::from_block0(&ledger).branch();
+        for _ in 0..length {
+            branch.skip_slots(1);
+            branch.produce_bft_block(leader, Vec::new()).unwrap();
+        }
+        (ledger, branch.blocks().to_vec())
+    }
+
+    #[test]
+    pub fn audit_replays_the_chain_and_checks_the_checkpoints() {
+        let leader = TestGen::leader_pair();
+        let (ledger, blocks) = bft_chain(&leader, 6);
+
+        let mut replayed = ledger.clone();
+        for block in &blocks[..2] {
+            replayed.apply_block(block.clone()).unwrap();
+        }
+        let at_two = ledger_digest(&replayed.ledger).unwrap();
+
+        let report = audit_from(
+            ledger.ledger.clone(),
+            ledger.block0_hash,
+            blocks.clone(),
+            &[Checkpoint {
+                chain_length: ChainLength(2),
+                digest: at_two,
+            }],
+        );
+        assert!(report.passed(), "{}", report);
+        assert_eq!(report.blocks_applied, 6);
+        assert_eq!(report.tip, blocks[5].header().id());
+        assert_eq!(report.tip_date.epoch, 1);
+        assert!(report.to_string().ends_with("result passed\n"));
+
+        let report = audit_from(
+            ledger.ledger.clone(),
+            ledger.block0_hash,
+            blocks,
+            &[
+                Checkpoint {
+                    chain_length: ChainLength(3),
+                    digest: at_two,
+                },
+                Checkpoint {
+                    chain_length: ChainLength(10),
+                    digest: at_two,
+                },
+            ],
+        );
+        assert!(!report.passed());
+        assert!(report.failure.is_none());
+        assert_eq!(report.mismatched_checkpoints().count(), 1);
+        assert_eq!(report.unreached_checkpoints.len(), 1);
+    }
+
+    #[test]
+    pub fn audit_stops_at_the_first_invalid_block() {
+        let leader = TestGen::leader_pair();
+        let (ledger, blocks) = bft_chain(&leader, 3);
+
+        let report = audit_from(
+            ledger.ledger.clone(),
+            ledger.block0_hash,
+            vec![blocks[0].clone(), blocks[2].clone()],
+            &[],
+        );
+        assert_eq!(report.blocks_applied, 1);
+        let failure = report.failure.unwrap();
+        assert_eq!(failure.block, blocks[2].header().id());
+        assert!(matches!(
+            failure.error,
+            AuditError::InvalidParent { expected, actual }
+                if expected == blocks[0].header().id() && actual == blocks[1].header().id()
+        ));
+
+        let intruder = BftBlockBuilder::new()
+            .with_date(blocks[1].header().block_date())
+            .with_chain_length(ChainLength(2))
+            .with_parent_id(blocks[0].header().id())
+            .build(&TestGen::leader_pair(), ledger.era());
+        let report = audit_from(
+            ledger.ledger.clone(),
+            ledger.block0_hash,
+            vec![blocks[0].clone(), intruder],
+            &[],
+        );
+        assert_eq!(report.blocks_applied, 1);
+        assert!(matches!(
+            report.failure.unwrap().error,
+            AuditError::Leadership(_)
+        ));
+    }
+}
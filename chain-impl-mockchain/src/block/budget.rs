@@ -0,0 +1,350 @@
+//! Reading of the blocks and fragments received from a peer within a budget.
+//!
+//! A node reading the data of an untrusted peer wants to stop as soon as the
+//! peer sends more than it is allowed to, before parsing the rest of the
+//! data. A [`ReadContext`] holds the [`ReadBudget`] of a peer, and the
+//! consumption of the budget by all the blocks and fragments read through
+//! it: the number of fragments and the number of bytes are cumulative over
+//! the lifetime of the context, the number of certificates is limited per
+//! block.
+//!
+//! The budget depends on the trust in the peer, see [`ReadBudget::for_peer`].
+//! An exhausted budget is reported with a [`BudgetViolation`], distinct from
+//! the malformed data.
+
+use super::{Block, ContentsBuilder, Header};
+use crate::fragment::{Fragment, FRAGMENT_SIZE_BYTES_LEN};
+use chain_core::mempack::{ReadBuf, ReadError, Readable};
+use thiserror::Error;
+
+/// The trust in a peer, selecting its default budget
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeerTrust {
+    /// a peer of the operator, not limited
+    Trusted,
+    /// a peer known to behave, with generous limits
+    Known,
+    /// any other peer
+    Untrusted,
+}
+
+/// The limits of a [`ReadContext`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadBudget {
+    /// number of fragments, over all the blocks and fragments read
+    pub max_fragments: usize,
+    /// number of bytes, over all the blocks and fragments read
+    pub max_bytes: usize,
+    /// number of certificates in a block
+    pub max_certificates_per_block: usize,
+}
+
+impl ReadBudget {
+    pub const fn unlimited() -> Self {
+        ReadBudget {
+            max_fragments: usize::MAX,
+            max_bytes: usize::MAX,
+            max_certificates_per_block: usize::MAX,
+        }
+    }
+
+    pub const fn for_peer(trust: PeerTrust) -> Self {
+        match trust {
+            PeerTrust::Trusted => Self::unlimited(),
+            PeerTrust::Known => ReadBudget {
+                max_fragments: 100_000,
+                max_bytes: 256 * 1024 * 1024,
+                max_certificates_per_block: 1_024,
+            },
+            PeerTrust::Untrusted => ReadBudget {
+                max_fragments: 10_000,
+                max_bytes: 16 * 1024 * 1024,
+                max_certificates_per_block: 64,
+            },
+        }
+    }
+}
+
+/// The limit of the budget exceeded by the data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum BudgetViolation {
+    #[error("more than {limit} fragments")]
+    Fragments { limit: usize },
+    #[error("{requested} bytes requested with {available} bytes left of the {limit} bytes")]
+    Bytes {
+        limit: usize,
+        available: usize,
+        requested: usize,
+    },
+    #[error("more than {limit} certificates in the block")]
+    CertificatesPerBlock { limit: usize },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum BudgetedReadError {
+    #[error("read budget exceeded")]
+    Budget(#[from] BudgetViolation),
+    #[error("malformed data")]
+    Read(#[from] ReadError),
+}
+
+/// The consumption of the budget of a [`ReadContext`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReadUsage {
+    pub fragments: usize,
+    pub bytes: usize,
+}
+
+/// Reads the blocks and fragments of a peer within its budget, see the
+/// module documentation
+#[derive(Debug, Clone)]
+pub struct ReadContext {
+    budget: ReadBudget,
+    usage: ReadUsage,
+}
+
+impl ReadContext {
+    pub fn new(budget: ReadBudget) -> Self {
+        ReadContext {
+            budget,
+            usage: ReadUsage::default(),
+        }
+    }
+
+    pub fn for_peer(trust: PeerTrust) -> Self {
+        Self::new(ReadBudget::for_peer(trust))
+    }
+
+    pub fn budget(&self) -> &ReadBudget {
+        &self.budget
+    }
+
+    pub fn usage(&self) -> ReadUsage {
+        self.usage
+    }
+
+    /// Start a new window, with the whole budget available again
+    pub fn reset(&mut self) {
+        self.usage = ReadUsage::default();
+    }
+
+    /// Read a serialized block, as its [`Unpack`](chain_core::property::Unpack)
+    /// implementation does, within the budget. The data read until the
+    /// failure, if any, is charged to the budget.
+    pub fn read_block(&mut self, bytes: &[u8]) -> Result<Block, BudgetedReadError> {
+        self.charge_bytes(bytes.len())?;
+        let mut buf = ReadBuf::from(bytes);
+        let header_size = buf.get_u16()? as usize;
+        let header = Header::read(&mut buf.split_to(header_size)?)?;
+
+        let mut remaining_content_size = header.block_content_size() as usize;
+        let mut contents = ContentsBuilder::new();
+        let mut certificates = 0;
+        while remaining_content_size > 0 {
+            let raw_size = buf.get_u32()? as usize;
+            let fragment_size = FRAGMENT_SIZE_BYTES_LEN + raw_size;
+            if fragment_size > remaining_content_size {
+                return Err(ReadError::SizeTooBig {
+                    size: fragment_size,
+                    limit: remaining_content_size,
+                }
+                .into());
+            }
+            let fragment = self.read_fragment_in(&mut buf.split_to(raw_size)?)?;
+            if fragment.certificate_kind().is_some() {
+                certificates += 1;
+                if certificates > self.budget.max_certificates_per_block {
+                    return Err(BudgetViolation::CertificatesPerBlock {
+                        limit: self.budget.max_certificates_per_block,
+                    }
+                    .into());
+                }
+            }
+            contents.push(fragment);
+            remaining_content_size -= fragment_size;
+        }
+        buf.expect_end()?;
+
+        let (content_hash, _) =
+            contents.current_hash_size_with(header.block_version().content_hash_scheme());
+        if header.block_content_hash() != content_hash {
+            return Err(ReadError::ContentHashMismatch {
+                header: header.block_content_hash().into(),
+                computed: content_hash.into(),
+            }
+            .into());
+        }
+        Ok(Block::new_unchecked(header, contents.into()))
+    }
+
+    /// Read a serialized fragment, without its size prefix, within the
+    /// budget
+    pub fn read_fragment(&mut self, bytes: &[u8]) -> Result<Fragment, BudgetedReadError> {
+        self.charge_bytes(bytes.len())?;
+        self.read_fragment_in(&mut ReadBuf::from(bytes))
+    }
+
+    fn read_fragment_in(&mut self, buf: &mut ReadBuf) -> Result<Fragment, BudgetedReadError> {
+        if self.usage.fragments >= self.budget.max_fragments {
+            return Err(BudgetViolation::Fragments {
+                limit: self.budget.max_fragments,
+            }
+            .into());
+        }
+        self.usage.fragments += 1;
+        let fragment = Fragment::read(buf)?;
+        buf.expect_end()?;
+        Ok(fragment)
+    }
+
+    fn charge_bytes(&mut self, requested: usize) -> Result<(), BudgetViolation> {
+        let available = self.budget.max_bytes - self.usage.bytes;
+        if requested > available {
+            return Err(BudgetViolation::Bytes {
+                limit: self.budget.max_bytes,
+                available,
+                requested,
+            });
+        }
+        self.usage.bytes += requested;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::{self, BlockDate, BlockVersion};
+    use crate::certificate::NameClaim;
+    use crate::config::ConfigParam;
+    use crate::fragment::{ConfigParams, ContentHashScheme};
+    use crate::transaction::TxBuilder;
+    use chain_core::property::Serialize;
+
+    fn block_with(fragments: Vec<Fragment>) -> Vec<u8> {
+        let mut contents = ContentsBuilder::new();
+        contents.push_many(fragments);
+        block::builder(BlockVersion::Genesis, contents.into(), |header| {
+            Ok::<_, ()>(
+                header
+                    .set_genesis()
+                    .set_date(BlockDate::first())
+                    .into_unsigned_header()
+                    .unwrap()
+                    .generalize(),
+            )
+        })
+        .unwrap()
+        .serialize_as_vec()
+        .unwrap()
+    }
+
+    fn initial(slots_per_epoch: u32) -> Fragment {
+        let mut params = ConfigParams::new();
+        params.push(ConfigParam::SlotsPerEpoch(slots_per_epoch));
+        Fragment::Initial(params)
+    }
+
+    #[test]
+    fn fragments_and_bytes_are_cumulative() {
+        let bytes = block_with((1..=3).map(initial).collect());
+        let budget = ReadBudget {
+            max_fragments: 5,
+            ..ReadBudget::unlimited()
+        };
+        let mut context = ReadContext::new(budget);
+        context.read_block(&bytes).unwrap();
+        assert_eq!(
+            context.usage(),
+            ReadUsage {
+                fragments: 3,
+                bytes: bytes.len()
+            }
+        );
+        assert_eq!(
+            context.read_block(&bytes),
+            Err(BudgetedReadError::Budget(BudgetViolation::Fragments {
+                limit: 5
+            }))
+        );
+
+        context.reset();
+        context.read_block(&bytes).unwrap();
+
+        let mut context = ReadContext::new(ReadBudget {
+            max_bytes: bytes.len() - 1,
+            ..ReadBudget::unlimited()
+        });
+        assert_eq!(
+            context.read_block(&bytes),
+            Err(BudgetedReadError::Budget(BudgetViolation::Bytes {
+                limit: bytes.len() - 1,
+                available: bytes.len() - 1,
+                requested: bytes.len(),
+            }))
+        );
+        assert_eq!(context.usage(), ReadUsage::default());
+    }
+
+    #[test]
+    fn certificates_are_limited_per_block() {
+        let name_claim = |name: &str| {
+            let tx = TxBuilder::new()
+                .set_payload(&NameClaim {
+                    name: name.parse().unwrap(),
+                })
+                .set_expiry_date(BlockDate::first().next_epoch())
+                .set_ios(&[], &[])
+                .set_witnesses(&[])
+                .set_payload_auth(&());
+            Fragment::NameClaim(tx)
+        };
+        let bytes = block_with(vec![initial(1), name_claim("alice"), name_claim("bob")]);
+
+        let budget = ReadBudget {
+            max_certificates_per_block: 1,
+            ..ReadBudget::for_peer(PeerTrust::Untrusted)
+        };
+        assert_eq!(
+            ReadContext::new(budget).read_block(&bytes),
+            Err(BudgetedReadError::Budget(
+                BudgetViolation::CertificatesPerBlock { limit: 1 }
+            ))
+        );
+        let mut context = ReadContext::for_peer(PeerTrust::Untrusted);
+        assert_eq!(context.read_block(&bytes).unwrap().fragments().count(), 3);
+
+        // the fragments of the mempool are counted alongside the blocks
+        let fragment = initial(4).to_raw().serialize_as_vec().unwrap();
+        let fragment = &fragment[FRAGMENT_SIZE_BYTES_LEN..];
+        assert_eq!(
+            context.read_fragment(fragment).unwrap().hash(),
+            initial(4).hash()
+        );
+        assert_eq!(context.usage().fragments, 4);
+    }
+
+    #[test]
+    fn content_hash_mismatch_reports_both_hashes() {
+        let mut bytes = block_with(vec![initial(1)]);
+        let declared = Block::read(&mut ReadBuf::from(&bytes))
+            .unwrap()
+            .header()
+            .block_content_hash();
+        // the last byte is the one of the slots per epoch
+        *bytes.last_mut().unwrap() = 2;
+        let mut contents = ContentsBuilder::new();
+        contents.push(initial(2));
+        let (computed, _) = contents.current_hash_size_with(ContentHashScheme::Sequential);
+
+        let error = ReadError::ContentHashMismatch {
+            header: declared.into(),
+            computed: computed.into(),
+        };
+        assert_eq!(Block::read(&mut ReadBuf::from(&bytes)), Err(error.clone()));
+        assert_eq!(
+            ReadContext::for_peer(PeerTrust::Untrusted).read_block(&bytes),
+            Err(BudgetedReadError::Read(error))
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use super::Block;
-use crate::fragment::Contents;
+use crate::fragment::{Contents, ContentsBuilder};
 use crate::header::{BlockVersion, Header, HeaderBuilderNew};
 
 /// Create a block from a block version, content and a header builder closure
@@ -12,3 +12,26 @@ where
     hdr_builder(HeaderBuilderNew::new(version, &contents))
         .map(|header| Block::new_unchecked(header, contents))
 }
+
+/// Create a block from a block version, a contents builder and a header builder closure
+///
+/// The hash and size of the contents maintained by the builder are used, the
+/// fragments are not hashed again.
+pub fn builder_from_contents<E, F>(
+    version: BlockVersion,
+    mut contents: ContentsBuilder,
+    hdr_builder: F,
+) -> Result<Block, E>
+where
+    F: FnOnce(HeaderBuilderNew) -> Result<Header, E>,
+{
+    let (content_hash, content_size) =
+        contents.current_hash_size_with(version.content_hash_scheme());
+    let contents: Contents = contents.into();
+    hdr_builder(HeaderBuilderNew::new_raw(
+        version,
+        &content_hash,
+        content_size,
+    ))
+    .map(|header| Block::new_unchecked(header, contents))
+}
@@ -0,0 +1,290 @@
+//! Reading of the contents of a block against its header, with diagnostics.
+//!
+//! The [`Unpack`](chain_core::property::Unpack) implementation of [`Block`]
+//! fails with a bare [`ReadError`] when the fragments do not add up to the
+//! content size declared by the header. [`read_block`] reads the same
+//! encoding, but reports which fragment breaks the declared size, at which
+//! offset of the contents, and how many bytes were declared and found.
+//!
+//! In the [`ContentReadMode::Lenient`] mode the reading stops at the first
+//! inconsistency instead of failing, and the fragments read until then are
+//! returned along with the diagnostic, so the recovery tools can salvage the
+//! fragments of a partially corrupt block. A block is only rebuilt from a
+//! report without any inconsistency.
+
+use super::{Block, BlockContentHash, BlockContentSize, ContentsBuilder, Header};
+use crate::fragment::{Fragment, FRAGMENT_SIZE_BYTES_LEN};
+use chain_core::mempack::{ReadBuf, ReadError, Readable};
+use thiserror::Error;
+
+/// How the inconsistencies of the contents with the header are handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentReadMode {
+    /// fail on the first inconsistency
+    Strict,
+    /// stop on the first inconsistency and report it with the fragments
+    /// read until then
+    Lenient,
+}
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum ContentError {
+    #[error("invalid block header")]
+    Header(#[source] ReadError),
+    #[error("fragment {index} at offset {offset} is {size} bytes, over the {remaining} bytes left of the {declared} bytes declared")]
+    FragmentOverflow {
+        index: usize,
+        offset: usize,
+        size: usize,
+        remaining: usize,
+        declared: BlockContentSize,
+    },
+    #[error(
+        "the contents end in the fragment {index}, after {actual} of the {declared} bytes declared"
+    )]
+    Truncated {
+        index: usize,
+        actual: usize,
+        declared: BlockContentSize,
+    },
+    #[error("fragment {index} at offset {offset} is malformed")]
+    MalformedFragment {
+        index: usize,
+        offset: usize,
+        #[source]
+        error: ReadError,
+    },
+    #[error("{trailing} bytes after the {declared} bytes of contents declared")]
+    TrailingBytes {
+        trailing: usize,
+        declared: BlockContentSize,
+    },
+    #[error("the contents hash to {actual} but the header declares {declared}")]
+    HashMismatch {
+        declared: BlockContentHash,
+        actual: BlockContentHash,
+    },
+}
+
+/// The outcome of the reading of a block
+#[derive(Debug, Clone)]
+pub struct ContentReport {
+    pub header: Header,
+    /// the fragments read, all of them if there is no inconsistency
+    pub fragments: Vec<Fragment>,
+    /// the size of the fragments read, their size prefixes included
+    pub actual_size: usize,
+    /// the first inconsistency, always `None` in the strict mode
+    pub error: Option<ContentError>,
+}
+
+impl ContentReport {
+    pub fn is_consistent(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// Rebuild the block, failing with the inconsistency of the contents
+    /// if any
+    pub fn into_block(self) -> Result<Block, ContentError> {
+        match self.error {
+            Some(error) => Err(error),
+            None => {
+                let mut contents = ContentsBuilder::new();
+                contents.push_many(self.fragments);
+                Ok(Block::new_unchecked(self.header, contents.into()))
+            }
+        }
+    }
+}
+
+/// Read the serialized block, checking its contents against the size and
+/// the hash declared by its header. The header needs to be valid in either
+/// mode.
+pub fn read_block(bytes: &[u8], mode: ContentReadMode) -> Result<ContentReport, ContentError> {
+    let mut buf = ReadBuf::from(bytes);
+    let header_size = buf.get_u16().map_err(ContentError::Header)? as usize;
+    let header = buf
+        .split_to(header_size)
+        .and_then(|mut header_buf| Header::read(&mut header_buf))
+        .map_err(ContentError::Header)?;
+
+    let mut report = ContentReport {
+        header,
+        fragments: Vec::new(),
+        actual_size: 0,
+        error: None,
+    };
+    let contents = buf.get_slice_end();
+    if let Err(error) = read_contents(contents, &mut report) {
+        match mode {
+            ContentReadMode::Strict => return Err(error),
+            ContentReadMode::Lenient => report.error = Some(error),
+        }
+    }
+    Ok(report)
+}
+
+fn read_contents(contents: &[u8], report: &mut ContentReport) -> Result<(), ContentError> {
+    let declared = report.header.block_content_size();
+    let declared_size = declared as usize;
+    let mut builder = ContentsBuilder::new();
+
+    while report.actual_size < declared_size {
+        let offset = report.actual_size;
+        let index = report.fragments.len();
+        let truncated = ContentError::Truncated {
+            index,
+            actual: contents.len(),
+            declared,
+        };
+        let size_bytes = contents
+            .get(offset..offset + FRAGMENT_SIZE_BYTES_LEN)
+            .ok_or_else(|| truncated.clone())?;
+        let raw_size = u32::from_be_bytes(size_bytes.try_into().unwrap()) as usize;
+        let size = FRAGMENT_SIZE_BYTES_LEN + raw_size;
+        let remaining = declared_size - offset;
+        if size > remaining {
+            return Err(ContentError::FragmentOverflow {
+                index,
+                offset,
+                size,
+                remaining,
+                declared,
+            });
+        }
+        let raw = contents
+            .get(offset + FRAGMENT_SIZE_BYTES_LEN..offset + size)
+            .ok_or(truncated)?;
+        let mut fragment_buf = ReadBuf::from(raw);
+        let fragment = Fragment::read(&mut fragment_buf)
+            .and_then(|fragment| fragment_buf.expect_end().map(|()| fragment))
+            .map_err(|error| ContentError::MalformedFragment {
+                index,
+                offset,
+                error,
+            })?;
+        builder.push(fragment.clone());
+        report.fragments.push(fragment);
+        report.actual_size += size;
+    }
+
+    if contents.len() > declared_size {
+        return Err(ContentError::TrailingBytes {
+            trailing: contents.len() - declared_size,
+            declared,
+        });
+    }
+    let scheme = report.header.block_version().content_hash_scheme();
+    let (actual, _) = builder.current_hash_size_with(scheme);
+    let declared = report.header.block_content_hash();
+    if actual != declared {
+        return Err(ContentError::HashMismatch { declared, actual });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::{self, BlockDate, BlockVersion};
+    use crate::config::ConfigParam;
+    use crate::fragment::ConfigParams;
+    use chain_core::property::Serialize;
+
+    fn block_with_fragments(count: u32) -> (Block, Vec<u8>) {
+        let mut contents = ContentsBuilder::new();
+        for slots_per_epoch in 1..=count {
+            let mut params = ConfigParams::new();
+            params.push(ConfigParam::SlotsPerEpoch(slots_per_epoch));
+            contents.push(Fragment::Initial(params));
+        }
+        let block = block::builder(BlockVersion::Genesis, contents.into(), |header| {
+            Ok::<_, ()>(
+                header
+                    .set_genesis()
+                    .set_date(BlockDate::first())
+                    .into_unsigned_header()
+                    .unwrap()
+                    .generalize(),
+            )
+        })
+        .unwrap();
+        let bytes = block.serialize_as_vec().unwrap();
+        (block, bytes)
+    }
+
+    #[test]
+    fn consistent_block_is_read_in_both_modes() {
+        let (block, bytes) = block_with_fragments(3);
+        for mode in [ContentReadMode::Strict, ContentReadMode::Lenient] {
+            let report = read_block(&bytes, mode).unwrap();
+            assert!(report.is_consistent());
+            assert_eq!(
+                report.actual_size,
+                block.header().block_content_size() as usize
+            );
+            assert_eq!(report.into_block().unwrap(), block);
+        }
+    }
+
+    #[test]
+    fn truncated_block_is_salvaged_in_lenient_mode() {
+        let (block, bytes) = block_with_fragments(3);
+        let fragment_size = block
+            .fragments()
+            .next()
+            .unwrap()
+            .to_raw()
+            .size_bytes_plus_size();
+        let truncated = &bytes[..bytes.len() - 1];
+
+        let expected = ContentError::Truncated {
+            index: 2,
+            actual: 3 * fragment_size - 1,
+            declared: block.header().block_content_size(),
+        };
+        assert_eq!(
+            read_block(truncated, ContentReadMode::Strict).unwrap_err(),
+            expected
+        );
+        let report = read_block(truncated, ContentReadMode::Lenient).unwrap();
+        assert_eq!(report.error, Some(expected.clone()));
+        assert!(report.fragments.iter().eq(block.fragments().take(2)));
+        assert_eq!(report.into_block().unwrap_err(), expected);
+    }
+
+    #[test]
+    fn oversized_fragment_is_located() {
+        let (block, mut bytes) = block_with_fragments(2);
+        let fragment_size = block
+            .fragments()
+            .next()
+            .unwrap()
+            .to_raw()
+            .size_bytes_plus_size();
+        let contents_offset = bytes.len() - 2 * fragment_size;
+        // grow the size prefix of the second fragment
+        let prefix = contents_offset + fragment_size + FRAGMENT_SIZE_BYTES_LEN - 1;
+        bytes[prefix] += 1;
+
+        assert_eq!(
+            read_block(&bytes, ContentReadMode::Strict).unwrap_err(),
+            ContentError::FragmentOverflow {
+                index: 1,
+                offset: fragment_size,
+                size: fragment_size + 1,
+                remaining: fragment_size,
+                declared: block.header().block_content_size(),
+            }
+        );
+
+        let (_, mut bytes) = block_with_fragments(1);
+        bytes.push(0);
+        let report = read_block(&bytes, ContentReadMode::Lenient).unwrap();
+        assert_eq!(report.fragments.len(), 1);
+        assert!(matches!(
+            report.error,
+            Some(ContentError::TrailingBytes { trailing: 1, .. })
+        ));
+    }
+}
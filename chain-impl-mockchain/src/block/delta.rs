@@ -0,0 +1,314 @@
+//! Encoding of a block as a delta against its parent, for archival storage.
+//!
+//! The delta keeps the header bytes that differ from the header of the
+//! parent and replaces the fragments already present in the parent with
+//! their index in the parent. Decoding needs the parent and rebuilds the
+//! exact bytes of the block, which are checked against the hash of the
+//! original bytes recorded in the delta.
+//!
+//! The encoding is only a storage format: blocks are always exchanged and
+//! identified with their full serialization.
+
+use super::{Block, HeaderId};
+use crate::fragment::{FragmentId, FragmentRaw};
+use crate::key::Hash;
+use chain_core::{
+    mempack::{ReadBuf, ReadError, Readable},
+    packer::Codec,
+    property::{Block as _, Serialize},
+};
+use std::collections::HashMap;
+use thiserror::Error;
+use typed_bytes::ByteBuilder;
+
+/// Version of the delta encoding, written as the first byte of every delta
+pub const BLOCK_DELTA_VERSION: u8 = 1;
+
+const FRAGMENT_FROM_PARENT: u8 = 0;
+const FRAGMENT_LITERAL: u8 = 1;
+
+/// runs of identical header bytes shorter than the overhead of a new run
+/// (offset and length) are copied in the enclosing run instead
+const HEADER_RUN_OVERHEAD: usize = 4;
+
+/// A block encoded as a delta against its parent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockDelta(Box<[u8]>);
+
+#[derive(Debug, Error)]
+pub enum BlockDeltaError {
+    #[error("the block {block} is not a child of the block {parent}")]
+    NotAChild { parent: HeaderId, block: HeaderId },
+    #[error("the delta is relative to the block {expected} but the block {got} was given")]
+    ParentMismatch { expected: HeaderId, got: HeaderId },
+    #[error("unsupported block delta version {0}")]
+    UnsupportedVersion(u8),
+    #[error("the delta references the fragment {index} but the parent has {count} fragments")]
+    UnknownParentFragment { index: u32, count: usize },
+    #[error("the decoded block does not match the hash of the original block")]
+    HashMismatch,
+    #[error("invalid block delta encoding")]
+    Read(#[from] ReadError),
+    #[error("cannot serialize the block")]
+    Io(#[from] std::io::Error),
+}
+
+impl BlockDelta {
+    /// Encode the block as a delta against its parent
+    pub fn encode(parent: &Block, block: &Block) -> Result<Self, BlockDeltaError> {
+        if block.parent_id() != parent.id() {
+            return Err(BlockDeltaError::NotAChild {
+                parent: parent.id(),
+                block: block.id(),
+            });
+        }
+
+        let original = block.serialize_as_vec()?;
+
+        let parent_fragments: HashMap<FragmentId, u32> = parent
+            .fragments()
+            .enumerate()
+            .map(|(index, fragment)| (fragment.hash(), index as u32))
+            .collect();
+
+        let header = block.header().as_slice();
+        let runs = header_runs(parent.header().as_slice(), header);
+
+        let bb = ByteBuilder::<Self>::new()
+            .u8(BLOCK_DELTA_VERSION)
+            .bytes(parent.id().as_ref())
+            .bytes(Hash::hash_bytes(&original).as_ref())
+            .u16(header.len() as u16)
+            .iter16(runs.iter(), |bb, (offset, len)| {
+                bb.u16(*offset as u16)
+                    .u16(*len as u16)
+                    .bytes(&header[*offset..*offset + *len])
+            })
+            .u32(block.fragments().count() as u32)
+            .fold(block.fragments(), |bb, fragment| {
+                match parent_fragments.get(&fragment.hash()) {
+                    Some(index) => bb.u8(FRAGMENT_FROM_PARENT).u32(*index),
+                    None => {
+                        let raw = fragment.to_raw();
+                        bb.u8(FRAGMENT_LITERAL)
+                            .u32(raw.as_ref().len() as u32)
+                            .bytes(raw.as_ref())
+                    }
+                }
+            });
+
+        Ok(Self(bb.finalize_as_vec().into()))
+    }
+
+    /// Identifier of the parent block the delta is relative to
+    pub fn parent_id(&self) -> Result<HeaderId, BlockDeltaError> {
+        let mut buf = ReadBuf::from(&self.0);
+        read_version(&mut buf)?;
+        Ok(<[u8; 32]>::read(&mut buf)?.into())
+    }
+
+    /// Rebuild the exact serialization of the block from its parent
+    pub fn decode(&self, parent: &Block) -> Result<Vec<u8>, BlockDeltaError> {
+        let mut buf = ReadBuf::from(&self.0);
+        read_version(&mut buf)?;
+        let parent_id: HeaderId = <[u8; 32]>::read(&mut buf)?.into();
+        if parent_id != parent.id() {
+            return Err(BlockDeltaError::ParentMismatch {
+                expected: parent_id,
+                got: parent.id(),
+            });
+        }
+        let hash: Hash = <[u8; 32]>::read(&mut buf)?.into();
+
+        let parent_header = parent.header().as_slice();
+        let header_len = buf.get_u16()? as usize;
+        let mut header: Vec<u8> = parent_header
+            .iter()
+            .copied()
+            .chain(std::iter::repeat(0))
+            .take(header_len)
+            .collect();
+        let nb_runs = buf.get_u16()?;
+        for _ in 0..nb_runs {
+            let offset = buf.get_u16()? as usize;
+            let len = buf.get_u16()? as usize;
+            let bytes = buf.get_slice(len)?;
+            header
+                .get_mut(offset..offset + len)
+                .ok_or_else(|| ReadError::SizeTooBig {
+                    size: offset + len,
+                    limit: header_len,
+                })?
+                .copy_from_slice(bytes);
+        }
+
+        let parent_fragments: Vec<FragmentRaw> = parent
+            .fragments()
+            .map(|fragment| fragment.to_raw())
+            .collect();
+
+        let mut codec = Codec::new(Vec::new());
+        codec.put_be_u16(header_len as u16)?;
+        codec.put_bytes(&header)?;
+        let nb_fragments = buf.get_u32()?;
+        for _ in 0..nb_fragments {
+            match buf.get_u8()? {
+                FRAGMENT_FROM_PARENT => {
+                    let index = buf.get_u32()?;
+                    let raw = parent_fragments.get(index as usize).ok_or(
+                        BlockDeltaError::UnknownParentFragment {
+                            index,
+                            count: parent_fragments.len(),
+                        },
+                    )?;
+                    codec.put_be_u32(raw.as_ref().len() as u32)?;
+                    codec.put_bytes(raw.as_ref())?;
+                }
+                FRAGMENT_LITERAL => {
+                    let len = buf.get_u32()?;
+                    codec.put_be_u32(len)?;
+                    codec.put_bytes(buf.get_slice(len as usize)?)?;
+                }
+                tag => {
+                    return Err(ReadError::UnknownTag {
+                        kind: "block delta fragment",
+                        tag: tag as u32,
+                    }
+                    .into())
+                }
+            }
+        }
+        buf.expect_end()?;
+
+        let original = codec.into_inner();
+        if Hash::hash_bytes(&original) != hash {
+            return Err(BlockDeltaError::HashMismatch);
+        }
+        Ok(original)
+    }
+
+    /// Rebuild the block from its parent
+    pub fn decode_block(&self, parent: &Block) -> Result<Block, BlockDeltaError> {
+        let bytes = self.decode(parent)?;
+        Ok(Block::read(&mut ReadBuf::from(&bytes))?)
+    }
+}
+
+impl AsRef<[u8]> for BlockDelta {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Box<[u8]>> for BlockDelta {
+    fn from(bytes: Box<[u8]>) -> Self {
+        Self(bytes)
+    }
+}
+
+fn read_version(buf: &mut ReadBuf) -> Result<(), BlockDeltaError> {
+    match buf.get_u8()? {
+        BLOCK_DELTA_VERSION => Ok(()),
+        version => Err(BlockDeltaError::UnsupportedVersion(version)),
+    }
+}
+
+/// the `(offset, len)` runs of the header differing from the parent header,
+/// the bytes past the end of the parent header are always part of a run
+fn header_runs(parent: &[u8], header: &[u8]) -> Vec<(usize, usize)> {
+    let mut runs: Vec<(usize, usize)> = Vec::new();
+    for (offset, byte) in header.iter().enumerate() {
+        if parent.get(offset) == Some(byte) {
+            continue;
+        }
+        match runs.last_mut() {
+            Some((start, len)) if offset - (*start + *len) < HEADER_RUN_OVERHEAD => {
+                *len = offset + 1 - *start;
+            }
+            _ => runs.push((offset, 1)),
+        }
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::{builder, BlockVersion, ContentsBuilder};
+    use crate::fragment::Fragment;
+    use crate::header::BlockDate;
+    use quickcheck::TestResult;
+    use quickcheck_macros::quickcheck;
+
+    fn block(parent: Option<&Block>, fragments: &[Fragment]) -> Block {
+        let mut contents = ContentsBuilder::new();
+        contents.push_many(fragments.iter().cloned());
+        builder(BlockVersion::Genesis, contents.into(), |hdr| {
+            let hdr = match parent {
+                Some(parent) => hdr
+                    .set_parent(&parent.id(), parent.chain_length().increase())
+                    .set_date(parent.date().next_epoch()),
+                None => hdr.set_genesis().set_date(BlockDate::first()),
+            };
+            Ok::<_, ()>(
+                hdr.into_unsigned_header()
+                    .expect("internal error cannot build unsigned block")
+                    .generalize(),
+            )
+        })
+        .unwrap()
+    }
+
+    #[quickcheck]
+    fn block_delta_bijection(shared: Vec<Fragment>, new: Vec<Fragment>) -> TestResult {
+        if shared.len() + new.len() > 12 {
+            return TestResult::discard();
+        }
+        let parent = block(Some(&block(None, &[])), &shared);
+        let fragments: Vec<Fragment> = new.iter().chain(shared.iter()).cloned().collect();
+        let child = block(Some(&parent), &fragments);
+
+        let delta = BlockDelta::encode(&parent, &child).unwrap();
+        let original = child.serialize_as_vec().unwrap();
+
+        assert_eq!(delta.parent_id().unwrap(), parent.id());
+        assert_eq!(delta.decode_block(&parent).unwrap().id(), child.id());
+        TestResult::from_bool(delta.decode(&parent).unwrap() == original)
+    }
+
+    #[quickcheck]
+    fn block_delta_requires_the_parent(fragments: Vec<Fragment>) -> TestResult {
+        if fragments.is_empty() || fragments.len() > 12 {
+            return TestResult::discard();
+        }
+        let root = block(None, &[]);
+        let parent = block(Some(&root), &fragments);
+        let other = block(Some(&root), &[]);
+        let child = block(Some(&parent), &[]);
+
+        assert!(matches!(
+            BlockDelta::encode(&other, &child),
+            Err(BlockDeltaError::NotAChild { .. })
+        ));
+        let delta = BlockDelta::encode(&parent, &child).unwrap();
+        TestResult::from_bool(matches!(
+            delta.decode(&other),
+            Err(BlockDeltaError::ParentMismatch { .. })
+        ))
+    }
+
+    #[test]
+    fn block_delta_detects_corruption() {
+        let parent = block(Some(&block(None, &[])), &[]);
+        let child = block(Some(&parent), &[]);
+        let delta = BlockDelta::encode(&parent, &child).unwrap();
+
+        // flip a bit of the hash of the original block
+        let mut corrupted = delta.as_ref().to_vec();
+        corrupted[1 + 32] ^= 1;
+        assert!(matches!(
+            BlockDelta::from(corrupted.into_boxed_slice()).decode(&parent),
+            Err(BlockDeltaError::HashMismatch)
+        ));
+    }
+}
@@ -0,0 +1,216 @@
+//! Framing of a block for the network transfer.
+//!
+//! The frame prefixes the serialized block with a fixed size preamble: a
+//! magic, the total length of the frame, a CRC32 of the block bytes and the
+//! id of the block header. A relay can read the preamble to route the block
+//! by its header id, and check the integrity of the transfer with the
+//! checksum, without deserializing the block.
+//!
+//! The framing is only a transport envelope: the block bytes are kept as is,
+//! and the preamble is never part of the hashed or signed content of a block.
+
+use super::{Block, HeaderId};
+use chain_core::{
+    mempack::{ReadBuf, ReadError, Readable},
+    property::{Block as _, Serialize},
+};
+use thiserror::Error;
+use typed_bytes::ByteBuilder;
+
+/// Magic written as the first bytes of every frame
+pub const BLOCK_FRAME_MAGIC: [u8; 4] = *b"BLKF";
+
+/// Size of the preamble: magic, total length, checksum and header id
+pub const BLOCK_FRAME_PREAMBLE_SIZE: usize = 4 + 4 + 4 + 32;
+
+/// A serialized block prefixed with its preamble
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockFrame(Box<[u8]>);
+
+/// The preamble of a frame, readable before the rest of the frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FramePreamble {
+    /// length of the whole frame, preamble included
+    pub length: u32,
+    /// CRC32 of the block bytes
+    pub checksum: u32,
+    pub header_id: HeaderId,
+}
+
+#[derive(Debug, Error)]
+pub enum BlockFrameError {
+    #[error("invalid block frame magic {0:?}")]
+    InvalidMagic([u8; 4]),
+    #[error("the frame declares {declared} bytes but has {actual} bytes")]
+    LengthMismatch { declared: u32, actual: usize },
+    #[error("the frame checksum is {declared:#010x} but the block checksum is {computed:#010x}")]
+    ChecksumMismatch { declared: u32, computed: u32 },
+    #[error("the frame is for the block {declared} but contains the block {got}")]
+    HeaderIdMismatch { declared: HeaderId, got: HeaderId },
+    #[error("the block of {0} bytes is too big to be framed")]
+    TooBig(usize),
+    #[error("invalid block frame encoding")]
+    Read(#[from] ReadError),
+    #[error("cannot serialize the block")]
+    Io(#[from] std::io::Error),
+}
+
+impl FramePreamble {
+    /// Read the preamble from the first bytes of a frame, the bytes after
+    /// the preamble are not looked at
+    pub fn read_from(bytes: &[u8]) -> Result<Self, BlockFrameError> {
+        let mut buf = ReadBuf::from(bytes);
+        let magic = <[u8; 4]>::read(&mut buf)?;
+        if magic != BLOCK_FRAME_MAGIC {
+            return Err(BlockFrameError::InvalidMagic(magic));
+        }
+        let length = buf.get_u32()?;
+        let checksum = buf.get_u32()?;
+        let header_id = <[u8; 32]>::read(&mut buf)?.into();
+        Ok(FramePreamble {
+            length,
+            checksum,
+            header_id,
+        })
+    }
+}
+
+impl BlockFrame {
+    /// Frame the serialization of the block
+    pub fn encode(block: &Block) -> Result<Self, BlockFrameError> {
+        let bytes = block.serialize_as_vec()?;
+        let length = bytes
+            .len()
+            .checked_add(BLOCK_FRAME_PREAMBLE_SIZE)
+            .and_then(|length| u32::try_from(length).ok())
+            .ok_or(BlockFrameError::TooBig(bytes.len()))?;
+
+        let bb = ByteBuilder::<Self>::new()
+            .bytes(&BLOCK_FRAME_MAGIC)
+            .u32(length)
+            .u32(crc32fast::hash(&bytes))
+            .bytes(block.id().as_ref())
+            .bytes(&bytes);
+
+        Ok(Self(bb.finalize_as_vec().into()))
+    }
+
+    pub fn preamble(&self) -> Result<FramePreamble, BlockFrameError> {
+        FramePreamble::read_from(&self.0)
+    }
+
+    /// Identifier of the framed block, as declared by the preamble
+    pub fn header_id(&self) -> Result<HeaderId, BlockFrameError> {
+        Ok(self.preamble()?.header_id)
+    }
+
+    /// Check the length and the checksum of the frame and return the bytes
+    /// of the block, which are not deserialized
+    pub fn block_bytes(&self) -> Result<&[u8], BlockFrameError> {
+        let preamble = self.preamble()?;
+        if preamble.length as usize != self.0.len() {
+            return Err(BlockFrameError::LengthMismatch {
+                declared: preamble.length,
+                actual: self.0.len(),
+            });
+        }
+        let bytes = &self.0[BLOCK_FRAME_PREAMBLE_SIZE..];
+        let computed = crc32fast::hash(bytes);
+        if computed != preamble.checksum {
+            return Err(BlockFrameError::ChecksumMismatch {
+                declared: preamble.checksum,
+                computed,
+            });
+        }
+        Ok(bytes)
+    }
+
+    /// Check the frame and deserialize the block, which has to be the block
+    /// declared by the preamble
+    pub fn decode_block(&self) -> Result<Block, BlockFrameError> {
+        let declared = self.header_id()?;
+        let mut buf = ReadBuf::from(self.block_bytes()?);
+        let block = Block::read(&mut buf)?;
+        buf.expect_end()?;
+        if block.id() != declared {
+            return Err(BlockFrameError::HeaderIdMismatch {
+                declared,
+                got: block.id(),
+            });
+        }
+        Ok(block)
+    }
+}
+
+impl AsRef<[u8]> for BlockFrame {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Box<[u8]>> for BlockFrame {
+    fn from(bytes: Box<[u8]>) -> Self {
+        Self(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck::TestResult;
+    use quickcheck_macros::quickcheck;
+
+    #[quickcheck]
+    fn block_frame_bijection(block: Block) -> TestResult {
+        let frame = BlockFrame::encode(&block).unwrap();
+        let preamble = FramePreamble::read_from(frame.as_ref()).unwrap();
+
+        assert_eq!(preamble.header_id, block.id());
+        assert_eq!(preamble.length as usize, frame.as_ref().len());
+        assert_eq!(
+            frame.block_bytes().unwrap(),
+            &block.serialize_as_vec().unwrap()[..]
+        );
+        TestResult::from_bool(frame.decode_block().unwrap() == block)
+    }
+
+    #[quickcheck]
+    fn block_frame_detects_corruption(block: Block, position: usize) -> TestResult {
+        let frame = BlockFrame::encode(&block).unwrap();
+        let mut corrupted = frame.as_ref().to_vec();
+        let position =
+            BLOCK_FRAME_PREAMBLE_SIZE + position % (corrupted.len() - BLOCK_FRAME_PREAMBLE_SIZE);
+        corrupted[position] ^= 1;
+        TestResult::from_bool(matches!(
+            BlockFrame::from(corrupted.into_boxed_slice()).block_bytes(),
+            Err(BlockFrameError::ChecksumMismatch { .. })
+        ))
+    }
+
+    #[quickcheck]
+    fn block_frame_checks_the_preamble(block: Block) -> TestResult {
+        let frame = BlockFrame::encode(&block).unwrap();
+
+        let mut bad_magic = frame.as_ref().to_vec();
+        bad_magic[0] ^= 1;
+        assert!(matches!(
+            BlockFrame::from(bad_magic.into_boxed_slice()).preamble(),
+            Err(BlockFrameError::InvalidMagic(_))
+        ));
+
+        let mut truncated = frame.as_ref().to_vec();
+        truncated.pop();
+        assert!(matches!(
+            BlockFrame::from(truncated.into_boxed_slice()).block_bytes(),
+            Err(BlockFrameError::LengthMismatch { .. })
+        ));
+
+        // a valid frame of the block bytes, declaring another header id
+        let mut other_id = frame.as_ref().to_vec();
+        other_id[12] ^= 1;
+        TestResult::from_bool(matches!(
+            BlockFrame::from(other_id.into_boxed_slice()).decode_block(),
+            Err(BlockFrameError::HeaderIdMismatch { .. })
+        ))
+    }
+}
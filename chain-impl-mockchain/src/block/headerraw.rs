@@ -1,4 +1,8 @@
-use chain_core::property;
+use chain_core::{
+    mempack::ReadError,
+    packer::Codec,
+    property::{ByteSource, Pack, Unpack},
+};
 
 /// Block Header Bytes
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -10,32 +14,18 @@ impl AsRef<[u8]> for HeaderRaw {
     }
 }
 
-impl property::Serialize for HeaderRaw {
-    type Error = std::io::Error;
-
-    fn serialize<W: std::io::Write>(&self, writer: W) -> Result<(), Self::Error> {
-        use chain_core::packer::*;
-        use std::io::Write;
-
-        let mut codec = Codec::new(writer);
-        codec.put_be_u16(self.0.len() as u16)?;
-        codec.write_all(&self.0)?;
-        Ok(())
+impl Pack for HeaderRaw {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_size_be_u16(self.0.len())?;
+        codec.put_bytes(&self.0)
     }
 }
 
-impl property::Deserialize for HeaderRaw {
-    type Error = std::io::Error;
-
-    fn deserialize<R: std::io::BufRead>(reader: R) -> Result<Self, Self::Error> {
-        use chain_core::packer::Codec;
-        use std::io::Read;
-
-        let mut codec = Codec::new(reader);
-
-        let header_size = codec.get_be_u16()? as usize;
-        let mut v = vec![0u8; header_size];
-        codec.read_exact(&mut v[..])?;
-        Ok(HeaderRaw(v))
+impl Unpack for HeaderRaw {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let header_size = source.take_u16()?;
+        source.take_vec(header_size as usize).map(HeaderRaw)
     }
 }
+
+chain_ser::impl_legacy_codecs!(HeaderRaw);
@@ -0,0 +1,146 @@
+//! Proofs of the inclusion of a fragment in a block.
+//!
+//! The blocks of the versions hashing their content as a Merkle tree, see
+//! [`BlockVersion::content_hash_scheme`], can prove that one of their
+//! fragments is in their content with an [`InclusionProof`]. A light client
+//! following the headers only checks the proof with [`verify_inclusion`],
+//! e.g. to check that a payment is in the chain without the whole block.
+
+use super::{Block, BlockVersion, Header};
+use crate::fragment::{ContentHashScheme, Fragment, FragmentId, InclusionProof};
+use thiserror::Error;
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum InclusionProofError {
+    #[error("the content hash of the blocks of version {0:?} is not a Merkle tree")]
+    UnsupportedVersion(BlockVersion),
+    #[error("fragment {0} is not in the block")]
+    FragmentNotFound(FragmentId),
+    #[error("the proof does not lead from the fragment {0} to the content hash of the header")]
+    InvalidProof(FragmentId),
+}
+
+fn check_version(header: &Header) -> Result<(), InclusionProofError> {
+    let version = header.block_version();
+    match version.content_hash_scheme() {
+        ContentHashScheme::MerkleTree => Ok(()),
+        ContentHashScheme::Sequential => Err(InclusionProofError::UnsupportedVersion(version)),
+    }
+}
+
+impl Block {
+    /// Proof that the fragment is in the block, checked against the header
+    /// with [`verify_inclusion`]
+    pub fn inclusion_proof(
+        &self,
+        fragment_id: &FragmentId,
+    ) -> Result<InclusionProof, InclusionProofError> {
+        check_version(&self.header)?;
+        let ids: Vec<FragmentId> = self.fragments().map(Fragment::hash).collect();
+        let index = ids
+            .iter()
+            .position(|id| id == fragment_id)
+            .ok_or(InclusionProofError::FragmentNotFound(*fragment_id))?;
+        Ok(InclusionProof::new(&ids, index).expect("the fragment is in the block"))
+    }
+}
+
+/// Check that the fragment is in the block of the header, without the
+/// contents of the block
+pub fn verify_inclusion(
+    header: &Header,
+    fragment_id: &FragmentId,
+    proof: &InclusionProof,
+) -> Result<(), InclusionProofError> {
+    check_version(header)?;
+    if proof.verify(fragment_id, &header.block_content_hash()) {
+        Ok(())
+    } else {
+        Err(InclusionProofError::InvalidProof(*fragment_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::{self, BlockDate, ContentsBuilder};
+    use crate::config::ConfigParam;
+    use crate::fragment::ConfigParams;
+    use chain_crypto::{testing::TestCryptoGen, Ed25519, SecretKey};
+
+    fn block_with_fragments(version: BlockVersion, count: u32) -> Block {
+        let mut contents = ContentsBuilder::new();
+        for slots_per_epoch in 1..=count {
+            let mut params = ConfigParams::new();
+            params.push(ConfigParam::SlotsPerEpoch(slots_per_epoch));
+            contents.push(Fragment::Initial(params));
+        }
+        let leader_key = SecretKey::<Ed25519>::generate(TestCryptoGen(0).get_rng(0));
+        block::builder_from_contents(version, contents, |header| {
+            Ok::<_, ()>(
+                header
+                    .set_genesis()
+                    .set_date(BlockDate::first())
+                    .into_bft_builder()
+                    .unwrap()
+                    .sign_using(&leader_key)
+                    .generalize(),
+            )
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn every_fragment_is_proven_against_the_header() {
+        let block = block_with_fragments(BlockVersion::Ed25519SignedMerkle, 7);
+        for fragment in block.fragments() {
+            let id = fragment.hash();
+            let proof = block.inclusion_proof(&id).unwrap();
+            assert_eq!(verify_inclusion(block.header(), &id, &proof), Ok(()));
+        }
+    }
+
+    #[test]
+    fn proof_of_another_block_is_rejected() {
+        let block = block_with_fragments(BlockVersion::Ed25519SignedMerkle, 5);
+        let other = block_with_fragments(BlockVersion::Ed25519SignedMerkle, 6);
+        let id = other.fragments().last().unwrap().hash();
+        assert_eq!(
+            block.inclusion_proof(&id),
+            Err(InclusionProofError::FragmentNotFound(id))
+        );
+        let proof = other.inclusion_proof(&id).unwrap();
+        assert_eq!(
+            verify_inclusion(block.header(), &id, &proof),
+            Err(InclusionProofError::InvalidProof(id))
+        );
+    }
+
+    #[test]
+    fn sequential_content_hash_has_no_proof() {
+        let block = block_with_fragments(BlockVersion::Ed25519Signed, 3);
+        let id = block.fragments().next().unwrap().hash();
+        assert_eq!(
+            block.inclusion_proof(&id),
+            Err(InclusionProofError::UnsupportedVersion(
+                BlockVersion::Ed25519Signed
+            ))
+        );
+    }
+
+    #[test]
+    fn merkle_block_is_read_back() {
+        use chain_core::property::{Deserialize, Serialize};
+
+        let block = block_with_fragments(BlockVersion::Ed25519SignedMerkle, 4);
+        assert_eq!(
+            block.header().block_content_hash(),
+            block
+                .contents()
+                .compute_hash_size_with(ContentHashScheme::MerkleTree)
+                .0
+        );
+        let bytes = block.serialize_as_vec().unwrap();
+        assert_eq!(Block::deserialize(bytes.as_slice()).unwrap(), block);
+    }
+}
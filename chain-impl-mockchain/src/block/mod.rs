@@ -1,26 +1,46 @@
 //! Representation of the block in the mockchain.
-use crate::fragment::{Fragment, FragmentRaw};
-use chain_core::mempack::{read_from_raw, ReadBuf, ReadError, Readable};
-use chain_core::property;
+use crate::fragment::{Fragment, FRAGMENT_SIZE_BYTES_LEN};
+use chain_core::mempack::{ReadBuf, ReadError, Readable};
+use chain_core::packer::Codec;
+use chain_core::property::{self, ByteSource, Pack, Unpack};
+use chain_core::shared::SharedBytes;
 
-use std::{io, slice};
+use std::slice;
 
+mod budget;
 mod builder;
+mod content_check;
+mod delta;
+mod framing;
 mod header;
 mod headerraw;
+mod inclusion;
 
 #[cfg(any(test, feature = "property-test-api"))]
 pub mod test;
 
 //pub use self::builder::BlockBuilder;
-pub use crate::fragment::{BlockContentHash, BlockContentSize, Contents, ContentsBuilder};
+pub use crate::fragment::{
+    BlockContentHash, BlockContentSize, ContentHashScheme, Contents, ContentsBuilder,
+    InclusionProof,
+};
 
 pub use self::headerraw::HeaderRaw;
 pub use crate::header::{
-    BftProof, BftSignature, Common, GenesisPraosProof, Header, HeaderId, KesSignature, Proof,
+    BftEndorsement, BftProof, BftSignature, Common, GenesisPraosProof, Header, HeaderId,
+    KesSignature, Proof,
 };
 
-pub use builder::builder;
+pub use budget::{
+    BudgetViolation, BudgetedReadError, PeerTrust, ReadBudget, ReadContext, ReadUsage,
+};
+pub use builder::{builder, builder_from_contents};
+pub use content_check::{read_block, ContentError, ContentReadMode, ContentReport};
+pub use delta::{BlockDelta, BlockDeltaError, BLOCK_DELTA_VERSION};
+pub use framing::{
+    BlockFrame, BlockFrameError, FramePreamble, BLOCK_FRAME_MAGIC, BLOCK_FRAME_PREAMBLE_SIZE,
+};
+pub use inclusion::{verify_inclusion, InclusionProofError};
 
 pub use crate::header::{BlockVersion, ChainLength};
 
@@ -59,6 +79,21 @@ impl Block {
     pub fn fragments(&self) -> impl Iterator<Item = &Fragment> {
         self.contents.iter()
     }
+
+    /// Read a block from a shared buffer, the transactions of the block
+    /// keep slices of the buffer instead of copies of their bytes.
+    ///
+    /// This covers the bytes of every fragment carried by a transaction,
+    /// including the ones holding a certificate, but only these bytes: a
+    /// certificate is decoded into an owned value when the payload of its
+    /// transaction is accessed, and the initial and legacy fragments are
+    /// owned values.
+    pub fn from_shared(bytes: &SharedBytes) -> Result<Self, ReadError> {
+        let mut buf = ReadBuf::from_shared(bytes);
+        let block = Self::read(&mut buf)?;
+        buf.expect_end()?;
+        Ok(block)
+    }
 }
 
 impl property::Block for Block {
@@ -92,116 +127,86 @@ impl property::Block for Block {
     }
 }
 
-impl property::Serialize for Block {
-    type Error = std::io::Error;
-
-    fn serialize<W: std::io::Write>(&self, mut writer: W) -> Result<(), Self::Error> {
-        let header_raw = {
-            let mut v = Vec::new();
-            self.header.serialize(&mut v)?;
-            HeaderRaw(v)
-        };
-        header_raw.serialize(&mut writer)?;
+impl Pack for Block {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        let header = self.header.as_slice();
+        codec.put_size_be_u16(header.len())?;
+        codec.put_bytes(header)?;
 
         for message in self.contents.iter() {
-            let message_raw = message.to_raw();
-            message_raw.serialize(&mut writer)?;
+            message.to_raw().pack(codec)?;
         }
         Ok(())
     }
 }
 
-impl property::Deserialize for Block {
-    type Error = std::io::Error;
-
-    fn deserialize<R: std::io::BufRead>(mut reader: R) -> Result<Self, Self::Error> {
-        let header_raw = HeaderRaw::deserialize(&mut reader)?;
-        let header = read_from_raw::<Header>(header_raw.as_ref())?;
-
-        let mut serialized_content_size = header.block_content_size();
-        let mut contents = ContentsBuilder::new();
-
-        while serialized_content_size > 0 {
-            let message_raw = FragmentRaw::deserialize(&mut reader)?;
-            let message_size = message_raw.size_bytes_plus_size() as u32;
-
-            if message_size > serialized_content_size {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    format!(
-                        "{} bytes remaining according to the header but got a fragment of size {}",
-                        message_size, serialized_content_size,
-                    ),
-                ));
-            }
-
-            let message = Fragment::from_raw(&message_raw)
-                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
-            contents.push(message);
-
-            serialized_content_size -= message_size;
-        }
-
-        let contents: Contents = contents.into();
-        let (content_hash, _content_size) = contents.compute_hash_size();
-
-        if content_hash != header.block_content_hash() {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                format!(
-                    "Inconsistent block content hash in header: block {} header {}",
-                    content_hash,
-                    header.block_content_hash()
-                ),
-            ));
-        }
-
-        Ok(Block { header, contents })
-    }
-}
-
-impl Readable for Block {
-    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        let header_size = buf.get_u16()? as usize;
-        let mut header_buf = buf.split_to(header_size)?;
-        let header = Header::read(&mut header_buf)?;
+impl Unpack for Block {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let header_size = source.take_u16()? as usize;
+        let header = source.with_slice(header_size, Header::read)?;
 
         let mut remaining_content_size = header.block_content_size() as usize;
         let mut contents = ContentsBuilder::new();
 
         while remaining_content_size > 0 {
-            let message_raw = FragmentRaw::read(buf)?;
-            let message_size = message_raw.size_bytes_plus_size();
+            let raw_size = source.take_u32()? as usize;
+            let message_size = FRAGMENT_SIZE_BYTES_LEN + raw_size;
 
             if message_size > remaining_content_size {
-                return Err(ReadError::StructureInvalid(format!(
-                    "{} bytes remaining according to the header but got a fragment of size {}",
-                    message_size, remaining_content_size
-                )));
+                return Err(ReadError::SizeTooBig {
+                    size: message_size,
+                    limit: remaining_content_size,
+                });
             }
 
-            let message = Fragment::from_raw(&message_raw)
-                .map_err(|e| ReadError::StructureInvalid(e.to_string()))?;
+            // the fragment is read directly from the source: when reading
+            // from a shared buffer, the transactions keep slices of it
+            let message = source.with_slice(raw_size, |buf| {
+                Fragment::read(buf).and_then(|fragment| buf.expect_end().map(|()| fragment))
+            })?;
             contents.push(message);
 
             remaining_content_size -= message_size;
         }
 
+        let (content_hash, _content_size) =
+            contents.current_hash_size_with(header.block_version().content_hash_scheme());
         let contents: Contents = contents.into();
-        let (content_hash, _content_size) = contents.compute_hash_size();
 
         if header.block_content_hash() != content_hash {
-            return Err(ReadError::InvalidData(format!(
-                "Inconsistent block content hash in header: block {} header {}",
-                content_hash,
-                header.block_content_hash()
-            )));
+            return Err(ReadError::ContentHashMismatch {
+                header: header.block_content_hash().into(),
+                computed: content_hash.into(),
+            });
         }
 
         Ok(Block { header, contents })
     }
 }
 
+impl property::Serialize for Block {
+    type Error = std::io::Error;
+
+    fn serialize<W: std::io::Write>(&self, writer: W) -> Result<(), Self::Error> {
+        self.pack(&mut Codec::new(writer))
+    }
+}
+
+/// Reads the serialized block, compressed or not, see [`crate::compression`]
+impl property::Deserialize for Block {
+    type Error = std::io::Error;
+
+    fn deserialize<R: std::io::BufRead>(reader: R) -> Result<Self, Self::Error> {
+        property::unpack_from_reader(crate::compression::decompressing_reader(reader)?)
+    }
+}
+
+impl Readable for Block {
+    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
+        Self::unpack(buf)
+    }
+}
+
 impl<'a> property::HasFragments<'a> for &'a Block {
     type Fragment = Fragment;
     type Fragments = slice::Iter<'a, Fragment>;
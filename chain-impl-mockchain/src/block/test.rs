@@ -3,7 +3,9 @@ use crate::block::Header;
 #[cfg(test)]
 use crate::header::HeaderDesc;
 #[cfg(test)]
-use crate::testing::serialization::{serialization_bijection, serialization_bijection_r};
+use crate::testing::serialization::{
+    canonical_encoding_r, serialization_bijection, serialization_bijection_r, Perturbation,
+};
 use crate::{
     block::{Block, BlockVersion, HeaderRaw},
     fragment::{Contents, ContentsBuilder, Fragment},
@@ -14,6 +16,8 @@ use chain_core::property::{Block as _, Deserialize, Serialize};
 #[cfg(test)]
 use chain_ser::mempack::{ReadBuf, Readable};
 #[cfg(test)]
+use chain_ser::shared::SharedBytes;
+#[cfg(test)]
 use quickcheck::TestResult;
 use quickcheck::{Arbitrary, Gen};
 
@@ -26,14 +30,27 @@ quickcheck! {
         serialization_bijection_r(b)
     }
 
-    fn block_serialization_bijection(b: Block) -> TestResult {
-        serialization_bijection(b)
-    }
-
     fn block_serialization_bijection_r(b: Block) -> TestResult {
         serialization_bijection_r(b)
     }
 
+    fn block_trailing_bytes_rejected(b: Block, trailing: Vec<u8>) -> TestResult {
+        canonical_encoding_r(&b, &Perturbation::TrailingBytes(trailing))
+    }
+
+    fn block_header_size_malleability(b: Block, delta: i8) -> TestResult {
+        canonical_encoding_r(&b, &Perturbation::LengthPrefix { delta: delta as i16 })
+    }
+
+    fn block_section_reordering(b: Block, first: u8, second: u8, len: u8) -> TestResult {
+        let perturbation = Perturbation::SwapSections {
+            first: first as usize,
+            second: second as usize,
+            len: 1 + len as usize % 32,
+        };
+        canonical_encoding_r(&b, &perturbation)
+    }
+
     fn block_properties(block: Block) -> TestResult {
 
         let vec = block.serialize_as_vec().unwrap();
@@ -72,7 +89,8 @@ quickcheck! {
     // TODO: add a separate test with headers with correct content size to stress hash
     // checking when tests are migrated to proptest
     fn inconsistent_block_deserialization(header: Header, contents: Contents) -> bool {
-        let (content_hash, content_size) = contents.compute_hash_size();
+        let (content_hash, content_size) =
+            contents.compute_hash_size_with(header.block_version().content_hash_scheme());
 
         let maybe_block = Block { header: header.clone(), contents };
         let block_de = Block::deserialize(maybe_block.serialize_as_vec().unwrap().as_ref());
@@ -87,6 +105,56 @@ quickcheck! {
     }
 }
 
+#[cfg(test)]
+mod prop {
+    use super::*;
+    use crate::fragment::ContentHashScheme;
+    use crate::testing::strategy::{block_strategy, fragment_strategy, MAX_FRAGMENTS};
+    use proptest::{collection::vec, prelude::*};
+    use test_strategy::proptest;
+
+    #[proptest]
+    fn block_serialization_bijection(#[strategy(block_strategy())] block: Block) {
+        let bytes = block.serialize_as_vec().unwrap();
+        prop_assert_eq!(Block::deserialize(&bytes[..]).unwrap(), block);
+    }
+
+    #[cfg(feature = "compression")]
+    #[proptest]
+    fn compressed_block_serialization_bijection(#[strategy(block_strategy())] block: Block) {
+        use crate::compression::{serialize_compressed, CompressionLevel};
+        let bytes = serialize_compressed(&block, Vec::new(), CompressionLevel::default()).unwrap();
+        prop_assert_eq!(Block::deserialize(&bytes[..]).unwrap(), block);
+    }
+
+    #[proptest]
+    fn block_from_shared_buffer(#[strategy(block_strategy())] block: Block) {
+        let bytes = SharedBytes::from(block.serialize_as_vec().unwrap());
+        let shared = Block::from_shared(&bytes).unwrap();
+        prop_assert_eq!(shared.header(), block.header());
+        prop_assert!(shared.fragments().eq(block.fragments()));
+    }
+
+    #[proptest]
+    fn contents_builder_running_hash_size(
+        #[strategy(vec(fragment_strategy(), 1..=MAX_FRAGMENTS))] fragments: Vec<Fragment>,
+        removed: usize,
+    ) {
+        let mut builder = ContentsBuilder::new();
+        builder.push_many(fragments);
+        let contents: Contents = builder.clone().into();
+        prop_assert_eq!(builder.current_hash_size(), contents.compute_hash_size());
+
+        builder.remove(removed % builder.len());
+        let contents: Contents = builder.clone().into();
+        prop_assert_eq!(builder.current_hash_size(), contents.compute_hash_size());
+        prop_assert_eq!(
+            builder.current_hash_size_with(ContentHashScheme::MerkleTree),
+            contents.compute_hash_size_with(ContentHashScheme::MerkleTree)
+        );
+    }
+}
+
 #[cfg(test)]
 fn are_desc_equal(left: HeaderDesc, right: HeaderDesc) -> bool {
     left.id == right.id
@@ -127,7 +195,7 @@ impl Arbitrary for Block {
             .set_date(date);
         let header = match ver {
             BlockVersion::Genesis => hdrbuilder.into_unsigned_header().unwrap().generalize(),
-            BlockVersion::Ed25519Signed => {
+            BlockVersion::Ed25519Signed | BlockVersion::Ed25519SignedMerkle => {
                 let bft_proof: BftProof = Arbitrary::arbitrary(g);
                 hdrbuilder
                     .into_bft_builder()
@@ -136,7 +204,7 @@ impl Arbitrary for Block {
                     .set_signature(bft_proof.signature)
                     .generalize()
             }
-            BlockVersion::KesVrfproof => {
+            BlockVersion::KesVrfproof | BlockVersion::KesVrfproofMerkle => {
                 let gp_proof: GenesisPraosProof = Arbitrary::arbitrary(g);
                 hdrbuilder
                     .into_genesis_praos_builder()
@@ -1,13 +1,18 @@
-use crate::accounting::account::{DelegationRatio, DelegationType, DELEGATION_RATIO_MAX_DECLS};
-use crate::certificate::CertificateSlice;
+use crate::accounting::account::{
+    DelegationAmounts, DelegationRatio, DelegationType, DELEGATION_AMOUNTS_MAX_DECLS,
+    DELEGATION_RATIO_MAX_DECLS,
+};
+use crate::certificate::{CertificateSlice, SerializedSize};
 use crate::transaction::{
     AccountBindingSignature, Payload, PayloadAuthData, PayloadData, PayloadSlice,
     UnspecifiedAccountIdentifier,
 };
+use crate::value::Value;
 
 use chain_core::{
-    mempack::{ReadBuf, ReadError, Readable},
-    property,
+    mempack::ReadError,
+    packer::Codec,
+    property::{ByteSource, Pack, Unpack},
 };
 use std::marker::PhantomData;
 use typed_bytes::{ByteArray, ByteBuilder};
@@ -34,6 +39,12 @@ impl OwnerStakeDelegation {
     }
 }
 
+impl SerializedSize for OwnerStakeDelegation {
+    fn serialized_size(&self) -> usize {
+        self.serialize_in(ByteBuilder::new()).len()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct StakeDelegation {
     pub account_id: UnspecifiedAccountIdentifier,
@@ -54,22 +65,86 @@ impl StakeDelegation {
     }
 }
 
-impl property::Serialize for OwnerStakeDelegation {
-    type Error = std::io::Error;
-    fn serialize<W: std::io::Write>(&self, mut writer: W) -> Result<(), Self::Error> {
-        let delegation_buf =
-            serialize_delegation_type(&self.delegation, ByteBuilder::new()).finalize_as_vec();
-        writer.write_all(&delegation_buf)
+impl SerializedSize for StakeDelegation {
+    fn serialized_size(&self) -> usize {
+        self.serialize_in(ByteBuilder::new()).len()
+    }
+}
+
+/// A delegation of explicit amounts of the stake of an account to
+/// multiple pools, see [`DelegationAmounts`].
+///
+/// It replaces any previous delegation of the account, and is replaced by
+/// any later one, whatever their form.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AmountStakeDelegation {
+    pub account_id: UnspecifiedAccountIdentifier,
+    pub delegation: DelegationAmounts,
+}
+
+impl AmountStakeDelegation {
+    pub fn serialize_in(&self, bb: ByteBuilder<Self>) -> ByteBuilder<Self> {
+        bb.bytes(self.account_id.as_ref())
+            .iter8(self.delegation.pools().iter(), |b, (pool_id, value)| {
+                b.u64(value.0).bytes(pool_id.as_ref())
+            })
+    }
+    pub fn serialize(&self) -> ByteArray<Self> {
+        self.serialize_in(ByteBuilder::new()).finalize()
+    }
+
+    pub fn get_delegation_amounts(&self) -> &DelegationAmounts {
+        &self.delegation
+    }
+}
+
+impl SerializedSize for AmountStakeDelegation {
+    fn serialized_size(&self) -> usize {
+        self.serialize_in(ByteBuilder::new()).len()
+    }
+}
+
+/// Enable or disable the redelegation of the rewards of an account.
+///
+/// Once enabled, the rewards received by the account are delegated in the
+/// proportions of the delegation of the account, even when it delegates
+/// explicit amounts, without any new delegation certificate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RewardsRedelegation {
+    pub account_id: UnspecifiedAccountIdentifier,
+    pub enabled: bool,
+}
+
+impl RewardsRedelegation {
+    pub fn serialize_in(&self, bb: ByteBuilder<Self>) -> ByteBuilder<Self> {
+        bb.bytes(self.account_id.as_ref()).u8(self.enabled as u8)
+    }
+    pub fn serialize(&self) -> ByteArray<Self> {
+        self.serialize_in(ByteBuilder::new()).finalize()
+    }
+}
+
+impl SerializedSize for RewardsRedelegation {
+    fn serialized_size(&self) -> usize {
+        self.serialize_in(ByteBuilder::new()).len()
+    }
+}
+
+impl Pack for OwnerStakeDelegation {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(self.serialize().as_slice())
     }
 }
 
-impl Readable for OwnerStakeDelegation {
-    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        let delegation = deserialize_delegation_type(buf)?;
+impl Unpack for OwnerStakeDelegation {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let delegation = deserialize_delegation_type(source)?;
         Ok(Self { delegation })
     }
 }
 
+chain_ser::impl_legacy_codecs!(OwnerStakeDelegation);
+
 impl Payload for OwnerStakeDelegation {
     const HAS_DATA: bool = true;
     const HAS_AUTH: bool = false;
@@ -90,25 +165,16 @@ impl Payload for OwnerStakeDelegation {
     }
 }
 
-impl property::Serialize for StakeDelegation {
-    type Error = std::io::Error;
-    fn serialize<W: std::io::Write>(&self, writer: W) -> Result<(), Self::Error> {
-        use chain_core::packer::*;
-        use std::io::Write;
-
-        let delegation_buf =
-            serialize_delegation_type(&self.delegation, ByteBuilder::new()).finalize_as_vec();
-        let mut codec = Codec::new(writer);
-        codec.write_all(self.account_id.as_ref())?;
-        codec.write_all(&delegation_buf)?;
-        Ok(())
+impl Pack for StakeDelegation {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(self.serialize().as_slice())
     }
 }
 
-impl Readable for StakeDelegation {
-    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        let account_identifier = <[u8; 32]>::read(buf)?;
-        let delegation = deserialize_delegation_type(buf)?;
+impl Unpack for StakeDelegation {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let account_identifier = <[u8; 32]>::unpack(source)?;
+        let delegation = deserialize_delegation_type(source)?;
         Ok(StakeDelegation {
             account_id: account_identifier.into(),
             delegation,
@@ -116,6 +182,8 @@ impl Readable for StakeDelegation {
     }
 }
 
+chain_ser::impl_legacy_codecs!(StakeDelegation);
+
 impl Payload for StakeDelegation {
     const HAS_DATA: bool = true;
     const HAS_AUTH: bool = true;
@@ -138,6 +206,114 @@ impl Payload for StakeDelegation {
     }
 }
 
+impl Pack for AmountStakeDelegation {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(self.serialize().as_slice())
+    }
+}
+
+// Format is:
+// ACCOUNT_ID (32 bytes) #POOLS (byte) [ AMOUNT (8 bytes) POOL_ID (32 bytes)] (repeated #POOLS time)
+impl Unpack for AmountStakeDelegation {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let account_identifier = <[u8; 32]>::unpack(source)?;
+        let sz = source.take_u8()? as usize;
+        if sz > DELEGATION_AMOUNTS_MAX_DECLS {
+            return Err(ReadError::SizeTooBig {
+                size: sz,
+                limit: DELEGATION_AMOUNTS_MAX_DECLS,
+            });
+        }
+        let mut pools = Vec::with_capacity(sz);
+        for _ in 0..sz {
+            let value = Value(source.take_u64()?);
+            let pool_id = <[u8; 32]>::unpack(source)?.into();
+            pools.push((pool_id, value))
+        }
+        let delegation = DelegationAmounts::new(pools)
+            .ok_or_else(|| ReadError::StructureInvalid("invalid delegation amounts".into()))?;
+        Ok(AmountStakeDelegation {
+            account_id: account_identifier.into(),
+            delegation,
+        })
+    }
+}
+
+chain_ser::impl_legacy_codecs!(AmountStakeDelegation);
+
+impl Payload for AmountStakeDelegation {
+    const HAS_DATA: bool = true;
+    const HAS_AUTH: bool = true;
+    type Auth = AccountBindingSignature;
+    fn payload_data(&self) -> PayloadData<Self> {
+        PayloadData(
+            self.serialize_in(ByteBuilder::new())
+                .finalize_as_vec()
+                .into(),
+            PhantomData,
+        )
+    }
+
+    fn payload_auth_data(auth: &Self::Auth) -> PayloadAuthData<Self> {
+        let bb = auth.serialize_in(ByteBuilder::new()).finalize_as_vec();
+        PayloadAuthData(bb.into(), PhantomData)
+    }
+    fn payload_to_certificate_slice(p: PayloadSlice<'_, Self>) -> Option<CertificateSlice<'_>> {
+        Some(CertificateSlice::from(p))
+    }
+}
+
+impl Pack for RewardsRedelegation {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(self.serialize().as_slice())
+    }
+}
+
+// Format is:
+// ACCOUNT_ID (32 bytes) ENABLED (byte, 0 or 1)
+impl Unpack for RewardsRedelegation {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let account_identifier = <[u8; 32]>::unpack(source)?;
+        let enabled = match source.take_u8()? {
+            0 => false,
+            1 => true,
+            _ => {
+                return Err(ReadError::StructureInvalid(
+                    "invalid rewards redelegation flag".into(),
+                ))
+            }
+        };
+        Ok(RewardsRedelegation {
+            account_id: account_identifier.into(),
+            enabled,
+        })
+    }
+}
+
+chain_ser::impl_legacy_codecs!(RewardsRedelegation);
+
+impl Payload for RewardsRedelegation {
+    const HAS_DATA: bool = true;
+    const HAS_AUTH: bool = true;
+    type Auth = AccountBindingSignature;
+    fn payload_data(&self) -> PayloadData<Self> {
+        PayloadData(
+            self.serialize_in(ByteBuilder::new())
+                .finalize_as_vec()
+                .into(),
+            PhantomData,
+        )
+    }
+
+    fn payload_auth_data(auth: &Self::Auth) -> PayloadAuthData<Self> {
+        let bb = auth.serialize_in(ByteBuilder::new()).finalize_as_vec();
+        PayloadAuthData(bb.into(), PhantomData)
+    }
+    fn payload_to_certificate_slice(p: PayloadSlice<'_, Self>) -> Option<CertificateSlice<'_>> {
+        Some(CertificateSlice::from(p))
+    }
+}
+
 // Format is either:
 // 0 (byte)
 // 1 (byte)     POOL_ID (32 bytes)
@@ -160,31 +336,31 @@ fn serialize_delegation_type(
     }
 }
 
-fn deserialize_delegation_type(buf: &mut ReadBuf) -> Result<DelegationType, ReadError> {
-    let parts = buf.get_u8()?;
+fn deserialize_delegation_type<S: ByteSource>(source: &mut S) -> Result<DelegationType, ReadError> {
+    let parts = source.take_u8()?;
     match parts {
         0 => Ok(DelegationType::NonDelegated),
         1 => {
-            let pool_id = <[u8; 32]>::read(buf)?.into();
+            let pool_id = <[u8; 32]>::unpack(source)?.into();
             Ok(DelegationType::Full(pool_id))
         }
         _ => {
-            let sz = buf.get_u8()?;
+            let sz = source.take_u8()?;
             if sz as usize > DELEGATION_RATIO_MAX_DECLS {
-                return Err(ReadError::SizeTooBig(
-                    sz as usize,
-                    DELEGATION_RATIO_MAX_DECLS,
-                ));
+                return Err(ReadError::SizeTooBig {
+                    size: sz as usize,
+                    limit: DELEGATION_RATIO_MAX_DECLS,
+                });
             }
             let mut pools = Vec::with_capacity(sz as usize);
             for _ in 0..sz {
-                let pool_parts = buf.get_u8()?;
-                let pool_id = <[u8; 32]>::read(buf)?.into();
+                let pool_parts = source.take_u8()?;
+                let pool_id = <[u8; 32]>::unpack(source)?.into();
                 pools.push((pool_id, pool_parts))
             }
             match DelegationRatio::new(parts, pools) {
                 None => Err(ReadError::StructureInvalid(
-                    "invalid delegation ratio".to_string(),
+                    "invalid delegation ratio".into(),
                 )),
                 Some(dr) => Ok(DelegationType::Ratio(dr)),
             }
@@ -1,12 +1,13 @@
 use crate::transaction::{SingleAccountBindingSignature, TransactionBindingAuthData};
 use crate::vote::CommitteeId;
 use crate::{
-    certificate::{CertificateSlice, VotePlanId},
+    certificate::{CertificateSlice, SerializedSize, VotePlanId},
     transaction::{Payload, PayloadAuthData, PayloadData, PayloadSlice},
 };
 use chain_core::{
-    mempack::{ReadBuf, ReadError, Readable},
-    property,
+    mempack::ReadError,
+    packer::Codec,
+    property::{ByteSource, Pack, Unpack},
 };
 use chain_crypto::Verification;
 use typed_bytes::{ByteArray, ByteBuilder};
@@ -17,6 +18,10 @@ pub struct EncryptedVoteTallyProof {
     pub signature: SingleAccountBindingSignature,
 }
 
+/// Start of the private tally of a vote plan, followed by a `VoteTally`
+/// with the decrypted tally. Deprecated from the
+/// `PROTOCOL_VERSION_UNIFIED_VOTE_TALLY` protocol version on, where the
+/// private `VoteTally` alone tallies the vote plan.
 #[derive(Debug, Eq, PartialEq, Hash, Clone)]
 pub struct EncryptedVoteTally {
     id: VotePlanId,
@@ -51,6 +56,12 @@ impl EncryptedVoteTally {
     }
 }
 
+impl SerializedSize for EncryptedVoteTally {
+    fn serialized_size(&self) -> usize {
+        self.serialize_in(ByteBuilder::new()).len()
+    }
+}
+
 /* Auth/Payload ************************************************************* */
 
 impl Payload for EncryptedVoteTally {
@@ -83,25 +94,33 @@ impl Payload for EncryptedVoteTally {
 
 /* Ser/De ******************************************************************* */
 
-impl property::Serialize for EncryptedVoteTally {
-    type Error = std::io::Error;
-    fn serialize<W: std::io::Write>(&self, mut writer: W) -> Result<(), Self::Error> {
-        writer.write_all(self.serialize().as_slice())?;
-        Ok(())
+impl Pack for EncryptedVoteTally {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(self.serialize().as_slice())
+    }
+}
+
+impl Pack for EncryptedVoteTallyProof {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(self.serialize_in(ByteBuilder::new()).finalize().as_slice())
     }
 }
 
-impl Readable for EncryptedVoteTallyProof {
-    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        let id = CommitteeId::read(buf)?;
-        let signature = SingleAccountBindingSignature::read(buf)?;
+impl Unpack for EncryptedVoteTallyProof {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let id = CommitteeId::unpack(source)?;
+        let signature = SingleAccountBindingSignature::unpack(source)?;
         Ok(Self { id, signature })
     }
 }
 
-impl Readable for EncryptedVoteTally {
-    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        let id = <[u8; 32]>::read(buf)?.into();
+chain_ser::impl_legacy_codecs!(EncryptedVoteTallyProof);
+
+impl Unpack for EncryptedVoteTally {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let id = <[u8; 32]>::unpack(source)?.into();
         Ok(Self { id })
     }
 }
+
+chain_ser::impl_legacy_codecs!(EncryptedVoteTally);
@@ -0,0 +1,166 @@
+//! Certificates defined outside of this crate.
+//!
+//! The certificate tags of [`EXTENSION_TAGS`] are reserved to the extensions.
+//! An extension certificate is carried as an opaque payload and opaque
+//! authentication data, both prefixed with their size, so the blocks parse
+//! without knowing the extensions. The extension registered for the tag
+//! decodes and applies the certificate, see
+//! [`CertificateExtensions`](crate::ledger::CertificateExtensions).
+
+use crate::{
+    certificate::{CertificateSlice, SerializedSize},
+    transaction::{Payload, PayloadAuthData, PayloadData, PayloadSlice},
+};
+
+use chain_core::{
+    layout::{DescribeLayout, Field, Int, Layout},
+    mempack::ReadError,
+    packer::Codec,
+    property::{ByteSource, Pack, Unpack},
+};
+use typed_bytes::{ByteArray, ByteBuilder};
+
+use std::marker::PhantomData;
+use std::ops::RangeInclusive;
+
+/// The certificate tags reserved to the extensions
+pub const EXTENSION_TAGS: RangeInclusive<u8> = 0x80..=0xff;
+
+/// A certificate of the extension registered for `tag`, the payload is
+/// encoded by the extension.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ExtensionCertificate {
+    tag: u8,
+    payload: Box<[u8]>,
+}
+
+/// The authentication data of an extension certificate, encoded by the
+/// extension. Empty for the extensions not authenticating their
+/// certificates.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ExtensionCertificateAuth(pub Box<[u8]>);
+
+impl ExtensionCertificate {
+    /// `None` if the tag is not in [`EXTENSION_TAGS`]
+    pub fn new(tag: u8, payload: impl Into<Box<[u8]>>) -> Option<Self> {
+        if EXTENSION_TAGS.contains(&tag) {
+            Some(ExtensionCertificate {
+                tag,
+                payload: payload.into(),
+            })
+        } else {
+            None
+        }
+    }
+
+    pub fn tag(&self) -> u8 {
+        self.tag
+    }
+
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    pub fn serialize_in(&self, bb: ByteBuilder<Self>) -> ByteBuilder<Self> {
+        bb.u8(self.tag)
+            .u32(self.payload.len() as u32)
+            .bytes(&self.payload)
+    }
+
+    pub fn serialize(&self) -> ByteArray<Self> {
+        self.serialize_in(ByteBuilder::new()).finalize()
+    }
+}
+
+impl SerializedSize for ExtensionCertificate {
+    fn serialized_size(&self) -> usize {
+        self.serialize_in(ByteBuilder::new()).len()
+    }
+}
+
+impl ExtensionCertificateAuth {
+    pub fn serialize_in(&self, bb: ByteBuilder<Self>) -> ByteBuilder<Self> {
+        bb.u32(self.0.len() as u32).bytes(&self.0)
+    }
+}
+
+/* Auth/Payload ************************************************************* */
+
+impl Payload for ExtensionCertificate {
+    const HAS_DATA: bool = true;
+    const HAS_AUTH: bool = true;
+    type Auth = ExtensionCertificateAuth;
+
+    fn payload_data(&self) -> PayloadData<Self> {
+        PayloadData(
+            self.serialize_in(ByteBuilder::new())
+                .finalize_as_vec()
+                .into(),
+            PhantomData,
+        )
+    }
+
+    fn payload_auth_data(auth: &Self::Auth) -> PayloadAuthData<Self> {
+        PayloadAuthData(
+            auth.serialize_in(ByteBuilder::new())
+                .finalize_as_vec()
+                .into(),
+            PhantomData,
+        )
+    }
+
+    fn payload_to_certificate_slice(p: PayloadSlice<'_, Self>) -> Option<CertificateSlice<'_>> {
+        Some(CertificateSlice::from(p))
+    }
+}
+
+/* Ser/De ******************************************************************* */
+
+impl Pack for ExtensionCertificate {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(self.serialize().as_slice())
+    }
+}
+
+impl DescribeLayout for ExtensionCertificate {
+    const NAME: &'static str = "Extension certificate";
+
+    fn layout() -> Layout {
+        Layout::Fields(vec![
+            Field::int("tag", Int::U8).describe("in the extension tags, 0x80 to 0xff"),
+            Field::prefixed("payload", Int::U32).describe("encoded by the extension"),
+        ])
+    }
+}
+
+impl Unpack for ExtensionCertificate {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let tag = source.take_u8()?;
+        if !EXTENSION_TAGS.contains(&tag) {
+            return Err(ReadError::UnknownTag {
+                kind: "extension certificate",
+                tag: tag as u32,
+            });
+        }
+        let len = source.take_u32()? as usize;
+        let payload = source.take_vec(len)?.into();
+        Ok(ExtensionCertificate { tag, payload })
+    }
+}
+
+chain_ser::impl_legacy_codecs!(ExtensionCertificate);
+
+impl Pack for ExtensionCertificateAuth {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(self.serialize_in(ByteBuilder::new()).finalize().as_slice())
+    }
+}
+
+impl Unpack for ExtensionCertificateAuth {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let len = source.take_u32()? as usize;
+        Ok(ExtensionCertificateAuth(source.take_vec(len)?.into()))
+    }
+}
+
+chain_ser::impl_legacy_codecs!(ExtensionCertificateAuth);
@@ -1,14 +1,15 @@
 use crate::{
     account::Identifier,
-    certificate::CertificateSlice,
+    certificate::{CertificateSlice, SerializedSize},
     tokens::{minting_policy::MintingPolicy, name::TokenName},
     transaction::{Payload, PayloadAuthData, PayloadData, PayloadSlice},
     value::Value,
 };
 
 use chain_core::{
-    mempack::{ReadBuf, ReadError, Readable},
-    property::Serialize,
+    mempack::ReadError,
+    packer::Codec,
+    property::{ByteSource, Pack, Unpack},
 };
 use typed_bytes::{ByteArray, ByteBuilder};
 
@@ -37,6 +38,12 @@ impl MintToken {
     }
 }
 
+impl SerializedSize for MintToken {
+    fn serialized_size(&self) -> usize {
+        self.serialize_in(ByteBuilder::new()).len()
+    }
+}
+
 impl Payload for MintToken {
     const HAS_DATA: bool = true;
 
@@ -62,22 +69,21 @@ impl Payload for MintToken {
     }
 }
 
-impl Serialize for MintToken {
-    type Error = std::io::Error;
-    fn serialize<W: std::io::Write>(&self, mut writer: W) -> Result<(), Self::Error> {
-        self.name.serialize(&mut writer)?;
-        self.policy.serialize(&mut writer)?;
-        self.to.serialize(&mut writer)?;
-        self.value.serialize(writer)
+impl Pack for MintToken {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        self.name.pack(codec)?;
+        self.policy.pack(codec)?;
+        self.to.pack(codec)?;
+        self.value.pack(codec)
     }
 }
 
-impl Readable for MintToken {
-    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        let name = TokenName::read(buf)?;
-        let policy = MintingPolicy::read(buf)?;
-        let to = Identifier::read(buf)?;
-        let value = Value::read(buf)?;
+impl Unpack for MintToken {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let name = TokenName::unpack(source)?;
+        let policy = MintingPolicy::unpack(source)?;
+        let to = Identifier::unpack(source)?;
+        let value = Value::unpack(source)?;
 
         Ok(Self {
             name,
@@ -88,6 +94,8 @@ impl Readable for MintToken {
     }
 }
 
+chain_ser::impl_legacy_codecs!(MintToken);
+
 #[cfg(any(test, feature = "property-test-api"))]
 mod tests {
     use super::*;
@@ -1,12 +1,17 @@
 mod delegation;
 mod encrypted_vote_tally;
+mod extension;
 mod mint_token;
+mod name;
+mod policy;
 mod pool;
 mod update_proposal;
 mod update_vote;
 mod vote_cast;
 mod vote_plan;
+mod vote_plan_cancel;
 mod vote_tally;
+mod voting_power_snapshot;
 
 #[cfg(any(test, feature = "property-test-api"))]
 mod test;
@@ -14,24 +19,40 @@ mod test;
 use crate::transaction::{Payload, PayloadData, PayloadSlice};
 
 pub use self::encrypted_vote_tally::{EncryptedVoteTally, EncryptedVoteTallyProof};
-pub use self::vote_cast::VoteCast;
+pub use self::vote_cast::{
+    SnapshotVoteCast, VoteCast, VoteCastBatch, VoteCastBatchError, VOTE_CAST_BATCH_MAX_CASTS,
+};
 pub use self::vote_plan::{
-    ExternalProposalDocument, ExternalProposalId, Proposal, Proposals, PushProposal, VoteAction,
-    VotePlan, VotePlanId, VotePlanProof,
+    ExternalProposalDocument, ExternalProposalId, Phase, Proposal, Proposals, PushProposal,
+    VoteAction, VotePlan, VotePlanId, VotePlanProof,
 };
+pub use self::vote_plan_cancel::{VotePlanCancel, VotePlanCancelProof};
 pub use self::vote_tally::{
     DecryptedPrivateTally, DecryptedPrivateTallyError, DecryptedPrivateTallyProposal, TallyProof,
     VoteTally, VoteTallyPayload,
 };
-pub use delegation::{OwnerStakeDelegation, StakeDelegation};
+pub use delegation::{
+    AmountStakeDelegation, OwnerStakeDelegation, RewardsRedelegation, StakeDelegation,
+};
+pub use extension::{ExtensionCertificate, ExtensionCertificateAuth, EXTENSION_TAGS};
 pub use mint_token::MintToken;
+pub use name::{NameClaim, NameRelease};
+pub use policy::{CertificateKind, CertificatePolicy};
 pub use pool::{
+    DelegatorPattern, DelegatorPolicy, DelegatorPolicyError, DelegatorPolicyKind, DelegatorPrefix,
     GenesisPraosLeaderHash, IndexSignatures, ManagementThreshold, PoolId, PoolOwnersSigned,
     PoolPermissions, PoolRegistration, PoolRegistrationHash, PoolRetirement, PoolSignature,
-    PoolUpdate,
+    PoolUpdate, RewardAccount, DELEGATOR_POLICY_MAX_PATTERNS, DELEGATOR_POLICY_VERSION,
 };
 pub use update_proposal::{BftLeaderBindingSignature, UpdateProposal, UpdateProposerId};
 pub use update_vote::{UpdateProposalId, UpdateVote, UpdateVoterId};
+pub use voting_power_snapshot::{VotingPowerSnapshot, VotingPowerSnapshotProof};
+
+/// Size in bytes of the serialized content of a certificate, as written by
+/// its `serialize_in`. The authentication data is not included.
+pub trait SerializedSize {
+    fn serialized_size(&self) -> usize;
+}
 
 pub enum CertificateSlice<'a> {
     StakeDelegation(PayloadSlice<'a, StakeDelegation>),
@@ -46,6 +67,15 @@ pub enum CertificateSlice<'a> {
     UpdateProposal(PayloadSlice<'a, UpdateProposal>),
     UpdateVote(PayloadSlice<'a, UpdateVote>),
     MintToken(PayloadSlice<'a, MintToken>),
+    NameClaim(PayloadSlice<'a, NameClaim>),
+    NameRelease(PayloadSlice<'a, NameRelease>),
+    VotingPowerSnapshot(PayloadSlice<'a, VotingPowerSnapshot>),
+    SnapshotVoteCast(PayloadSlice<'a, SnapshotVoteCast>),
+    VoteCastBatch(PayloadSlice<'a, VoteCastBatch>),
+    AmountStakeDelegation(PayloadSlice<'a, AmountStakeDelegation>),
+    RewardsRedelegation(PayloadSlice<'a, RewardsRedelegation>),
+    Extension(PayloadSlice<'a, ExtensionCertificate>),
+    VotePlanCancel(PayloadSlice<'a, VotePlanCancel>),
 }
 
 impl<'a> From<PayloadSlice<'a, StakeDelegation>> for CertificateSlice<'a> {
@@ -119,6 +149,60 @@ impl<'a> From<PayloadSlice<'a, MintToken>> for CertificateSlice<'a> {
     }
 }
 
+impl<'a> From<PayloadSlice<'a, NameClaim>> for CertificateSlice<'a> {
+    fn from(payload: PayloadSlice<'a, NameClaim>) -> CertificateSlice<'a> {
+        CertificateSlice::NameClaim(payload)
+    }
+}
+
+impl<'a> From<PayloadSlice<'a, NameRelease>> for CertificateSlice<'a> {
+    fn from(payload: PayloadSlice<'a, NameRelease>) -> CertificateSlice<'a> {
+        CertificateSlice::NameRelease(payload)
+    }
+}
+
+impl<'a> From<PayloadSlice<'a, VotingPowerSnapshot>> for CertificateSlice<'a> {
+    fn from(payload: PayloadSlice<'a, VotingPowerSnapshot>) -> CertificateSlice<'a> {
+        CertificateSlice::VotingPowerSnapshot(payload)
+    }
+}
+
+impl<'a> From<PayloadSlice<'a, SnapshotVoteCast>> for CertificateSlice<'a> {
+    fn from(payload: PayloadSlice<'a, SnapshotVoteCast>) -> CertificateSlice<'a> {
+        CertificateSlice::SnapshotVoteCast(payload)
+    }
+}
+
+impl<'a> From<PayloadSlice<'a, VoteCastBatch>> for CertificateSlice<'a> {
+    fn from(payload: PayloadSlice<'a, VoteCastBatch>) -> CertificateSlice<'a> {
+        CertificateSlice::VoteCastBatch(payload)
+    }
+}
+
+impl<'a> From<PayloadSlice<'a, AmountStakeDelegation>> for CertificateSlice<'a> {
+    fn from(payload: PayloadSlice<'a, AmountStakeDelegation>) -> CertificateSlice<'a> {
+        CertificateSlice::AmountStakeDelegation(payload)
+    }
+}
+
+impl<'a> From<PayloadSlice<'a, RewardsRedelegation>> for CertificateSlice<'a> {
+    fn from(payload: PayloadSlice<'a, RewardsRedelegation>) -> CertificateSlice<'a> {
+        CertificateSlice::RewardsRedelegation(payload)
+    }
+}
+
+impl<'a> From<PayloadSlice<'a, ExtensionCertificate>> for CertificateSlice<'a> {
+    fn from(payload: PayloadSlice<'a, ExtensionCertificate>) -> CertificateSlice<'a> {
+        CertificateSlice::Extension(payload)
+    }
+}
+
+impl<'a> From<PayloadSlice<'a, VotePlanCancel>> for CertificateSlice<'a> {
+    fn from(payload: PayloadSlice<'a, VotePlanCancel>) -> CertificateSlice<'a> {
+        CertificateSlice::VotePlanCancel(payload)
+    }
+}
+
 impl<'a> CertificateSlice<'a> {
     pub fn into_owned(self) -> Certificate {
         match self {
@@ -140,6 +224,51 @@ impl<'a> CertificateSlice<'a> {
             CertificateSlice::UpdateProposal(c) => Certificate::UpdateProposal(c.into_payload()),
             CertificateSlice::UpdateVote(c) => Certificate::UpdateVote(c.into_payload()),
             CertificateSlice::MintToken(c) => Certificate::MintToken(c.into_payload()),
+            CertificateSlice::NameClaim(c) => Certificate::NameClaim(c.into_payload()),
+            CertificateSlice::NameRelease(c) => Certificate::NameRelease(c.into_payload()),
+            CertificateSlice::VotingPowerSnapshot(c) => {
+                Certificate::VotingPowerSnapshot(c.into_payload())
+            }
+            CertificateSlice::SnapshotVoteCast(c) => {
+                Certificate::SnapshotVoteCast(c.into_payload())
+            }
+            CertificateSlice::VoteCastBatch(c) => Certificate::VoteCastBatch(c.into_payload()),
+            CertificateSlice::AmountStakeDelegation(c) => {
+                Certificate::AmountStakeDelegation(c.into_payload())
+            }
+            CertificateSlice::RewardsRedelegation(c) => {
+                Certificate::RewardsRedelegation(c.into_payload())
+            }
+            CertificateSlice::Extension(c) => Certificate::Extension(c.into_payload()),
+            CertificateSlice::VotePlanCancel(c) => Certificate::VotePlanCancel(c.into_payload()),
+        }
+    }
+}
+
+impl<'a> SerializedSize for CertificateSlice<'a> {
+    fn serialized_size(&self) -> usize {
+        match self {
+            CertificateSlice::StakeDelegation(c) => c.as_bytes().len(),
+            CertificateSlice::OwnerStakeDelegation(c) => c.as_bytes().len(),
+            CertificateSlice::PoolRegistration(c) => c.as_bytes().len(),
+            CertificateSlice::PoolRetirement(c) => c.as_bytes().len(),
+            CertificateSlice::PoolUpdate(c) => c.as_bytes().len(),
+            CertificateSlice::VotePlan(c) => c.as_bytes().len(),
+            CertificateSlice::VoteCast(c) => c.as_bytes().len(),
+            CertificateSlice::VoteTally(c) => c.as_bytes().len(),
+            CertificateSlice::EncryptedVoteTally(c) => c.as_bytes().len(),
+            CertificateSlice::UpdateProposal(c) => c.as_bytes().len(),
+            CertificateSlice::UpdateVote(c) => c.as_bytes().len(),
+            CertificateSlice::MintToken(c) => c.as_bytes().len(),
+            CertificateSlice::NameClaim(c) => c.as_bytes().len(),
+            CertificateSlice::NameRelease(c) => c.as_bytes().len(),
+            CertificateSlice::VotingPowerSnapshot(c) => c.as_bytes().len(),
+            CertificateSlice::SnapshotVoteCast(c) => c.as_bytes().len(),
+            CertificateSlice::VoteCastBatch(c) => c.as_bytes().len(),
+            CertificateSlice::AmountStakeDelegation(c) => c.as_bytes().len(),
+            CertificateSlice::RewardsRedelegation(c) => c.as_bytes().len(),
+            CertificateSlice::Extension(c) => c.as_bytes().len(),
+            CertificateSlice::VotePlanCancel(c) => c.as_bytes().len(),
         }
     }
 }
@@ -158,6 +287,15 @@ pub enum CertificatePayload {
     UpdateProposal(PayloadData<UpdateProposal>),
     UpdateVote(PayloadData<UpdateVote>),
     MintToken(PayloadData<MintToken>),
+    NameClaim(PayloadData<NameClaim>),
+    NameRelease(PayloadData<NameRelease>),
+    VotingPowerSnapshot(PayloadData<VotingPowerSnapshot>),
+    SnapshotVoteCast(PayloadData<SnapshotVoteCast>),
+    VoteCastBatch(PayloadData<VoteCastBatch>),
+    AmountStakeDelegation(PayloadData<AmountStakeDelegation>),
+    RewardsRedelegation(PayloadData<RewardsRedelegation>),
+    Extension(PayloadData<ExtensionCertificate>),
+    VotePlanCancel(PayloadData<VotePlanCancel>),
 }
 
 impl CertificatePayload {
@@ -175,6 +313,15 @@ impl CertificatePayload {
             CertificatePayload::UpdateProposal(payload) => payload.borrow().into(),
             CertificatePayload::UpdateVote(payload) => payload.borrow().into(),
             CertificatePayload::MintToken(payload) => payload.borrow().into(),
+            CertificatePayload::NameClaim(payload) => payload.borrow().into(),
+            CertificatePayload::NameRelease(payload) => payload.borrow().into(),
+            CertificatePayload::VotingPowerSnapshot(payload) => payload.borrow().into(),
+            CertificatePayload::SnapshotVoteCast(payload) => payload.borrow().into(),
+            CertificatePayload::VoteCastBatch(payload) => payload.borrow().into(),
+            CertificatePayload::AmountStakeDelegation(payload) => payload.borrow().into(),
+            CertificatePayload::RewardsRedelegation(payload) => payload.borrow().into(),
+            CertificatePayload::Extension(payload) => payload.borrow().into(),
+            CertificatePayload::VotePlanCancel(payload) => payload.borrow().into(),
         }
     }
 }
@@ -214,6 +361,33 @@ impl<'a> From<&'a Certificate> for CertificatePayload {
             Certificate::MintToken(payload) => {
                 CertificatePayload::MintToken(payload.payload_data())
             }
+            Certificate::NameClaim(payload) => {
+                CertificatePayload::NameClaim(payload.payload_data())
+            }
+            Certificate::NameRelease(payload) => {
+                CertificatePayload::NameRelease(payload.payload_data())
+            }
+            Certificate::VotingPowerSnapshot(payload) => {
+                CertificatePayload::VotingPowerSnapshot(payload.payload_data())
+            }
+            Certificate::SnapshotVoteCast(payload) => {
+                CertificatePayload::SnapshotVoteCast(payload.payload_data())
+            }
+            Certificate::VoteCastBatch(payload) => {
+                CertificatePayload::VoteCastBatch(payload.payload_data())
+            }
+            Certificate::AmountStakeDelegation(payload) => {
+                CertificatePayload::AmountStakeDelegation(payload.payload_data())
+            }
+            Certificate::RewardsRedelegation(payload) => {
+                CertificatePayload::RewardsRedelegation(payload.payload_data())
+            }
+            Certificate::Extension(payload) => {
+                CertificatePayload::Extension(payload.payload_data())
+            }
+            Certificate::VotePlanCancel(payload) => {
+                CertificatePayload::VotePlanCancel(payload.payload_data())
+            }
         }
     }
 }
@@ -233,6 +407,43 @@ pub enum Certificate {
     UpdateProposal(UpdateProposal),
     UpdateVote(UpdateVote),
     MintToken(MintToken),
+    NameClaim(NameClaim),
+    NameRelease(NameRelease),
+    VotingPowerSnapshot(VotingPowerSnapshot),
+    SnapshotVoteCast(SnapshotVoteCast),
+    VoteCastBatch(VoteCastBatch),
+    AmountStakeDelegation(AmountStakeDelegation),
+    RewardsRedelegation(RewardsRedelegation),
+    Extension(ExtensionCertificate),
+    VotePlanCancel(VotePlanCancel),
+}
+
+impl SerializedSize for Certificate {
+    fn serialized_size(&self) -> usize {
+        match self {
+            Certificate::StakeDelegation(c) => c.serialized_size(),
+            Certificate::OwnerStakeDelegation(c) => c.serialized_size(),
+            Certificate::PoolRegistration(c) => c.serialized_size(),
+            Certificate::PoolRetirement(c) => c.serialized_size(),
+            Certificate::PoolUpdate(c) => c.serialized_size(),
+            Certificate::VotePlan(c) => c.serialized_size(),
+            Certificate::VoteCast(c) => c.serialized_size(),
+            Certificate::VoteTally(c) => c.serialized_size(),
+            Certificate::EncryptedVoteTally(c) => c.serialized_size(),
+            Certificate::UpdateProposal(c) => c.serialized_size(),
+            Certificate::UpdateVote(c) => c.serialized_size(),
+            Certificate::MintToken(c) => c.serialized_size(),
+            Certificate::NameClaim(c) => c.serialized_size(),
+            Certificate::NameRelease(c) => c.serialized_size(),
+            Certificate::VotingPowerSnapshot(c) => c.serialized_size(),
+            Certificate::SnapshotVoteCast(c) => c.serialized_size(),
+            Certificate::VoteCastBatch(c) => c.serialized_size(),
+            Certificate::AmountStakeDelegation(c) => c.serialized_size(),
+            Certificate::RewardsRedelegation(c) => c.serialized_size(),
+            Certificate::Extension(c) => c.serialized_size(),
+            Certificate::VotePlanCancel(c) => c.serialized_size(),
+        }
+    }
 }
 
 impl From<StakeDelegation> for Certificate {
@@ -307,6 +518,60 @@ impl From<MintToken> for Certificate {
     }
 }
 
+impl From<NameClaim> for Certificate {
+    fn from(name_claim: NameClaim) -> Self {
+        Self::NameClaim(name_claim)
+    }
+}
+
+impl From<NameRelease> for Certificate {
+    fn from(name_release: NameRelease) -> Self {
+        Self::NameRelease(name_release)
+    }
+}
+
+impl From<VotingPowerSnapshot> for Certificate {
+    fn from(snapshot: VotingPowerSnapshot) -> Self {
+        Self::VotingPowerSnapshot(snapshot)
+    }
+}
+
+impl From<SnapshotVoteCast> for Certificate {
+    fn from(vote_cast: SnapshotVoteCast) -> Self {
+        Self::SnapshotVoteCast(vote_cast)
+    }
+}
+
+impl From<VoteCastBatch> for Certificate {
+    fn from(batch: VoteCastBatch) -> Self {
+        Self::VoteCastBatch(batch)
+    }
+}
+
+impl From<AmountStakeDelegation> for Certificate {
+    fn from(cert: AmountStakeDelegation) -> Certificate {
+        Certificate::AmountStakeDelegation(cert)
+    }
+}
+
+impl From<RewardsRedelegation> for Certificate {
+    fn from(cert: RewardsRedelegation) -> Certificate {
+        Certificate::RewardsRedelegation(cert)
+    }
+}
+
+impl From<ExtensionCertificate> for Certificate {
+    fn from(cert: ExtensionCertificate) -> Certificate {
+        Certificate::Extension(cert)
+    }
+}
+
+impl From<VotePlanCancel> for Certificate {
+    fn from(cancel: VotePlanCancel) -> Certificate {
+        Certificate::VotePlanCancel(cancel)
+    }
+}
+
 impl Certificate {
     pub fn need_auth(&self) -> bool {
         match self {
@@ -322,6 +587,15 @@ impl Certificate {
             Certificate::UpdateProposal(_) => <UpdateProposal as Payload>::HAS_AUTH,
             Certificate::UpdateVote(_) => <UpdateVote as Payload>::HAS_AUTH,
             Certificate::MintToken(_) => <MintToken as Payload>::HAS_AUTH,
+            Certificate::NameClaim(_) => <NameClaim as Payload>::HAS_AUTH,
+            Certificate::NameRelease(_) => <NameRelease as Payload>::HAS_AUTH,
+            Certificate::VotingPowerSnapshot(_) => <VotingPowerSnapshot as Payload>::HAS_AUTH,
+            Certificate::SnapshotVoteCast(_) => <SnapshotVoteCast as Payload>::HAS_AUTH,
+            Certificate::VoteCastBatch(_) => <VoteCastBatch as Payload>::HAS_AUTH,
+            Certificate::AmountStakeDelegation(_) => <AmountStakeDelegation as Payload>::HAS_AUTH,
+            Certificate::RewardsRedelegation(_) => <RewardsRedelegation as Payload>::HAS_AUTH,
+            Certificate::Extension(_) => <ExtensionCertificate as Payload>::HAS_AUTH,
+            Certificate::VotePlanCancel(_) => <VotePlanCancel as Payload>::HAS_AUTH,
         }
     }
 }
@@ -365,7 +639,22 @@ mod tests {
             Certificate::UpdateProposal(_) => true,
             Certificate::UpdateVote(_) => true,
             Certificate::MintToken(_) => false,
+            Certificate::NameClaim(_) => false,
+            Certificate::NameRelease(_) => false,
+            Certificate::VotingPowerSnapshot(_) => true,
+            Certificate::SnapshotVoteCast(_) => false,
+            Certificate::VoteCastBatch(_) => false,
+            Certificate::AmountStakeDelegation(_) => true,
+            Certificate::RewardsRedelegation(_) => true,
+            Certificate::Extension(_) => true,
+            Certificate::VotePlanCancel(_) => true,
         };
         TestResult::from_bool(certificate.need_auth() == expected_result)
     }
+
+    #[quickcheck]
+    pub fn serialized_size_matches_payload(certificate: Certificate) -> bool {
+        let payload = CertificatePayload::from(&certificate);
+        certificate.serialized_size() == payload.as_slice().serialized_size()
+    }
 }
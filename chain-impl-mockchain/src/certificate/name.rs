@@ -0,0 +1,180 @@
+use crate::{
+    certificate::{CertificateSlice, SerializedSize},
+    names::Name,
+    transaction::{Payload, PayloadAuthData, PayloadData, PayloadSlice},
+};
+
+use chain_core::{
+    mempack::ReadError,
+    packer::Codec,
+    property::{ByteSource, Pack, Unpack},
+};
+use typed_bytes::{ByteArray, ByteBuilder};
+
+use std::marker::PhantomData;
+
+/// Claim (or renew) a name for the account of the single input of the
+/// transaction carrying the certificate.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NameClaim {
+    pub name: Name,
+}
+
+/// Release a name claimed by the account of the single input of the
+/// transaction carrying the certificate.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NameRelease {
+    pub name: Name,
+}
+
+impl NameClaim {
+    pub fn new(name: Name) -> Self {
+        Self { name }
+    }
+
+    pub fn serialize_in(&self, bb: ByteBuilder<Self>) -> ByteBuilder<Self> {
+        let name = self.name.as_str().as_bytes();
+        bb.u8(name.len() as u8).bytes(name)
+    }
+
+    pub fn serialize(&self) -> ByteArray<Self> {
+        self.serialize_in(ByteBuilder::new()).finalize()
+    }
+}
+
+impl SerializedSize for NameClaim {
+    fn serialized_size(&self) -> usize {
+        self.serialize_in(ByteBuilder::new()).len()
+    }
+}
+
+impl NameRelease {
+    pub fn new(name: Name) -> Self {
+        Self { name }
+    }
+
+    pub fn serialize_in(&self, bb: ByteBuilder<Self>) -> ByteBuilder<Self> {
+        let name = self.name.as_str().as_bytes();
+        bb.u8(name.len() as u8).bytes(name)
+    }
+
+    pub fn serialize(&self) -> ByteArray<Self> {
+        self.serialize_in(ByteBuilder::new()).finalize()
+    }
+}
+
+impl SerializedSize for NameRelease {
+    fn serialized_size(&self) -> usize {
+        self.serialize_in(ByteBuilder::new()).len()
+    }
+}
+
+/* Auth/Payload ************************************************************* */
+
+impl Payload for NameClaim {
+    const HAS_DATA: bool = true;
+    const HAS_AUTH: bool = false;
+    type Auth = ();
+
+    fn payload_data(&self) -> PayloadData<Self> {
+        PayloadData(
+            self.serialize_in(ByteBuilder::new())
+                .finalize_as_vec()
+                .into(),
+            PhantomData,
+        )
+    }
+
+    fn payload_auth_data(_: &Self::Auth) -> PayloadAuthData<Self> {
+        PayloadAuthData(Box::new([]), PhantomData)
+    }
+
+    fn payload_to_certificate_slice(p: PayloadSlice<'_, Self>) -> Option<CertificateSlice<'_>> {
+        Some(CertificateSlice::from(p))
+    }
+}
+
+impl Payload for NameRelease {
+    const HAS_DATA: bool = true;
+    const HAS_AUTH: bool = false;
+    type Auth = ();
+
+    fn payload_data(&self) -> PayloadData<Self> {
+        PayloadData(
+            self.serialize_in(ByteBuilder::new())
+                .finalize_as_vec()
+                .into(),
+            PhantomData,
+        )
+    }
+
+    fn payload_auth_data(_: &Self::Auth) -> PayloadAuthData<Self> {
+        PayloadAuthData(Box::new([]), PhantomData)
+    }
+
+    fn payload_to_certificate_slice(p: PayloadSlice<'_, Self>) -> Option<CertificateSlice<'_>> {
+        Some(CertificateSlice::from(p))
+    }
+}
+
+/* Ser/De ******************************************************************* */
+
+impl Pack for NameClaim {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        self.name.pack(codec)
+    }
+}
+
+impl Unpack for NameClaim {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        Name::unpack(source).map(Self::new)
+    }
+}
+
+chain_ser::impl_legacy_codecs!(NameClaim);
+
+impl Pack for NameRelease {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        self.name.pack(codec)
+    }
+}
+
+impl Unpack for NameRelease {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        Name::unpack(source).map(Self::new)
+    }
+}
+
+chain_ser::impl_legacy_codecs!(NameRelease);
+
+#[cfg(any(test, feature = "property-test-api"))]
+mod tests {
+    use super::*;
+    #[cfg(test)]
+    use crate::testing::serialization::serialization_bijection_r;
+    #[cfg(test)]
+    use quickcheck::TestResult;
+    use quickcheck::{Arbitrary, Gen};
+
+    impl Arbitrary for NameClaim {
+        fn arbitrary<G: Gen>(g: &mut G) -> Self {
+            Self::new(Arbitrary::arbitrary(g))
+        }
+    }
+
+    impl Arbitrary for NameRelease {
+        fn arbitrary<G: Gen>(g: &mut G) -> Self {
+            Self::new(Arbitrary::arbitrary(g))
+        }
+    }
+
+    quickcheck! {
+        fn name_claim_serialization_bijection(b: NameClaim) -> TestResult {
+            serialization_bijection_r(b)
+        }
+
+        fn name_release_serialization_bijection(b: NameRelease) -> TestResult {
+            serialization_bijection_r(b)
+        }
+    }
+}
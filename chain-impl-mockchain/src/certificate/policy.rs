@@ -0,0 +1,193 @@
+use super::Certificate;
+use std::fmt::{self, Display, Formatter};
+
+/// The kinds of certificates, as they are enabled or disabled by a
+/// [`CertificatePolicy`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CertificateKind {
+    StakeDelegation = 0,
+    OwnerStakeDelegation = 1,
+    PoolRegistration = 2,
+    PoolRetirement = 3,
+    PoolUpdate = 4,
+    VotePlan = 5,
+    VoteCast = 6,
+    VoteTally = 7,
+    EncryptedVoteTally = 8,
+    UpdateProposal = 9,
+    UpdateVote = 10,
+    MintToken = 11,
+    NameClaim = 12,
+    NameRelease = 13,
+    VotingPowerSnapshot = 14,
+    SnapshotVoteCast = 15,
+    VoteCastBatch = 16,
+    AmountStakeDelegation = 17,
+    RewardsRedelegation = 18,
+    Extension = 19,
+    VotePlanCancel = 20,
+}
+
+impl CertificateKind {
+    pub const ALL: [CertificateKind; 21] = [
+        CertificateKind::StakeDelegation,
+        CertificateKind::OwnerStakeDelegation,
+        CertificateKind::PoolRegistration,
+        CertificateKind::PoolRetirement,
+        CertificateKind::PoolUpdate,
+        CertificateKind::VotePlan,
+        CertificateKind::VoteCast,
+        CertificateKind::VoteTally,
+        CertificateKind::EncryptedVoteTally,
+        CertificateKind::UpdateProposal,
+        CertificateKind::UpdateVote,
+        CertificateKind::MintToken,
+        CertificateKind::NameClaim,
+        CertificateKind::NameRelease,
+        CertificateKind::VotingPowerSnapshot,
+        CertificateKind::SnapshotVoteCast,
+        CertificateKind::VoteCastBatch,
+        CertificateKind::AmountStakeDelegation,
+        CertificateKind::RewardsRedelegation,
+        CertificateKind::Extension,
+        CertificateKind::VotePlanCancel,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CertificateKind::StakeDelegation => "stake delegation",
+            CertificateKind::OwnerStakeDelegation => "owner stake delegation",
+            CertificateKind::PoolRegistration => "pool registration",
+            CertificateKind::PoolRetirement => "pool retirement",
+            CertificateKind::PoolUpdate => "pool update",
+            CertificateKind::VotePlan => "vote plan",
+            CertificateKind::VoteCast => "vote cast",
+            CertificateKind::VoteTally => "vote tally",
+            CertificateKind::EncryptedVoteTally => "encrypted vote tally",
+            CertificateKind::UpdateProposal => "update proposal",
+            CertificateKind::UpdateVote => "update vote",
+            CertificateKind::MintToken => "mint token",
+            CertificateKind::NameClaim => "name claim",
+            CertificateKind::NameRelease => "name release",
+            CertificateKind::VotingPowerSnapshot => "voting power snapshot",
+            CertificateKind::SnapshotVoteCast => "snapshot vote cast",
+            CertificateKind::VoteCastBatch => "vote cast batch",
+            CertificateKind::AmountStakeDelegation => "amount stake delegation",
+            CertificateKind::RewardsRedelegation => "rewards redelegation",
+            CertificateKind::Extension => "extension",
+            CertificateKind::VotePlanCancel => "vote plan cancel",
+        }
+    }
+
+    fn bit(self) -> u32 {
+        1 << self as u32
+    }
+}
+
+impl Display for CertificateKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl Certificate {
+    pub fn kind(&self) -> CertificateKind {
+        match self {
+            Certificate::StakeDelegation(_) => CertificateKind::StakeDelegation,
+            Certificate::OwnerStakeDelegation(_) => CertificateKind::OwnerStakeDelegation,
+            Certificate::PoolRegistration(_) => CertificateKind::PoolRegistration,
+            Certificate::PoolRetirement(_) => CertificateKind::PoolRetirement,
+            Certificate::PoolUpdate(_) => CertificateKind::PoolUpdate,
+            Certificate::VotePlan(_) => CertificateKind::VotePlan,
+            Certificate::VoteCast(_) => CertificateKind::VoteCast,
+            Certificate::VoteTally(_) => CertificateKind::VoteTally,
+            Certificate::EncryptedVoteTally(_) => CertificateKind::EncryptedVoteTally,
+            Certificate::UpdateProposal(_) => CertificateKind::UpdateProposal,
+            Certificate::UpdateVote(_) => CertificateKind::UpdateVote,
+            Certificate::MintToken(_) => CertificateKind::MintToken,
+            Certificate::NameClaim(_) => CertificateKind::NameClaim,
+            Certificate::NameRelease(_) => CertificateKind::NameRelease,
+            Certificate::VotingPowerSnapshot(_) => CertificateKind::VotingPowerSnapshot,
+            Certificate::SnapshotVoteCast(_) => CertificateKind::SnapshotVoteCast,
+            Certificate::VoteCastBatch(_) => CertificateKind::VoteCastBatch,
+            Certificate::AmountStakeDelegation(_) => CertificateKind::AmountStakeDelegation,
+            Certificate::RewardsRedelegation(_) => CertificateKind::RewardsRedelegation,
+            Certificate::Extension(_) => CertificateKind::Extension,
+            Certificate::VotePlanCancel(_) => CertificateKind::VotePlanCancel,
+        }
+    }
+}
+
+/// The certificate kinds disabled on a chain, every kind is enabled by
+/// default.
+///
+/// The policy is encoded as a bitmask of the disabled kinds, the bit `n`
+/// standing for the kind of discriminant `n`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CertificatePolicy(u32);
+
+impl CertificatePolicy {
+    const KNOWN_KINDS: u32 = (1 << CertificateKind::ALL.len()) - 1;
+
+    pub fn allow_all() -> Self {
+        CertificatePolicy(0)
+    }
+
+    pub fn disable(self, kind: CertificateKind) -> Self {
+        CertificatePolicy(self.0 | kind.bit())
+    }
+
+    pub fn enable(self, kind: CertificateKind) -> Self {
+        CertificatePolicy(self.0 & !kind.bit())
+    }
+
+    pub fn is_enabled(&self, kind: CertificateKind) -> bool {
+        self.0 & kind.bit() == 0
+    }
+
+    pub fn disabled(&self) -> impl Iterator<Item = CertificateKind> + '_ {
+        CertificateKind::ALL
+            .into_iter()
+            .filter(move |kind| !self.is_enabled(*kind))
+    }
+
+    pub fn to_bits(self) -> u32 {
+        self.0
+    }
+
+    /// `None` if a bit does not stand for a known certificate kind
+    pub fn from_bits(bits: u32) -> Option<Self> {
+        if bits & !Self::KNOWN_KINDS == 0 {
+            Some(CertificatePolicy(bits))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn policy_bits_roundtrip() {
+        let policy = CertificatePolicy::allow_all()
+            .disable(CertificateKind::PoolRegistration)
+            .disable(CertificateKind::AmountStakeDelegation);
+        assert!(!policy.is_enabled(CertificateKind::PoolRegistration));
+        assert!(policy.is_enabled(CertificateKind::VoteCast));
+        assert_eq!(
+            policy.disabled().collect::<Vec<_>>(),
+            vec![
+                CertificateKind::PoolRegistration,
+                CertificateKind::AmountStakeDelegation
+            ]
+        );
+        assert_eq!(CertificatePolicy::from_bits(policy.to_bits()), Some(policy));
+        assert_eq!(CertificatePolicy::from_bits(1 << 21), None);
+        assert_eq!(
+            policy.enable(CertificateKind::PoolRegistration),
+            CertificatePolicy::allow_all().disable(CertificateKind::AmountStakeDelegation)
+        );
+    }
+}
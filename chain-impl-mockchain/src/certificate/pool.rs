@@ -1,17 +1,24 @@
-use super::CertificateSlice;
-use crate::key::{deserialize_public_key, deserialize_signature, GenesisPraosLeader};
+use super::{CertificateSlice, SerializedSize};
+use crate::account;
+use crate::key::{unpack_public_key, unpack_signature, GenesisPraosLeader};
 use crate::rewards::TaxType;
 use crate::transaction::{
     AccountIdentifier, Payload, PayloadAuthData, PayloadData, PayloadSlice,
     SingleAccountBindingSignature, TransactionBindingAuthData,
 };
+use crate::value::Value;
+use chain_addr::{Address, Kind};
 use chain_core::{
-    mempack::{ReadBuf, ReadError, Readable},
-    property,
+    mempack::ReadError,
+    packer::Codec,
+    property::{ByteSource, Pack, Unpack},
+};
+use chain_crypto::{
+    digest::DigestOf, AsymmetricPublicKey, Blake2b256, Ed25519, PublicKey, Verification,
 };
-use chain_crypto::{digest::DigestOf, Blake2b256, Ed25519, PublicKey, Verification};
 use chain_time::{DurationSeconds, TimeOffsetSeconds};
 use std::marker::PhantomData;
+use thiserror::Error;
 use typed_bytes::{ByteArray, ByteBuilder};
 
 /// Pool ID
@@ -44,10 +51,106 @@ pub struct PoolRegistration {
     pub operators: Box<[PublicKey<Ed25519>]>,
     /// Rewarding
     pub rewards: TaxType,
-    /// Reward account
-    pub reward_account: Option<AccountIdentifier>,
+    /// Reward account, the owners share the rewards if None
+    pub reward_account: Option<RewardAccount>,
+    /// Stake the owners commit to delegate to the pool, the pool doesn't
+    /// get any reward for the epochs where the owners' stake is below it
+    pub pledge: Value,
     /// Genesis Praos keys
     pub keys: GenesisPraosLeader,
+    /// Accounts which can delegate to the pool, any account if None
+    pub delegator_policy: Option<DelegatorPolicy>,
+}
+
+/// Where the rewards of the owners of a pool are paid
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RewardAccount {
+    Account(AccountIdentifier),
+    /// an address of the same discrimination as the chain, which is not a
+    /// script address: the rewards are credited to the account of an account
+    /// or multisig address, and are paid as a new unspent output to a single
+    /// or group address
+    Address(Address),
+}
+
+impl RewardAccount {
+    /// the single account credited with the rewards, if any
+    pub fn single_account(&self) -> Option<account::Identifier> {
+        match self {
+            RewardAccount::Account(AccountIdentifier::Single(account)) => Some(account.clone()),
+            RewardAccount::Account(AccountIdentifier::Multi(_)) => None,
+            RewardAccount::Address(address) => match address.kind() {
+                Kind::Account(pk) => Some(pk.clone().into()),
+                _ => None,
+            },
+        }
+    }
+}
+
+impl From<AccountIdentifier> for RewardAccount {
+    fn from(account: AccountIdentifier) -> Self {
+        RewardAccount::Account(account)
+    }
+}
+
+/// Flag of the reward account tag of a registration, set if the pledge of
+/// the pool follows the reward account. A registration without a pledge
+/// keeps the encoding, and so the id, it had before the pledges.
+const PLEDGE_FLAG: u8 = 0x80;
+
+/// Flag of the reward account tag of a registration, set if the delegator
+/// policy of the pool follows the pledge, see [`PLEDGE_FLAG`]
+const DELEGATOR_POLICY_FLAG: u8 = 0x40;
+
+/// Version of the delegator policy encoding, written before the policy when
+/// the registration has one
+pub const DELEGATOR_POLICY_VERSION: u8 = 1;
+
+/// Maximum number of patterns of a delegator policy
+pub const DELEGATOR_POLICY_MAX_PATTERNS: usize = 64;
+
+/// Restriction of the accounts delegating to a pool. The stake of the
+/// accounts which are not allowed is excluded from the stake of the pool.
+/// The owners of the pool are always allowed.
+///
+/// A policy has at most [`DELEGATOR_POLICY_MAX_PATTERNS`] patterns, so it
+/// can always be serialized in a registration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DelegatorPolicy {
+    kind: DelegatorPolicyKind,
+    patterns: Box<[DelegatorPattern]>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DelegatorPolicyKind {
+    /// only the matching accounts can delegate to the pool
+    Allow,
+    /// the matching accounts cannot delegate to the pool
+    Deny,
+}
+
+/// Accounts matched by a delegator policy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DelegatorPattern {
+    Account(account::Identifier),
+    /// the accounts whose public key starts with the prefix
+    Prefix(DelegatorPrefix),
+}
+
+/// Prefix of the public key of the accounts matched by a delegator
+/// pattern, not empty and shorter than a public key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DelegatorPrefix(Box<[u8]>);
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum DelegatorPolicyError {
+    #[error("delegator prefix of {0} bytes, expected between 1 and {} bytes", Ed25519::PUBLIC_KEY_SIZE - 1)]
+    InvalidPrefixLength(usize),
+    #[error(
+        "delegator policy of {0} patterns, expected at most {}",
+        DELEGATOR_POLICY_MAX_PATTERNS
+    )]
+    TooManyPatterns(usize),
 }
 
 /// Permission system related to the pool
@@ -110,6 +213,143 @@ pub struct PoolOwnersSignature {
 
 pub type PoolOwnersSigned = PoolOwnersSignature;
 
+impl DelegatorPrefix {
+    pub fn new(prefix: impl Into<Box<[u8]>>) -> Result<Self, DelegatorPolicyError> {
+        let prefix = prefix.into();
+        if prefix.is_empty() || prefix.len() >= Ed25519::PUBLIC_KEY_SIZE {
+            return Err(DelegatorPolicyError::InvalidPrefixLength(prefix.len()));
+        }
+        Ok(DelegatorPrefix(prefix))
+    }
+}
+
+impl AsRef<[u8]> for DelegatorPrefix {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl DelegatorPattern {
+    pub fn matches(&self, account: &account::Identifier) -> bool {
+        match self {
+            DelegatorPattern::Account(id) => id == account,
+            DelegatorPattern::Prefix(prefix) => {
+                account.as_ref().as_ref().starts_with(prefix.as_ref())
+            }
+        }
+    }
+
+    fn serialize_in(&self, bb: ByteBuilder<PoolRegistration>) -> ByteBuilder<PoolRegistration> {
+        match self {
+            DelegatorPattern::Account(id) => bb.u8(0).bytes(id.as_ref().as_ref()),
+            DelegatorPattern::Prefix(prefix) => bb.u8(1).u8(prefix.0.len() as u8).bytes(&prefix.0),
+        }
+    }
+
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        match source.take_u8()? {
+            0 => Ok(DelegatorPattern::Account(unpack_public_key(source)?.into())),
+            1 => {
+                let len = source.take_u8()? as usize;
+                if len == 0 || len >= Ed25519::PUBLIC_KEY_SIZE {
+                    return Err(ReadError::StructureInvalid(
+                        "invalid delegator prefix length".into(),
+                    ));
+                }
+                Ok(DelegatorPattern::Prefix(DelegatorPrefix(
+                    source.take_vec(len)?.into(),
+                )))
+            }
+            n => Err(ReadError::UnknownTag {
+                kind: "delegator pattern",
+                tag: n as u32,
+            }),
+        }
+    }
+}
+
+impl DelegatorPolicy {
+    pub fn new(
+        kind: DelegatorPolicyKind,
+        patterns: impl Into<Box<[DelegatorPattern]>>,
+    ) -> Result<Self, DelegatorPolicyError> {
+        let patterns = patterns.into();
+        if patterns.len() > DELEGATOR_POLICY_MAX_PATTERNS {
+            return Err(DelegatorPolicyError::TooManyPatterns(patterns.len()));
+        }
+        Ok(DelegatorPolicy { kind, patterns })
+    }
+
+    /// only the accounts matching one of the patterns can delegate to the pool
+    pub fn allow(
+        patterns: impl Into<Box<[DelegatorPattern]>>,
+    ) -> Result<Self, DelegatorPolicyError> {
+        Self::new(DelegatorPolicyKind::Allow, patterns)
+    }
+
+    /// the accounts matching one of the patterns cannot delegate to the pool
+    pub fn deny(
+        patterns: impl Into<Box<[DelegatorPattern]>>,
+    ) -> Result<Self, DelegatorPolicyError> {
+        Self::new(DelegatorPolicyKind::Deny, patterns)
+    }
+
+    pub fn kind(&self) -> DelegatorPolicyKind {
+        self.kind
+    }
+
+    pub fn patterns(&self) -> &[DelegatorPattern] {
+        &self.patterns
+    }
+
+    /// Check whether the account can delegate to the pool, without taking
+    /// the owners of the pool into account
+    pub fn allows(&self, account: &account::Identifier) -> bool {
+        let matched = self
+            .patterns()
+            .iter()
+            .any(|pattern| pattern.matches(account));
+        match self.kind {
+            DelegatorPolicyKind::Allow => matched,
+            DelegatorPolicyKind::Deny => !matched,
+        }
+    }
+
+    fn serialize_in(&self, bb: ByteBuilder<PoolRegistration>) -> ByteBuilder<PoolRegistration> {
+        let bb = match self.kind {
+            DelegatorPolicyKind::Allow => bb.u8(0),
+            DelegatorPolicyKind::Deny => bb.u8(1),
+        };
+        bb.iter8(&mut self.patterns().iter(), |bb, pattern| {
+            pattern.serialize_in(bb)
+        })
+    }
+
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let kind = match source.take_u8()? {
+            0 => DelegatorPolicyKind::Allow,
+            1 => DelegatorPolicyKind::Deny,
+            n => {
+                return Err(ReadError::UnknownTag {
+                    kind: "delegator policy",
+                    tag: n as u32,
+                })
+            }
+        };
+        let nb_patterns = source.take_u8()? as usize;
+        if nb_patterns > DELEGATOR_POLICY_MAX_PATTERNS {
+            return Err(ReadError::SizeTooBig {
+                size: nb_patterns,
+                limit: DELEGATOR_POLICY_MAX_PATTERNS,
+            });
+        }
+        let patterns = (0..nb_patterns)
+            .map(|_| DelegatorPattern::unpack(source))
+            .collect::<Result<Box<[_]>, _>>()?;
+        Ok(DelegatorPolicy { kind, patterns })
+    }
+}
+
 impl PoolRegistration {
     pub fn serialize_in(&self, bb: ByteBuilder<Self>) -> ByteBuilder<Self> {
         let bb = bb
@@ -122,10 +362,33 @@ impl PoolRegistration {
             .iter8(&mut self.operators.iter(), |bb, o| bb.bytes(o.as_ref()))
             .sub(|sbb| self.rewards.serialize_in(sbb));
 
-        match &self.reward_account {
-            None => bb.u8(0),
-            Some(AccountIdentifier::Single(pk)) => bb.u8(1).bytes(pk.as_ref().as_ref()),
-            Some(AccountIdentifier::Multi(pk)) => bb.u8(2).bytes(pk.as_ref()),
+        let mut flags = 0;
+        if self.pledge != Value::zero() {
+            flags |= PLEDGE_FLAG;
+        }
+        if self.delegator_policy.is_some() {
+            flags |= DELEGATOR_POLICY_FLAG;
+        }
+        let bb = match &self.reward_account {
+            None => bb.u8(flags),
+            Some(RewardAccount::Account(AccountIdentifier::Single(pk))) => {
+                bb.u8(flags | 1).bytes(pk.as_ref().as_ref())
+            }
+            Some(RewardAccount::Account(AccountIdentifier::Multi(pk))) => {
+                bb.u8(flags | 2).bytes(pk.as_ref())
+            }
+            Some(RewardAccount::Address(address)) => bb.u8(flags | 3).bytes(&address.to_bytes()),
+        };
+
+        let bb = if flags & PLEDGE_FLAG != 0 {
+            bb.u64(self.pledge.0)
+        } else {
+            bb
+        };
+
+        match &self.delegator_policy {
+            None => bb,
+            Some(policy) => policy.serialize_in(bb.u8(DELEGATOR_POLICY_VERSION)),
         }
     }
 
@@ -141,6 +404,22 @@ impl PoolRegistration {
     pub fn management_threshold(&self) -> u8 {
         self.permissions.management_threshold()
     }
+
+    /// Check whether the account can delegate to the pool
+    pub fn allows_delegator(&self, account: &account::Identifier) -> bool {
+        match &self.delegator_policy {
+            None => true,
+            Some(policy) => {
+                policy.allows(account) || self.owners.iter().any(|owner| owner == account.as_ref())
+            }
+        }
+    }
+}
+
+impl SerializedSize for PoolRegistration {
+    fn serialized_size(&self) -> usize {
+        self.serialize_in(ByteBuilder::new()).len()
+    }
 }
 
 impl PoolUpdate {
@@ -155,11 +434,17 @@ impl PoolUpdate {
     }
 }
 
-impl Readable for PoolUpdate {
-    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        let pool_id = <[u8; 32]>::read(buf)?.into();
-        let last_pool_reg_hash = <[u8; 32]>::read(buf)?.into();
-        let new_pool_reg = PoolRegistration::read(buf)?;
+impl SerializedSize for PoolUpdate {
+    fn serialized_size(&self) -> usize {
+        self.serialize_in(ByteBuilder::new()).len()
+    }
+}
+
+impl Unpack for PoolUpdate {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let pool_id = <[u8; 32]>::unpack(source)?.into();
+        let last_pool_reg_hash = <[u8; 32]>::unpack(source)?.into();
+        let new_pool_reg = PoolRegistration::unpack(source)?;
         Ok(PoolUpdate {
             pool_id,
             last_pool_reg_hash,
@@ -168,6 +453,8 @@ impl Readable for PoolUpdate {
     }
 }
 
+chain_ser::impl_legacy_codecs!(PoolUpdate);
+
 impl PoolRetirement {
     pub fn serialize_in(&self, bb: ByteBuilder<Self>) -> ByteBuilder<Self> {
         bb.bytes(self.pool_id.as_ref())
@@ -179,10 +466,16 @@ impl PoolRetirement {
     }
 }
 
-impl Readable for PoolRetirement {
-    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        let pool_id = <[u8; 32]>::read(buf)?.into();
-        let retirement_time = DurationSeconds::from(buf.get_u64()?).into();
+impl SerializedSize for PoolRetirement {
+    fn serialized_size(&self) -> usize {
+        self.serialize_in(ByteBuilder::new()).len()
+    }
+}
+
+impl Unpack for PoolRetirement {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let pool_id = <[u8; 32]>::unpack(source)?.into();
+        let retirement_time = DurationSeconds::from(source.take_u64()?).into();
         Ok(PoolRetirement {
             pool_id,
             retirement_time,
@@ -190,19 +483,17 @@ impl Readable for PoolRetirement {
     }
 }
 
-impl property::Serialize for PoolUpdate {
-    type Error = std::io::Error;
-    fn serialize<W: std::io::Write>(&self, mut writer: W) -> Result<(), Self::Error> {
-        writer.write_all(self.serialize().as_slice())?;
-        Ok(())
+chain_ser::impl_legacy_codecs!(PoolRetirement);
+
+impl Pack for PoolUpdate {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(self.serialize().as_slice())
     }
 }
 
-impl property::Serialize for PoolRetirement {
-    type Error = std::io::Error;
-    fn serialize<W: std::io::Write>(&self, mut writer: W) -> Result<(), Self::Error> {
-        writer.write_all(self.serialize().as_slice())?;
-        Ok(())
+impl Pack for PoolRetirement {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(self.serialize().as_slice())
     }
 }
 
@@ -256,48 +547,76 @@ impl Payload for PoolRetirement {
     }
 }
 
-impl property::Serialize for PoolRegistration {
-    type Error = std::io::Error;
-    fn serialize<W: std::io::Write>(&self, mut writer: W) -> Result<(), Self::Error> {
-        writer.write_all(self.serialize().as_slice())?;
-        Ok(())
+impl Pack for PoolRegistration {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(self.serialize().as_slice())
     }
 }
 
-impl Readable for PoolRegistration {
-    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        let serial = buf.get_u128()?;
-        let start_validity = DurationSeconds::from(buf.get_u64()?).into();
-        let permissions = PoolPermissions::from_u64(buf.get_u64()?).ok_or_else(|| {
-            ReadError::StructureInvalid("permission value not correct".to_string())
-        })?;
-        let keys = GenesisPraosLeader::read(buf)?;
+impl Unpack for PoolRegistration {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let serial = source.take_u128()?;
+        let start_validity = DurationSeconds::from(source.take_u64()?).into();
+        let permissions = PoolPermissions::from_u64(source.take_u64()?)
+            .ok_or_else(|| ReadError::StructureInvalid("permission value not correct".into()))?;
+        let keys = GenesisPraosLeader::unpack(source)?;
 
-        let owners_nb = buf.get_u8()?;
+        let owners_nb = source.take_u8()?;
         let mut owners = Vec::with_capacity(owners_nb as usize);
         for _ in 0..owners_nb {
-            owners.push(deserialize_public_key(buf)?);
+            owners.push(unpack_public_key(source)?);
         }
 
-        let operators_nb = buf.get_u8()?;
+        let operators_nb = source.take_u8()?;
         let mut operators = Vec::with_capacity(operators_nb as usize);
         for _ in 0..operators_nb {
-            operators.push(deserialize_public_key(buf)?);
+            operators.push(unpack_public_key(source)?);
         }
 
-        let rewards = TaxType::read_frombuf(buf)?;
-        let reward_account = match buf.get_u8()? {
+        let rewards = TaxType::unpack(source)?;
+        let reward_account_tag = source.take_u8()?;
+        let reward_account = match reward_account_tag & !(PLEDGE_FLAG | DELEGATOR_POLICY_FLAG) {
             0 => None,
             1 => {
-                let pk = deserialize_public_key(buf)?;
-                Some(AccountIdentifier::Single(pk.into()))
+                let pk = unpack_public_key(source)?;
+                Some(AccountIdentifier::Single(pk.into()).into())
             }
             2 => {
                 let mut pk = [0u8; 32];
-                buf.copy_to_slice_mut(&mut pk)?;
-                Some(AccountIdentifier::Multi(pk.into()))
+                source.take_into(&mut pk)?;
+                Some(AccountIdentifier::Multi(pk.into()).into())
+            }
+            3 => Some(RewardAccount::Address(Address::unpack(source)?)),
+            n => {
+                return Err(ReadError::UnknownTag {
+                    kind: "pool reward account",
+                    tag: n as u32,
+                })
+            }
+        };
+        let pledge = if reward_account_tag & PLEDGE_FLAG != 0 {
+            let pledge = Value::unpack(source)?;
+            if pledge == Value::zero() {
+                return Err(ReadError::StructureInvalid(
+                    "pool registration with an encoded zero pledge".into(),
+                ));
+            }
+            pledge
+        } else {
+            Value::zero()
+        };
+        let delegator_policy = if reward_account_tag & DELEGATOR_POLICY_FLAG != 0 {
+            match source.take_u8()? {
+                DELEGATOR_POLICY_VERSION => Some(DelegatorPolicy::unpack(source)?),
+                n => {
+                    return Err(ReadError::UnknownTag {
+                        kind: "delegator policy version",
+                        tag: n as u32,
+                    })
+                }
             }
-            n => return Err(ReadError::UnknownTag(n as u32)),
+        } else {
+            None
         };
 
         let info = Self {
@@ -308,12 +627,16 @@ impl Readable for PoolRegistration {
             operators: operators.into(),
             rewards,
             reward_account,
+            pledge,
             keys,
+            delegator_policy,
         };
         Ok(info)
     }
 }
 
+chain_ser::impl_legacy_codecs!(PoolRegistration);
+
 impl Payload for PoolRegistration {
     const HAS_DATA: bool = true;
     const HAS_AUTH: bool = true;
@@ -419,41 +742,68 @@ impl PoolOwnersSignature {
     }
 }
 
-impl Readable for PoolOwnersSigned {
-    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        let sigs_nb = buf.get_u8()? as usize;
-        if sigs_nb == 0 {
-            return Err(ReadError::StructureInvalid(
-                "pool owner signature with 0 signatures".to_string(),
-            ));
-        }
-        let mut signatures = Vec::new();
-        for _ in 0..sigs_nb {
-            let nb = buf.get_u8()?;
-            let sig = deserialize_signature(buf)?;
-            signatures.push((nb, SingleAccountBindingSignature(sig)))
-        }
-        Ok(PoolOwnersSigned { signatures })
+impl Pack for PoolOwnersSigned {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(self.serialize_in(ByteBuilder::new()).finalize().as_slice())
+    }
+}
+
+impl Unpack for PoolOwnersSigned {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let sigs_nb = source.take_u8()?;
+        unpack_owners_signatures(sigs_nb, source)
+    }
+}
+
+chain_ser::impl_legacy_codecs!(PoolOwnersSigned);
+
+// the number of signatures is read by the caller, as it also tells apart
+// the owners signatures from the operator signature of a `PoolSignature`
+fn unpack_owners_signatures<S: ByteSource>(
+    sigs_nb: u8,
+    source: &mut S,
+) -> Result<PoolOwnersSigned, ReadError> {
+    if sigs_nb == 0 {
+        return Err(ReadError::StructureInvalid(
+            "pool owner signature with 0 signatures".into(),
+        ));
+    }
+    let mut signatures = Vec::with_capacity(sigs_nb as usize);
+    for _ in 0..sigs_nb {
+        let nb = source.take_u8()?;
+        let sig = unpack_signature(source)?;
+        signatures.push((nb, SingleAccountBindingSignature(sig)))
     }
+    Ok(PoolOwnersSigned { signatures })
 }
 
-impl Readable for PoolSignature {
-    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        match buf.peek_u8()? {
+impl Pack for PoolSignature {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(self.serialize_in(ByteBuilder::new()).finalize().as_slice())
+    }
+}
+
+impl Unpack for PoolSignature {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        match source.take_u8()? {
             0 => {
-                let _ = buf.get_u8()?;
-                let sig = deserialize_signature(buf)?;
+                let sig = unpack_signature(source)?;
                 Ok(PoolSignature::Operator(SingleAccountBindingSignature(sig)))
             }
-            _ => PoolOwnersSigned::read(buf).map(PoolSignature::Owners),
+            sigs_nb => unpack_owners_signatures(sigs_nb, source).map(PoolSignature::Owners),
         }
     }
 }
 
+chain_ser::impl_legacy_codecs!(PoolSignature);
+
 #[cfg(test)]
 mod tests {
 
-    use super::{PoolOwnersSigned, PoolPermissions};
+    use super::{
+        DelegatorPattern, DelegatorPolicy, DelegatorPolicyError, DelegatorPrefix, PoolOwnersSigned,
+        PoolPermissions, DELEGATOR_POLICY_MAX_PATTERNS,
+    };
     use crate::{
         chaintypes::HeaderId,
         date::BlockDate,
@@ -765,4 +1115,60 @@ mod tests {
             info
         );
     }
+
+    #[test]
+    pub fn delegator_policy_patterns() {
+        let owner = TestGen::public_key();
+        let account = TestGen::identifier();
+        let other = TestGen::identifier();
+        let prefix = DelegatorPrefix::new(&account.as_ref().as_ref()[..4]).unwrap();
+        let mut stake_pool = StakePoolBuilder::new()
+            .with_owners(vec![owner.clone()])
+            .build()
+            .info();
+        assert!(stake_pool.allows_delegator(&other));
+
+        stake_pool.delegator_policy =
+            Some(DelegatorPolicy::allow(vec![DelegatorPattern::Prefix(prefix.clone())]).unwrap());
+        assert!(stake_pool.allows_delegator(&account));
+        assert!(stake_pool.allows_delegator(&owner.clone().into()));
+        assert_eq!(
+            stake_pool.allows_delegator(&other),
+            other.as_ref().as_ref().starts_with(prefix.as_ref())
+        );
+
+        stake_pool.delegator_policy =
+            Some(DelegatorPolicy::deny(vec![DelegatorPattern::Account(account.clone())]).unwrap());
+        assert!(!stake_pool.allows_delegator(&account));
+        assert!(stake_pool.allows_delegator(&other));
+
+        // the owners can always delegate to their pool
+        stake_pool.delegator_policy = Some(DelegatorPolicy::allow(Vec::new()).unwrap());
+        assert!(stake_pool.allows_delegator(&owner.into()));
+        assert!(!stake_pool.allows_delegator(&account));
+    }
+
+    #[test]
+    pub fn delegator_policy_is_validated_when_built() {
+        assert_eq!(
+            DelegatorPrefix::new(Vec::new()),
+            Err(DelegatorPolicyError::InvalidPrefixLength(0))
+        );
+        assert_eq!(
+            DelegatorPrefix::new(vec![0; 32]),
+            Err(DelegatorPolicyError::InvalidPrefixLength(32))
+        );
+        assert!(DelegatorPrefix::new(vec![0; 31]).is_ok());
+
+        let pattern = DelegatorPattern::Prefix(DelegatorPrefix::new(vec![0]).unwrap());
+        assert!(
+            DelegatorPolicy::deny(vec![pattern.clone(); DELEGATOR_POLICY_MAX_PATTERNS]).is_ok()
+        );
+        assert_eq!(
+            DelegatorPolicy::deny(vec![pattern; DELEGATOR_POLICY_MAX_PATTERNS + 1]),
+            Err(DelegatorPolicyError::TooManyPatterns(
+                DELEGATOR_POLICY_MAX_PATTERNS + 1
+            ))
+        );
+    }
 }
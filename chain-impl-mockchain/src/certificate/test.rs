@@ -4,8 +4,15 @@ use crate::fragment::ConfigParams;
 use crate::ledger::governance::TreasuryGovernanceAction;
 use crate::rewards::TaxType;
 use crate::testing::data::CommitteeMembersManager;
+#[cfg(test)]
+use crate::testing::serialization::serialization_bijection;
+use crate::transaction::AccountIdentifier;
+use crate::value::Value;
 use crate::vote;
-use crate::{accounting::account::DelegationType, tokens::identifier::TokenIdentifier};
+use crate::{
+    accounting::account::{DelegationAmounts, DelegationType, DELEGATION_AMOUNTS_MAX_DECLS},
+    tokens::identifier::TokenIdentifier,
+};
 #[cfg(test)]
 use chain_core::mempack::{ReadBuf, Readable};
 use chain_crypto::{testing, Ed25519};
@@ -90,6 +97,32 @@ impl Arbitrary for StakeDelegation {
     }
 }
 
+impl Arbitrary for AmountStakeDelegation {
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        let nb_pools = 1 + usize::arbitrary(g) % DELEGATION_AMOUNTS_MAX_DECLS;
+        let mut pools: Vec<(PoolId, Value)> = Vec::with_capacity(nb_pools);
+        while pools.len() < nb_pools {
+            let pool_id = PoolId::arbitrary(g);
+            if pools.iter().all(|(id, _)| *id != pool_id) {
+                pools.push((pool_id, Value(1 + u32::arbitrary(g) as u64)));
+            }
+        }
+        AmountStakeDelegation {
+            account_id: Arbitrary::arbitrary(g),
+            delegation: DelegationAmounts::new(pools).unwrap(),
+        }
+    }
+}
+
+impl Arbitrary for RewardsRedelegation {
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        RewardsRedelegation {
+            account_id: Arbitrary::arbitrary(g),
+            enabled: Arbitrary::arbitrary(g),
+        }
+    }
+}
+
 impl Arbitrary for OwnerStakeDelegation {
     fn arbitrary<G: Gen>(g: &mut G) -> Self {
         Self {
@@ -145,11 +178,50 @@ impl Arbitrary for PoolRegistration {
             operators: operators.into(),
             rewards: TaxType::zero(),
             reward_account: None,
+            pledge: Arbitrary::arbitrary(g),
             keys,
+            delegator_policy: Arbitrary::arbitrary(g),
+        }
+    }
+}
+
+impl Arbitrary for RewardAccount {
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        if bool::arbitrary(g) {
+            RewardAccount::Account(Arbitrary::arbitrary(g))
+        } else {
+            RewardAccount::Address(Arbitrary::arbitrary(g))
+        }
+    }
+}
+
+impl Arbitrary for DelegatorPattern {
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        if bool::arbitrary(g) {
+            DelegatorPattern::Account(Arbitrary::arbitrary(g))
+        } else {
+            let len = 1 + usize::arbitrary(g) % 31;
+            let prefix: Vec<u8> = (0..len).map(|_| u8::arbitrary(g)).collect();
+            DelegatorPattern::Prefix(DelegatorPrefix::new(prefix).unwrap())
         }
     }
 }
 
+impl Arbitrary for DelegatorPolicy {
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        let nb_patterns = usize::arbitrary(g) % (DELEGATOR_POLICY_MAX_PATTERNS + 1);
+        let patterns: Vec<_> = (0..nb_patterns)
+            .map(|_| DelegatorPattern::arbitrary(g))
+            .collect();
+        let kind = if bool::arbitrary(g) {
+            DelegatorPolicyKind::Allow
+        } else {
+            DelegatorPolicyKind::Deny
+        };
+        DelegatorPolicy::new(kind, patterns).unwrap()
+    }
+}
+
 impl Arbitrary for TreasuryGovernanceAction {
     fn arbitrary<G: Gen>(g: &mut G) -> Self {
         TreasuryGovernanceAction::TransferToRewards {
@@ -249,6 +321,46 @@ impl Arbitrary for VoteCast {
     }
 }
 
+impl Arbitrary for SnapshotVoteCast {
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        SnapshotVoteCast::new(
+            Arbitrary::arbitrary(g),
+            Arbitrary::arbitrary(g),
+            Arbitrary::arbitrary(g),
+        )
+    }
+}
+
+impl Arbitrary for VoteCastBatch {
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        let vote_plan = VotePlanId::arbitrary(g);
+        let nb_casts = 1 + usize::arbitrary(g) % 8;
+        let casts = (0..nb_casts)
+            .map(|index| VoteCast::new(vote_plan.clone(), index as u8, Arbitrary::arbitrary(g)))
+            .collect();
+        VoteCastBatch::new(casts).unwrap()
+    }
+}
+
+impl Arbitrary for VotingPowerSnapshot {
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        VotingPowerSnapshot::new(
+            Arbitrary::arbitrary(g),
+            Arbitrary::arbitrary(g),
+            Arbitrary::arbitrary(g),
+        )
+    }
+}
+
+impl Arbitrary for VotingPowerSnapshotProof {
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        Self {
+            id: Arbitrary::arbitrary(g),
+            signature: Arbitrary::arbitrary(g),
+        }
+    }
+}
+
 fn arbitrary_decrypted_private_tally<G: Gen>(g: &mut G) -> DecryptedPrivateTally {
     let proposals_n = u8::arbitrary(g);
     let mut inner = Vec::new();
@@ -324,9 +436,41 @@ impl Arbitrary for EncryptedVoteTallyProof {
     }
 }
 
+impl Arbitrary for ExtensionCertificate {
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        let tag = *EXTENSION_TAGS.start() | u8::arbitrary(g);
+        let payload: Vec<u8> = Arbitrary::arbitrary(g);
+        ExtensionCertificate::new(tag, payload).unwrap()
+    }
+}
+
+impl Arbitrary for ExtensionCertificateAuth {
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        let auth: Vec<u8> = Arbitrary::arbitrary(g);
+        ExtensionCertificateAuth(auth.into())
+    }
+}
+
+impl Arbitrary for VotePlanCancel {
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        Self::new(Arbitrary::arbitrary(g))
+    }
+}
+
+impl Arbitrary for VotePlanCancelProof {
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        let len = usize::arbitrary(g) % 8;
+        let signatures =
+            std::iter::repeat_with(|| (Arbitrary::arbitrary(g), Arbitrary::arbitrary(g)))
+                .take(len)
+                .collect();
+        Self { signatures }
+    }
+}
+
 impl Arbitrary for Certificate {
     fn arbitrary<G: Gen>(g: &mut G) -> Self {
-        let option = u8::arbitrary(g) % 12;
+        let option = u8::arbitrary(g) % 21;
         match option {
             0 => Certificate::StakeDelegation(Arbitrary::arbitrary(g)),
             1 => Certificate::OwnerStakeDelegation(Arbitrary::arbitrary(g)),
@@ -340,6 +484,15 @@ impl Arbitrary for Certificate {
             9 => Certificate::UpdateProposal(Arbitrary::arbitrary(g)),
             10 => Certificate::UpdateVote(Arbitrary::arbitrary(g)),
             11 => Certificate::MintToken(Arbitrary::arbitrary(g)),
+            12 => Certificate::NameClaim(Arbitrary::arbitrary(g)),
+            13 => Certificate::NameRelease(Arbitrary::arbitrary(g)),
+            14 => Certificate::VotingPowerSnapshot(Arbitrary::arbitrary(g)),
+            15 => Certificate::SnapshotVoteCast(Arbitrary::arbitrary(g)),
+            16 => Certificate::VoteCastBatch(Arbitrary::arbitrary(g)),
+            17 => Certificate::AmountStakeDelegation(Arbitrary::arbitrary(g)),
+            18 => Certificate::RewardsRedelegation(Arbitrary::arbitrary(g)),
+            19 => Certificate::Extension(Arbitrary::arbitrary(g)),
+            20 => Certificate::VotePlanCancel(Arbitrary::arbitrary(g)),
             _ => panic!("unimplemented"),
         }
     }
@@ -355,3 +508,231 @@ fn pool_reg_serialization_bijection(b: PoolRegistration) -> TestResult {
     assert_eq!(buf.get_slice_end(), &[]);
     TestResult::from_bool(left == result)
 }
+
+#[quickcheck]
+fn pool_reg_with_reward_account_serialization_bijection(
+    b: PoolRegistration,
+    reward_account: RewardAccount,
+) -> TestResult {
+    let b = PoolRegistration {
+        reward_account: Some(reward_account),
+        ..b
+    };
+    let b_got = b.serialize();
+    let mut buf = ReadBuf::from(b_got.as_ref());
+    let result = PoolRegistration::read(&mut buf);
+    let left = Ok(b);
+    assert_eq!(left, result);
+    assert_eq!(buf.get_slice_end(), &[]);
+    TestResult::from_bool(left == result)
+}
+
+#[quickcheck]
+fn pool_reg_pledge_is_only_encoded_if_any(b: PoolRegistration) -> TestResult {
+    let without_pledge = PoolRegistration {
+        pledge: Value::zero(),
+        ..b.clone()
+    };
+    let with_pledge = PoolRegistration {
+        pledge: Value(1),
+        ..b
+    };
+    TestResult::from_bool(
+        with_pledge.serialize().len() == without_pledge.serialize().len() + 8
+            && with_pledge.to_id() != without_pledge.to_id(),
+    )
+}
+
+#[quickcheck]
+fn pool_reg_in_the_former_layout_is_decoded(
+    b: PoolRegistration,
+    account: Option<AccountIdentifier>,
+) -> TestResult {
+    let b = PoolRegistration {
+        reward_account: account.map(RewardAccount::Account),
+        pledge: Value::zero(),
+        delegator_policy: None,
+        ..b
+    };
+    // the layout of the registrations before the pledge and the delegator
+    // policy, the registration ends with the reward account
+    let bb = typed_bytes::ByteBuilder::<PoolRegistration>::new()
+        .u128(b.serial)
+        .u64(b.start_validity.into())
+        .u64(b.management_threshold() as u64)
+        .bytes(b.keys.vrf_public_key.as_ref())
+        .bytes(b.keys.kes_public_key.as_ref())
+        .iter8(&mut b.owners.iter(), |bb, o| bb.bytes(o.as_ref()))
+        .iter8(&mut b.operators.iter(), |bb, o| bb.bytes(o.as_ref()))
+        .sub(|sbb| b.rewards.serialize_in(sbb));
+    let bytes = match &b.reward_account {
+        None => bb.u8(0),
+        Some(RewardAccount::Account(AccountIdentifier::Single(pk))) => {
+            bb.u8(1).bytes(pk.as_ref().as_ref())
+        }
+        Some(RewardAccount::Account(AccountIdentifier::Multi(pk))) => bb.u8(2).bytes(pk.as_ref()),
+        Some(RewardAccount::Address(_)) => unreachable!(),
+    }
+    .finalize_as_vec();
+    assert_eq!(b.serialize().as_slice(), &bytes[..]);
+
+    // in a transaction, the registration is followed by the rest of the
+    // transaction
+    let trailing = [0xff; 10];
+    let with_trailing = [&bytes[..], &trailing[..]].concat();
+    let mut buf = ReadBuf::from(&with_trailing);
+    assert_eq!(PoolRegistration::read(&mut buf), Ok(b));
+    TestResult::from_bool(buf.get_slice_end() == trailing)
+}
+
+#[quickcheck]
+fn vote_cast_batch_serialization_bijection(b: VoteCastBatch) -> TestResult {
+    let b_got = b.serialize();
+    let mut buf = ReadBuf::from(b_got.as_ref());
+    let result = VoteCastBatch::read(&mut buf);
+    let left = Ok(b);
+    assert_eq!(left, result);
+    assert_eq!(buf.get_slice_end(), &[]);
+    TestResult::from_bool(left == result)
+}
+
+#[quickcheck]
+fn amount_stake_delegation_serialization_bijection(b: AmountStakeDelegation) -> TestResult {
+    let b_got = b.serialize();
+    let mut buf = ReadBuf::from(b_got.as_ref());
+    let result = AmountStakeDelegation::read(&mut buf);
+    let left = Ok(b);
+    assert_eq!(left, result);
+    assert_eq!(buf.get_slice_end(), &[]);
+    TestResult::from_bool(left == result)
+}
+
+#[quickcheck]
+fn rewards_redelegation_serialization_bijection(b: RewardsRedelegation) -> TestResult {
+    let b_got = b.serialize();
+    let mut buf = ReadBuf::from(b_got.as_ref());
+    let result = RewardsRedelegation::read(&mut buf);
+    let left = Ok(b);
+    assert_eq!(left, result);
+    assert_eq!(buf.get_slice_end(), &[]);
+    TestResult::from_bool(left == result)
+}
+
+#[quickcheck]
+fn extension_certificate_serialization_bijection(b: ExtensionCertificate) -> TestResult {
+    let b_got = b.serialize();
+    let mut buf = ReadBuf::from(b_got.as_ref());
+    let result = ExtensionCertificate::read(&mut buf);
+    let left = Ok(b);
+    assert_eq!(left, result);
+    assert_eq!(buf.get_slice_end(), &[]);
+    TestResult::from_bool(left == result)
+}
+
+#[test]
+fn extension_certificate_tag_is_reserved() {
+    assert!(ExtensionCertificate::new(*EXTENSION_TAGS.start() - 1, vec![1, 2]).is_none());
+    let mut bytes = ExtensionCertificate::new(*EXTENSION_TAGS.start(), vec![1, 2])
+        .unwrap()
+        .serialize()
+        .as_slice()
+        .to_vec();
+    bytes[0] = *EXTENSION_TAGS.start() - 1;
+    assert!(ExtensionCertificate::read(&mut ReadBuf::from(&bytes[..])).is_err());
+}
+
+#[test]
+fn delegation_amounts_limits() {
+    let pool = |i: u8| PoolId::from(chain_crypto::digest::Digest::digest(&[i]));
+    assert_eq!(DelegationAmounts::new(Vec::new()), None);
+    assert_eq!(DelegationAmounts::new(vec![(pool(1), Value::zero())]), None);
+    assert_eq!(
+        DelegationAmounts::new(vec![(pool(1), Value(10)), (pool(1), Value(20))]),
+        None
+    );
+    assert_eq!(
+        DelegationAmounts::new(vec![(pool(1), Value(u64::MAX)), (pool(2), Value(1))]),
+        None
+    );
+    assert_eq!(
+        DelegationAmounts::new(
+            (0..=DELEGATION_AMOUNTS_MAX_DECLS as u8)
+                .map(|i| (pool(i), Value(1)))
+                .collect()
+        ),
+        None
+    );
+    let amounts = DelegationAmounts::new(vec![(pool(1), Value(10)), (pool(2), Value(20))]).unwrap();
+    assert_eq!(amounts.total(), Value(30));
+}
+
+#[test]
+fn vote_cast_batch_limits() {
+    let vote_plan = VotePlanId::from([1; 32]);
+    let cast = |index: usize| {
+        VoteCast::new(
+            vote_plan.clone(),
+            index as u8,
+            vote::Payload::public(vote::Choice::new(1)),
+        )
+    };
+
+    assert_eq!(
+        VoteCastBatch::new(Vec::new()),
+        Err(VoteCastBatchError::Empty)
+    );
+    assert_eq!(
+        VoteCastBatch::new((0..=VOTE_CAST_BATCH_MAX_CASTS).map(cast).collect()),
+        Err(VoteCastBatchError::TooManyCasts {
+            count: VOTE_CAST_BATCH_MAX_CASTS + 1,
+            max: VOTE_CAST_BATCH_MAX_CASTS,
+        })
+    );
+    assert_eq!(
+        VoteCastBatch::new(vec![cast(1), cast(2), cast(1)]),
+        Err(VoteCastBatchError::DuplicateCast {
+            vote_plan: vote_plan.clone(),
+            proposal_index: 1,
+        })
+    );
+    assert!(VoteCastBatch::new((0..VOTE_CAST_BATCH_MAX_CASTS).map(cast).collect()).is_ok());
+}
+
+#[quickcheck]
+fn vote_plan_cancel_serialization_bijection(b: VotePlanCancel) -> TestResult {
+    let b_got = b.serialize();
+    let mut buf = ReadBuf::from(b_got.as_ref());
+    let result = VotePlanCancel::read(&mut buf);
+    let left = Ok(b);
+    assert_eq!(left, result);
+    assert_eq!(buf.get_slice_end(), &[]);
+    TestResult::from_bool(left == result)
+}
+
+// the certificates are decoded from a stream, as in a ledger snapshot, the
+// same way as from the buffer of a block
+
+#[quickcheck]
+fn pool_reg_stream_decoding_bijection(b: PoolRegistration) -> TestResult {
+    serialization_bijection(b)
+}
+
+#[quickcheck]
+fn stake_delegation_stream_decoding_bijection(b: StakeDelegation) -> TestResult {
+    serialization_bijection(b)
+}
+
+#[quickcheck]
+fn vote_plan_stream_decoding_bijection(b: VotePlan) -> TestResult {
+    serialization_bijection(b)
+}
+
+#[quickcheck]
+fn vote_cast_batch_stream_decoding_bijection(b: VoteCastBatch) -> TestResult {
+    serialization_bijection(b)
+}
+
+#[quickcheck]
+fn vote_tally_stream_decoding_bijection(b: VoteTally) -> TestResult {
+    serialization_bijection(b)
+}
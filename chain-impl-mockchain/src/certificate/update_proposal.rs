@@ -2,13 +2,14 @@ use crate::fragment::config::ConfigParams;
 use crate::transaction::SingleAccountBindingSignature;
 
 use crate::{
-    certificate::CertificateSlice,
+    certificate::{CertificateSlice, SerializedSize},
     key::BftLeaderId,
     transaction::{Payload, PayloadAuthData, PayloadData, PayloadSlice},
 };
 use chain_core::{
-    mempack::{ReadBuf, ReadError, Readable},
-    property::{self, Serialize},
+    mempack::ReadError,
+    packer::Codec,
+    property::{ByteSource, Pack, Serialize, Unpack},
 };
 use typed_bytes::{ByteArray, ByteBuilder};
 
@@ -47,6 +48,12 @@ impl UpdateProposal {
     }
 }
 
+impl SerializedSize for UpdateProposal {
+    fn serialized_size(&self) -> usize {
+        self.serialize_in(ByteBuilder::new()).len()
+    }
+}
+
 pub type BftLeaderBindingSignature = SingleAccountBindingSignature;
 
 /* Auth/Payload ************************************************************* */
@@ -79,36 +86,20 @@ impl Payload for UpdateProposal {
 
 /* Ser/De ******************************************************************* */
 
-impl property::Serialize for UpdateProposal {
-    type Error = std::io::Error;
-    fn serialize<W: std::io::Write>(&self, writer: W) -> Result<(), Self::Error> {
-        use chain_core::packer::*;
-        let mut codec = Codec::new(writer);
-        self.changes.serialize(&mut codec)?;
-        self.proposer_id.serialize(&mut codec)?;
-        Ok(())
+impl Pack for UpdateProposal {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        self.changes.pack(codec)?;
+        self.proposer_id.pack(codec)
     }
 }
 
-impl property::Deserialize for UpdateProposal {
-    type Error = std::io::Error;
-    fn deserialize<R: std::io::BufRead>(reader: R) -> Result<Self, Self::Error> {
-        use chain_core::packer::*;
-        let mut codec = Codec::new(reader);
-        let changes = ConfigParams::deserialize(&mut codec)?;
-        let proposer_id = UpdateProposerId::deserialize(&mut codec)?;
-        Ok(Self {
-            changes,
-            proposer_id,
-        })
-    }
-}
-
-impl Readable for UpdateProposal {
-    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        let changes = ConfigParams::read(buf)?;
-        let proposer_id = UpdateProposerId::read(buf)?;
+impl Unpack for UpdateProposal {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let changes = ConfigParams::unpack(source)?;
+        let proposer_id = UpdateProposerId::unpack(source)?;
 
         Ok(Self::new(changes, proposer_id))
     }
 }
+
+chain_ser::impl_legacy_codecs!(UpdateProposal);
@@ -1,12 +1,13 @@
 use crate::{
-    certificate::{BftLeaderBindingSignature, CertificateSlice},
+    certificate::{BftLeaderBindingSignature, CertificateSlice, SerializedSize},
     key::BftLeaderId,
     transaction::{Payload, PayloadAuthData, PayloadData, PayloadSlice},
 };
 
 use chain_core::{
-    mempack::{ReadBuf, ReadError, Readable},
-    property,
+    mempack::ReadError,
+    packer::Codec,
+    property::{ByteSource, Pack, Unpack},
 };
 use typed_bytes::{ByteArray, ByteBuilder};
 
@@ -45,6 +46,12 @@ impl UpdateVote {
     }
 }
 
+impl SerializedSize for UpdateVote {
+    fn serialized_size(&self) -> usize {
+        self.serialize_in(ByteBuilder::new()).len()
+    }
+}
+
 /* Auth/Payload ************************************************************* */
 
 impl Payload for UpdateVote {
@@ -75,22 +82,20 @@ impl Payload for UpdateVote {
 
 /* Ser/De ******************************************************************* */
 
-impl property::Serialize for UpdateVote {
-    type Error = std::io::Error;
-    fn serialize<W: std::io::Write>(&self, writer: W) -> Result<(), Self::Error> {
-        use chain_core::packer::*;
-        let mut codec = Codec::new(writer);
-        self.proposal_id.serialize(&mut codec)?;
-        self.voter_id.serialize(&mut codec)?;
-        Ok(())
+impl Pack for UpdateVote {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        self.proposal_id.pack(codec)?;
+        self.voter_id.pack(codec)
     }
 }
 
-impl Readable for UpdateVote {
-    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        let proposal_id = UpdateProposalId::read(buf)?;
-        let voter_id = UpdateVoterId::read(buf)?;
+impl Unpack for UpdateVote {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let proposal_id = UpdateProposalId::unpack(source)?;
+        let voter_id = UpdateVoterId::unpack(source)?;
 
         Ok(Self::new(proposal_id, voter_id))
     }
 }
+
+chain_ser::impl_legacy_codecs!(UpdateVote);
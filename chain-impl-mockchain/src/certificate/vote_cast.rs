@@ -1,14 +1,21 @@
 use crate::{
-    certificate::{CertificateSlice, VotePlanId},
+    certificate::{CertificateSlice, SerializedSize, VotePlanId},
     transaction::{Payload, PayloadAuthData, PayloadData, PayloadSlice},
-    vote,
+    value::Value,
+    vote::{self, VotingPowerProof},
 };
 use chain_core::{
-    mempack::{ReadBuf, ReadError, Readable},
-    property,
+    mempack::ReadError,
+    packer::Codec,
+    property::{ByteSource, Pack, Unpack},
 };
+use std::collections::HashSet;
+use thiserror::Error;
 use typed_bytes::{ByteArray, ByteBuilder};
 
+/// Maximum number of vote casts in a batch
+pub const VOTE_CAST_BATCH_MAX_CASTS: usize = 64;
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct VoteCast {
     vote_plan: VotePlanId,
@@ -16,6 +23,39 @@ pub struct VoteCast {
     payload: vote::Payload,
 }
 
+/// Vote cast for a vote plan with a voting power snapshot, along with the
+/// proof of the voting power of the voter in the snapshot.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SnapshotVoteCast {
+    cast: VoteCast,
+    voting_power: Value,
+    proof: VotingPowerProof,
+}
+
+/// Several vote casts from the same account, authenticated with the single
+/// witness of the transaction carrying the batch.
+///
+/// Each cast is validated on its own when the batch is applied to the ledger,
+/// and either all the casts of the batch are applied or none of them. A batch
+/// cannot contain two casts for the same proposal.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VoteCastBatch {
+    casts: Vec<VoteCast>,
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum VoteCastBatchError {
+    #[error("Vote cast batch is empty")]
+    Empty,
+    #[error("Vote cast batch has {count} casts, only {max} are allowed")]
+    TooManyCasts { count: usize, max: usize },
+    #[error("Vote cast batch has more than one cast for the proposal {proposal_index} of the vote plan {vote_plan}")]
+    DuplicateCast {
+        vote_plan: VotePlanId,
+        proposal_index: u8,
+    },
+}
+
 impl VoteCast {
     pub fn new(vote_plan: VotePlanId, proposal_index: u8, payload: vote::Payload) -> Self {
         Self {
@@ -51,6 +91,107 @@ impl VoteCast {
     }
 }
 
+impl SerializedSize for VoteCast {
+    fn serialized_size(&self) -> usize {
+        self.serialize_in(ByteBuilder::new()).len()
+    }
+}
+
+impl VoteCastBatch {
+    pub fn new(casts: Vec<VoteCast>) -> Result<Self, VoteCastBatchError> {
+        if casts.is_empty() {
+            return Err(VoteCastBatchError::Empty);
+        }
+        if casts.len() > VOTE_CAST_BATCH_MAX_CASTS {
+            return Err(VoteCastBatchError::TooManyCasts {
+                count: casts.len(),
+                max: VOTE_CAST_BATCH_MAX_CASTS,
+            });
+        }
+        let mut proposals = HashSet::with_capacity(casts.len());
+        for cast in casts.iter() {
+            if !proposals.insert((cast.vote_plan(), cast.proposal_index())) {
+                return Err(VoteCastBatchError::DuplicateCast {
+                    vote_plan: cast.vote_plan().clone(),
+                    proposal_index: cast.proposal_index(),
+                });
+            }
+        }
+        Ok(Self { casts })
+    }
+
+    pub fn casts(&self) -> &[VoteCast] {
+        &self.casts
+    }
+
+    pub fn nb_casts(&self) -> usize {
+        self.casts.len()
+    }
+
+    pub(crate) fn into_casts(self) -> Vec<VoteCast> {
+        self.casts
+    }
+
+    pub fn serialize_in(&self, bb: ByteBuilder<Self>) -> ByteBuilder<Self> {
+        bb.iter8(self.casts.iter(), |bb, cast| {
+            bb.sub(|bb| cast.serialize_in(bb))
+        })
+    }
+
+    pub fn serialize(&self) -> ByteArray<Self> {
+        self.serialize_in(ByteBuilder::new()).finalize()
+    }
+}
+
+impl SerializedSize for VoteCastBatch {
+    fn serialized_size(&self) -> usize {
+        self.serialize_in(ByteBuilder::new()).len()
+    }
+}
+
+impl SnapshotVoteCast {
+    pub fn new(cast: VoteCast, voting_power: Value, proof: VotingPowerProof) -> Self {
+        Self {
+            cast,
+            voting_power,
+            proof,
+        }
+    }
+
+    pub fn cast(&self) -> &VoteCast {
+        &self.cast
+    }
+
+    pub fn voting_power(&self) -> Value {
+        self.voting_power
+    }
+
+    pub fn proof(&self) -> &VotingPowerProof {
+        &self.proof
+    }
+
+    pub(crate) fn into_parts(self) -> (VoteCast, Value, VotingPowerProof) {
+        (self.cast, self.voting_power, self.proof)
+    }
+
+    pub fn serialize_in(&self, bb: ByteBuilder<Self>) -> ByteBuilder<Self> {
+        let bb = bb
+            .sub(|bb| self.cast.serialize_in(bb))
+            .u64(self.voting_power.0);
+        self.proof.serialize_in(bb)
+    }
+
+    pub fn serialize(&self) -> ByteArray<Self> {
+        self.serialize_in(ByteBuilder::new()).finalize()
+    }
+}
+
+impl SerializedSize for SnapshotVoteCast {
+    fn serialized_size(&self) -> usize {
+        self.serialize_in(ByteBuilder::new()).len()
+    }
+}
+
 /* Auth/Payload ************************************************************* */
 
 impl Payload for VoteCast {
@@ -76,22 +217,105 @@ impl Payload for VoteCast {
     }
 }
 
+impl Payload for VoteCastBatch {
+    const HAS_DATA: bool = true;
+    const HAS_AUTH: bool = false;
+    type Auth = ();
+
+    fn payload_data(&self) -> PayloadData<Self> {
+        PayloadData(
+            self.serialize_in(ByteBuilder::new())
+                .finalize_as_vec()
+                .into(),
+            std::marker::PhantomData,
+        )
+    }
+
+    fn payload_auth_data(_: &Self::Auth) -> PayloadAuthData<Self> {
+        PayloadAuthData(Vec::with_capacity(0).into(), std::marker::PhantomData)
+    }
+
+    fn payload_to_certificate_slice(p: PayloadSlice<'_, Self>) -> Option<CertificateSlice<'_>> {
+        Some(CertificateSlice::from(p))
+    }
+}
+
+impl Payload for SnapshotVoteCast {
+    const HAS_DATA: bool = true;
+    const HAS_AUTH: bool = false;
+    type Auth = ();
+
+    fn payload_data(&self) -> PayloadData<Self> {
+        PayloadData(
+            self.serialize_in(ByteBuilder::new())
+                .finalize_as_vec()
+                .into(),
+            std::marker::PhantomData,
+        )
+    }
+
+    fn payload_auth_data(_: &Self::Auth) -> PayloadAuthData<Self> {
+        PayloadAuthData(Vec::with_capacity(0).into(), std::marker::PhantomData)
+    }
+
+    fn payload_to_certificate_slice(p: PayloadSlice<'_, Self>) -> Option<CertificateSlice<'_>> {
+        Some(CertificateSlice::from(p))
+    }
+}
+
 /* Ser/De ******************************************************************* */
 
-impl property::Serialize for VoteCast {
-    type Error = std::io::Error;
-    fn serialize<W: std::io::Write>(&self, mut writer: W) -> Result<(), Self::Error> {
-        writer.write_all(self.serialize().as_slice())?;
-        Ok(())
+impl Pack for VoteCast {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(self.serialize().as_slice())
     }
 }
 
-impl Readable for VoteCast {
-    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        let vote_plan = <[u8; 32]>::read(buf)?.into();
-        let proposal_index = buf.get_u8()?;
-        let payload = vote::Payload::read(buf)?;
+impl Unpack for VoteCast {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let vote_plan = <[u8; 32]>::unpack(source)?.into();
+        let proposal_index = source.take_u8()?;
+        let payload = vote::Payload::unpack(source)?;
 
         Ok(Self::new(vote_plan, proposal_index, payload))
     }
 }
+
+chain_ser::impl_legacy_codecs!(VoteCast);
+
+impl Pack for VoteCastBatch {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(self.serialize().as_slice())
+    }
+}
+
+impl Unpack for VoteCastBatch {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let nb_casts = source.take_u8()? as usize;
+        let mut casts = Vec::with_capacity(nb_casts);
+        for _ in 0..nb_casts {
+            casts.push(VoteCast::unpack(source)?);
+        }
+        Self::new(casts).map_err(|e| ReadError::InvalidData(e.to_string().into()))
+    }
+}
+
+chain_ser::impl_legacy_codecs!(VoteCastBatch);
+
+impl Pack for SnapshotVoteCast {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(self.serialize().as_slice())
+    }
+}
+
+impl Unpack for SnapshotVoteCast {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let cast = VoteCast::unpack(source)?;
+        let voting_power = Value::unpack(source)?;
+        let proof = VotingPowerProof::unpack(source)?;
+
+        Ok(Self::new(cast, voting_power, proof))
+    }
+}
+
+chain_ser::impl_legacy_codecs!(SnapshotVoteCast);
@@ -1,6 +1,6 @@
 use crate::{
     block::BlockDate,
-    certificate::CertificateSlice,
+    certificate::{CertificateSlice, SerializedSize},
     ledger::governance::{Governance, ParametersGovernanceAction, TreasuryGovernanceAction},
     tokens::identifier::TokenIdentifier,
     transaction::{
@@ -10,8 +10,9 @@ use crate::{
     vote,
 };
 use chain_core::{
-    mempack::{ReadBuf, ReadError, Readable},
-    property,
+    mempack::ReadError,
+    packer::Codec,
+    property::{ByteSource, Pack, Unpack},
 };
 use chain_crypto::{digest::DigestOf, Blake2b256, Verification};
 use chain_vote::MemberPublicKey;
@@ -54,6 +55,20 @@ pub struct VotePlan {
     voting_token: TokenIdentifier,
 }
 
+/// Phase of a vote plan at a given date, the dates of the vote plan are
+/// the (included) start of each phase
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Phase {
+    /// before `vote_start`
+    Before,
+    /// `[vote_start..vote_end[`: the votes can be cast
+    Voting,
+    /// `[vote_end..committee_end[`: the committee can tally the votes
+    Tallying,
+    /// from `committee_end`
+    Closed,
+}
+
 #[derive(Debug, Clone)]
 pub struct VotePlanProof {
     pub id: vote::CommitteeId,
@@ -269,13 +284,40 @@ impl VotePlan {
         self.vote_end <= date
     }
 
+    /// phase of the vote plan at the given date
+    pub fn phase_at(&self, date: BlockDate) -> Phase {
+        if date < self.vote_start {
+            Phase::Before
+        } else if date < self.vote_end {
+            Phase::Voting
+        } else if date < self.committee_end {
+            Phase::Tallying
+        } else {
+            Phase::Closed
+        }
+    }
+
+    /// tells if it is possible to vote at the given date, i.e. if the vote
+    /// plan is in the [`Phase::Voting`] phase
+    #[inline]
+    pub fn can_vote_at(&self, date: BlockDate) -> bool {
+        self.phase_at(date) == Phase::Voting
+    }
+
+    /// tells if the committee can tally the votes at the given date, i.e. if
+    /// the vote plan is in the [`Phase::Tallying`] phase
+    #[inline]
+    pub fn can_tally_at(&self, date: BlockDate) -> bool {
+        self.phase_at(date) == Phase::Tallying
+    }
+
     /// tells if it is possible to vote at the given date
     ///
     /// `[vote_start..vote_end[`: from the start date (included) to
     /// the end (not included).
     #[inline]
     pub fn can_vote(&self, date: BlockDate) -> bool {
-        self.vote_started(date) && !self.vote_finished(date)
+        self.can_vote_at(date)
     }
 
     #[inline]
@@ -295,7 +337,7 @@ impl VotePlan {
     ///
     #[inline]
     pub fn committee_time(&self, date: BlockDate) -> bool {
-        self.committee_started(date) && !self.committee_finished(date)
+        self.can_tally_at(date)
     }
 
     pub fn serialize_in(&self, bb: ByteBuilder<Self>) -> ByteBuilder<Self> {
@@ -329,6 +371,12 @@ impl VotePlan {
     }
 }
 
+impl SerializedSize for VotePlan {
+    fn serialized_size(&self) -> usize {
+        self.serialize_in(ByteBuilder::new()).len()
+    }
+}
+
 impl VotePlanProof {
     pub fn serialize_in(&self, bb: ByteBuilder<Self>) -> ByteBuilder<Self> {
         bb.bytes(self.id.as_ref()).bytes(self.signature.as_ref())
@@ -381,64 +429,85 @@ impl Deref for Proposals {
 
 /* Ser/De ******************************************************************* */
 
-impl property::Serialize for VotePlan {
-    type Error = std::io::Error;
-    fn serialize<W: std::io::Write>(&self, mut writer: W) -> Result<(), Self::Error> {
-        writer.write_all(self.serialize().as_slice())?;
-        Ok(())
+impl Pack for VotePlan {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(self.serialize().as_slice())
+    }
+}
+
+impl Pack for VotePlanProof {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(self.serialize_in(ByteBuilder::new()).finalize().as_slice())
     }
 }
 
-impl Readable for VotePlanProof {
-    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        let id = vote::CommitteeId::read(buf)?;
-        let signature = SingleAccountBindingSignature::read(buf)?;
+impl Unpack for VotePlanProof {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let id = vote::CommitteeId::unpack(source)?;
+        let signature = SingleAccountBindingSignature::unpack(source)?;
         Ok(Self { id, signature })
     }
 }
 
-impl Readable for VoteAction {
-    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        match buf.get_u8()? {
+chain_ser::impl_legacy_codecs!(VotePlanProof);
+
+impl Pack for VoteAction {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(&self.serialize_in(ByteBuilder::new()).finalize_as_vec())
+    }
+}
+
+impl Unpack for VoteAction {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        match source.take_u8()? {
             0 => Ok(Self::OffChain),
-            1 => TreasuryGovernanceAction::read(buf).map(|action| Self::Treasury { action }),
-            2 => ParametersGovernanceAction::read(buf).map(|action| Self::Parameters { action }),
-            t => Err(ReadError::UnknownTag(t as u32)),
+            1 => TreasuryGovernanceAction::unpack(source).map(|action| Self::Treasury { action }),
+            2 => {
+                ParametersGovernanceAction::unpack(source).map(|action| Self::Parameters { action })
+            }
+            t => Err(ReadError::UnknownTag {
+                kind: "vote action",
+                tag: t as u32,
+            }),
         }
     }
 }
 
-impl Readable for VotePlan {
-    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
+chain_ser::impl_legacy_codecs!(VoteAction);
+
+impl Unpack for VotePlan {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
         let vote_start = BlockDate {
-            epoch: buf.get_u32()?,
-            slot_id: buf.get_u32()?,
+            epoch: source.take_u32()?,
+            slot_id: source.take_u32()?,
         };
         let vote_end = BlockDate {
-            epoch: buf.get_u32()?,
-            slot_id: buf.get_u32()?,
+            epoch: source.take_u32()?,
+            slot_id: source.take_u32()?,
         };
         let committee_end = BlockDate {
-            epoch: buf.get_u32()?,
-            slot_id: buf.get_u32()?,
+            epoch: source.take_u32()?,
+            slot_id: source.take_u32()?,
         };
 
-        let payload_type = buf
-            .get_u8()?
+        let payload_type = source
+            .take_u8()?
             .try_into()
-            .map_err(|e: vote::TryFromIntError| ReadError::StructureInvalid(e.to_string()))?;
+            .map_err(|e: vote::TryFromIntError| {
+                ReadError::StructureInvalid(e.to_string().into())
+            })?;
 
-        let proposal_size = buf.get_u8()? as usize;
+        let proposal_size = source.take_u8()? as usize;
         let mut proposals = Proposals {
             proposals: Vec::with_capacity(proposal_size),
         };
         for _ in 0..proposal_size {
-            let external_id = <[u8; 32]>::read(buf)?.into();
-            let options = buf.get_u8().and_then(|num_choices| {
+            let external_id = <[u8; 32]>::unpack(source)?.into();
+            let options = source.take_u8().and_then(|num_choices| {
                 vote::Options::new_length(num_choices)
-                    .map_err(|e| ReadError::StructureInvalid(e.to_string()))
+                    .map_err(|e| ReadError::StructureInvalid(e.to_string().into()))
             })?;
-            let action = VoteAction::read(buf)?;
+            let action = VoteAction::unpack(source)?;
 
             let proposal = Proposal {
                 external_id,
@@ -449,16 +518,19 @@ impl Readable for VotePlan {
             proposals.proposals.push(proposal);
         }
 
-        let member_keys_len = buf.get_u8()?;
+        let member_keys_len = source.take_u8()?;
         let mut committee_public_keys = Vec::new();
         for _ in 0..member_keys_len {
-            let key_buf = buf.get_slice(MemberPublicKey::BYTES_LEN)?;
-            committee_public_keys.push(MemberPublicKey::from_bytes(key_buf).ok_or_else(|| {
-                ReadError::StructureInvalid("invalid public key format".to_string())
-            })?);
+            let mut key_buf = [0; MemberPublicKey::BYTES_LEN];
+            source.take_into(&mut key_buf)?;
+            committee_public_keys.push(
+                MemberPublicKey::from_bytes(&key_buf).ok_or_else(|| {
+                    ReadError::StructureInvalid("invalid public key format".into())
+                })?,
+            );
         }
 
-        let voting_token = TokenIdentifier::read(buf)?;
+        let voting_token = TokenIdentifier::unpack(source)?;
 
         Ok(Self {
             vote_start,
@@ -472,6 +544,8 @@ impl Readable for VotePlan {
     }
 }
 
+chain_ser::impl_legacy_codecs!(VotePlan);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -479,6 +553,7 @@ mod tests {
     use crate::testing::VoteTestGen;
     use crate::tokens::name::{TokenName, TOKEN_NAME_MAX_SIZE};
     use crate::tokens::policy_hash::{PolicyHash, POLICY_HASH_SIZE};
+    use chain_core::mempack::{ReadBuf, Readable as _};
     use chain_core::property::BlockDate as BlockDateProp;
     use quickcheck_macros::quickcheck;
     use std::convert::TryFrom;
@@ -530,6 +605,46 @@ mod tests {
         assert!(vote_plan.committee_finished(committee_finished));
     }
 
+    #[quickcheck]
+    fn phase_at_matches_the_date_windows(vote_plan: VotePlan, date: BlockDate) -> bool {
+        let expected = if !vote_plan.vote_started(date) {
+            Phase::Before
+        } else if !vote_plan.vote_finished(date) {
+            Phase::Voting
+        } else if !vote_plan.committee_finished(date) {
+            Phase::Tallying
+        } else {
+            Phase::Closed
+        };
+        vote_plan.phase_at(date) == expected
+            && vote_plan.can_vote_at(date) == (expected == Phase::Voting)
+            && vote_plan.can_tally_at(date) == (expected == Phase::Tallying)
+    }
+
+    #[test]
+    pub fn vote_plan_phases_at_the_boundaries() {
+        let vote_start = BlockDate::first().next_epoch();
+        let vote_end = vote_start.next_epoch();
+        let committee_end = vote_end.next_epoch();
+        let vote_plan = VotePlan::new(
+            vote_start,
+            vote_end,
+            committee_end,
+            VoteTestGen::proposals(1),
+            vote::PayloadType::Public,
+            Vec::new(),
+            TokenIdentifier {
+                policy_hash: PolicyHash::from([0u8; POLICY_HASH_SIZE]),
+                token_name: TokenName::try_from(vec![0u8; TOKEN_NAME_MAX_SIZE]).unwrap(),
+            },
+        );
+
+        assert_eq!(vote_plan.phase_at(BlockDate::first()), Phase::Before);
+        assert_eq!(vote_plan.phase_at(vote_start), Phase::Voting);
+        assert_eq!(vote_plan.phase_at(vote_end), Phase::Tallying);
+        assert_eq!(vote_plan.phase_at(committee_end), Phase::Closed);
+    }
+
     #[test]
     pub fn correct_vote_plan_timeline() {
         let vote_start = BlockDate::from_epoch_slot_id(1, 0);
@@ -0,0 +1,151 @@
+use crate::transaction::{SingleAccountBindingSignature, TransactionBindingAuthData};
+use crate::vote::CommitteeId;
+use crate::{
+    certificate::{CertificateSlice, SerializedSize, VotePlanId},
+    transaction::{Payload, PayloadAuthData, PayloadData, PayloadSlice},
+};
+use chain_core::{
+    mempack::ReadError,
+    packer::Codec,
+    property::{ByteSource, Pack, Unpack},
+};
+use chain_crypto::Verification;
+use typed_bytes::{ByteArray, ByteBuilder};
+
+/// Emergency cancellation of a vote plan, before the votes are tallied.
+///
+/// The cancellation is signed by a majority of the committee of the vote
+/// plan, see [`VotePlanCancelProof::threshold`]. Once cancelled, the vote
+/// plan accepts no more votes nor tally and its proposals are reported as
+/// cancelled in its status, until it is removed from the ledger at the end
+/// of its committee time.
+#[derive(Debug, Eq, PartialEq, Hash, Clone)]
+pub struct VotePlanCancel {
+    vote_plan: VotePlanId,
+}
+
+/// The signatures of the committee members cancelling the vote plan
+#[derive(Debug, Clone)]
+pub struct VotePlanCancelProof {
+    pub signatures: Vec<(CommitteeId, SingleAccountBindingSignature)>,
+}
+
+impl VotePlanCancel {
+    pub fn new(vote_plan: VotePlanId) -> Self {
+        Self { vote_plan }
+    }
+
+    pub fn vote_plan(&self) -> &VotePlanId {
+        &self.vote_plan
+    }
+
+    pub fn serialize_in(&self, bb: ByteBuilder<Self>) -> ByteBuilder<Self> {
+        bb.bytes(self.vote_plan.as_ref())
+    }
+
+    pub fn serialize(&self) -> ByteArray<Self> {
+        self.serialize_in(ByteBuilder::new()).finalize()
+    }
+}
+
+impl SerializedSize for VotePlanCancel {
+    fn serialized_size(&self) -> usize {
+        self.serialize_in(ByteBuilder::new()).len()
+    }
+}
+
+impl VotePlanCancelProof {
+    /// number of the signatures of distinct committee members required to
+    /// cancel a vote plan of a committee of `committee_size` members, a
+    /// strict majority
+    pub fn threshold(committee_size: usize) -> usize {
+        committee_size / 2 + 1
+    }
+
+    pub fn serialize_in(&self, bb: ByteBuilder<Self>) -> ByteBuilder<Self> {
+        bb.iter8(self.signatures.iter(), |bb, (id, signature)| {
+            bb.bytes(id.as_ref()).bytes(signature.as_ref())
+        })
+    }
+
+    /// verify all the signatures, the signers being members of the committee
+    /// is checked by the ledger
+    pub fn verify<'a>(&self, verify_data: &TransactionBindingAuthData<'a>) -> Verification {
+        let verified = self.signatures.iter().all(|(id, signature)| {
+            signature.verify_slice(&id.public_key(), verify_data) == Verification::Success
+        });
+        if verified {
+            Verification::Success
+        } else {
+            Verification::Failed
+        }
+    }
+}
+
+/* Auth/Payload ************************************************************* */
+
+impl Payload for VotePlanCancel {
+    const HAS_DATA: bool = true;
+    const HAS_AUTH: bool = true;
+    type Auth = VotePlanCancelProof;
+
+    fn payload_data(&self) -> PayloadData<Self> {
+        PayloadData(
+            self.serialize_in(ByteBuilder::new())
+                .finalize_as_vec()
+                .into(),
+            std::marker::PhantomData,
+        )
+    }
+
+    fn payload_auth_data(auth: &Self::Auth) -> PayloadAuthData<Self> {
+        PayloadAuthData(
+            auth.serialize_in(ByteBuilder::new())
+                .finalize_as_vec()
+                .into(),
+            std::marker::PhantomData,
+        )
+    }
+
+    fn payload_to_certificate_slice(p: PayloadSlice<'_, Self>) -> Option<CertificateSlice<'_>> {
+        Some(CertificateSlice::from(p))
+    }
+}
+
+/* Ser/De ******************************************************************* */
+
+impl Pack for VotePlanCancel {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(self.serialize().as_slice())
+    }
+}
+
+impl Pack for VotePlanCancelProof {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(self.serialize_in(ByteBuilder::new()).finalize().as_slice())
+    }
+}
+
+impl Unpack for VotePlanCancelProof {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let len = source.take_u8()? as usize;
+        let mut signatures = Vec::with_capacity(len);
+        for _ in 0..len {
+            let id = CommitteeId::unpack(source)?;
+            let signature = SingleAccountBindingSignature::unpack(source)?;
+            signatures.push((id, signature));
+        }
+        Ok(Self { signatures })
+    }
+}
+
+chain_ser::impl_legacy_codecs!(VotePlanCancelProof);
+
+impl Unpack for VotePlanCancel {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let vote_plan = <[u8; 32]>::unpack(source)?.into();
+        Ok(Self::new(vote_plan))
+    }
+}
+
+chain_ser::impl_legacy_codecs!(VotePlanCancel);
@@ -1,5 +1,5 @@
 use crate::{
-    certificate::{CertificateSlice, VotePlanId},
+    certificate::{CertificateSlice, SerializedSize, VotePlanId},
     transaction::{
         Payload, PayloadAuthData, PayloadData, PayloadSlice, SingleAccountBindingSignature,
         TransactionBindingAuthData,
@@ -7,8 +7,9 @@ use crate::{
     vote::{CommitteeId, PayloadType, TryFromIntError},
 };
 use chain_core::{
-    mempack::{ReadBuf, ReadError, Readable},
-    property,
+    mempack::ReadError,
+    packer::Codec,
+    property::{ByteSource, Pack, Unpack},
 };
 use chain_crypto::Verification;
 use chain_vote::TallyDecryptShare;
@@ -133,6 +134,12 @@ impl VoteTally {
     }
 }
 
+impl SerializedSize for VoteTally {
+    fn serialized_size(&self) -> usize {
+        self.serialize_in(ByteBuilder::new()).len()
+    }
+}
+
 impl TallyProof {
     pub fn serialize_in(&self, bb: ByteBuilder<Self>) -> ByteBuilder<Self> {
         match self {
@@ -225,64 +232,69 @@ impl Payload for VoteTally {
 
 /* Ser/De ******************************************************************* */
 
-impl property::Serialize for VoteTally {
-    type Error = std::io::Error;
-    fn serialize<W: std::io::Write>(&self, mut writer: W) -> Result<(), Self::Error> {
-        writer.write_all(self.serialize().as_slice())?;
-        Ok(())
+impl Pack for VoteTally {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(self.serialize().as_slice())
+    }
+}
+
+impl Pack for TallyProof {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(self.serialize_in(ByteBuilder::new()).finalize().as_slice())
     }
 }
 
-impl Readable for TallyProof {
-    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        match buf.get_u8()? {
+impl Unpack for TallyProof {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        match source.take_u8()? {
             0 => {
-                let id = CommitteeId::read(buf)?;
-                let signature = SingleAccountBindingSignature::read(buf)?;
+                let id = CommitteeId::unpack(source)?;
+                let signature = SingleAccountBindingSignature::unpack(source)?;
                 Ok(Self::Public { id, signature })
             }
             1 => {
-                let id = CommitteeId::read(buf)?;
-                let signature = SingleAccountBindingSignature::read(buf)?;
+                let id = CommitteeId::unpack(source)?;
+                let signature = SingleAccountBindingSignature::unpack(source)?;
                 Ok(Self::Private { id, signature })
             }
-            _ => Err(ReadError::StructureInvalid(
-                "Unknown Tally proof type".to_owned(),
-            )),
+            tag => Err(ReadError::UnknownTag {
+                kind: "tally proof",
+                tag: tag as u32,
+            }),
         }
     }
 }
 
-impl Readable for VoteTally {
-    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        let id = <[u8; 32]>::read(buf)?.into();
-        let payload_type = buf
-            .get_u8()?
+chain_ser::impl_legacy_codecs!(TallyProof);
+
+impl Unpack for VoteTally {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let id = <[u8; 32]>::unpack(source)?.into();
+        let payload_type = source
+            .take_u8()?
             .try_into()
-            .map_err(|e: TryFromIntError| ReadError::StructureInvalid(e.to_string()))?;
+            .map_err(|e: TryFromIntError| ReadError::StructureInvalid(e.to_string().into()))?;
 
         let payload = match payload_type {
             PayloadType::Public => VoteTallyPayload::Public,
             PayloadType::Private => {
-                let proposals_number = buf.get_u8()? as usize;
+                let proposals_number = source.take_u8()? as usize;
                 let mut proposals = Vec::with_capacity(proposals_number);
                 for _i in 0..proposals_number {
-                    let shares_number = buf.get_u8()? as usize;
-                    let options_number = buf.get_u8()? as usize;
+                    let shares_number = source.take_u8()? as usize;
+                    let options_number = source.take_u8()? as usize;
                     let share_bytes = TallyDecryptShare::bytes_len(options_number);
                     let mut shares = Vec::with_capacity(shares_number);
                     for _j in 0..shares_number {
-                        let s_buf = buf.get_slice(share_bytes)?;
-                        let share = TallyDecryptShare::from_bytes(s_buf).ok_or_else(|| {
-                            ReadError::StructureInvalid(
-                                "invalid decrypt share structure".to_owned(),
-                            )
+                        let s_buf = source.take_vec(share_bytes)?;
+                        let share = TallyDecryptShare::from_bytes(&s_buf).ok_or_else(|| {
+                            ReadError::StructureInvalid("invalid decrypt share structure".into())
                         })?;
                         shares.push(share);
                     }
                     let mut decrypted = Vec::with_capacity(options_number);
                     for _j in 0..options_number {
-                        decrypted.push(buf.get_u64()?);
+                        decrypted.push(source.take_u64()?);
                     }
                     let shares = shares.into_boxed_slice();
                     let decrypted = decrypted.into_boxed_slice();
@@ -294,7 +306,7 @@ impl Readable for VoteTally {
 
                 VoteTallyPayload::Private {
                     inner: DecryptedPrivateTally::new(proposals)
-                        .map_err(|err| ReadError::InvalidData(err.to_string()))?,
+                        .map_err(|err| ReadError::InvalidData(err.to_string().into()))?,
                 }
             }
         };
@@ -302,3 +314,5 @@ impl Readable for VoteTally {
         Ok(Self { id, payload })
     }
 }
+
+chain_ser::impl_legacy_codecs!(VoteTally);
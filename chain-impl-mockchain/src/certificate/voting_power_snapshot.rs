@@ -0,0 +1,147 @@
+use crate::transaction::{SingleAccountBindingSignature, TransactionBindingAuthData};
+use crate::vote::{CommitteeId, VotingPowerRoot};
+use crate::{
+    certificate::{CertificateSlice, SerializedSize, VotePlanId},
+    transaction::{Payload, PayloadAuthData, PayloadData, PayloadSlice},
+    value::Value,
+};
+use chain_core::{
+    mempack::ReadError,
+    packer::Codec,
+    property::{ByteSource, Pack, Unpack},
+};
+use chain_crypto::Verification;
+use typed_bytes::{ByteArray, ByteBuilder};
+
+/// Snapshot of the voting power of the accounts for a vote plan, published
+/// by a member of the committee before the vote starts.
+///
+/// The votes for the plan are then weighted with the voting power proven
+/// against the `root`, see [`SnapshotVoteCast`](crate::certificate::SnapshotVoteCast).
+#[derive(Debug, Eq, PartialEq, Hash, Clone)]
+pub struct VotingPowerSnapshot {
+    vote_plan: VotePlanId,
+    root: VotingPowerRoot,
+    total_power: Value,
+}
+
+#[derive(Debug, Clone)]
+pub struct VotingPowerSnapshotProof {
+    pub id: CommitteeId,
+    pub signature: SingleAccountBindingSignature,
+}
+
+impl VotingPowerSnapshotProof {
+    pub fn serialize_in(&self, bb: ByteBuilder<Self>) -> ByteBuilder<Self> {
+        bb.bytes(self.id.as_ref()).bytes(self.signature.as_ref())
+    }
+
+    pub fn verify<'a>(&self, verify_data: &TransactionBindingAuthData<'a>) -> Verification {
+        let pk = self.id.public_key();
+        self.signature.verify_slice(&pk, verify_data)
+    }
+}
+
+impl VotingPowerSnapshot {
+    pub fn new(vote_plan: VotePlanId, root: VotingPowerRoot, total_power: Value) -> Self {
+        Self {
+            vote_plan,
+            root,
+            total_power,
+        }
+    }
+
+    pub fn vote_plan(&self) -> &VotePlanId {
+        &self.vote_plan
+    }
+
+    pub fn root(&self) -> &VotingPowerRoot {
+        &self.root
+    }
+
+    /// sum of the voting power of all the accounts in the snapshot
+    pub fn total_power(&self) -> Value {
+        self.total_power
+    }
+
+    pub fn serialize_in(&self, bb: ByteBuilder<Self>) -> ByteBuilder<Self> {
+        bb.bytes(self.vote_plan.as_ref())
+            .bytes(self.root.as_ref())
+            .u64(self.total_power.0)
+    }
+
+    pub fn serialize(&self) -> ByteArray<Self> {
+        self.serialize_in(ByteBuilder::new()).finalize()
+    }
+}
+
+impl SerializedSize for VotingPowerSnapshot {
+    fn serialized_size(&self) -> usize {
+        self.serialize_in(ByteBuilder::new()).len()
+    }
+}
+
+/* Auth/Payload ************************************************************* */
+
+impl Payload for VotingPowerSnapshot {
+    const HAS_DATA: bool = true;
+    const HAS_AUTH: bool = true;
+    type Auth = VotingPowerSnapshotProof;
+
+    fn payload_data(&self) -> PayloadData<Self> {
+        PayloadData(
+            self.serialize_in(ByteBuilder::new())
+                .finalize_as_vec()
+                .into(),
+            std::marker::PhantomData,
+        )
+    }
+
+    fn payload_auth_data(auth: &Self::Auth) -> PayloadAuthData<Self> {
+        PayloadAuthData(
+            auth.serialize_in(ByteBuilder::new())
+                .finalize_as_vec()
+                .into(),
+            std::marker::PhantomData,
+        )
+    }
+
+    fn payload_to_certificate_slice(p: PayloadSlice<'_, Self>) -> Option<CertificateSlice<'_>> {
+        Some(CertificateSlice::from(p))
+    }
+}
+
+/* Ser/De ******************************************************************* */
+
+impl Pack for VotingPowerSnapshot {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(self.serialize().as_slice())
+    }
+}
+
+impl Pack for VotingPowerSnapshotProof {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(self.serialize_in(ByteBuilder::new()).finalize().as_slice())
+    }
+}
+
+impl Unpack for VotingPowerSnapshotProof {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let id = CommitteeId::unpack(source)?;
+        let signature = SingleAccountBindingSignature::unpack(source)?;
+        Ok(Self { id, signature })
+    }
+}
+
+chain_ser::impl_legacy_codecs!(VotingPowerSnapshotProof);
+
+impl Unpack for VotingPowerSnapshot {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let vote_plan = <[u8; 32]>::unpack(source)?.into();
+        let root = <[u8; 32]>::unpack(source)?.into();
+        let total_power = Value::unpack(source)?;
+        Ok(Self::new(vote_plan, root, total_power))
+    }
+}
+
+chain_ser::impl_legacy_codecs!(VotingPowerSnapshot);
@@ -1,7 +1,7 @@
 use crate::certificate::PoolId;
 use crate::chaintypes::ChainLength;
 use crate::date::BlockDate;
-use crate::fragment::BlockContentHash;
+use crate::fragment::{BlockContentHash, ContentHashScheme};
 
 use crate::key::Hash;
 
@@ -53,6 +53,7 @@ pub struct HeaderContentEvalContext {
     pub(crate) block_date: BlockDate,
     pub(crate) chain_length: ChainLength,
     pub(crate) content_hash: BlockContentHash,
+    pub(crate) content_hash_scheme: ContentHashScheme,
     pub(crate) consensus_eval_context: ConsensusEvalContext,
 }
 
@@ -95,6 +96,7 @@ mod test {
                 chain_length: Arbitrary::arbitrary(g),
                 consensus_eval_context: Arbitrary::arbitrary(g),
                 content_hash: Arbitrary::arbitrary(g),
+                content_hash_scheme: ContentHashScheme::Sequential,
             }
         }
     }
@@ -3,10 +3,13 @@
 //! Common types that are found in the header but are used or stored in the
 //! ledger for either validation or some state tracking
 
-use crate::key::Hash;
+use crate::key::Id;
+use chain_crypto::Blake2b256;
 use strum_macros::{Display, EnumString, IntoStaticStr};
 
-pub type HeaderId = Hash; // TODO: change to DigestOf<Blake2b256, Header>
+/// Identifier of a header, computed with the hash algorithm `H` (by default
+/// `Blake2b256`)
+pub type HeaderId<H = Blake2b256> = Id<H>;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ChainLength(pub(crate) u32);
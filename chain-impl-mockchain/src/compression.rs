@@ -0,0 +1,209 @@
+//! Transparent compression of the serialized blocks and ledger snapshots.
+//!
+//! A compressed value is written in an envelope: the
+//! [`COMPRESSION_MAGIC`], the [`CompressionAlgorithm`] byte, then the
+//! compressed stream of the usual serialization of the value. The magic
+//! cannot start a serialized block (it would declare a header far bigger
+//! than any header) nor a ledger snapshot (it is not an entry code), so the
+//! [`Deserialize`](chain_core::property::Deserialize) implementations of
+//! [`Block`](crate::block::Block) and [`Ledger`](crate::ledger::Ledger)
+//! accept both the compressed and the uncompressed data, see
+//! [`decompressing_reader`]. The data is decompressed as it is read.
+//!
+//! The zstd compression needs the `compression` feature. Without it, the
+//! envelopes are still recognized, and reading one fails with an
+//! `Unsupported` error instead of a confusing decoding error.
+
+use std::io::{BufRead, Chain, Cursor, Read};
+
+/// Magic written as the first bytes of every compressed envelope
+pub const COMPRESSION_MAGIC: [u8; 4] = *b"JCMP";
+
+/// Size of the envelope before the compressed stream
+pub const COMPRESSION_ENVELOPE_PREFIX_SIZE: usize = COMPRESSION_MAGIC.len() + 1;
+
+/// The compression of the stream following the magic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionAlgorithm {
+    Zstd = 1,
+}
+
+impl CompressionAlgorithm {
+    pub fn from_u8(n: u8) -> Option<Self> {
+        match n {
+            1 => Some(CompressionAlgorithm::Zstd),
+            _ => None,
+        }
+    }
+}
+
+/// Whether the bytes start with a compressed envelope
+pub fn is_compressed(bytes: &[u8]) -> bool {
+    bytes.starts_with(&COMPRESSION_MAGIC)
+}
+
+type Prefixed<R> = Chain<Cursor<Vec<u8>>, R>;
+
+/// A reader of the uncompressed data, see [`decompressing_reader`]
+pub struct Decompressing<R: BufRead>(DecompressingInner<R>);
+
+enum DecompressingInner<R: BufRead> {
+    Plain(Prefixed<R>),
+    #[cfg(feature = "compression")]
+    Zstd(std::io::BufReader<zstd::stream::read::Decoder<'static, R>>),
+}
+
+/// Read the uncompressed data of the reader, decompressing it if it starts
+/// with a compressed envelope, or as is otherwise.
+///
+/// Only the first bytes of the reader are read by this function, the rest
+/// is decompressed as it is read from the returned reader.
+pub fn decompressing_reader<R: BufRead>(mut reader: R) -> Result<Decompressing<R>, std::io::Error> {
+    let mut prefix = Vec::with_capacity(COMPRESSION_MAGIC.len());
+    (&mut reader)
+        .take(COMPRESSION_MAGIC.len() as u64)
+        .read_to_end(&mut prefix)?;
+    if prefix != COMPRESSION_MAGIC {
+        return Ok(Decompressing(DecompressingInner::Plain(
+            Cursor::new(prefix).chain(reader),
+        )));
+    }
+
+    let mut algorithm = [0u8; 1];
+    reader.read_exact(&mut algorithm)?;
+    match CompressionAlgorithm::from_u8(algorithm[0]) {
+        None => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("unknown compression algorithm {}", algorithm[0]),
+        )),
+        #[cfg(feature = "compression")]
+        Some(CompressionAlgorithm::Zstd) => {
+            let decoder = zstd::stream::read::Decoder::with_buffer(reader)?.single_frame();
+            Ok(Decompressing(DecompressingInner::Zstd(
+                std::io::BufReader::new(decoder),
+            )))
+        }
+        #[cfg(not(feature = "compression"))]
+        Some(algorithm) => Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            format!(
+                "{:?} compressed data cannot be read without the compression feature",
+                algorithm
+            ),
+        )),
+    }
+}
+
+impl<R: BufRead> Read for Decompressing<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match &mut self.0 {
+            DecompressingInner::Plain(reader) => reader.read(buf),
+            #[cfg(feature = "compression")]
+            DecompressingInner::Zstd(reader) => reader.read(buf),
+        }
+    }
+}
+
+impl<R: BufRead> BufRead for Decompressing<R> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        match &mut self.0 {
+            DecompressingInner::Plain(reader) => reader.fill_buf(),
+            #[cfg(feature = "compression")]
+            DecompressingInner::Zstd(reader) => reader.fill_buf(),
+        }
+    }
+
+    fn consume(&mut self, amt: usize) {
+        match &mut self.0 {
+            DecompressingInner::Plain(reader) => reader.consume(amt),
+            #[cfg(feature = "compression")]
+            DecompressingInner::Zstd(reader) => reader.consume(amt),
+        }
+    }
+}
+
+/// The zstd compression level, from 1 (fastest) to 22 (smallest), the
+/// default is 3
+#[cfg(feature = "compression")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompressionLevel(pub i32);
+
+#[cfg(feature = "compression")]
+impl Default for CompressionLevel {
+    fn default() -> Self {
+        CompressionLevel(zstd::DEFAULT_COMPRESSION_LEVEL)
+    }
+}
+
+/// Write the serialization of the value in a zstd compressed envelope,
+/// readable by the [`Deserialize`](chain_core::property::Deserialize)
+/// implementation of the value
+#[cfg(feature = "compression")]
+pub fn serialize_compressed<T, W>(
+    value: &T,
+    mut writer: W,
+    level: CompressionLevel,
+) -> Result<W, std::io::Error>
+where
+    T: chain_core::property::Serialize<Error = std::io::Error>,
+    W: std::io::Write,
+{
+    writer.write_all(&COMPRESSION_MAGIC)?;
+    writer.write_all(&[CompressionAlgorithm::Zstd as u8])?;
+    let mut encoder = zstd::stream::write::Encoder::new(writer, level.0)?;
+    value.serialize(&mut encoder)?;
+    encoder.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uncompressed_data_is_read_as_is() {
+        let data: &[u8] = &[0x4a, 0x43, 0x4d];
+        let mut read = Vec::new();
+        decompressing_reader(data)
+            .unwrap()
+            .read_to_end(&mut read)
+            .unwrap();
+        assert_eq!(read, data);
+        assert!(!is_compressed(data));
+    }
+
+    #[test]
+    fn unknown_algorithm_is_rejected() {
+        let data: &[u8] = b"JCMP\x07rest";
+        let error = decompressing_reader(data).err().unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn compressed_data_is_decompressed() {
+        let data = vec![42u8; 4096];
+        let mut writer = Vec::new();
+        writer.extend_from_slice(&COMPRESSION_MAGIC);
+        writer.push(CompressionAlgorithm::Zstd as u8);
+        let mut encoder = zstd::stream::write::Encoder::new(writer, 3).unwrap();
+        std::io::Write::write_all(&mut encoder, &data).unwrap();
+        let compressed = encoder.finish().unwrap();
+        assert!(is_compressed(&compressed));
+        assert!(compressed.len() < data.len());
+
+        let mut read = Vec::new();
+        decompressing_reader(compressed.as_slice())
+            .unwrap()
+            .read_to_end(&mut read)
+            .unwrap();
+        assert_eq!(read, data);
+    }
+
+    #[cfg(not(feature = "compression"))]
+    #[test]
+    fn compressed_data_needs_the_feature() {
+        let data: &[u8] = b"JCMP\x01rest";
+        let error = decompressing_reader(data).err().unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::Unsupported);
+    }
+}
@@ -1,8 +1,10 @@
+use crate::certificate::CertificatePolicy;
 use crate::date::Epoch;
 #[cfg(feature = "evm")]
 use crate::evm::{BlockGasLimit, Config, GasPrice};
 use crate::key::BftLeaderId;
 use crate::milli::Milli;
+use crate::names::NameRegistryParams;
 use crate::rewards::{Ratio, TaxType};
 use crate::value::Value;
 use crate::{
@@ -11,18 +13,22 @@ use crate::{
     vote::CommitteeId,
 };
 use chain_addr::Discrimination;
-use chain_core::mempack::{ReadBuf, ReadError, Readable};
+use chain_core::mempack::{ReadBuf, ReadError};
 use chain_core::packer::Codec;
-use chain_core::property;
+use chain_core::property::{ByteSource, Pack, Unpack};
 use chain_crypto::PublicKey;
 use std::{
     fmt::{self, Display, Formatter},
-    io::{self, Cursor, Write},
+    io::{self, Write},
     num::{NonZeroU32, NonZeroU64},
 };
-use strum_macros::{AsRefStr, EnumIter, EnumString};
+use strum_macros::{AsRefStr, EnumIter, EnumString, IntoStaticStr};
 use typed_bytes::ByteBuilder;
 
+mod registry;
+
+pub use registry::{registry, Activation, ConfigParamDoc};
+
 /// Possible errors
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Error {
@@ -47,6 +53,28 @@ impl Display for Error {
 
 impl std::error::Error for Error {}
 
+/// The payload of a config parameter is too long to be serialized, see
+/// [`Tag::max_payload_len`]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct PayloadTooLong {
+    pub tag: Tag,
+    pub len: usize,
+}
+
+impl Display for PayloadTooLong {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Config parameter '{}' payload of {} bytes exceeds the maximum of {} bytes",
+            self.tag.as_ref(),
+            self.len,
+            self.tag.max_payload_len()
+        )
+    }
+}
+
+impl std::error::Error for PayloadTooLong {}
+
 impl From<ReadError> for Error {
     fn from(_: ReadError) -> Self {
         Error::StructureInvalid
@@ -55,7 +83,7 @@ impl From<ReadError> for Error {
 
 impl From<Error> for ReadError {
     fn from(error: Error) -> ReadError {
-        ReadError::StructureInvalid(error.to_string())
+        ReadError::StructureInvalid(error.to_string().into())
     }
 }
 
@@ -91,6 +119,32 @@ pub enum ConfigParam {
     EvmConfiguration(EvmConfig),
     #[cfg(feature = "evm")]
     EvmEnvironment(EvmEnvSettings),
+    NameRegistryParams(NameRegistryParams),
+    /// fee per byte of certificate, 0 to disable
+    PerCertificateByteFee(u64),
+    /// minimum stake the owners of a pool need to pledge, 0 to disable
+    MinimumPoolPledge(Value),
+    /// scale the rewards of the pools by the ratio of the blocks they
+    /// produced to the blocks they were expected to produce
+    PoolPerformanceRewards(bool),
+    /// total supply of the chain, the treasury, the reward pot and the
+    /// initial funds of the block0 cannot exceed it
+    TotalSupply(Value),
+    /// number of BFT leaders which need to sign a block, the slot leader
+    /// included, the other leaders endorsing the block in its header
+    BftQuorum(u8),
+    /// version of the ledger rules, only raised by the update proposals
+    ProtocolVersion(u32),
+    /// certificate kinds disabled on the chain, replacing the previous policy
+    CertificatePolicy(CertificatePolicy),
+    /// vote plans a committee member can submit in an epoch, 0 to disable
+    MaxVotePlansPerEpoch(u32),
+    /// proposals of a vote plan, 0 to disable
+    MaxProposalsPerVotePlan(u32),
+    /// slots at the start of an epoch over which the active slots
+    /// coefficient moves from the one of the previous epoch to the new one,
+    /// 0 for an immediate change
+    ActiveSlotsCoeffTransition(u32),
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -151,7 +205,7 @@ pub struct EvmEnvSettings {
 }
 
 // Discriminants can NEVER be 1024 or higher
-#[derive(AsRefStr, Clone, Copy, Debug, EnumIter, EnumString, PartialEq)]
+#[derive(AsRefStr, Clone, Copy, Debug, EnumIter, EnumString, IntoStaticStr, PartialEq, Eq)]
 pub enum Tag {
     #[strum(to_string = "discrimination")]
     Discrimination = 1,
@@ -211,9 +265,38 @@ pub enum Tag {
     #[cfg(feature = "evm")]
     #[strum(to_string = "evm-environment-params")]
     EvmEnvironment = 31,
+    #[strum(to_string = "name-registry-params")]
+    NameRegistryParams = 32,
+    #[strum(to_string = "per-certificate-byte-fee")]
+    PerCertificateByteFee = 33,
+    #[strum(to_string = "minimum-pool-pledge")]
+    MinimumPoolPledge = 34,
+    #[strum(to_string = "pool-performance-rewards")]
+    PoolPerformanceRewards = 35,
+    #[strum(to_string = "total-supply")]
+    TotalSupply = 36,
+    #[strum(to_string = "bft-quorum")]
+    BftQuorum = 37,
+    #[strum(to_string = "protocol-version")]
+    ProtocolVersion = 38,
+    #[strum(to_string = "certificate-policy")]
+    CertificatePolicy = 39,
+    #[strum(to_string = "max-vote-plans-per-epoch")]
+    MaxVotePlansPerEpoch = 40,
+    #[strum(to_string = "max-proposals-per-vote-plan")]
+    MaxProposalsPerVotePlan = 41,
+    #[strum(to_string = "active-slots-coeff-transition")]
+    ActiveSlotsCoeffTransition = 42,
 }
 
 impl Tag {
+    /// The maximum length of the payload of a parameter with this tag. The
+    /// length is serialized in 9 bits next to the tag, so it is currently
+    /// [`MAX_PAYLOAD_LEN`] for all the tags.
+    pub const fn max_payload_len(self) -> usize {
+        MAX_PAYLOAD_LEN
+    }
+
     pub fn from_u16(v: u16) -> Option<Self> {
         match v {
             1 => Some(Tag::Discrimination),
@@ -246,6 +329,17 @@ impl Tag {
             30 => Some(Tag::EvmConfiguration),
             #[cfg(feature = "evm")]
             31 => Some(Tag::EvmEnvironment),
+            32 => Some(Tag::NameRegistryParams),
+            33 => Some(Tag::PerCertificateByteFee),
+            34 => Some(Tag::MinimumPoolPledge),
+            35 => Some(Tag::PoolPerformanceRewards),
+            36 => Some(Tag::TotalSupply),
+            37 => Some(Tag::BftQuorum),
+            38 => Some(Tag::ProtocolVersion),
+            39 => Some(Tag::CertificatePolicy),
+            40 => Some(Tag::MaxVotePlansPerEpoch),
+            41 => Some(Tag::MaxProposalsPerVotePlan),
+            42 => Some(Tag::ActiveSlotsCoeffTransition),
             _ => None,
         }
     }
@@ -286,15 +380,26 @@ impl<'a> From<&'a ConfigParam> for Tag {
             ConfigParam::EvmConfiguration(_) => Tag::EvmConfiguration,
             #[cfg(feature = "evm")]
             ConfigParam::EvmEnvironment(_) => Tag::EvmEnvironment,
+            ConfigParam::NameRegistryParams(_) => Tag::NameRegistryParams,
+            ConfigParam::PerCertificateByteFee(_) => Tag::PerCertificateByteFee,
+            ConfigParam::MinimumPoolPledge(_) => Tag::MinimumPoolPledge,
+            ConfigParam::PoolPerformanceRewards(_) => Tag::PoolPerformanceRewards,
+            ConfigParam::TotalSupply(_) => Tag::TotalSupply,
+            ConfigParam::BftQuorum(_) => Tag::BftQuorum,
+            ConfigParam::ProtocolVersion(_) => Tag::ProtocolVersion,
+            ConfigParam::CertificatePolicy(_) => Tag::CertificatePolicy,
+            ConfigParam::MaxVotePlansPerEpoch(_) => Tag::MaxVotePlansPerEpoch,
+            ConfigParam::MaxProposalsPerVotePlan(_) => Tag::MaxProposalsPerVotePlan,
+            ConfigParam::ActiveSlotsCoeffTransition(_) => Tag::ActiveSlotsCoeffTransition,
         }
     }
 }
 
-impl Readable for ConfigParam {
-    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        let taglen = TagLen(buf.get_u16()?);
-        let bytes = buf.get_slice(taglen.get_len())?;
-        match taglen.get_tag()? {
+impl ConfigParam {
+    /// Decode the parameter from its tag and payload, shared by all the
+    /// decoders of the parameters.
+    fn from_tag_payload(tag: Tag, bytes: &[u8]) -> Result<Self, Error> {
+        match tag {
             Tag::Block0Date => ConfigParamVariant::from_payload(bytes).map(ConfigParam::Block0Date),
             Tag::Discrimination => {
                 ConfigParamVariant::from_payload(bytes).map(ConfigParam::Discrimination)
@@ -377,17 +482,65 @@ impl Readable for ConfigParam {
             Tag::EvmEnvironment => {
                 ConfigParamVariant::from_payload(bytes).map(ConfigParam::EvmEnvironment)
             }
+            Tag::NameRegistryParams => {
+                ConfigParamVariant::from_payload(bytes).map(ConfigParam::NameRegistryParams)
+            }
+            Tag::PerCertificateByteFee => {
+                ConfigParamVariant::from_payload(bytes).map(ConfigParam::PerCertificateByteFee)
+            }
+            Tag::MinimumPoolPledge => {
+                ConfigParamVariant::from_payload(bytes).map(ConfigParam::MinimumPoolPledge)
+            }
+            Tag::PoolPerformanceRewards => {
+                ConfigParamVariant::from_payload(bytes).map(ConfigParam::PoolPerformanceRewards)
+            }
+            Tag::TotalSupply => {
+                ConfigParamVariant::from_payload(bytes).map(ConfigParam::TotalSupply)
+            }
+            Tag::BftQuorum => ConfigParamVariant::from_payload(bytes).map(ConfigParam::BftQuorum),
+            Tag::ProtocolVersion => {
+                ConfigParamVariant::from_payload(bytes).map(ConfigParam::ProtocolVersion)
+            }
+            Tag::CertificatePolicy => {
+                ConfigParamVariant::from_payload(bytes).map(ConfigParam::CertificatePolicy)
+            }
+            Tag::MaxVotePlansPerEpoch => {
+                ConfigParamVariant::from_payload(bytes).map(ConfigParam::MaxVotePlansPerEpoch)
+            }
+            Tag::MaxProposalsPerVotePlan => {
+                ConfigParamVariant::from_payload(bytes).map(ConfigParam::MaxProposalsPerVotePlan)
+            }
+            Tag::ActiveSlotsCoeffTransition => {
+                ConfigParamVariant::from_payload(bytes).map(ConfigParam::ActiveSlotsCoeffTransition)
+            }
         }
-        .map_err(Into::into)
     }
 }
 
-impl property::Serialize for ConfigParam {
-    type Error = io::Error;
+impl Unpack for ConfigParam {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let taglen = TagLen(source.take_u16()?);
+        let tag = taglen.get_tag()?;
+        source.with_slice(taglen.get_len(), |buf| {
+            ConfigParam::from_tag_payload(tag, buf.get_slice_end()).map_err(Into::into)
+        })
+    }
+}
 
-    fn serialize<W: Write>(&self, writer: W) -> Result<(), Self::Error> {
-        let tag = Tag::from(self);
-        let bytes = match self {
+impl Pack for ConfigParam {
+    fn pack<W: Write>(&self, codec: &mut Codec<W>) -> Result<(), io::Error> {
+        let bytes = self.to_payload();
+        let taglen = TagLen::new(Tag::from(self), bytes.len())
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        codec.put_be_u16(taglen.0)?;
+        codec.put_bytes(&bytes)
+    }
+}
+
+impl ConfigParam {
+    /// The payload of the parameter, serialized after its tag and length
+    fn to_payload(&self) -> Vec<u8> {
+        match self {
             ConfigParam::Block0Date(data) => data.to_payload(),
             ConfigParam::Discrimination(data) => data.to_payload(),
             ConfigParam::ConsensusVersion(data) => data.to_payload(),
@@ -418,47 +571,30 @@ impl property::Serialize for ConfigParam {
             ConfigParam::EvmConfiguration(data) => data.to_payload(),
             #[cfg(feature = "evm")]
             ConfigParam::EvmEnvironment(data) => data.to_payload(),
-        };
-        let taglen = TagLen::new(tag, bytes.len()).ok_or_else(|| {
-            io::Error::new(
-                io::ErrorKind::InvalidData,
-                "initial ent payload too big".to_string(),
-            )
-        })?;
-        let mut codec = Codec::new(writer);
-        codec.put_be_u16(taglen.0)?;
-        codec.write_all(&bytes)
+            ConfigParam::NameRegistryParams(data) => data.to_payload(),
+            ConfigParam::PerCertificateByteFee(data) => data.to_payload(),
+            ConfigParam::MinimumPoolPledge(data) => data.to_payload(),
+            ConfigParam::PoolPerformanceRewards(data) => data.to_payload(),
+            ConfigParam::TotalSupply(data) => data.to_payload(),
+            ConfigParam::BftQuorum(data) => data.to_payload(),
+            ConfigParam::ProtocolVersion(data) => data.to_payload(),
+            ConfigParam::CertificatePolicy(data) => data.to_payload(),
+            ConfigParam::MaxVotePlansPerEpoch(data) => data.to_payload(),
+            ConfigParam::MaxProposalsPerVotePlan(data) => data.to_payload(),
+            ConfigParam::ActiveSlotsCoeffTransition(data) => data.to_payload(),
+        }
     }
-}
 
-impl property::Deserialize for ConfigParam {
-    type Error = std::io::Error;
-
-    fn deserialize<R: std::io::BufRead>(reader: R) -> Result<Self, Self::Error> {
-        let mut codec = Codec::new(reader);
-        let tag_len = TagLen(codec.get_be_u16()?);
-        let len = tag_len.get_len();
-        let bytes = codec.get_bytes(len)?;
-        // we will replicate the buffer so we can reuse the reader method
-        let mut cursor = Cursor::new(Vec::with_capacity(2 + len));
-        {
-            let mut writer = Codec::new(&mut cursor);
-            writer.put_be_u16(tag_len.0)?;
-            writer.put_bytes(&bytes)?;
-        }
-        cursor.set_position(0);
-        let mut read_buff = ReadBuf::from(cursor.get_ref());
-        match ConfigParam::read(&mut read_buff) {
-            Ok(res) => Ok(res),
-            Err(err) => Err(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("Error reading ConfigParam: {}", err),
-            )),
-        }
+    /// Check the payload of the parameter is not too long to be serialized,
+    /// the serialization of the parameter fails with this error otherwise
+    pub fn check_payload_len(&self) -> Result<(), PayloadTooLong> {
+        TagLen::new(Tag::from(self), self.to_payload().len()).map(|_| ())
     }
 }
 
-trait ConfigParamVariant: Clone + Eq + PartialEq {
+chain_ser::impl_legacy_codecs!(ConfigParam);
+
+pub(crate) trait ConfigParamVariant: Clone + Eq + PartialEq {
     fn to_payload(&self) -> Vec<u8>;
     fn from_payload(payload: &[u8]) -> Result<Self, Error>;
 }
@@ -484,7 +620,7 @@ impl ConfigParamVariant for TaxType {
 
     fn from_payload(payload: &[u8]) -> Result<Self, Error> {
         let mut rb = ReadBuf::from(payload);
-        let tax_type = TaxType::read_frombuf(&mut rb)?;
+        let tax_type = TaxType::unpack(&mut rb)?;
         rb.expect_end()?;
         Ok(tax_type)
     }
@@ -745,6 +881,7 @@ impl ConfigParamVariant for LinearFee {
             certificate: u64::from_payload(&payload[16..24])?,
             per_certificate_fees: PerCertificateFee::default(),
             per_vote_certificate_fees: PerVoteCertificateFee::default(),
+            per_certificate_byte_fee: None,
         })
     }
 }
@@ -866,17 +1003,53 @@ impl ConfigParamVariant for EvmEnvSettings {
     }
 }
 
+impl ConfigParamVariant for CertificatePolicy {
+    fn to_payload(&self) -> Vec<u8> {
+        self.to_bits().to_payload()
+    }
+
+    fn from_payload(payload: &[u8]) -> Result<Self, Error> {
+        u32::from_payload(payload)
+            .and_then(|bits| CertificatePolicy::from_bits(bits).ok_or(Error::StructureInvalid))
+    }
+}
+
+impl ConfigParamVariant for NameRegistryParams {
+    fn to_payload(&self) -> Vec<u8> {
+        let bb: ByteBuilder<NameRegistryParams> = ByteBuilder::new();
+        bb.u64(self.base_fee)
+            .u64(self.short_name_fee)
+            .u32(self.duration.get())
+            .finalize_as_vec()
+    }
+
+    fn from_payload(payload: &[u8]) -> Result<Self, Error> {
+        let mut rb = ReadBuf::from(payload);
+        let base_fee = rb.get_u64()?;
+        let short_name_fee = rb.get_u64()?;
+        let duration = rb.get_nz_u32()?;
+        rb.expect_end()?;
+        Ok(NameRegistryParams {
+            base_fee,
+            short_name_fee,
+            duration,
+        })
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 struct TagLen(u16);
 
-const MAXIMUM_LEN: usize = 512;
+/// The maximum length of the payload of a config parameter, see
+/// [`Tag::max_payload_len`]
+pub const MAX_PAYLOAD_LEN: usize = 511;
 
 impl TagLen {
-    pub fn new(tag: Tag, len: usize) -> Option<Self> {
-        if len < MAXIMUM_LEN {
-            Some(TagLen((tag as u16) << 9 | len as u16))
+    pub fn new(tag: Tag, len: usize) -> Result<Self, PayloadTooLong> {
+        if len <= tag.max_payload_len() {
+            Ok(TagLen((tag as u16) << 9 | len as u16))
         } else {
-            None
+            Err(PayloadTooLong { tag, len })
         }
     }
 
@@ -892,6 +1065,7 @@ impl TagLen {
 #[cfg(any(test, feature = "property-test-api"))]
 mod test {
     use super::*;
+    use crate::certificate::CertificateKind;
     #[cfg(test)]
     use quickcheck::TestResult;
     use quickcheck::{Arbitrary, Gen};
@@ -906,9 +1080,39 @@ mod test {
         assert_eq!(evm_params, other_evm);
     }
 
+    #[test]
+    fn tag_len_payload_too_long() {
+        let tag = Tag::AddBftLeader;
+        assert!(TagLen::new(tag, tag.max_payload_len()).is_ok());
+        assert_eq!(
+            TagLen::new(tag, MAX_PAYLOAD_LEN + 1),
+            Err(PayloadTooLong {
+                tag,
+                len: MAX_PAYLOAD_LEN + 1
+            })
+        );
+    }
+
+    #[test]
+    fn arbitrary_config_params_cover_every_tag() {
+        let mut g = quickcheck::StdThreadGen::new(10);
+        let tags: std::collections::BTreeSet<u16> =
+            std::iter::repeat_with(|| Tag::from(&ConfigParam::arbitrary(&mut g)) as u16)
+                .take(10_000)
+                .collect();
+        let missing: Vec<Tag> = Tag::iter()
+            .filter(|tag| !tags.contains(&(*tag as u16)))
+            .collect();
+        assert!(
+            missing.is_empty(),
+            "no arbitrary parameter for {:?}",
+            missing
+        );
+    }
+
     quickcheck! {
         fn tag_len_computation_correct(tag: Tag, len: usize) -> TestResult {
-            let len = len % MAXIMUM_LEN;
+            let len = len % (MAX_PAYLOAD_LEN + 1);
             let tag_len = TagLen::new(tag, len).unwrap();
 
             assert_eq!(Ok(tag), tag_len.get_tag(), "Invalid tag");
@@ -916,6 +1120,10 @@ mod test {
             TestResult::passed()
         }
 
+        fn config_param_payload_fits(param: ConfigParam) -> bool {
+            param.check_payload_len().is_ok()
+        }
+
         fn linear_fee_to_payload_from_payload(fee: LinearFee) -> TestResult {
             let payload = fee.to_payload();
             let decoded = LinearFee::from_payload(&payload).unwrap();
@@ -939,8 +1147,19 @@ mod test {
             param == decoded
         }
 
+        fn config_param_deserialize_consecutive(first: ConfigParam, second: ConfigParam) -> bool {
+            use chain_core::property::{Serialize as _, Deserialize as _};
+            let mut bytes = first.serialize_as_vec().unwrap();
+            bytes.extend(second.serialize_as_vec().unwrap());
+            let mut reader = bytes.as_slice();
+
+            ConfigParam::deserialize(&mut reader).unwrap() == first
+                && ConfigParam::deserialize(&mut reader).unwrap() == second
+                && reader.is_empty()
+        }
+
         fn config_param_serialize_readable(param: ConfigParam) -> bool {
-            use chain_core::property::Serialize as _;
+            use chain_core::{mempack::Readable as _, property::Serialize as _};
             let bytes = param.serialize_as_vec().unwrap();
             let mut reader = ReadBuf::from(&bytes);
             let decoded = ConfigParam::read(&mut reader).unwrap();
@@ -1015,7 +1234,9 @@ mod test {
 
     impl Arbitrary for ConfigParam {
         fn arbitrary<G: Gen>(g: &mut G) -> Self {
-            match u8::arbitrary(g) % 30 {
+            // every tag has an arm, so the codecs are tested with all of them
+            const VARIANTS: u8 = if cfg!(feature = "evm") { 43 } else { 41 };
+            match u8::arbitrary(g) % VARIANTS {
                 0 => ConfigParam::Block0Date(Arbitrary::arbitrary(g)),
                 1 => ConfigParam::Discrimination(Arbitrary::arbitrary(g)),
                 2 => ConfigParam::ConsensusVersion(Arbitrary::arbitrary(g)),
@@ -1041,9 +1262,9 @@ mod test {
                 16 => ConfigParam::AddCommitteeId(Arbitrary::arbitrary(g)),
                 17 => ConfigParam::RemoveCommitteeId(Arbitrary::arbitrary(g)),
                 18 => ConfigParam::PerVoteCertificateFees(Arbitrary::arbitrary(g)),
-                19 => ConfigParam::RewardPot(Arbitrary::arbitrary(g)),
-                20 => ConfigParam::RewardParams(Arbitrary::arbitrary(g)),
-                21 => ConfigParam::RewardParams(Arbitrary::arbitrary(g)),
+                19 => ConfigParam::EpochStabilityDepth(Arbitrary::arbitrary(g)),
+                20 => ConfigParam::KesUpdateSpeed(Arbitrary::arbitrary(g)),
+                21 => ConfigParam::TreasuryParams(Arbitrary::arbitrary(g)),
                 22 => ConfigParam::FeesInTreasury(Arbitrary::arbitrary(g)),
                 23 => ConfigParam::RewardLimitNone,
                 24 => ConfigParam::RewardLimitByAbsoluteStake(Arbitrary::arbitrary(g)),
@@ -1052,10 +1273,26 @@ mod test {
                 27 => ConfigParam::RemoveCommitteeId(Arbitrary::arbitrary(g)),
                 28 => ConfigParam::PerCertificateFees(Arbitrary::arbitrary(g)),
                 29 => ConfigParam::TransactionMaxExpiryEpochs(Arbitrary::arbitrary(g)),
+                30 => ConfigParam::NameRegistryParams(Arbitrary::arbitrary(g)),
+                31 => ConfigParam::PerCertificateByteFee(Arbitrary::arbitrary(g)),
+                32 => ConfigParam::MinimumPoolPledge(Arbitrary::arbitrary(g)),
+                33 => ConfigParam::PoolPerformanceRewards(Arbitrary::arbitrary(g)),
+                34 => ConfigParam::TotalSupply(Arbitrary::arbitrary(g)),
+                35 => ConfigParam::BftQuorum(Arbitrary::arbitrary(g)),
+                36 => ConfigParam::ProtocolVersion(Arbitrary::arbitrary(g)),
+                37 => ConfigParam::CertificatePolicy(
+                    CertificatePolicy::from_bits(
+                        u32::arbitrary(g) % (1 << CertificateKind::ALL.len()),
+                    )
+                    .unwrap(),
+                ),
+                38 => ConfigParam::MaxVotePlansPerEpoch(Arbitrary::arbitrary(g)),
+                39 => ConfigParam::MaxProposalsPerVotePlan(Arbitrary::arbitrary(g)),
+                40 => ConfigParam::ActiveSlotsCoeffTransition(Arbitrary::arbitrary(g)),
                 #[cfg(feature = "evm")]
-                30 => ConfigParam::EvmConfiguration(Arbitrary::arbitrary(g)),
+                41 => ConfigParam::EvmConfiguration(Arbitrary::arbitrary(g)),
                 #[cfg(feature = "evm")]
-                31 => ConfigParam::EvmEnvironment(Arbitrary::arbitrary(g)),
+                42 => ConfigParam::EvmEnvironment(Arbitrary::arbitrary(g)),
                 _ => unreachable!(),
             }
         }
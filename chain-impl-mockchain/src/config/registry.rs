@@ -0,0 +1,419 @@
+//! Documentation of the configuration parameters, introspectable at runtime.
+//!
+//! Every [`Tag`] is described by a [`ConfigParamDoc`]: its name (the same
+//! string the tag is parsed from), the type and unit of its value, its
+//! default and when it takes effect. The description is an exhaustive match
+//! on the tag so a new parameter cannot be added without its documentation,
+//! and the defaults are taken from [`Settings::new`].
+
+use super::{ConfigParam, Tag};
+#[cfg(feature = "evm")]
+use super::{EvmConfig, EvmEnvSettings};
+use crate::setting::Settings;
+use crate::value::Value;
+use std::num::NonZeroU32;
+use strum::IntoEnumIterator;
+
+/// When a parameter takes effect
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Activation {
+    /// only set by the initial fragment of the block0, the parameter cannot
+    /// be changed by an update proposal
+    Block0Only,
+    /// set by the block0 and changed by the accepted update proposals, which
+    /// are applied at the start of the next epoch
+    EpochBoundary,
+}
+
+/// Documentation of a configuration parameter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigParamDoc {
+    pub tag: Tag,
+    /// name of the parameter, as the tag is parsed from
+    pub name: &'static str,
+    /// type of the value of the parameter
+    pub value_type: &'static str,
+    pub unit: Option<&'static str>,
+    /// value of the parameter when it is not set, `None` if the parameter
+    /// is required, or only adds to or removes from a list
+    pub default: Option<ConfigParam>,
+    pub activation: Activation,
+    pub description: &'static str,
+}
+
+impl Tag {
+    /// Documentation of the parameter
+    pub fn doc(self) -> ConfigParamDoc {
+        let settings = Settings::new();
+        let doc = |value_type, unit, default, activation, description| ConfigParamDoc {
+            tag: self,
+            name: self.into(),
+            value_type,
+            unit,
+            default,
+            activation,
+            description,
+        };
+        use Activation::*;
+        match self {
+            Tag::Discrimination => doc(
+                "discrimination",
+                None,
+                None,
+                Block0Only,
+                "discrimination of the addresses of the chain, production or test",
+            ),
+            Tag::Block0Date => doc(
+                "u64",
+                Some("seconds since the UNIX epoch"),
+                None,
+                Block0Only,
+                "start time of the chain",
+            ),
+            Tag::ConsensusVersion => doc(
+                "consensus type",
+                None,
+                Some(ConfigParam::ConsensusVersion(settings.consensus_version)),
+                EpochBoundary,
+                "consensus used to elect the leaders of the slots, bft or genesis praos",
+            ),
+            Tag::SlotsPerEpoch => doc(
+                "u32",
+                Some("slots"),
+                Some(ConfigParam::SlotsPerEpoch(settings.slots_per_epoch)),
+                EpochBoundary,
+                "number of slots in an epoch",
+            ),
+            Tag::SlotDuration => doc(
+                "u8",
+                Some("seconds"),
+                Some(ConfigParam::SlotDuration(settings.slot_duration)),
+                EpochBoundary,
+                "duration of a slot",
+            ),
+            Tag::EpochStabilityDepth => doc(
+                "u32",
+                Some("blocks"),
+                Some(ConfigParam::EpochStabilityDepth(
+                    settings.epoch_stability_depth,
+                )),
+                EpochBoundary,
+                "depth after which a block cannot be rolled back",
+            ),
+            Tag::ConsensusGenesisPraosActiveSlotsCoeff => doc(
+                "milli",
+                None,
+                Some(ConfigParam::ConsensusGenesisPraosActiveSlotsCoeff(
+                    settings.active_slots_coeff.into(),
+                )),
+                EpochBoundary,
+                "ratio of the slots with a leader in genesis praos, in (0, 1]",
+            ),
+            Tag::BlockContentMaxSize => doc(
+                "u32",
+                Some("bytes"),
+                Some(ConfigParam::BlockContentMaxSize(
+                    settings.block_content_max_size,
+                )),
+                EpochBoundary,
+                "maximum size of the content of a block",
+            ),
+            Tag::AddBftLeader => doc(
+                "bft leader id",
+                None,
+                None,
+                EpochBoundary,
+                "add a leader to the BFT leaders",
+            ),
+            Tag::RemoveBftLeader => doc(
+                "bft leader id",
+                None,
+                None,
+                EpochBoundary,
+                "remove a leader from the BFT leaders",
+            ),
+            Tag::LinearFee => doc(
+                "linear fee",
+                Some("value"),
+                Some(ConfigParam::LinearFee(settings.linear_fees)),
+                EpochBoundary,
+                "fees of the transactions: constant, per input and output, per certificate",
+            ),
+            Tag::ProposalExpiration => doc(
+                "u32",
+                Some("epochs"),
+                Some(ConfigParam::ProposalExpiration(
+                    settings.proposal_expiration,
+                )),
+                EpochBoundary,
+                "number of epochs an update proposal can be voted on",
+            ),
+            Tag::KesUpdateSpeed => doc(
+                "u32",
+                Some("seconds"),
+                None,
+                Block0Only,
+                "maximum time between two updates of the KES key of a stake pool",
+            ),
+            Tag::TreasuryAdd => doc(
+                "value",
+                Some("value"),
+                Some(ConfigParam::TreasuryAdd(Value::zero())),
+                Block0Only,
+                "initial value of the treasury",
+            ),
+            Tag::TreasuryParams => doc(
+                "tax type",
+                None,
+                None,
+                EpochBoundary,
+                "part of the rewards of an epoch taken by the treasury, none when not set",
+            ),
+            Tag::RewardPot => doc(
+                "value",
+                Some("value"),
+                Some(ConfigParam::RewardPot(Value::zero())),
+                Block0Only,
+                "initial value of the reward pot",
+            ),
+            Tag::RewardParams => doc(
+                "reward params",
+                None,
+                None,
+                EpochBoundary,
+                "rewards drawn from the reward pot at each epoch, none when not set",
+            ),
+            Tag::PerCertificateFees => doc(
+                "per certificate fee",
+                Some("value"),
+                Some(ConfigParam::PerCertificateFees(
+                    settings.linear_fees.per_certificate_fees,
+                )),
+                EpochBoundary,
+                "fees of specific certificates, replacing the certificate fee of the linear fee",
+            ),
+            Tag::FeesInTreasury => doc(
+                "bool",
+                None,
+                Some(ConfigParam::FeesInTreasury(false)),
+                EpochBoundary,
+                "send the fees to the treasury instead of the rewards",
+            ),
+            Tag::RewardLimitNone => doc(
+                "none",
+                None,
+                Some(ConfigParam::RewardLimitNone),
+                EpochBoundary,
+                "do not limit the rewards of the epoch",
+            ),
+            Tag::RewardLimitByAbsoluteStake => doc(
+                "ratio",
+                None,
+                None,
+                EpochBoundary,
+                "limit the rewards of the epoch by the ratio of the total supply being staked",
+            ),
+            Tag::PoolRewardParticipationCapping => doc(
+                "(non zero u32, non zero u32)",
+                Some("pools"),
+                None,
+                EpochBoundary,
+                "minimum and maximum number of pools the rewards are capped by",
+            ),
+            Tag::AddCommitteeId => doc(
+                "committee id",
+                None,
+                None,
+                EpochBoundary,
+                "add a member to the vote committee",
+            ),
+            Tag::RemoveCommitteeId => doc(
+                "committee id",
+                None,
+                None,
+                EpochBoundary,
+                "remove a member from the vote committee",
+            ),
+            Tag::PerVoteCertificateFees => doc(
+                "per vote certificate fee",
+                Some("value"),
+                Some(ConfigParam::PerVoteCertificateFees(
+                    settings.linear_fees.per_vote_certificate_fees,
+                )),
+                EpochBoundary,
+                "fees of the vote plan and vote cast certificates",
+            ),
+            Tag::TransactionMaxExpiryEpochs => doc(
+                "u8",
+                Some("epochs"),
+                Some(ConfigParam::TransactionMaxExpiryEpochs(
+                    settings.transaction_max_expiry_epochs,
+                )),
+                EpochBoundary,
+                "maximum number of epochs a transaction can be valid for",
+            ),
+            #[cfg(feature = "evm")]
+            Tag::EvmConfiguration => doc(
+                "evm config",
+                None,
+                Some(ConfigParam::EvmConfiguration(EvmConfig::default())),
+                EpochBoundary,
+                "fork of the EVM rules",
+            ),
+            #[cfg(feature = "evm")]
+            Tag::EvmEnvironment => doc(
+                "evm environment settings",
+                None,
+                Some(ConfigParam::EvmEnvironment(EvmEnvSettings::default())),
+                EpochBoundary,
+                "gas price and block gas limit of the EVM",
+            ),
+            Tag::NameRegistryParams => doc(
+                "name registry params",
+                None,
+                None,
+                EpochBoundary,
+                "parameters of the name registry, disabled when not set",
+            ),
+            Tag::PerCertificateByteFee => doc(
+                "u64",
+                Some("value per byte"),
+                Some(ConfigParam::PerCertificateByteFee(0)),
+                EpochBoundary,
+                "fee per byte of certificate, 0 to disable",
+            ),
+            Tag::MinimumPoolPledge => doc(
+                "value",
+                Some("value"),
+                Some(ConfigParam::MinimumPoolPledge(settings.minimum_pool_pledge)),
+                EpochBoundary,
+                "minimum stake the owners of a pool need to pledge, 0 to disable",
+            ),
+            Tag::PoolPerformanceRewards => doc(
+                "bool",
+                None,
+                Some(ConfigParam::PoolPerformanceRewards(
+                    settings.pool_performance_rewards,
+                )),
+                EpochBoundary,
+                "scale the rewards of the pools by their performance over the epoch",
+            ),
+            Tag::TotalSupply => doc(
+                "value",
+                Some("value"),
+                None,
+                Block0Only,
+                "total supply of the chain, not enforced when not set",
+            ),
+            Tag::BftQuorum => doc(
+                "u8",
+                Some("leaders"),
+                Some(ConfigParam::BftQuorum(settings.bft_quorum)),
+                EpochBoundary,
+                "number of BFT leaders which need to sign a block, the slot leader included",
+            ),
+            Tag::ProtocolVersion => doc(
+                "u32",
+                None,
+                Some(ConfigParam::ProtocolVersion(settings.protocol_version)),
+                EpochBoundary,
+                "version of the ledger rules, which cannot be downgraded",
+            ),
+            Tag::CertificatePolicy => doc(
+                "u32",
+                None,
+                Some(ConfigParam::CertificatePolicy(settings.certificate_policy)),
+                EpochBoundary,
+                "certificate kinds disabled on the chain, all of them are enabled by default",
+            ),
+            Tag::MaxVotePlansPerEpoch => doc(
+                "u32",
+                Some("vote plans"),
+                Some(ConfigParam::MaxVotePlansPerEpoch(
+                    settings.max_vote_plans_per_epoch.map_or(0, NonZeroU32::get),
+                )),
+                EpochBoundary,
+                "vote plans a committee member can submit in an epoch, 0 to disable",
+            ),
+            Tag::MaxProposalsPerVotePlan => doc(
+                "u32",
+                Some("proposals"),
+                Some(ConfigParam::MaxProposalsPerVotePlan(
+                    settings
+                        .max_proposals_per_vote_plan
+                        .map_or(0, NonZeroU32::get),
+                )),
+                EpochBoundary,
+                "proposals of a vote plan, 0 to disable",
+            ),
+            Tag::ActiveSlotsCoeffTransition => doc(
+                "u32",
+                Some("slots"),
+                Some(ConfigParam::ActiveSlotsCoeffTransition(
+                    settings.active_slots_coeff_transition,
+                )),
+                EpochBoundary,
+                "slots over which a change of the active slots coefficient is phased in, 0 for an immediate change",
+            ),
+        }
+    }
+}
+
+/// The documentation of all the configuration parameters, in the order of
+/// their tags
+pub fn registry() -> impl Iterator<Item = ConfigParamDoc> {
+    Tag::iter().map(Tag::doc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fragment::ConfigParams;
+    use std::str::FromStr;
+
+    #[test]
+    fn registry_names_parse_to_their_tag() {
+        for doc in registry() {
+            assert_eq!(Tag::from_str(doc.name), Ok(doc.tag));
+            assert_eq!(Tag::from_u16(doc.tag as u16), Some(doc.tag));
+        }
+    }
+
+    #[test]
+    fn registry_defaults_are_of_their_tag() {
+        for doc in registry() {
+            if let Some(default) = &doc.default {
+                assert_eq!(Tag::from(default), doc.tag, "{}", doc.name);
+            }
+        }
+    }
+
+    #[test]
+    fn registry_defaults_leave_the_settings_unchanged() {
+        let settings = Settings::new();
+        for doc in registry().filter(|doc| doc.activation == Activation::EpochBoundary) {
+            if let Some(default) = doc.default {
+                let mut params = ConfigParams::new();
+                params.push(default);
+                assert_eq!(
+                    settings.try_apply(&params).unwrap(),
+                    settings,
+                    "{}",
+                    doc.name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn registry_block0_parameters_are_read_only() {
+        let settings = Settings::new();
+        for doc in registry().filter(|doc| doc.activation == Activation::Block0Only) {
+            if let Some(default) = doc.default {
+                let mut params = ConfigParams::new();
+                params.push(default);
+                assert!(settings.try_apply(&params).is_err(), "{}", doc.name);
+            }
+        }
+    }
+}
@@ -1,12 +1,15 @@
 use chain_core::property;
-use chain_time::era::EpochPosition;
 use chain_time::era::TimeEra;
+use chain_time::era::{Epoch as TimeEpoch, EpochPosition, EpochSlotOffset};
 
 use std::{error, fmt, num::ParseIntError, str};
 
 /// Non unique identifier of the transaction position in the
 /// blockchain. There may be many transactions related to the same
 /// `SlotId`.
+///
+/// The dates are ordered as the slots of the chain, by epoch then by slot,
+/// and written `EPOCH.SLOT` by their `Display` and `FromStr` implementations.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct BlockDate {
     pub epoch: Epoch,
@@ -17,37 +20,72 @@ pub type Epoch = u32;
 pub type SlotId = u32;
 
 impl BlockDate {
-    pub fn first() -> BlockDate {
-        BlockDate {
-            epoch: 0,
-            slot_id: 0,
-        }
+    pub const fn new(epoch: Epoch, slot_id: SlotId) -> BlockDate {
+        BlockDate { epoch, slot_id }
+    }
+
+    pub const fn first() -> BlockDate {
+        BlockDate::new(0, 0)
+    }
+
+    /// The first slot of the epoch
+    pub const fn epoch_start(epoch: Epoch) -> BlockDate {
+        BlockDate::new(epoch, 0)
     }
 
     /// Get the slot following this one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slot is not in the era or if the epoch overflows, see
+    /// [`BlockDate::checked_next`]
     #[must_use = "this returns the result of the operation, without modifying the original"]
     pub fn next(self, era: &TimeEra) -> BlockDate {
+        assert!(self.slot_id < era.slots_per_epoch());
+        self.checked_next(era).expect("block date epoch overflow")
+    }
+
+    /// Get the slot following this one, `None` if the slot is not in the
+    /// era or if it is the last slot of the last epoch
+    #[must_use = "this returns the result of the operation, without modifying the original"]
+    pub fn checked_next(self, era: &TimeEra) -> Option<BlockDate> {
         let epoch_duration = era.slots_per_epoch();
-        assert!(self.slot_id < epoch_duration);
-        if self.slot_id + 1 == epoch_duration {
-            BlockDate {
-                epoch: self.epoch + 1,
-                slot_id: 0,
-            }
+        if self.slot_id >= epoch_duration {
+            None
+        } else if self.slot_id + 1 == epoch_duration {
+            self.checked_next_epoch()
         } else {
-            BlockDate {
-                epoch: self.epoch,
-                slot_id: self.slot_id + 1,
-            }
+            Some(BlockDate::new(self.epoch, self.slot_id + 1))
         }
     }
 
+    /// Get the first slot of the next epoch.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the epoch overflows, see [`BlockDate::checked_next_epoch`]
     #[must_use = "this returns the result of the operation, without modifying the original"]
     pub fn next_epoch(self) -> BlockDate {
-        BlockDate {
-            epoch: self.epoch + 1,
-            slot_id: 0,
-        }
+        self.checked_next_epoch()
+            .expect("block date epoch overflow")
+    }
+
+    #[must_use = "this returns the result of the operation, without modifying the original"]
+    pub fn checked_next_epoch(self) -> Option<BlockDate> {
+        self.epoch.checked_add(1).map(BlockDate::epoch_start)
+    }
+
+    /// Get the first slot of the next epoch, or this date if it is in the
+    /// last epoch
+    #[must_use = "this returns the result of the operation, without modifying the original"]
+    pub fn saturating_next_epoch(self) -> BlockDate {
+        self.checked_next_epoch().unwrap_or(self)
+    }
+
+    /// Get the first slot of the epoch `epochs` epochs after this one
+    #[must_use = "this returns the result of the operation, without modifying the original"]
+    pub fn checked_add_epochs(self, epochs: Epoch) -> Option<BlockDate> {
+        self.epoch.checked_add(epochs).map(BlockDate::epoch_start)
     }
 }
 
@@ -60,6 +98,15 @@ impl From<EpochPosition> for BlockDate {
     }
 }
 
+impl From<BlockDate> for EpochPosition {
+    fn from(date: BlockDate) -> EpochPosition {
+        EpochPosition {
+            epoch: TimeEpoch(date.epoch),
+            slot: EpochSlotOffset(date.slot_id),
+        }
+    }
+}
+
 impl property::BlockDate for BlockDate {
     fn from_epoch_slot_id(epoch: Epoch, slot_id: SlotId) -> Self {
         BlockDate { epoch, slot_id }
@@ -103,6 +150,37 @@ impl error::Error for BlockDateParseError {
     }
 }
 
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::BlockDate;
+    use serde::de::{Deserialize, Deserializer, Error};
+    use serde::ser::{Serialize, Serializer};
+
+    /// `"EPOCH.SLOT"` in the human readable formats, an `(epoch, slot)`
+    /// tuple in the binary ones
+    impl Serialize for BlockDate {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            if serializer.is_human_readable() {
+                serializer.collect_str(self)
+            } else {
+                (self.epoch, self.slot_id).serialize(serializer)
+            }
+        }
+    }
+
+    impl<'de> Deserialize<'de> for BlockDate {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            if deserializer.is_human_readable() {
+                let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+                s.parse().map_err(D::Error::custom)
+            } else {
+                let (epoch, slot_id) = Deserialize::deserialize(deserializer)?;
+                Ok(BlockDate { epoch, slot_id })
+            }
+        }
+    }
+}
+
 impl str::FromStr for BlockDate {
     type Err = BlockDateParseError;
 
@@ -162,6 +240,54 @@ mod tests {
         }
     }
 
+    #[test]
+    fn checked_increments() {
+        let era = TimeEra::new(chain_time::Slot::from(0), TimeEpoch(0), 10);
+        assert_eq!(
+            BlockDate::new(3, 8).checked_next(&era),
+            Some(BlockDate::new(3, 9))
+        );
+        assert_eq!(
+            BlockDate::new(3, 9).checked_next(&era),
+            Some(BlockDate::epoch_start(4))
+        );
+        assert_eq!(BlockDate::new(3, 10).checked_next(&era), None);
+        assert_eq!(BlockDate::new(Epoch::MAX, 9).checked_next(&era), None);
+
+        let last = BlockDate::new(Epoch::MAX, 2);
+        assert_eq!(last.checked_next_epoch(), None);
+        assert_eq!(last.saturating_next_epoch(), last);
+        assert_eq!(
+            BlockDate::new(3, 2).checked_add_epochs(2),
+            Some(BlockDate::epoch_start(5))
+        );
+        assert_eq!(last.checked_add_epochs(1), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_as_string() {
+        let date = BlockDate::new(42, 12);
+        assert_eq!(serde_json::to_string(&date).unwrap(), "\"42.12\"");
+        assert_eq!(
+            serde_json::from_str::<BlockDate>("\"42.12\"").unwrap(),
+            date
+        );
+        assert!(serde_json::from_str::<BlockDate>("\"42\"").is_err());
+    }
+
+    #[cfg(test)]
+    #[quickcheck_macros::quickcheck]
+    fn display_parse_roundtrip(date: BlockDate) -> bool {
+        date.to_string().parse::<BlockDate>() == Ok(date)
+    }
+
+    #[cfg(test)]
+    #[quickcheck_macros::quickcheck]
+    fn order_is_chain_order(a: BlockDate, b: BlockDate) -> bool {
+        a.cmp(&b) == (a.epoch, a.slot_id).cmp(&(b.epoch, b.slot_id))
+    }
+
     impl Arbitrary for BlockDate {
         fn arbitrary<G: Gen>(g: &mut G) -> Self {
             BlockDate {
@@ -280,7 +280,10 @@ impl Readable for EvmTransaction {
                     access_list,
                 })
             }
-            n => Err(ReadError::UnknownTag(n as u32)),
+            n => Err(ReadError::UnknownTag {
+                kind: "evm transaction",
+                tag: n as u32,
+            }),
         }
     }
 }
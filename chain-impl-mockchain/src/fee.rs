@@ -1,10 +1,14 @@
-use crate::certificate::CertificateSlice;
+use crate::certificate::{CertificateSlice, SerializedSize, VoteCastBatch};
 use crate::transaction as tx;
+use crate::transaction::PayloadSlice;
 use crate::value::Value;
 use std::num::NonZeroU64;
 
 /// Linear fee using the basic affine formula
 /// `COEFFICIENT * bytes(COUNT(tx.inputs) + COUNT(tx.outputs)) + CONSTANT + CERTIFICATE*COUNT(certificates)`.
+///
+/// When set, the per certificate byte fee adds `BYTE_FEE * SIZE(certificate)`
+/// to the fee of the certificate.
 #[derive(PartialEq, Eq, PartialOrd, Debug, Clone, Copy)]
 pub struct LinearFee {
     pub constant: u64,
@@ -12,6 +16,7 @@ pub struct LinearFee {
     pub certificate: u64,
     pub per_certificate_fees: PerCertificateFee,
     pub per_vote_certificate_fees: PerVoteCertificateFee,
+    pub per_certificate_byte_fee: Option<NonZeroU64>,
 }
 
 #[derive(PartialEq, Eq, PartialOrd, Debug, Clone, Copy, Default)]
@@ -27,6 +32,24 @@ pub struct PerVoteCertificateFee {
     pub certificate_vote_cast: Option<NonZeroU64>,
 }
 
+/// The fees in use in the current epoch and the fees which will be in use
+/// from the start of the next epoch.
+///
+/// The fees are only modified by the update proposals accepted at an epoch
+/// transition, the fees of a pending fragment can be computed with
+/// `current` until the end of the epoch.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct FeeSchedule {
+    pub current: LinearFee,
+    pub next_epoch: LinearFee,
+}
+
+impl FeeSchedule {
+    pub fn changes_at_next_epoch(&self) -> bool {
+        self.current != self.next_epoch
+    }
+}
+
 impl LinearFee {
     pub fn new(constant: u64, coefficient: u64, certificate: u64) -> Self {
         LinearFee {
@@ -35,6 +58,7 @@ impl LinearFee {
             certificate,
             per_certificate_fees: PerCertificateFee::default(),
             per_vote_certificate_fees: PerVoteCertificateFee::default(),
+            per_certificate_byte_fee: None,
         }
     }
 
@@ -45,6 +69,10 @@ impl LinearFee {
     pub fn per_vote_certificate_fees(&mut self, per_vote_certificate_fees: PerVoteCertificateFee) {
         self.per_vote_certificate_fees = per_vote_certificate_fees;
     }
+
+    pub fn per_certificate_byte_fee(&mut self, per_certificate_byte_fee: Option<NonZeroU64>) {
+        self.per_certificate_byte_fee = per_certificate_byte_fee;
+    }
 }
 
 impl PerCertificateFee {
@@ -65,7 +93,9 @@ impl PerCertificateFee {
             CertificateSlice::PoolRegistration(_) => {
                 self.certificate_pool_registration.map(|v| Value(v.get()))
             }
-            CertificateSlice::StakeDelegation(_) => {
+            CertificateSlice::StakeDelegation(_)
+            | CertificateSlice::AmountStakeDelegation(_)
+            | CertificateSlice::RewardsRedelegation(_) => {
                 self.certificate_stake_delegation.map(|v| Value(v.get()))
             }
             CertificateSlice::OwnerStakeDelegation(_) => self
@@ -90,12 +120,22 @@ impl PerVoteCertificateFee {
     fn fees_for_certificate<'a>(&self, cert: &CertificateSlice<'a>) -> Option<Value> {
         match cert {
             CertificateSlice::VotePlan(_) => self.certificate_vote_plan.map(|v| Value(v.get())),
-            CertificateSlice::VoteCast(_) => self.certificate_vote_cast.map(|v| Value(v.get())),
+            CertificateSlice::VoteCast(_) | CertificateSlice::SnapshotVoteCast(_) => {
+                self.certificate_vote_cast.map(|v| Value(v.get()))
+            }
+            // each cast of a batch pays the fee of a standalone vote cast
+            CertificateSlice::VoteCastBatch(batch) => self
+                .certificate_vote_cast
+                .map(|v| Value(v.get().saturating_mul(nb_vote_casts(batch)))),
             _ => None,
         }
     }
 }
 
+fn nb_vote_casts(batch: &PayloadSlice<'_, VoteCastBatch>) -> u64 {
+    batch.clone().into_payload().nb_casts() as u64
+}
+
 pub trait FeeAlgorithm {
     fn baseline(&self) -> Value;
     fn fees_for_inputs_outputs(&self, inputs: u8, outputs: u8) -> Value;
@@ -114,6 +154,18 @@ pub trait FeeAlgorithm {
             tx.nb_outputs(),
         )
     }
+
+    /// The fee of a batch of transactions: the baseline is paid only once
+    /// for the whole batch.
+    fn calculate_batch(&self, batch: &tx::TransactionBatch) -> Value {
+        batch
+            .transactions()
+            .iter()
+            .fold(self.baseline(), |fee, tx| {
+                let tx = tx.as_slice();
+                fee.saturating_add(self.fees_for_inputs_outputs(tx.nb_inputs(), tx.nb_outputs()))
+            })
+    }
 }
 
 impl FeeAlgorithm for LinearFee {
@@ -133,7 +185,21 @@ impl FeeAlgorithm for LinearFee {
         let f2 = self
             .per_vote_certificate_fees
             .fees_for_certificate(&cert_slice);
-        f1.or(f2).unwrap_or(Value(self.certificate))
+        let size_fee = self.per_certificate_byte_fee.map_or(0, |fee| {
+            fee.get()
+                .saturating_mul(cert_slice.serialized_size() as u64)
+        });
+        // without a vote cast fee, each cast of a batch still pays the flat
+        // fee of a standalone vote cast
+        let flat_fee = match &cert_slice {
+            CertificateSlice::VoteCastBatch(batch) => {
+                Value(self.certificate.saturating_mul(nb_vote_casts(batch)))
+            }
+            _ => Value(self.certificate),
+        };
+        f1.or(f2)
+            .unwrap_or(flat_fee)
+            .saturating_add(Value(size_fee))
     }
 }
 
@@ -141,7 +207,7 @@ impl FeeAlgorithm for LinearFee {
 mod test {
     use super::*;
     #[cfg(test)]
-    use crate::certificate::{Certificate, CertificatePayload};
+    use crate::certificate::{Certificate, CertificatePayload, SerializedSize};
     #[cfg(test)]
     use quickcheck::TestResult;
     use quickcheck::{Arbitrary, Gen};
@@ -174,6 +240,7 @@ mod test {
                 certificate: Arbitrary::arbitrary(g),
                 per_certificate_fees: PerCertificateFee::new(None, None, None),
                 per_vote_certificate_fees: PerVoteCertificateFee::new(None, None),
+                per_certificate_byte_fee: None,
             }
         }
     }
@@ -217,6 +284,47 @@ mod test {
         }
     }
 
+    #[quickcheck]
+    pub fn linear_fee_certificate_byte_fee_calculation(
+        certificate: Certificate,
+        fee: LinearFee,
+        byte_fee: u64,
+    ) -> TestResult {
+        let byte_fee = match NonZeroU64::new(byte_fee % 1_000) {
+            Some(byte_fee) => byte_fee,
+            None => return TestResult::discard(),
+        };
+        let mut sized_fee = fee;
+        sized_fee.per_certificate_byte_fee(Some(byte_fee));
+
+        let certificate_payload: CertificatePayload = (&certificate).into();
+        let size_fee = Value(byte_fee.get() * certificate.serialized_size() as u64);
+        let expected_value = fee
+            .fees_for_certificate(certificate_payload.as_slice())
+            .saturating_add(size_fee);
+        let fee_value = sized_fee.fees_for_certificate(certificate_payload.as_slice());
+
+        if fee_value == expected_value {
+            TestResult::passed()
+        } else {
+            TestResult::error(format!("Wrong fee: {} vs {}", fee_value, expected_value))
+        }
+    }
+
+    #[quickcheck]
+    pub fn vote_cast_batch_flat_fee_per_cast(batch: VoteCastBatch, fee: LinearFee) -> TestResult {
+        let expected_value = Value(fee.certificate.saturating_mul(batch.nb_casts() as u64));
+        let certificate = Certificate::VoteCastBatch(batch);
+        let certificate_payload: CertificatePayload = (&certificate).into();
+        let fee_value = fee.fees_for_certificate(certificate_payload.as_slice());
+
+        if fee_value == expected_value {
+            TestResult::passed()
+        } else {
+            TestResult::error(format!("Wrong fee: {} vs {}", fee_value, expected_value))
+        }
+    }
+
     #[cfg(test)]
     fn calculate_expected_cert_fee_value(certificate: &Certificate, fee: &LinearFee) -> u64 {
         let cert_fees = fee.per_certificate_fees;
@@ -225,14 +333,22 @@ mod test {
             Certificate::PoolRegistration { .. } => {
                 cert_fees.certificate_pool_registration.unwrap().into()
             }
-            Certificate::StakeDelegation { .. } => {
+            Certificate::StakeDelegation { .. }
+            | Certificate::AmountStakeDelegation { .. }
+            | Certificate::RewardsRedelegation { .. } => {
                 cert_fees.certificate_stake_delegation.unwrap().into()
             }
             Certificate::OwnerStakeDelegation { .. } => {
                 cert_fees.certificate_owner_stake_delegation.unwrap().into()
             }
             Certificate::VotePlan { .. } => vote_cert_fees.certificate_vote_plan.unwrap().into(),
-            Certificate::VoteCast { .. } => vote_cert_fees.certificate_vote_cast.unwrap().into(),
+            Certificate::VoteCast { .. } | Certificate::SnapshotVoteCast { .. } => {
+                vote_cert_fees.certificate_vote_cast.unwrap().into()
+            }
+            Certificate::VoteCastBatch(batch) => {
+                u64::from(vote_cert_fees.certificate_vote_cast.unwrap())
+                    .saturating_mul(batch.nb_casts() as u64)
+            }
             _ => fee.certificate,
         }
     }
@@ -1,6 +1,9 @@
 use crate::config::ConfigParam;
-use chain_core::mempack::{ReadBuf, ReadError, Readable};
-use chain_core::property;
+use chain_core::{
+    mempack::ReadError,
+    packer::Codec,
+    property::{ByteSource, Pack, Unpack},
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct ConfigParams(pub(crate) Vec<ConfigParam>);
@@ -19,47 +22,31 @@ impl ConfigParams {
     }
 }
 
-impl property::Serialize for ConfigParams {
-    type Error = std::io::Error;
-
-    fn serialize<W: std::io::Write>(&self, mut writer: W) -> Result<(), Self::Error> {
+impl Pack for ConfigParams {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
         // FIXME: put params in canonical order (e.g. sorted by tag)?
-        use chain_core::packer::*;
-        Codec::new(&mut writer).put_be_u16(self.0.len() as u16)?;
+        codec.put_size_be_u16(self.0.len())?;
         for config in &self.0 {
-            config.serialize(&mut writer)?
+            config.pack(codec)?
         }
         Ok(())
     }
 }
 
-impl property::Deserialize for ConfigParams {
-    type Error = std::io::Error;
-
-    fn deserialize<R: std::io::BufRead>(reader: R) -> Result<Self, Self::Error> {
-        use chain_core::packer::Codec;
-        let mut codec = Codec::new(reader);
-        let size = codec.get_be_u16()? as usize;
-        let mut config_params: Vec<ConfigParam> = Vec::with_capacity(size);
-        for _ in 0..size {
-            config_params.push(ConfigParam::deserialize(&mut codec)?);
-        }
-        Ok(ConfigParams(config_params))
-    }
-}
-
-impl Readable for ConfigParams {
-    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
+impl Unpack for ConfigParams {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
         // FIXME: check canonical order?
-        let len = buf.get_u16()?;
+        let len = source.take_u16()?;
         let mut configs: Vec<ConfigParam> = Vec::with_capacity(len as usize);
         for _ in 0..len {
-            configs.push(ConfigParam::read(buf)?);
+            configs.push(ConfigParam::unpack(source)?);
         }
         Ok(ConfigParams(configs))
     }
 }
 
+chain_ser::impl_legacy_codecs!(ConfigParams);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -75,7 +62,7 @@ mod tests {
         }
 
         fn config_params_serialize_readable(params: ConfigParams) -> bool {
-            use chain_core::property::Serialize as _;
+            use chain_core::{mempack::{ReadBuf, Readable as _}, property::Serialize as _};
             let bytes = params.serialize_as_vec().unwrap();
             let mut reader = ReadBuf::from(&bytes);
             let decoded = ConfigParams::read(&mut reader).unwrap();
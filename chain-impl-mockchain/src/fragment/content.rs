@@ -1,11 +1,25 @@
+use super::merkle::merkle_root;
 use crate::fragment::Fragment;
 use crate::key::Hash;
 use chain_core::property::Serialize;
+use cryptoxide::{blake2b::Blake2b, digest::Digest};
 use std::slice;
 
 pub type BlockContentHash = Hash;
 pub type BlockContentSize = u32;
 
+/// How the content hash of a block is computed from its fragments, given by
+/// the version of the block header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentHashScheme {
+    /// hash of the concatenation of the serialized fragments
+    Sequential,
+    /// root of the Merkle tree of the fragment ids, the inclusion of a
+    /// fragment can be proven against the header only, see
+    /// [`InclusionProof`](super::InclusionProof)
+    MerkleTree,
+}
+
 /// Block Contents
 ///
 /// To create this structure, make a ContentsBuilder and use into()
@@ -54,21 +68,73 @@ impl Contents {
     pub fn compute_hash(&self) -> BlockContentHash {
         self.compute_hash_size().0
     }
+
+    /// hash and size of the contents with the given hash scheme, the size
+    /// does not depend on the scheme
+    pub fn compute_hash_size_with(
+        &self,
+        scheme: ContentHashScheme,
+    ) -> (BlockContentHash, BlockContentSize) {
+        match scheme {
+            ContentHashScheme::Sequential => self.compute_hash_size(),
+            ContentHashScheme::MerkleTree => {
+                let mut size = 0;
+                let root = merkle_root(self.iter().map(|fragment| {
+                    let raw = fragment.to_raw();
+                    size += raw.size_bytes_plus_size() as u32;
+                    raw.id()
+                }));
+                (root, size)
+            }
+        }
+    }
 }
 
-#[derive(Clone, Default)]
+/// Builder of the block contents
+///
+/// The hash and the size of the contents are updated as the fragments are
+/// pushed, so they are available without walking the fragments again when
+/// sealing the block. Removing a fragment invalidates the running hash, which
+/// is recomputed on the next call to `current_hash_size`.
+#[derive(Clone)]
 pub struct ContentsBuilder {
     fragments: Vec<Fragment>,
+    hasher: Option<Blake2b>,
+    size: BlockContentSize,
+}
+
+impl Default for ContentsBuilder {
+    fn default() -> Self {
+        ContentsBuilder::new()
+    }
+}
+
+const CONTENT_HASH_SIZE: usize = 32;
+
+fn content_hasher() -> Blake2b {
+    Blake2b::new(CONTENT_HASH_SIZE)
+}
+
+fn hash_fragment(hasher: &mut Blake2b, fragment: &Fragment) -> BlockContentSize {
+    let mut bytes = Vec::new();
+    fragment.to_raw().serialize(&mut bytes).unwrap();
+    hasher.input(&bytes);
+    bytes.len() as u32
 }
 
 impl ContentsBuilder {
     pub fn new() -> Self {
         ContentsBuilder {
             fragments: Vec::new(),
+            hasher: Some(content_hasher()),
+            size: 0,
         }
     }
 
     pub fn push(&mut self, fragment: Fragment) {
+        if let Some(hasher) = &mut self.hasher {
+            self.size += hash_fragment(hasher, &fragment);
+        }
         self.fragments.push(fragment)
     }
 
@@ -77,7 +143,63 @@ impl ContentsBuilder {
     where
         I: IntoIterator<Item = Fragment>,
     {
-        self.fragments.extend(fragments);
+        for fragment in fragments {
+            self.push(fragment);
+        }
         self
     }
+
+    /// remove the fragment at the given index from the block to build
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> Fragment {
+        self.hasher = None;
+        self.fragments.remove(index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.fragments.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fragments.is_empty()
+    }
+
+    /// hash and size of the contents built so far, same as
+    /// `Contents::compute_hash_size` on the final contents
+    pub fn current_hash_size(&mut self) -> (BlockContentHash, BlockContentSize) {
+        let fragments = &self.fragments;
+        let size = &mut self.size;
+        let hasher = self.hasher.get_or_insert_with(|| {
+            let mut hasher = content_hasher();
+            *size = fragments
+                .iter()
+                .map(|fragment| hash_fragment(&mut hasher, fragment))
+                .sum();
+            hasher
+        });
+
+        let mut out = [0; CONTENT_HASH_SIZE];
+        hasher.clone().result(&mut out);
+        (Hash::from_bytes(out), self.size)
+    }
+
+    /// hash and size of the contents built so far with the given hash
+    /// scheme, same as `Contents::compute_hash_size_with` on the final
+    /// contents. Only the sequential hash is maintained as the fragments are
+    /// pushed, the Merkle tree is hashed on every call.
+    pub fn current_hash_size_with(
+        &mut self,
+        scheme: ContentHashScheme,
+    ) -> (BlockContentHash, BlockContentSize) {
+        match scheme {
+            ContentHashScheme::Sequential => self.current_hash_size(),
+            ContentHashScheme::MerkleTree => {
+                let (_, size) = self.current_hash_size();
+                (merkle_root(self.fragments.iter().map(Fragment::hash)), size)
+            }
+        }
+    }
 }
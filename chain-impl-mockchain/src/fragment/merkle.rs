@@ -0,0 +1,253 @@
+//! Merkle tree of the fragment ids of a block.
+//!
+//! With [`ContentHashScheme::MerkleTree`](super::ContentHashScheme), the
+//! content hash of a block is the root of a binary tree over the ids of its
+//! fragments, in the order of the block. A leaf is `H(0x00 || id)` and an
+//! inner node `H(0x01 || left || right)`, so that a leaf can never be taken
+//! for a node. The last node of a level without a sibling is promoted to the
+//! next level as is. The tree of no fragment has the hash of the empty
+//! contents for root, the same as with the sequential hash.
+//!
+//! An [`InclusionProof`] holds the siblings of the path from the leaf of a
+//! fragment to the root: a light client knowing only the header of a block
+//! can check that a fragment is in the block, without its other fragments.
+
+use super::FragmentId;
+use crate::key::Hash;
+use chain_core::{
+    mempack::ReadError,
+    packer::Codec,
+    property::{ByteSource, Pack, Unpack},
+};
+
+const LEAF_TAG: u8 = 0;
+const NODE_TAG: u8 = 1;
+
+fn hash_leaf(id: &FragmentId) -> Hash {
+    let mut bytes = [0u8; 33];
+    bytes[0] = LEAF_TAG;
+    bytes[1..].copy_from_slice(id.as_ref());
+    Hash::hash_bytes(&bytes)
+}
+
+fn hash_node(left: &Hash, right: &Hash) -> Hash {
+    let mut bytes = [0u8; 65];
+    bytes[0] = NODE_TAG;
+    bytes[1..33].copy_from_slice(left.as_ref());
+    bytes[33..].copy_from_slice(right.as_ref());
+    Hash::hash_bytes(&bytes)
+}
+
+fn next_level(level: &[Hash]) -> Vec<Hash> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => hash_node(left, right),
+            [last] => *last,
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+/// Root of the Merkle tree of the fragment ids
+pub(super) fn merkle_root<I>(ids: I) -> Hash
+where
+    I: IntoIterator<Item = FragmentId>,
+{
+    let mut level: Vec<Hash> = ids.into_iter().map(|id| hash_leaf(&id)).collect();
+    if level.is_empty() {
+        return Hash::hash_bytes(&[]);
+    }
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    level[0]
+}
+
+/// Proof that a fragment is in the contents of a block, checked against the
+/// content hash of the header only
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InclusionProof {
+    /// index of the fragment in the block
+    pub index: u32,
+    /// number of fragments of the block
+    pub leaf_count: u32,
+    /// the siblings of the path from the leaf to the root, from the leaf up,
+    /// without the levels where the path has no sibling
+    pub siblings: Vec<Hash>,
+}
+
+impl InclusionProof {
+    /// The proof of the fragment at `index` among the fragment ids,
+    /// `None` if the index is out of bounds
+    pub(crate) fn new(ids: &[FragmentId], index: usize) -> Option<Self> {
+        if index >= ids.len() {
+            return None;
+        }
+        let mut level: Vec<Hash> = ids.iter().map(hash_leaf).collect();
+        let mut position = index;
+        let mut siblings = Vec::new();
+        while level.len() > 1 {
+            let sibling = position ^ 1;
+            if sibling < level.len() {
+                siblings.push(level[sibling]);
+            }
+            level = next_level(&level);
+            position /= 2;
+        }
+        Some(InclusionProof {
+            index: index as u32,
+            leaf_count: ids.len() as u32,
+            siblings,
+        })
+    }
+
+    /// The root of the tree the proof leads to from the fragment id, `None`
+    /// if the proof is not well formed
+    pub fn root(&self, fragment_id: &FragmentId) -> Option<Hash> {
+        if self.index >= self.leaf_count {
+            return None;
+        }
+        let mut siblings = self.siblings.iter();
+        let mut node = hash_leaf(fragment_id);
+        let mut position = self.index;
+        let mut width = self.leaf_count;
+        while width > 1 {
+            let promoted = position == width - 1 && width % 2 == 1;
+            if !promoted {
+                let sibling = siblings.next()?;
+                node = if position % 2 == 0 {
+                    hash_node(&node, sibling)
+                } else {
+                    hash_node(sibling, &node)
+                };
+            }
+            position /= 2;
+            width = (width + 1) / 2;
+        }
+        match siblings.next() {
+            Some(_) => None,
+            None => Some(node),
+        }
+    }
+
+    /// Check that the fragment is in the contents of the given content hash
+    pub fn verify(&self, fragment_id: &FragmentId, content_hash: &Hash) -> bool {
+        self.root(fragment_id).as_ref() == Some(content_hash)
+    }
+}
+
+impl Pack for InclusionProof {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_be_u32(self.index)?;
+        codec.put_be_u32(self.leaf_count)?;
+        codec.put_size_u8(self.siblings.len())?;
+        for sibling in &self.siblings {
+            codec.put_bytes(sibling.as_ref())?;
+        }
+        Ok(())
+    }
+}
+
+impl Unpack for InclusionProof {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let index = source.take_u32()?;
+        let leaf_count = source.take_u32()?;
+        let count = source.take_u8()?;
+        let siblings = (0..count)
+            .map(|_| Hash::unpack(source))
+            .collect::<Result<_, _>>()?;
+        Ok(InclusionProof {
+            index,
+            leaf_count,
+            siblings,
+        })
+    }
+}
+
+chain_ser::impl_legacy_codecs!(InclusionProof);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck::TestResult;
+    use quickcheck_macros::quickcheck;
+
+    fn ids(count: usize) -> Vec<FragmentId> {
+        (0..count as u32)
+            .map(|n| FragmentId::hash_bytes(&n.to_be_bytes()))
+            .collect()
+    }
+
+    #[test]
+    fn empty_tree_has_the_hash_of_the_empty_contents() {
+        assert_eq!(merkle_root(Vec::new()), Hash::hash_bytes(&[]));
+    }
+
+    #[test]
+    fn single_fragment_root_is_its_leaf() {
+        let ids = ids(1);
+        assert_eq!(merkle_root(ids.clone()), hash_leaf(&ids[0]));
+        let proof = InclusionProof::new(&ids, 0).unwrap();
+        assert!(proof.siblings.is_empty());
+        assert!(proof.verify(&ids[0], &merkle_root(ids.clone())));
+    }
+
+    #[quickcheck]
+    fn every_fragment_has_a_valid_proof(count: u8) -> TestResult {
+        let ids = ids(count as usize);
+        let root = merkle_root(ids.clone());
+        for (index, id) in ids.iter().enumerate() {
+            let proof = InclusionProof::new(&ids, index).unwrap();
+            if !proof.verify(id, &root) {
+                return TestResult::error(format!("invalid proof of the fragment {}", index));
+            }
+        }
+        TestResult::from_bool(InclusionProof::new(&ids, ids.len()).is_none())
+    }
+
+    #[test]
+    fn proof_of_another_fragment_is_rejected() {
+        let ids = ids(5);
+        let root = merkle_root(ids.clone());
+        let proof = InclusionProof::new(&ids, 2).unwrap();
+        assert!(!proof.verify(&ids[3], &root));
+        assert!(!proof.verify(&ids[2], &Hash::hash_bytes(&[])));
+    }
+
+    #[test]
+    fn malformed_proofs_are_rejected() {
+        let ids = ids(6);
+        let root = merkle_root(ids.clone());
+        let proof = InclusionProof::new(&ids, 4).unwrap();
+
+        let mut extra_sibling = proof.clone();
+        extra_sibling.siblings.push(root);
+        assert_eq!(extra_sibling.root(&ids[4]), None);
+
+        let mut missing_sibling = proof.clone();
+        missing_sibling.siblings.pop();
+        assert_eq!(missing_sibling.root(&ids[4]), None);
+
+        let out_of_bounds = InclusionProof {
+            index: 6,
+            ..proof.clone()
+        };
+        assert_eq!(out_of_bounds.root(&ids[4]), None);
+
+        let wrong_index = InclusionProof { index: 5, ..proof };
+        assert!(!wrong_index.verify(&ids[4], &root));
+    }
+
+    #[test]
+    fn serialization_bijection() {
+        use chain_core::mempack::{ReadBuf, Readable};
+        use chain_core::property::Serialize;
+
+        let ids = ids(9);
+        let proof = InclusionProof::new(&ids, 7).unwrap();
+        let bytes = proof.serialize_as_vec().unwrap();
+        let decoded = InclusionProof::read(&mut ReadBuf::from(&bytes)).unwrap();
+        assert_eq!(decoded, proof);
+    }
+}
@@ -1,20 +1,30 @@
 pub mod config;
 mod content;
+mod merkle;
+pub mod pool;
 mod raw;
+pub mod relay;
 
+use crate::key::IdHashAlgorithm;
 use crate::legacy;
+use chain_core::layout::{DescribeLayout, Field, Int, Layout, Variant};
 use chain_core::mempack::{ReadBuf, ReadError, Readable};
 use chain_core::property;
 
 pub use config::ConfigParams;
-pub use raw::{FragmentId, FragmentRaw};
+pub use pool::{FragmentPool, FragmentPoolError, PendingFragment};
+pub use raw::{FragmentId, FragmentRaw, FRAGMENT_SIZE_BYTES_LEN};
+pub use relay::{RelayDecision, RelayPolicy};
 
-pub use content::{BlockContentHash, BlockContentSize, Contents, ContentsBuilder};
+pub use content::{
+    BlockContentHash, BlockContentSize, ContentHashScheme, Contents, ContentsBuilder,
+};
+pub use merkle::InclusionProof;
 
 use crate::{
     certificate,
     evm::EvmTransaction,
-    transaction::{NoExtra, Transaction},
+    transaction::{NoExtra, Transaction, TransactionBatch},
 };
 
 #[cfg(any(test, feature = "property-test-api"))]
@@ -36,9 +46,23 @@ pub enum Fragment {
     VotePlan(Transaction<certificate::VotePlan>),
     VoteCast(Transaction<certificate::VoteCast>),
     VoteTally(Transaction<certificate::VoteTally>),
+    /// rejected from the `PROTOCOL_VERSION_UNIFIED_VOTE_TALLY` protocol
+    /// version on, the private tally is started by the `VoteTally`
     EncryptedVoteTally(Transaction<certificate::EncryptedVoteTally>),
     MintToken(Transaction<certificate::MintToken>),
     Evm(Transaction<EvmTransaction>),
+    TransactionBatch(TransactionBatch),
+    NameClaim(Transaction<certificate::NameClaim>),
+    NameRelease(Transaction<certificate::NameRelease>),
+    VotingPowerSnapshot(Transaction<certificate::VotingPowerSnapshot>),
+    SnapshotVoteCast(Transaction<certificate::SnapshotVoteCast>),
+    VoteCastBatch(Transaction<certificate::VoteCastBatch>),
+    AmountStakeDelegation(Transaction<certificate::AmountStakeDelegation>),
+    RewardsRedelegation(Transaction<certificate::RewardsRedelegation>),
+    LegacyMigration(legacy::MigrationCertificate),
+    /// a certificate of an extension, see [`certificate::EXTENSION_TAGS`]
+    ExtensionCertificate(Transaction<certificate::ExtensionCertificate>),
+    VotePlanCancel(Transaction<certificate::VotePlanCancel>),
 }
 
 impl PartialEq for Fragment {
@@ -67,6 +91,17 @@ pub(super) enum FragmentTag {
     EncryptedVoteTally = 13,
     MintToken = 14,
     Evm = 15,
+    TransactionBatch = 16,
+    NameClaim = 17,
+    NameRelease = 18,
+    VotingPowerSnapshot = 19,
+    SnapshotVoteCast = 20,
+    VoteCastBatch = 21,
+    AmountStakeDelegation = 22,
+    LegacyMigration = 23,
+    RewardsRedelegation = 24,
+    ExtensionCertificate = 25,
+    VotePlanCancel = 26,
 }
 
 impl FragmentTag {
@@ -88,9 +123,53 @@ impl FragmentTag {
             13 => Some(FragmentTag::EncryptedVoteTally),
             14 => Some(FragmentTag::MintToken),
             15 => Some(FragmentTag::Evm),
+            16 => Some(FragmentTag::TransactionBatch),
+            17 => Some(FragmentTag::NameClaim),
+            18 => Some(FragmentTag::NameRelease),
+            19 => Some(FragmentTag::VotingPowerSnapshot),
+            20 => Some(FragmentTag::SnapshotVoteCast),
+            21 => Some(FragmentTag::VoteCastBatch),
+            22 => Some(FragmentTag::AmountStakeDelegation),
+            23 => Some(FragmentTag::LegacyMigration),
+            24 => Some(FragmentTag::RewardsRedelegation),
+            25 => Some(FragmentTag::ExtensionCertificate),
+            26 => Some(FragmentTag::VotePlanCancel),
             _ => None,
         }
     }
+
+    /// Stable name of the fragment kind, to label the metrics with
+    pub fn name(self) -> &'static str {
+        match self {
+            FragmentTag::Initial => "initial",
+            FragmentTag::OldUtxoDeclaration => "old_utxo_declaration",
+            FragmentTag::Transaction => "transaction",
+            FragmentTag::OwnerStakeDelegation => "owner_stake_delegation",
+            FragmentTag::StakeDelegation => "stake_delegation",
+            FragmentTag::PoolRegistration => "pool_registration",
+            FragmentTag::PoolRetirement => "pool_retirement",
+            FragmentTag::PoolUpdate => "pool_update",
+            FragmentTag::UpdateProposal => "update_proposal",
+            FragmentTag::UpdateVote => "update_vote",
+            FragmentTag::VotePlan => "vote_plan",
+            FragmentTag::VoteCast => "vote_cast",
+            FragmentTag::VoteTally => "vote_tally",
+            FragmentTag::EncryptedVoteTally => "encrypted_vote_tally",
+            FragmentTag::MintToken => "mint_token",
+            FragmentTag::Evm => "evm",
+            FragmentTag::TransactionBatch => "transaction_batch",
+            FragmentTag::NameClaim => "name_claim",
+            FragmentTag::NameRelease => "name_release",
+            FragmentTag::VotingPowerSnapshot => "voting_power_snapshot",
+            FragmentTag::SnapshotVoteCast => "snapshot_vote_cast",
+            FragmentTag::VoteCastBatch => "vote_cast_batch",
+            FragmentTag::AmountStakeDelegation => "amount_stake_delegation",
+            FragmentTag::LegacyMigration => "legacy_migration",
+            FragmentTag::RewardsRedelegation => "rewards_redelegation",
+            FragmentTag::ExtensionCertificate => "extension_certificate",
+            FragmentTag::VotePlanCancel => "vote_plan_cancel",
+        }
+    }
 }
 
 impl Fragment {
@@ -113,6 +192,51 @@ impl Fragment {
             Fragment::EncryptedVoteTally(_) => FragmentTag::EncryptedVoteTally,
             Fragment::MintToken(_) => FragmentTag::MintToken,
             Fragment::Evm(_) => FragmentTag::Evm,
+            Fragment::TransactionBatch(_) => FragmentTag::TransactionBatch,
+            Fragment::NameClaim(_) => FragmentTag::NameClaim,
+            Fragment::NameRelease(_) => FragmentTag::NameRelease,
+            Fragment::VotingPowerSnapshot(_) => FragmentTag::VotingPowerSnapshot,
+            Fragment::SnapshotVoteCast(_) => FragmentTag::SnapshotVoteCast,
+            Fragment::VoteCastBatch(_) => FragmentTag::VoteCastBatch,
+            Fragment::AmountStakeDelegation(_) => FragmentTag::AmountStakeDelegation,
+            Fragment::LegacyMigration(_) => FragmentTag::LegacyMigration,
+            Fragment::RewardsRedelegation(_) => FragmentTag::RewardsRedelegation,
+            Fragment::ExtensionCertificate(_) => FragmentTag::ExtensionCertificate,
+            Fragment::VotePlanCancel(_) => FragmentTag::VotePlanCancel,
+        }
+    }
+
+    /// The kind of the certificate carried by the fragment, if any
+    pub fn certificate_kind(&self) -> Option<certificate::CertificateKind> {
+        use certificate::CertificateKind;
+        match self {
+            Fragment::OwnerStakeDelegation(_) => Some(CertificateKind::OwnerStakeDelegation),
+            Fragment::StakeDelegation(_) => Some(CertificateKind::StakeDelegation),
+            Fragment::PoolRegistration(_) => Some(CertificateKind::PoolRegistration),
+            Fragment::PoolRetirement(_) => Some(CertificateKind::PoolRetirement),
+            Fragment::PoolUpdate(_) => Some(CertificateKind::PoolUpdate),
+            Fragment::UpdateProposal(_) => Some(CertificateKind::UpdateProposal),
+            Fragment::UpdateVote(_) => Some(CertificateKind::UpdateVote),
+            Fragment::VotePlan(_) => Some(CertificateKind::VotePlan),
+            Fragment::VoteCast(_) => Some(CertificateKind::VoteCast),
+            Fragment::VoteTally(_) => Some(CertificateKind::VoteTally),
+            Fragment::EncryptedVoteTally(_) => Some(CertificateKind::EncryptedVoteTally),
+            Fragment::MintToken(_) => Some(CertificateKind::MintToken),
+            Fragment::NameClaim(_) => Some(CertificateKind::NameClaim),
+            Fragment::NameRelease(_) => Some(CertificateKind::NameRelease),
+            Fragment::VotingPowerSnapshot(_) => Some(CertificateKind::VotingPowerSnapshot),
+            Fragment::SnapshotVoteCast(_) => Some(CertificateKind::SnapshotVoteCast),
+            Fragment::VoteCastBatch(_) => Some(CertificateKind::VoteCastBatch),
+            Fragment::AmountStakeDelegation(_) => Some(CertificateKind::AmountStakeDelegation),
+            Fragment::RewardsRedelegation(_) => Some(CertificateKind::RewardsRedelegation),
+            Fragment::ExtensionCertificate(_) => Some(CertificateKind::Extension),
+            Fragment::VotePlanCancel(_) => Some(CertificateKind::VotePlanCancel),
+            Fragment::Initial(_)
+            | Fragment::OldUtxoDeclaration(_)
+            | Fragment::Transaction(_)
+            | Fragment::Evm(_)
+            | Fragment::TransactionBatch(_)
+            | Fragment::LegacyMigration(_) => None,
         }
     }
 
@@ -141,18 +265,55 @@ impl Fragment {
             Fragment::EncryptedVoteTally(vote_tally) => vote_tally.serialize(&mut codec).unwrap(),
             Fragment::MintToken(mint_token) => mint_token.serialize(&mut codec).unwrap(),
             Fragment::Evm(deployment) => deployment.serialize(&mut codec).unwrap(),
+            Fragment::TransactionBatch(batch) => batch.serialize(&mut codec).unwrap(),
+            Fragment::NameClaim(name_claim) => name_claim.serialize(&mut codec).unwrap(),
+            Fragment::NameRelease(name_release) => name_release.serialize(&mut codec).unwrap(),
+            Fragment::VotingPowerSnapshot(snapshot) => snapshot.serialize(&mut codec).unwrap(),
+            Fragment::SnapshotVoteCast(vote_cast) => vote_cast.serialize(&mut codec).unwrap(),
+            Fragment::VoteCastBatch(batch) => batch.serialize(&mut codec).unwrap(),
+            Fragment::AmountStakeDelegation(od) => od.serialize(&mut codec).unwrap(),
+            Fragment::LegacyMigration(migration) => migration.serialize(&mut codec).unwrap(),
+            Fragment::RewardsRedelegation(od) => od.serialize(&mut codec).unwrap(),
+            Fragment::ExtensionCertificate(tx) => tx.serialize(&mut codec).unwrap(),
+            Fragment::VotePlanCancel(tx) => tx.serialize(&mut codec).unwrap(),
         }
         FragmentRaw(codec.into_inner())
     }
 
+    /// Decode a fragment from its raw representation.
+    ///
+    /// The whole raw buffer has to be consumed: accepting trailing bytes
+    /// would allow different raw fragments (and so different ids) to
+    /// decode into the same fragment.
     pub fn from_raw(raw: &FragmentRaw) -> Result<Self, ReadError> {
         let mut buf = ReadBuf::from(raw.as_ref());
-        Fragment::read(&mut buf)
+        let fragment = Fragment::read(&mut buf)?;
+        buf.expect_end()?;
+        Ok(fragment)
     }
 
-    /// The ID of a message is a hash of its serialization *without* the size.
+    /// The exact bytes hashed to compute the ID of the fragment, for every
+    /// type of fragment:
+    ///
+    /// * a padding byte, always `0`;
+    /// * the tag of the fragment type;
+    /// * the serialization of the content of the fragment.
+    ///
+    /// This is the raw representation of the fragment: the size prefix
+    /// written in front of it in the block contents is *not* included.
+    pub fn id_preimage(&self) -> Vec<u8> {
+        self.to_raw().0
+    }
+
+    /// The ID of a message is a hash of its serialization *without* the size,
+    /// see [`Fragment::id_preimage`].
     pub fn hash(&self) -> FragmentId {
-        self.to_raw().id()
+        self.hash_with()
+    }
+
+    /// The ID of the fragment computed with the hash algorithm `H`
+    pub fn hash_with<H: IdHashAlgorithm>(&self) -> FragmentId<H> {
+        self.to_raw().id_with()
     }
 
     /// How many bytes it will take up once serialized in a block
@@ -165,10 +326,10 @@ impl Readable for Fragment {
     fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
         let padding_tag = buf.get_u8()?;
         if padding_tag != 0 {
-            return Err(ReadError::StructureInvalid(format!(
-                "fragment padding tag expected at 0 but got {}",
-                padding_tag
-            )));
+            return Err(ReadError::UnknownTag {
+                kind: "fragment padding",
+                tag: padding_tag as u32,
+            });
         }
 
         let tag = buf.get_u8()?;
@@ -203,7 +364,83 @@ impl Readable for Fragment {
             }
             Some(FragmentTag::MintToken) => Transaction::read(buf).map(Fragment::MintToken),
             Some(FragmentTag::Evm) => Transaction::read(buf).map(Fragment::Evm),
-            None => Err(ReadError::UnknownTag(tag as u32)),
+            Some(FragmentTag::TransactionBatch) => {
+                TransactionBatch::read(buf).map(Fragment::TransactionBatch)
+            }
+            Some(FragmentTag::NameClaim) => Transaction::read(buf).map(Fragment::NameClaim),
+            Some(FragmentTag::NameRelease) => Transaction::read(buf).map(Fragment::NameRelease),
+            Some(FragmentTag::VotingPowerSnapshot) => {
+                Transaction::read(buf).map(Fragment::VotingPowerSnapshot)
+            }
+            Some(FragmentTag::SnapshotVoteCast) => {
+                Transaction::read(buf).map(Fragment::SnapshotVoteCast)
+            }
+            Some(FragmentTag::VoteCastBatch) => Transaction::read(buf).map(Fragment::VoteCastBatch),
+            Some(FragmentTag::AmountStakeDelegation) => {
+                Transaction::read(buf).map(Fragment::AmountStakeDelegation)
+            }
+            Some(FragmentTag::LegacyMigration) => {
+                legacy::MigrationCertificate::read(buf).map(Fragment::LegacyMigration)
+            }
+            Some(FragmentTag::RewardsRedelegation) => {
+                Transaction::read(buf).map(Fragment::RewardsRedelegation)
+            }
+            Some(FragmentTag::ExtensionCertificate) => {
+                Transaction::read(buf).map(Fragment::ExtensionCertificate)
+            }
+            Some(FragmentTag::VotePlanCancel) => {
+                Transaction::read(buf).map(Fragment::VotePlanCancel)
+            }
+            None => Err(ReadError::UnknownTag {
+                kind: "fragment",
+                tag: tag as u32,
+            }),
+        }
+    }
+}
+
+impl DescribeLayout for Fragment {
+    const NAME: &'static str = "Fragment";
+
+    fn layout() -> Layout {
+        Layout::Fields(vec![Field::prefixed_of::<RawLayout>("raw", Int::U32)
+            .describe("the raw fragment, hashed for the fragment id")])
+    }
+}
+
+/// the layout of a [`FragmentRaw`] without its size
+struct RawLayout;
+
+impl DescribeLayout for RawLayout {
+    const NAME: &'static str = "Fragment raw";
+
+    fn layout() -> Layout {
+        Layout::Fields(vec![
+            Field::fixed("padding", 1).describe("always 0"),
+            Field::of::<ContentLayout>("content"),
+        ])
+    }
+}
+
+struct ContentLayout;
+
+impl DescribeLayout for ContentLayout {
+    const NAME: &'static str = "Fragment content";
+
+    fn layout() -> Layout {
+        let variants = (0..=u8::MAX)
+            .filter_map(FragmentTag::from_u8)
+            .map(|tag| {
+                Variant::new(
+                    tag as u64,
+                    tag.name(),
+                    vec![Field::remaining("content").describe("the content of the fragment kind")],
+                )
+            })
+            .collect();
+        Layout::Tagged {
+            tag: Int::U8,
+            variants,
         }
     }
 }
@@ -215,6 +452,9 @@ impl property::Serialize for Fragment {
     }
 }
 
+// The stream codecs of the fragment read and write the size prefixed
+// `FragmentRaw`, while `Readable` reads the fragment content only, so the
+// fragment cannot use `impl_legacy_codecs`.
 impl property::Deserialize for Fragment {
     type Error = std::io::Error;
     fn deserialize<R: std::io::BufRead>(reader: R) -> Result<Self, Self::Error> {
@@ -0,0 +1,221 @@
+//! On-disk format for a pool of pending fragments.
+//!
+//! A node can save its pending fragments on shutdown and rehydrate them on
+//! restart. The format is made of a header, an index and the fragments data:
+//!
+//! ```text
+//! MAGIC (4 bytes) VERSION (u8) NB_ENTRIES (u32)
+//! NB_ENTRIES * [ FRAGMENT_ID (32 bytes) EXPIRY_EPOCH (u32) EXPIRY_SLOT (u32) OFFSET (u64) ]
+//! NB_ENTRIES * [ SIZE (u32) RAW_FRAGMENT (SIZE bytes) ]
+//! ```
+//!
+//! All integers are big endian, the offsets are relative to the start of the
+//! fragments data. The index can be read without decoding the fragments, so
+//! the expired fragments are skipped cheaply when loading. Fragments are
+//! content addressed: the identifier of every loaded fragment is checked
+//! against the index.
+
+use super::{Fragment, FragmentId, FragmentRaw};
+use crate::date::BlockDate;
+use chain_core::{
+    mempack::{ReadBuf, ReadError, Readable},
+    packer::Codec,
+    property::Serialize as _,
+};
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use thiserror::Error;
+
+const MAGIC: &[u8; 4] = b"JFPL";
+const VERSION: u8 = 1;
+
+#[derive(Debug, Error)]
+pub enum FragmentPoolError {
+    #[error("I/O error")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid fragment pool encoding")]
+    Read(#[from] ReadError),
+    #[error("Not a fragment pool file")]
+    InvalidMagic,
+    #[error("Unsupported fragment pool version {0}")]
+    UnsupportedVersion(u8),
+    #[error("Fragment {actual} found where {expected} is indexed")]
+    IdMismatch {
+        expected: FragmentId,
+        actual: FragmentId,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingFragment {
+    pub raw: FragmentRaw,
+    /// the date after which the fragment can be dropped from the pool
+    pub expiry: BlockDate,
+}
+
+/// Pending fragments, indexed by their identifier
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FragmentPool {
+    entries: BTreeMap<FragmentId, PendingFragment>,
+}
+
+impl FragmentPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Add the fragment to the pool, returns its identifier.
+    ///
+    /// If the fragment is already in the pool, only its expiry date is updated.
+    pub fn insert(&mut self, fragment: &Fragment, expiry: BlockDate) -> FragmentId {
+        self.insert_raw(fragment.to_raw(), expiry)
+    }
+
+    pub fn insert_raw(&mut self, raw: FragmentRaw, expiry: BlockDate) -> FragmentId {
+        let id = raw.id();
+        self.entries.insert(id, PendingFragment { raw, expiry });
+        id
+    }
+
+    pub fn remove(&mut self, id: &FragmentId) -> Option<PendingFragment> {
+        self.entries.remove(id)
+    }
+
+    pub fn get(&self, id: &FragmentId) -> Option<&PendingFragment> {
+        self.entries.get(id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&FragmentId, &PendingFragment)> {
+        self.entries.iter()
+    }
+
+    /// Drop the fragments expired at the given date, returns how many were dropped.
+    pub fn remove_expired(&mut self, date: BlockDate) -> usize {
+        let before = self.entries.len();
+        self.entries.retain(|_, entry| entry.expiry >= date);
+        before - self.entries.len()
+    }
+
+    pub fn save<W: Write>(&self, writer: W) -> Result<(), FragmentPoolError> {
+        let mut codec = Codec::new(writer);
+        codec.put_bytes(MAGIC)?;
+        codec.put_u8(VERSION)?;
+        codec.put_be_u32(self.entries.len() as u32)?;
+
+        let mut offset = 0u64;
+        for (id, entry) in self.entries.iter() {
+            codec.put_bytes(id.as_ref())?;
+            codec.put_be_u32(entry.expiry.epoch)?;
+            codec.put_be_u32(entry.expiry.slot_id)?;
+            codec.put_be_u64(offset)?;
+            offset += entry.raw.size_bytes_plus_size() as u64;
+        }
+        for entry in self.entries.values() {
+            entry.raw.serialize(&mut codec)?;
+        }
+        Ok(())
+    }
+
+    /// Load all the fragments of a saved pool.
+    pub fn load<R: Read>(reader: R) -> Result<Self, FragmentPoolError> {
+        Self::load_filtered(reader, |_| true)
+    }
+
+    /// Load the fragments of a saved pool which have not expired at the given date.
+    pub fn load_unexpired<R: Read>(reader: R, date: BlockDate) -> Result<Self, FragmentPoolError> {
+        Self::load_filtered(reader, |expiry| expiry >= date)
+    }
+
+    fn load_filtered<R, F>(mut reader: R, keep: F) -> Result<Self, FragmentPoolError>
+    where
+        R: Read,
+        F: Fn(BlockDate) -> bool,
+    {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        let mut buf = ReadBuf::from(&bytes);
+
+        if buf.get_slice(MAGIC.len())? != MAGIC {
+            return Err(FragmentPoolError::InvalidMagic);
+        }
+        let version = buf.get_u8()?;
+        if version != VERSION {
+            return Err(FragmentPoolError::UnsupportedVersion(version));
+        }
+
+        let nb_entries = buf.get_u32()?;
+        let mut index = Vec::new();
+        for _ in 0..nb_entries {
+            let id = FragmentId::read(&mut buf)?;
+            let expiry = BlockDate {
+                epoch: buf.get_u32()?,
+                slot_id: buf.get_u32()?,
+            };
+            let offset = buf.get_u64()? as usize;
+            if keep(expiry) {
+                index.push((id, expiry, offset));
+            }
+        }
+        let data_offset = buf.position();
+        let data = buf.get_slice_end();
+
+        let mut pool = FragmentPool::new();
+        for (id, expiry, offset) in index {
+            let slice = data.get(offset..).ok_or(ReadError::NotEnoughBytes {
+                offset: data_offset,
+                left: data.len(),
+                demanded: offset,
+            })?;
+            let raw = FragmentRaw::read(&mut ReadBuf::from(slice))?;
+            if raw.id() != id {
+                return Err(FragmentPoolError::IdMismatch {
+                    expected: id,
+                    actual: raw.id(),
+                });
+            }
+            pool.entries.insert(id, PendingFragment { raw, expiry });
+        }
+        Ok(pool)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck_macros::quickcheck;
+
+    #[quickcheck]
+    fn fragment_pool_save_load(fragments: Vec<(Fragment, BlockDate)>) -> bool {
+        let mut pool = FragmentPool::new();
+        for (fragment, expiry) in fragments.iter() {
+            pool.insert(fragment, *expiry);
+        }
+        let mut bytes = Vec::new();
+        pool.save(&mut bytes).unwrap();
+        FragmentPool::load(bytes.as_slice()).unwrap() == pool
+    }
+
+    #[quickcheck]
+    fn fragment_pool_load_unexpired(
+        fragments: Vec<(Fragment, BlockDate)>,
+        date: BlockDate,
+    ) -> bool {
+        let mut pool = FragmentPool::new();
+        for (fragment, expiry) in fragments.iter() {
+            pool.insert(fragment, *expiry);
+        }
+        let mut bytes = Vec::new();
+        pool.save(&mut bytes).unwrap();
+        let loaded = FragmentPool::load_unexpired(bytes.as_slice(), date).unwrap();
+        pool.remove_expired(date);
+        loaded == pool
+    }
+}
@@ -1,8 +1,14 @@
-use crate::key::Hash;
-use chain_core::property;
-use chain_ser::mempack::{ReadBuf, ReadError, Readable};
-
-pub type FragmentId = Hash;
+use crate::key::{Id, IdHashAlgorithm};
+use chain_core::{
+    mempack::ReadError,
+    packer::Codec,
+    property::{ByteSource, Pack, Unpack},
+};
+use chain_crypto::Blake2b256;
+
+/// Identifier of a fragment, computed with the hash algorithm `H` (by
+/// default `Blake2b256`)
+pub type FragmentId<H = Blake2b256> = Id<H>;
 pub const FRAGMENT_SIZE_BYTES_LEN: usize = 4;
 
 /// A serialized Message
@@ -15,6 +21,11 @@ impl FragmentRaw {
     }
 
     pub fn id(&self) -> FragmentId {
+        self.id_with()
+    }
+
+    /// The id of the fragment computed with the hash algorithm `H`
+    pub fn id_with<H: IdHashAlgorithm>(&self) -> FragmentId<H> {
         FragmentId::hash_bytes(self.0.as_ref())
     }
 }
@@ -25,35 +36,18 @@ impl AsRef<[u8]> for FragmentRaw {
     }
 }
 
-impl Readable for FragmentRaw {
-    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        let size = buf.get_u32()?;
-        let mut v = vec![0u8; size as usize];
-        buf.copy_to_slice_mut(&mut v)?;
-        Ok(FragmentRaw(v))
+impl Pack for FragmentRaw {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_size_be_u32(self.0.len())?;
+        codec.put_bytes(&self.0)
     }
 }
 
-impl property::Deserialize for FragmentRaw {
-    type Error = std::io::Error;
-    fn deserialize<R: std::io::BufRead>(reader: R) -> Result<Self, Self::Error> {
-        use chain_core::packer::*;
-        let mut codec = Codec::new(reader);
-        let size = codec.get_be_u32()?;
-        let mut v = vec![0u8; size as usize];
-        codec.into_inner().read_exact(&mut v)?;
-        Ok(FragmentRaw(v))
+impl Unpack for FragmentRaw {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let size = source.take_u32()?;
+        source.take_vec(size as usize).map(FragmentRaw)
     }
 }
 
-impl property::Serialize for FragmentRaw {
-    type Error = std::io::Error;
-    fn serialize<W: std::io::Write>(&self, writer: W) -> Result<(), Self::Error> {
-        use chain_core::packer::*;
-
-        let mut codec = Codec::new(writer);
-        codec.put_be_u32(self.0.len() as u32)?;
-        codec.into_inner().write_all(&self.0)?;
-        Ok(())
-    }
-}
+chain_ser::impl_legacy_codecs!(FragmentRaw);
@@ -0,0 +1,333 @@
+//! Rebroadcast policy for the fragments received from the network.
+//!
+//! A node relaying the fragments it receives needs to drop the fragments it
+//! has already relayed, the fragments the ledger would reject because of
+//! their validity dates, and to stop an account from flooding the network.
+//! The [`RelayPolicy`] answers whether a fragment should be rebroadcast:
+//!
+//! * a fragment is remembered until its expiry date, after which the ledger
+//!   rejects it anyway, so the memory of the policy is bounded by the
+//!   fragments which can still be applied;
+//! * the validity dates are checked the way the ledger checks them, with the
+//!   settings of the ledger;
+//! * the fragments spending from the same account are limited to a number
+//!   of unexpired fragments relayed at the same time.
+//!
+//! The witnesses are not verified: the policy is only a cheap filter applied
+//! before the fragments are checked against the ledger.
+
+use super::{Fragment, FragmentId};
+use crate::date::BlockDate;
+use crate::ledger::check::{valid_transaction_date, TxValidityError};
+use crate::setting::Settings;
+use crate::transaction::{InputEnum, TransactionSlice, UnspecifiedAccountIdentifier};
+use std::collections::{BTreeMap, HashMap};
+
+/// The answer of the policy for a received fragment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RelayDecision {
+    /// the fragment is new, it is now remembered as relayed
+    Rebroadcast,
+    /// the fragment has already been relayed
+    AlreadySeen,
+    /// the account the fragment spends from has too many fragments relayed
+    RateLimited,
+    /// the ledger would reject the fragment at this date
+    InvalidDate(TxValidityError),
+    /// the fragment is only found in the block0
+    NotRelayable,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RelayedFragment {
+    expiry: BlockDate,
+    origin: Option<UnspecifiedAccountIdentifier>,
+}
+
+/// The fragments relayed and not expired yet, indexed by their identifier
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelayPolicy {
+    max_per_origin: usize,
+    relayed: BTreeMap<FragmentId, RelayedFragment>,
+    per_origin: HashMap<UnspecifiedAccountIdentifier, usize>,
+}
+
+impl RelayPolicy {
+    /// New policy relaying at most `max_per_origin` unexpired fragments
+    /// spending from the same account
+    pub fn new(max_per_origin: usize) -> Self {
+        RelayPolicy {
+            max_per_origin,
+            relayed: BTreeMap::new(),
+            per_origin: HashMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.relayed.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.relayed.is_empty()
+    }
+
+    pub fn is_relayed(&self, id: &FragmentId) -> bool {
+        self.relayed.contains_key(id)
+    }
+
+    /// number of unexpired fragments relayed for the account
+    pub fn relayed_by(&self, origin: &UnspecifiedAccountIdentifier) -> usize {
+        self.per_origin.get(origin).copied().unwrap_or(0)
+    }
+
+    /// Decide whether the fragment received at the given date should be
+    /// rebroadcast, and remember it if so.
+    ///
+    /// A rate limited fragment is not remembered, so it can be relayed once
+    /// the fragments of its account have expired.
+    pub fn should_rebroadcast(
+        &mut self,
+        fragment: &Fragment,
+        settings: &Settings,
+        date: BlockDate,
+    ) -> RelayDecision {
+        let id = fragment.hash();
+        if self.relayed.contains_key(&id) {
+            return RelayDecision::AlreadySeen;
+        }
+        let expiry = match fragment_expiry(fragment) {
+            Some(expiry) => expiry,
+            None => return RelayDecision::NotRelayable,
+        };
+        if let Err(error) = valid_transaction_date(settings, expiry, date) {
+            return RelayDecision::InvalidDate(error);
+        }
+        let origin = fragment_origin(fragment);
+        if let Some(origin) = &origin {
+            if self.relayed_by(origin) >= self.max_per_origin {
+                return RelayDecision::RateLimited;
+            }
+            *self.per_origin.entry(origin.clone()).or_insert(0) += 1;
+        }
+        self.relayed.insert(id, RelayedFragment { expiry, origin });
+        RelayDecision::Rebroadcast
+    }
+
+    /// Forget the fragments expired at the given date, returns how many were
+    /// forgotten.
+    pub fn remove_expired(&mut self, date: BlockDate) -> usize {
+        let before = self.relayed.len();
+        let per_origin = &mut self.per_origin;
+        self.relayed.retain(|_, relayed| {
+            if relayed.expiry >= date {
+                return true;
+            }
+            if let Some(origin) = &relayed.origin {
+                if let Some(count) = per_origin.get_mut(origin) {
+                    *count -= 1;
+                    if *count == 0 {
+                        per_origin.remove(origin);
+                    }
+                }
+            }
+            false
+        });
+        before - self.relayed.len()
+    }
+}
+
+/// The date after which the ledger rejects the fragment, `None` for the
+/// fragments of the block0.
+///
+/// A batch expires with the first of its transactions to expire.
+pub fn fragment_expiry(fragment: &Fragment) -> Option<BlockDate> {
+    match fragment {
+        Fragment::Initial(_) | Fragment::OldUtxoDeclaration(_) => None,
+        Fragment::Transaction(tx) => Some(tx.as_slice().valid_until()),
+        Fragment::OwnerStakeDelegation(tx) => Some(tx.as_slice().valid_until()),
+        Fragment::StakeDelegation(tx) => Some(tx.as_slice().valid_until()),
+        Fragment::AmountStakeDelegation(tx) => Some(tx.as_slice().valid_until()),
+        Fragment::RewardsRedelegation(tx) => Some(tx.as_slice().valid_until()),
+        Fragment::ExtensionCertificate(tx) => Some(tx.as_slice().valid_until()),
+        Fragment::VotePlanCancel(tx) => Some(tx.as_slice().valid_until()),
+        Fragment::LegacyMigration(migration) => Some(migration.valid_until),
+        Fragment::PoolRegistration(tx) => Some(tx.as_slice().valid_until()),
+        Fragment::PoolRetirement(tx) => Some(tx.as_slice().valid_until()),
+        Fragment::PoolUpdate(tx) => Some(tx.as_slice().valid_until()),
+        Fragment::UpdateProposal(tx) => Some(tx.as_slice().valid_until()),
+        Fragment::UpdateVote(tx) => Some(tx.as_slice().valid_until()),
+        Fragment::VotePlan(tx) => Some(tx.as_slice().valid_until()),
+        Fragment::VoteCast(tx) => Some(tx.as_slice().valid_until()),
+        Fragment::VoteTally(tx) => Some(tx.as_slice().valid_until()),
+        Fragment::EncryptedVoteTally(tx) => Some(tx.as_slice().valid_until()),
+        Fragment::MintToken(tx) => Some(tx.as_slice().valid_until()),
+        Fragment::Evm(tx) => Some(tx.as_slice().valid_until()),
+        Fragment::NameClaim(tx) => Some(tx.as_slice().valid_until()),
+        Fragment::NameRelease(tx) => Some(tx.as_slice().valid_until()),
+        Fragment::VotingPowerSnapshot(tx) => Some(tx.as_slice().valid_until()),
+        Fragment::SnapshotVoteCast(tx) => Some(tx.as_slice().valid_until()),
+        Fragment::VoteCastBatch(tx) => Some(tx.as_slice().valid_until()),
+        Fragment::TransactionBatch(batch) => batch
+            .transactions()
+            .iter()
+            .map(|tx| tx.as_slice().valid_until())
+            .min(),
+    }
+}
+
+/// The account the fragment spends from first, `None` if the fragment only
+/// spends utxos.
+pub fn fragment_origin(fragment: &Fragment) -> Option<UnspecifiedAccountIdentifier> {
+    match fragment {
+        Fragment::Initial(_) | Fragment::OldUtxoDeclaration(_) | Fragment::LegacyMigration(_) => {
+            None
+        }
+        Fragment::Transaction(tx) => transaction_origin(&tx.as_slice()),
+        Fragment::OwnerStakeDelegation(tx) => transaction_origin(&tx.as_slice()),
+        Fragment::StakeDelegation(tx) => transaction_origin(&tx.as_slice()),
+        Fragment::AmountStakeDelegation(tx) => transaction_origin(&tx.as_slice()),
+        Fragment::RewardsRedelegation(tx) => transaction_origin(&tx.as_slice()),
+        Fragment::ExtensionCertificate(tx) => transaction_origin(&tx.as_slice()),
+        Fragment::VotePlanCancel(tx) => transaction_origin(&tx.as_slice()),
+        Fragment::PoolRegistration(tx) => transaction_origin(&tx.as_slice()),
+        Fragment::PoolRetirement(tx) => transaction_origin(&tx.as_slice()),
+        Fragment::PoolUpdate(tx) => transaction_origin(&tx.as_slice()),
+        Fragment::UpdateProposal(tx) => transaction_origin(&tx.as_slice()),
+        Fragment::UpdateVote(tx) => transaction_origin(&tx.as_slice()),
+        Fragment::VotePlan(tx) => transaction_origin(&tx.as_slice()),
+        Fragment::VoteCast(tx) => transaction_origin(&tx.as_slice()),
+        Fragment::VoteTally(tx) => transaction_origin(&tx.as_slice()),
+        Fragment::EncryptedVoteTally(tx) => transaction_origin(&tx.as_slice()),
+        Fragment::MintToken(tx) => transaction_origin(&tx.as_slice()),
+        Fragment::Evm(tx) => transaction_origin(&tx.as_slice()),
+        Fragment::NameClaim(tx) => transaction_origin(&tx.as_slice()),
+        Fragment::NameRelease(tx) => transaction_origin(&tx.as_slice()),
+        Fragment::VotingPowerSnapshot(tx) => transaction_origin(&tx.as_slice()),
+        Fragment::SnapshotVoteCast(tx) => transaction_origin(&tx.as_slice()),
+        Fragment::VoteCastBatch(tx) => transaction_origin(&tx.as_slice()),
+        Fragment::TransactionBatch(batch) => batch
+            .transactions()
+            .iter()
+            .find_map(|tx| transaction_origin(&tx.as_slice())),
+    }
+}
+
+fn transaction_origin<P>(tx: &TransactionSlice<P>) -> Option<UnspecifiedAccountIdentifier> {
+    tx.inputs().iter().find_map(|input| match input.to_enum() {
+        InputEnum::AccountInput(account_id, _) => Some(account_id),
+        InputEnum::UtxoInput(_) => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{
+            builders::{witness_builder::make_witness, TestTx},
+            data::AddressDataValue,
+            TestGen,
+        },
+        transaction::{NoExtra, TxBuilder},
+        value::Value,
+    };
+    use chain_addr::Discrimination;
+
+    fn account_fragment(from: &AddressDataValue, value: u64, valid_until: BlockDate) -> Fragment {
+        let tx_builder = TxBuilder::new()
+            .set_payload(&NoExtra)
+            .set_expiry_date(valid_until)
+            .set_ios(&[from.make_input_with_value(None, Value(value))], &[]);
+        let witness = make_witness(
+            &TestGen::parent_id(),
+            &from.address_data,
+            &tx_builder.get_auth_data_for_witness().hash(),
+        );
+        TestTx::new(tx_builder.set_witnesses(&[witness]).set_payload_auth(&())).get_fragment()
+    }
+
+    #[test]
+    fn fragments_are_relayed_once() {
+        let account = AddressDataValue::account(Discrimination::Test, Value(1000));
+        let settings = Settings::new();
+        let date = BlockDate::first();
+        let fragment = account_fragment(&account, 1, date.next_epoch());
+        let mut policy = RelayPolicy::new(10);
+
+        assert_eq!(
+            policy.should_rebroadcast(&fragment, &settings, date),
+            RelayDecision::Rebroadcast
+        );
+        assert!(policy.is_relayed(&fragment.hash()));
+        assert_eq!(
+            policy.should_rebroadcast(&fragment, &settings, date),
+            RelayDecision::AlreadySeen
+        );
+    }
+
+    #[test]
+    fn fragments_are_checked_against_their_validity() {
+        let account = AddressDataValue::account(Discrimination::Test, Value(1000));
+        let settings = Settings::new();
+        let date = BlockDate {
+            epoch: 1,
+            slot_id: 0,
+        };
+        let mut policy = RelayPolicy::new(10);
+
+        let expired = account_fragment(&account, 1, BlockDate::first());
+        assert_eq!(
+            policy.should_rebroadcast(&expired, &settings, date),
+            RelayDecision::InvalidDate(TxValidityError::TransactionExpired)
+        );
+        let too_long = account_fragment(&account, 2, date.next_epoch().next_epoch());
+        assert_eq!(
+            policy.should_rebroadcast(&too_long, &settings, date),
+            RelayDecision::InvalidDate(TxValidityError::TransactionValidForTooLong)
+        );
+        assert!(policy.is_empty());
+    }
+
+    #[test]
+    fn origins_are_rate_limited_until_their_fragments_expire() {
+        let account = AddressDataValue::account(Discrimination::Test, Value(1000));
+        let other = AddressDataValue::account(Discrimination::Test, Value(1000));
+        let settings = Settings::new();
+        let date = BlockDate::first();
+        let expiry = date.next_epoch();
+        let mut policy = RelayPolicy::new(2);
+
+        for value in 1..=2 {
+            let fragment = account_fragment(&account, value, expiry);
+            assert_eq!(
+                policy.should_rebroadcast(&fragment, &settings, date),
+                RelayDecision::Rebroadcast
+            );
+        }
+        let limited = account_fragment(&account, 3, expiry);
+        assert_eq!(
+            policy.should_rebroadcast(&limited, &settings, date),
+            RelayDecision::RateLimited
+        );
+        assert!(!policy.is_relayed(&limited.hash()));
+        assert_eq!(
+            policy.should_rebroadcast(&account_fragment(&other, 1, expiry), &settings, date),
+            RelayDecision::Rebroadcast
+        );
+
+        let origin = fragment_origin(&limited).unwrap();
+        assert_eq!(policy.relayed_by(&origin), 2);
+        let later = BlockDate {
+            epoch: expiry.epoch,
+            slot_id: expiry.slot_id + 1,
+        };
+        assert_eq!(policy.remove_expired(later), 3);
+        assert_eq!(policy.relayed_by(&origin), 0);
+
+        let renewed = account_fragment(&account, 3, later.next_epoch());
+        assert_eq!(
+            policy.should_rebroadcast(&renewed, &settings, later),
+            RelayDecision::Rebroadcast
+        );
+    }
+}
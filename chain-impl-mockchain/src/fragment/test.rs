@@ -1,7 +1,10 @@
 use super::*;
 use crate::config::ConfigParam;
 #[cfg(test)]
-use crate::testing::serialization::{serialization_bijection, serialization_bijection_r};
+use crate::testing::serialization::{
+    fragment_raw_canonical_encoding, serialization_bijection, serialization_bijection_r,
+    Perturbation,
+};
 #[cfg(test)]
 use quickcheck::TestResult;
 use quickcheck::{Arbitrary, Gen};
@@ -10,9 +13,9 @@ use quickcheck_macros::quickcheck;
 impl Arbitrary for Fragment {
     fn arbitrary<G: Gen>(g: &mut G) -> Self {
         #[cfg(not(feature = "evm"))]
-        let r = g.next_u32() % 15;
+        let r = g.next_u32() % 26;
         #[cfg(feature = "evm")]
-        let r = g.next_u32() % 16;
+        let r = g.next_u32() % 27;
         match r {
             0 => Fragment::Initial(Arbitrary::arbitrary(g)),
             1 => Fragment::OldUtxoDeclaration(Arbitrary::arbitrary(g)),
@@ -29,8 +32,19 @@ impl Arbitrary for Fragment {
             12 => Fragment::VoteTally(Arbitrary::arbitrary(g)),
             13 => Fragment::EncryptedVoteTally(Arbitrary::arbitrary(g)),
             14 => Fragment::MintToken(Arbitrary::arbitrary(g)),
+            15 => Fragment::TransactionBatch(Arbitrary::arbitrary(g)),
+            16 => Fragment::NameClaim(Arbitrary::arbitrary(g)),
+            17 => Fragment::NameRelease(Arbitrary::arbitrary(g)),
+            18 => Fragment::VotingPowerSnapshot(Arbitrary::arbitrary(g)),
+            19 => Fragment::SnapshotVoteCast(Arbitrary::arbitrary(g)),
+            20 => Fragment::VoteCastBatch(Arbitrary::arbitrary(g)),
+            21 => Fragment::AmountStakeDelegation(Arbitrary::arbitrary(g)),
+            22 => Fragment::LegacyMigration(Arbitrary::arbitrary(g)),
+            23 => Fragment::RewardsRedelegation(Arbitrary::arbitrary(g)),
+            24 => Fragment::ExtensionCertificate(Arbitrary::arbitrary(g)),
+            25 => Fragment::VotePlanCancel(Arbitrary::arbitrary(g)),
             #[cfg(feature = "evm")]
-            15 => Fragment::Evm(Arbitrary::arbitrary(g)),
+            26 => Fragment::Evm(Arbitrary::arbitrary(g)),
             _ => unreachable!(),
         }
     }
@@ -46,6 +60,28 @@ quickcheck! {
         TestResult::from_bool(b == b_got)
     }
 
+    fn fragment_id_is_the_hash_of_the_preimage(b: Fragment) -> TestResult {
+        let preimage = b.id_preimage();
+        TestResult::from_bool(
+            FragmentId::hash_bytes(&preimage) == b.hash()
+                && preimage[..2] == [0, b.get_tag() as u8]
+                && preimage.len() + raw::FRAGMENT_SIZE_BYTES_LEN == b.serialized_size(),
+        )
+    }
+
+    fn fragment_raw_trailing_bytes_rejected(b: Fragment, trailing: Vec<u8>) -> TestResult {
+        fragment_raw_canonical_encoding(&b, &Perturbation::TrailingBytes(trailing))
+    }
+
+    fn fragment_raw_section_reordering(b: Fragment, first: u8, second: u8, len: u8) -> TestResult {
+        let perturbation = Perturbation::SwapSections {
+            first: first as usize,
+            second: second as usize,
+            len: 1 + len as usize % 32,
+        };
+        fragment_raw_canonical_encoding(&b, &perturbation)
+    }
+
     fn initial_ents_serialization_bijection(config_params: ConfigParams) -> TestResult {
         serialization_bijection_r(config_params)
     }
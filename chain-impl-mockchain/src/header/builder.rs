@@ -56,7 +56,7 @@ pub fn header_builder(
     version: BlockVersion,
     contents: &Contents,
 ) -> HeaderBuilder<HeaderSetParenting> {
-    let block_content_info = contents.compute_hash_size();
+    let block_content_info = contents.compute_hash_size_with(version.content_hash_scheme());
     header_builder_raw(version, &block_content_info.0, block_content_info.1)
 }
 
@@ -71,7 +71,7 @@ impl HeaderBuilderNew {
         header_builder(version, contents)
     }
 
-    /// recommended to use new(), this is only for test
+    /// recommended to use new(), the hash and size are not checked against any content
     pub fn new_raw(
         version: BlockVersion,
         content_hash: &BlockContentHash,
@@ -88,7 +88,7 @@ impl HeaderBuilder<HeaderSetParenting> {
     pub fn set_genesis(self) -> HeaderBuilder<HeaderSetDate> {
         let mut hdr = self.0;
         hdr.set_height(0);
-        hdr.set_parent_hash(&HeaderId::zero_hash().into());
+        hdr.set_parent_hash(&<HeaderId>::zero_hash().into());
         HeaderBuilder(hdr, PhantomData)
     }
 
@@ -127,7 +127,7 @@ impl HeaderBuilder<HeaderCommonDone> {
 
     /// Tentatively transition to a BFT Header builder
     pub fn into_bft_builder(self) -> Option<HeaderBftBuilder<HeaderSetConsensusData>> {
-        match self.0.version() {
+        match cstruct::layout(self.0.version()) {
             cstruct::VERSION_BFT => Some(HeaderBftBuilder(self.0, PhantomData)),
             _ => None,
         }
@@ -137,7 +137,7 @@ impl HeaderBuilder<HeaderCommonDone> {
     pub fn into_genesis_praos_builder(
         self,
     ) -> Option<HeaderGenesisPraosBuilder<HeaderSetConsensusData>> {
-        match self.0.version() {
+        match cstruct::layout(self.0.version()) {
             cstruct::VERSION_GP => Some(HeaderGenesisPraosBuilder(self.0, PhantomData)),
             _ => None,
         }
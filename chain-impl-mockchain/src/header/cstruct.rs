@@ -41,6 +41,20 @@ pub const HEADER_BFT_SIZE: usize = HEADER_OFFSET_BFT_SIGNATURE + size_of::<BftSi
 
 pub const HEADER_BFT_AUTHED_SIZE: usize = HEADER_OFFSET_BFT_SIGNATURE;
 
+// BFT endorsements, optionally following the BFT signature: the number of
+// endorsements and the endorsements themselves (leader id and signature)
+pub(super) type BftEndorsementsCount = u8;
+
+const HEADER_OFFSET_BFT_ENDORSEMENTS_COUNT: usize = HEADER_BFT_SIZE;
+const HEADER_OFFSET_BFT_ENDORSEMENTS: usize =
+    HEADER_OFFSET_BFT_ENDORSEMENTS_COUNT + size_of::<BftEndorsementsCount>();
+
+pub const BFT_ENDORSEMENT_SIZE: usize = size_of::<BftLeaderId>() + size_of::<BftSignature>();
+pub const BFT_MAX_ENDORSEMENTS: usize = 16;
+
+pub const HEADER_BFT_ENDORSED_MAX_SIZE: usize =
+    HEADER_OFFSET_BFT_ENDORSEMENTS + BFT_ENDORSEMENT_SIZE * BFT_MAX_ENDORSEMENTS;
+
 // GenesisPraos
 const HEADER_OFFSET_GP_ID: usize = HEADER_COMMON_SIZE;
 const HEADER_OFFSET_GP_VRF_PROOF: usize = HEADER_OFFSET_GP_ID + size_of::<GpNodeId>();
@@ -51,7 +65,7 @@ pub const HEADER_GP_SIZE: usize = HEADER_OFFSET_GP_KES_SIG + size_of::<GpKesSign
 pub const HEADER_GP_AUTHED_SIZE: usize = HEADER_OFFSET_GP_KES_SIG;
 
 pub const HEADER_MIN_KNOWN_SIZE: usize = HEADER_COMMON_SIZE;
-pub const HEADER_MAX_KNOWN_SIZE: usize = HEADER_GP_SIZE;
+pub const HEADER_MAX_KNOWN_SIZE: usize = HEADER_BFT_ENDORSED_MAX_SIZE;
 
 // ************************************************************************
 // Header union construction & accessors
@@ -61,23 +75,36 @@ pub(super) type HeaderUnsigned = [u8; HEADER_COMMON_SIZE];
 pub(super) type HeaderBft = [u8; HEADER_BFT_SIZE];
 pub(super) type HeaderGp = [u8; HEADER_GP_SIZE];
 
-pub(super) union Header {
+pub(super) union HeaderFixed {
     unsigned: HeaderUnsigned,
     bft: HeaderBft,
     gp: HeaderGp,
 }
 
+// The BFT endorsements do not fit in the fixed size union, a BFT header
+// carrying some of them is kept whole, with its endorsements, out of it.
+// The endorsements are added once the header is signed, none of the fields
+// of the union is set afterwards.
+pub(super) struct Header {
+    fixed: HeaderFixed,
+    endorsed: Option<Box<[u8]>>,
+}
+
 impl Clone for Header {
     fn clone(&self) -> Self {
         let mut gp = [0u8; HEADER_GP_SIZE];
-        gp[..].copy_from_slice(unsafe { &self.gp[..] });
-        Header { gp }
+        gp[..].copy_from_slice(unsafe { &self.fixed.gp[..] });
+        Header {
+            fixed: HeaderFixed { gp },
+            endorsed: self.endorsed.clone(),
+        }
     }
 }
 
 impl PartialEq for Header {
     fn eq(&self, other: &Self) -> bool {
-        unsafe { self.gp[..] == other.gp[..] }
+        let fixed_eq = unsafe { self.fixed.gp[..] == other.fixed.gp[..] };
+        fixed_eq && self.endorsed == other.endorsed
     }
 }
 impl Eq for Header {}
@@ -85,46 +112,72 @@ impl Eq for Header {}
 pub(super) const VERSION_UNSIGNED: Version = 0;
 pub(super) const VERSION_BFT: Version = 1;
 pub(super) const VERSION_GP: Version = 2;
+pub(super) const VERSION_BFT_MERKLE: Version = 3;
+pub(super) const VERSION_GP_MERKLE: Version = 4;
+
+/// The payload layout of a header version, the versions hashing the block
+/// content as a Merkle tree share the layout of their signed version
+pub(super) const fn layout(version: Version) -> Version {
+    match version {
+        VERSION_BFT_MERKLE => VERSION_BFT,
+        VERSION_GP_MERKLE => VERSION_GP,
+        version => version,
+    }
+}
 
 pub struct HeaderSlice<'a>(&'a [u8]);
 
 impl Header {
     pub fn version(&self) -> Version {
         let mut buf = [0u8; size_of::<Version>()];
-        let s = unsafe { &self.unsigned[HEADER_OFFSET_VERSION..HEADER_OFFSET_CONTENT_SIZE] };
+        let s = unsafe { &self.fixed.unsigned[HEADER_OFFSET_VERSION..HEADER_OFFSET_CONTENT_SIZE] };
         buf.copy_from_slice(s);
         Version::from_be_bytes(buf)
     }
 
     pub fn as_slice(&self) -> HeaderSlice<'_> {
-        match self.version() {
-            VERSION_UNSIGNED => unsafe { HeaderSlice(&self.unsigned[..]) },
-            VERSION_BFT => unsafe { HeaderSlice(&self.bft[..]) },
-            VERSION_GP => unsafe { HeaderSlice(&self.gp[..]) },
+        match layout(self.version()) {
+            VERSION_UNSIGNED => unsafe { HeaderSlice(&self.fixed.unsigned[..]) },
+            VERSION_BFT => match &self.endorsed {
+                Some(endorsed) => HeaderSlice(&endorsed[..]),
+                None => unsafe { HeaderSlice(&self.fixed.bft[..]) },
+            },
+            VERSION_GP => unsafe { HeaderSlice(&self.fixed.gp[..]) },
             _ => panic!("Header: cstruct: as slice with undefined version"),
         }
     }
 
     pub(self) fn as_slice_mut(&mut self) -> &mut [u8] {
-        match self.version() {
-            VERSION_UNSIGNED => unsafe { &mut self.unsigned[..] },
-            VERSION_BFT => unsafe { &mut self.bft[..] },
-            VERSION_GP => unsafe { &mut self.gp[..] },
+        match layout(self.version()) {
+            VERSION_UNSIGNED => unsafe { &mut self.fixed.unsigned[..] },
+            VERSION_BFT => unsafe { &mut self.fixed.bft[..] },
+            VERSION_GP => unsafe { &mut self.fixed.gp[..] },
             _ => panic!("Header: cstruct: as slice mut with undefined version"),
         }
     }
 
     pub fn new(version: Version) -> Header {
         let gp = [0u8; HEADER_GP_SIZE];
-        let mut hdr = Header { gp };
+        let mut hdr = Header {
+            fixed: HeaderFixed { gp },
+            endorsed: None,
+        };
         hdr.set_version(version);
         hdr
     }
 
+    // the endorsements are only present if there is at least one of them
+    pub fn bft_endorsements_count(&self) -> BftEndorsementsCount {
+        match &self.endorsed {
+            None => 0,
+            Some(endorsed) => endorsed[HEADER_OFFSET_BFT_ENDORSEMENTS_COUNT],
+        }
+    }
+
     pub fn set_version(&mut self, s: Version) {
         let sbuf = s.to_be_bytes();
         unsafe {
-            self.unsigned[HEADER_OFFSET_VERSION..HEADER_OFFSET_CONTENT_SIZE]
+            self.fixed.unsigned[HEADER_OFFSET_VERSION..HEADER_OFFSET_CONTENT_SIZE]
                 .copy_from_slice(&sbuf[..])
         }
     }
@@ -132,7 +185,7 @@ impl Header {
     pub fn set_content_size(&mut self, s: ContentSize) {
         let sbuf = s.to_be_bytes();
         unsafe {
-            self.unsigned[HEADER_OFFSET_CONTENT_SIZE..HEADER_OFFSET_DATE_EPOCH]
+            self.fixed.unsigned[HEADER_OFFSET_CONTENT_SIZE..HEADER_OFFSET_DATE_EPOCH]
                 .copy_from_slice(&sbuf[..])
         }
     }
@@ -140,7 +193,7 @@ impl Header {
     pub fn set_date_epoch(&mut self, s: DateEpoch) {
         let sbuf = s.to_be_bytes();
         unsafe {
-            self.unsigned[HEADER_OFFSET_DATE_EPOCH..HEADER_OFFSET_DATE_SLOTID]
+            self.fixed.unsigned[HEADER_OFFSET_DATE_EPOCH..HEADER_OFFSET_DATE_SLOTID]
                 .copy_from_slice(&sbuf[..])
         }
     }
@@ -148,7 +201,7 @@ impl Header {
     pub fn set_date_slotid(&mut self, s: DateSlotid) {
         let sbuf = s.to_be_bytes();
         unsafe {
-            self.unsigned[HEADER_OFFSET_DATE_SLOTID..HEADER_OFFSET_HEIGHT]
+            self.fixed.unsigned[HEADER_OFFSET_DATE_SLOTID..HEADER_OFFSET_HEIGHT]
                 .copy_from_slice(&sbuf[..])
         }
     }
@@ -156,89 +209,138 @@ impl Header {
     pub fn set_height(&mut self, s: Height) {
         let sbuf = s.to_be_bytes();
         unsafe {
-            self.unsigned[HEADER_OFFSET_HEIGHT..HEADER_OFFSET_CONTENT_HASH]
+            self.fixed.unsigned[HEADER_OFFSET_HEIGHT..HEADER_OFFSET_CONTENT_HASH]
                 .copy_from_slice(&sbuf[..])
         }
     }
 
     pub fn set_content_hash(&mut self, s: &ContentHash) {
         unsafe {
-            self.unsigned[HEADER_OFFSET_CONTENT_HASH..HEADER_OFFSET_PARENT_HASH]
+            self.fixed.unsigned[HEADER_OFFSET_CONTENT_HASH..HEADER_OFFSET_PARENT_HASH]
                 .copy_from_slice(&s[..])
         }
     }
 
     pub fn set_parent_hash(&mut self, s: &ParentHash) {
         unsafe {
-            self.unsigned[HEADER_OFFSET_PARENT_HASH..HEADER_COMMON_SIZE].copy_from_slice(&s[..])
+            self.fixed.unsigned[HEADER_OFFSET_PARENT_HASH..HEADER_COMMON_SIZE]
+                .copy_from_slice(&s[..])
         }
     }
 
     #[allow(dead_code)]
     pub fn set_bft_leader_id(&mut self, s: &BftLeaderId) {
-        assert_eq!(self.version(), VERSION_BFT);
+        assert_eq!(layout(self.version()), VERSION_BFT);
         unsafe {
-            self.bft[HEADER_OFFSET_BFT_LEADER_ID..HEADER_OFFSET_BFT_SIGNATURE]
+            self.fixed.bft[HEADER_OFFSET_BFT_LEADER_ID..HEADER_OFFSET_BFT_SIGNATURE]
                 .copy_from_slice(&s[..])
         }
     }
 
     pub fn set_bft_leader_id_slice(&mut self, s: &[u8]) {
-        assert_eq!(self.version(), VERSION_BFT);
+        assert_eq!(layout(self.version()), VERSION_BFT);
         assert_eq!(s.len(), size_of::<BftLeaderId>());
         unsafe {
-            self.bft[HEADER_OFFSET_BFT_LEADER_ID..HEADER_OFFSET_BFT_SIGNATURE].copy_from_slice(s)
+            self.fixed.bft[HEADER_OFFSET_BFT_LEADER_ID..HEADER_OFFSET_BFT_SIGNATURE]
+                .copy_from_slice(s)
         }
     }
 
     #[allow(dead_code)]
     pub fn set_bft_signature(&mut self, s: &BftSignature) {
-        assert_eq!(self.version(), VERSION_BFT);
-        unsafe { self.bft[HEADER_OFFSET_BFT_SIGNATURE..HEADER_BFT_SIZE].copy_from_slice(&s[..]) }
+        assert_eq!(layout(self.version()), VERSION_BFT);
+        unsafe {
+            self.fixed.bft[HEADER_OFFSET_BFT_SIGNATURE..HEADER_BFT_SIZE].copy_from_slice(&s[..])
+        }
     }
 
     pub fn set_bft_signature_slice(&mut self, s: &[u8]) {
-        assert_eq!(self.version(), VERSION_BFT);
+        assert_eq!(layout(self.version()), VERSION_BFT);
         assert_eq!(s.len(), size_of::<BftSignature>());
-        unsafe { self.bft[HEADER_OFFSET_BFT_SIGNATURE..HEADER_BFT_SIZE].copy_from_slice(s) }
+        unsafe { self.fixed.bft[HEADER_OFFSET_BFT_SIGNATURE..HEADER_BFT_SIZE].copy_from_slice(s) }
+    }
+
+    /// Append an endorsement to the BFT header, the header needs to have
+    /// room for it
+    pub fn push_bft_endorsement_slice(&mut self, leader_id: &[u8], signature: &[u8]) {
+        let count = self.bft_endorsements_count() as usize;
+        self.insert_bft_endorsement_slice(count, leader_id, signature)
+    }
+
+    /// Insert an endorsement at the given index of the endorsements of the
+    /// BFT header, shifting the following ones, the header needs to have
+    /// room for it
+    pub fn insert_bft_endorsement_slice(
+        &mut self,
+        index: usize,
+        leader_id: &[u8],
+        signature: &[u8],
+    ) {
+        assert_eq!(layout(self.version()), VERSION_BFT);
+        assert_eq!(leader_id.len(), size_of::<BftLeaderId>());
+        assert_eq!(signature.len(), size_of::<BftSignature>());
+        let count = self.bft_endorsements_count();
+        assert!((count as usize) < BFT_MAX_ENDORSEMENTS);
+        assert!(index <= count as usize);
+        let mut endorsed = match self.endorsed.take() {
+            Some(endorsed) => endorsed.into_vec(),
+            None => {
+                let mut endorsed = Vec::with_capacity(HEADER_OFFSET_BFT_ENDORSEMENTS);
+                endorsed.extend_from_slice(unsafe { &self.fixed.bft[..] });
+                endorsed.push(0);
+                endorsed
+            }
+        };
+        let ofs = HEADER_OFFSET_BFT_ENDORSEMENTS + BFT_ENDORSEMENT_SIZE * index;
+        let following = endorsed.split_off(ofs);
+        endorsed.extend_from_slice(leader_id);
+        endorsed.extend_from_slice(signature);
+        endorsed.extend_from_slice(&following);
+        endorsed[HEADER_OFFSET_BFT_ENDORSEMENTS_COUNT] = count + 1;
+        self.endorsed = Some(endorsed.into());
     }
 
     pub fn set_gp_node_id(&mut self, s: &GpNodeId) {
-        assert_eq!(self.version(), VERSION_GP);
-        unsafe { self.gp[HEADER_OFFSET_GP_ID..HEADER_OFFSET_GP_VRF_PROOF].copy_from_slice(&s[..]) }
+        assert_eq!(layout(self.version()), VERSION_GP);
+        unsafe {
+            self.fixed.gp[HEADER_OFFSET_GP_ID..HEADER_OFFSET_GP_VRF_PROOF].copy_from_slice(&s[..])
+        }
     }
 
     #[allow(dead_code)]
     pub fn set_gp_node_id_slice(&mut self, s: &[u8]) {
-        assert_eq!(self.version(), VERSION_GP);
+        assert_eq!(layout(self.version()), VERSION_GP);
         assert_eq!(s.len(), size_of::<GpNodeId>());
-        unsafe { self.gp[HEADER_OFFSET_GP_ID..HEADER_OFFSET_GP_VRF_PROOF].copy_from_slice(s) }
+        unsafe { self.fixed.gp[HEADER_OFFSET_GP_ID..HEADER_OFFSET_GP_VRF_PROOF].copy_from_slice(s) }
     }
 
     pub fn set_gp_vrf_proof(&mut self, s: &GpVrfProof) {
-        assert_eq!(self.version(), VERSION_GP);
+        assert_eq!(layout(self.version()), VERSION_GP);
         unsafe {
-            self.gp[HEADER_OFFSET_GP_VRF_PROOF..HEADER_OFFSET_GP_KES_SIG].copy_from_slice(&s[..])
+            self.fixed.gp[HEADER_OFFSET_GP_VRF_PROOF..HEADER_OFFSET_GP_KES_SIG]
+                .copy_from_slice(&s[..])
         }
     }
 
     #[allow(dead_code)]
     pub fn set_gp_vrf_proof_slice(&mut self, s: &[u8]) {
-        assert_eq!(self.version(), VERSION_GP);
+        assert_eq!(layout(self.version()), VERSION_GP);
         assert_eq!(s.len(), size_of::<GpVrfProof>());
-        unsafe { self.gp[HEADER_OFFSET_GP_VRF_PROOF..HEADER_OFFSET_GP_KES_SIG].copy_from_slice(s) }
+        unsafe {
+            self.fixed.gp[HEADER_OFFSET_GP_VRF_PROOF..HEADER_OFFSET_GP_KES_SIG].copy_from_slice(s)
+        }
     }
 
     #[allow(dead_code)]
     pub fn set_gp_kes_signature(&mut self, s: &GpKesSignature) {
-        assert_eq!(self.version(), VERSION_GP);
-        unsafe { self.gp[HEADER_OFFSET_GP_KES_SIG..HEADER_GP_SIZE].copy_from_slice(&s[..]) }
+        assert_eq!(layout(self.version()), VERSION_GP);
+        unsafe { self.fixed.gp[HEADER_OFFSET_GP_KES_SIG..HEADER_GP_SIZE].copy_from_slice(&s[..]) }
     }
 
     pub fn set_gp_kes_signature_slice(&mut self, s: &[u8]) {
-        assert_eq!(self.version(), VERSION_GP);
+        assert_eq!(layout(self.version()), VERSION_GP);
         assert_eq!(s.len(), size_of::<GpKesSignature>());
-        unsafe { self.gp[HEADER_OFFSET_GP_KES_SIG..HEADER_GP_SIZE].copy_from_slice(s) }
+        unsafe { self.fixed.gp[HEADER_OFFSET_GP_KES_SIG..HEADER_GP_SIZE].copy_from_slice(s) }
     }
 }
 
@@ -246,10 +348,12 @@ impl Header {
 pub enum HeaderError {
     #[error("invalid payload size for block header")]
     InvalidSize,
-    #[error("unknown version of the block header payload")]
-    UnknownVersion,
+    #[error("unknown version {0} of the block header payload")]
+    UnknownVersion(Version),
     #[error("block header payload size {got} does not match the expected {expected} bytes")]
     SizeMismatch { expected: usize, got: usize },
+    #[error("invalid number of BFT endorsements {0}")]
+    InvalidEndorsementsCount(usize),
 }
 
 impl<'a> HeaderSlice<'a> {
@@ -263,7 +367,7 @@ impl<'a> HeaderSlice<'a> {
         }
 
         let hdr = HeaderSlice(slice);
-        match hdr.version() {
+        match layout(hdr.version()) {
             VERSION_UNSIGNED => {
                 if len != HEADER_COMMON_SIZE {
                     return Err(HeaderError::SizeMismatch {
@@ -274,11 +378,18 @@ impl<'a> HeaderSlice<'a> {
                 Ok(hdr)
             }
             VERSION_BFT => {
-                if len != HEADER_BFT_SIZE {
-                    return Err(HeaderError::SizeMismatch {
-                        expected: HEADER_BFT_SIZE,
-                        got: len,
-                    });
+                let expected = if len > HEADER_BFT_SIZE {
+                    // an empty list of endorsements is never serialized
+                    let count = slice[HEADER_OFFSET_BFT_ENDORSEMENTS_COUNT] as usize;
+                    if count == 0 || count > BFT_MAX_ENDORSEMENTS {
+                        return Err(HeaderError::InvalidEndorsementsCount(count));
+                    }
+                    HEADER_OFFSET_BFT_ENDORSEMENTS + BFT_ENDORSEMENT_SIZE * count
+                } else {
+                    HEADER_BFT_SIZE
+                };
+                if len != expected {
+                    return Err(HeaderError::SizeMismatch { expected, got: len });
                 }
                 Ok(hdr)
             }
@@ -291,7 +402,7 @@ impl<'a> HeaderSlice<'a> {
                 }
                 Ok(hdr)
             }
-            _ => Err(HeaderError::UnknownVersion),
+            version => Err(HeaderError::UnknownVersion(version)),
         }
     }
 
@@ -301,7 +412,13 @@ impl<'a> HeaderSlice<'a> {
 
     pub(super) fn to_owned(&self) -> Header {
         let mut new = Header::new(self.version());
-        new.as_slice_mut().copy_from_slice(self.0);
+        if layout(self.version()) == VERSION_BFT && self.0.len() > HEADER_BFT_SIZE {
+            new.as_slice_mut()
+                .copy_from_slice(&self.0[..HEADER_BFT_SIZE]);
+            new.endorsed = Some(self.0.into());
+        } else {
+            new.as_slice_mut().copy_from_slice(self.0);
+        }
         new
     }
 
@@ -356,7 +473,7 @@ impl<'a> HeaderSlice<'a> {
     }
 
     pub fn bft_leader_id_ref(&self) -> &[u8] {
-        assert_eq!(self.version(), VERSION_BFT);
+        assert_eq!(layout(self.version()), VERSION_BFT);
         &self.0[HEADER_OFFSET_BFT_LEADER_ID..HEADER_OFFSET_BFT_SIGNATURE]
     }
 
@@ -367,7 +484,7 @@ impl<'a> HeaderSlice<'a> {
     }
 
     pub fn bft_signature_ref(&self) -> &[u8] {
-        assert_eq!(self.version(), VERSION_BFT);
+        assert_eq!(layout(self.version()), VERSION_BFT);
         &self.0[HEADER_OFFSET_BFT_SIGNATURE..HEADER_BFT_SIZE]
     }
 
@@ -377,8 +494,28 @@ impl<'a> HeaderSlice<'a> {
         buf
     }
 
+    pub fn bft_endorsements_count(&self) -> BftEndorsementsCount {
+        assert_eq!(layout(self.version()), VERSION_BFT);
+        match self.0.get(HEADER_OFFSET_BFT_ENDORSEMENTS_COUNT) {
+            None => 0,
+            Some(count) => *count,
+        }
+    }
+
+    /// leader id and signature of the endorsement at the given index
+    pub fn bft_endorsement(&self, index: usize) -> (BftLeaderId, BftSignature) {
+        assert!(index < self.bft_endorsements_count() as usize);
+        let ofs = HEADER_OFFSET_BFT_ENDORSEMENTS + BFT_ENDORSEMENT_SIZE * index;
+        let sig_ofs = ofs + size_of::<BftLeaderId>();
+        let mut leader_id = [0u8; size_of::<BftLeaderId>()];
+        leader_id.copy_from_slice(&self.0[ofs..sig_ofs]);
+        let mut signature = [0u8; size_of::<BftSignature>()];
+        signature.copy_from_slice(&self.0[sig_ofs..sig_ofs + size_of::<BftSignature>()]);
+        (leader_id, signature)
+    }
+
     pub fn gp_node_id_ref(&self) -> &[u8] {
-        assert_eq!(self.version(), VERSION_GP);
+        assert_eq!(layout(self.version()), VERSION_GP);
         &self.0[HEADER_OFFSET_GP_ID..HEADER_OFFSET_GP_VRF_PROOF]
     }
 
@@ -389,7 +526,7 @@ impl<'a> HeaderSlice<'a> {
     }
 
     pub fn gp_vrf_proof_ref(&self) -> &[u8] {
-        assert_eq!(self.version(), VERSION_GP);
+        assert_eq!(layout(self.version()), VERSION_GP);
         &self.0[HEADER_OFFSET_GP_VRF_PROOF..HEADER_OFFSET_GP_KES_SIG]
     }
 
@@ -400,7 +537,7 @@ impl<'a> HeaderSlice<'a> {
     }
 
     pub fn gp_kes_signature_ref(&self) -> &[u8] {
-        assert_eq!(self.version(), VERSION_GP);
+        assert_eq!(layout(self.version()), VERSION_GP);
         &self.0[HEADER_OFFSET_GP_KES_SIG..HEADER_GP_SIZE]
     }
 
@@ -411,12 +548,19 @@ impl<'a> HeaderSlice<'a> {
     }
 
     pub fn slice_bft_auth(self) -> &'a [u8] {
-        assert_eq!(self.version(), VERSION_BFT);
+        assert_eq!(layout(self.version()), VERSION_BFT);
         &self.0[0..HEADER_BFT_AUTHED_SIZE]
     }
 
+    /// the BFT header up to the signature of the slot leader, without the
+    /// endorsements
+    pub fn slice_bft_signed(self) -> &'a [u8] {
+        assert_eq!(layout(self.version()), VERSION_BFT);
+        &self.0[0..HEADER_BFT_SIZE]
+    }
+
     pub fn slice_gp_auth(self) -> &'a [u8] {
-        assert_eq!(self.version(), VERSION_GP);
+        assert_eq!(layout(self.version()), VERSION_GP);
         &self.0[0..HEADER_GP_AUTHED_SIZE]
     }
 }
@@ -446,6 +590,85 @@ mod tests {
         header.set_gp_kes_signature(&gp_kes_signature);
     }
 
+    #[test]
+    pub fn header_bft_endorsements() {
+        let mut header = Header::new(VERSION_BFT);
+        assert_eq!(header.as_slice().as_slice().len(), HEADER_BFT_SIZE);
+        assert_eq!(header.as_slice().bft_endorsements_count(), 0);
+
+        for i in 0..BFT_MAX_ENDORSEMENTS {
+            header.push_bft_endorsement_slice(&[i as u8; 32], &[i as u8; 64]);
+        }
+        let slice = header.as_slice();
+        assert_eq!(slice.as_slice().len(), HEADER_BFT_ENDORSED_MAX_SIZE);
+        assert_eq!(
+            slice.bft_endorsement(3),
+            ([3; 32], [3; 64]),
+            "endorsement not found at its index"
+        );
+
+        let owned = HeaderSlice::from_slice(slice.as_slice())
+            .unwrap()
+            .to_owned();
+        assert!(owned == header);
+    }
+
+    #[test]
+    pub fn header_bft_endorsements_are_not_in_the_fixed_buffer() {
+        assert_eq!(size_of::<HeaderFixed>(), HEADER_GP_SIZE);
+
+        let mut header = Header::new(VERSION_BFT);
+        header.set_bft_signature(&[1; 64]);
+        header.push_bft_endorsement_slice(&[2; 32], &[2; 64]);
+        let slice = header.as_slice();
+        assert_eq!(slice.bft_signature(), [1; 64]);
+        assert_eq!(slice.slice_bft_signed(), unsafe { &header.fixed.bft[..] });
+    }
+
+    #[test]
+    pub fn header_bft_endorsement_inserted_at_its_index() {
+        let mut header = Header::new(VERSION_BFT);
+        header.push_bft_endorsement_slice(&[0; 32], &[0; 64]);
+        header.push_bft_endorsement_slice(&[2; 32], &[2; 64]);
+        header.insert_bft_endorsement_slice(1, &[1; 32], &[1; 64]);
+        let slice = header.as_slice();
+        assert_eq!(slice.bft_endorsements_count(), 3);
+        for i in 0..3 {
+            assert_eq!(slice.bft_endorsement(i), ([i as u8; 32], [i as u8; 64]));
+        }
+    }
+
+    #[test]
+    pub fn header_slice_from_slice_invalid_endorsements() {
+        let mut header = Header::new(VERSION_BFT);
+        header.push_bft_endorsement_slice(&[0; 32], &[0; 64]);
+        let bytes = header.as_slice().as_slice().to_vec();
+
+        assert_eq!(
+            HeaderSlice::from_slice(&bytes[..bytes.len() - 1])
+                .err()
+                .unwrap(),
+            HeaderError::SizeMismatch {
+                expected: HEADER_OFFSET_BFT_ENDORSEMENTS + BFT_ENDORSEMENT_SIZE,
+                got: bytes.len() - 1
+            }
+        );
+
+        let mut empty = bytes[..HEADER_BFT_SIZE + 1].to_vec();
+        empty[HEADER_OFFSET_BFT_ENDORSEMENTS_COUNT] = 0;
+        assert_eq!(
+            HeaderSlice::from_slice(&empty).err().unwrap(),
+            HeaderError::InvalidEndorsementsCount(0)
+        );
+
+        let mut too_many = bytes;
+        too_many[HEADER_OFFSET_BFT_ENDORSEMENTS_COUNT] = BFT_MAX_ENDORSEMENTS as u8 + 1;
+        assert_eq!(
+            HeaderSlice::from_slice(&too_many).err().unwrap(),
+            HeaderError::InvalidEndorsementsCount(BFT_MAX_ENDORSEMENTS + 1)
+        );
+    }
+
     #[test]
     pub fn header_slice_from_slice_below_min_known_size() {
         assert_eq!(
@@ -25,10 +25,41 @@ pub enum Proof {
     GenesisPraos(GenesisPraosProof),
 }
 
+/// The identity of the producer of a block, as found in the proof of its
+/// header
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BlockProducer {
+    BftLeader(BftLeaderId),
+    Pool(PoolId),
+}
+
 #[derive(Debug, Clone)]
 pub struct BftProof {
     pub(crate) leader_id: BftLeaderId,
     pub(crate) signature: BftSignature,
+    pub(crate) endorsements: Vec<BftEndorsement>,
+}
+
+/// Endorsement of a BFT header by a leader other than the slot leader. The
+/// endorsement signs the data signed by the slot leader prefixed with
+/// [`BftEndorsement::DOMAIN_TAG`]. A header carries exactly the endorsements
+/// needed to reach the quorum, sorted by leader id. The endorsements are not
+/// part of the header id, any set of them reaching the quorum endorses the
+/// same block.
+#[derive(Debug, Clone)]
+pub struct BftEndorsement {
+    pub(crate) leader_id: BftLeaderId,
+    pub(crate) signature: BftSignature,
+}
+
+impl BftEndorsement {
+    /// tag separating the endorsements from the signatures of the headers
+    pub const DOMAIN_TAG: &'static [u8] = b"bft-endorsement";
+
+    /// data signed by an endorsement of the header of the given auth data
+    pub(crate) fn signed_data(header_auth_data: &[u8]) -> Vec<u8> {
+        [Self::DOMAIN_TAG, header_auth_data].concat()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -1,6 +1,8 @@
-use super::components::VrfProof;
+use super::components::{BftSignature, VrfProof};
 use super::cstruct;
-use super::deconstruct::{BftProof, Common, GenesisPraosProof, Proof};
+use super::deconstruct::{
+    BftEndorsement, BftProof, BlockProducer, Common, GenesisPraosProof, Proof,
+};
 use super::version::BlockVersion;
 
 use crate::certificate::PoolId;
@@ -8,9 +10,10 @@ use crate::chaineval::{ConsensusEvalContext, HeaderContentEvalContext};
 use crate::chaintypes::{ChainLength, HeaderId};
 use crate::date::BlockDate;
 use crate::fragment::{BlockContentHash, BlockContentSize};
-use crate::key::BftLeaderId;
+use crate::key::{BftLeaderId, IdHashAlgorithm};
 use crate::leadership;
 
+use chain_crypto::{Ed25519, SecretKey};
 use std::fmt::{self, Debug};
 use std::num::NonZeroUsize;
 
@@ -37,6 +40,10 @@ pub enum Header {
 
 impl HeaderUnsigned {
     pub fn id(&self) -> HeaderId {
+        self.id_with()
+    }
+
+    pub fn id_with<H: IdHashAlgorithm>(&self) -> HeaderId<H> {
         HeaderId::hash_bytes(self.0.as_slice().as_slice())
     }
 
@@ -46,8 +53,50 @@ impl HeaderUnsigned {
 }
 
 impl HeaderBft {
+    /// The id of the header, the endorsements are not part of it
     pub fn id(&self) -> HeaderId {
-        HeaderId::hash_bytes(self.0.as_slice().as_slice())
+        self.id_with()
+    }
+
+    /// The id of the header computed with the hash algorithm `H`, the
+    /// endorsements are not part of it either
+    pub fn id_with<H: IdHashAlgorithm>(&self) -> HeaderId<H> {
+        HeaderId::hash_bytes(self.0.as_slice().slice_bft_signed())
+    }
+
+    /// Endorse the header with the key of a BFT leader, signing the data
+    /// signed by the slot leader prefixed with the endorsement domain tag
+    pub fn endorse_using(&self, sk: &SecretKey<Ed25519>) -> BftEndorsement {
+        let data = BftEndorsement::signed_data(self.0.as_slice().slice_bft_auth());
+        let sig = sk.sign_slice(&data);
+        BftEndorsement {
+            leader_id: BftLeaderId(sk.to_public()),
+            signature: BftSignature(sig),
+        }
+    }
+
+    /// Add an endorsement to the header, keeping the endorsements sorted by
+    /// leader id. The endorsements are neither part of the header id nor of
+    /// the data signed by the slot leader.
+    pub fn add_endorsement(self, endorsement: &BftEndorsement) -> Result<Self, HeaderError> {
+        let count = self.0.bft_endorsements_count() as usize;
+        if count == cstruct::BFT_MAX_ENDORSEMENTS {
+            return Err(HeaderError::InvalidEndorsementsCount(count + 1));
+        }
+        let slice = self.0.as_slice();
+        let index = (0..count)
+            .take_while(|index| {
+                let (leader_id, _) = slice.bft_endorsement(*index);
+                leader_id[..] <= *endorsement.leader_id.as_ref()
+            })
+            .count();
+        let mut hdr = self.0;
+        hdr.insert_bft_endorsement_slice(
+            index,
+            endorsement.leader_id.0.as_ref(),
+            endorsement.signature.0.as_ref(),
+        );
+        Ok(HeaderBft(hdr))
     }
 
     pub fn generalize(self) -> Header {
@@ -57,6 +106,10 @@ impl HeaderBft {
 
 impl HeaderGenesisPraos {
     pub fn id(&self) -> HeaderId {
+        self.id_with()
+    }
+
+    pub fn id_with<H: IdHashAlgorithm>(&self) -> HeaderId<H> {
         HeaderId::hash_bytes(self.0.as_slice().as_slice())
     }
 
@@ -97,15 +150,20 @@ impl fmt::Display for HeaderDesc {
 
 impl Header {
     pub fn id(&self) -> HeaderId {
+        self.id_with()
+    }
+
+    /// The id of the header computed with the hash algorithm `H`
+    pub fn id_with<H: IdHashAlgorithm>(&self) -> HeaderId<H> {
         match self {
-            Header::Unsigned(h) => h.id(),
-            Header::GenesisPraos(h) => h.id(),
-            Header::Bft(h) => h.id(),
+            Header::Unsigned(h) => h.id_with(),
+            Header::GenesisPraos(h) => h.id_with(),
+            Header::Bft(h) => h.id_with(),
         }
     }
 
     pub fn size(&self) -> NonZeroUsize {
-        self.block_version().get_size()
+        NonZeroUsize::new(self.as_slice().len()).expect("header cannot be empty")
     }
 
     // deprecated: use .id()
@@ -122,7 +180,7 @@ impl Header {
         }
     }
 
-    fn get_cstruct(&self) -> cstruct::HeaderSlice<'_> {
+    pub(super) fn get_cstruct(&self) -> cstruct::HeaderSlice<'_> {
         match self {
             Header::Unsigned(h) => h.0.as_slice(),
             Header::GenesisPraos(h) => h.0.as_slice(),
@@ -144,11 +202,8 @@ impl Header {
 
     #[inline]
     pub fn block_version(&self) -> BlockVersion {
-        match self {
-            Header::Unsigned(_) => BlockVersion::Genesis,
-            Header::GenesisPraos(_) => BlockVersion::KesVrfproof,
-            Header::Bft(_) => BlockVersion::Ed25519Signed,
-        }
+        BlockVersion::from_u16(self.get_cstruct().version())
+            .expect("header slice only know version")
     }
 
     #[inline]
@@ -184,8 +239,12 @@ impl Header {
         let hdr = hdr_slice.to_owned();
         match BlockVersion::from_u16(hdr.version()).expect("header slice only know version") {
             BlockVersion::Genesis => Ok(Header::Unsigned(HeaderUnsigned(hdr))),
-            BlockVersion::Ed25519Signed => Ok(Header::Bft(HeaderBft(hdr))),
-            BlockVersion::KesVrfproof => Ok(Header::GenesisPraos(HeaderGenesisPraos(hdr))),
+            BlockVersion::Ed25519Signed | BlockVersion::Ed25519SignedMerkle => {
+                Ok(Header::Bft(HeaderBft(hdr)))
+            }
+            BlockVersion::KesVrfproof | BlockVersion::KesVrfproofMerkle => {
+                Ok(Header::GenesisPraos(HeaderGenesisPraos(hdr)))
+            }
         }
     }
 
@@ -207,13 +266,26 @@ impl Header {
     }
 
     pub fn proof(&self) -> Proof {
-        match self.block_version() {
-            BlockVersion::Genesis => Proof::None,
-            BlockVersion::Ed25519Signed => Proof::Bft(BftProof {
-                leader_id: self.get_cstruct().bft_leader_id().into(),
-                signature: self.get_cstruct().bft_signature().into(),
-            }),
-            BlockVersion::KesVrfproof => Proof::GenesisPraos(GenesisPraosProof {
+        match self {
+            Header::Unsigned(_) => Proof::None,
+            Header::Bft(_) => {
+                let cs = self.get_cstruct();
+                let endorsements = (0..cs.bft_endorsements_count() as usize)
+                    .map(|index| {
+                        let (leader_id, signature) = cs.bft_endorsement(index);
+                        BftEndorsement {
+                            leader_id: leader_id.into(),
+                            signature: signature.into(),
+                        }
+                    })
+                    .collect();
+                Proof::Bft(BftProof {
+                    leader_id: cs.bft_leader_id().into(),
+                    signature: cs.bft_signature().into(),
+                    endorsements,
+                })
+            }
+            Header::GenesisPraos(_) => Proof::GenesisPraos(GenesisPraosProof {
                 node_id: self.get_cstruct().gp_node_id().into(),
                 vrf_proof: VrfProof(self.get_cstruct().gp_vrf_proof()),
                 kes_proof: self.get_cstruct().gp_kes_signature().into(),
@@ -223,23 +295,38 @@ impl Header {
 
     #[inline]
     pub fn get_stakepool_id(&self) -> Option<PoolId> {
-        match self.block_version() {
-            BlockVersion::KesVrfproof => Some(self.get_cstruct().gp_node_id().into()),
+        match self {
+            Header::GenesisPraos(_) => Some(self.get_cstruct().gp_node_id().into()),
             _ => None,
         }
     }
 
     #[inline]
     pub fn get_bft_leader_id(&self) -> Option<BftLeaderId> {
-        match self.block_version() {
-            BlockVersion::Ed25519Signed => Some(self.get_cstruct().bft_leader_id().into()),
+        match self {
+            Header::Bft(_) => Some(self.get_cstruct().bft_leader_id().into()),
             _ => None,
         }
     }
 
+    /// The producer of the block, the BFT leader or the stake pool which
+    /// signed the header. `None` for the unsigned headers, like the header of
+    /// the block 0.
+    pub fn producer(&self) -> Option<BlockProducer> {
+        match self {
+            Header::Unsigned(_) => None,
+            Header::Bft(_) => Some(BlockProducer::BftLeader(
+                self.get_cstruct().bft_leader_id().into(),
+            )),
+            Header::GenesisPraos(_) => {
+                Some(BlockProducer::Pool(self.get_cstruct().gp_node_id().into()))
+            }
+        }
+    }
+
     pub fn get_consensus_eval_context(&self) -> ConsensusEvalContext {
-        match self.block_version() {
-            BlockVersion::KesVrfproof => {
+        match self {
+            Header::GenesisPraos(_) => {
                 let nonce = VrfProof(self.get_cstruct().gp_vrf_proof())
                     .to_vrf_proof()
                     .map(|p| leadership::genesis::witness_to_nonce(&p))
@@ -250,8 +337,8 @@ impl Header {
                     pool_creator: node_id.into(),
                 }
             }
-            BlockVersion::Ed25519Signed => ConsensusEvalContext::Bft,
-            BlockVersion::Genesis => ConsensusEvalContext::Genesis,
+            Header::Bft(_) => ConsensusEvalContext::Bft,
+            Header::Unsigned(_) => ConsensusEvalContext::Genesis,
         }
     }
 
@@ -260,6 +347,7 @@ impl Header {
             block_date: self.block_date(),
             chain_length: self.chain_length(),
             content_hash: self.block_content_hash(),
+            content_hash_scheme: self.block_version().content_hash_scheme(),
             consensus_eval_context: self.get_consensus_eval_context(),
         }
     }
@@ -280,7 +368,8 @@ impl Debug for Header {
             Header::Unsigned(_) => r,
             Header::Bft(_) => r
                 .field("bft-leader-id", &hs.bft_leader_id())
-                .field("bft-sig", &hs.bft_signature_ref()),
+                .field("bft-sig", &hs.bft_signature_ref())
+                .field("bft-endorsements", &hs.bft_endorsements_count()),
             Header::GenesisPraos(_) => r
                 .field("pool-id", &hs.gp_node_id())
                 .field("vrf-proof", &hs.gp_vrf_proof_ref())
@@ -306,9 +395,18 @@ impl property::Serialize for Header {
 impl Readable for Header {
     fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
         Header::from_slice(buf.get_slice_end()).map_err(|e| match e {
-            HeaderError::InvalidSize => ReadError::NotEnoughBytes(0, 0),
-            HeaderError::UnknownVersion => ReadError::UnknownTag(0),
-            HeaderError::SizeMismatch { expected, got } => ReadError::SizeTooBig(expected, got),
+            HeaderError::InvalidSize => ReadError::StructureInvalid("invalid header size".into()),
+            HeaderError::UnknownVersion(version) => ReadError::UnknownTag {
+                kind: "header version",
+                tag: version as u32,
+            },
+            HeaderError::SizeMismatch { expected, got } => ReadError::SizeMismatch {
+                expected,
+                actual: got,
+            },
+            HeaderError::InvalidEndorsementsCount(_) => {
+                ReadError::StructureInvalid("invalid number of BFT endorsements".into())
+            }
         })
     }
 }
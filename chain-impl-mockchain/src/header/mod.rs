@@ -4,6 +4,7 @@ mod cstruct;
 mod deconstruct;
 #[allow(clippy::module_inception)]
 mod header;
+pub mod sanity;
 mod version;
 
 #[cfg(any(test, feature = "property-test-api"))]
@@ -17,6 +18,6 @@ pub use builder::{
     HeaderSetConsensusData, HeaderSetConsensusSignature,
 };
 pub use components::{BftSignature, KesSignature, VrfProof};
-pub use deconstruct::{BftProof, Common, GenesisPraosProof, Proof};
+pub use deconstruct::{BftEndorsement, BftProof, BlockProducer, Common, GenesisPraosProof, Proof};
 pub use header::{Header, HeaderBft, HeaderDesc, HeaderGenesisPraos, HeaderUnsigned};
 pub use version::{AnyBlockVersion, BlockVersion};
@@ -0,0 +1,347 @@
+//! Stateless checks of the headers.
+//!
+//! The checks below only need the header, and optionally the limits of the
+//! chain the header claims to belong to, so a network layer can drop the
+//! headers which can never be accepted before looking up their parent or
+//! touching the state of the ledger. A header passing them still needs to be
+//! validated against the leadership and the ledger.
+//!
+//! The date of a header can also be checked against the wall clock with
+//! [`check_clock`], given the time frame and the era of the chain, to reject
+//! the headers claiming a date far in the future.
+//!
+//! The size of the header and the consistency of its version with the shape
+//! of its proof are checked when parsing it, see [`Header::from_slice`].
+
+use super::components::{HeaderAuth, VrfProof};
+use super::cstruct::HeaderError;
+use super::header::Header;
+use super::version::BlockVersion;
+use crate::chaintypes::{ChainLength, HeaderId};
+use crate::date::BlockDate;
+use crate::fragment::{BlockContentHash, BlockContentSize};
+use crate::key::BftLeaderId;
+use crate::setting::Settings;
+use chain_crypto::{Signature, SumEd25519_12};
+use chain_time::{SlotVerdict, TimeEra, TimeFrame};
+use std::time::{Duration, SystemTime};
+use thiserror::Error;
+
+/// Limits of the chain the headers are checked against, the limits left
+/// unset are not checked
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SanityLimits {
+    pub block_content_max_size: Option<BlockContentSize>,
+    pub slots_per_epoch: Option<u32>,
+}
+
+impl From<&Settings> for SanityLimits {
+    fn from(settings: &Settings) -> Self {
+        SanityLimits {
+            block_content_max_size: Some(settings.block_content_max_size),
+            slots_per_epoch: Some(settings.slots_per_epoch),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Error, PartialEq)]
+pub enum SanityError {
+    #[error("malformed header")]
+    Malformed(#[from] HeaderError),
+    #[error("the block0 header has a parent {parent}")]
+    Block0WithParent { parent: HeaderId },
+    #[error("the block0 header is signed with a {version:?} proof")]
+    Block0Signed { version: BlockVersion },
+    #[error("the header at chain length {chain_length} has no parent")]
+    MissingParent { chain_length: ChainLength },
+    #[error("the header at chain length {chain_length} is not signed")]
+    Unsigned { chain_length: ChainLength },
+    #[error("empty content with a content hash {hash}")]
+    EmptyContentHash { hash: BlockContentHash },
+    #[error("content of {size} bytes with the hash of an empty content")]
+    NonEmptyContentHash { size: BlockContentSize },
+    #[error("content of {size} bytes over the maximum of {max} bytes")]
+    ContentTooBig {
+        size: BlockContentSize,
+        max: BlockContentSize,
+    },
+    #[error("slot {slot_id} out of the {slots_per_epoch} slots of an epoch")]
+    SlotOutOfEpoch { slot_id: u32, slots_per_epoch: u32 },
+    #[error("the date {date} is {ahead:?} ahead of the wall clock")]
+    FutureDate { date: BlockDate, ahead: Duration },
+    #[error("the date {date} is out of the time frame")]
+    DateOutOfFrame { date: BlockDate },
+    #[error("the BFT leader {0:?} signs the header more than once")]
+    DuplicateBftSigner(BftLeaderId),
+    #[error("invalid VRF proof")]
+    InvalidVrfProof,
+    #[error("invalid KES signature")]
+    InvalidKesSignature,
+}
+
+/// Parse the header and check it
+pub fn check_slice(slice: &[u8], limits: &SanityLimits) -> Result<Header, SanityError> {
+    let header = Header::from_slice(slice)?;
+    check(&header, limits)?;
+    Ok(header)
+}
+
+/// Check the fields of the header, the consistency of the parent with the
+/// chain length and the version, and the shape of the proof
+pub fn check(header: &Header, limits: &SanityLimits) -> Result<(), SanityError> {
+    check_parent(header)?;
+    check_content(header, limits)?;
+    check_date(header, limits)?;
+    check_proof(header)
+}
+
+/// Check the date of the header against the wall clock `now`: the headers of
+/// the slots starting after `now` by more than the tolerance are rejected, the
+/// tolerance accounting for the skew of the clocks and the leap seconds, see
+/// [`chain_time::clock`]
+pub fn check_clock(
+    header: &Header,
+    era: &TimeEra,
+    time_frame: &TimeFrame,
+    now: SystemTime,
+    tolerance: Duration,
+) -> Result<(), SanityError> {
+    let date = header.block_date();
+    match time_frame.check_position(era, date.into(), &now, tolerance) {
+        SlotVerdict::Past | SlotVerdict::Current => Ok(()),
+        SlotVerdict::Future { ahead } => Err(SanityError::FutureDate { date, ahead }),
+        SlotVerdict::OutOfFrame => Err(SanityError::DateOutOfFrame { date }),
+    }
+}
+
+/// only the block0 is unsigned and has no parent, it is at chain length 0
+fn check_parent(header: &Header) -> Result<(), SanityError> {
+    let chain_length = header.chain_length();
+    let parent = header.block_parent_hash();
+    let version = header.block_version();
+    if chain_length == ChainLength(0) {
+        if parent != HeaderId::zero_hash() {
+            return Err(SanityError::Block0WithParent { parent });
+        }
+        if version != BlockVersion::Genesis {
+            return Err(SanityError::Block0Signed { version });
+        }
+    } else {
+        if parent == HeaderId::zero_hash() {
+            return Err(SanityError::MissingParent { chain_length });
+        }
+        if version == BlockVersion::Genesis {
+            return Err(SanityError::Unsigned { chain_length });
+        }
+    }
+    Ok(())
+}
+
+fn check_content(header: &Header, limits: &SanityLimits) -> Result<(), SanityError> {
+    let size = header.block_content_size();
+    let hash = header.block_content_hash();
+    // an empty content hashes the empty slice, whatever the content hash
+    // scheme of the version
+    let empty_hash = BlockContentHash::hash_bytes(&[]);
+    if size == 0 && hash != empty_hash {
+        return Err(SanityError::EmptyContentHash { hash });
+    }
+    if size != 0 && hash == empty_hash {
+        return Err(SanityError::NonEmptyContentHash { size });
+    }
+    match limits.block_content_max_size {
+        Some(max) if size > max => Err(SanityError::ContentTooBig { size, max }),
+        _ => Ok(()),
+    }
+}
+
+fn check_date(header: &Header, limits: &SanityLimits) -> Result<(), SanityError> {
+    let slot_id = header.block_date().slot_id;
+    match limits.slots_per_epoch {
+        Some(slots_per_epoch) if slot_id >= slots_per_epoch => Err(SanityError::SlotOutOfEpoch {
+            slot_id,
+            slots_per_epoch,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// The keys and the signatures of a BFT proof are checked for their size
+/// only, which the parsing ensures, but a leader cannot sign twice. The VRF
+/// proof and the KES signature of a Genesis Praos proof need to decode,
+/// [`Header::proof`] panics otherwise.
+fn check_proof(header: &Header) -> Result<(), SanityError> {
+    let cs = header.get_cstruct();
+    match header.block_version() {
+        BlockVersion::Genesis => Ok(()),
+        BlockVersion::Ed25519Signed | BlockVersion::Ed25519SignedMerkle => {
+            let mut signers = vec![cs.bft_leader_id()];
+            for index in 0..cs.bft_endorsements_count() as usize {
+                let (leader_id, _) = cs.bft_endorsement(index);
+                if signers.contains(&leader_id) {
+                    return Err(SanityError::DuplicateBftSigner(leader_id.into()));
+                }
+                signers.push(leader_id);
+            }
+            Ok(())
+        }
+        BlockVersion::KesVrfproof | BlockVersion::KesVrfproofMerkle => {
+            VrfProof(cs.gp_vrf_proof())
+                .to_vrf_proof()
+                .ok_or(SanityError::InvalidVrfProof)?;
+            Signature::<HeaderAuth, SumEd25519_12>::from_binary(cs.gp_kes_signature_ref())
+                .map_err(|_| SanityError::InvalidKesSignature)?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fragment::Contents;
+    use crate::header::{cstruct, HeaderBuilderNew};
+    use chain_crypto::{Ed25519, SecretKey};
+    use chain_time::{Epoch, Slot, SlotDuration, Timeline, LEAP_SECOND_TOLERANCE};
+    use rand_core::OsRng;
+
+    fn bft_header(chain_length: u32, slot_id: u32, endorsers: &[&SecretKey<Ed25519>]) -> Header {
+        let leader = SecretKey::<Ed25519>::generate(OsRng);
+        let mut header = HeaderBuilderNew::new(BlockVersion::Ed25519Signed, &Contents::empty())
+            .set_parent(&HeaderId::hash_bytes(&[1]), ChainLength(chain_length))
+            .set_date(BlockDate { epoch: 0, slot_id })
+            .into_bft_builder()
+            .unwrap()
+            .sign_using(&leader);
+        for endorser in endorsers {
+            let endorsement = header.endorse_using(endorser);
+            header = header.add_endorsement(&endorsement).unwrap();
+        }
+        header.generalize()
+    }
+
+    #[test]
+    fn block0_and_bft_headers_are_sane() {
+        let block0 = HeaderBuilderNew::new(BlockVersion::Genesis, &Contents::empty())
+            .set_genesis()
+            .set_date(BlockDate::first())
+            .into_unsigned_header()
+            .unwrap()
+            .generalize();
+        assert_eq!(check(&block0, &SanityLimits::default()), Ok(()));
+
+        let endorser = SecretKey::<Ed25519>::generate(OsRng);
+        let header = bft_header(1, 3, &[&endorser]);
+        let limits = SanityLimits::from(&Settings::new());
+        assert_eq!(check_slice(header.as_slice(), &limits), Ok(header));
+    }
+
+    #[test]
+    fn insane_headers_are_rejected() {
+        let limits = SanityLimits {
+            block_content_max_size: Some(1024),
+            slots_per_epoch: Some(10),
+        };
+        assert_eq!(
+            check(&bft_header(0, 0, &[]), &limits),
+            Err(SanityError::Block0WithParent {
+                parent: HeaderId::hash_bytes(&[1])
+            })
+        );
+        assert_eq!(
+            check(&bft_header(1, 10, &[]), &limits),
+            Err(SanityError::SlotOutOfEpoch {
+                slot_id: 10,
+                slots_per_epoch: 10
+            })
+        );
+
+        let endorser = SecretKey::<Ed25519>::generate(OsRng);
+        assert_eq!(
+            check(&bft_header(1, 0, &[&endorser, &endorser]), &limits),
+            Err(SanityError::DuplicateBftSigner(endorser.to_public().into()))
+        );
+
+        let content_too_big = HeaderBuilderNew::new_raw(
+            BlockVersion::Ed25519Signed,
+            &HeaderId::hash_bytes(&[2]),
+            2048,
+        )
+        .set_parent(&HeaderId::hash_bytes(&[1]), ChainLength(1))
+        .set_date(BlockDate::first())
+        .into_bft_builder()
+        .unwrap()
+        .sign_using(&endorser)
+        .generalize();
+        assert_eq!(
+            check(&content_too_big, &limits),
+            Err(SanityError::ContentTooBig {
+                size: 2048,
+                max: 1024
+            })
+        );
+
+        assert!(matches!(
+            check_slice(&[0; 8], &limits),
+            Err(SanityError::Malformed(HeaderError::InvalidSize))
+        ));
+
+        // a Genesis Praos header with bytes which are not a VRF proof
+        let mut bytes = vec![0xff; cstruct::HEADER_GP_SIZE];
+        bytes[0..2].copy_from_slice(&cstruct::VERSION_GP.to_be_bytes());
+        assert_eq!(
+            check_slice(&bytes, &SanityLimits::default()),
+            Err(SanityError::InvalidVrfProof)
+        );
+    }
+
+    #[test]
+    fn headers_from_the_future_are_rejected() {
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+        let time_frame = TimeFrame::new(Timeline::new(start), SlotDuration::from_secs(10));
+        let era = TimeEra::new(Slot::from(0), Epoch(0), 10);
+        // during the slot 3 of the epoch 1
+        let now = start + Duration::from_secs(135);
+        let check = |slot_id| {
+            check_clock(
+                &bft_header(1, slot_id, &[]),
+                &era,
+                &time_frame,
+                now,
+                LEAP_SECOND_TOLERANCE,
+            )
+        };
+
+        // the header dates are in the epoch 0
+        assert_eq!(check(9), Ok(()));
+        assert_eq!(
+            check(10),
+            Err(SanityError::DateOutOfFrame {
+                date: BlockDate {
+                    epoch: 0,
+                    slot_id: 10
+                }
+            })
+        );
+
+        let header = HeaderBuilderNew::new(BlockVersion::Genesis, &Contents::empty())
+            .set_genesis()
+            .set_date(BlockDate {
+                epoch: 1,
+                slot_id: 5,
+            })
+            .into_unsigned_header()
+            .unwrap()
+            .generalize();
+        assert_eq!(
+            check_clock(&header, &era, &time_frame, now, LEAP_SECOND_TOLERANCE),
+            Err(SanityError::FutureDate {
+                date: BlockDate {
+                    epoch: 1,
+                    slot_id: 5
+                },
+                ahead: Duration::from_secs(14)
+            })
+        );
+    }
+}
@@ -1,6 +1,8 @@
 use super::*;
 use crate::chaintypes::ChainLength;
-use crate::header::{BftProof, BftSignature, Common, GenesisPraosProof, KesSignature};
+use crate::header::{
+    BftEndorsement, BftProof, BftSignature, Common, GenesisPraosProof, KesSignature,
+};
 use crate::key::BftLeaderId;
 #[cfg(test)]
 use crate::testing::serialization::serialization_bijection_r;
@@ -17,11 +19,24 @@ quickcheck! {
     fn header_serialization_bijection(b: Header) -> TestResult {
         serialization_bijection_r(b)
     }
+
+    fn header_producer_is_the_signer_of_the_proof(header: Header) -> bool {
+        match (header.proof(), header.producer()) {
+            (Proof::None, None) => true,
+            (Proof::Bft(proof), Some(BlockProducer::BftLeader(leader_id))) => {
+                proof.leader_id == leader_id
+            }
+            (Proof::GenesisPraos(proof), Some(BlockProducer::Pool(pool_id))) => {
+                proof.node_id == pool_id
+            }
+            _ => false,
+        }
+    }
 }
 
 impl Arbitrary for BlockVersion {
     fn arbitrary<G: Gen>(g: &mut G) -> Self {
-        BlockVersion::from_u16(u16::arbitrary(g) % 3).unwrap()
+        BlockVersion::from_u16(u16::arbitrary(g) % 5).unwrap()
     }
 }
 
@@ -49,9 +64,24 @@ impl Arbitrary for BftProof {
         let sk: chain_crypto::SecretKey<Ed25519> = Arbitrary::arbitrary(g);
         let pk = sk.to_public();
         let signature = sk.sign(&[0u8, 1, 2, 3]);
+        let endorsements = std::iter::repeat_with(|| Arbitrary::arbitrary(g))
+            .take(usize::arbitrary(g) % 4)
+            .collect();
         BftProof {
             leader_id: BftLeaderId(pk),
             signature: BftSignature(signature.coerce()),
+            endorsements,
+        }
+    }
+}
+
+impl Arbitrary for BftEndorsement {
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        let sk: chain_crypto::SecretKey<Ed25519> = Arbitrary::arbitrary(g);
+        let signature = sk.sign(&[0u8, 1, 2, 3]);
+        BftEndorsement {
+            leader_id: BftLeaderId(sk.to_public()),
+            signature: BftSignature(signature.coerce()),
         }
     }
 }
@@ -95,16 +125,22 @@ impl Arbitrary for Header {
         .set_date(common.block_date);
         match common.block_version {
             BlockVersion::Genesis => hdrbuilder.into_unsigned_header().unwrap().generalize(),
-            BlockVersion::Ed25519Signed => {
+            BlockVersion::Ed25519Signed | BlockVersion::Ed25519SignedMerkle => {
                 let bft_proof: BftProof = Arbitrary::arbitrary(g);
-                hdrbuilder
+                let header = hdrbuilder
                     .into_bft_builder()
                     .unwrap()
                     .set_consensus_data(&bft_proof.leader_id)
-                    .set_signature(bft_proof.signature)
+                    .set_signature(bft_proof.signature);
+                bft_proof
+                    .endorsements
+                    .iter()
+                    .fold(header, |header, endorsement| {
+                        header.add_endorsement(endorsement).unwrap()
+                    })
                     .generalize()
             }
-            BlockVersion::KesVrfproof => {
+            BlockVersion::KesVrfproof | BlockVersion::KesVrfproofMerkle => {
                 let gp_proof: GenesisPraosProof = Arbitrary::arbitrary(g);
                 hdrbuilder
                     .into_genesis_praos_builder()
@@ -1,5 +1,6 @@
 use super::cstruct;
 use crate::chaintypes::ConsensusType;
+use crate::fragment::ContentHashScheme;
 use std::num::NonZeroUsize;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -55,6 +56,10 @@ pub enum BlockVersion {
     Genesis,
     Ed25519Signed,
     KesVrfproof,
+    /// same as `Ed25519Signed`, with the content hash as a Merkle tree
+    Ed25519SignedMerkle,
+    /// same as `KesVrfproof`, with the content hash as a Merkle tree
+    KesVrfproofMerkle,
 }
 
 impl BlockVersion {
@@ -63,6 +68,8 @@ impl BlockVersion {
             cstruct::VERSION_UNSIGNED => Some(BlockVersion::Genesis),
             cstruct::VERSION_BFT => Some(BlockVersion::Ed25519Signed),
             cstruct::VERSION_GP => Some(BlockVersion::KesVrfproof),
+            cstruct::VERSION_BFT_MERKLE => Some(BlockVersion::Ed25519SignedMerkle),
+            cstruct::VERSION_GP_MERKLE => Some(BlockVersion::KesVrfproofMerkle),
             _ => None,
         }
     }
@@ -72,23 +79,29 @@ impl BlockVersion {
             BlockVersion::Genesis => cstruct::VERSION_UNSIGNED,
             BlockVersion::Ed25519Signed => cstruct::VERSION_BFT,
             BlockVersion::KesVrfproof => cstruct::VERSION_GP,
+            BlockVersion::Ed25519SignedMerkle => cstruct::VERSION_BFT_MERKLE,
+            BlockVersion::KesVrfproofMerkle => cstruct::VERSION_GP_MERKLE,
         }
     }
 
     pub const fn get_size(self) -> NonZeroUsize {
-        const SIZE: [NonZeroUsize; 3] = [
+        const SIZE: [NonZeroUsize; 5] = [
             unsafe { NonZeroUsize::new_unchecked(cstruct::HEADER_COMMON_SIZE) },
             unsafe { NonZeroUsize::new_unchecked(cstruct::HEADER_BFT_SIZE) },
             unsafe { NonZeroUsize::new_unchecked(cstruct::HEADER_GP_SIZE) },
+            unsafe { NonZeroUsize::new_unchecked(cstruct::HEADER_BFT_SIZE) },
+            unsafe { NonZeroUsize::new_unchecked(cstruct::HEADER_GP_SIZE) },
         ];
         SIZE[self as usize]
     }
 
     pub const fn get_auth_size(self) -> NonZeroUsize {
-        const SIZE: [NonZeroUsize; 3] = [
+        const SIZE: [NonZeroUsize; 5] = [
             unsafe { NonZeroUsize::new_unchecked(cstruct::HEADER_COMMON_SIZE) },
             unsafe { NonZeroUsize::new_unchecked(cstruct::HEADER_BFT_AUTHED_SIZE) },
             unsafe { NonZeroUsize::new_unchecked(cstruct::HEADER_GP_AUTHED_SIZE) },
+            unsafe { NonZeroUsize::new_unchecked(cstruct::HEADER_BFT_AUTHED_SIZE) },
+            unsafe { NonZeroUsize::new_unchecked(cstruct::HEADER_GP_AUTHED_SIZE) },
         ];
         SIZE[self as usize]
     }
@@ -96,8 +109,24 @@ impl BlockVersion {
     pub fn to_consensus_type(self) -> Option<ConsensusType> {
         match self {
             BlockVersion::Genesis => None,
-            BlockVersion::Ed25519Signed => Some(ConsensusType::Bft),
-            BlockVersion::KesVrfproof => Some(ConsensusType::GenesisPraos),
+            BlockVersion::Ed25519Signed | BlockVersion::Ed25519SignedMerkle => {
+                Some(ConsensusType::Bft)
+            }
+            BlockVersion::KesVrfproof | BlockVersion::KesVrfproofMerkle => {
+                Some(ConsensusType::GenesisPraos)
+            }
+        }
+    }
+
+    /// How the content hash of the blocks of this version is computed
+    pub fn content_hash_scheme(self) -> ContentHashScheme {
+        match self {
+            BlockVersion::Genesis | BlockVersion::Ed25519Signed | BlockVersion::KesVrfproof => {
+                ContentHashScheme::Sequential
+            }
+            BlockVersion::Ed25519SignedMerkle | BlockVersion::KesVrfproofMerkle => {
+                ContentHashScheme::MerkleTree
+            }
         }
     }
 }
@@ -106,6 +135,7 @@ impl BlockVersion {
 mod tests {
 
     use crate::chaintypes::ConsensusType;
+    use crate::fragment::ContentHashScheme;
     use crate::header::{AnyBlockVersion, BlockVersion};
     use quickcheck::TestResult;
     use quickcheck_macros::quickcheck;
@@ -174,5 +204,29 @@ mod tests {
             BlockVersion::KesVrfproof.to_consensus_type(),
             Some(ConsensusType::GenesisPraos)
         );
+        assert_eq!(
+            BlockVersion::Ed25519SignedMerkle.to_consensus_type(),
+            Some(ConsensusType::Bft)
+        );
+        assert_eq!(
+            BlockVersion::KesVrfproofMerkle.to_consensus_type(),
+            Some(ConsensusType::GenesisPraos)
+        );
+    }
+
+    #[test]
+    pub fn content_hash_scheme() {
+        assert_eq!(
+            BlockVersion::Ed25519Signed.content_hash_scheme(),
+            ContentHashScheme::Sequential
+        );
+        assert_eq!(
+            BlockVersion::Ed25519SignedMerkle.content_hash_scheme(),
+            ContentHashScheme::MerkleTree
+        );
+        assert_eq!(
+            BlockVersion::KesVrfproofMerkle.content_hash_scheme(),
+            ContentHashScheme::MerkleTree
+        );
     }
 }
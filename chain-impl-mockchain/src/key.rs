@@ -2,7 +2,7 @@
 //! the user keys.
 //!
 use chain_core::mempack::{read_mut_slice, ReadBuf, ReadError, Readable};
-use chain_core::property;
+use chain_core::property::{self, ByteSource, Pack, Unpack};
 use chain_crypto as crypto;
 use chain_crypto::{
     digest::DigestOf, AsymmetricKey, AsymmetricPublicKey, Blake2b256, Ed25519, PublicKey,
@@ -12,6 +12,7 @@ use rand_core::{CryptoRng, RngCore};
 use typed_bytes::ByteBuilder;
 
 use chain_core::packer::Codec;
+use std::marker::PhantomData;
 use std::str::FromStr;
 
 #[derive(Clone)]
@@ -55,23 +56,28 @@ pub type AccountSignature<T> = crypto::Signature<T, crypto::Ed25519>;
 
 pub type Ed25519Signature<T> = crypto::Signature<T, crypto::Ed25519>;
 
-fn chain_crypto_pub_err(e: crypto::PublicKeyError) -> ReadError {
+fn chain_crypto_pub_err<A: AsymmetricPublicKey>(
+    e: crypto::PublicKeyError,
+    size: usize,
+) -> ReadError {
     match e {
-        crypto::PublicKeyError::SizeInvalid => {
-            ReadError::StructureInvalid("publickey size invalid".to_string())
-        }
+        crypto::PublicKeyError::SizeInvalid => ReadError::SizeMismatch {
+            expected: A::PUBLIC_KEY_SIZE,
+            actual: size,
+        },
         crypto::PublicKeyError::StructureInvalid => {
-            ReadError::StructureInvalid("publickey structure invalid".to_string())
+            ReadError::StructureInvalid("publickey structure invalid".into())
         }
     }
 }
 fn chain_crypto_sig_err(e: crypto::SignatureError) -> ReadError {
     match e {
-        crypto::SignatureError::SizeInvalid { expected, got } => ReadError::StructureInvalid(
-            format!("signature size invalid, expected {} got {}", expected, got),
-        ),
+        crypto::SignatureError::SizeInvalid { expected, got } => ReadError::SizeMismatch {
+            expected,
+            actual: got,
+        },
         crypto::SignatureError::StructureInvalid => {
-            ReadError::StructureInvalid("signature structure invalid".to_string())
+            ReadError::StructureInvalid("signature structure invalid".into())
         }
     }
 }
@@ -97,7 +103,7 @@ where
 {
     let mut bytes = vec![0u8; A::PUBLIC_KEY_SIZE];
     read_mut_slice(buf, &mut bytes[..])?;
-    crypto::PublicKey::from_binary(&bytes).map_err(chain_crypto_pub_err)
+    crypto::PublicKey::from_binary(&bytes).map_err(|e| chain_crypto_pub_err::<A>(e, bytes.len()))
 }
 #[inline]
 pub fn deserialize_signature<A, T>(buf: &mut ReadBuf) -> Result<crypto::Signature<T, A>, ReadError>
@@ -109,6 +115,25 @@ where
     crypto::Signature::from_binary(&bytes).map_err(chain_crypto_sig_err)
 }
 
+/// Read a public key from any [`ByteSource`]
+#[inline]
+pub fn unpack_public_key<A, S>(source: &mut S) -> Result<crypto::PublicKey<A>, ReadError>
+where
+    A: AsymmetricPublicKey,
+    S: ByteSource,
+{
+    source.with_slice(A::PUBLIC_KEY_SIZE, deserialize_public_key)
+}
+/// Read a signature from any [`ByteSource`]
+#[inline]
+pub fn unpack_signature<A, T, S>(source: &mut S) -> Result<crypto::Signature<T, A>, ReadError>
+where
+    A: VerificationAlgorithm,
+    S: ByteSource,
+{
+    source.with_slice(A::SIGNATURE_SIZE, deserialize_signature)
+}
+
 pub fn make_signature<T, A>(
     spending_key: &crypto::SecretKey<A>,
     data: &T,
@@ -216,99 +241,160 @@ impl<T: Clone, A: VerificationAlgorithm> Clone for Signed<T, A> {
     }
 }
 
-/// Hash that is used as an address of the various components.
+/// Digest algorithm of the identifiers of the chain (headers, fragments...).
+///
+/// Whatever the algorithm, an identifier is 32 bytes long, so changing the
+/// algorithm does not change the layout of the structures referencing it.
+pub trait IdHashAlgorithm:
+    Copy + Eq + Ord + std::hash::Hash + std::fmt::Debug + Send + Sync + 'static
+{
+    /// name of the algorithm, used in the debug output of the ids like the
+    /// debug output of the `chain_crypto` digests
+    const NAME: &'static str;
+
+    fn digest(bytes: &[u8]) -> [u8; 32];
+}
+
+impl IdHashAlgorithm for crypto::Blake2b256 {
+    const NAME: &'static str = "Blake2b256";
+
+    fn digest(bytes: &[u8]) -> [u8; 32] {
+        crypto::Blake2b256::new(bytes).into()
+    }
+}
+
+/// SHA3-256, to experiment with chains not using the default algorithm
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct Hash(crypto::Blake2b256);
-impl Hash {
+pub enum Sha3_256 {}
+
+impl IdHashAlgorithm for Sha3_256 {
+    const NAME: &'static str = "Sha3_256";
+
+    fn digest(bytes: &[u8]) -> [u8; 32] {
+        use cryptoxide::digest::Digest;
+        let mut sha3 = cryptoxide::sha3::Sha3_256::new();
+        let mut out = [0; 32];
+        sha3.input(bytes);
+        sha3.result(&mut out);
+        out
+    }
+}
+
+/// Identifier computed with the hash algorithm `H`
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Id<H: IdHashAlgorithm = crypto::Blake2b256> {
+    bytes: [u8; 32],
+    algorithm: PhantomData<H>,
+}
+
+/// Hash that is used as an address of the various components.
+pub type Hash = Id<crypto::Blake2b256>;
+
+impl<H: IdHashAlgorithm> Id<H> {
     /// All 0 hash used as a special hash
     pub fn zero_hash() -> Self {
-        Hash(crypto::Blake2b256::from([0; crypto::Blake2b256::HASH_SIZE]))
+        Id::from_bytes([0; 32])
     }
     pub fn hash_bytes(bytes: &[u8]) -> Self {
-        Hash(crypto::Blake2b256::new(bytes))
+        Id::from_bytes(H::digest(bytes))
     }
     pub fn from_bytes(bytes: [u8; 32]) -> Self {
-        Hash(crypto::Blake2b256::from(bytes))
+        Id {
+            bytes,
+            algorithm: PhantomData,
+        }
     }
 
     #[inline]
     pub fn as_bytes(&self) -> &[u8] {
-        self.0.as_ref()
+        &self.bytes
     }
-}
 
-impl From<[u8; 32]> for Hash {
-    fn from(a: [u8; 32]) -> Self {
-        Hash::from_bytes(a)
+    /// Write the identifier in lowercase hexadecimal, like the `Display`
+    /// implementation, without allocating
+    pub fn write_hex<W: std::fmt::Write + ?Sized>(&self, writer: &mut W) -> std::fmt::Result {
+        crypto::display::write_hex(&self.bytes, writer)
+    }
+
+    /// Write the Bech32 representation of the identifier with the prefix
+    /// `hrp` without allocating
+    pub fn write_bech32<W: std::fmt::Write>(&self, hrp: &str, writer: &mut W) -> std::fmt::Result {
+        crypto::bech32::write_bech32(hrp, &self.bytes, writer)
     }
 }
 
-impl From<Hash> for [u8; 32] {
-    fn from(h: Hash) -> Self {
-        h.0.into()
+impl<H: IdHashAlgorithm> From<[u8; 32]> for Id<H> {
+    fn from(a: [u8; 32]) -> Self {
+        Id::from_bytes(a)
     }
 }
 
-impl<'a> From<&'a Hash> for &'a [u8; 32] {
-    fn from(h: &'a Hash) -> Self {
-        (&h.0).into()
+impl<H: IdHashAlgorithm> From<Id<H>> for [u8; 32] {
+    fn from(h: Id<H>) -> Self {
+        h.bytes
     }
 }
 
-impl property::Serialize for Hash {
-    type Error = std::io::Error;
-    fn serialize<W: std::io::Write>(&self, mut writer: W) -> Result<(), Self::Error> {
-        writer.write_all(self.0.as_hash_bytes())?;
-        Ok(())
+impl<'a, H: IdHashAlgorithm> From<&'a Id<H>> for &'a [u8; 32] {
+    fn from(h: &'a Id<H>) -> Self {
+        &h.bytes
     }
 }
 
-impl property::Deserialize for Hash {
-    type Error = std::io::Error;
-    fn deserialize<R: std::io::BufRead>(mut reader: R) -> Result<Self, Self::Error> {
-        let mut buffer = [0; crypto::Blake2b256::HASH_SIZE];
-        reader.read_exact(&mut buffer)?;
-        Ok(Hash(crypto::Blake2b256::from(buffer)))
+impl<H: IdHashAlgorithm> Pack for Id<H> {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(&self.bytes)
     }
 }
 
-impl Readable for Hash {
-    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        let bytes = <[u8; crypto::Blake2b256::HASH_SIZE]>::read(buf)?;
-        Ok(Hash(crypto::Blake2b256::from(bytes)))
+impl<H: IdHashAlgorithm> Unpack for Id<H> {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let mut bytes = [0; 32];
+        source.take_into(&mut bytes)?;
+        Ok(Id::from_bytes(bytes))
     }
 }
 
-impl property::BlockId for Hash {
-    fn zero() -> Hash {
-        Hash(crypto::Blake2b256::from([0; crypto::Blake2b256::HASH_SIZE]))
+chain_ser::impl_legacy_codecs!(Hash);
+
+impl<H: IdHashAlgorithm> property::BlockId for Id<H> {
+    fn zero() -> Self {
+        Id::zero_hash()
     }
 }
 
-impl property::FragmentId for Hash {}
+impl<H: IdHashAlgorithm> property::FragmentId for Id<H> {}
 
-impl AsRef<[u8]> for Hash {
+impl<H: IdHashAlgorithm> AsRef<[u8]> for Id<H> {
     fn as_ref(&self) -> &[u8] {
-        self.0.as_ref()
+        &self.bytes
     }
 }
 
 impl From<crypto::Blake2b256> for Hash {
     fn from(hash: crypto::Blake2b256) -> Self {
-        Hash(hash)
+        Id::from_bytes(hash.into())
+    }
+}
+
+impl<H: IdHashAlgorithm> std::fmt::Display for Id<H> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.write_hex(f)
     }
 }
 
-impl std::fmt::Display for Hash {
+impl<H: IdHashAlgorithm> std::fmt::Debug for Id<H> {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "{}", self.0)
+        write!(f, "Hash({}(0x", H::NAME)?;
+        self.write_hex(f)?;
+        f.write_str("))")
     }
 }
 
-impl FromStr for Hash {
+impl<H: IdHashAlgorithm> FromStr for Id<H> {
     type Err = crypto::hash::Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Hash(crypto::Blake2b256::from_str(s)?))
+        Ok(Id::from_bytes(crypto::Blake2b256::from_str(s)?.into()))
     }
 }
 
@@ -329,36 +415,21 @@ impl BftLeaderId {
     }
 }
 
-impl property::Serialize for BftLeaderId {
-    type Error = std::io::Error;
-    fn serialize<W: std::io::Write>(&self, writer: W) -> Result<(), Self::Error> {
-        serialize_public_key(&self.0, writer)
+impl Pack for BftLeaderId {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        serialize_public_key(&self.0, codec)
     }
 }
 
-impl property::Deserialize for BftLeaderId {
-    type Error = std::io::Error;
-
-    fn deserialize<R: std::io::BufRead>(reader: R) -> Result<Self, Self::Error> {
-        let mut codec = Codec::new(reader);
-        let size: usize = 32;
-        let bytes = codec.get_bytes(size)?;
-        let mut buff = ReadBuf::from(&bytes);
-        match deserialize_public_key(&mut buff) {
-            Ok(pk) => Ok(BftLeaderId(pk)),
-            Err(e) => Err(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("Error reading LeaderId public key: {}", e),
-            )),
-        }
+impl Unpack for BftLeaderId {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        source
+            .with_slice(BftVerificationAlg::PUBLIC_KEY_SIZE, deserialize_public_key)
+            .map(BftLeaderId)
     }
 }
 
-impl Readable for BftLeaderId {
-    fn read(reader: &mut ReadBuf) -> Result<Self, ReadError> {
-        deserialize_public_key(reader).map(BftLeaderId)
-    }
-}
+chain_ser::impl_legacy_codecs!(BftLeaderId);
 
 impl AsRef<[u8]> for BftLeaderId {
     fn as_ref(&self) -> &[u8] {
@@ -390,10 +461,17 @@ impl GenesisPraosLeader {
     }
 }
 
-impl Readable for GenesisPraosLeader {
-    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        let vrf_public_key = deserialize_public_key(buf)?;
-        let kes_public_key = deserialize_public_key(buf)?;
+impl Pack for GenesisPraosLeader {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        serialize_public_key(&self.vrf_public_key, &mut *codec)?;
+        serialize_public_key(&self.kes_public_key, codec)
+    }
+}
+
+impl Unpack for GenesisPraosLeader {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let vrf_public_key = unpack_public_key(source)?;
+        let kes_public_key = unpack_public_key(source)?;
         Ok(GenesisPraosLeader {
             kes_public_key,
             vrf_public_key,
@@ -401,20 +479,25 @@ impl Readable for GenesisPraosLeader {
     }
 }
 
+chain_ser::impl_legacy_codecs!(GenesisPraosLeader);
+
 #[cfg(any(test, feature = "property-test-api"))]
 mod tests {
     use super::*;
     #[cfg(test)]
     use crate::testing::serialization::serialization_bijection;
-    use chain_crypto::{testing, PublicKey, RistrettoGroup2HashDh, SecretKey, SumEd25519_12};
+    use chain_crypto::{
+        bech32::Bech32, testing, PublicKey, RistrettoGroup2HashDh, SecretKey, SumEd25519_12,
+    };
     use lazy_static::lazy_static;
     #[cfg(test)]
     use quickcheck::TestResult;
     use quickcheck::{quickcheck, Arbitrary, Gen};
 
-    impl Arbitrary for Hash {
+    impl<H: IdHashAlgorithm> Arbitrary for Id<H> {
         fn arbitrary<G: Gen>(g: &mut G) -> Self {
-            Hash(Arbitrary::arbitrary(g))
+            let hash: crypto::Blake2b256 = Arbitrary::arbitrary(g);
+            Id::from_bytes(hash.into())
         }
     }
     impl Arbitrary for EitherEd25519SecretKey {
@@ -447,5 +530,51 @@ mod tests {
         fn leader_id_serialize_deserialize_biyection(leader_id: BftLeaderId) -> TestResult {
             serialization_bijection(leader_id)
         }
+
+        fn hash_serialize_deserialize_biyection(hash: Hash) -> TestResult {
+            serialization_bijection(hash)
+        }
+    }
+
+    #[test]
+    fn default_id_hash_algorithm_is_blake2b256() {
+        let bytes = b"some header";
+        let expected: [u8; 32] = crypto::Blake2b256::new(bytes).into();
+        assert_eq!(Hash::hash_bytes(bytes).as_bytes(), &expected);
+        assert_eq!(
+            Id::<Sha3_256>::hash_bytes(bytes).to_string().len(),
+            Hash::hash_bytes(bytes).to_string().len()
+        );
+        assert_ne!(
+            Id::<Sha3_256>::hash_bytes(bytes).as_bytes(),
+            Hash::hash_bytes(bytes).as_bytes()
+        );
+    }
+
+    #[test]
+    fn sha3_256_id_known_answer() {
+        assert_eq!(
+            Id::<Sha3_256>::hash_bytes(b"").to_string(),
+            "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a"
+        );
+    }
+
+    #[test]
+    fn id_is_written_without_allocation_like_its_string() {
+        let id = Hash::hash_bytes(b"some fragment");
+        let mut written = String::new();
+        id.write_hex(&mut written).unwrap();
+        assert_eq!(written, id.to_string());
+        assert_eq!(written, hex::encode(id.as_bytes()));
+        assert_eq!(
+            format!("{:?}", id),
+            format!("Hash(Blake2b256(0x{}))", written)
+        );
+
+        let hash = crypto::Blake2b256::from(<[u8; 32]>::from(id));
+        let mut written = String::new();
+        id.write_bech32(crypto::Blake2b256::BECH32_HRP, &mut written)
+            .unwrap();
+        assert_eq!(written, hash.to_bech32_str());
     }
 }
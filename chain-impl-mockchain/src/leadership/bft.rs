@@ -1,8 +1,9 @@
-use crate::block::{BftProof, BlockDate, Header, Proof};
+use crate::block::{BftEndorsement, BftProof, BlockDate, Header, Proof};
 use crate::{
     key::BftLeaderId,
     leadership::{Error, ErrorKind, Verification},
     ledger::Ledger,
+    setting::Settings,
 };
 use std::sync::Arc;
 
@@ -13,17 +14,24 @@ pub struct BftRoundRobinIndex(u64);
 #[derive(Debug)]
 pub struct LeadershipData {
     pub(crate) leaders: Arc<[BftLeaderId]>,
+    /// number of leaders which need to sign a block, the slot leader included
+    pub(crate) quorum: usize,
 }
 
 impl LeadershipData {
     /// Create a new BFT leadership
     pub fn new(ledger: &Ledger) -> Option<Self> {
-        if ledger.settings.bft_leaders.len() == 0 {
+        Self::from_settings(&ledger.settings)
+    }
+
+    pub(crate) fn from_settings(settings: &Settings) -> Option<Self> {
+        if settings.bft_leaders.len() == 0 {
             return None;
         }
 
         Some(LeadershipData {
-            leaders: Arc::clone(&ledger.settings.bft_leaders),
+            leaders: Arc::clone(&settings.bft_leaders),
+            quorum: settings.bft_quorum as usize,
         })
     }
 
@@ -48,6 +56,7 @@ impl LeadershipData {
                 let BftProof {
                     leader_id,
                     signature,
+                    endorsements,
                 } = bft_proof;
 
                 let leader = self.get_leader_at(block_header.block_date());
@@ -63,7 +72,9 @@ impl LeadershipData {
                         chain_crypto::Verification::Failed => {
                             Verification::Failure(Error::new(ErrorKind::InvalidLeaderSignature))
                         }
-                        chain_crypto::Verification::Success => Verification::Success,
+                        chain_crypto::Verification::Success => {
+                            self.verify_endorsements(block_header, &leader, endorsements)
+                        }
                     }
                 }
             }
@@ -71,6 +82,48 @@ impl LeadershipData {
         }
     }
 
+    /// Every endorsement needs to be a valid signature of a leader other than
+    /// the slot leader, each leader endorsing at most once, and the
+    /// endorsements need to bring the number of signers up to the quorum.
+    /// The endorsements are canonical: sorted by leader id, without any
+    /// beyond the quorum. They are not part of the header id, so headers of
+    /// the same block endorsed by different leaders share their id.
+    fn verify_endorsements(
+        &self,
+        block_header: &Header,
+        slot_leader: &BftLeaderId,
+        endorsements: &[BftEndorsement],
+    ) -> Verification {
+        let signed_data = BftEndorsement::signed_data(block_header.as_auth_slice());
+        let mut signers = vec![slot_leader];
+        for endorsement in endorsements {
+            let endorser = &endorsement.leader_id;
+            if !self.leaders.contains(endorser) || signers.contains(&endorser) {
+                return Verification::Failure(Error::new(ErrorKind::InvalidEndorsement));
+            }
+            match endorsement
+                .signature
+                .0
+                .verify_slice(endorser.as_public_key(), &signed_data)
+            {
+                chain_crypto::Verification::Failed => {
+                    return Verification::Failure(Error::new(ErrorKind::InvalidEndorsement))
+                }
+                chain_crypto::Verification::Success => signers.push(endorser),
+            }
+        }
+        let sorted = endorsements
+            .windows(2)
+            .all(|pair| pair[0].leader_id.as_ref() < pair[1].leader_id.as_ref());
+        if !sorted || endorsements.len() > self.quorum.saturating_sub(1) {
+            Verification::Failure(Error::new(ErrorKind::NonCanonicalEndorsements))
+        } else if signers.len() < self.quorum {
+            Verification::Failure(Error::new(ErrorKind::BftQuorumNotReached))
+        } else {
+            Verification::Success
+        }
+    }
+
     #[inline]
     pub(crate) fn get_leader_at(&self, date: BlockDate) -> BftLeaderId {
         let BftRoundRobinIndex(ofs) = self.offset(date.slot_id as u64);
@@ -82,6 +135,7 @@ impl LeadershipData {
 mod tests {
 
     use super::*;
+    use crate::block::BftSignature;
     use crate::fragment::Contents;
     use crate::header::BlockVersion;
     use crate::header::ChainLength;
@@ -91,8 +145,8 @@ mod tests {
     use crate::ledger::Pots;
     use crate::setting::Settings;
     use crate::testing::data::AddressData;
-    use crate::testing::TestGen;
-    use chain_crypto::Ed25519;
+    use crate::testing::{ConfigBuilder, LedgerBuilder, TestGen};
+    use chain_crypto::{Ed25519, SecretKey};
     fn block_date() -> BlockDate {
         BlockDate {
             epoch: 0,
@@ -207,4 +261,154 @@ mod tests {
 
         assert!(leadership_data.verify(&header).success());
     }
+
+    fn quorum_leadership(
+        leaders_count: usize,
+        quorum: u8,
+    ) -> (Vec<SecretKey<Ed25519>>, LeadershipData) {
+        let leaders_keys: Vec<SecretKey<Ed25519>> =
+            TestGen::secret_keys().take(leaders_count).collect();
+        let leaders: Vec<BftLeaderId> = leaders_keys
+            .iter()
+            .map(|key| BftLeaderId(key.to_public()))
+            .collect();
+        let config = ConfigBuilder::new()
+            .with_leaders(&leaders)
+            .with_bft_quorum(quorum);
+        let test_ledger = LedgerBuilder::from_config(config)
+            .build()
+            .expect("cannot build ledger");
+        let leadership_data =
+            LeadershipData::new(&test_ledger.ledger).expect("leaders ids collection is empty");
+        (leaders_keys, leadership_data)
+    }
+
+    fn quorum_header(leaders_keys: &[SecretKey<Ed25519>], endorsers: &[usize]) -> Header {
+        // the slot leader of the block date is the second leader
+        let header = HeaderBuilderNew::new(BlockVersion::Ed25519Signed, &Contents::empty())
+            .set_parent(&TestGen::hash(), ChainLength(1))
+            .set_date(block_date())
+            .into_bft_builder()
+            .unwrap()
+            .sign_using(&leaders_keys[1]);
+        endorsers
+            .iter()
+            .fold(header, |header, endorser| {
+                let endorsement = header.endorse_using(&leaders_keys[*endorser]);
+                header.add_endorsement(&endorsement).unwrap()
+            })
+            .generalize()
+    }
+
+    #[test]
+    fn verify_quorum_reached() {
+        let (leaders_keys, leadership_data) = quorum_leadership(3, 3);
+
+        let header = quorum_header(&leaders_keys, &[0, 2]);
+        assert!(leadership_data.verify(&header).success());
+    }
+
+    #[test]
+    fn verify_quorum_not_reached() {
+        let (leaders_keys, leadership_data) = quorum_leadership(3, 3);
+
+        for endorsers in [&[][..], &[0], &[2]] {
+            let header = quorum_header(&leaders_keys, endorsers);
+            match leadership_data.verify(&header) {
+                Verification::Failure(error) => {
+                    assert_eq!(error.kind, ErrorKind::BftQuorumNotReached)
+                }
+                Verification::Success => panic!("block accepted below the quorum"),
+            }
+        }
+    }
+
+    fn assert_non_canonical(leadership_data: &LeadershipData, header: &Header) {
+        match leadership_data.verify(header) {
+            Verification::Failure(error) => {
+                assert_eq!(error.kind, ErrorKind::NonCanonicalEndorsements)
+            }
+            Verification::Success => panic!("non canonical endorsements accepted"),
+        }
+    }
+
+    #[test]
+    fn verify_endorsements_without_quorum() {
+        let (leaders_keys, leadership_data) = quorum_leadership(3, 1);
+
+        assert!(leadership_data
+            .verify(&quorum_header(&leaders_keys, &[]))
+            .success());
+        assert_non_canonical(&leadership_data, &quorum_header(&leaders_keys, &[2]));
+    }
+
+    #[test]
+    fn verify_endorsements_beyond_quorum() {
+        let (leaders_keys, leadership_data) = quorum_leadership(3, 2);
+
+        for endorsers in [&[0][..], &[2]] {
+            let header = quorum_header(&leaders_keys, endorsers);
+            assert!(leadership_data.verify(&header).success());
+            assert_eq!(header.id(), quorum_header(&leaders_keys, &[]).id());
+        }
+        assert_non_canonical(&leadership_data, &quorum_header(&leaders_keys, &[0, 2]));
+    }
+
+    #[test]
+    fn verify_unsorted_endorsements() {
+        let (leaders_keys, leadership_data) = quorum_leadership(3, 3);
+        let header = quorum_header(&leaders_keys, &[2, 0]);
+        assert_eq!(header.id(), quorum_header(&leaders_keys, &[0, 2]).id());
+        assert!(leadership_data.verify(&header).success());
+
+        // swap the two endorsements at the end of the header
+        let mut bytes = header.as_slice().to_vec();
+        let endorsements = bytes.len() - 2 * 96;
+        bytes[endorsements..].rotate_left(96);
+        let swapped = Header::from_slice(&bytes).unwrap();
+        assert_non_canonical(&leadership_data, &swapped);
+    }
+
+    #[test]
+    fn verify_invalid_endorsements() {
+        let (leaders_keys, leadership_data) = quorum_leadership(3, 2);
+
+        // the slot leader endorsing its own block, a leader endorsing twice
+        for endorsers in [&[1][..], &[0, 0]] {
+            let header = quorum_header(&leaders_keys, endorsers);
+            match leadership_data.verify(&header) {
+                Verification::Failure(error) => {
+                    assert_eq!(error.kind, ErrorKind::InvalidEndorsement)
+                }
+                Verification::Success => panic!("invalid endorsement accepted"),
+            }
+        }
+
+        let header = HeaderBuilderNew::new(BlockVersion::Ed25519Signed, &Contents::empty())
+            .set_parent(&TestGen::hash(), ChainLength(1))
+            .set_date(block_date())
+            .into_bft_builder()
+            .unwrap()
+            .sign_using(&leaders_keys[1]);
+
+        // an endorsement by a key which is not a leader
+        let not_a_leader = header.endorse_using(&TestGen::secret_key());
+        let endorsed = header.clone().add_endorsement(&not_a_leader).unwrap();
+        assert!(leadership_data.verify(&endorsed.generalize()).failure());
+
+        // a leader id with the signature of another key
+        let mut forged = header.endorse_using(&TestGen::secret_key());
+        forged.leader_id = BftLeaderId(leaders_keys[0].to_public());
+        let endorsed = header.clone().add_endorsement(&forged).unwrap();
+        assert!(leadership_data.verify(&endorsed.generalize()).failure());
+
+        // a signature of the header without the endorsement domain tag
+        let auth_data = header.clone().generalize().as_auth_slice().to_vec();
+        let undomained = BftEndorsement {
+            leader_id: BftLeaderId(leaders_keys[0].to_public()),
+            signature: BftSignature(leaders_keys[0].sign_slice(&auth_data)),
+        };
+        let endorsed = header.add_endorsement(&undomained).unwrap();
+        assert!(leadership_data.verify(&endorsed.generalize()).failure());
+    }
 }
@@ -0,0 +1,225 @@
+//! Cache of the header leadership verification outcomes.
+//!
+//! The same header may be verified several times (when received from the
+//! network, when fetched, when applied), each time checking its signature
+//! and, for Genesis-Praos, its VRF proof. The cache keeps the outcome of
+//! the verification of the most recently used headers so these checks are
+//! made only once.
+//!
+//! The outcome of a verification depends on the leadership of the header's
+//! epoch, every entry records the epoch it was verified for. The entries of
+//! an epoch must be invalidated with [`VerificationCache::invalidate_epoch`]
+//! when its leadership changes (e.g. when switching to a branch with a
+//! different stake distribution).
+
+use super::{Error, ErrorKind, Leadership, Verification};
+use crate::{block::Header, date::Epoch, header::HeaderId};
+use std::collections::{BTreeMap, HashMap};
+use std::num::NonZeroUsize;
+
+struct CacheEntry {
+    epoch: Epoch,
+    outcome: Result<(), ErrorKind>,
+    last_used: u64,
+}
+
+/// LRU cache of the verification outcome of headers, keyed by header id
+pub struct VerificationCache {
+    capacity: NonZeroUsize,
+    entries: HashMap<HeaderId, CacheEntry>,
+    usage: BTreeMap<u64, HeaderId>,
+    tick: u64,
+    hits: u64,
+    misses: u64,
+}
+
+impl VerificationCache {
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        VerificationCache {
+            capacity,
+            entries: HashMap::new(),
+            usage: BTreeMap::new(),
+            tick: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> NonZeroUsize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// number of lookups answered from the cache
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// number of lookups which required a verification
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Drop the outcomes of the headers verified for the given epoch.
+    pub fn invalidate_epoch(&mut self, epoch: Epoch) {
+        let usage = &mut self.usage;
+        self.entries.retain(|_, entry| {
+            if entry.epoch == epoch {
+                usage.remove(&entry.last_used);
+            }
+            entry.epoch != epoch
+        });
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.usage.clear();
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn lookup(&mut self, id: &HeaderId, epoch: Epoch) -> Option<Result<(), ErrorKind>> {
+        let tick = self.next_tick();
+        match self.entries.get_mut(id) {
+            Some(entry) if entry.epoch == epoch => {
+                self.usage.remove(&entry.last_used);
+                self.usage.insert(tick, *id);
+                entry.last_used = tick;
+                self.hits += 1;
+                Some(entry.outcome.clone())
+            }
+            _ => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    fn insert(&mut self, id: HeaderId, epoch: Epoch, outcome: Result<(), ErrorKind>) {
+        let tick = self.next_tick();
+        if let Some(previous) = self.entries.insert(
+            id,
+            CacheEntry {
+                epoch,
+                outcome,
+                last_used: tick,
+            },
+        ) {
+            self.usage.remove(&previous.last_used);
+        }
+        self.usage.insert(tick, id);
+
+        while self.entries.len() > self.capacity.get() {
+            let oldest = match self.usage.keys().next() {
+                Some(oldest) => *oldest,
+                None => break,
+            };
+            if let Some(id) = self.usage.remove(&oldest) {
+                self.entries.remove(&id);
+            }
+        }
+    }
+}
+
+impl Leadership {
+    /// Same as [`Leadership::verify`], the outcome is looked up in the cache
+    /// first and recorded in the cache otherwise.
+    ///
+    /// A failure returned from the cache only carries the kind of the error,
+    /// not its cause.
+    pub fn verify_cached(
+        &self,
+        block_header: &Header,
+        cache: &mut VerificationCache,
+    ) -> Verification {
+        let id = block_header.hash();
+        if let Some(outcome) = cache.lookup(&id, self.epoch) {
+            return match outcome {
+                Ok(()) => Verification::Success,
+                Err(kind) => Verification::Failure(Error::new(kind)),
+            };
+        }
+
+        let verification = self.verify(block_header);
+        let outcome = match &verification {
+            Verification::Success => Ok(()),
+            Verification::Failure(error) => Err(error.kind.clone()),
+        };
+        cache.insert(id, self.epoch, outcome);
+        verification
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::leadership::tests::{generate_header_for_leader, generate_ledger_with_bft_leaders};
+    use crate::testing::data::AddressData;
+    use chain_crypto::Ed25519;
+
+    fn capacity(capacity: usize) -> NonZeroUsize {
+        NonZeroUsize::new(capacity).unwrap()
+    }
+
+    #[test]
+    pub fn verification_outcome_is_cached() {
+        let leader_key = AddressData::generate_key_pair::<Ed25519>();
+        let other_key = AddressData::generate_key_pair::<Ed25519>();
+        let (_, ledger) = generate_ledger_with_bft_leaders(vec![leader_key.public_key().clone()]);
+        let leadership = Leadership::new(0, &ledger);
+        let mut cache = VerificationCache::new(capacity(10));
+
+        let valid = generate_header_for_leader(leader_key.private_key().clone(), 0);
+        let invalid = generate_header_for_leader(other_key.private_key().clone(), 1);
+
+        assert!(leadership.verify_cached(&valid, &mut cache).success());
+        assert!(leadership.verify_cached(&valid, &mut cache).success());
+        assert!(leadership.verify_cached(&invalid, &mut cache).failure());
+        match leadership.verify_cached(&invalid, &mut cache) {
+            Verification::Failure(error) => assert_eq!(error.kind, ErrorKind::InvalidLeader),
+            Verification::Success => panic!("cached failure returned as a success"),
+        }
+        assert_eq!(cache.len(), 2);
+        assert_eq!((cache.hits(), cache.misses()), (2, 2));
+
+        cache.invalidate_epoch(0);
+        assert!(cache.is_empty());
+        assert!(leadership.verify_cached(&valid, &mut cache).success());
+        assert_eq!(cache.misses(), 3);
+    }
+
+    #[test]
+    pub fn least_recently_used_entry_is_evicted() {
+        let leader_key = AddressData::generate_key_pair::<Ed25519>();
+        let (_, ledger) = generate_ledger_with_bft_leaders(vec![leader_key.public_key().clone()]);
+        let leadership = Leadership::new(0, &ledger);
+        let mut cache = VerificationCache::new(capacity(2));
+
+        let headers: Vec<Header> = (0..3)
+            .map(|slot_id| generate_header_for_leader(leader_key.private_key().clone(), slot_id))
+            .collect();
+
+        leadership.verify_cached(&headers[0], &mut cache);
+        leadership.verify_cached(&headers[1], &mut cache);
+        // make the first header the most recently used one
+        leadership.verify_cached(&headers[0], &mut cache);
+        leadership.verify_cached(&headers[2], &mut cache);
+        assert_eq!(cache.len(), 2);
+
+        let misses = cache.misses();
+        leadership.verify_cached(&headers[0], &mut cache);
+        assert_eq!(cache.misses(), misses);
+        leadership.verify_cached(&headers[1], &mut cache);
+        assert_eq!(cache.misses(), misses + 1);
+    }
+}
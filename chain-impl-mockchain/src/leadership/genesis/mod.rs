@@ -7,7 +7,7 @@ use crate::{
     header::{Header, HeaderDesc, Proof},
     leadership::{Error, ErrorKind, Verification},
     ledger::Ledger,
-    setting::ActiveSlotsCoeff,
+    setting::{ActiveSlotsCoeff, Settings},
     stake::{PercentStake, PoolsState, Stake, StakeDistribution},
 };
 use chain_crypto::Verification as SigningVerification;
@@ -25,6 +25,11 @@ pub struct LeadershipData {
     // the epoch this leader selection is valid for
     epoch: Epoch,
     active_slots_coeff: ActiveSlotsCoeff,
+    // the coefficient of the previous epoch, the coefficient of the epoch
+    // is phased in from it over the first `active_slots_coeff_transition`
+    // slots
+    previous_active_slots_coeff: ActiveSlotsCoeff,
+    active_slots_coeff_transition: u32,
 }
 
 #[derive(Debug, Error)]
@@ -54,15 +59,44 @@ enum VrfError {
 
 impl LeadershipData {
     pub fn new(epoch: Epoch, ledger: &Ledger) -> Self {
+        Self::with_settings(epoch, ledger, &ledger.settings)
+    }
+
+    /// the leadership of the epoch with the given settings of the epoch, the
+    /// stake pools and the stake distribution are the ones of the ledger.
+    ///
+    /// The active slots coefficient of the previous epoch is taken from the
+    /// settings history of the ledger, without it the coefficient of the
+    /// epoch applies from the first slot.
+    pub(crate) fn with_settings(epoch: Epoch, ledger: &Ledger, settings: &Settings) -> Self {
+        let previous_active_slots_coeff = epoch
+            .checked_sub(1)
+            .and_then(|previous| ledger.settings_at(previous))
+            .map_or(settings.active_slots_coeff, |previous| {
+                previous.active_slots_coeff
+            });
         LeadershipData {
-            epoch_nonce: ledger.settings.consensus_nonce.clone(),
+            epoch_nonce: settings.consensus_nonce.clone(),
             nodes: ledger.delegation.clone(),
             distribution: ledger.get_stake_distribution(),
             epoch,
-            active_slots_coeff: ledger.settings.active_slots_coeff,
+            active_slots_coeff: settings.active_slots_coeff,
+            previous_active_slots_coeff,
+            active_slots_coeff_transition: settings.active_slots_coeff_transition,
         }
     }
 
+    /// the active slots coefficient the leaders of the slot are elected
+    /// with, see [`ActiveSlotsCoeff::in_transition`]
+    pub fn active_slots_coeff_at(&self, slot_id: u32) -> ActiveSlotsCoeff {
+        ActiveSlotsCoeff::in_transition(
+            self.previous_active_slots_coeff,
+            self.active_slots_coeff,
+            slot_id,
+            self.active_slots_coeff_transition,
+        )
+    }
+
     pub fn distribution(&self) -> &StakeDistribution {
         &self.distribution
     }
@@ -111,7 +145,7 @@ impl LeadershipData {
                     stake: percent_stake,
                     nonce: &self.epoch_nonce,
                     slot_id: date.slot_id,
-                    active_slots_coeff: self.active_slots_coeff,
+                    active_slots_coeff: self.active_slots_coeff_at(date.slot_id),
                 };
                 Ok(evaluator.evaluate(vrf_key))
             }
@@ -161,7 +195,8 @@ impl LeadershipData {
                             stake: percent_stake,
                             nonce: &self.epoch_nonce,
                             slot_id: block_header.block_date().slot_id,
-                            active_slots_coeff: self.active_slots_coeff,
+                            active_slots_coeff: self
+                                .active_slots_coeff_at(block_header.block_date().slot_id),
                         };
 
                         match evaluator.verify(&pool_info.keys.vrf_public_key, &proof) {
@@ -227,7 +262,6 @@ mod tests {
         builders::{GenesisPraosBlockBuilder, StakePoolBuilder},
         ConfigBuilder, LedgerBuilder,
     };
-    use crate::value::Value;
     use chain_core::property::ChainLength;
     use chain_crypto::{RistrettoGroup2HashDh, SecretKey};
 
@@ -261,7 +295,7 @@ mod tests {
                 slots_per_epoch: 1700,
                 active_slots_coeff,
                 pools_count,
-                value: Stake::from_value(Value(100)),
+                value: Stake(100),
             }
         }
 
@@ -394,14 +428,14 @@ mod tests {
         let (big_pool_id, big_pool_vrf_private_key) = make_pool(&mut ledger);
         pools.insert(
             big_pool_id.clone(),
-            (big_pool_vrf_private_key, 0, Stake::from_value(Value(1000))),
+            (big_pool_vrf_private_key, 0, Stake(1000)),
         );
 
         for _i in 0..10 {
             let (small_pool_id, small_pool_vrf_private_key) = make_pool(&mut ledger);
             pools.insert(
                 small_pool_id.clone(),
-                (small_pool_vrf_private_key, 0, Stake::from_value(Value(100))),
+                (small_pool_vrf_private_key, 0, Stake(100)),
             );
         }
 
@@ -427,7 +461,7 @@ mod tests {
                     Some(_witness) => {
                         any_found = true;
                         *times_selected += 1;
-                        if *value == Stake::from_value(Value(100)) {
+                        if *value == Stake(100) {
                             any_small = true;
                         }
                     }
@@ -481,11 +515,7 @@ mod tests {
             .register_stake_pool(stake_pool.info())
             .expect("cannot register stake pool");
         let mut selection = LeadershipData::new(selection_epoch, &ledger);
-        update_stake_pool_total_value(
-            &mut selection,
-            &stake_pool.id(),
-            Stake::from_value(Value(100)),
-        );
+        update_stake_pool_total_value(&mut selection, &stake_pool.id(), Stake(100));
 
         assert!(selection
             .leader(&stake_pool.id(), stake_pool.vrf().private_key(), date)
@@ -690,4 +720,42 @@ mod tests {
 
         assert!(selection.verify(&header).failure());
     }
+
+    #[test]
+    pub fn leadership_of_the_slots_of_the_transition() {
+        let date = BlockDate::first();
+        let testledger = LedgerBuilder::from_config(ConfigBuilder::new())
+            .build()
+            .expect("cannot build test ledger");
+        let mut ledger = testledger.ledger;
+
+        let stake_pool = StakePoolBuilder::new().build();
+        *ledger.delegation_mut() = ledger
+            .delegation()
+            .register_stake_pool(stake_pool.info())
+            .expect("cannot register stake pool");
+        let mut selection = LeadershipData::new(date.epoch, &ledger);
+        update_stake_pool_total_value(&mut selection, &stake_pool.id(), Stake(100));
+
+        // a coefficient of 1 elects the pool with the whole stake in every
+        // slot
+        let coeff = |millis| ActiveSlotsCoeff::try_from(Milli::from_millis(millis)).unwrap();
+        selection.previous_active_slots_coeff = coeff(1000);
+        selection.active_slots_coeff = coeff(1);
+        selection.active_slots_coeff_transition = 10;
+        assert_eq!(selection.active_slots_coeff_at(0), coeff(1000));
+        assert_eq!(selection.active_slots_coeff_at(9), coeff(101));
+        assert_eq!(selection.active_slots_coeff_at(10), coeff(1));
+
+        assert!(selection
+            .leader(&stake_pool.id(), stake_pool.vrf().private_key(), date)
+            .unwrap()
+            .is_some());
+        let block = GenesisPraosBlockBuilder::new()
+            .with_date(date)
+            .with_chain_length(ledger.chain_length())
+            .with_parent_id(testledger.block0_hash)
+            .build(&stake_pool, ledger.era());
+        assert!(selection.verify(block.header()).success());
+    }
 }
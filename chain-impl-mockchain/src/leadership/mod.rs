@@ -11,8 +11,11 @@ use chain_crypto::{Ed25519, RistrettoGroup2HashDh, SecretKey, SumEd25519_12};
 use chain_time::era::TimeEra;
 
 pub mod bft;
+mod cache;
 pub mod genesis;
 
+pub use self::cache::VerificationCache;
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ErrorKind {
     Failure,
@@ -22,6 +25,9 @@ pub enum ErrorKind {
     InvalidLeader,
     InvalidLeaderSignature,
     InvalidLeaderProof,
+    InvalidEndorsement,
+    BftQuorumNotReached,
+    NonCanonicalEndorsements,
     InvalidBlockMessage,
     InvalidStateUpdate,
     VrfNonceIsEmptyButNotSupposedTo,
@@ -102,10 +108,14 @@ impl LeadershipConsensus {
     #[inline]
     fn verify_version(&self, block_version: BlockVersion) -> Verification {
         match self {
-            LeadershipConsensus::Bft(_) if block_version == BlockVersion::Ed25519Signed => {
+            LeadershipConsensus::Bft(_)
+                if block_version.to_consensus_type() == Some(ConsensusType::Bft) =>
+            {
                 Verification::Success
             }
-            LeadershipConsensus::GenesisPraos(_) if block_version == BlockVersion::KesVrfproof => {
+            LeadershipConsensus::GenesisPraos(_)
+                if block_version.to_consensus_type() == Some(ConsensusType::GenesisPraos) =>
+            {
                 Verification::Success
             }
             _ => Verification::Failure(Error::new(ErrorKind::IncompatibleBlockVersion)),
@@ -150,20 +160,25 @@ impl LeadershipConsensus {
 }
 
 impl Leadership {
+    /// Create the leadership of the given epoch. For the past epochs still
+    /// in the settings history of the ledger, the settings of the epoch are
+    /// used so the blocks received late are verified against the parameters
+    /// they were produced with.
     pub fn new(epoch: Epoch, ledger: &Ledger) -> Self {
-        let inner = match ledger.settings.consensus_version {
+        let settings = ledger.settings_at(epoch).unwrap_or(&ledger.settings);
+        let inner = match settings.consensus_version {
             ConsensusType::Bft => {
-                LeadershipConsensus::Bft(bft::LeadershipData::new(ledger).unwrap())
-            }
-            ConsensusType::GenesisPraos => {
-                LeadershipConsensus::GenesisPraos(genesis::LeadershipData::new(epoch, ledger))
+                LeadershipConsensus::Bft(bft::LeadershipData::from_settings(settings).unwrap())
             }
+            ConsensusType::GenesisPraos => LeadershipConsensus::GenesisPraos(
+                genesis::LeadershipData::with_settings(epoch, ledger, settings),
+            ),
         };
         Leadership {
             epoch,
             era: ledger.era.clone(),
             inner,
-            ledger_parameters: ledger.get_ledger_parameters(),
+            ledger_parameters: LedgerParameters::from_settings(settings),
         }
     }
 
@@ -274,6 +289,14 @@ impl std::fmt::Display for ErrorKind {
             ErrorKind::InvalidLeader => write!(f, "Block has unexpected block leader"),
             ErrorKind::InvalidLeaderSignature => write!(f, "Block signature is invalid"),
             ErrorKind::InvalidLeaderProof => write!(f, "Block proof is invalid"),
+            ErrorKind::InvalidEndorsement => write!(f, "Block has an invalid endorsement"),
+            ErrorKind::BftQuorumNotReached => {
+                write!(f, "Block is not signed by enough BFT leaders")
+            }
+            ErrorKind::NonCanonicalEndorsements => write!(
+                f,
+                "Block endorsements are not exactly the ones needed for the quorum, sorted by leader id"
+            ),
             ErrorKind::InvalidBlockMessage => write!(f, "Invalid block message"),
             ErrorKind::InvalidStateUpdate => write!(f, "Invalid State Update"),
             ErrorKind::VrfNonceIsEmptyButNotSupposedTo => write!(f, "Vrf Nonce is empty"),
@@ -0,0 +1,348 @@
+//! Optional index of the fragments that modified the balance of every account.
+//!
+//! The index is not part of the consensus state: it is only maintained when
+//! enabled on a given ledger instance, it is not part of the ledger recovery
+//! format and it has its own serialization so it can be stored alongside
+//! (but separately from) the ledger snapshots.
+
+use super::ledger::Ledger;
+use crate::account;
+use crate::date::BlockDate;
+use crate::fragment::{Fragment, FragmentId};
+use crate::transaction::{InputEnum, Payload, TransactionSlice};
+use crate::value::Value;
+use chain_addr::Kind;
+use chain_core::{
+    mempack::ReadError,
+    packer::Codec,
+    property::{ByteSource, Pack, Unpack},
+};
+use imhamt::Hamt;
+use std::collections::hash_map::DefaultHasher;
+use std::sync::Arc;
+
+/// Signed change of the balance of an account
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BalanceDelta {
+    Credit(Value),
+    Debit(Value),
+}
+
+impl BalanceDelta {
+    pub(super) fn between(before: Value, after: Value) -> Option<Self> {
+        use std::cmp::Ordering::*;
+        match after.cmp(&before) {
+            Equal => None,
+            Greater => Some(BalanceDelta::Credit(Value(after.0 - before.0))),
+            Less => Some(BalanceDelta::Debit(Value(before.0 - after.0))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountHistoryEntry {
+    pub date: BlockDate,
+    pub fragment_id: FragmentId,
+    pub delta: BalanceDelta,
+}
+
+/// Map of account identifier to the list of the fragments which modified
+/// the account's balance, in order of application.
+#[derive(Clone, PartialEq, Eq)]
+pub struct AccountHistory(Hamt<DefaultHasher, account::Identifier, Arc<Vec<AccountHistoryEntry>>>);
+
+impl Default for AccountHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for AccountHistory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.0.iter()).finish()
+    }
+}
+
+impl AccountHistory {
+    pub fn new() -> Self {
+        AccountHistory(Hamt::new())
+    }
+
+    /// all the recorded entries of the given account, oldest first
+    pub fn get(&self, account: &account::Identifier) -> &[AccountHistoryEntry] {
+        self.0
+            .lookup(account)
+            .map(|entries| entries.as_slice())
+            .unwrap_or(&[])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&account::Identifier, &[AccountHistoryEntry])> {
+        self.0
+            .iter()
+            .map(|(account, entries)| (account, entries.as_slice()))
+    }
+
+    #[must_use = "this returns the result of the operation, without modifying the original"]
+    pub fn record(&self, account: &account::Identifier, entry: AccountHistoryEntry) -> Self {
+        let new_entries = Arc::new(vec![entry.clone()]);
+        AccountHistory(
+            self.0
+                .insert_or_update_simple(account.clone(), new_entries, |entries| {
+                    let mut entries = entries.as_ref().clone();
+                    entries.push(entry);
+                    Some(Arc::new(entries))
+                }),
+        )
+    }
+}
+
+impl Pack for AccountHistory {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_be_u32(self.0.size() as u32)?;
+        for (account, entries) in self.0.iter() {
+            account.pack(codec)?;
+            codec.put_be_u32(entries.len() as u32)?;
+            for entry in entries.iter() {
+                codec.put_be_u32(entry.date.epoch)?;
+                codec.put_be_u32(entry.date.slot_id)?;
+                entry.fragment_id.pack(codec)?;
+                let (tag, value) = match entry.delta {
+                    BalanceDelta::Credit(value) => (0, value),
+                    BalanceDelta::Debit(value) => (1, value),
+                };
+                codec.put_u8(tag)?;
+                codec.put_be_u64(value.0)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Unpack for AccountHistory {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let mut history = Hamt::new();
+        let nb_accounts = source.take_u32()?;
+        for _ in 0..nb_accounts {
+            let account = account::Identifier::unpack(source)?;
+            let nb_entries = source.take_u32()? as usize;
+            let mut entries = Vec::with_capacity(nb_entries);
+            for _ in 0..nb_entries {
+                let epoch = source.take_u32()?;
+                let slot_id = source.take_u32()?;
+                let fragment_id = FragmentId::unpack(source)?;
+                let delta = match source.take_u8()? {
+                    0 => BalanceDelta::Credit(Value::unpack(source)?),
+                    1 => BalanceDelta::Debit(Value::unpack(source)?),
+                    tag => {
+                        return Err(ReadError::UnknownTag {
+                            kind: "balance delta",
+                            tag: tag as u32,
+                        })
+                    }
+                };
+                entries.push(AccountHistoryEntry {
+                    date: BlockDate { epoch, slot_id },
+                    fragment_id,
+                    delta,
+                });
+            }
+            history = history
+                .insert(account, Arc::new(entries))
+                .map_err(|_| ReadError::StructureInvalid("duplicated account".into()))?;
+        }
+        Ok(AccountHistory(history))
+    }
+}
+
+chain_ser::impl_legacy_codecs!(AccountHistory);
+
+impl Ledger {
+    /// Start maintaining the account history index on this ledger
+    /// (and on all the ledgers derived from it).
+    ///
+    /// Only the fragments applied from now on are recorded.
+    pub fn enable_account_history(mut self) -> Self {
+        if self.account_history.is_none() {
+            self.account_history = Some(AccountHistory::new());
+        }
+        self
+    }
+
+    /// Stop maintaining the account history index and drop it.
+    pub fn disable_account_history(mut self) -> Self {
+        self.account_history = None;
+        self
+    }
+
+    /// the account history index, if enabled
+    pub fn account_history(&self) -> Option<&AccountHistory> {
+        self.account_history.as_ref()
+    }
+
+    /// Record in the account history the balance changes between `before`
+    /// and `self` of the accounts involved in `fragment`.
+    pub(super) fn record_account_history(
+        &mut self,
+        before: &Ledger,
+        fragment: &Fragment,
+        date: BlockDate,
+    ) {
+        let mut history = match self.account_history.take() {
+            None => return,
+            Some(history) => history,
+        };
+
+        let fragment_id = fragment.hash();
+        let mut accounts = fragment_accounts(fragment);
+        accounts.sort();
+        accounts.dedup();
+
+        for account in accounts {
+            let balance = |ledger: &Ledger| {
+                ledger
+                    .accounts
+                    .get_state(&account)
+                    .map(|state| state.value())
+                    .unwrap_or_else(|_| Value::zero())
+            };
+            if let Some(delta) = BalanceDelta::between(balance(before), balance(&*self)) {
+                history = history.record(
+                    &account,
+                    AccountHistoryEntry {
+                        date,
+                        fragment_id,
+                        delta,
+                    },
+                );
+            }
+        }
+
+        self.account_history = Some(history);
+    }
+}
+
+pub(super) fn fragment_accounts(fragment: &Fragment) -> Vec<account::Identifier> {
+    match fragment {
+        Fragment::Initial(_) | Fragment::OldUtxoDeclaration(_) | Fragment::Evm(_) => Vec::new(),
+        Fragment::Transaction(tx) => transaction_accounts(&tx.as_slice()),
+        Fragment::OwnerStakeDelegation(tx) => transaction_accounts(&tx.as_slice()),
+        Fragment::StakeDelegation(tx) => transaction_accounts(&tx.as_slice()),
+        Fragment::AmountStakeDelegation(tx) => transaction_accounts(&tx.as_slice()),
+        Fragment::RewardsRedelegation(tx) => transaction_accounts(&tx.as_slice()),
+        Fragment::ExtensionCertificate(tx) => transaction_accounts(&tx.as_slice()),
+        Fragment::VotePlanCancel(tx) => transaction_accounts(&tx.as_slice()),
+        Fragment::LegacyMigration(migration) => match migration.destination.kind() {
+            Kind::Account(public_key) => vec![public_key.clone().into()],
+            _ => Vec::new(),
+        },
+        Fragment::PoolRegistration(tx) => transaction_accounts(&tx.as_slice()),
+        Fragment::PoolRetirement(tx) => transaction_accounts(&tx.as_slice()),
+        Fragment::PoolUpdate(tx) => transaction_accounts(&tx.as_slice()),
+        Fragment::UpdateProposal(tx) => transaction_accounts(&tx.as_slice()),
+        Fragment::UpdateVote(tx) => transaction_accounts(&tx.as_slice()),
+        Fragment::VotePlan(tx) => transaction_accounts(&tx.as_slice()),
+        Fragment::VoteCast(tx) => transaction_accounts(&tx.as_slice()),
+        Fragment::VoteTally(tx) => transaction_accounts(&tx.as_slice()),
+        Fragment::EncryptedVoteTally(tx) => transaction_accounts(&tx.as_slice()),
+        Fragment::MintToken(tx) => transaction_accounts(&tx.as_slice()),
+        Fragment::NameClaim(tx) => transaction_accounts(&tx.as_slice()),
+        Fragment::NameRelease(tx) => transaction_accounts(&tx.as_slice()),
+        Fragment::VotingPowerSnapshot(tx) => transaction_accounts(&tx.as_slice()),
+        Fragment::SnapshotVoteCast(tx) => transaction_accounts(&tx.as_slice()),
+        Fragment::VoteCastBatch(tx) => transaction_accounts(&tx.as_slice()),
+        Fragment::TransactionBatch(batch) => batch
+            .transactions()
+            .iter()
+            .flat_map(|tx| transaction_accounts(&tx.as_slice()))
+            .collect(),
+    }
+}
+
+fn transaction_accounts<P: Payload>(tx: &TransactionSlice<P>) -> Vec<account::Identifier> {
+    let inputs = tx
+        .inputs()
+        .iter()
+        .filter_map(|input| match input.to_enum() {
+            InputEnum::AccountInput(account_id, _) => account_id.to_single_account(),
+            InputEnum::UtxoInput(_) => None,
+        });
+    let outputs = tx
+        .outputs()
+        .iter()
+        .filter_map(|output| match output.address.kind() {
+            Kind::Account(public_key) => Some(public_key.clone().into()),
+            _ => None,
+        });
+    inputs.chain(outputs).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{
+        builders::TestTxBuilder,
+        data::AddressDataValue,
+        ledger::{ConfigBuilder, LedgerBuilder},
+    };
+    use chain_addr::Discrimination;
+    use chain_core::{
+        mempack::{ReadBuf, Readable},
+        property::{Deserialize as _, Serialize},
+    };
+
+    #[test]
+    pub fn transfer_is_recorded_for_both_accounts() {
+        let faucet = AddressDataValue::account(Discrimination::Test, Value(1_000));
+        let receiver = AddressDataValue::account(Discrimination::Test, Value(0));
+        let mut test_ledger = LedgerBuilder::from_config(ConfigBuilder::new())
+            .faucet(&faucet)
+            .build()
+            .unwrap();
+        test_ledger.ledger = test_ledger.ledger.clone().enable_account_history();
+
+        let fragment = TestTxBuilder::new(test_ledger.block0_hash)
+            .move_funds(&mut test_ledger, &faucet, &receiver, Value(100))
+            .get_fragment();
+        test_ledger
+            .apply_fragment(&fragment, test_ledger.date())
+            .unwrap();
+
+        let history = test_ledger.ledger.account_history().unwrap();
+        let sender_history = history.get(&faucet.to_id());
+        let receiver_history = history.get(&receiver.to_id());
+
+        assert_eq!(sender_history.len(), 1);
+        assert_eq!(sender_history[0].fragment_id, fragment.hash());
+        assert!(matches!(sender_history[0].delta, BalanceDelta::Debit(_)));
+        assert_eq!(
+            receiver_history,
+            &[AccountHistoryEntry {
+                date: test_ledger.date(),
+                fragment_id: fragment.hash(),
+                delta: BalanceDelta::Credit(Value(100)),
+            }]
+        );
+
+        let bytes = history.serialize_as_vec().unwrap();
+        let mut buf = ReadBuf::from(&bytes);
+        let decoded = AccountHistory::read(&mut buf).unwrap();
+        assert!(buf.expect_end().is_ok());
+        assert!(decoded == *history);
+    }
+
+    #[test]
+    pub fn history_is_not_part_of_the_ledger_state() {
+        let ledger: Ledger = LedgerBuilder::from_config(ConfigBuilder::new())
+            .faucet_value(Value(42000))
+            .build()
+            .unwrap()
+            .into();
+        let with_history = ledger.clone().enable_account_history();
+        assert_eq!(with_history, ledger);
+
+        let bytes = with_history.serialize_as_vec().unwrap();
+        let decoded = Ledger::deserialize(bytes.as_slice()).unwrap();
+        assert!(decoded.account_history().is_none());
+        assert_eq!(decoded, with_history);
+    }
+}
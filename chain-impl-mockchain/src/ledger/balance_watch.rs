@@ -0,0 +1,213 @@
+//! Notification of the balances of some accounts crossing thresholds.
+//!
+//! The watch list is registered on a ledger instance, like the account
+//! history it is not part of the consensus state nor of the ledger recovery
+//! format. For every applied block, the balance of each watched account
+//! before the block (the rewards of an epoch transition included) is
+//! compared with its balance after the block, and a
+//! [`BalanceThresholdReport`] is emitted in the events of the block for
+//! every threshold crossed. A missing account has a zero balance.
+
+use super::ledger::Ledger;
+use crate::account;
+use crate::value::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+
+/// The direction of the crossing of a threshold
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThresholdCrossing {
+    /// the balance was below the threshold and reaches it
+    Rising,
+    /// the balance was at or above the threshold and goes below it
+    Falling,
+}
+
+/// A threshold crossed by the balance of a watched account
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalanceThresholdReport {
+    pub account: account::Identifier,
+    pub threshold: Value,
+    pub crossing: ThresholdCrossing,
+    pub before: Value,
+    pub after: Value,
+}
+
+/// The thresholds of the watched accounts
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BalanceWatches(Arc<BTreeMap<account::Identifier, BTreeSet<Value>>>);
+
+impl BalanceWatches {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Watch the balance of the account crossing the threshold
+    pub fn watch(mut self, account: account::Identifier, threshold: Value) -> Self {
+        Arc::make_mut(&mut self.0)
+            .entry(account)
+            .or_default()
+            .insert(threshold);
+        self
+    }
+
+    /// Stop watching all the thresholds of the account
+    pub fn unwatch(mut self, account: &account::Identifier) -> Self {
+        Arc::make_mut(&mut self.0).remove(account);
+        self
+    }
+
+    /// the thresholds watched for the account, in increasing order
+    pub fn thresholds(&self, account: &account::Identifier) -> impl Iterator<Item = Value> + '_ {
+        self.0.get(account).into_iter().flatten().copied()
+    }
+
+    /// the balances of the watched accounts in the ledger
+    pub(super) fn balances(&self, ledger: &Ledger) -> Vec<(account::Identifier, Value)> {
+        self.0
+            .keys()
+            .map(|account| (account.clone(), balance(ledger, account)))
+            .collect()
+    }
+
+    /// the thresholds crossed between the balances and the ledger, by
+    /// account then by threshold in the direction of the crossing
+    pub(super) fn crossings(
+        &self,
+        before: &[(account::Identifier, Value)],
+        ledger: &Ledger,
+    ) -> Vec<BalanceThresholdReport> {
+        let mut reports = Vec::new();
+        for (account, before) in before {
+            let after = balance(ledger, account);
+            let mut crossed: Vec<Value> = self
+                .thresholds(account)
+                .filter(|t| (before < t && *t <= after) || (after < *t && t <= before))
+                .collect();
+            let crossing = if after > *before {
+                ThresholdCrossing::Rising
+            } else {
+                crossed.reverse();
+                ThresholdCrossing::Falling
+            };
+            reports.extend(crossed.into_iter().map(|threshold| BalanceThresholdReport {
+                account: account.clone(),
+                threshold,
+                crossing,
+                before: *before,
+                after,
+            }));
+        }
+        reports
+    }
+}
+
+fn balance(ledger: &Ledger, account: &account::Identifier) -> Value {
+    ledger
+        .accounts
+        .get_state(account)
+        .map(|state| state.value())
+        .unwrap_or_else(|_| Value::zero())
+}
+
+impl Ledger {
+    /// Emit the crossings of the thresholds of the watched accounts in the
+    /// events of the blocks applied from now on
+    pub fn with_balance_watches(mut self, watches: BalanceWatches) -> Self {
+        self.balance_watches = watches;
+        self
+    }
+
+    pub fn balance_watches(&self) -> &BalanceWatches {
+        &self.balance_watches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{
+        builders::TestTxBuilder, data::AddressDataValue, ConfigBuilder, LedgerBuilder, TestGen,
+    };
+    use chain_addr::Discrimination;
+    use chain_core::property::{Deserialize as _, Serialize as _};
+
+    #[test]
+    fn crossed_thresholds_are_reported_in_the_block_events() {
+        let alice = AddressDataValue::account(Discrimination::Test, Value(1_000));
+        let bob = AddressDataValue::account(Discrimination::Test, Value(0));
+        let leader_pair = TestGen::leader_pair();
+        let mut test_ledger =
+            LedgerBuilder::from_config(ConfigBuilder::new().with_leaders(&[leader_pair.id()]))
+                .faucet(&alice)
+                .build()
+                .unwrap();
+        let watches = BalanceWatches::new()
+            .watch(alice.to_id(), Value(900))
+            .watch(alice.to_id(), Value(500))
+            .watch(alice.to_id(), Value(100))
+            .watch(bob.to_id(), Value(100))
+            .watch(bob.to_id(), Value(2_000));
+        test_ledger.ledger = test_ledger.ledger.clone().with_balance_watches(watches);
+
+        let transfer = TestTxBuilder::new(test_ledger.block0_hash)
+            .move_funds(&mut test_ledger, &alice, &bob, Value(600))
+            .get_fragment();
+        let block = test_ledger.forge_bft_block_with_fragments(
+            &leader_pair,
+            test_ledger.date(),
+            vec![transfer],
+        );
+        let events = test_ledger.apply_block_with_events(block).unwrap();
+        let bob_after = balance(&test_ledger.ledger, &bob.to_id());
+
+        let report = |account: &AddressDataValue, threshold, crossing, before, after| {
+            BalanceThresholdReport {
+                account: account.to_id(),
+                threshold: Value(threshold),
+                crossing,
+                before: Value(before),
+                after,
+            }
+        };
+        let mut expected = vec![
+            report(&alice, 900, ThresholdCrossing::Falling, 1_000, Value(400)),
+            report(&alice, 500, ThresholdCrossing::Falling, 1_000, Value(400)),
+            report(&bob, 100, ThresholdCrossing::Rising, 0, bob_after),
+        ];
+        expected.sort_by(|a, b| a.account.cmp(&b.account));
+        let reports: Vec<_> = events.balance_crossings().cloned().collect();
+        assert_eq!(reports, expected);
+        assert!(events.block_reward().is_some());
+
+        // without a crossing, nothing is reported
+        let block = test_ledger.forge_bft_block_with_fragments(
+            &leader_pair,
+            test_ledger.date(),
+            Vec::new(),
+        );
+        let events = test_ledger.apply_block_with_events(block).unwrap();
+        assert_eq!(events.balance_crossings().count(), 0);
+    }
+
+    #[test]
+    fn watches_are_not_part_of_the_ledger_state() {
+        let ledger: Ledger = LedgerBuilder::from_config(ConfigBuilder::new())
+            .faucet_value(Value(42000))
+            .build()
+            .unwrap()
+            .into();
+        let watched = ledger
+            .clone()
+            .with_balance_watches(BalanceWatches::new().watch(TestGen::identifier(), Value(100)));
+        assert_eq!(watched, ledger);
+
+        let bytes = watched.serialize_as_vec().unwrap();
+        let decoded = Ledger::deserialize(bytes.as_slice()).unwrap();
+        assert_eq!(decoded, watched);
+    }
+}
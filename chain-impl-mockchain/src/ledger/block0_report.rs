@@ -0,0 +1,328 @@
+//! Validation report of the initial configuration of a block0.
+//!
+//! The report compares the parameters of the initial fragment with the
+//! defaults of [`Settings::new`], listing the parameters explicitly set, the
+//! required or recommended parameters which are missing and the values which
+//! are valid but most likely a mistake. It is meant for the tools creating
+//! genesis files, to be checked before launching a chain.
+
+use crate::{
+    chaintypes::ConsensusType,
+    config::{ConfigParam, Tag},
+    fragment::ConfigParams,
+    milli::Milli,
+    setting::{FeesGoesTo, Settings},
+};
+use std::{
+    fmt,
+    num::{NonZeroU32, NonZeroU64},
+};
+
+/// How an explicitly set parameter compares with its default value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefaultComparison {
+    /// the parameter has no default value: it is required, or it adds to
+    /// the state instead of replacing a setting
+    NoDefault,
+    SameAsDefault,
+    DiffersFromDefault,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExplicitSetting {
+    pub tag: Tag,
+    pub comparison: DefaultComparison,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MissingParameter {
+    /// the block0 is rejected without this parameter
+    Required(Tag),
+    /// the default value is used, which is not suitable for most chains
+    Recommended(Tag),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SuspiciousValue {
+    ZeroSlotDuration,
+    ZeroSlotsPerEpoch,
+    ZeroKesUpdateSpeed,
+    ZeroBlockContentMaxSize,
+    /// neither the constant nor the coefficient of the linear fee is set
+    ZeroFees,
+    InvalidActiveSlotsCoeff(Milli),
+    /// a parameter which can be set only once is set multiple times
+    Duplicated(Tag),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block0ConfigReport {
+    pub explicit: Vec<ExplicitSetting>,
+    pub missing: Vec<MissingParameter>,
+    pub suspicious: Vec<SuspiciousValue>,
+}
+
+const REQUIRED: [Tag; 5] = [
+    Tag::Block0Date,
+    Tag::Discrimination,
+    Tag::SlotDuration,
+    Tag::SlotsPerEpoch,
+    Tag::KesUpdateSpeed,
+];
+
+const SINGLE_VALUED: [Tag; 8] = [
+    Tag::Block0Date,
+    Tag::Discrimination,
+    Tag::ConsensusVersion,
+    Tag::SlotDuration,
+    Tag::SlotsPerEpoch,
+    Tag::EpochStabilityDepth,
+    Tag::ConsensusGenesisPraosActiveSlotsCoeff,
+    Tag::TotalSupply,
+];
+
+fn compare<T: PartialEq>(value: &T, default: &T) -> DefaultComparison {
+    if value == default {
+        DefaultComparison::SameAsDefault
+    } else {
+        DefaultComparison::DiffersFromDefault
+    }
+}
+
+fn default_comparison(param: &ConfigParam, defaults: &Settings) -> DefaultComparison {
+    match param {
+        ConfigParam::ConsensusVersion(version) => compare(version, &defaults.consensus_version),
+        ConfigParam::EpochStabilityDepth(depth) => compare(depth, &defaults.epoch_stability_depth),
+        ConfigParam::ConsensusGenesisPraosActiveSlotsCoeff(coeff) => {
+            compare(coeff, &Milli::from(defaults.active_slots_coeff))
+        }
+        ConfigParam::BlockContentMaxSize(size) => compare(size, &defaults.block_content_max_size),
+        ConfigParam::LinearFee(fee) => compare(fee, &defaults.linear_fees),
+        ConfigParam::ProposalExpiration(expiration) => {
+            compare(expiration, &defaults.proposal_expiration)
+        }
+        ConfigParam::PerCertificateFees(fees) => {
+            compare(fees, &defaults.linear_fees.per_certificate_fees)
+        }
+        ConfigParam::PerVoteCertificateFees(fees) => {
+            compare(fees, &defaults.linear_fees.per_vote_certificate_fees)
+        }
+        ConfigParam::PerCertificateByteFee(fee) => compare(
+            &NonZeroU64::new(*fee),
+            &defaults.linear_fees.per_certificate_byte_fee,
+        ),
+        ConfigParam::FeesInTreasury(in_treasury) => compare(
+            in_treasury,
+            &(defaults.fees_goes_to == FeesGoesTo::Treasury),
+        ),
+        ConfigParam::RewardLimitNone => DefaultComparison::SameAsDefault,
+        ConfigParam::TransactionMaxExpiryEpochs(epochs) => {
+            compare(epochs, &defaults.transaction_max_expiry_epochs)
+        }
+        ConfigParam::MinimumPoolPledge(pledge) => compare(pledge, &defaults.minimum_pool_pledge),
+        ConfigParam::BftQuorum(quorum) => compare(quorum, &defaults.bft_quorum),
+        ConfigParam::ProtocolVersion(version) => compare(version, &defaults.protocol_version),
+        ConfigParam::CertificatePolicy(policy) => compare(policy, &defaults.certificate_policy),
+        ConfigParam::MaxVotePlansPerEpoch(limit) => {
+            compare(&NonZeroU32::new(*limit), &defaults.max_vote_plans_per_epoch)
+        }
+        ConfigParam::MaxProposalsPerVotePlan(limit) => compare(
+            &NonZeroU32::new(*limit),
+            &defaults.max_proposals_per_vote_plan,
+        ),
+        ConfigParam::PoolPerformanceRewards(enabled) => {
+            compare(enabled, &defaults.pool_performance_rewards)
+        }
+        ConfigParam::ActiveSlotsCoeffTransition(slots) => {
+            compare(slots, &defaults.active_slots_coeff_transition)
+        }
+        _ => DefaultComparison::NoDefault,
+    }
+}
+
+impl Block0ConfigReport {
+    pub fn new(params: &ConfigParams) -> Self {
+        let defaults = Settings::new();
+        let mut explicit = Vec::new();
+        let mut suspicious = Vec::new();
+        let mut consensus_version = defaults.consensus_version;
+        let mut has_bft_leader = false;
+        let mut linear_fee = defaults.linear_fees;
+
+        for param in params.iter() {
+            let tag = Tag::from(param);
+            explicit.push(ExplicitSetting {
+                tag,
+                comparison: default_comparison(param, &defaults),
+            });
+
+            match param {
+                ConfigParam::ConsensusVersion(version) => consensus_version = *version,
+                ConfigParam::AddBftLeader(_) => has_bft_leader = true,
+                ConfigParam::LinearFee(fee) => linear_fee = *fee,
+                ConfigParam::SlotDuration(0) => suspicious.push(SuspiciousValue::ZeroSlotDuration),
+                ConfigParam::SlotsPerEpoch(0) => {
+                    suspicious.push(SuspiciousValue::ZeroSlotsPerEpoch)
+                }
+                ConfigParam::KesUpdateSpeed(0) => {
+                    suspicious.push(SuspiciousValue::ZeroKesUpdateSpeed)
+                }
+                ConfigParam::BlockContentMaxSize(0) => {
+                    suspicious.push(SuspiciousValue::ZeroBlockContentMaxSize)
+                }
+                ConfigParam::ConsensusGenesisPraosActiveSlotsCoeff(coeff)
+                    if *coeff == Milli::ZERO || *coeff > Milli::ONE =>
+                {
+                    suspicious.push(SuspiciousValue::InvalidActiveSlotsCoeff(*coeff))
+                }
+                _ => (),
+            }
+        }
+
+        let is_set = |tag: Tag| explicit.iter().any(|setting| setting.tag == tag);
+
+        for tag in SINGLE_VALUED {
+            if explicit.iter().filter(|setting| setting.tag == tag).count() > 1 {
+                suspicious.push(SuspiciousValue::Duplicated(tag));
+            }
+        }
+        if linear_fee.constant == 0 && linear_fee.coefficient == 0 {
+            suspicious.push(SuspiciousValue::ZeroFees);
+        }
+
+        let mut missing: Vec<_> = REQUIRED
+            .into_iter()
+            .filter(|tag| !is_set(*tag))
+            .map(MissingParameter::Required)
+            .collect();
+        if !has_bft_leader {
+            missing.push(MissingParameter::Required(Tag::AddBftLeader));
+        }
+
+        let mut recommended = vec![Tag::ConsensusVersion, Tag::LinearFee];
+        if consensus_version == ConsensusType::GenesisPraos {
+            recommended.extend([
+                Tag::ConsensusGenesisPraosActiveSlotsCoeff,
+                Tag::EpochStabilityDepth,
+                Tag::RewardParams,
+                Tag::TreasuryParams,
+            ]);
+        }
+        missing.extend(
+            recommended
+                .into_iter()
+                .filter(|tag| !is_set(*tag))
+                .map(MissingParameter::Recommended),
+        );
+
+        Block0ConfigReport {
+            explicit,
+            missing,
+            suspicious,
+        }
+    }
+
+    /// whether the block0 can be applied, i.e. no required parameter is missing
+    pub fn has_required_parameters(&self) -> bool {
+        !self
+            .missing
+            .iter()
+            .any(|missing| matches!(missing, MissingParameter::Required(_)))
+    }
+
+    /// whether nothing is missing and no value is suspicious
+    pub fn is_clean(&self) -> bool {
+        self.missing.is_empty() && self.suspicious.is_empty()
+    }
+}
+
+impl fmt::Display for MissingParameter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MissingParameter::Required(tag) => write!(f, "missing required {}", tag.as_ref()),
+            MissingParameter::Recommended(tag) => {
+                write!(f, "missing recommended {}", tag.as_ref())
+            }
+        }
+    }
+}
+
+impl fmt::Display for SuspiciousValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SuspiciousValue::ZeroSlotDuration => write!(f, "slot duration is zero"),
+            SuspiciousValue::ZeroSlotsPerEpoch => write!(f, "number of slots per epoch is zero"),
+            SuspiciousValue::ZeroKesUpdateSpeed => write!(f, "KES update speed is zero"),
+            SuspiciousValue::ZeroBlockContentMaxSize => {
+                write!(f, "block content maximum size is zero")
+            }
+            SuspiciousValue::ZeroFees => write!(f, "transactions have no fees"),
+            SuspiciousValue::InvalidActiveSlotsCoeff(coeff) => {
+                write!(f, "active slots coefficient {} is not in (0,1]", coeff)
+            }
+            SuspiciousValue::Duplicated(tag) => write!(f, "{} is set multiple times", tag.as_ref()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fee::LinearFee;
+    use crate::testing::ledger::ConfigBuilder;
+
+    #[test]
+    pub fn default_test_config_report() {
+        let mut config = ConfigBuilder::new().with_fee(LinearFee::new(1, 1, 1));
+        config.normalize();
+        let report = Block0ConfigReport::new(&config.build());
+
+        assert!(report.has_required_parameters());
+        assert!(report.is_clean(), "{:?}", report);
+        assert!(report.explicit.contains(&ExplicitSetting {
+            tag: Tag::ConsensusGenesisPraosActiveSlotsCoeff,
+            comparison: DefaultComparison::SameAsDefault,
+        }));
+        assert!(report.explicit.contains(&ExplicitSetting {
+            tag: Tag::LinearFee,
+            comparison: DefaultComparison::DiffersFromDefault,
+        }));
+    }
+
+    #[test]
+    pub fn missing_and_suspicious_parameters_are_reported() {
+        let mut params = ConfigParams::new();
+        params.push(ConfigParam::ConsensusVersion(ConsensusType::GenesisPraos));
+        params.push(ConfigParam::SlotDuration(0));
+        params.push(ConfigParam::SlotDuration(10));
+        params.push(ConfigParam::ConsensusGenesisPraosActiveSlotsCoeff(
+            Milli::ZERO,
+        ));
+        let report = Block0ConfigReport::new(&params);
+
+        assert!(!report.has_required_parameters());
+        assert_eq!(
+            report.missing,
+            vec![
+                MissingParameter::Required(Tag::Block0Date),
+                MissingParameter::Required(Tag::Discrimination),
+                MissingParameter::Required(Tag::SlotsPerEpoch),
+                MissingParameter::Required(Tag::KesUpdateSpeed),
+                MissingParameter::Required(Tag::AddBftLeader),
+                MissingParameter::Recommended(Tag::LinearFee),
+                MissingParameter::Recommended(Tag::EpochStabilityDepth),
+                MissingParameter::Recommended(Tag::RewardParams),
+                MissingParameter::Recommended(Tag::TreasuryParams),
+            ]
+        );
+        assert_eq!(
+            report.suspicious,
+            vec![
+                SuspiciousValue::ZeroSlotDuration,
+                SuspiciousValue::InvalidActiveSlotsCoeff(Milli::ZERO),
+                SuspiciousValue::Duplicated(Tag::SlotDuration),
+                SuspiciousValue::ZeroFees,
+            ]
+        );
+    }
+}
@@ -0,0 +1,158 @@
+//! Validation of the value created by a block0.
+//!
+//! The block0 creates all the value of a chain: the treasury, the reward pot
+//! and the initial funds of its transactions and old UTxO declarations. When
+//! the initial fragment declares the total supply of the chain, the pots need
+//! to fit in it, and the initial funds in what is left of it, the circulating
+//! supply. Without a declared supply only the overflows are rejected.
+//!
+//! The validation runs when the ledger is created from the block0, and can
+//! be run on the fragments of a block0 before it is built.
+
+use super::ledger::Block0Error;
+use crate::config::ConfigParam;
+use crate::fragment::Fragment;
+use crate::value::Value;
+
+/// The value created by a block0
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Block0Supply {
+    /// the supply declared in the initial fragment
+    pub total_supply: Option<Value>,
+    pub treasury: Value,
+    pub rewards: Value,
+    pub initial_funds: Value,
+}
+
+impl Block0Supply {
+    /// Collect the value created by the fragments of a block0, without
+    /// applying them
+    pub fn from_fragments<'a, I>(contents: I) -> Result<Self, Block0Error>
+    where
+        I: IntoIterator<Item = &'a Fragment>,
+    {
+        let mut supply = Block0Supply {
+            total_supply: None,
+            treasury: Value::zero(),
+            rewards: Value::zero(),
+            initial_funds: Value::zero(),
+        };
+        let mut funds = Vec::new();
+        for content in contents {
+            match content {
+                Fragment::Initial(init_ents) => {
+                    for param in init_ents.iter() {
+                        match param {
+                            ConfigParam::TotalSupply(v) => supply.total_supply = Some(*v),
+                            ConfigParam::TreasuryAdd(v) => supply.treasury = *v,
+                            ConfigParam::RewardPot(v) => supply.rewards = *v,
+                            _ => (),
+                        }
+                    }
+                }
+                Fragment::OldUtxoDeclaration(old) => {
+                    funds.extend(old.addrs.iter().map(|(_, value)| *value));
+                }
+                Fragment::Transaction(tx) => {
+                    funds.extend(tx.as_slice().outputs().iter().map(|output| output.value));
+                }
+                _ => (),
+            }
+        }
+        supply.initial_funds =
+            Value::sum(funds.into_iter()).map_err(|_| Block0Error::UtxoTotalValueTooBig)?;
+        Ok(supply)
+    }
+
+    /// the value of the treasury and the reward pot together
+    pub fn pots(&self) -> Result<Value, Block0Error> {
+        (self.treasury + self.rewards).map_err(|_| Block0Error::PotsTotalValueTooBig)
+    }
+
+    /// what is left of the declared supply once the pots are filled, `None`
+    /// if no supply is declared
+    pub fn circulating_supply(&self) -> Result<Option<Value>, Block0Error> {
+        let total_supply = match self.total_supply {
+            None => return Ok(None),
+            Some(total_supply) => total_supply,
+        };
+        (total_supply - self.pots()?)
+            .map(Some)
+            .map_err(|_| Block0Error::PotsExceedTotalSupply {
+                treasury: self.treasury,
+                rewards: self.rewards,
+                total_supply,
+            })
+    }
+
+    pub fn validate(&self) -> Result<(), Block0Error> {
+        let pots = self.pots()?;
+        (pots + self.initial_funds).map_err(|_| Block0Error::UtxoTotalValueTooBig)?;
+        match self.circulating_supply()? {
+            Some(circulating_supply) if self.initial_funds > circulating_supply => {
+                Err(Block0Error::InitialFundsExceedCirculatingSupply {
+                    initial_funds: self.initial_funds,
+                    circulating_supply,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn supply(total_supply: Option<u64>, treasury: u64, rewards: u64, funds: u64) -> Block0Supply {
+        Block0Supply {
+            total_supply: total_supply.map(Value),
+            treasury: Value(treasury),
+            rewards: Value(rewards),
+            initial_funds: Value(funds),
+        }
+    }
+
+    #[test]
+    fn supply_is_optional() {
+        assert_eq!(supply(None, 100, 100, 1_000).validate(), Ok(()));
+        assert_eq!(
+            supply(None, u64::MAX, 1, 0).validate(),
+            Err(Block0Error::PotsTotalValueTooBig)
+        );
+        assert_eq!(
+            supply(None, u64::MAX - 1, 1, 1).validate(),
+            Err(Block0Error::UtxoTotalValueTooBig)
+        );
+    }
+
+    #[test]
+    fn supply_can_be_fully_allocated() {
+        let supply = supply(Some(1_200), 100, 100, 1_000);
+        assert_eq!(supply.circulating_supply(), Ok(Some(Value(1_000))));
+        assert_eq!(supply.validate(), Ok(()));
+    }
+
+    #[test]
+    fn pots_exceeding_the_supply_are_rejected() {
+        assert_eq!(
+            supply(Some(150), 100, 100, 0).validate(),
+            Err(Block0Error::PotsExceedTotalSupply {
+                treasury: Value(100),
+                rewards: Value(100),
+                total_supply: Value(150),
+            })
+        );
+    }
+
+    #[test]
+    fn funds_exceeding_the_circulating_supply_are_rejected() {
+        assert_eq!(
+            supply(Some(1_000), 100, 100, 1_000).validate(),
+            Err(Block0Error::InitialFundsExceedCirculatingSupply {
+                initial_funds: Value(1_000),
+                circulating_supply: Value(800),
+            })
+        );
+    }
+}
@@ -4,7 +4,7 @@ use crate::date::BlockDate;
 use crate::setting;
 use crate::transaction::*;
 use crate::value::Value;
-use chain_addr::Address;
+use chain_addr::{Address, Discrimination, Kind};
 use thiserror::Error;
 
 pub const CHECK_TX_MAXIMUM_INPUTS: u8 = 255;
@@ -78,7 +78,7 @@ pub(super) fn valid_stake_owner_delegation_transaction(
 /// check that the transaction input/outputs/witnesses is valid for the ballot
 ///
 /// * Only 1 input (subsequently 1 witness), no output
-pub(super) fn valid_vote_cast(tx: &TransactionSlice<certificate::VoteCast>) -> LedgerCheck {
+pub(super) fn valid_vote_cast<P: Payload>(tx: &TransactionSlice<P>) -> LedgerCheck {
     if_cond_fail_with!(
         tx.inputs().nb_inputs() != 1
             || tx.witnesses().nb_witnesses() != 1
@@ -87,6 +87,20 @@ pub(super) fn valid_vote_cast(tx: &TransactionSlice<certificate::VoteCast>) -> L
     )
 }
 
+/// check that the transaction input/outputs/witnesses is valid for a name claim or release
+///
+/// * Only 1 input (subsequently 1 witness), no output
+pub(super) fn valid_name_certificate_transaction<P: Payload>(
+    tx: &TransactionSlice<P>,
+) -> LedgerCheck {
+    if_cond_fail_with!(
+        tx.inputs().nb_inputs() != 1
+            || tx.witnesses().nb_witnesses() != 1
+            || tx.outputs().nb_outputs() != 0,
+        Error::NameCertificateInvalidTransaction
+    )
+}
+
 /// check that the pool registration certificate is valid
 ///
 /// * management threshold T is valid: 0 < T <= #owners
@@ -117,6 +131,41 @@ pub(super) fn valid_pool_registration_certificate(
     Ok(())
 }
 
+/// check that the pledge of the pool is at least the minimum pledge
+/// the designated reward address needs to be of the chain and able to
+/// receive the rewards, a script cannot
+pub(super) fn valid_pool_reward_account(
+    auth_cert: &certificate::PoolRegistration,
+    discrimination: Discrimination,
+) -> LedgerCheck {
+    if let Some(certificate::RewardAccount::Address(address)) = &auth_cert.reward_account {
+        if_cond_fail_with!(
+            address.discrimination() != discrimination,
+            Error::PoolRegistrationRewardAddressInvalidDiscrimination
+        )?;
+        if_cond_fail_with!(
+            matches!(address.kind(), Kind::Script(_)),
+            Error::PoolRegistrationRewardAddressInvalidKind {
+                kind: address.to_kind_type()
+            }
+        )?;
+    }
+    Ok(())
+}
+
+pub(super) fn valid_pool_pledge(
+    auth_cert: &certificate::PoolRegistration,
+    minimum_pool_pledge: Value,
+) -> LedgerCheck {
+    if_cond_fail_with!(
+        auth_cert.pledge < minimum_pool_pledge,
+        Error::PoolRegistrationPledgeBelowMinimum {
+            pledge: auth_cert.pledge,
+            minimum: minimum_pool_pledge,
+        }
+    )
+}
+
 pub(super) fn valid_pool_owner_signature(pos: &certificate::PoolOwnersSigned) -> LedgerCheck {
     if_cond_fail_with!(
         pos.signatures.is_empty(),
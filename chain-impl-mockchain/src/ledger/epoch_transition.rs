@@ -0,0 +1,200 @@
+//! State transitions at the switch to a new epoch.
+//!
+//! When the first block of an epoch is applied, the ledger goes through the
+//! stages of [`EpochTransitionStage::ALL`], in this order:
+//!
+//! 1. [`DistributeRewards`](EpochTransitionStage::DistributeRewards): the
+//!    rewards of the epoch being left are distributed by
+//!    [`Ledger::distribute_rewards`], which needs the stake distribution and
+//!    is called before the block is applied. The stage only checks it was.
+//! 2. [`RecordSettings`](EpochTransitionStage::RecordSettings): the settings
+//!    in force during the epoch being left are kept in the settings history.
+//! 3. [`ProcessUpdateProposals`](EpochTransitionStage::ProcessUpdateProposals):
+//!    the accepted update proposals are applied to the settings, in the order
+//!    of their proposal date then of their id, and the expired ones are
+//!    dropped.
+//! 4. [`ExpireNames`](EpochTransitionStage::ExpireNames): the names expired
+//!    at the start of the new epoch are forgotten.
+//!
+//! The settings need to be recorded before the update proposals change them,
+//! and the rewards are distributed with the settings of the epoch being left.
+//! The expiry of the names only depends on the records of the names, so this
+//! stage can be applied at any point of the transition.
+//!
+//! The pool retirements and the end of the vote plans are not part of the
+//! transition: a retirement is effective as soon as its certificate is
+//! applied, and the dates of a vote plan are checked against the date of the
+//! fragments applied to it.
+//!
+//! An [`EpochTransitionReport`] is emitted in the events of the block after
+//! each stage.
+
+use super::ledger::Ledger;
+use crate::date::{BlockDate, Epoch};
+
+/// A stage of the transition to a new epoch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EpochTransitionStage {
+    DistributeRewards,
+    RecordSettings,
+    ProcessUpdateProposals,
+    ExpireNames,
+}
+
+/// A stage of the transition applied to the ledger, emitted as an event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EpochTransitionReport {
+    pub stage: EpochTransitionStage,
+    /// the epoch being left
+    pub from: Epoch,
+    /// the epoch of the block triggering the transition
+    pub to: Epoch,
+}
+
+impl EpochTransitionStage {
+    /// all the stages, in the order they are applied
+    pub const ALL: [EpochTransitionStage; 4] = [
+        EpochTransitionStage::DistributeRewards,
+        EpochTransitionStage::RecordSettings,
+        EpochTransitionStage::ProcessUpdateProposals,
+        EpochTransitionStage::ExpireNames,
+    ];
+}
+
+impl Ledger {
+    /// Apply a stage of the transition from the date of the ledger to the
+    /// epoch of `new_date`
+    pub(super) fn apply_epoch_transition_stage(
+        mut self,
+        stage: EpochTransitionStage,
+        new_date: BlockDate,
+    ) -> Self {
+        match stage {
+            EpochTransitionStage::DistributeRewards => {
+                if self.leaders_log.total() > 0 {
+                    panic!("internal error: apply_block called after epoch transition, but distribute_rewards has not been called")
+                }
+            }
+            EpochTransitionStage::RecordSettings => {
+                self.settings_history =
+                    self.settings_history
+                        .record(self.date.epoch, &self.settings, new_date.epoch);
+            }
+            EpochTransitionStage::ProcessUpdateProposals => {
+                let (updates, settings) =
+                    self.updates
+                        .process_proposals(self.settings, self.date, new_date);
+                self.updates = updates;
+                self.settings = settings;
+            }
+            EpochTransitionStage::ExpireNames => {
+                self.names = self.names.remove_expired(new_date.epoch);
+            }
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{
+        arbitrary::update_proposal::UpdateProposalData, ConfigBuilder, LedgerBuilder, TestGen,
+    };
+    use quickcheck::TestResult;
+    use quickcheck_macros::quickcheck;
+
+    fn apply_stages(
+        ledger: &Ledger,
+        stages: &[EpochTransitionStage],
+        new_date: BlockDate,
+    ) -> Ledger {
+        stages.iter().fold(ledger.clone(), |ledger, stage| {
+            ledger.apply_epoch_transition_stage(*stage, new_date)
+        })
+    }
+
+    #[quickcheck]
+    fn names_expiry_is_order_independent(ledger: Ledger, epochs: u8) -> TestResult {
+        let new_date = match ledger.date.checked_add_epochs(1 + Epoch::from(epochs)) {
+            Some(new_date) => new_date,
+            None => return TestResult::discard(),
+        };
+        let expected = apply_stages(&ledger, &EpochTransitionStage::ALL, new_date);
+        for position in 0..EpochTransitionStage::ALL.len() {
+            let mut stages: Vec<_> = EpochTransitionStage::ALL
+                .iter()
+                .copied()
+                .filter(|stage| *stage != EpochTransitionStage::ExpireNames)
+                .collect();
+            stages.insert(position, EpochTransitionStage::ExpireNames);
+            if apply_stages(&ledger, &stages, new_date) != expected {
+                return TestResult::error(format!("stages {:?} diverge", stages));
+            }
+        }
+        TestResult::passed()
+    }
+
+    #[quickcheck]
+    fn settings_are_recorded_before_the_update_proposals(
+        update_proposal_data: UpdateProposalData,
+    ) -> TestResult {
+        let config = ConfigBuilder::new().with_leaders(&update_proposal_data.leaders_ids());
+        let mut ledger: Ledger = LedgerBuilder::from_config(config)
+            .build()
+            .expect("cannot build test ledger")
+            .into();
+        let proposal_id = TestGen::hash();
+        ledger.updates = ledger
+            .updates
+            .apply_proposal(
+                proposal_id,
+                update_proposal_data.proposal.clone(),
+                &ledger.settings,
+                ledger.date,
+            )
+            .unwrap();
+        for vote in update_proposal_data.gen_votes(proposal_id) {
+            ledger.updates = ledger.updates.apply_vote(&vote, &ledger.settings).unwrap();
+        }
+
+        let settings_before = ledger.settings.clone();
+        let new_date = ledger.date.next_epoch();
+        let ledger = apply_stages(&ledger, &EpochTransitionStage::ALL, new_date);
+
+        assert!(ledger.updates.proposals.is_empty());
+        TestResult::from_bool(
+            ledger.settings_history.get(new_date.epoch - 1) == Some(&settings_before),
+        )
+    }
+
+    #[test]
+    fn epoch_transition_stages_are_reported_in_order() {
+        let leader_pair = TestGen::leader_pair();
+        let mut test_ledger =
+            LedgerBuilder::from_config(ConfigBuilder::new().with_leaders(&[leader_pair.id()]))
+                .build()
+                .unwrap();
+        let from = test_ledger.date().epoch;
+        let block = test_ledger.forge_bft_block_with_fragments(
+            &leader_pair,
+            test_ledger.date().next_epoch(),
+            Vec::new(),
+        );
+        let events = test_ledger.apply_block_with_events(block).unwrap();
+
+        let stages: Vec<_> = events.epoch_transitions().copied().collect();
+        assert_eq!(
+            stages,
+            EpochTransitionStage::ALL
+                .iter()
+                .map(|stage| EpochTransitionReport {
+                    stage: *stage,
+                    from,
+                    to: from + 1,
+                })
+                .collect::<Vec<_>>()
+        );
+        assert!(events.block_reward().is_some());
+    }
+}
@@ -0,0 +1,246 @@
+//! Events emitted by the ledger while applying a block or distributing the
+//! rewards of an epoch.
+//!
+//! Events are derived information: they are not part of the ledger state and
+//! are not required to validate the chain, but they make explicit what happened
+//! during the application of a block for consumers like explorers or wallets.
+
+use super::balance_watch::BalanceThresholdReport;
+use super::epoch_transition::EpochTransitionReport;
+use super::finality::FinalityReport;
+use super::ledger::LedgerParameters;
+use super::parameter_sanity::ParameterSanityReport;
+use super::reward_info::PledgeNotMet;
+use crate::certificate::{ExternalProposalId, PoolId, RewardAccount, VotePlanId};
+use crate::chaineval::ConsensusEvalContext;
+use crate::chaintypes::ChainLength;
+use crate::date::BlockDate;
+use crate::setting::FeesGoesTo;
+use crate::stake::PoolPerformance;
+use crate::value::Value;
+
+/// A single event emitted during the application of a block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LedgerEvent {
+    BlockReward(BlockRewardSummary),
+    PoolPerformance(PoolPerformanceReport),
+    EpochTransition(EpochTransitionReport),
+    Finality(FinalityReport),
+    RewardAccountFallback(RewardAccountFallbackReport),
+    PledgeForfeit(PledgeForfeitReport),
+    BalanceThreshold(BalanceThresholdReport),
+    VotePlanCancelled(VotePlanCancelReport),
+    ParameterWarnings(ParameterSanityReport),
+}
+
+/// Ordered list of the events emitted during the application of a block or
+/// the distribution of the rewards
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LedgerEvents(Vec<LedgerEvent>);
+
+impl LedgerEvents {
+    pub fn new() -> Self {
+        LedgerEvents(Vec::new())
+    }
+
+    pub fn push(&mut self, event: LedgerEvent) {
+        self.0.push(event)
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, LedgerEvent> {
+        self.0.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// the reward summary of the block, every applied block emits exactly one
+    pub fn block_reward(&self) -> Option<&BlockRewardSummary> {
+        self.iter().find_map(|event| match event {
+            LedgerEvent::BlockReward(summary) => Some(summary),
+            _ => None,
+        })
+    }
+
+    /// the performance of the pools over the epoch, emitted when the rewards
+    /// are distributed
+    pub fn pool_performances(&self) -> impl Iterator<Item = &PoolPerformanceReport> {
+        self.iter().filter_map(|event| match event {
+            LedgerEvent::PoolPerformance(report) => Some(report),
+            _ => None,
+        })
+    }
+
+    /// the stages of the epoch transition, emitted by the first block of an
+    /// epoch in the order they are applied
+    pub fn epoch_transitions(&self) -> impl Iterator<Item = &EpochTransitionReport> {
+        self.iter().filter_map(|event| match event {
+            LedgerEvent::EpochTransition(report) => Some(report),
+            _ => None,
+        })
+    }
+
+    /// the rewards of the pools which could not be paid to their reward
+    /// account, emitted when the rewards are distributed
+    pub fn reward_account_fallbacks(&self) -> impl Iterator<Item = &RewardAccountFallbackReport> {
+        self.iter().filter_map(|event| match event {
+            LedgerEvent::RewardAccountFallback(report) => Some(report),
+            _ => None,
+        })
+    }
+
+    /// the rewards forfeited by the pools whose owners' stake is below the
+    /// pledge, emitted when the rewards are distributed
+    pub fn pledge_forfeits(&self) -> impl Iterator<Item = &PledgeForfeitReport> {
+        self.iter().filter_map(|event| match event {
+            LedgerEvent::PledgeForfeit(report) => Some(report),
+            _ => None,
+        })
+    }
+
+    /// the thresholds crossed by the balances of the watched accounts, see
+    /// [`BalanceWatches`](super::BalanceWatches)
+    pub fn balance_crossings(&self) -> impl Iterator<Item = &BalanceThresholdReport> {
+        self.iter().filter_map(|event| match event {
+            LedgerEvent::BalanceThreshold(report) => Some(report),
+            _ => None,
+        })
+    }
+
+    /// the vote plans cancelled by the `VotePlanCancel` certificates of the
+    /// block, in the order of the fragments
+    pub fn vote_plan_cancellations(&self) -> impl Iterator<Item = &VotePlanCancelReport> {
+        self.iter().filter_map(|event| match event {
+            LedgerEvent::VotePlanCancelled(report) => Some(report),
+            _ => None,
+        })
+    }
+
+    /// the warnings of the parameters in force from the epoch of the block,
+    /// emitted by the first block of an epoch when its epoch transition
+    /// changed the settings, see [`Ledger::parameter_warnings`]
+    ///
+    /// [`Ledger::parameter_warnings`]: super::Ledger::parameter_warnings
+    pub fn parameter_warnings(&self) -> Option<&ParameterSanityReport> {
+        self.iter().find_map(|event| match event {
+            LedgerEvent::ParameterWarnings(report) => Some(report),
+            _ => None,
+        })
+    }
+
+    /// the blocks made final by the block, emitted when the last final block
+    /// moves forward
+    pub fn finality(&self) -> Option<&FinalityReport> {
+        self.iter().find_map(|event| match event {
+            LedgerEvent::Finality(report) => Some(report),
+            _ => None,
+        })
+    }
+}
+
+impl IntoIterator for LedgerEvents {
+    type Item = LedgerEvent;
+    type IntoIter = std::vec::IntoIter<LedgerEvent>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+/// Coinbase-like record of the value collected by a block.
+///
+/// The fees are not transfered at the time the block is applied: they are
+/// accumulated in the fees pot and distributed at the end of the epoch. This
+/// summary records what the block contributed and how it is going to be split,
+/// following the ledger parameters in force when the block was applied.
+///
+/// Only the ratio part of the treasury tax is attributed to blocks, the fixed
+/// part is levied once per epoch on the total rewards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockRewardSummary {
+    pub chain_length: ChainLength,
+    pub date: BlockDate,
+    /// the stake pool which created the block, None for BFT and genesis blocks
+    pub producer: Option<PoolId>,
+    /// the total of the fees collected from the fragments of the block
+    pub fees: Value,
+    /// the part of the collected fees going to the treasury
+    pub treasury_cut: Value,
+}
+
+impl BlockRewardSummary {
+    pub(super) fn new(
+        ledger_params: &LedgerParameters,
+        chain_length: ChainLength,
+        date: BlockDate,
+        consensus_eval_context: &ConsensusEvalContext,
+        fees: Value,
+    ) -> Self {
+        let producer = match consensus_eval_context {
+            ConsensusEvalContext::Bft | ConsensusEvalContext::Genesis => None,
+            ConsensusEvalContext::Praos { pool_creator, .. } => Some(pool_creator.clone()),
+        };
+        let treasury_cut = match ledger_params.fees_goes_to {
+            FeesGoesTo::Treasury => fees,
+            FeesGoesTo::Rewards => {
+                let ratio = &ledger_params.treasury_tax.ratio;
+                let cut =
+                    (fees.0 as u128 * ratio.numerator as u128) / ratio.denominator.get() as u128;
+                Value(cut as u64)
+            }
+        };
+        BlockRewardSummary {
+            chain_length,
+            date,
+            producer,
+            fees,
+            treasury_cut,
+        }
+    }
+
+    /// the part of the collected fees going to the rewards of the block producer
+    /// and its delegators
+    pub fn rewards(&self) -> Value {
+        self.fees
+            .checked_sub(self.treasury_cut)
+            .unwrap_or_else(|_| Value::zero())
+    }
+}
+
+/// Performance of a pool over the epoch for which the rewards are distributed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolPerformanceReport {
+    pub pool_id: PoolId,
+    pub performance: PoolPerformance,
+}
+
+/// Rewards of the owners of a pool sent to the treasury, the reward account
+/// of the pool being a multisig account which is not declared
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RewardAccountFallbackReport {
+    pub pool_id: PoolId,
+    pub reward_account: RewardAccount,
+    pub value: Value,
+}
+
+/// Rewards of a pool sent to the treasury, the owners' stake being below the
+/// pledge
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PledgeForfeitReport {
+    pub pool_id: PoolId,
+    pub pledge_not_met: PledgeNotMet,
+}
+
+/// Vote plan cancelled by the committee before its tally, its proposals are
+/// never tallied
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VotePlanCancelReport {
+    pub vote_plan: VotePlanId,
+    pub date: BlockDate,
+    pub proposals: Vec<ExternalProposalId>,
+}
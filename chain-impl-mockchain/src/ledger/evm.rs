@@ -1,9 +1,9 @@
 use crate::chaineval::HeaderContentEvalContext;
 use crate::evm::EvmTransaction;
-use crate::ledger::Error;
+use crate::ledger::{EntryStats, Error};
 use chain_evm::{
     machine::{BlockHash, BlockNumber, Config, Environment, VirtualMachine},
-    state::{AccountTrie, Balance, LogsState},
+    state::{AccountTrie, LogsState},
 };
 
 #[derive(Clone, PartialEq, Eq)]
@@ -113,15 +113,15 @@ impl Ledger {
 }
 
 impl Ledger {
-    pub(crate) fn stats(&self) -> String {
+    pub(crate) fn stats(&self) -> EntryStats {
         let Ledger { accounts, .. } = self;
-        let mut count = 0;
-        let mut total = Balance::zero();
-        for (_, account) in accounts {
-            count += 1;
-            total += account.balance;
+        let mut stats = EntryStats::default();
+        for (address, account) in accounts {
+            stats.count += 1;
+            stats.approximate_size +=
+                std::mem::size_of_val(address) + std::mem::size_of_val(account);
         }
-        format!("EVM accounts: #{} Total={:?}", count, total)
+        stats
     }
 
     pub(crate) fn info_eq(&self, other: &Self) -> String {
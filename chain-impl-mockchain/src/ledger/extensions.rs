@@ -0,0 +1,369 @@
+//! Registry of the extension certificates.
+//!
+//! A downstream crate defines its own certificates (the certificates of a
+//! sidechain for example) by implementing [`CertificateExtension`] for each
+//! of them and registering them under a tag of
+//! [`EXTENSION_TAGS`](crate::certificate::EXTENSION_TAGS). The registry is
+//! given to the ledger at construction, see
+//! [`Ledger::new_with_certificate_extensions`].
+//!
+//! When the ledger applies an extension certificate, the transaction
+//! carrying it is applied first (inputs, outputs and fee), then the
+//! extension of the tag decodes the certificate and validates it against the
+//! ledger. A certificate whose tag has no registered extension is rejected.
+//!
+//! Like the address screening hook, the registry is not part of the state of
+//! the ledger: it is not serialized and has to be given again to a restored
+//! ledger with [`Ledger::with_certificate_extensions`]. The state of an
+//! extension, if any, is kept by the extension itself.
+
+use super::ledger::{Error, Ledger};
+use crate::certificate::{ExtensionCertificate, EXTENSION_TAGS};
+use crate::date::BlockDate;
+use crate::fragment::{Fragment, FragmentId};
+use crate::header::HeaderId;
+use crate::transaction::TransactionSlice;
+use chain_core::mempack::{ReadBuf, Readable};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+use thiserror::Error;
+
+/// The reason given by an extension to reject a certificate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtensionRejection {
+    pub reason: String,
+}
+
+impl ExtensionRejection {
+    pub fn new(reason: impl Into<String>) -> Self {
+        ExtensionRejection {
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for ExtensionRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.reason)
+    }
+}
+
+/// What an extension sees of the application of its certificate
+pub struct ExtensionContext<'a> {
+    /// the ledger with the transaction carrying the certificate applied
+    pub ledger: &'a Ledger,
+    pub fragment_id: &'a FragmentId,
+    pub date: BlockDate,
+    /// the transaction carrying the certificate, the data signed by the
+    /// authentication of the certificate is its
+    /// `transaction_binding_auth_data`
+    pub transaction: &'a TransactionSlice<'a, ExtensionCertificate>,
+}
+
+/// A certificate defined outside of this crate, see the module
+/// documentation
+pub trait CertificateExtension: Send + Sync + 'static {
+    /// The certificate, decoded from the payload of the
+    /// [`ExtensionCertificate`]
+    type Certificate: Readable;
+    /// The authentication of the certificate, decoded from the
+    /// [`ExtensionCertificateAuth`](crate::certificate::ExtensionCertificateAuth)
+    type Auth: Readable;
+
+    /// name of the extension, used in the errors
+    fn name(&self) -> &'static str;
+
+    /// Validate the certificate against the ledger, the fragment is rejected
+    /// with the rejection
+    fn apply(
+        &self,
+        context: &ExtensionContext<'_>,
+        certificate: Self::Certificate,
+        auth: Self::Auth,
+    ) -> Result<(), ExtensionRejection>;
+}
+
+/// the extensions with the type of their certificate erased
+trait AnyCertificateExtension: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn apply(
+        &self,
+        context: &ExtensionContext<'_>,
+        certificate: &[u8],
+        auth: &[u8],
+    ) -> Result<(), ExtensionApplyError>;
+}
+
+enum ExtensionApplyError {
+    Malformed,
+    Rejected(ExtensionRejection),
+}
+
+/// decode the whole buffer, like the fragments
+fn decode<T: Readable>(bytes: &[u8]) -> Option<T> {
+    let mut buf = ReadBuf::from(bytes);
+    let decoded = T::read(&mut buf).ok()?;
+    buf.expect_end().ok()?;
+    Some(decoded)
+}
+
+impl<E: CertificateExtension> AnyCertificateExtension for E {
+    fn name(&self) -> &'static str {
+        CertificateExtension::name(self)
+    }
+
+    fn apply(
+        &self,
+        context: &ExtensionContext<'_>,
+        certificate: &[u8],
+        auth: &[u8],
+    ) -> Result<(), ExtensionApplyError> {
+        let certificate = decode(certificate).ok_or(ExtensionApplyError::Malformed)?;
+        let auth = decode(auth).ok_or(ExtensionApplyError::Malformed)?;
+        CertificateExtension::apply(self, context, certificate, auth)
+            .map_err(ExtensionApplyError::Rejected)
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum ExtensionRegistryError {
+    #[error("The tag {tag} is not reserved to the extension certificates")]
+    TagNotReserved { tag: u8 },
+    #[error("The tag {tag} is already registered for the extension '{name}'")]
+    TagAlreadyRegistered { tag: u8, name: &'static str },
+}
+
+/// The extensions of a ledger by certificate tag, empty by default. Two
+/// registries are equal if they register extensions of the same names for
+/// the same tags.
+#[derive(Clone, Default)]
+pub struct CertificateExtensions(Arc<BTreeMap<u8, Arc<dyn AnyCertificateExtension>>>);
+
+impl CertificateExtensions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the extension for the certificates with the `tag`
+    pub fn register<E: CertificateExtension>(
+        mut self,
+        tag: u8,
+        extension: E,
+    ) -> Result<Self, ExtensionRegistryError> {
+        if !EXTENSION_TAGS.contains(&tag) {
+            return Err(ExtensionRegistryError::TagNotReserved { tag });
+        }
+        if let Some(registered) = self.0.get(&tag) {
+            return Err(ExtensionRegistryError::TagAlreadyRegistered {
+                tag,
+                name: registered.name(),
+            });
+        }
+        Arc::make_mut(&mut self.0).insert(tag, Arc::new(extension));
+        Ok(self)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The name of the extension registered for the tag
+    pub fn name(&self, tag: u8) -> Option<&'static str> {
+        self.0.get(&tag).map(|extension| extension.name())
+    }
+
+    /// The registered tags and the names of their extension, by tag
+    pub fn iter(&self) -> impl Iterator<Item = (u8, &'static str)> + '_ {
+        self.0
+            .iter()
+            .map(|(tag, extension)| (*tag, extension.name()))
+    }
+}
+
+impl fmt::Debug for CertificateExtensions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl PartialEq for CertificateExtensions {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for CertificateExtensions {}
+
+impl Ledger {
+    /// Same as [`Ledger::new`], the extension certificates of the block0
+    /// and of all the following blocks being applied with `extensions`
+    pub fn new_with_certificate_extensions<'a, I>(
+        block0_initial_hash: HeaderId,
+        contents: I,
+        extensions: CertificateExtensions,
+    ) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = &'a Fragment>,
+    {
+        Self::new_with(block0_initial_hash, contents, extensions)
+    }
+
+    /// Apply the extension certificates with `extensions` from now on, for
+    /// a ledger restored from its serialization or created without
+    /// extensions
+    pub fn with_certificate_extensions(mut self, extensions: CertificateExtensions) -> Self {
+        self.certificate_extensions = extensions;
+        self
+    }
+
+    pub fn certificate_extensions(&self) -> &CertificateExtensions {
+        &self.certificate_extensions
+    }
+
+    /// Validate the certificate of the transaction with its extension, the
+    /// transaction being already applied on the ledger
+    pub(super) fn apply_extension_certificate(
+        &self,
+        fragment_id: &FragmentId,
+        date: BlockDate,
+        tx: &TransactionSlice<'_, ExtensionCertificate>,
+    ) -> Result<(), Error> {
+        let certificate = tx.payload().into_payload();
+        let tag = certificate.tag();
+        let extension = self
+            .certificate_extensions
+            .0
+            .get(&tag)
+            .ok_or(Error::UnknownCertificateExtension { tag })?;
+        let auth = tx.payload_auth().into_payload_auth();
+        let context = ExtensionContext {
+            ledger: self,
+            fragment_id,
+            date,
+            transaction: tx,
+        };
+        extension
+            .apply(&context, certificate.payload(), &auth.0)
+            .map_err(|error| match error {
+                ExtensionApplyError::Malformed => Error::ExtensionCertificateMalformed {
+                    tag,
+                    name: extension.name(),
+                },
+                ExtensionApplyError::Rejected(rejection) => Error::ExtensionCertificateRejected {
+                    tag,
+                    name: extension.name(),
+                    rejection,
+                },
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::certificate::Certificate;
+    use crate::testing::{builders::TestTxCertBuilder, data::Wallet, ConfigBuilder, LedgerBuilder};
+    use crate::value::Value;
+
+    const TAG: u8 = 0x80;
+
+    /// accepts the non zero certificates
+    struct NonZero;
+
+    impl CertificateExtension for NonZero {
+        type Certificate = u8;
+        type Auth = ();
+
+        fn name(&self) -> &'static str {
+            "non-zero"
+        }
+
+        fn apply(
+            &self,
+            _context: &ExtensionContext<'_>,
+            certificate: u8,
+            _auth: (),
+        ) -> Result<(), ExtensionRejection> {
+            if certificate == 0 {
+                Err(ExtensionRejection::new("zero"))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    #[test]
+    fn register_in_the_reserved_tags_only() {
+        let extensions = CertificateExtensions::new().register(TAG, NonZero).unwrap();
+        assert_eq!(extensions.name(TAG), Some("non-zero"));
+        assert_eq!(
+            extensions.clone().register(TAG, NonZero).unwrap_err(),
+            ExtensionRegistryError::TagAlreadyRegistered {
+                tag: TAG,
+                name: "non-zero"
+            }
+        );
+        assert_eq!(
+            extensions.clone().register(0x7f, NonZero).unwrap_err(),
+            ExtensionRegistryError::TagNotReserved { tag: 0x7f }
+        );
+        assert_eq!(
+            extensions,
+            CertificateExtensions::new().register(TAG, NonZero).unwrap()
+        );
+        assert_ne!(extensions, CertificateExtensions::new());
+    }
+
+    #[test]
+    fn extension_certificates_apply_with_their_extension() {
+        let alice = Wallet::from_value(Value(100));
+        let mut test_ledger = LedgerBuilder::from_config(ConfigBuilder::new())
+            .faucets_wallets(vec![&alice])
+            .build()
+            .unwrap();
+        let extensions = CertificateExtensions::new().register(TAG, NonZero).unwrap();
+        test_ledger.ledger = test_ledger
+            .ledger
+            .clone()
+            .with_certificate_extensions(extensions);
+
+        let fragment = |tag: u8, payload: &[u8]| {
+            let certificate =
+                Certificate::Extension(ExtensionCertificate::new(tag, payload).unwrap());
+            TestTxCertBuilder::new(test_ledger.block0_hash, test_ledger.fee()).make_transaction(
+                test_ledger.date(),
+                &[alice.clone()],
+                &certificate,
+                Default::default(),
+            )
+        };
+        let unknown = fragment(TAG + 1, &[1]);
+        let malformed = fragment(TAG, &[1, 2]);
+        let rejected = fragment(TAG, &[0]);
+        let accepted = fragment(TAG, &[1]);
+
+        let date = test_ledger.date();
+        assert_eq!(
+            test_ledger.apply_fragment(&unknown, date),
+            Err(Error::UnknownCertificateExtension { tag: TAG + 1 })
+        );
+        assert_eq!(
+            test_ledger.apply_fragment(&malformed, date),
+            Err(Error::ExtensionCertificateMalformed {
+                tag: TAG,
+                name: "non-zero"
+            })
+        );
+        assert_eq!(
+            test_ledger.apply_fragment(&rejected, date),
+            Err(Error::ExtensionCertificateRejected {
+                tag: TAG,
+                name: "non-zero",
+                rejection: ExtensionRejection::new("zero"),
+            })
+        );
+        assert_eq!(test_ledger.apply_fragment(&accepted, date), Ok(()));
+    }
+}
@@ -0,0 +1,127 @@
+//! Audit of the fees collected by the fragments of a block.
+//!
+//! The fee of every fragment is computed again from the ledger parameters
+//! given by the caller, usually the ones a transaction builder used, and
+//! compared with the value the ledger actually appended to the fees pot when
+//! applying the fragment. A discrepancy shows the builder and the ledger do
+//! not agree on the fees, for example when an update of the fees was missed.
+
+use super::ledger::{Error, Ledger, LedgerParameters};
+use crate::block::Block;
+use crate::fee::FeeAlgorithm;
+use crate::fragment::{Fragment, FragmentId};
+use crate::setting::Settings;
+use crate::value::Value;
+
+/// The fee of a fragment of the block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FragmentFeeAudit {
+    pub fragment_id: FragmentId,
+    /// the fee computed from the audited parameters
+    pub expected: Value,
+    /// the value added to the fees pot by the application of the fragment
+    pub collected: Value,
+}
+
+impl FragmentFeeAudit {
+    pub fn is_consistent(&self) -> bool {
+        self.expected == self.collected
+    }
+}
+
+/// The fees of all the fragments of a block, in the order of the block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockFeeAudit {
+    pub fragments: Vec<FragmentFeeAudit>,
+}
+
+impl BlockFeeAudit {
+    /// The fragments whose collected fee differs from the expected one
+    pub fn discrepancies(&self) -> impl Iterator<Item = &FragmentFeeAudit> {
+        self.fragments
+            .iter()
+            .filter(|fragment| !fragment.is_consistent())
+    }
+
+    pub fn is_consistent(&self) -> bool {
+        self.discrepancies().next().is_none()
+    }
+}
+
+impl Ledger {
+    /// Apply the fragments of the block on top of the ledger and compare the
+    /// fee collected for each of them with the fee computed from `params`.
+    ///
+    /// The block goes through the same checks of chain length and date as
+    /// when it is applied, and the error of the first fragment the ledger
+    /// rejects is returned.
+    pub fn audit_block_fees(
+        &self,
+        params: &LedgerParameters,
+        block: &Block,
+    ) -> Result<BlockFeeAudit, Error> {
+        let header = block.header();
+        let mut block_ledger = self.begin_block(header.chain_length(), header.block_date())?;
+        let mut fragments = Vec::new();
+        for fragment in block.contents().iter() {
+            let fees_before = block_ledger.ledger().pots.fees_value();
+            let expected = expected_fee(params, &block_ledger.ledger().settings, fragment);
+            block_ledger = block_ledger.apply_fragment(fragment)?;
+            let collected = block_ledger
+                .ledger()
+                .pots
+                .fees_value()
+                .checked_sub(fees_before)
+                .unwrap_or_default();
+            fragments.push(FragmentFeeAudit {
+                fragment_id: fragment.hash(),
+                expected,
+                collected,
+            });
+        }
+        Ok(BlockFeeAudit { fragments })
+    }
+}
+
+/// The fee of the fragment with the fees of the parameters, the fee of the
+/// name claims comes from the name registry of the settings
+fn expected_fee(params: &LedgerParameters, settings: &Settings, fragment: &Fragment) -> Value {
+    let fees = &params.fees;
+    match fragment {
+        Fragment::Initial(_)
+        | Fragment::OldUtxoDeclaration(_)
+        | Fragment::Evm(_)
+        | Fragment::LegacyMigration(_) => Value::zero(),
+        Fragment::Transaction(tx) => fees.calculate_tx(&tx.as_slice()),
+        Fragment::OwnerStakeDelegation(tx) => fees.calculate_tx(&tx.as_slice()),
+        Fragment::StakeDelegation(tx) => fees.calculate_tx(&tx.as_slice()),
+        Fragment::AmountStakeDelegation(tx) => fees.calculate_tx(&tx.as_slice()),
+        Fragment::RewardsRedelegation(tx) => fees.calculate_tx(&tx.as_slice()),
+        Fragment::PoolRegistration(tx) => fees.calculate_tx(&tx.as_slice()),
+        Fragment::PoolRetirement(tx) => fees.calculate_tx(&tx.as_slice()),
+        Fragment::PoolUpdate(tx) => fees.calculate_tx(&tx.as_slice()),
+        Fragment::UpdateProposal(tx) => fees.calculate_tx(&tx.as_slice()),
+        Fragment::UpdateVote(tx) => fees.calculate_tx(&tx.as_slice()),
+        Fragment::VotePlan(tx) => fees.calculate_tx(&tx.as_slice()),
+        Fragment::VoteCast(tx) => fees.calculate_tx(&tx.as_slice()),
+        Fragment::VoteTally(tx) => fees.calculate_tx(&tx.as_slice()),
+        Fragment::EncryptedVoteTally(tx) => fees.calculate_tx(&tx.as_slice()),
+        Fragment::MintToken(tx) => fees.calculate_tx(&tx.as_slice()),
+        Fragment::NameRelease(tx) => fees.calculate_tx(&tx.as_slice()),
+        Fragment::VotingPowerSnapshot(tx) => fees.calculate_tx(&tx.as_slice()),
+        Fragment::SnapshotVoteCast(tx) => fees.calculate_tx(&tx.as_slice()),
+        Fragment::VoteCastBatch(tx) => fees.calculate_tx(&tx.as_slice()),
+        Fragment::ExtensionCertificate(tx) => fees.calculate_tx(&tx.as_slice()),
+        Fragment::VotePlanCancel(tx) => fees.calculate_tx(&tx.as_slice()),
+        Fragment::TransactionBatch(batch) => fees.calculate_batch(batch),
+        Fragment::NameClaim(tx) => {
+            let tx = tx.as_slice();
+            let claim_fee = settings
+                .name_registry
+                .as_ref()
+                .map(|registry| registry.claim_fee(&tx.payload().into_payload().name))
+                .unwrap_or_else(Value::zero);
+            fees.calculate_tx(&tx).saturating_add(claim_fee)
+        }
+    }
+}
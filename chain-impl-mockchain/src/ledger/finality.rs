@@ -0,0 +1,158 @@
+//! Finality of the blocks, from the epoch stability depth.
+//!
+//! A block is final once `epoch_stability_depth` blocks are chained on top
+//! of it: the chain cannot be rolled back to a fork starting before it. The
+//! depth is taken from the settings of the ledger at the tip of the chain.
+//!
+//! A [`FinalityReport`] is emitted in the events of a block when the last
+//! final block moves forward. When an update proposal raises the depth, the
+//! last final block computed from the new depth moves back: no report is
+//! emitted, and the blocks between are reported again once it moves past
+//! them, so the consumers are expected to ignore the blocks already final.
+
+use super::ledger::Ledger;
+use crate::chaintypes::ChainLength;
+
+/// Whether a block can still be rolled back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Finality {
+    Final,
+    /// the block can be rolled back, `depth` blocks are chained on top of it
+    Rollbackable {
+        depth: u32,
+    },
+}
+
+impl Finality {
+    /// Finality of the block at `chain_length` when the tip of the chain is
+    /// at `tip`. A block ahead of the tip is rollbackable at depth 0.
+    pub fn classify(
+        chain_length: ChainLength,
+        tip: ChainLength,
+        epoch_stability_depth: u32,
+    ) -> Self {
+        let depth = tip.0.saturating_sub(chain_length.0);
+        if depth >= epoch_stability_depth && chain_length <= tip {
+            Finality::Final
+        } else {
+            Finality::Rollbackable { depth }
+        }
+    }
+
+    pub fn is_final(self) -> bool {
+        self == Finality::Final
+    }
+}
+
+/// The blocks made final by the application of a block, emitted as an event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FinalityReport {
+    /// the last block final before the application, if any
+    pub previous: Option<ChainLength>,
+    /// the last block final after the application, the blocks after
+    /// `previous` up to this one became final
+    pub last_final: ChainLength,
+}
+
+impl FinalityReport {
+    pub(super) fn between(before: &Ledger, after: &Ledger) -> Option<Self> {
+        let previous = before.last_final_chain_length();
+        let last_final = after.last_final_chain_length()?;
+        if previous.map_or(true, |previous| last_final > previous) {
+            Some(FinalityReport {
+                previous,
+                last_final,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl Ledger {
+    /// the chain length of the last final block, given the chain length and
+    /// the epoch stability depth of this ledger. `None` while the chain is
+    /// shorter than the depth.
+    pub fn last_final_chain_length(&self) -> Option<ChainLength> {
+        self.chain_length
+            .nth_ancestor(self.settings.epoch_stability_depth)
+    }
+
+    /// Finality of the block at `chain_length`, this ledger being the state
+    /// at the tip of the chain
+    pub fn finality(&self, chain_length: ChainLength) -> Finality {
+        Finality::classify(
+            chain_length,
+            self.chain_length,
+            self.settings.epoch_stability_depth,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{ConfigBuilder, LedgerBuilder, TestGen};
+
+    #[test]
+    fn blocks_are_final_at_the_stability_depth() {
+        let tip = ChainLength(10);
+        assert_eq!(Finality::classify(ChainLength(7), tip, 3), Finality::Final);
+        assert_eq!(
+            Finality::classify(ChainLength(8), tip, 3),
+            Finality::Rollbackable { depth: 2 }
+        );
+        assert_eq!(
+            Finality::classify(ChainLength(11), tip, 3),
+            Finality::Rollbackable { depth: 0 }
+        );
+        assert!(Finality::classify(tip, tip, 0).is_final());
+    }
+
+    #[test]
+    fn finality_is_reported_when_the_threshold_advances() {
+        let leader_pair = TestGen::leader_pair();
+        let mut test_ledger = LedgerBuilder::from_config(
+            ConfigBuilder::new()
+                .with_leaders(&[leader_pair.id()])
+                .with_epoch_stability_depth(2),
+        )
+        .build()
+        .unwrap();
+
+        let mut reports = Vec::new();
+        for _ in 0..4 {
+            let block = test_ledger.forge_bft_block_with_fragments(
+                &leader_pair,
+                test_ledger.date(),
+                Vec::new(),
+            );
+            let events = test_ledger.apply_block_with_events(block).unwrap();
+            reports.push(events.finality().copied());
+        }
+
+        assert_eq!(
+            reports,
+            vec![
+                None,
+                Some(FinalityReport {
+                    previous: None,
+                    last_final: ChainLength(0),
+                }),
+                Some(FinalityReport {
+                    previous: Some(ChainLength(0)),
+                    last_final: ChainLength(1),
+                }),
+                Some(FinalityReport {
+                    previous: Some(ChainLength(1)),
+                    last_final: ChainLength(2),
+                }),
+            ]
+        );
+        assert!(test_ledger.ledger.finality(ChainLength(2)).is_final());
+        assert_eq!(
+            test_ledger.ledger.finality(ChainLength(3)),
+            Finality::Rollbackable { depth: 1 }
+        );
+    }
+}
@@ -0,0 +1,249 @@
+//! Log of the final status of the fragments.
+//!
+//! The log answers "what happened to my transaction" for the fragments a
+//! node has seen: either included in a block of the chain or rejected. Like
+//! the account history, it is not part of the consensus state and is only
+//! maintained by the nodes wanting to answer such queries: the blocks are
+//! recorded with [`Ledger::apply_block_with_status_log`] and the fragments
+//! rejected before reaching a block (by the mempool for example) with
+//! [`FragmentStatusLog::record_rejected`].
+//!
+//! The log is bounded: once it holds `capacity` statuses, the statuses of
+//! the oldest epochs are dropped first. Older epochs can also be pruned
+//! explicitly with [`FragmentStatusLog::prune`].
+
+use super::ledger::{Error, Ledger, LedgerParameters};
+use crate::block::Block;
+use crate::chaintypes::HeaderId;
+use crate::date::{BlockDate, Epoch};
+use crate::fragment::FragmentId;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+/// The final status of a fragment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FragmentStatus {
+    /// the fragment is in the block `block` of date `date`
+    InABlock { date: BlockDate, block: HeaderId },
+    /// the fragment was rejected, `reason` is the error of the ledger
+    Rejected { reason: String },
+}
+
+impl FragmentStatus {
+    pub fn is_in_a_block(&self) -> bool {
+        matches!(self, FragmentStatus::InABlock { .. })
+    }
+}
+
+/// Bounded map of the fragment identifiers to their final status, see the
+/// module documentation
+#[derive(Debug, Clone)]
+pub struct FragmentStatusLog {
+    capacity: usize,
+    statuses: HashMap<FragmentId, (Epoch, FragmentStatus)>,
+    /// the fragments recorded in every epoch, in order of recording. May
+    /// still list a fragment whose status was since recorded in another
+    /// epoch.
+    epochs: BTreeMap<Epoch, VecDeque<FragmentId>>,
+}
+
+impl FragmentStatusLog {
+    /// A log keeping at most `capacity` statuses
+    pub fn new(capacity: usize) -> Self {
+        FragmentStatusLog {
+            capacity,
+            statuses: HashMap::new(),
+            epochs: BTreeMap::new(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.statuses.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.statuses.is_empty()
+    }
+
+    pub fn get(&self, fragment_id: &FragmentId) -> Option<&FragmentStatus> {
+        self.statuses.get(fragment_id).map(|(_, status)| status)
+    }
+
+    /// Record all the fragments of the block as in the block
+    pub fn record_block(&mut self, block: &Block) {
+        let header = block.header();
+        let status = FragmentStatus::InABlock {
+            date: header.block_date(),
+            block: header.id(),
+        };
+        for fragment in block.contents().iter() {
+            self.record(fragment.hash(), header.block_date().epoch, status.clone());
+        }
+    }
+
+    /// Record the rejection of the fragment at `date`. A fragment already
+    /// in a block keeps its status, the rejection is then the one of a
+    /// resubmission of the fragment.
+    pub fn record_rejected(
+        &mut self,
+        fragment_id: FragmentId,
+        date: BlockDate,
+        reason: impl Into<String>,
+    ) {
+        if self
+            .get(&fragment_id)
+            .map_or(false, FragmentStatus::is_in_a_block)
+        {
+            return;
+        }
+        let status = FragmentStatus::Rejected {
+            reason: reason.into(),
+        };
+        self.record(fragment_id, date.epoch, status);
+    }
+
+    /// Drop the statuses recorded in the epochs before `epoch`
+    pub fn prune(&mut self, epoch: Epoch) {
+        let kept = self.epochs.split_off(&epoch);
+        let pruned = std::mem::replace(&mut self.epochs, kept);
+        for (pruned_epoch, fragment_ids) in pruned {
+            for fragment_id in fragment_ids {
+                self.remove_if_recorded_in(&fragment_id, pruned_epoch);
+            }
+        }
+    }
+
+    fn record(&mut self, fragment_id: FragmentId, epoch: Epoch, status: FragmentStatus) {
+        self.statuses.insert(fragment_id, (epoch, status));
+        self.epochs.entry(epoch).or_default().push_back(fragment_id);
+        while self.statuses.len() > self.capacity {
+            self.evict_oldest();
+        }
+    }
+
+    fn evict_oldest(&mut self) {
+        let mut oldest = match self.epochs.first_entry() {
+            None => return,
+            Some(oldest) => oldest,
+        };
+        let epoch = *oldest.key();
+        let fragment_id = oldest.get_mut().pop_front();
+        if oldest.get().is_empty() {
+            oldest.remove();
+        }
+        if let Some(fragment_id) = fragment_id {
+            self.remove_if_recorded_in(&fragment_id, epoch);
+        }
+    }
+
+    fn remove_if_recorded_in(&mut self, fragment_id: &FragmentId, epoch: Epoch) {
+        if self
+            .statuses
+            .get(fragment_id)
+            .map(|(recorded, _)| *recorded)
+            == Some(epoch)
+        {
+            self.statuses.remove(fragment_id);
+        }
+    }
+}
+
+impl Ledger {
+    /// Same as [`Ledger::apply_block`], recording the fragments of the
+    /// block in `log` once the block is applied
+    pub fn apply_block_with_status_log(
+        &self,
+        ledger_params: LedgerParameters,
+        block: &Block,
+        log: &mut FragmentStatusLog,
+    ) -> Result<Self, Error> {
+        let header = block.header();
+        let ledger = self.apply_block(
+            ledger_params,
+            block.contents(),
+            &header.get_content_eval_context(),
+        )?;
+        log.record_block(block);
+        Ok(ledger)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{
+        scenario::{prepare_scenario, wallet, FragmentFactory},
+        TestGen,
+    };
+
+    #[test]
+    fn fragments_of_the_applied_blocks_are_in_a_block() {
+        let (mut ledger, controller) = prepare_scenario()
+            .with_initials(vec![
+                wallet("Alice").with(1_000).owns("stake_pool"),
+                wallet("Bob").with(1_000),
+            ])
+            .build()
+            .unwrap();
+        let alice = controller.wallet("Alice").unwrap();
+        let bob = controller.wallet("Bob").unwrap();
+        let stake_pool = controller.stake_pool("stake_pool").unwrap();
+        let mut log = FragmentStatusLog::new(10);
+
+        let fragment =
+            FragmentFactory::from_ledger(&ledger).transaction(&alice, &bob, &mut ledger, 100);
+        let fragment_id = fragment.hash();
+        let block = ledger.forge_praos_block_with_fragments(&stake_pool, vec![fragment]);
+        ledger.ledger = ledger
+            .ledger
+            .apply_block_with_status_log(ledger.ledger.get_ledger_parameters(), &block, &mut log)
+            .unwrap();
+
+        let in_a_block = FragmentStatus::InABlock {
+            date: block.header().block_date(),
+            block: block.header().id(),
+        };
+        assert_eq!(log.get(&fragment_id), Some(&in_a_block));
+
+        // resubmitted and rejected by the mempool
+        log.record_rejected(fragment_id, ledger.date(), "already spent");
+        assert_eq!(log.get(&fragment_id), Some(&in_a_block));
+
+        let rejected = TestGen::hash();
+        log.record_rejected(rejected, ledger.date(), "invalid witness");
+        assert_eq!(
+            log.get(&rejected),
+            Some(&FragmentStatus::Rejected {
+                reason: "invalid witness".to_owned()
+            })
+        );
+        assert_eq!(log.len(), 2);
+    }
+
+    #[test]
+    fn oldest_epochs_are_dropped_first() {
+        let date = |epoch| BlockDate { epoch, slot_id: 0 };
+        let mut log = FragmentStatusLog::new(3);
+        let ids: Vec<_> = (0..4).map(|_| TestGen::hash()).collect();
+        log.record_rejected(ids[0], date(1), "");
+        log.record_rejected(ids[1], date(0), "");
+        log.record_rejected(ids[2], date(1), "");
+        log.record_rejected(ids[3], date(2), "");
+        assert_eq!(log.len(), 3);
+        assert!(log.get(&ids[1]).is_none());
+
+        log.prune(2);
+        assert_eq!(log.len(), 1);
+        assert!(log.get(&ids[3]).is_some());
+
+        // a status recorded again is not pruned with its former epoch
+        log.record_rejected(ids[0], date(2), "");
+        log.record_rejected(ids[0], date(3), "");
+        log.prune(3);
+        assert_eq!(log.len(), 1);
+        assert!(log.get(&ids[0]).is_some());
+    }
+}
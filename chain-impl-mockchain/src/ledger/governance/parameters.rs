@@ -1,5 +1,9 @@
 use crate::{ledger::governance::GovernanceAcceptanceCriteria, value::Value};
-use chain_core::mempack::{ReadBuf, ReadError, Readable};
+use chain_core::{
+    mempack::ReadError,
+    packer::Codec,
+    property::{ByteSource, Pack, Unpack},
+};
 use imhamt::Hamt;
 use std::collections::hash_map::DefaultHasher;
 use typed_bytes::ByteBuilder;
@@ -102,19 +106,30 @@ impl ParametersGovernance {
 
 /* Ser/De ******************************************************************* */
 
-impl Readable for ParametersGovernanceAction {
-    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        match buf.get_u8()? {
+impl Pack for ParametersGovernanceAction {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(self.serialize_in(ByteBuilder::new()).finalize().as_slice())
+    }
+}
+
+impl Unpack for ParametersGovernanceAction {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        match source.take_u8()? {
             0 => Ok(Self::NoOp),
             1 => {
-                let value = Value::read(buf)?;
+                let value = Value::unpack(source)?;
                 Ok(Self::RewardAdd { value })
             }
-            t => Err(ReadError::UnknownTag(t as u32)),
+            t => Err(ReadError::UnknownTag {
+                kind: "parameters governance action",
+                tag: t as u32,
+            }),
         }
     }
 }
 
+chain_ser::impl_legacy_codecs!(ParametersGovernanceAction);
+
 #[cfg(test)]
 mod tests {
 
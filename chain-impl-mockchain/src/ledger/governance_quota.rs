@@ -0,0 +1,112 @@
+//! Rate limiting of the governance fragments.
+//!
+//! The vote plans submitted by each committee member are counted per epoch
+//! and checked against the `MaxVotePlansPerEpoch` configuration parameter,
+//! while the proposals of a vote plan are checked against
+//! `MaxProposalsPerVotePlan`. The count of a member only stands for the
+//! epoch it was recorded in, it starts again from zero on the first
+//! submission of a new epoch.
+
+use crate::{date::Epoch, setting::Settings, vote::CommitteeId};
+use imhamt::{Hamt, HamtIter, InsertError};
+use std::{collections::hash_map::DefaultHasher, num::NonZeroU32};
+
+/// The vote plans submitted by a committee member during an epoch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VotePlanSubmissions {
+    pub epoch: Epoch,
+    pub count: u32,
+}
+
+/// The vote plans submitted by the committee members, see the module
+/// documentation
+#[derive(Clone, PartialEq, Eq)]
+pub struct GovernanceQuotas(Hamt<DefaultHasher, CommitteeId, VotePlanSubmissions>);
+
+impl Default for GovernanceQuotas {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GovernanceQuotas {
+    pub fn new() -> Self {
+        GovernanceQuotas(Hamt::new())
+    }
+
+    /// Number of vote plans submitted by the member during the epoch
+    pub fn submitted(&self, member: &CommitteeId, epoch: Epoch) -> u32 {
+        self.0
+            .lookup(member)
+            .filter(|submissions| submissions.epoch == epoch)
+            .map_or(0, |submissions| submissions.count)
+    }
+
+    /// Count one more vote plan submitted by the member during the epoch
+    pub(crate) fn record_vote_plan(&self, member: CommitteeId, epoch: Epoch) -> Self {
+        let first = VotePlanSubmissions { epoch, count: 1 };
+        GovernanceQuotas(
+            self.0
+                .insert_or_update_simple(member, first, |submissions| {
+                    if submissions.epoch == epoch {
+                        Some(VotePlanSubmissions {
+                            epoch,
+                            count: submissions.count.saturating_add(1),
+                        })
+                    } else {
+                        Some(first)
+                    }
+                }),
+        )
+    }
+
+    /// Set the submissions of a member, only used when rebuilding the ledger
+    /// from its entries
+    pub fn set_for(
+        &mut self,
+        member: CommitteeId,
+        submissions: VotePlanSubmissions,
+    ) -> Result<(), InsertError> {
+        self.0 = self.0.insert(member, submissions)?;
+        Ok(())
+    }
+
+    pub fn iter(&self) -> HamtIter<'_, CommitteeId, VotePlanSubmissions> {
+        self.0.iter()
+    }
+}
+
+/// The consumption of the governance quota of a committee member
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GovernanceQuota {
+    /// the epoch the quota stands for
+    pub epoch: Epoch,
+    pub vote_plans_submitted: u32,
+    /// not limited when not set
+    pub max_vote_plans: Option<NonZeroU32>,
+    /// not limited when not set
+    pub max_proposals_per_vote_plan: Option<NonZeroU32>,
+}
+
+impl GovernanceQuota {
+    pub(super) fn new(
+        quotas: &GovernanceQuotas,
+        settings: &Settings,
+        member: &CommitteeId,
+        epoch: Epoch,
+    ) -> Self {
+        GovernanceQuota {
+            epoch,
+            vote_plans_submitted: quotas.submitted(member, epoch),
+            max_vote_plans: settings.max_vote_plans_per_epoch,
+            max_proposals_per_vote_plan: settings.max_proposals_per_vote_plan,
+        }
+    }
+
+    /// Number of vote plans the member can still submit during the epoch,
+    /// `None` when not limited
+    pub fn remaining_vote_plans(&self) -> Option<u32> {
+        self.max_vote_plans
+            .map(|max| max.get().saturating_sub(self.vote_plans_submitted))
+    }
+}
@@ -1,53 +1,242 @@
 use super::ledger::Ledger;
+use crate::account;
+use crate::accounting::account::AccountState;
+use crate::certificate::{PoolId, PoolRegistration, Proposal, VotePlan, VotePlanId};
+use crate::fragment::FragmentId;
+use crate::legacy;
+use crate::multisig::{self, DeclElement, Declaration};
+use crate::names::{Name, NameRecord};
+use crate::stake::PoolState;
+use crate::tokens::identifier::TokenIdentifier;
+use crate::transaction::Output;
 use crate::value::Value;
+use crate::vote::{ValidatedPayload, VotePlanManager};
+use chain_addr::Address;
+use chain_crypto::{Ed25519, PublicKey};
+use std::fmt;
+use std::mem::size_of;
+
+/// Number of entries of a category of the ledger state, along with an
+/// approximation of the memory they use.
+///
+/// The size only accounts for the entries themselves: the overhead of the
+/// containers is not included, and the data shared between ledger states
+/// is counted in every state.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EntryStats {
+    pub count: usize,
+    pub approximate_size: usize,
+}
+
+impl EntryStats {
+    fn add(&mut self, size: usize) {
+        self.count += 1;
+        self.approximate_size += size;
+    }
+}
+
+impl fmt::Display for EntryStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{} ~{}B", self.count, self.approximate_size)
+    }
+}
+
+/// Size of the ledger state, per category of entries
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LedgerStats {
+    pub utxos: EntryStats,
+    pub utxos_total: Value,
+    pub oldutxos: EntryStats,
+    pub oldutxos_total: Value,
+    pub accounts: EntryStats,
+    pub accounts_total: Value,
+    pub multisig_accounts: EntryStats,
+    pub multisig_total: Value,
+    pub multisig_declarations: EntryStats,
+    pub pools: EntryStats,
+    pub vote_plans: EntryStats,
+    pub proposals: EntryStats,
+    pub votes: EntryStats,
+    pub names: EntryStats,
+    #[cfg(feature = "evm")]
+    pub evm_accounts: EntryStats,
+}
+
+impl LedgerStats {
+    /// approximate size of all the entries of the ledger
+    pub fn approximate_size(&self) -> usize {
+        let LedgerStats {
+            utxos,
+            utxos_total: _,
+            oldutxos,
+            oldutxos_total: _,
+            accounts,
+            accounts_total: _,
+            multisig_accounts,
+            multisig_total: _,
+            multisig_declarations,
+            pools,
+            vote_plans,
+            proposals,
+            votes,
+            names,
+            #[cfg(feature = "evm")]
+            evm_accounts,
+        } = self;
+
+        [
+            utxos,
+            oldutxos,
+            accounts,
+            multisig_accounts,
+            multisig_declarations,
+            pools,
+            vote_plans,
+            proposals,
+            votes,
+            names,
+            #[cfg(feature = "evm")]
+            evm_accounts,
+        ]
+        .iter()
+        .map(|stats| stats.approximate_size)
+        .sum()
+    }
+}
+
+impl fmt::Display for LedgerStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "utxos   : {} Total={}", self.utxos, self.utxos_total)?;
+        writeln!(
+            f,
+            "oldutxos: {} Total={}",
+            self.oldutxos, self.oldutxos_total
+        )?;
+        writeln!(
+            f,
+            "accounts: {} Total={}",
+            self.accounts, self.accounts_total
+        )?;
+        writeln!(
+            f,
+            "multisig: {} Total={}",
+            self.multisig_accounts, self.multisig_total
+        )?;
+        writeln!(f, "multisig declarations: {}", self.multisig_declarations)?;
+        writeln!(f, "pools: {}", self.pools)?;
+        writeln!(f, "vote plans: {}", self.vote_plans)?;
+        writeln!(f, "proposals: {}", self.proposals)?;
+        writeln!(f, "names: {}", self.names)?;
+        #[cfg(feature = "evm")]
+        writeln!(f, "EVM accounts: {}", self.evm_accounts)?;
+        write!(f, "votes: {}", self.votes)
+    }
+}
+
+fn account_size<Extra>(state: &AccountState<Extra>) -> usize {
+    size_of::<account::Identifier>()
+        + size_of::<AccountState<Extra>>()
+        + state.tokens.size() * (size_of::<TokenIdentifier>() + size_of::<Value>())
+}
+
+fn pool_size(registration: &PoolRegistration) -> usize {
+    size_of::<PoolId>()
+        + size_of::<PoolState>()
+        + size_of::<PoolRegistration>()
+        + (registration.owners.len() + registration.operators.len())
+            * size_of::<PublicKey<Ed25519>>()
+}
+
+fn vote_plan_size(manager: &VotePlanManager) -> usize {
+    size_of::<VotePlanId>()
+        + size_of::<VotePlanManager>()
+        + size_of::<VotePlan>()
+        + manager.plan().committee_public_keys().len() * size_of::<chain_vote::MemberPublicKey>()
+}
 
 impl Ledger {
-    pub fn stats(&self) -> Vec<String> {
+    /// Count the entries of the ledger state and approximate their size.
+    ///
+    /// This walks the whole state, it is meant for diagnostics and not to be
+    /// called on every block.
+    pub fn stats(&self) -> LedgerStats {
         let Ledger {
             utxos,
             oldutxos,
             accounts,
             settings: _,
+            settings_history: _,
             updates: _,
             multisig,
-            delegation: _,
+            delegation,
             static_params: _,
             date: _,
             chain_length: _,
             era: _,
             pots: _,
             leaders_log: _,
-            votes: _,
+            votes,
             governance: _,
+            governance_quotas: _,
             #[cfg(feature = "evm")]
             evm,
             token_totals: _,
+            names,
+            account_history: _,
+            #[cfg(feature = "address-screening")]
+                address_screening: _,
+            certificate_extensions: _,
+            balance_watches: _,
         } = self;
 
-        let stats = vec![
-            format!(
-                "utxos   : #{} Total={:?}",
-                utxos.iter().count(),
-                Value::sum(utxos.iter().map(|x| x.output.value))
-            ),
-            format!(
-                "oldutxos: #{} Total={:?}",
-                oldutxos.iter().count(),
-                Value::sum(oldutxos.iter().map(|x| x.output.value))
-            ),
-            format!(
-                "accounts: #{} Total={:?}",
-                accounts.iter().count(),
-                Value::sum(accounts.iter().map(|x| x.1.value))
-            ),
-            format!(
-                "multisig: #{} Total={:?}",
-                multisig.iter_accounts().count(),
-                Value::sum(multisig.iter_accounts().map(|x| x.1.value))
-            ),
-            #[cfg(feature = "evm")]
-            evm.stats(),
-        ];
+        let mut stats = LedgerStats::default();
+        let utxo_size = size_of::<FragmentId>() + size_of::<u8>() + size_of::<Output<Address>>();
+        for entry in utxos.iter() {
+            stats.utxos.add(utxo_size);
+            stats.utxos_total = stats.utxos_total.saturating_add(entry.output.value);
+        }
+        let oldutxo_size =
+            size_of::<FragmentId>() + size_of::<u8>() + size_of::<Output<legacy::OldAddress>>();
+        for entry in oldutxos.iter() {
+            stats.oldutxos.add(oldutxo_size);
+            stats.oldutxos_total = stats.oldutxos_total.saturating_add(entry.output.value);
+        }
+        for (_, state) in accounts.iter() {
+            stats.accounts.add(account_size(state));
+            stats.accounts_total = stats.accounts_total.saturating_add(state.value);
+        }
+        for (_, state) in multisig.iter_accounts() {
+            stats.multisig_accounts.add(account_size(state));
+            stats.multisig_total = stats.multisig_total.saturating_add(state.value);
+        }
+        for (_, declaration) in multisig.iter_declarations() {
+            stats.multisig_declarations.add(
+                size_of::<multisig::Identifier>()
+                    + size_of::<Declaration>()
+                    + declaration.owners.len() * size_of::<DeclElement>(),
+            );
+        }
+        for (_, pool) in delegation.stake_pools.iter() {
+            stats.pools.add(pool_size(&pool.registration));
+        }
+        for (_, manager) in votes.plans.iter() {
+            stats.vote_plans.add(vote_plan_size(manager));
+            let nb_proposals = manager.plan().proposals().len();
+            stats.proposals.count += nb_proposals;
+            stats.proposals.approximate_size += nb_proposals * size_of::<Proposal>();
+            let vote_size = size_of::<account::Identifier>() + size_of::<ValidatedPayload>();
+            stats.votes.count += manager.nb_votes();
+            stats.votes.approximate_size += manager.nb_votes() * vote_size;
+        }
+        for (name, _) in names.iter() {
+            stats
+                .names
+                .add(size_of::<Name>() + name.len() + size_of::<NameRecord>());
+        }
+        #[cfg(feature = "evm")]
+        {
+            stats.evm_accounts = evm.stats();
+        }
 
         stats
     }
@@ -58,6 +247,7 @@ impl Ledger {
             oldutxos: oldutxos1,
             accounts: accounts1,
             settings: settings1,
+            settings_history: settings_history1,
             updates: updates1,
             multisig: multisig1,
             delegation: delegation1,
@@ -69,9 +259,16 @@ impl Ledger {
             leaders_log: leaders_log1,
             votes: votes1,
             governance: governance1,
+            governance_quotas: governance_quotas1,
             #[cfg(feature = "evm")]
                 evm: evm1,
             token_totals: token_totals1,
+            names: names1,
+            account_history: _,
+            #[cfg(feature = "address-screening")]
+                address_screening: _,
+            certificate_extensions: _,
+            balance_watches: _,
         } = self;
 
         let Ledger {
@@ -79,6 +276,7 @@ impl Ledger {
             oldutxos: oldutxos2,
             accounts: accounts2,
             settings: settings2,
+            settings_history: settings_history2,
             updates: updates2,
             multisig: multisig2,
             delegation: delegation2,
@@ -90,9 +288,16 @@ impl Ledger {
             leaders_log: leaders_log2,
             votes: votes2,
             governance: governance2,
+            governance_quotas: governance_quotas2,
             #[cfg(feature = "evm")]
                 evm: evm2,
             token_totals: token_totals2,
+            names: names2,
+            account_history: _,
+            #[cfg(feature = "address-screening")]
+                address_screening: _,
+            certificate_extensions: _,
+            balance_watches: _,
         } = other;
 
         let info = vec![
@@ -101,6 +306,10 @@ impl Ledger {
             format!("accounts-same: {}", accounts1 == accounts2),
             format!("multisig-same: {}", multisig1 == multisig2),
             format!("settings-same: {}", settings1 == settings2),
+            format!(
+                "settings-history-same: {}",
+                settings_history1 == settings_history2
+            ),
             format!("delegation-same: {}", delegation1 == delegation2),
             format!("static_params-same: {}", static_params1 == static_params2),
             format!("updates-same: {}", updates1 == updates2),
@@ -111,9 +320,14 @@ impl Ledger {
             format!("leaders-log-same: {}", leaders_log1 == leaders_log2),
             format!("vote-plans: {}", votes1 == votes2),
             format!("governance: {}", governance1 == governance2),
+            format!(
+                "governance-quotas: {}",
+                governance_quotas1 == governance_quotas2
+            ),
             #[cfg(feature = "evm")]
             evm1.info_eq(evm2),
             format!("token-totals: {}", token_totals1 == token_totals2),
+            format!("names: {}", names1 == names2),
         ];
 
         info
@@ -0,0 +1,323 @@
+//! Bulk import of the initial funds of a block0.
+//!
+//! A token distribution launch creates hundreds of thousands of outputs in
+//! the block0. Declaring them one transaction each wastes a fragment header
+//! and a transaction header per output, so [`InitialFunds`] collects them
+//! and packs them into as few initial transactions as the transaction format
+//! allows: at most [`CHECK_TX_MAXIMUM_OUTPUTS`] outputs each, and optionally
+//! under a maximum size of the serialized fragment.
+//!
+//! The funds are validated as they are added, and their total can be checked
+//! against the supply declared by the other fragments of the block0 before
+//! the block0 is built.
+
+use super::block0_supply::Block0Supply;
+use super::check::CHECK_TX_MAXIMUM_OUTPUTS;
+use super::ledger::Block0Error;
+use crate::date::BlockDate;
+use crate::fragment::Fragment;
+use crate::transaction::{Output, TxBuilder};
+use crate::value::Value;
+use chain_addr::{Address, AddressReadable, Discrimination};
+use std::io::BufRead;
+use std::ops::Range;
+use thiserror::Error;
+
+/// the optional first line of the CSV of the initial funds
+pub const CSV_HEADER: &str = "address,value";
+
+#[derive(Debug, Error)]
+pub enum InitialFundsError {
+    #[error("line {line}: expected `address,value`")]
+    MalformedLine { line: usize },
+    #[error("line {line}: invalid address")]
+    InvalidAddress {
+        line: usize,
+        #[source]
+        source: chain_addr::Error,
+    },
+    #[error("line {line}: invalid value")]
+    InvalidValue { line: usize },
+    #[error("the address {address:?} is not of the {expected:?} discrimination")]
+    InvalidDiscrimination {
+        address: Address,
+        expected: Discrimination,
+    },
+    #[error("zero initial funds for the address {address:?}")]
+    ZeroValue { address: Address },
+    #[error("the total of the initial funds is too big")]
+    TotalValueTooBig,
+    #[error("the output of {size} bytes does not fit in a fragment of {max} bytes")]
+    OutputTooBig { size: usize, max: usize },
+    #[error("cannot read the initial funds")]
+    Io(#[from] std::io::Error),
+    #[error("the initial funds do not fit in the supply of the block0")]
+    Supply(#[from] Block0Error),
+}
+
+/// The initial funds of a block0, packed in initial transactions
+#[derive(Debug, Clone)]
+pub struct InitialFunds {
+    discrimination: Discrimination,
+    max_fragment_size: Option<usize>,
+    outputs: Vec<Output<Address>>,
+    total: Value,
+}
+
+/// size of the serialized output, its address then its value
+fn output_size(output: &Output<Address>) -> usize {
+    output.address.to_bytes().len() + output.value.bytes().len()
+}
+
+fn initial_transaction(outputs: &[Output<Address>]) -> Fragment {
+    let tx = TxBuilder::new()
+        .set_nopayload()
+        .set_expiry_date(BlockDate::first().next_epoch())
+        .set_ios(&[], outputs)
+        .set_witnesses_unchecked(&[])
+        .set_payload_auth(&());
+    Fragment::Transaction(tx)
+}
+
+impl InitialFunds {
+    pub fn new(discrimination: Discrimination) -> Self {
+        InitialFunds {
+            discrimination,
+            max_fragment_size: None,
+            outputs: Vec::new(),
+            total: Value::zero(),
+        }
+    }
+
+    /// Limit the size of the serialized initial transactions, the size
+    /// prefix of the fragments included
+    pub fn with_max_fragment_size(mut self, max_fragment_size: usize) -> Self {
+        self.max_fragment_size = Some(max_fragment_size);
+        self
+    }
+
+    /// Read the funds from a CSV of `address,value` lines, the addresses
+    /// being bech32 encoded with any prefix. The empty lines are skipped,
+    /// and the first line may be the [`CSV_HEADER`].
+    pub fn from_csv<R: BufRead>(
+        discrimination: Discrimination,
+        reader: R,
+    ) -> Result<Self, InitialFundsError> {
+        let mut funds = InitialFunds::new(discrimination);
+        for (index, line) in reader.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || (index == 0 && line == CSV_HEADER) {
+                continue;
+            }
+            let (address, value) = line
+                .split_once(',')
+                .ok_or(InitialFundsError::MalformedLine { line: line_number })?;
+            let address = AddressReadable::from_string_anyprefix(address.trim())
+                .map_err(|source| InitialFundsError::InvalidAddress {
+                    line: line_number,
+                    source,
+                })?
+                .to_address();
+            let value = value
+                .trim()
+                .parse()
+                .map_err(|_| InitialFundsError::InvalidValue { line: line_number })?;
+            funds.add(address, value)?;
+        }
+        Ok(funds)
+    }
+
+    /// Add the funds of an address. An address can be funded more than
+    /// once, each time in its own output.
+    pub fn add(&mut self, address: Address, value: Value) -> Result<(), InitialFundsError> {
+        if address.discrimination() != self.discrimination {
+            return Err(InitialFundsError::InvalidDiscrimination {
+                address,
+                expected: self.discrimination,
+            });
+        }
+        if value == Value::zero() {
+            return Err(InitialFundsError::ZeroValue { address });
+        }
+        let output = Output::from_address(address, value);
+        if let Some(max) = self.max_fragment_size {
+            let size =
+                initial_transaction(&[]).to_raw().size_bytes_plus_size() + output_size(&output);
+            if size > max {
+                return Err(InitialFundsError::OutputTooBig { size, max });
+            }
+        }
+        self.total = self
+            .total
+            .checked_add(value)
+            .map_err(|_| InitialFundsError::TotalValueTooBig)?;
+        self.outputs.push(output);
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.outputs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.outputs.is_empty()
+    }
+
+    pub fn total(&self) -> Value {
+        self.total
+    }
+
+    /// the outputs of every initial transaction, filled in order up to the
+    /// number of outputs and the size limits
+    fn chunks(&self) -> Vec<Range<usize>> {
+        let max_outputs = CHECK_TX_MAXIMUM_OUTPUTS as usize;
+        let empty_size = initial_transaction(&[]).to_raw().size_bytes_plus_size();
+        let mut chunks = Vec::new();
+        let mut start = 0;
+        let mut size = empty_size;
+        for (index, output) in self.outputs.iter().enumerate() {
+            let output_size = output_size(output);
+            let full = index > start
+                && (index - start == max_outputs
+                    || self
+                        .max_fragment_size
+                        .map_or(false, |max| size + output_size > max));
+            if full {
+                chunks.push(start..index);
+                start = index;
+                size = empty_size;
+            }
+            size += output_size;
+        }
+        if start < self.outputs.len() {
+            chunks.push(start..self.outputs.len());
+        }
+        chunks
+    }
+
+    /// The initial transactions declaring the funds, to add to the contents
+    /// of the block0
+    pub fn fragments(&self) -> impl Iterator<Item = Fragment> + '_ {
+        self.chunks()
+            .into_iter()
+            .map(move |chunk| initial_transaction(&self.outputs[chunk]))
+    }
+
+    /// Check the funds against the supply declared by the other fragments
+    /// of the block0, and return the supply of the whole block0
+    pub fn validate<'a, I>(&self, block0: I) -> Result<Block0Supply, InitialFundsError>
+    where
+        I: IntoIterator<Item = &'a Fragment>,
+    {
+        let mut supply = Block0Supply::from_fragments(block0)?;
+        supply.initial_funds = supply
+            .initial_funds
+            .checked_add(self.total)
+            .map_err(|_| Block0Error::UtxoTotalValueTooBig)?;
+        supply.validate()?;
+        Ok(supply)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConfigParam;
+    use crate::testing::{
+        data::AddressData,
+        ledger::{ConfigBuilder, LedgerBuilder},
+    };
+
+    fn addresses(count: usize) -> Vec<Address> {
+        (0..count)
+            .map(|_| AddressData::utxo(Discrimination::Test).address)
+            .collect()
+    }
+
+    #[test]
+    fn funds_are_packed_in_few_initial_transactions() {
+        let mut funds = InitialFunds::new(Discrimination::Test);
+        for address in addresses(600) {
+            funds.add(address, Value(10)).unwrap();
+        }
+        assert_eq!(funds.total(), Value(6_000));
+
+        let fragments: Vec<_> = funds.fragments().collect();
+        let outputs: Vec<_> = fragments
+            .iter()
+            .map(|fragment| match fragment {
+                Fragment::Transaction(tx) => tx.as_slice().nb_outputs(),
+                fragment => panic!("unexpected fragment {:?}", fragment),
+            })
+            .collect();
+        assert_eq!(outputs, vec![254, 254, 92]);
+
+        let config = ConfigBuilder::new();
+        let block0_params = Fragment::Initial(config.clone().build());
+        let supply = funds.validate(std::iter::once(&block0_params)).unwrap();
+        assert_eq!(supply.initial_funds, Value(6_000));
+
+        let test_ledger = LedgerBuilder::from_config(config)
+            .fragments(&fragments)
+            .build()
+            .unwrap();
+        assert_eq!(test_ledger.ledger.utxos().count(), 600);
+    }
+
+    #[test]
+    fn funds_are_packed_under_the_size_limit() {
+        let mut funds = InitialFunds::new(Discrimination::Test).with_max_fragment_size(1_024);
+        for address in addresses(100) {
+            funds.add(address, Value(1)).unwrap();
+        }
+        let fragments: Vec<_> = funds.fragments().collect();
+        assert!(fragments.len() > 1);
+        assert!(fragments
+            .iter()
+            .all(|fragment| fragment.to_raw().size_bytes_plus_size() <= 1_024));
+    }
+
+    #[test]
+    fn funds_are_read_from_csv() {
+        let addresses = addresses(2);
+        let csv = format!(
+            "{}\n{},100\n\n{}, 200\n",
+            CSV_HEADER,
+            AddressReadable::from_address("ta", &addresses[0]),
+            AddressReadable::from_address("ta", &addresses[1]),
+        );
+        let funds = InitialFunds::from_csv(Discrimination::Test, csv.as_bytes()).unwrap();
+        assert_eq!(funds.len(), 2);
+        assert_eq!(funds.total(), Value(300));
+
+        assert!(matches!(
+            InitialFunds::from_csv(Discrimination::Test, "ta1xyz".as_bytes()),
+            Err(InitialFundsError::MalformedLine { line: 1 })
+        ));
+        let zero = format!("{},0", AddressReadable::from_address("ta", &addresses[0]));
+        assert!(matches!(
+            InitialFunds::from_csv(Discrimination::Test, zero.as_bytes()),
+            Err(InitialFundsError::ZeroValue { .. })
+        ));
+        assert!(matches!(
+            InitialFunds::from_csv(Discrimination::Production, csv.as_bytes()),
+            Err(InitialFundsError::InvalidDiscrimination { .. })
+        ));
+    }
+
+    #[test]
+    fn funds_exceeding_the_supply_are_rejected() {
+        let mut funds = InitialFunds::new(Discrimination::Test);
+        funds.add(addresses(1).remove(0), Value(1_000)).unwrap();
+        let mut params = ConfigBuilder::new().build();
+        params.push(ConfigParam::TotalSupply(Value(500)));
+        assert!(matches!(
+            funds.validate(std::iter::once(&Fragment::Initial(params))),
+            Err(InitialFundsError::Supply(
+                Block0Error::InitialFundsExceedCirculatingSupply { .. }
+            ))
+        ));
+    }
+}
@@ -0,0 +1,285 @@
+//! Resolution of the inputs of a transaction against the ledger.
+//!
+//! The inputs are resolved the way the ledger applies them: in order, each
+//! one against the state left by the previous ones, so an output spent twice
+//! or an account spending more than its funds over several inputs is
+//! reported. The witnesses only tell which ledger an input spends from and
+//! the spending counter of the accounts, the signatures are not verified and
+//! the ledger is left untouched.
+//!
+//! The ledger spends the inputs it applies with the same functions, before
+//! verifying their witnesses.
+
+use super::ledger::{match_identifier_witness, Error, Ledger, MatchingIdentifierWitness};
+use crate::account::{self, SpendingCounter, SpendingCycle};
+use crate::legacy;
+use crate::multisig;
+use crate::transaction::{
+    Input, InputEnum, Output, Payload, TransactionSlice, UtxoPointer, Witness,
+};
+use crate::utxo;
+use crate::value::Value;
+use chain_addr::Address;
+
+/// An input of a transaction, as found in the ledger
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolvedInput {
+    Utxo {
+        pointer: UtxoPointer,
+        output: Output<Address>,
+    },
+    OldUtxo {
+        pointer: UtxoPointer,
+        output: Output<legacy::OldAddress>,
+    },
+    Account {
+        account: account::Identifier,
+        spending_counter: SpendingCounter,
+        value: Value,
+    },
+    Multisig {
+        account: multisig::Identifier,
+        spending_counter: SpendingCounter,
+        value: Value,
+    },
+}
+
+impl ResolvedInput {
+    /// the value spent by the input
+    pub fn value(&self) -> Value {
+        match self {
+            ResolvedInput::Utxo { output, .. } => output.value,
+            ResolvedInput::OldUtxo { output, .. } => output.value,
+            ResolvedInput::Account { value, .. } | ResolvedInput::Multisig { value, .. } => *value,
+        }
+    }
+}
+
+/// The parts of the ledger the inputs are spent from
+struct InputsState {
+    utxos: utxo::Ledger<Address>,
+    oldutxos: utxo::Ledger<legacy::OldAddress>,
+    accounts: account::Ledger,
+    multisig: multisig::Ledger,
+}
+
+impl InputsState {
+    fn resolve(&mut self, input: &Input, witness: &Witness) -> Result<ResolvedInput, Error> {
+        match input.to_enum() {
+            InputEnum::UtxoInput(pointer) => match witness {
+                Witness::Account(_, _) | Witness::Multisig(_, _) => {
+                    Err(Error::ExpectingUtxoWitness)
+                }
+                Witness::OldUtxo(..) => {
+                    let (oldutxos, output) = spend_utxo(&self.oldutxos, &pointer)?;
+                    self.oldutxos = oldutxos;
+                    Ok(ResolvedInput::OldUtxo { pointer, output })
+                }
+                Witness::Utxo(_) => {
+                    let (utxos, output) = spend_utxo(&self.utxos, &pointer)?;
+                    self.utxos = utxos;
+                    Ok(ResolvedInput::Utxo { pointer, output })
+                }
+            },
+            InputEnum::AccountInput(account_id, value) => {
+                match match_identifier_witness(&account_id, witness)? {
+                    MatchingIdentifierWitness::Single(account, _, spending_counter) => {
+                        let (accounts, _) =
+                            spend_account(&self.accounts, &account, spending_counter, value)?;
+                        self.accounts = accounts;
+                        Ok(ResolvedInput::Account {
+                            account,
+                            spending_counter,
+                            value,
+                        })
+                    }
+                    MatchingIdentifierWitness::Multi(account, _, spending_counter) => {
+                        let (multisig, _, _) =
+                            spend_multisig(&self.multisig, &account, spending_counter, value)?;
+                        self.multisig = multisig;
+                        Ok(ResolvedInput::Multisig {
+                            account,
+                            spending_counter,
+                            value,
+                        })
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Remove the output of the utxo input from the ledger, checking it has the
+/// value stated by the input
+pub(super) fn spend_utxo<OutAddress: Clone>(
+    utxos: &utxo::Ledger<OutAddress>,
+    pointer: &UtxoPointer,
+) -> Result<(utxo::Ledger<OutAddress>, Output<OutAddress>), Error> {
+    let (utxos, output) = utxos.remove(&pointer.transaction_id, pointer.output_index)?;
+    if pointer.value != output.value {
+        return Err(Error::UtxoValueNotMatching {
+            expected: pointer.value,
+            value: output.value,
+        });
+    }
+    Ok((utxos, output))
+}
+
+/// Remove the value of the input from the account, along with the spending
+/// cycle of the counter before it is spent, which the witness signs
+pub(super) fn spend_account(
+    accounts: &account::Ledger,
+    account: &account::Identifier,
+    spending_counter: SpendingCounter,
+    value: Value,
+) -> Result<(account::Ledger, SpendingCycle), Error> {
+    // .remove_value() check if there's enough value and if not, returns a Err.
+    let spent = accounts.remove_value(account, spending_counter, value)?;
+    let cycle = accounts.get_spending_cycle(account, spending_counter)?;
+    Ok((spent, cycle))
+}
+
+/// Same as [`spend_account`] for a multisig account, along with the
+/// declaration the witness must satisfy
+pub(super) fn spend_multisig<'a>(
+    multisig: &'a multisig::Ledger,
+    account: &multisig::Identifier,
+    spending_counter: SpendingCounter,
+    value: Value,
+) -> Result<(multisig::Ledger, &'a multisig::Declaration, SpendingCycle), Error> {
+    let (spent, declaration) = multisig.remove_value(account, spending_counter, value)?;
+    let cycle = multisig.get_spending_cycle(account, spending_counter)?;
+    Ok((spent, declaration, cycle))
+}
+
+impl Ledger {
+    /// Resolve every input of the transaction against the ledger, without
+    /// applying the transaction nor verifying its witnesses.
+    ///
+    /// The result of an input is the error the ledger would fail with when
+    /// applying it. An input failing to resolve doesn't spend anything for
+    /// the resolution of the next inputs.
+    pub fn resolve_inputs<Extra: Payload>(
+        &self,
+        tx: &TransactionSlice<Extra>,
+    ) -> Vec<Result<ResolvedInput, Error>> {
+        let mut state = InputsState {
+            utxos: self.utxos.clone(),
+            oldutxos: self.oldutxos.clone(),
+            accounts: self.accounts.clone(),
+            multisig: self.multisig.clone(),
+        };
+        tx.inputs_and_witnesses()
+            .iter()
+            .map(|(input, witness)| state.resolve(&input, &witness))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        accounting::account::LedgerError,
+        date::BlockDate,
+        testing::{
+            builders::{witness_builder::make_witness, TestTx},
+            data::AddressDataValue,
+            ledger::{ConfigBuilder, LedgerBuilder},
+        },
+        transaction::{NoExtra, TxBuilder},
+        value::ValueError,
+    };
+    use chain_addr::Discrimination;
+
+    #[test]
+    fn inputs_are_resolved_in_order() {
+        let utxo_faucet = AddressDataValue::utxo(Discrimination::Test, Value(1000));
+        let account_faucet = AddressDataValue::account(Discrimination::Test, Value(1000));
+        let test_ledger = LedgerBuilder::from_config(ConfigBuilder::new())
+            .faucets(&[utxo_faucet.clone(), account_faucet.clone()])
+            .build()
+            .unwrap();
+        let block0_hash = test_ledger.block0_hash;
+        let ledger: Ledger = test_ledger.into();
+        let utxo = ledger.utxos().next().unwrap();
+
+        let inputs = [
+            utxo_faucet.make_input(Some(utxo.clone())),
+            account_faucet.make_input_with_value(None, Value(600)),
+            // the output is already spent by the first input
+            utxo_faucet.make_input(Some(utxo.clone())),
+        ];
+        let tx_builder = TxBuilder::new()
+            .set_payload(&NoExtra)
+            .set_expiry_date(BlockDate::first().next_epoch())
+            .set_ios(&inputs, &[]);
+        let auth_data_hash = tx_builder.get_auth_data_for_witness().hash();
+        let witnesses = [
+            make_witness(&block0_hash, &utxo_faucet.address_data, &auth_data_hash),
+            make_witness(&block0_hash, &account_faucet.address_data, &auth_data_hash),
+            make_witness(&block0_hash, &utxo_faucet.address_data, &auth_data_hash),
+        ];
+        let tx = TestTx::new(tx_builder.set_witnesses(&witnesses).set_payload_auth(&()));
+
+        let resolved = ledger.resolve_inputs(&tx.as_slice());
+        assert_eq!(resolved.len(), 3);
+        assert_eq!(
+            resolved[0],
+            Ok(ResolvedInput::Utxo {
+                pointer: UtxoPointer::new(utxo.fragment_id, utxo.output_index, Value(1000)),
+                output: utxo.output.clone(),
+            })
+        );
+        assert_eq!(
+            resolved[1].as_ref().map(ResolvedInput::value),
+            Ok(Value(600))
+        );
+        assert!(matches!(resolved[2], Err(Error::UtxoError(_))));
+
+        // the ledger is left untouched
+        assert_eq!(ledger.utxos().count(), 1);
+    }
+
+    #[test]
+    fn account_funds_are_spent_across_inputs() {
+        let faucet = AddressDataValue::account(Discrimination::Test, Value(1000));
+        let test_ledger = LedgerBuilder::from_config(ConfigBuilder::new())
+            .faucet(&faucet)
+            .build()
+            .unwrap();
+        let block0_hash = test_ledger.block0_hash;
+        let ledger: Ledger = test_ledger.into();
+
+        let inputs = [
+            faucet.make_input_with_value(None, Value(600)),
+            faucet.make_input_with_value(None, Value(600)),
+        ];
+        let tx_builder = TxBuilder::new()
+            .set_payload(&NoExtra)
+            .set_expiry_date(BlockDate::first().next_epoch())
+            .set_ios(&inputs, &[]);
+        let witness = make_witness(
+            &block0_hash,
+            &faucet.address_data,
+            &tx_builder.get_auth_data_for_witness().hash(),
+        );
+        let tx = TestTx::new(
+            tx_builder
+                .set_witnesses(&[witness.clone(), witness])
+                .set_payload_auth(&()),
+        );
+
+        let resolved = ledger.resolve_inputs(&tx.as_slice());
+        assert_eq!(
+            resolved[0].as_ref().map(ResolvedInput::value),
+            Ok(Value(600))
+        );
+        assert_eq!(
+            resolved[1],
+            Err(Error::Account(LedgerError::ValueError(
+                ValueError::NegativeAmount
+            )))
+        );
+    }
+}
@@ -1,20 +1,55 @@
+//! Iteration over the entries of the ledger.
+//!
+//! [`Ledger::iter`] walks the internal structures as they are laid out in
+//! memory: the order of the entries of a same kind follows the layout of the
+//! tries, which depends on the history of the insertions and removals. Two
+//! ledgers in the same state may then list their entries in different orders.
+//!
+//! [`Ledger::iter_canonical`] lists the entries in the canonical order, which
+//! only depends on the state of the ledger:
+//!
+//! 1. the entries are grouped by kind, in the order of the variants of
+//!    [`Entry`]: globals, utxos, old utxos, accounts, config params, update
+//!    proposals, multisig accounts, multisig declarations, stake pools, pots,
+//!    leader participations, vote plans, names, settings of the past epochs
+//!    and governance quotas;
+//! 2. the entries of a same kind are sorted by key: fragment id then output
+//!    index for the utxos, identifier for the accounts, the update proposals,
+//!    the multisig entries, the stake pools and the leader participations,
+//!    vote plan id for the vote plans, name for the names, epoch for the
+//!    settings of the past epochs and committee member for the governance
+//!    quotas. The config
+//!    params and the pots, which are derived from fixed fields of the ledger,
+//!    keep the order in which the ledger lists them.
+//!
+//! The serialization of the ledger and the ledger digests use the canonical
+//! order, so the snapshots taken by different nodes at the same state are
+//! byte-identical.
+
+use super::balance_watch::BalanceWatches;
+use super::extensions::CertificateExtensions;
 use super::governance::Governance;
+use super::governance_quota::{GovernanceQuotas, VotePlanSubmissions};
 use super::ledger::{Error, Ledger, LedgerStaticParameters};
 use super::pots::{self, Pots};
+use super::settings_history::SettingsHistory;
 use super::LeadersParticipationRecord;
 use crate::certificate::{VotePlan, VotePlanId};
 use crate::chaintypes::ChainLength;
 use crate::config::ConfigParam;
-use crate::date::BlockDate;
+use crate::date::{BlockDate, Epoch};
+use crate::fragment::ConfigParams;
 use crate::key::Hash;
 #[cfg(feature = "evm")]
 use crate::ledger::evm;
 use crate::ledger::token_distribution::TokenTotals;
+use crate::names::{Name, NameRecord, NameRegistry};
 use crate::stake::PoolsState;
-use crate::vote::{VotePlanLedger, VotePlanManager};
+use crate::vote::{CommitteeId, VotePlanLedger, VotePlanManager};
 use crate::{account, legacy, multisig, setting, update, utxo};
 use chain_addr::Address;
 use chain_time::TimeEra;
+use std::cmp::Ordering;
 use std::sync::Arc;
 
 pub enum Entry<'a> {
@@ -50,6 +85,9 @@ pub enum Entry<'a> {
     StakePool((&'a crate::certificate::PoolId, &'a crate::stake::PoolState)),
     LeaderParticipation((&'a crate::certificate::PoolId, &'a u32)),
     VotePlan(&'a VotePlan),
+    Name((&'a Name, &'a NameRecord)),
+    EpochSettings((Epoch, ConfigParams)),
+    GovernanceQuota((&'a CommitteeId, &'a VotePlanSubmissions)),
 }
 
 #[derive(Clone)]
@@ -81,6 +119,9 @@ pub enum EntryOwned {
     StakePool((crate::certificate::PoolId, crate::stake::PoolState)),
     LeaderParticipation((crate::certificate::PoolId, u32)),
     VotePlan(VotePlan),
+    Name((Name, NameRecord)),
+    EpochSettings((Epoch, ConfigParams)),
+    GovernanceQuota((CommitteeId, VotePlanSubmissions)),
     StopEntry,
 }
 
@@ -125,11 +166,74 @@ impl EntryOwned {
                 Some(Entry::LeaderParticipation((pool_id, participation)))
             }
             EntryOwned::VotePlan(vote_plan) => Some(Entry::VotePlan(vote_plan)),
+            EntryOwned::Name((name, record)) => Some(Entry::Name((name, record))),
+            EntryOwned::EpochSettings((epoch, params)) => {
+                Some(Entry::EpochSettings((*epoch, params.clone())))
+            }
+            EntryOwned::GovernanceQuota((member, submissions)) => {
+                Some(Entry::GovernanceQuota((member, submissions)))
+            }
             EntryOwned::StopEntry => None,
         }
     }
 }
 
+impl<'a> Entry<'a> {
+    /// rank of the kind of the entry in the canonical order
+    pub fn kind_rank(&self) -> u8 {
+        match self {
+            Entry::Globals(_) => 0,
+            Entry::Utxo(_) => 1,
+            Entry::OldUtxo(_) => 2,
+            Entry::Account(_) => 3,
+            Entry::ConfigParam(_) => 4,
+            Entry::UpdateProposal(_) => 5,
+            Entry::MultisigAccount(_) => 6,
+            Entry::MultisigDeclaration(_) => 7,
+            Entry::StakePool(_) => 8,
+            Entry::Pot(_) => 9,
+            Entry::LeaderParticipation(_) => 10,
+            Entry::VotePlan(_) => 11,
+            Entry::Name(_) => 12,
+            Entry::EpochSettings(_) => 13,
+            Entry::GovernanceQuota(_) => 14,
+        }
+    }
+
+    /// compare two entries following the canonical order, see the module
+    /// documentation
+    pub fn canonical_cmp(&self, other: &Entry<'_>) -> Ordering {
+        self.kind_rank()
+            .cmp(&other.kind_rank())
+            .then_with(|| match (self, other) {
+                (Entry::Utxo(a), Entry::Utxo(b)) => {
+                    (a.fragment_id, a.output_index).cmp(&(b.fragment_id, b.output_index))
+                }
+                (Entry::OldUtxo(a), Entry::OldUtxo(b)) => {
+                    (a.fragment_id, a.output_index).cmp(&(b.fragment_id, b.output_index))
+                }
+                (Entry::Account((a, _)), Entry::Account((b, _))) => a.cmp(b),
+                (Entry::UpdateProposal((a, _)), Entry::UpdateProposal((b, _))) => a.cmp(b),
+                (Entry::MultisigAccount((a, _)), Entry::MultisigAccount((b, _))) => a.cmp(b),
+                (Entry::MultisigDeclaration((a, _)), Entry::MultisigDeclaration((b, _))) => {
+                    a.cmp(b)
+                }
+                (Entry::StakePool((a, _)), Entry::StakePool((b, _))) => a.cmp(b),
+                (Entry::LeaderParticipation((a, _)), Entry::LeaderParticipation((b, _))) => {
+                    a.cmp(b)
+                }
+                (Entry::VotePlan(a), Entry::VotePlan(b)) => a.to_id().cmp(&b.to_id()),
+                (Entry::Name((a, _)), Entry::Name((b, _))) => a.cmp(b),
+                (Entry::EpochSettings((a, _)), Entry::EpochSettings((b, _))) => a.cmp(b),
+                (Entry::GovernanceQuota((a, _)), Entry::GovernanceQuota((b, _))) => {
+                    a.as_ref().cmp(b.as_ref())
+                }
+                // globals, config params and pots keep the order of the ledger
+                _ => Ordering::Equal,
+            })
+    }
+}
+
 #[derive(Eq, PartialEq, Clone, Debug)]
 pub struct Globals {
     pub date: BlockDate,
@@ -159,6 +263,9 @@ enum IterState<'a> {
     Pots(pots::Entries<'a>),
     LeaderParticipations(imhamt::HamtIter<'a, crate::certificate::PoolId, u32>),
     VotePlan(imhamt::HamtIter<'a, VotePlanId, VotePlanManager>),
+    Names(imhamt::HamtIter<'a, Name, NameRecord>),
+    EpochSettings(std::slice::Iter<'a, (Epoch, setting::Settings)>),
+    GovernanceQuotas(imhamt::HamtIter<'a, CommitteeId, VotePlanSubmissions>),
     Done,
 }
 
@@ -255,23 +362,77 @@ impl<'a> Iterator for LedgerIterator<'a> {
             },
             IterState::VotePlan(iter) => match iter.next() {
                 None => {
-                    self.state = IterState::Done;
+                    self.state = IterState::Names(self.ledger.names.iter());
                     self.next()
                 }
                 Some((_, plan_manager)) => Some(Entry::VotePlan(plan_manager.plan())),
             },
+            IterState::Names(iter) => match iter.next() {
+                None => {
+                    self.state = IterState::EpochSettings(self.ledger.settings_history.iter());
+                    self.next()
+                }
+                Some(x) => Some(Entry::Name(x)),
+            },
+            IterState::EpochSettings(iter) => match iter.next() {
+                None => {
+                    self.state = IterState::GovernanceQuotas(self.ledger.governance_quotas.iter());
+                    self.next()
+                }
+                Some((epoch, settings)) => {
+                    Some(Entry::EpochSettings((*epoch, settings.to_config_params())))
+                }
+            },
+            IterState::GovernanceQuotas(iter) => match iter.next() {
+                None => {
+                    self.state = IterState::Done;
+                    self.next()
+                }
+                Some(x) => Some(Entry::GovernanceQuota(x)),
+            },
             IterState::Done => None,
         }
     }
 }
 
+/// Iterator over the entries of the ledger in the canonical order, see
+/// [`Ledger::iter_canonical`]
+pub struct CanonicalLedgerIterator<'a>(std::vec::IntoIter<Entry<'a>>);
+
+impl<'a> Iterator for CanonicalLedgerIterator<'a> {
+    type Item = Entry<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a> ExactSizeIterator for CanonicalLedgerIterator<'a> {}
+
 impl Ledger {
+    /// iterate over the entries of the ledger, in the order of the internal
+    /// structures
     pub fn iter(&self) -> LedgerIterator<'_> {
         LedgerIterator {
             ledger: self,
             state: IterState::Initial,
         }
     }
+
+    /// iterate over the entries of the ledger in the canonical order, which
+    /// only depends on the state of the ledger (see the module documentation).
+    ///
+    /// The entries are collected and sorted before the iteration starts.
+    pub fn iter_canonical(&self) -> CanonicalLedgerIterator<'_> {
+        let mut entries: Vec<Entry<'_>> = self.iter().collect();
+        // the sort is stable, the entries without a key keep their order
+        entries.sort_by(|a, b| a.canonical_cmp(b));
+        CanonicalLedgerIterator(entries.into_iter())
+    }
 }
 
 impl<'a> std::iter::FromIterator<Entry<'a>> for Result<Ledger, Error> {
@@ -294,6 +455,9 @@ impl<'a> std::iter::FromIterator<Entry<'a>> for Result<Ledger, Error> {
         #[cfg(feature = "evm")]
         let evm = evm::Ledger::new();
         let token_totals = TokenTotals::default();
+        let mut names = NameRegistry::new();
+        let mut epoch_settings = vec![];
+        let mut governance_quotas = GovernanceQuotas::new();
 
         for entry in iter {
             match entry {
@@ -351,6 +515,15 @@ impl<'a> std::iter::FromIterator<Entry<'a>> for Result<Ledger, Error> {
                         )
                         .unwrap();
                 }
+                Entry::Name((name, record)) => {
+                    names = names.insert_record(name.clone(), record.clone());
+                }
+                Entry::EpochSettings((epoch, params)) => {
+                    epoch_settings.push((epoch, setting::Settings::new().try_apply(&params)?));
+                }
+                Entry::GovernanceQuota((member, submissions)) => {
+                    governance_quotas.set_for(*member, *submissions).unwrap()
+                }
             }
         }
 
@@ -361,6 +534,7 @@ impl<'a> std::iter::FromIterator<Entry<'a>> for Result<Ledger, Error> {
             oldutxos: oldutxos.into_iter().collect(),
             accounts: accounts.into_iter().collect(),
             settings: setting::Settings::new().try_apply(&config_params)?,
+            settings_history: SettingsHistory::from_entries(epoch_settings),
             updates,
             multisig: multisig::Ledger::restore(multisig_accounts, multisig_declarations),
             delegation,
@@ -372,9 +546,16 @@ impl<'a> std::iter::FromIterator<Entry<'a>> for Result<Ledger, Error> {
             leaders_log,
             votes,
             governance,
+            governance_quotas,
             #[cfg(feature = "evm")]
             evm,
             token_totals,
+            names,
+            account_history: None,
+            #[cfg(feature = "address-screening")]
+            address_screening: None,
+            certificate_extensions: CertificateExtensions::default(),
+            balance_watches: BalanceWatches::default(),
         })
     }
 }
@@ -384,9 +565,10 @@ mod tests {
     use super::*;
     use crate::{
         ledger::{Entry, Ledger},
-        testing::{ConfigBuilder, LedgerBuilder},
+        testing::{data::Wallet, ConfigBuilder, LedgerBuilder},
         value::Value,
     };
+    use chain_core::property::Serialize;
 
     use quickcheck::{Arbitrary, Gen};
 
@@ -476,6 +658,15 @@ mod tests {
                 Entry::VotePlan(plan) => {
                     println!("VotePlan {}", plan.to_id());
                 }
+                Entry::Name((name, record)) => {
+                    println!("Name {} {:?}", name, record);
+                }
+                Entry::EpochSettings((epoch, params)) => {
+                    println!("EpochSettings {} {:?}", epoch, params);
+                }
+                Entry::GovernanceQuota((member, submissions)) => {
+                    println!("GovernanceQuota {} {:?}", member, submissions);
+                }
             }
         }
     }
@@ -496,4 +687,33 @@ mod tests {
 
         assert!(ledger == ledger2)
     }
+
+    #[test]
+    pub fn canonical_iteration_only_depends_on_the_state() {
+        let wallets: Vec<Wallet> = (0..16)
+            .map(|i| Wallet::from_value(Value(100 + i)))
+            .collect();
+        let testledger = LedgerBuilder::from_config(ConfigBuilder::new())
+            .faucets_wallets(wallets.iter().collect())
+            .faucet_value(Value(42000))
+            .build()
+            .expect("cannot build test ledger");
+
+        let ledger = testledger.ledger;
+        // the restored ledger is rebuilt in the order of hash maps, its
+        // internal layout may differ from the original ledger
+        let restored: Ledger = ledger.iter().collect::<Result<_, _>>().unwrap();
+
+        let entries: Vec<_> = ledger.iter_canonical().collect();
+        assert_eq!(entries.len(), ledger.iter().count());
+        assert!(entries
+            .windows(2)
+            .all(|pair| pair[0].canonical_cmp(&pair[1]) != Ordering::Greater));
+
+        let mut bytes = Vec::new();
+        ledger.serialize(&mut bytes).unwrap();
+        let mut restored_bytes = Vec::new();
+        restored.serialize(&mut restored_bytes).unwrap();
+        assert_eq!(bytes, restored_bytes);
+    }
 }
@@ -50,6 +50,11 @@ impl LeadersParticipationRecord {
         Ok(())
     }
 
+    /// Number of blocks created by a pool, 0 if the pool has no record
+    pub fn blocks_for(&self, pool: &PoolId) -> u32 {
+        self.log.lookup(pool).copied().unwrap_or(0)
+    }
+
     /// Iterate over all known pool record
     pub fn iter(&self) -> HamtIter<'_, PoolId, u32> {
         self.log.iter()
@@ -174,6 +179,19 @@ mod tests {
         verify_total(&leaders_participation_record, 11);
     }
 
+    #[test]
+    pub fn test_blocks_for() {
+        let stake_pool_id = new_stake_pool_id();
+
+        let leaders_participation_record = create_log(vec![(stake_pool_id.clone(), 3)]);
+
+        assert_eq!(3, leaders_participation_record.blocks_for(&stake_pool_id));
+        assert_eq!(
+            0,
+            leaders_participation_record.blocks_for(&new_stake_pool_id())
+        );
+    }
+
     fn create_log(records: Vec<(PoolId, u32)>) -> LeadersParticipationRecord {
         let mut leaders_participation_record = LeadersParticipationRecord::new();
         for (pool_id, count) in records.iter() {
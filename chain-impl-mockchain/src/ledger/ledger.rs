@@ -1,25 +1,48 @@
 //! Mockchain ledger. Ledger exists in order to update the
 //! current state and verify transactions.
 
+use super::account_history::AccountHistory;
+use super::balance_watch::BalanceWatches;
+use super::block0_supply::Block0Supply;
 use super::check::{self, TxValidityError, TxVerifyError};
+use super::epoch_transition::{EpochTransitionReport, EpochTransitionStage};
+use super::events::{
+    BlockRewardSummary, LedgerEvent, LedgerEvents, PledgeForfeitReport, PoolPerformanceReport,
+    RewardAccountFallbackReport, VotePlanCancelReport,
+};
 #[cfg(feature = "evm")]
 use super::evm;
+use super::extensions::{CertificateExtensions, ExtensionRejection};
+use super::finality::FinalityReport;
 use super::governance::{Governance, ParametersGovernanceAction, TreasuryGovernanceAction};
+use super::governance_quota::{GovernanceQuota, GovernanceQuotas};
+use super::input_resolution::{spend_account, spend_multisig, spend_utxo};
 use super::leaderlog::LeadersParticipationRecord;
+use super::metrics::{measure_fragment, LedgerMetrics, NoopMetrics};
+use super::parameter_sanity::ParameterSanityReport;
+use super::pool_rewards::{compute_pool_rewards, PoolRewardsParams, RewardsComputation};
 use super::pots::Pots;
 use super::reward_info::{EpochRewardsInfo, RewardsInfoParameters};
+#[cfg(feature = "address-screening")]
+use super::screening::{ScreeningFlow, ScreeningHook, ScreeningVeto};
+use super::settings_history::SettingsHistory;
 use super::token_distribution::{TokenDistribution, TokenTotals};
+use super::witness_failure::{self, WitnessContext, WitnessFailure};
 
 use crate::certificate::MintToken;
 use crate::chaineval::HeaderContentEvalContext;
 use crate::chaintypes::{ChainLength, ConsensusType, HeaderId};
 use crate::config::{self, ConfigParam};
 use crate::date::{BlockDate, Epoch};
-use crate::fee::{FeeAlgorithm, LinearFee};
+use crate::fee::{FeeAlgorithm, FeeSchedule, LinearFee};
 use crate::fragment::{BlockContentHash, BlockContentSize, Contents, Fragment, FragmentId};
+use crate::header::{BlockProducer, Header};
+use crate::key::BftLeaderId;
+use crate::milli::Milli;
+use crate::names::{Name, NameRegistry, NameRegistryError};
 use crate::rewards;
 use crate::setting::ActiveSlotsCoeffError;
-use crate::stake::{PercentStake, PoolError, PoolStakeInformation, PoolsState, StakeDistribution};
+use crate::stake::{PoolError, PoolPerformance, PoolsState, Stake, StakeDistribution};
 use crate::tokens::identifier::TokenIdentifier;
 use crate::tokens::minting_policy::MintingPolicyViolation;
 use crate::transaction::*;
@@ -29,18 +52,19 @@ use crate::vote::{CommitteeId, VotePlanLedger, VotePlanLedgerError, VotePlanStat
 use crate::{account, certificate, legacy, multisig, setting, stake, update, utxo};
 use crate::{
     certificate::{
-        BftLeaderBindingSignature, OwnerStakeDelegation, PoolId, UpdateProposal, UpdateProposalId,
-        UpdateVote, VoteAction, VoteCast, VotePlan,
+        BftLeaderBindingSignature, CertificateKind, OwnerStakeDelegation, PoolId, SnapshotVoteCast,
+        UpdateProposal, UpdateProposalId, UpdateVote, VoteAction, VoteCast, VoteCastBatch,
+        VotePlan, VotePlanId,
     },
     chaineval::ConsensusEvalContext,
 };
-use chain_addr::{Address, Discrimination, Kind};
+use chain_addr::{Address, Discrimination, Kind, KindType};
 use chain_crypto::Verification;
 use chain_time::{Epoch as TimeEpoch, SlotDuration, TimeEra, TimeFrame, Timeline};
 use std::collections::HashSet;
 use std::mem::swap;
 use std::sync::Arc;
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, Instant, SystemTime};
 use thiserror::Error;
 
 // static parameters, effectively this is constant in the parameter of the blockchain
@@ -72,6 +96,10 @@ pub struct LedgerParameters {
     pub fees_goes_to: setting::FeesGoesTo,
     /// List of committee members
     pub committees: Arc<[CommitteeId]>,
+    /// Minimum pledge of the stake pools
+    pub minimum_pool_pledge: Value,
+    /// Scale the rewards of the pools by their performance over the epoch
+    pub pool_performance_rewards: bool,
 }
 
 /// Overall ledger structure.
@@ -81,12 +109,13 @@ pub struct LedgerParameters {
 ///
 /// The ledger can be easily and cheaply cloned despite containing reference
 /// to a lot of data (millions of utxos, thousands of accounts, ..)
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone)]
 pub struct Ledger {
     pub(crate) utxos: utxo::Ledger<Address>,
     pub(crate) oldutxos: utxo::Ledger<legacy::OldAddress>,
     pub(crate) accounts: account::Ledger,
     pub(crate) settings: setting::Settings,
+    pub(crate) settings_history: SettingsHistory,
     pub(crate) updates: update::UpdateState,
     pub(crate) multisig: multisig::Ledger,
     pub(crate) delegation: PoolsState,
@@ -98,9 +127,16 @@ pub struct Ledger {
     pub(crate) leaders_log: LeadersParticipationRecord,
     pub(crate) votes: VotePlanLedger,
     pub(crate) governance: Governance,
+    pub(crate) governance_quotas: GovernanceQuotas,
     #[cfg(feature = "evm")]
     pub(crate) evm: evm::Ledger,
     pub(crate) token_totals: TokenTotals,
+    pub(crate) names: NameRegistry,
+    pub(crate) account_history: Option<AccountHistory>,
+    #[cfg(feature = "address-screening")]
+    pub(crate) address_screening: Option<ScreeningHook>,
+    pub(crate) certificate_extensions: CertificateExtensions,
+    pub(crate) balance_watches: BalanceWatches,
 }
 
 #[derive(Debug, Clone)]
@@ -108,8 +144,86 @@ pub struct ApplyBlockLedger {
     ledger: Ledger,
     ledger_params: LedgerParameters,
     block_date: BlockDate,
+    initial_fees: Value,
+    events: LedgerEvents,
+    /// the balances of the watched accounts before the block
+    watched_balances: Vec<(account::Identifier, Value)>,
+}
+
+/// The record of a block producer in the ledger, see
+/// [`Ledger::resolve_producer`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProducerRecord<'a> {
+    /// a BFT leader of the settings, with its index in the leaders
+    BftLeader {
+        leader_id: &'a BftLeaderId,
+        index: usize,
+    },
+    Pool(&'a stake::PoolState),
+}
+
+// The account history, the address screening, the certificate extensions and
+// the balance watches are options of the node running the ledger: they are
+// not part of its state, are not serialized, and two ledgers differing only by
+// them are equal.
+impl PartialEq for Ledger {
+    fn eq(&self, other: &Self) -> bool {
+        let Ledger {
+            utxos,
+            oldutxos,
+            accounts,
+            settings,
+            settings_history,
+            updates,
+            multisig,
+            delegation,
+            static_params,
+            date,
+            chain_length,
+            era,
+            pots,
+            leaders_log,
+            votes,
+            governance,
+            governance_quotas,
+            #[cfg(feature = "evm")]
+            evm,
+            token_totals,
+            names,
+            account_history: _,
+            #[cfg(feature = "address-screening")]
+                address_screening: _,
+            certificate_extensions: _,
+            balance_watches: _,
+        } = self;
+        #[cfg(feature = "evm")]
+        if evm != &other.evm {
+            return false;
+        }
+        utxos == &other.utxos
+            && oldutxos == &other.oldutxos
+            && accounts == &other.accounts
+            && settings == &other.settings
+            && settings_history == &other.settings_history
+            && updates == &other.updates
+            && multisig == &other.multisig
+            && delegation == &other.delegation
+            && static_params == &other.static_params
+            && date == &other.date
+            && chain_length == &other.chain_length
+            && era == &other.era
+            && pots == &other.pots
+            && leaders_log == &other.leaders_log
+            && votes == &other.votes
+            && governance == &other.governance
+            && governance_quotas == &other.governance_quotas
+            && token_totals == &other.token_totals
+            && names == &other.names
+    }
 }
 
+impl Eq for Ledger {}
+
 // Dummy implementation of Debug for Ledger
 impl std::fmt::Debug for Ledger {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -173,6 +287,29 @@ pub enum Block0Error {
     HasVoteCast,
     #[error("Vote tallying are not valid in the block0")]
     HasVoteTally,
+    #[error("Transaction batches are not valid in the block0")]
+    HasTransactionBatch,
+    #[error("Name certificates are not valid in the block0")]
+    HasNameCertificate,
+    #[error("Voting power snapshots are not valid in the block0")]
+    HasVotingPowerSnapshot,
+    #[error("Vote plan cancellations are not valid in the block0")]
+    HasVotePlanCancel,
+    #[error("Legacy migrations are not valid in the block0")]
+    HasLegacyMigration,
+    #[error("Total value of the treasury and the reward pot is too big")]
+    PotsTotalValueTooBig,
+    #[error("The treasury ({treasury}) and the reward pot ({rewards}) exceed the total supply ({total_supply})")]
+    PotsExceedTotalSupply {
+        treasury: Value,
+        rewards: Value,
+        total_supply: Value,
+    },
+    #[error("The initial funds ({initial_funds}) exceed the circulating supply ({circulating_supply}) left by the treasury and the reward pot")]
+    InitialFundsExceedCirculatingSupply {
+        initial_funds: Value,
+        circulating_supply: Value,
+    },
 }
 
 pub type OutputOldAddress = Output<legacy::OldAddress>;
@@ -245,6 +382,12 @@ pub enum Error {
     AccountIdentifierInvalid,
     #[error("Invalid discrimination")]
     InvalidDiscrimination,
+    #[error("Invalid {witness:?} witness of the input {index}: {failure}")]
+    InputWitnessInvalid {
+        index: usize,
+        witness: WitnessKind,
+        failure: WitnessFailure,
+    },
     #[error("Expected an account witness")]
     ExpectingAccountWitness,
     #[error("Expected a UTxO witness")]
@@ -259,6 +402,10 @@ pub enum Error {
     OwnerStakeDelegationInvalidTransaction,
     #[error("Transaction for VoteCast is invalid. expecting 1 input, 1 witness and 0 output")]
     VoteCastInvalidTransaction,
+    #[error(
+        "Transaction for a name certificate is invalid. expecting 1 input, 1 witness and 0 output"
+    )]
+    NameCertificateInvalidTransaction,
     #[error("Wrong chain length, expected {expected} but received {actual}")]
     WrongChainLength {
         actual: ChainLength,
@@ -280,6 +427,11 @@ pub enum Error {
     IncompleteLedger,
     #[error("Ledger pot value invalid: {error}")]
     PotValueInvalid { error: ValueError },
+    #[error("Value of the {part} overflowed: {overflow}")]
+    ValueSumOverflow {
+        part: &'static str,
+        overflow: SumOverflow,
+    },
     #[error("Pool registration with no owner")]
     PoolRegistrationHasNoOwner,
     #[error("Pool registration with too many owners")]
@@ -290,6 +442,12 @@ pub enum Error {
     PoolRegistrationManagementThresholdZero,
     #[error("Pool registration management threshold above owners")]
     PoolRegistrationManagementThresholdAbove,
+    #[error("Pool registration pledge {pledge} is below the minimum pledge {minimum}")]
+    PoolRegistrationPledgeBelowMinimum { pledge: Value, minimum: Value },
+    #[error("Pool registration reward address has the wrong discrimination")]
+    PoolRegistrationRewardAddressInvalidDiscrimination,
+    #[error("Pool registration reward address of kind {kind:?} cannot receive rewards")]
+    PoolRegistrationRewardAddressInvalidKind { kind: KindType },
     #[error("Pool Update not allowed yet")]
     PoolUpdateNotAllowedYet,
     #[error("Stake Delegation payload signature failed")]
@@ -302,10 +460,20 @@ pub enum Error {
     VotePlanProofInvalidCommittee,
     #[error("Vote plan contains proposal(s) that does not pass governance criteria")]
     VotePlanInvalidGovernanceParameters,
+    #[error("Vote plan has {proposals} proposals, above the limit of {limit}")]
+    VotePlanTooManyProposals { proposals: usize, limit: u32 },
+    #[error("Committee member {member} already submitted {limit} vote plans in this epoch")]
+    VotePlanQuotaExceeded { member: CommitteeId, limit: u32 },
     #[error("Vote Tally Proof failed")]
     VoteTallyProofFailed,
     #[error("Vote tally decryption failed")]
     VoteTallyDecryptionFailed,
+    #[error("Encrypted vote tally fragments are replaced by the private vote tally since the protocol version {since}")]
+    EncryptedVoteTallyDeprecated { since: u32 },
+    #[error("Voting power snapshot proof failed")]
+    VotingPowerSnapshotProofFailed,
+    #[error("Vote plan cancellation proof failed")]
+    VotePlanCancelProofFailed,
     #[error("Pool update payload signature failed")]
     PoolUpdateSignatureFailed,
     #[error("Pool update last known registration hash doesn't match")]
@@ -324,14 +492,59 @@ pub enum Error {
     UpdateVoteSignatureFailed,
     #[error("minting policy violation")]
     MintingPolicyViolation(#[from] MintingPolicyViolation),
+    #[error("Name registry error")]
+    NameRegistry(#[from] NameRegistryError),
     #[error("evm transactions are disabled, the node was built without the 'evm' feature")]
     DisabledEvmTransactions,
+    #[error("Legacy migration with a public key not matching the legacy address")]
+    LegacyMigrationInvalidPublicKey { old_address: legacy::OldAddress },
+    #[error("Legacy migration with invalid signature")]
+    LegacyMigrationInvalidSignature { old_address: legacy::OldAddress },
+    #[error("Legacy migration of an address without any legacy funds")]
+    LegacyMigrationNoFunds { old_address: legacy::OldAddress },
+    #[error("The legacy funds ({funds}) do not cover the fee of the migration ({fee})")]
+    LegacyMigrationFundsBelowFee { funds: Value, fee: Value },
+    #[error("{kind} certificates are disabled on this chain")]
+    CertificateDisabled { kind: CertificateKind },
+    #[error("No extension is registered for the certificate tag {tag}")]
+    UnknownCertificateExtension { tag: u8 },
+    #[error("The certificate of the extension '{name}' (tag {tag}) is malformed")]
+    ExtensionCertificateMalformed { tag: u8, name: &'static str },
+    #[error("The certificate is rejected by the extension '{name}' (tag {tag}): {rejection}")]
+    ExtensionCertificateRejected {
+        tag: u8,
+        name: &'static str,
+        rejection: ExtensionRejection,
+    },
     #[cfg(feature = "evm")]
     #[error("evm transaction error")]
     EvmTransactionError(#[from] chain_evm::machine::Error),
+    #[cfg(feature = "address-screening")]
+    #[error("The {flow} {index} is vetoed by the address screening: {veto}")]
+    AddressScreeningVeto {
+        flow: ScreeningFlow,
+        index: usize,
+        veto: ScreeningVeto,
+    },
 }
 
 impl LedgerParameters {
+    pub(crate) fn from_settings(settings: &setting::Settings) -> Self {
+        LedgerParameters {
+            fees: settings.linear_fees,
+            treasury_tax: settings
+                .treasury_params
+                .unwrap_or_else(rewards::TaxType::zero),
+            reward_params: settings.to_reward_params(),
+            block_content_max_size: settings.block_content_max_size,
+            epoch_stability_depth: settings.epoch_stability_depth,
+            fees_goes_to: settings.fees_goes_to,
+            committees: settings.committees.clone(),
+            minimum_pool_pledge: settings.minimum_pool_pledge,
+            pool_performance_rewards: settings.pool_performance_rewards,
+        }
+    }
+
     pub fn treasury_tax(&self) -> rewards::TaxType {
         self.treasury_tax
     }
@@ -349,6 +562,7 @@ impl Ledger {
             oldutxos: utxo::Ledger::new(),
             accounts: account::Ledger::new(),
             settings,
+            settings_history: SettingsHistory::new(),
             updates: update::UpdateState::new(),
             multisig: multisig::Ledger::new(),
             delegation: PoolsState::new(),
@@ -360,13 +574,35 @@ impl Ledger {
             leaders_log: LeadersParticipationRecord::new(),
             votes: VotePlanLedger::new(),
             governance: Governance::default(),
+            governance_quotas: GovernanceQuotas::new(),
             #[cfg(feature = "evm")]
             evm: evm::Ledger::new(),
             token_totals: TokenTotals::default(),
+            names: NameRegistry::new(),
+            account_history: None,
+            #[cfg(feature = "address-screening")]
+            address_screening: None,
+            certificate_extensions: CertificateExtensions::default(),
+            balance_watches: BalanceWatches::default(),
         }
     }
 
     pub fn new<'a, I>(block0_initial_hash: HeaderId, contents: I) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = &'a Fragment>,
+    {
+        Self::new_with(
+            block0_initial_hash,
+            contents,
+            CertificateExtensions::default(),
+        )
+    }
+
+    pub(super) fn new_with<'a, I>(
+        block0_initial_hash: HeaderId,
+        contents: I,
+        certificate_extensions: CertificateExtensions,
+    ) -> Result<Self, Error>
     where
         I: IntoIterator<Item = &'a Fragment>,
     {
@@ -378,13 +614,14 @@ impl Ledger {
             None => Err(Error::Block0(Block0Error::InitialMessageMissing)),
         }?;
 
-        let mut ledger = {
+        let (mut ledger, total_supply) = {
             let mut regular_ents = crate::fragment::ConfigParams::new();
             let mut block0_start_time = None;
             let mut slot_duration = None;
             let mut discrimination = None;
             let mut slots_per_epoch = None;
             let mut kes_update_speed = None;
+            let mut total_supply = None;
             let mut pots = Pots::zero();
 
             for param in init_ents.iter() {
@@ -410,6 +647,9 @@ impl Ledger {
                     ConfigParam::RewardPot(v) => {
                         pots.rewards = *v;
                     }
+                    ConfigParam::TotalSupply(v) => {
+                        total_supply = Some(*v);
+                    }
                     _ => regular_ents.push(param.clone()),
                 }
             }
@@ -447,8 +687,12 @@ impl Ledger {
                     Block0Error::InitialMessageNoConsensusLeaderId,
                 ));
             }
-            Ledger::empty(settings, static_params, era, pots)
+            (
+                Ledger::empty(settings, static_params, era, pots),
+                total_supply,
+            )
         };
+        ledger.certificate_extensions = certificate_extensions;
 
         let params = ledger.get_ledger_parameters();
 
@@ -481,6 +725,16 @@ impl Ledger {
                     check::valid_block0_cert_transaction(&tx)?;
                     ledger = ledger.apply_stake_delegation(&tx.payload().into_payload())?;
                 }
+                Fragment::AmountStakeDelegation(tx) => {
+                    let tx = tx.as_slice();
+                    check::valid_block0_cert_transaction(&tx)?;
+                    ledger = ledger.apply_amount_stake_delegation(&tx.payload().into_payload())?;
+                }
+                Fragment::RewardsRedelegation(tx) => {
+                    let tx = tx.as_slice();
+                    check::valid_block0_cert_transaction(&tx)?;
+                    ledger = ledger.apply_rewards_redelegation(&tx.payload().into_payload())?;
+                }
                 Fragment::PoolRegistration(tx) => {
                     let tx = tx.as_slice();
                     check::valid_block0_cert_transaction(&tx)?;
@@ -506,9 +760,17 @@ impl Ledger {
                         tx.payload_auth().into_payload_auth(),
                     )?;
                 }
-                Fragment::VoteCast(_) => {
+                Fragment::VoteCast(_)
+                | Fragment::SnapshotVoteCast(_)
+                | Fragment::VoteCastBatch(_) => {
                     return Err(Error::Block0(Block0Error::HasVoteCast));
                 }
+                Fragment::VotingPowerSnapshot(_) => {
+                    return Err(Error::Block0(Block0Error::HasVotingPowerSnapshot));
+                }
+                Fragment::VotePlanCancel(_) => {
+                    return Err(Error::Block0(Block0Error::HasVotePlanCancel));
+                }
                 Fragment::VoteTally(_) => {
                     return Err(Error::Block0(Block0Error::HasVoteTally));
                 }
@@ -532,10 +794,38 @@ impl Ledger {
                         return Err(Error::DisabledEvmTransactions);
                     }
                 }
+                Fragment::TransactionBatch(_) => {
+                    return Err(Error::Block0(Block0Error::HasTransactionBatch));
+                }
+                Fragment::NameClaim(_) | Fragment::NameRelease(_) => {
+                    return Err(Error::Block0(Block0Error::HasNameCertificate));
+                }
+                Fragment::LegacyMigration(_) => {
+                    return Err(Error::Block0(Block0Error::HasLegacyMigration));
+                }
+                Fragment::ExtensionCertificate(tx) => {
+                    let tx = tx.as_slice();
+                    check::valid_block0_cert_transaction(&tx)?;
+                    ledger.apply_extension_certificate(&fragment_id, ledger.date(), &tx)?;
+                }
             }
         }
 
         ledger.validate_utxo_total_value()?;
+        let pots = ledger
+            .pots
+            .total_value()
+            .map_err(|overflow| Error::ValueSumOverflow {
+                part: "pots",
+                overflow,
+            })?;
+        Block0Supply {
+            total_supply,
+            treasury: ledger.pots.treasury_value(),
+            rewards: ledger.pots.rewards,
+            initial_funds: (ledger.get_total_value()? - pots)?,
+        }
+        .validate()?;
         Ok(ledger)
     }
 
@@ -569,11 +859,38 @@ impl Ledger {
         ledger_params: &LedgerParameters,
         rewards_info_params: RewardsInfoParameters,
     ) -> Result<(Self, EpochRewardsInfo), Error> {
+        self.distribute_rewards_with_events(distribution, ledger_params, rewards_info_params)
+            .map(|(ledger, rewards_info, _)| (ledger, rewards_info))
+    }
+
+    /// Same as [`Ledger::distribute_rewards`], also returning the events
+    /// emitted for the epoch
+    pub fn distribute_rewards_with_events(
+        &self,
+        distribution: &StakeDistribution,
+        ledger_params: &LedgerParameters,
+        rewards_info_params: RewardsInfoParameters,
+    ) -> Result<(Self, EpochRewardsInfo, LedgerEvents), Error> {
+        self.distribute_rewards_using(
+            distribution,
+            ledger_params,
+            rewards_info_params,
+            RewardsComputation::default(),
+        )
+    }
+
+    pub(crate) fn distribute_rewards_using(
+        &self,
+        distribution: &StakeDistribution,
+        ledger_params: &LedgerParameters,
+        rewards_info_params: RewardsInfoParameters,
+        computation: RewardsComputation,
+    ) -> Result<(Self, EpochRewardsInfo, LedgerEvents), Error> {
         let mut new_ledger = self.clone();
         let mut rewards_info = EpochRewardsInfo::new(rewards_info_params);
 
         if self.leaders_log.total() == 0 {
-            return Ok((new_ledger, rewards_info));
+            return Ok((new_ledger, rewards_info, LedgerEvents::new()));
         }
 
         let treasury_initial_value = new_ledger.pots.treasury_value();
@@ -583,8 +900,14 @@ impl Ledger {
 
         let epoch = new_ledger.date.epoch + 1;
 
+        let total_stake =
+            Stake::checked_sum(distribution.to_pools.values().map(|pool| pool.stake.total))
+                .map_err(|overflow| Error::ValueSumOverflow {
+                    part: "stake distribution",
+                    overflow: overflow.map(|stake| Value(stake.0)),
+                })?;
         let system_info = rewards::SystemInformation {
-            declared_stake: distribution.get_total_stake(),
+            declared_stake: total_stake,
         };
 
         let expected_epoch_reward = rewards::rewards_contribution_calculation(
@@ -608,7 +931,13 @@ impl Ledger {
         // to the treasury directly
         match ledger_params.fees_goes_to {
             setting::FeesGoesTo::Rewards => {
-                total_reward = (total_reward + new_ledger.pots.siphon_fees()).unwrap();
+                let fees = new_ledger.pots.siphon_fees();
+                total_reward = Value::checked_sum([total_reward, fees]).map_err(|overflow| {
+                    Error::ValueSumOverflow {
+                        part: "epoch rewards",
+                        overflow,
+                    }
+                })?;
             }
             setting::FeesGoesTo::Treasury => {
                 let fees = new_ledger.pots.siphon_fees();
@@ -623,10 +952,33 @@ impl Ledger {
             treasury_distr.after_tax
         };
 
+        let mut events = LedgerEvents::new();
+
         // distribute the rest to all leaders now
         let mut leaders_log = LeadersParticipationRecord::new();
         swap(&mut new_ledger.leaders_log, &mut leaders_log);
 
+        // record the performance of all the pools with stake for the epoch,
+        // including the ones which did not create any block
+        for (pool_id, pool_distribution) in distribution.to_pools.iter() {
+            let performance = PoolPerformance {
+                epoch: self.date.epoch,
+                expected: expected_blocks(
+                    self.era.slots_per_epoch(),
+                    &self.settings,
+                    pool_distribution.stake.total,
+                    total_stake,
+                ),
+                produced: leaders_log.blocks_for(pool_id),
+            };
+            rewards_info.set_performance(pool_id, performance);
+            if pool_distribution.registration.is_some() {
+                new_ledger
+                    .delegation
+                    .stake_pool_set_performance(pool_id, performance)?;
+            }
+        }
+
         if total_reward > Value::zero() {
             // pool capping only exists if there's enough participants
             let pool_capper = match ledger_params.reward_params.pool_participation_capping {
@@ -644,33 +996,60 @@ impl Ledger {
             let total_blocks = leaders_log.total();
             let reward_unit = total_reward.split_in(total_blocks);
 
-            for (pool_id, pool_blocks) in leaders_log.iter() {
-                // possibly cap the reward for a given pool.
-                // if this is capped, then the overflow amount is send to treasury
-                let pool_total_reward_uncapped = reward_unit.parts.scale(*pool_blocks).unwrap();
-                let pool_total_reward = match pool_capper {
-                    None => pool_total_reward_uncapped,
-                    Some(pool_cap) => {
-                        let actual_pool_total = std::cmp::min(pool_cap, pool_total_reward_uncapped);
-                        let forfeited = (pool_total_reward_uncapped - actual_pool_total).unwrap();
-                        new_ledger.pots.treasury_add(forfeited)?;
-                        actual_pool_total
-                    }
-                };
-
-                match distribution.to_pools.get(pool_id) {
-                    Some(pool_distribution) => {
-                        new_ledger.distribute_poolid_rewards(
-                            &mut rewards_info,
-                            epoch,
-                            pool_id,
-                            pool_total_reward,
-                            pool_distribution,
-                        )?;
-                    }
-                    _ => {
-                        // dump reward to treasury
-                        new_ledger.pots.treasury_add(pool_total_reward)?;
+            let params = PoolRewardsParams {
+                reward_unit: reward_unit.parts,
+                pool_cap: pool_capper,
+                pool_performance_rewards: ledger_params.pool_performance_rewards,
+                minimum_pool_pledge: ledger_params.minimum_pool_pledge,
+            };
+            let leaders: Vec<(PoolId, u32)> = leaders_log
+                .iter()
+                .map(|(pool_id, pool_blocks)| (pool_id.clone(), *pool_blocks))
+                .collect();
+            let pools_rewards = compute_pool_rewards(
+                computation,
+                &params,
+                &leaders,
+                &rewards_info.performances,
+                distribution,
+            )?;
+
+            // apply the rewards of the pools in the order of the leaders log
+            for ((pool_id, _), pool_rewards) in leaders.iter().zip(pools_rewards) {
+                new_ledger.pots.treasury_add(pool_rewards.treasury)?;
+                if let Some(pledge_not_met) = pool_rewards.pledge_not_met {
+                    rewards_info.set_pledge_not_met(pool_id, pledge_not_met);
+                    events.push(LedgerEvent::PledgeForfeit(PledgeForfeitReport {
+                        pool_id: pool_id.clone(),
+                        pledge_not_met,
+                    }));
+                }
+                if let Some((taxed, after_tax)) = pool_rewards.stake_pool {
+                    rewards_info.set_stake_pool(pool_id, taxed, after_tax);
+                    new_ledger
+                        .delegation
+                        .stake_pool_set_rewards(pool_id, epoch, taxed, after_tax)?;
+                }
+                for (account, value) in pool_rewards.accounts {
+                    new_ledger.accounts =
+                        new_ledger
+                            .accounts
+                            .add_rewards_to_account(&account, epoch, value, ())?;
+                    rewards_info.add_to_account(&account, value);
+                }
+                if let Some((reward_account, value)) = pool_rewards.reward_account {
+                    if new_ledger.pay_reward_account(pool_id, epoch, &reward_account, value)? {
+                        rewards_info.set_reward_account(pool_id, reward_account, value);
+                    } else {
+                        // the reward account is stale, the rewards go to the treasury
+                        new_ledger.pots.treasury_add(value)?;
+                        events.push(LedgerEvent::RewardAccountFallback(
+                            RewardAccountFallbackReport {
+                                pool_id: pool_id.clone(),
+                                reward_account,
+                                value,
+                            },
+                        ));
                     }
                 }
             }
@@ -685,95 +1064,82 @@ impl Ledger {
             (new_ledger.pots.treasury_value() - treasury_initial_value).unwrap();
         rewards_info.set_treasury(treasury_added_value);
 
-        Ok((new_ledger, rewards_info))
+        for (pool_id, performance) in rewards_info.performances.iter() {
+            events.push(LedgerEvent::PoolPerformance(PoolPerformanceReport {
+                pool_id: pool_id.clone(),
+                performance: *performance,
+            }));
+        }
+
+        Ok((new_ledger, rewards_info, events))
     }
 
-    fn distribute_poolid_rewards(
+    /// Pay the rewards of the owners of a pool to its reward account, which
+    /// is not a single account. A single or group address is paid with a new
+    /// unspent output, identified by the pool and the epoch as no fragment
+    /// pays it. Returns false if the account cannot be credited: a multisig
+    /// account which is not declared, or a script address.
+    fn pay_reward_account(
         &mut self,
-        reward_info: &mut EpochRewardsInfo,
-        epoch: Epoch,
         pool_id: &PoolId,
-        total_reward: Value,
-        distribution: &PoolStakeInformation,
-    ) -> Result<(), Error> {
-        let reg = match distribution.registration {
-            None => {
-                self.pots.treasury_add(total_reward)?;
-                return Ok(());
+        epoch: Epoch,
+        reward_account: &certificate::RewardAccount,
+        value: Value,
+    ) -> Result<bool, Error> {
+        if value == Value::zero() {
+            return Ok(true);
+        }
+        let address = match reward_account {
+            certificate::RewardAccount::Account(AccountIdentifier::Single(account)) => {
+                self.accounts = self
+                    .accounts
+                    .add_rewards_to_account(account, epoch, value, ())?;
+                return Ok(true);
+            }
+            certificate::RewardAccount::Account(AccountIdentifier::Multi(account)) => {
+                return Ok(self.pay_multisig_reward(account, value));
             }
-            Some(ref reg) => reg,
+            certificate::RewardAccount::Address(address) => address,
         };
-
-        let distr = rewards::tax_cut(total_reward, &reg.rewards).unwrap();
-
-        reward_info.set_stake_pool(pool_id, distr.taxed, distr.after_tax);
-        self.delegation
-            .stake_pool_set_rewards(pool_id, epoch, distr.taxed, distr.after_tax)?;
-
-        // distribute to pool owners (or the reward account)
-        match &reg.reward_account {
-            Some(reward_account) => match reward_account {
-                AccountIdentifier::Single(single_account) => {
-                    self.accounts = self.accounts.add_rewards_to_account(
-                        single_account,
-                        epoch,
-                        distr.taxed,
-                        (),
-                    )?;
-                    reward_info.add_to_account(single_account, distr.taxed);
-                }
-                AccountIdentifier::Multi(_multi_account) => unimplemented!(),
-            },
-            None => {
-                if reg.owners.len() > 1 {
-                    let splitted = distr.taxed.split_in(reg.owners.len() as u32);
-                    for owner in &reg.owners {
-                        let id = owner.clone().into();
-                        self.accounts =
-                            self.accounts
-                                .add_rewards_to_account(&id, epoch, splitted.parts, ())?;
-                        reward_info.add_to_account(&id, splitted.parts);
-                    }
-                    // pool owners 0 get potentially an extra sweetener of value 1 to #owners - 1
-                    if splitted.remaining > Value::zero() {
-                        let id = reg.owners[0].clone().into();
-                        self.accounts = self.accounts.add_rewards_to_account(
-                            &id,
-                            epoch,
-                            splitted.remaining,
-                            (),
-                        )?;
-                        reward_info.add_to_account(&id, splitted.remaining);
+        match address.kind() {
+            Kind::Account(pk) => {
+                self.accounts =
+                    self.accounts
+                        .add_rewards_to_account(&pk.clone().into(), epoch, value, ())?;
+                Ok(true)
+            }
+            Kind::Multisig(account) => Ok(self.pay_multisig_reward(&(*account).into(), value)),
+            Kind::Single(_) | Kind::Group(_, _) => {
+                if let Kind::Group(_, account) = address.kind() {
+                    let account = account.clone().into();
+                    if !self.accounts.exists(&account) {
+                        self.accounts = self.accounts.add_account(&account, Value::zero(), ())?;
                     }
-                } else {
-                    let id = reg.owners[0].clone().into();
-                    self.accounts =
-                        self.accounts
-                            .add_rewards_to_account(&id, epoch, distr.taxed, ())?;
-                    reward_info.add_to_account(&id, distr.taxed);
                 }
+                let fragment_id = FragmentId::hash_bytes(
+                    &[
+                        b"pool-reward".as_ref(),
+                        pool_id.as_ref(),
+                        &epoch.to_be_bytes(),
+                    ]
+                    .concat(),
+                );
+                let output = Output::from_address(address.clone(), value);
+                self.utxos = self.utxos.add(&fragment_id, &[(0, output)])?;
+                Ok(true)
             }
+            Kind::Script(_) => Ok(false),
         }
+    }
 
-        // distribute the rest to delegators
-        let mut leftover_reward = distr.after_tax;
-        if leftover_reward > Value::zero() {
-            for (account, stake) in distribution.stake.accounts.iter() {
-                let ps = PercentStake::new(*stake, distribution.stake.total);
-                let r = ps.scale_value(distr.after_tax);
-                leftover_reward = (leftover_reward - r).unwrap();
-                self.accounts = self
-                    .accounts
-                    .add_rewards_to_account(account, epoch, r, ())?;
-                reward_info.add_to_account(account, r);
+    fn pay_multisig_reward(&mut self, account: &multisig::Identifier, value: Value) -> bool {
+        match self.multisig.add_value(account, value) {
+            Ok(multisig) => {
+                self.multisig = multisig;
+                true
             }
+            Err(_) => false,
         }
-
-        if leftover_reward > Value::zero() {
-            self.pots.treasury_add(leftover_reward)?;
-        }
-
-        Ok(())
     }
 
     pub fn begin_block(
@@ -782,6 +1148,8 @@ impl Ledger {
         block_date: BlockDate,
     ) -> Result<ApplyBlockLedger, Error> {
         let mut new_ledger = self.clone();
+        // taken before the epoch transition, for the rewards to be watched
+        let watched_balances = self.balance_watches.balances(self);
 
         new_ledger.chain_length = self.chain_length.increase();
 
@@ -800,18 +1168,32 @@ impl Ledger {
             });
         }
 
-        // double check that if we had an epoch transition, distribute_rewards has been called
-        if block_date.epoch > new_ledger.date.epoch && self.leaders_log.total() > 0 {
-            panic!("internal error: apply_block called after epoch transition, but distribute_rewards has not been called")
+        // Go through the stages of the epoch transition, see `epoch_transition`
+        let mut events = LedgerEvents::new();
+        if block_date.epoch > new_ledger.date.epoch {
+            let from = new_ledger.date.epoch;
+            for stage in EpochTransitionStage::ALL {
+                new_ledger = new_ledger.apply_epoch_transition_stage(stage, block_date);
+                events.push(LedgerEvent::EpochTransition(EpochTransitionReport {
+                    stage,
+                    from,
+                    to: block_date.epoch,
+                }));
+            }
+            if new_ledger.settings != self.settings {
+                let warnings = new_ledger.parameter_warnings();
+                if !warnings.is_empty() {
+                    events.push(LedgerEvent::ParameterWarnings(ParameterSanityReport {
+                        epoch: block_date.epoch,
+                        warnings,
+                    }));
+                }
+            }
         }
 
-        // Process Update proposals if needed
-        let (updates, settings) =
-            new_ledger
-                .updates
-                .process_proposals(new_ledger.settings, new_ledger.date, block_date);
-        new_ledger.updates = updates;
-        new_ledger.settings = settings;
+        if let Some(report) = FinalityReport::between(self, &new_ledger) {
+            events.push(LedgerEvent::Finality(report));
+        }
 
         #[cfg(feature = "evm")]
         {
@@ -829,8 +1211,11 @@ impl Ledger {
 
         Ok(ApplyBlockLedger {
             ledger_params: new_ledger.get_ledger_parameters(),
+            initial_fees: new_ledger.pots.fees_value(),
             ledger: new_ledger,
             block_date,
+            events,
+            watched_balances,
         })
     }
 
@@ -841,7 +1226,44 @@ impl Ledger {
         contents: &Contents,
         metadata: &HeaderContentEvalContext,
     ) -> Result<Self, Error> {
-        let (content_hash, content_size) = contents.compute_hash_size();
+        self.apply_block_with_events(ledger_params, contents, metadata)
+            .map(|(ledger, _)| ledger)
+    }
+
+    /// Same as [`Ledger::apply_block`], also returning the events emitted
+    /// while applying the block
+    pub fn apply_block_with_events(
+        &self,
+        ledger_params: LedgerParameters,
+        contents: &Contents,
+        metadata: &HeaderContentEvalContext,
+    ) -> Result<(Self, LedgerEvents), Error> {
+        self.apply_block_with_metrics_and_events(ledger_params, contents, metadata, NoopMetrics)
+    }
+
+    /// Same as [`Ledger::apply_block`], reporting the application of the
+    /// block and of each of its fragments to the metrics sink
+    pub fn apply_block_with_metrics<M: LedgerMetrics>(
+        &self,
+        ledger_params: LedgerParameters,
+        contents: &Contents,
+        metadata: &HeaderContentEvalContext,
+        metrics: M,
+    ) -> Result<Self, Error> {
+        self.apply_block_with_metrics_and_events(ledger_params, contents, metadata, metrics)
+            .map(|(ledger, _)| ledger)
+    }
+
+    fn apply_block_with_metrics_and_events<M: LedgerMetrics>(
+        &self,
+        ledger_params: LedgerParameters,
+        contents: &Contents,
+        metadata: &HeaderContentEvalContext,
+        metrics: M,
+    ) -> Result<(Self, LedgerEvents), Error> {
+        let start = metrics.enabled().then(Instant::now);
+        let (content_hash, content_size) =
+            contents.compute_hash_size_with(metadata.content_hash_scheme);
 
         if content_size > ledger_params.block_content_max_size {
             return Err(Error::InvalidContentSize {
@@ -857,17 +1279,37 @@ impl Ledger {
             });
         }
 
+        #[cfg(feature = "tracing")]
+        let _span = super::trace::block_span(metadata, content_size);
+
         let new_block_ledger = self.begin_block(metadata.chain_length, metadata.block_date)?;
 
         #[cfg(feature = "evm")]
         let new_block_ledger = new_block_ledger.update_evm_block(metadata);
 
-        let new_block_ledger = contents
-            .iter()
-            .try_fold(new_block_ledger, |new_block_ledger, fragment| {
-                new_block_ledger.apply_fragment(fragment)
-            })?;
-        Ok(new_block_ledger.finish(&metadata.consensus_eval_context))
+        let new_block_ledger =
+            contents
+                .iter()
+                .try_fold(new_block_ledger, |new_block_ledger, fragment| {
+                    if start.is_some() {
+                        new_block_ledger.apply_fragment_with_metrics(fragment, &metrics)
+                    } else {
+                        new_block_ledger.apply_fragment(fragment)
+                    }
+                })?;
+        let fees = start.map(|_| {
+            new_block_ledger
+                .ledger
+                .pots
+                .fees_value()
+                .checked_sub(new_block_ledger.initial_fees)
+                .unwrap_or_default()
+        });
+        let result = new_block_ledger.finish_with_events(&metadata.consensus_eval_context);
+        if let (Some(start), Some(fees)) = (start, fees) {
+            metrics.block_applied(contents.iter().count(), fees, start.elapsed());
+        }
+        Ok(result)
     }
 
     /// Try to apply a message to the State, and return the new State if successful
@@ -880,9 +1322,30 @@ impl Ledger {
         ledger_params: &LedgerParameters,
         content: &Fragment,
         block_date: BlockDate,
+    ) -> Result<Self, Error> {
+        let mut result = self.apply_fragment_content(ledger_params, content, block_date);
+        if let Ok(new_ledger) = &mut result {
+            new_ledger.record_account_history(self, content, block_date);
+        }
+        #[cfg(feature = "tracing")]
+        super::trace::fragment_applied(self, content, &result);
+        result
+    }
+
+    fn apply_fragment_content(
+        &self,
+        ledger_params: &LedgerParameters,
+        content: &Fragment,
+        block_date: BlockDate,
     ) -> Result<Self, Error> {
         let mut new_ledger = self.clone();
 
+        if let Some(kind) = content.certificate_kind() {
+            if !self.settings.certificate_policy.is_enabled(kind) {
+                return Err(Error::CertificateDisabled { kind });
+            }
+        }
+
         let fragment_id = content.hash();
         match content {
             Fragment::Initial(_) => return Err(Error::Block0OnlyFragmentReceived),
@@ -920,29 +1383,40 @@ impl Ledger {
                 let tx = tx.as_slice();
                 let payload = tx.payload().into_payload();
                 let payload_auth = tx.payload_auth().into_payload_auth();
-                let verified = match payload_auth {
-                    AccountBindingSignature::Single(signature) => {
-                        let account_pk = payload
-                            .account_id
-                            .to_single_account()
-                            .ok_or(Error::AccountIdentifierInvalid)?;
-                        signature
-                            .verify_slice(&account_pk.into(), &tx.transaction_binding_auth_data())
-                    }
-                    AccountBindingSignature::Multi(_) => {
-                        // TODO
-                        Verification::Failed
-                    }
-                };
-
-                if verified == Verification::Failed {
-                    return Err(Error::StakeDelegationSignatureFailed);
-                }
+                verify_delegation_signature(&tx, &payload.account_id, &payload_auth)?;
 
                 let (new_ledger_, _fee) =
                     new_ledger.apply_transaction(&fragment_id, &tx, block_date, ledger_params)?;
                 new_ledger = new_ledger_.apply_stake_delegation(&payload)?;
             }
+            Fragment::AmountStakeDelegation(tx) => {
+                let tx = tx.as_slice();
+                let payload = tx.payload().into_payload();
+                let payload_auth = tx.payload_auth().into_payload_auth();
+                verify_delegation_signature(&tx, &payload.account_id, &payload_auth)?;
+
+                let (new_ledger_, _fee) =
+                    new_ledger.apply_transaction(&fragment_id, &tx, block_date, ledger_params)?;
+                new_ledger = new_ledger_.apply_amount_stake_delegation(&payload)?;
+            }
+            Fragment::RewardsRedelegation(tx) => {
+                let tx = tx.as_slice();
+                let payload = tx.payload().into_payload();
+                let payload_auth = tx.payload_auth().into_payload_auth();
+                verify_delegation_signature(&tx, &payload.account_id, &payload_auth)?;
+
+                let (new_ledger_, _fee) =
+                    new_ledger.apply_transaction(&fragment_id, &tx, block_date, ledger_params)?;
+                new_ledger = new_ledger_.apply_rewards_redelegation(&payload)?;
+            }
+            Fragment::LegacyMigration(migration) => {
+                new_ledger = new_ledger.apply_legacy_migration(
+                    fragment_id,
+                    migration,
+                    block_date,
+                    ledger_params,
+                )?;
+            }
             Fragment::PoolRegistration(tx) => {
                 let tx = tx.as_slice();
                 let (new_ledger_, _fee) =
@@ -1036,6 +1510,52 @@ impl Ledger {
                 new_ledger =
                     new_ledger_.apply_vote_cast(account_id, tx.payload().into_payload())?;
             }
+            Fragment::SnapshotVoteCast(tx) => {
+                let tx = tx.as_slice();
+                // this is a lightweight check, do this early to avoid doing any unnecessary computation
+                check::valid_vote_cast(&tx)?;
+                let account_id = vote_cast_voter(&tx)?;
+                let (new_ledger_, _fee) =
+                    new_ledger.apply_transaction(&fragment_id, &tx, block_date, ledger_params)?;
+
+                new_ledger = new_ledger_
+                    .apply_snapshot_vote_cast(account_id, tx.payload().into_payload())?;
+            }
+            Fragment::VoteCastBatch(tx) => {
+                let tx = tx.as_slice();
+                // this is a lightweight check, do this early to avoid doing any unnecessary computation
+                check::valid_vote_cast(&tx)?;
+                let account_id = vote_cast_voter(&tx)?;
+                let (new_ledger_, _fee) =
+                    new_ledger.apply_transaction(&fragment_id, &tx, block_date, ledger_params)?;
+
+                new_ledger =
+                    new_ledger_.apply_vote_cast_batch(account_id, tx.payload().into_payload())?;
+            }
+            Fragment::VotingPowerSnapshot(tx) => {
+                let tx = tx.as_slice();
+
+                let (new_ledger_, _fee) =
+                    new_ledger.apply_transaction(&fragment_id, &tx, block_date, ledger_params)?;
+
+                new_ledger = new_ledger_.apply_voting_power_snapshot(
+                    &tx.payload().into_payload(),
+                    &tx.transaction_binding_auth_data(),
+                    tx.payload_auth().into_payload_auth(),
+                )?;
+            }
+            Fragment::VotePlanCancel(tx) => {
+                let tx = tx.as_slice();
+
+                let (new_ledger_, _fee) =
+                    new_ledger.apply_transaction(&fragment_id, &tx, block_date, ledger_params)?;
+
+                new_ledger = new_ledger_.apply_vote_plan_cancel(
+                    &tx.payload().into_payload(),
+                    &tx.transaction_binding_auth_data(),
+                    tx.payload_auth().into_payload_auth(),
+                )?;
+            }
             Fragment::VoteTally(tx) => {
                 let tx = tx.as_slice();
 
@@ -1080,17 +1600,89 @@ impl Ledger {
                     return Err(Error::DisabledEvmTransactions);
                 }
             }
+            Fragment::TransactionBatch(batch) => {
+                let (new_ledger_, _fee) =
+                    new_ledger.apply_transaction_batch(batch, block_date, ledger_params)?;
+                new_ledger = new_ledger_;
+            }
+            Fragment::NameClaim(tx) => {
+                let tx = tx.as_slice();
+                // this is a lightweight check, do this early to avoid doing any unnecessary computation
+                check::valid_name_certificate_transaction(&tx)?;
+                let params = new_ledger
+                    .settings
+                    .name_registry
+                    .ok_or(NameRegistryError::Disabled)?;
+                let claim = tx.payload().into_payload();
+                let owner = name_certificate_owner(&tx)?;
+
+                let (new_ledger_, _fee) = new_ledger.apply_transaction_with_extra_fee(
+                    &fragment_id,
+                    &tx,
+                    block_date,
+                    ledger_params,
+                    params.claim_fee(&claim.name),
+                )?;
+                new_ledger = new_ledger_;
+                new_ledger.names =
+                    new_ledger
+                        .names
+                        .claim(claim.name, owner, block_date.epoch, params.duration)?;
+            }
+            Fragment::NameRelease(tx) => {
+                let tx = tx.as_slice();
+                // this is a lightweight check, do this early to avoid doing any unnecessary computation
+                check::valid_name_certificate_transaction(&tx)?;
+                if new_ledger.settings.name_registry.is_none() {
+                    return Err(NameRegistryError::Disabled.into());
+                }
+                let release = tx.payload().into_payload();
+                let owner = name_certificate_owner(&tx)?;
+
+                let (new_ledger_, _fee) =
+                    new_ledger.apply_transaction(&fragment_id, &tx, block_date, ledger_params)?;
+                new_ledger = new_ledger_;
+                new_ledger.names =
+                    new_ledger
+                        .names
+                        .release(&release.name, &owner, block_date.epoch)?;
+            }
+            Fragment::ExtensionCertificate(tx) => {
+                let tx = tx.as_slice();
+
+                let (new_ledger_, _fee) =
+                    new_ledger.apply_transaction(&fragment_id, &tx, block_date, ledger_params)?;
+                new_ledger = new_ledger_;
+                new_ledger.apply_extension_certificate(&fragment_id, block_date, &tx)?;
+            }
         }
 
         Ok(new_ledger)
     }
 
     pub fn apply_transaction<'a, Extra>(
+        self,
+        fragment_id: &FragmentId,
+        tx: &TransactionSlice<'a, Extra>,
+        cur_date: BlockDate,
+        dyn_params: &LedgerParameters,
+    ) -> Result<(Self, Value), Error>
+    where
+        Extra: Payload,
+        LinearFee: FeeAlgorithm,
+    {
+        self.apply_transaction_with_extra_fee(fragment_id, tx, cur_date, dyn_params, Value::zero())
+    }
+
+    /// Same as [`Ledger::apply_transaction`], the transaction paying
+    /// `extra_fee` on top of the fee of the ledger parameters.
+    fn apply_transaction_with_extra_fee<'a, Extra>(
         mut self,
         fragment_id: &FragmentId,
         tx: &TransactionSlice<'a, Extra>,
         cur_date: BlockDate,
         dyn_params: &LedgerParameters,
+        extra_fee: Value,
     ) -> Result<(Self, Value), Error>
     where
         Extra: Payload,
@@ -1098,15 +1690,50 @@ impl Ledger {
     {
         check::valid_transaction_ios_number(tx)?;
         check::valid_transaction_date(&self.settings, tx.valid_until(), cur_date)?;
-        let fee = calculate_fee(tx, dyn_params);
+        let fee = calculate_fee(tx, dyn_params).checked_add(extra_fee)?;
         tx.verify_strictly_balanced(fee)?;
+        #[cfg(feature = "address-screening")]
+        self.screen_inputs(tx)?;
         self = self.apply_tx_inputs(tx)?;
         self = self.apply_tx_outputs(*fragment_id, tx.outputs())?;
         self = self.apply_tx_fee(fee)?;
         Ok((self, fee))
     }
 
+    /// Apply all the transactions of the batch, the batch is applied atomically
+    /// since any failure discards the resulting ledger.
+    ///
+    /// The outputs of each transaction are referenced by the identifier the
+    /// transaction would have as a standalone fragment.
+    pub fn apply_transaction_batch(
+        mut self,
+        batch: &TransactionBatch,
+        cur_date: BlockDate,
+        dyn_params: &LedgerParameters,
+    ) -> Result<(Self, Value), Error> {
+        let fee = dyn_params.fees.calculate_batch(batch);
+        batch.verify_strictly_balanced(fee)?;
+        for (tx, fragment_id) in batch.transactions().iter().zip(batch.transaction_ids()) {
+            let tx = tx.as_slice();
+            check::valid_transaction_ios_number(&tx)?;
+            check::valid_transaction_date(&self.settings, tx.valid_until(), cur_date)?;
+            #[cfg(feature = "address-screening")]
+            self.screen_inputs(&tx)?;
+            self = self.apply_tx_inputs(&tx)?;
+            self = self.apply_tx_outputs(fragment_id, tx.outputs())?;
+        }
+        self = self.apply_tx_fee(fee)?;
+        Ok((self, fee))
+    }
+
+    /// Apply the changes of the update immediately.
+    ///
+    /// The fees cannot be changed this way, they are only changed by the
+    /// proposals accepted at an epoch transition.
     pub fn apply_update(mut self, update: &UpdateProposal) -> Result<Self, Error> {
+        if update::changes_fees(update.changes()) {
+            return Err(update::Error::FeesChangeOutsideEpochTransition.into());
+        }
         self.settings = self.settings.try_apply(update.changes())?;
         Ok(self)
     }
@@ -1175,11 +1802,31 @@ impl Ledger {
         }
         committee.extend(dyn_params.committees.iter());
 
-        if !committee.contains(&sig.id) {
+        if !sig.id.is_member_of(&committee) {
             return Err(Error::VotePlanProofInvalidCommittee);
         }
 
+        if let Some(limit) = self.settings.max_proposals_per_vote_plan {
+            if vote_plan.proposals().len() > limit.get() as usize {
+                return Err(Error::VotePlanTooManyProposals {
+                    proposals: vote_plan.proposals().len(),
+                    limit: limit.get(),
+                });
+            }
+        }
+        if let Some(limit) = self.settings.max_vote_plans_per_epoch {
+            if self.governance_quotas.submitted(&sig.id, cur_date.epoch) >= limit.get() {
+                return Err(Error::VotePlanQuotaExceeded {
+                    member: sig.id,
+                    limit: limit.get(),
+                });
+            }
+        }
+
         self.votes = self.votes.add_vote_plan(cur_date, vote_plan, committee)?;
+        self.governance_quotas = self
+            .governance_quotas
+            .record_vote_plan(sig.id, cur_date.epoch);
         Ok(self)
     }
 
@@ -1188,7 +1835,73 @@ impl Ledger {
         account_id: account::Identifier,
         vote: VoteCast,
     ) -> Result<Self, Error> {
-        self.votes = self.votes.apply_vote(self.date(), account_id, vote)?;
+        let token_distribution = self.token_distribution();
+        self.votes = self
+            .votes
+            .apply_vote(self.date(), account_id, vote, token_distribution)?;
+        Ok(self)
+    }
+
+    /// apply every cast of the batch in order, the first cast failing
+    /// its validation fails the whole batch
+    pub fn apply_vote_cast_batch(
+        mut self,
+        account_id: account::Identifier,
+        batch: VoteCastBatch,
+    ) -> Result<Self, Error> {
+        for vote in batch.into_casts() {
+            let token_distribution = self.token_distribution();
+            self.votes =
+                self.votes
+                    .apply_vote(self.date(), account_id.clone(), vote, token_distribution)?;
+        }
+        Ok(self)
+    }
+
+    pub fn apply_snapshot_vote_cast(
+        mut self,
+        account_id: account::Identifier,
+        vote: SnapshotVoteCast,
+    ) -> Result<Self, Error> {
+        self.votes = self
+            .votes
+            .apply_snapshot_vote(self.date(), account_id, vote)?;
+        Ok(self)
+    }
+
+    pub fn apply_voting_power_snapshot<'a>(
+        mut self,
+        snapshot: &certificate::VotingPowerSnapshot,
+        bad: &TransactionBindingAuthData<'a>,
+        sig: certificate::VotingPowerSnapshotProof,
+    ) -> Result<Self, Error> {
+        if sig.verify(bad) == Verification::Failed {
+            return Err(Error::VotingPowerSnapshotProofFailed);
+        }
+
+        self.votes = self
+            .votes
+            .apply_voting_power_snapshot(self.date(), snapshot, sig.id)?;
+        Ok(self)
+    }
+
+    /// cancel a vote plan before its tally. The vote plans carry no deposit
+    /// in this ledger, only the fee of the certificate is charged.
+    pub fn apply_vote_plan_cancel<'a>(
+        mut self,
+        cancel: &certificate::VotePlanCancel,
+        bad: &TransactionBindingAuthData<'a>,
+        sig: certificate::VotePlanCancelProof,
+    ) -> Result<Self, Error> {
+        if sig.verify(bad) == Verification::Failed {
+            return Err(Error::VotePlanCancelProofFailed);
+        }
+
+        self.votes = self.votes.apply_vote_plan_cancel(
+            self.date(),
+            cancel,
+            sig.signatures.into_iter().map(|(id, _)| id),
+        )?;
         Ok(self)
     }
 
@@ -1200,6 +1913,15 @@ impl Ledger {
             .collect()
     }
 
+    /// The status of the vote plan with the encrypted tally a private tally
+    /// applied at the date of the ledger would compute. The committee
+    /// members decrypt their shares of it for the private `VoteTally`.
+    pub fn preview_private_tally(&self, id: &VotePlanId) -> Result<VotePlanStatus, Error> {
+        self.votes
+            .preview_private_tally(self.date(), self.token_distribution(), id)
+            .map_err(Error::from)
+    }
+
     pub fn apply_vote_tally<'a>(
         mut self,
         tally: &certificate::VoteTally,
@@ -1214,14 +1936,31 @@ impl Ledger {
 
         let token_distribution = self.token_distribution();
 
-        self.votes = self.votes.apply_committee_result(
-            self.date(),
-            token_distribution,
-            &self.governance,
-            tally,
-            sig,
-            |action: &VoteAction| actions.push(action.clone()),
-        )?;
+        self.votes = match sig {
+            // since the unified vote tally, the private tally is started and
+            // finalized by the same fragment
+            certificate::TallyProof::Private { id, .. }
+                if self.settings.protocol_version
+                    >= setting::PROTOCOL_VERSION_UNIFIED_VOTE_TALLY =>
+            {
+                self.votes.apply_private_tally(
+                    self.date(),
+                    token_distribution,
+                    &self.governance,
+                    tally,
+                    id,
+                    |action: &VoteAction| actions.push(action.clone()),
+                )?
+            }
+            sig => self.votes.apply_committee_result(
+                self.date(),
+                token_distribution,
+                &self.governance,
+                tally,
+                sig,
+                |action: &VoteAction| actions.push(action.clone()),
+            )?,
+        };
 
         for action in actions {
             match action {
@@ -1250,6 +1989,11 @@ impl Ledger {
         bad: &TransactionBindingAuthData<'a>,
         sig: certificate::EncryptedVoteTallyProof,
     ) -> Result<Self, Error> {
+        if self.settings.protocol_version >= setting::PROTOCOL_VERSION_UNIFIED_VOTE_TALLY {
+            return Err(Error::EncryptedVoteTallyDeprecated {
+                since: setting::PROTOCOL_VERSION_UNIFIED_VOTE_TALLY,
+            });
+        }
         if sig.verify(bad) == Verification::Failed {
             return Err(Error::VoteTallyProofFailed);
         }
@@ -1287,6 +2031,8 @@ impl Ledger {
         cert: &certificate::PoolRegistration,
     ) -> Result<Self, Error> {
         check::valid_pool_registration_certificate(cert)?;
+        check::valid_pool_pledge(cert, self.settings.minimum_pool_pledge)?;
+        check::valid_pool_reward_account(cert, self.static_params.discrimination)?;
 
         self.delegation = self.delegation.register_stake_pool(cert.clone())?;
         Ok(self)
@@ -1325,6 +2071,8 @@ impl Ledger {
         }
 
         let new = &auth_cert.new_pool_reg;
+        check::valid_pool_pledge(new, self.settings.minimum_pool_pledge)?;
+        check::valid_pool_reward_account(new, self.static_params.discrimination)?;
 
         // don't allow any fees update for now
         if new.rewards != state.registration.rewards {
@@ -1360,6 +2108,34 @@ impl Ledger {
         Ok(self)
     }
 
+    pub fn apply_amount_stake_delegation(
+        mut self,
+        auth_cert: &certificate::AmountStakeDelegation,
+    ) -> Result<Self, Error> {
+        let account_key = auth_cert
+            .account_id
+            .to_single_account()
+            .ok_or(Error::AccountIdentifierInvalid)?;
+        self.accounts = self
+            .accounts
+            .set_delegation_amounts(&account_key, auth_cert.get_delegation_amounts())?;
+        Ok(self)
+    }
+
+    pub fn apply_rewards_redelegation(
+        mut self,
+        auth_cert: &certificate::RewardsRedelegation,
+    ) -> Result<Self, Error> {
+        let account_key = auth_cert
+            .account_id
+            .to_single_account()
+            .ok_or(Error::AccountIdentifierInvalid)?;
+        self.accounts = self
+            .accounts
+            .set_rewards_redelegation(&account_key, auth_cert.enabled)?;
+        Ok(self)
+    }
+
     pub fn apply_owner_stake_delegation(
         mut self,
         account_id: &UnspecifiedAccountIdentifier,
@@ -1400,8 +2176,15 @@ impl Ledger {
         Ok(self)
     }
 
+    /// the stake distribution of the current balances, snapshotted at the
+    /// epoch of the ledger
     pub fn get_stake_distribution(&self) -> StakeDistribution {
-        stake::get_distribution(&self.accounts, &self.delegation, &self.utxos)
+        stake::get_distribution(
+            &self.accounts,
+            &self.delegation,
+            &self.utxos,
+            self.date.epoch,
+        )
     }
 
     /// access the ledger static parameters
@@ -1417,22 +2200,50 @@ impl Ledger {
         &self.token_totals
     }
 
+    /// The names claimed on the chain, including the expired ones which are
+    /// purged at the next epoch transition
+    pub fn names(&self) -> &NameRegistry {
+        &self.names
+    }
+
+    /// The governance quota of the committee member consumed during the
+    /// current epoch of the ledger
+    pub fn governance_quota(&self, member: &CommitteeId) -> GovernanceQuota {
+        GovernanceQuota::new(
+            &self.governance_quotas,
+            &self.settings,
+            member,
+            self.date.epoch,
+        )
+    }
+
+    /// The account the name is bound to at the current date of the ledger
+    pub fn resolve_name(&self, name: &Name) -> Option<&account::Identifier> {
+        self.names.resolve(name, self.date.epoch)
+    }
+
     pub fn token_distribution(&self) -> TokenDistribution<()> {
         TokenDistribution::new(self.token_totals.clone(), self.accounts.clone())
     }
 
     pub fn get_ledger_parameters(&self) -> LedgerParameters {
-        LedgerParameters {
-            fees: self.settings.linear_fees,
-            treasury_tax: self
-                .settings
-                .treasury_params
-                .unwrap_or_else(rewards::TaxType::zero),
-            reward_params: self.settings.to_reward_params(),
-            block_content_max_size: self.settings.block_content_max_size,
-            epoch_stability_depth: self.settings.epoch_stability_depth,
-            fees_goes_to: self.settings.fees_goes_to,
-            committees: self.settings.committees.clone(),
+        LedgerParameters::from_settings(&self.settings)
+    }
+
+    /// The ledger parameters of the given epoch, see [`Ledger::settings_at`]
+    pub fn get_ledger_parameters_at(&self, epoch: Epoch) -> Option<LedgerParameters> {
+        self.settings_at(epoch).map(LedgerParameters::from_settings)
+    }
+
+    /// The fees of the current epoch and of the next one, given the votes
+    /// received so far on the pending update proposals.
+    pub fn fee_schedule(&self) -> FeeSchedule {
+        FeeSchedule {
+            current: self.settings.linear_fees,
+            next_epoch: self
+                .updates
+                .next_epoch_settings(&self.settings, self.date)
+                .linear_fees,
         }
     }
 
@@ -1462,6 +2273,65 @@ impl Ledger {
         &self.settings
     }
 
+    /// The settings effective during the given epoch. The settings of the
+    /// past epochs are kept for the last [`SETTINGS_HISTORY_DEPTH`] epochs,
+    /// `None` is returned for older epochs and for the epochs after the
+    /// current one, whose settings are not known yet.
+    ///
+    /// [`SETTINGS_HISTORY_DEPTH`]: super::settings_history::SETTINGS_HISTORY_DEPTH
+    pub fn settings_at(&self, epoch: Epoch) -> Option<&setting::Settings> {
+        use std::cmp::Ordering::*;
+        match epoch.cmp(&self.date.epoch) {
+            Less => self.settings_history.get(epoch),
+            Equal => Some(&self.settings),
+            Greater => None,
+        }
+    }
+
+    pub fn settings_history(&self) -> &SettingsHistory {
+        &self.settings_history
+    }
+
+    /// The performance of the pool over the last epoch for which the rewards
+    /// were distributed
+    pub fn pool_performance(&self, pool_id: &PoolId) -> Option<&PoolPerformance> {
+        self.delegation
+            .lookup(pool_id)
+            .and_then(|pool_state| pool_state.last_performance())
+    }
+
+    /// The performance of the pool during the given epoch, as long as the
+    /// epoch is in the history kept for the pool
+    pub fn pool_performance_at(&self, pool_id: &PoolId, epoch: Epoch) -> Option<&PoolPerformance> {
+        self.delegation
+            .lookup(pool_id)
+            .and_then(|pool_state| pool_state.performance_at(epoch))
+    }
+
+    /// Resolve the producer of a block to its record in the current state of
+    /// the ledger. `None` when the producer is not a BFT leader of the
+    /// current settings, or not a registered pool anymore.
+    pub fn resolve_producer(&self, producer: &BlockProducer) -> Option<ProducerRecord<'_>> {
+        match producer {
+            BlockProducer::BftLeader(leader_id) => self
+                .settings
+                .bft_leaders
+                .iter()
+                .enumerate()
+                .find(|(_, leader)| *leader == leader_id)
+                .map(|(index, leader_id)| ProducerRecord::BftLeader { leader_id, index }),
+            BlockProducer::Pool(pool_id) => {
+                self.delegation.lookup(pool_id).map(ProducerRecord::Pool)
+            }
+        }
+    }
+
+    /// The record of the producer of the block of the header, see
+    /// [`Ledger::resolve_producer`]. `None` for the unsigned headers.
+    pub fn block_producer(&self, header: &Header) -> Option<ProducerRecord<'_>> {
+        self.resolve_producer(&header.producer()?)
+    }
+
     pub fn delegation(&self) -> &PoolsState {
         &self.delegation
     }
@@ -1479,27 +2349,30 @@ impl Ledger {
     }
 
     fn validate_utxo_total_value(&self) -> Result<(), Error> {
-        self.get_total_value()?;
+        self.get_total_value()
+            .map_err(|_| Error::Block0(Block0Error::UtxoTotalValueTooBig))?;
         Ok(())
     }
 
+    /// The total value held by the ledger. The parts of the ledger are summed
+    /// separately, so an overflow is reported with the part it happened in
+    /// and the index of the entry in the iteration order of the part.
     pub fn get_total_value(&self) -> Result<Value, Error> {
-        let old_utxo_values = self.oldutxos.iter().map(|entry| entry.output.value);
-        let new_utxo_values = self.utxos.iter().map(|entry| entry.output.value);
-        let account_value = self
-            .accounts
-            .get_total_value()
-            .map_err(|_| Error::Block0(Block0Error::UtxoTotalValueTooBig))?;
-        let multisig_value = self
-            .multisig
-            .get_total_value()
-            .map_err(|_| Error::Block0(Block0Error::UtxoTotalValueTooBig))?;
-        let all_utxo_values = old_utxo_values
-            .chain(new_utxo_values)
-            .chain(Some(account_value))
-            .chain(Some(multisig_value))
-            .chain(self.pots.values());
-        Value::sum(all_utxo_values).map_err(|_| Error::Block0(Block0Error::UtxoTotalValueTooBig))
+        let overflow = |part| move |overflow| Error::ValueSumOverflow { part, overflow };
+        let parts = [
+            Value::checked_sum(self.oldutxos.iter().map(|entry| entry.output.value))
+                .map_err(overflow("legacy utxos"))?,
+            Value::checked_sum(self.utxos.iter().map(|entry| entry.output.value))
+                .map_err(overflow("utxos"))?,
+            self.accounts
+                .get_total_value()
+                .map_err(overflow("accounts"))?,
+            self.multisig
+                .get_total_value()
+                .map_err(overflow("multisig accounts"))?,
+            self.pots.total_value().map_err(overflow("pots"))?,
+        ];
+        Value::checked_sum(parts).map_err(overflow("ledger"))
     }
 
     fn apply_tx_inputs<Extra: Payload>(
@@ -1507,54 +2380,89 @@ impl Ledger {
         tx: &TransactionSlice<Extra>,
     ) -> Result<Self, Error> {
         let sign_data_hash = tx.transaction_sign_data_hash();
-        for (input, witness) in tx.inputs_and_witnesses().iter() {
-            match input.to_enum() {
-                InputEnum::UtxoInput(utxo) => {
-                    self = self.apply_input_to_utxo(&sign_data_hash, &utxo, &witness)?
-                }
-                InputEnum::AccountInput(account_id, value) => {
-                    match match_identifier_witness(&account_id, &witness)? {
-                        MatchingIdentifierWitness::Single(
-                            account_id,
+        let static_params = self.static_params.clone();
+        for (index, (input, witness)) in tx.inputs_and_witnesses().iter().enumerate() {
+            // the state before the input, to diagnose a rejected witness
+            let accounts = self.accounts.clone();
+            let context = WitnessContext {
+                block0_hash: &static_params.block0_initial_hash,
+                sign_data_hash: &sign_data_hash,
+                accounts: &accounts,
+            };
+            let input_account = match input.to_enum() {
+                InputEnum::AccountInput(account_id, _) => account_id.to_single_account(),
+                InputEnum::UtxoInput(_) => None,
+            };
+            self = self
+                .apply_tx_input(&sign_data_hash, input, &witness)
+                .map_err(|error| {
+                    witness_failure::diagnose(
+                        index,
+                        &witness,
+                        input_account.as_ref(),
+                        &context,
+                        error,
+                    )
+                })?;
+        }
+        Ok(self)
+    }
+
+    fn apply_tx_input(
+        mut self,
+        sign_data_hash: &TransactionSignDataHash,
+        input: Input,
+        witness: &Witness,
+    ) -> Result<Self, Error> {
+        match input.to_enum() {
+            InputEnum::UtxoInput(utxo) => self.apply_input_to_utxo(sign_data_hash, &utxo, witness),
+            InputEnum::AccountInput(account_id, value) => {
+                match match_identifier_witness(&account_id, witness)? {
+                    MatchingIdentifierWitness::Single(account_id, witness, spending_counter) => {
+                        self.accounts = input_single_account_verify(
+                            self.accounts,
+                            &self.static_params.block0_initial_hash,
+                            sign_data_hash,
+                            &account_id,
                             witness,
                             spending_counter,
-                        ) => {
-                            self.accounts = input_single_account_verify(
-                                self.accounts,
-                                &self.static_params.block0_initial_hash,
-                                &sign_data_hash,
-                                &account_id,
-                                witness,
-                                spending_counter,
-                                value,
-                            )?
-                        }
-                        MatchingIdentifierWitness::Multi(account_id, witness, spending_counter) => {
-                            self.multisig = input_multi_account_verify(
-                                self.multisig,
-                                &self.static_params.block0_initial_hash,
-                                &sign_data_hash,
-                                &account_id,
-                                witness,
-                                spending_counter,
-                                value,
-                            )?
-                        }
+                            value,
+                        )?
+                    }
+                    MatchingIdentifierWitness::Multi(account_id, witness, spending_counter) => {
+                        self.multisig = input_multi_account_verify(
+                            self.multisig,
+                            &self.static_params.block0_initial_hash,
+                            sign_data_hash,
+                            &account_id,
+                            witness,
+                            spending_counter,
+                            value,
+                        )?
                     }
                 }
+                Ok(self)
             }
         }
-        Ok(self)
     }
 
     fn apply_tx_outputs(
-        mut self,
+        self,
         fragment_id: FragmentId,
         outputs: OutputsSlice<'_>,
     ) -> Result<Self, Error> {
+        self.apply_outputs(fragment_id, outputs.iter())
+    }
+
+    fn apply_outputs<I>(mut self, fragment_id: FragmentId, outputs: I) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = Output<Address>>,
+    {
         let mut new_utxos = Vec::new();
-        for (index, output) in outputs.iter().enumerate() {
+        for (index, output) in outputs.into_iter().enumerate() {
             check::valid_output_value(&output)?;
+            #[cfg(feature = "address-screening")]
+            self.screen_output(index, &output)?;
 
             if output.address.discrimination() != self.static_params.discrimination {
                 return Err(Error::InvalidDiscrimination);
@@ -1593,6 +2501,68 @@ impl Ledger {
         Ok(self)
     }
 
+    /// Move all the legacy outputs of the legacy address of the migration to
+    /// a single output to the destination, less the fee
+    fn apply_legacy_migration(
+        mut self,
+        fragment_id: FragmentId,
+        migration: &legacy::MigrationCertificate,
+        cur_date: BlockDate,
+        dyn_params: &LedgerParameters,
+    ) -> Result<Self, Error> {
+        check::valid_transaction_date(&self.settings, migration.valid_until, cur_date)?;
+        let old_address = &migration.old_address;
+        if !migration.matches_old_address() {
+            return Err(Error::LegacyMigrationInvalidPublicKey {
+                old_address: old_address.clone(),
+            });
+        }
+        let challenge = migration.challenge(&self.static_params.block0_initial_hash);
+        if migration
+            .signature
+            .verify(&migration.public_key, &challenge)
+            == Verification::Failed
+        {
+            return Err(Error::LegacyMigrationInvalidSignature {
+                old_address: old_address.clone(),
+            });
+        }
+
+        let pointers: Vec<_> = self
+            .oldutxos
+            .iter()
+            .filter(|entry| &entry.output.address == old_address)
+            .map(|entry| (entry.fragment_id, entry.output_index))
+            .collect();
+        if pointers.is_empty() {
+            return Err(Error::LegacyMigrationNoFunds {
+                old_address: old_address.clone(),
+            });
+        }
+        let mut funds = Value::zero();
+        for (_index, (old_fragment_id, output_index)) in pointers.into_iter().enumerate() {
+            let (oldutxos, output) = self.oldutxos.remove(&old_fragment_id, output_index)?;
+            #[cfg(feature = "address-screening")]
+            self.screen_input(
+                _index,
+                &super::ResolvedInput::OldUtxo {
+                    pointer: UtxoPointer::new(old_fragment_id, output_index, output.value),
+                    output: output.clone(),
+                },
+            )?;
+            self.oldutxos = oldutxos;
+            funds = (funds + output.value)?;
+        }
+
+        let fee = dyn_params.fees.calculate(None, 1, 1);
+        let value = funds
+            .checked_sub(fee)
+            .map_err(|_| Error::LegacyMigrationFundsBelowFee { funds, fee })?;
+        let output = Output::from_address(migration.destination.clone(), value);
+        self.apply_outputs(fragment_id, std::iter::once(output))?
+            .apply_tx_fee(fee)
+    }
+
     fn add_value_or_create_account(
         &mut self,
         account: &account::Identifier,
@@ -1623,17 +2593,8 @@ impl Ledger {
             Witness::Account(_, _) => Err(Error::ExpectingUtxoWitness),
             Witness::Multisig(_, _) => Err(Error::ExpectingUtxoWitness),
             Witness::OldUtxo(pk, cc, signature) => {
-                let (old_utxos, associated_output) = self
-                    .oldutxos
-                    .remove(&utxo.transaction_id, utxo.output_index)?;
-
+                let (old_utxos, associated_output) = spend_utxo(&self.oldutxos, utxo)?;
                 self.oldutxos = old_utxos;
-                if utxo.value != associated_output.value {
-                    return Err(Error::UtxoValueNotMatching {
-                        expected: utxo.value,
-                        value: associated_output.value,
-                    });
-                };
 
                 if legacy::oldaddress_from_xpub(&associated_output.address, pk, cc)
                     == legacy::OldAddressMatchXPub::No
@@ -1662,15 +2623,8 @@ impl Ledger {
                 Ok(self)
             }
             Witness::Utxo(signature) => {
-                let (new_utxos, associated_output) =
-                    self.utxos.remove(&utxo.transaction_id, utxo.output_index)?;
+                let (new_utxos, associated_output) = spend_utxo(&self.utxos, utxo)?;
                 self.utxos = new_utxos;
-                if utxo.value != associated_output.value {
-                    return Err(Error::UtxoValueNotMatching {
-                        expected: utxo.value,
-                        value: associated_output.value,
-                    });
-                }
 
                 let data_to_verify = WitnessUtxoData::new(
                     &self.static_params.block0_initial_hash,
@@ -1707,14 +2661,62 @@ impl ApplyBlockLedger {
         self.block_date
     }
 
+    /// the ledger with the fragments applied so far
+    pub(super) fn ledger(&self) -> &Ledger {
+        &self.ledger
+    }
+
     pub fn apply_fragment(&self, fragment: &Fragment) -> Result<Self, Error> {
         let ledger = self
             .ledger
             .apply_fragment(&self.ledger_params, fragment, self.block_date)?;
-        Ok(ApplyBlockLedger {
+        Ok(self.with_fragment_applied(fragment, ledger))
+    }
+
+    /// Same as [`ApplyBlockLedger::apply_fragment`], reporting the
+    /// application of the fragment to the metrics sink
+    pub fn apply_fragment_with_metrics<M: LedgerMetrics>(
+        &self,
+        fragment: &Fragment,
+        metrics: M,
+    ) -> Result<Self, Error> {
+        let ledger = measure_fragment(&metrics, &self.ledger, fragment, || {
+            self.ledger
+                .apply_fragment(&self.ledger_params, fragment, self.block_date)
+        })?;
+        Ok(self.with_fragment_applied(fragment, ledger))
+    }
+
+    /// the block with the ledger the fragment is applied to, and the events
+    /// emitted by the fragment
+    fn with_fragment_applied(&self, fragment: &Fragment, ledger: Ledger) -> Self {
+        let mut events = self.events.clone();
+        if let Fragment::VotePlanCancel(tx) = fragment {
+            let vote_plan = tx.as_slice().payload().into_payload().vote_plan().clone();
+            let proposals = ledger
+                .votes
+                .plans
+                .lookup(&vote_plan)
+                .map(|manager| {
+                    manager
+                        .plan()
+                        .proposals()
+                        .iter()
+                        .map(|proposal| proposal.external_id().clone())
+                        .collect()
+                })
+                .unwrap_or_default();
+            events.push(LedgerEvent::VotePlanCancelled(VotePlanCancelReport {
+                vote_plan,
+                date: self.block_date,
+                proposals,
+            }));
+        }
+        ApplyBlockLedger {
             ledger,
+            events,
             ..self.clone()
-        })
+        }
     }
 
     #[cfg(feature = "evm")]
@@ -1728,7 +2730,35 @@ impl ApplyBlockLedger {
     }
 
     pub fn finish(self, consensus_eval_context: &ConsensusEvalContext) -> Ledger {
+        self.finish_with_events(consensus_eval_context).0
+    }
+
+    pub fn finish_with_events(
+        self,
+        consensus_eval_context: &ConsensusEvalContext,
+    ) -> (Ledger, LedgerEvents) {
         let mut new_ledger = self.ledger;
+        let mut events = self.events;
+
+        // fees can only be appended to the pot while applying fragments
+        let fees = new_ledger
+            .pots
+            .fees_value()
+            .checked_sub(self.initial_fees)
+            .unwrap_or_else(|_| Value::zero());
+        events.push(LedgerEvent::BlockReward(BlockRewardSummary::new(
+            &self.ledger_params,
+            new_ledger.chain_length,
+            self.block_date,
+            consensus_eval_context,
+            fees,
+        )));
+        for report in new_ledger
+            .balance_watches
+            .crossings(&self.watched_balances, &new_ledger)
+        {
+            events.push(LedgerEvent::BalanceThreshold(report));
+        }
 
         // Update the ledger metadata related to eval context
         new_ledger.date = self.block_date;
@@ -1743,7 +2773,7 @@ impl ApplyBlockLedger {
             }
         };
 
-        new_ledger
+        (new_ledger, events)
     }
 }
 
@@ -1755,16 +2785,93 @@ fn apply_old_declaration(
     assert!(decl.addrs.len() < 255);
     let mut outputs = Vec::with_capacity(decl.addrs.len());
     for (i, d) in decl.addrs.iter().enumerate() {
-        let output = Output {
-            address: d.0.clone(),
-            value: d.1,
-        };
+        let output = Output::from_address(d.0.clone(), d.1);
         outputs.push((i as u8, output))
     }
     utxos = utxos.add(fragment_id, &outputs)?;
     Ok(utxos)
 }
 
+/// The account of the single input of a name certificate transaction
+fn name_certificate_owner<P: Payload>(
+    tx: &TransactionSlice<P>,
+) -> Result<account::Identifier, Error> {
+    match tx
+        .inputs()
+        .iter()
+        .map(|input| input.to_enum())
+        .zip(tx.witnesses().iter())
+        .next()
+    {
+        Some((InputEnum::AccountInput(account_id, _), Witness::Account(_, _))) => account_id
+            .to_single_account()
+            .ok_or(Error::AccountIdentifierInvalid),
+        _ => Err(Error::NameCertificateInvalidTransaction),
+    }
+}
+
+fn vote_cast_voter<P: Payload>(tx: &TransactionSlice<P>) -> Result<account::Identifier, Error> {
+    match tx
+        .inputs()
+        .iter()
+        .map(|input| input.to_enum())
+        .zip(tx.witnesses().iter())
+        .next()
+    {
+        Some((InputEnum::AccountInput(account_id, _), Witness::Account(_, _))) => account_id
+            .to_single_account()
+            .ok_or(Error::AccountIdentifierInvalid),
+        _ => Err(Error::VoteCastInvalidTransaction),
+    }
+}
+
+/// Check the signature of the delegating account over the delegation transaction
+fn verify_delegation_signature<P: Payload>(
+    tx: &TransactionSlice<P>,
+    account_id: &UnspecifiedAccountIdentifier,
+    payload_auth: &AccountBindingSignature,
+) -> Result<(), Error> {
+    let verified = match payload_auth {
+        AccountBindingSignature::Single(signature) => {
+            let account_pk = account_id
+                .to_single_account()
+                .ok_or(Error::AccountIdentifierInvalid)?;
+            signature.verify_slice(&account_pk.into(), &tx.transaction_binding_auth_data())
+        }
+        AccountBindingSignature::Multi(_) => {
+            // TODO
+            Verification::Failed
+        }
+    };
+
+    if verified == Verification::Failed {
+        return Err(Error::StakeDelegationSignatureFailed);
+    }
+    Ok(())
+}
+
+/// Number of blocks a pool is expected to create during an epoch: the number
+/// of slots with a leader in the epoch, on average, weighted by the share of
+/// the stake of the pool, rounded to the nearest block.
+fn expected_blocks(
+    slots_per_epoch: u32,
+    settings: &setting::Settings,
+    pool_stake: Stake,
+    total_stake: Stake,
+) -> u32 {
+    if total_stake.0 == 0 {
+        return 0;
+    }
+    let active_slots =
+        slots_per_epoch as u128 * Milli::from(settings.active_slots_coeff).to_millis() as u128;
+    let denominator = 1000 * total_stake.0 as u128;
+    let expected = (active_slots * pool_stake.0 as u128 + denominator / 2) / denominator;
+    expected as u32
+}
+
+/// Check the pledge of a pool at the time of the rewards distribution: the
+/// stake delegated by the owners needs to cover the highest of the pledge
+/// declared by the pool and the minimum pledge.
 fn calculate_fee<'a, Extra: Payload>(
     tx: &TransactionSlice<'a, Extra>,
     dyn_params: &LedgerParameters,
@@ -1785,7 +2892,7 @@ pub enum MatchingIdentifierWitness<'a> {
     ),
 }
 
-fn match_identifier_witness<'a>(
+pub(super) fn match_identifier_witness<'a>(
     account: &UnspecifiedAccountIdentifier,
     witness: &'a Witness,
 ) -> Result<MatchingIdentifierWitness<'a>, Error> {
@@ -1810,7 +2917,7 @@ fn match_identifier_witness<'a>(
 }
 
 fn input_single_account_verify<'a>(
-    mut ledger: account::Ledger,
+    ledger: account::Ledger,
     block0_hash: &HeaderId,
     sign_data_hash: &TransactionSignDataHash,
     account: &account::Identifier,
@@ -1818,11 +2925,10 @@ fn input_single_account_verify<'a>(
     spending_counter: account::SpendingCounter,
     value: Value,
 ) -> Result<account::Ledger, Error> {
-    // .remove_value() check if there's enough value and if not, returns a Err.
-    let new_ledger = ledger.remove_value(account, spending_counter, value)?;
-    ledger = new_ledger;
+    let (ledger, cycle) = spend_account(&ledger, account, spending_counter, value)?;
 
-    let tidsc = WitnessAccountData::new(block0_hash, sign_data_hash, spending_counter);
+    let tidsc =
+        WitnessAccountData::new_in_cycle(block0_hash, sign_data_hash, spending_counter, cycle);
     let verified = witness.verify(account.as_ref(), &tidsc);
     if verified == chain_crypto::Verification::Failed {
         return Err(Error::AccountInvalidSignature {
@@ -1834,7 +2940,7 @@ fn input_single_account_verify<'a>(
 }
 
 fn input_multi_account_verify<'a>(
-    mut ledger: multisig::Ledger,
+    ledger: multisig::Ledger,
     block0_hash: &HeaderId,
     sign_data_hash: &TransactionSignDataHash,
     account: &multisig::Identifier,
@@ -1842,18 +2948,18 @@ fn input_multi_account_verify<'a>(
     spending_counter: account::SpendingCounter,
     value: Value,
 ) -> Result<multisig::Ledger, Error> {
-    // .remove_value() check if there's enough value and if not, returns a Err.
-    let (new_ledger, declaration) = ledger.remove_value(account, spending_counter, value)?;
+    let (new_ledger, declaration, cycle) =
+        spend_multisig(&ledger, account, spending_counter, value)?;
 
-    let data_to_verify = WitnessMultisigData::new(block0_hash, sign_data_hash, spending_counter);
+    let data_to_verify =
+        WitnessMultisigData::new_in_cycle(block0_hash, sign_data_hash, spending_counter, cycle);
     if !witness.verify(declaration, &data_to_verify) {
         return Err(Error::MultisigInvalidSignature {
             multisig: account.clone(),
             witness: Witness::Multisig(spending_counter, witness.clone()),
         });
     }
-    ledger = new_ledger;
-    Ok(ledger)
+    Ok(new_ledger)
 }
 
 #[cfg(test)]
@@ -1862,7 +2968,7 @@ mod tests {
     use super::*;
     use crate::{
         account::{Identifier, SpendingCounter},
-        accounting::account::account_state::AccountState,
+        accounting::account::{account_state::AccountState, SpendingCounterIncreasing},
         fee::LinearFee,
         key::Hash,
         multisig,
@@ -1908,6 +3014,8 @@ mod tests {
                 epoch_stability_depth: Arbitrary::arbitrary(g),
                 fees_goes_to: Arbitrary::arbitrary(g),
                 committees: committees.into(),
+                minimum_pool_pledge: Arbitrary::arbitrary(g),
+                pool_performance_rewards: Arbitrary::arbitrary(g),
             }
         }
     }
@@ -2126,6 +3234,62 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_input_single_account_verify_across_counter_wrap() {
+        let account = AddressData::account(Discrimination::Test);
+        let block0_hash = TestGen::hash();
+        let id: Identifier = account.public_key().into();
+        let last = SpendingCounter::new(0, u32::MAX);
+        let first = last.increment();
+
+        let mut state = AccountState::new(Value(100), ());
+        state.spending = SpendingCounterIncreasing::new_from_counter(last);
+        let account_ledger: account::Ledger = iter::once((id.clone(), state)).collect();
+        let sign_data_hash =
+            single_transaction_sign_by(account.make_input(Value(10), None), &block0_hash, &account)
+                .hash();
+        let sign = |counter, cycle| {
+            Witness::new_account_in_cycle(&block0_hash, &sign_data_hash, counter, cycle, |d| {
+                account.private_key().sign(d)
+            })
+        };
+
+        let account_ledger = super::input_single_account_verify(
+            account_ledger,
+            &block0_hash,
+            &sign_data_hash,
+            &id,
+            to_account_witness(&sign(last, 0)),
+            last,
+            Value(10),
+        )
+        .unwrap();
+        assert_eq!(account_ledger.get_spending_cycle(&id, first), Ok(1));
+
+        // the witness of the first cycle cannot be replayed after the wrap
+        let result = super::input_single_account_verify(
+            account_ledger.clone(),
+            &block0_hash,
+            &sign_data_hash,
+            &id,
+            to_account_witness(&sign(first, 0)),
+            first,
+            Value(10),
+        );
+        assert!(matches!(result, Err(Error::AccountInvalidSignature { .. })));
+
+        let result = super::input_single_account_verify(
+            account_ledger,
+            &block0_hash,
+            &sign_data_hash,
+            &id,
+            to_account_witness(&sign(first, 1)),
+            first,
+            Value(10),
+        );
+        assert!(result.is_ok())
+    }
+
     #[test]
     fn test_input_account_wrong_value() {
         let account = AddressData::account(Discrimination::Test);
@@ -2351,6 +3515,8 @@ mod tests {
                 epoch_stability_depth: 1000,
                 fees_goes_to: FeesGoesTo::Rewards,
                 committees: Arc::new([]),
+                minimum_pool_pledge: Value::zero(),
+                pool_performance_rewards: false,
             };
             InternalApplyTransactionTestParams {
                 dyn_params,
@@ -0,0 +1,181 @@
+//! Metrics hooks of the ledger application.
+//!
+//! A node exports the ledger metrics to its own monitoring system by
+//! implementing [`LedgerMetrics`] and applying the blocks with
+//! [`Ledger::apply_block_with_metrics`]. The ledger only calls the sink and
+//! keeps no counter itself. With the [`NoopMetrics`] sink, which is what
+//! [`Ledger::apply_block`] uses, the fragments are neither timed nor
+//! inspected, so the application is unaffected.
+
+use super::{Error, Ledger};
+use crate::fragment::Fragment;
+use crate::value::Value;
+use std::time::{Duration, Instant};
+
+/// A sink of the ledger application metrics.
+///
+/// The fragment kinds are stable snake case names such as `"transaction"`
+/// or `"vote_cast"`, suitable as the label of a counter. The verification
+/// time is the time spent checking and applying the fragment.
+pub trait LedgerMetrics {
+    /// Whether the metrics are collected at all, checked once per block
+    fn enabled(&self) -> bool {
+        true
+    }
+
+    /// A fragment was applied, the fee is the one collected in the fee pot
+    fn fragment_applied(&self, _kind: &'static str, _fee: Value, _verification_time: Duration) {}
+
+    /// A fragment was rejected, which fails the application of its block
+    fn fragment_rejected(&self, _kind: &'static str, _error: &Error, _verification_time: Duration) {
+    }
+
+    /// A block was applied with all its fragments
+    fn block_applied(&self, _fragments: usize, _fees: Value, _application_time: Duration) {}
+}
+
+/// The sink ignoring every metric
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopMetrics;
+
+impl LedgerMetrics for NoopMetrics {
+    fn enabled(&self) -> bool {
+        false
+    }
+}
+
+impl<M: LedgerMetrics + ?Sized> LedgerMetrics for &M {
+    fn enabled(&self) -> bool {
+        (**self).enabled()
+    }
+
+    fn fragment_applied(&self, kind: &'static str, fee: Value, verification_time: Duration) {
+        (**self).fragment_applied(kind, fee, verification_time)
+    }
+
+    fn fragment_rejected(&self, kind: &'static str, error: &Error, verification_time: Duration) {
+        (**self).fragment_rejected(kind, error, verification_time)
+    }
+
+    fn block_applied(&self, fragments: usize, fees: Value, application_time: Duration) {
+        (**self).block_applied(fragments, fees, application_time)
+    }
+}
+
+/// Apply the fragment with `apply`, reporting its outcome to the sink.
+///
+/// The fee is recovered from the fee pot, which the ledger credits with
+/// exactly the fee of the fragment on success.
+pub(super) fn measure_fragment<M, F>(
+    metrics: &M,
+    before: &Ledger,
+    fragment: &Fragment,
+    apply: F,
+) -> Result<Ledger, Error>
+where
+    M: LedgerMetrics + ?Sized,
+    F: FnOnce() -> Result<Ledger, Error>,
+{
+    let start = Instant::now();
+    let result = apply();
+    let verification_time = start.elapsed();
+    let kind = fragment.get_tag().name();
+    match &result {
+        Ok(after) => {
+            let fee = after
+                .pots
+                .fees_value()
+                .checked_sub(before.pots.fees_value())
+                .unwrap_or_default();
+            metrics.fragment_applied(kind, fee, verification_time);
+        }
+        Err(error) => metrics.fragment_rejected(kind, error, verification_time),
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fee::LinearFee,
+        testing::{
+            builders::TestTxBuilder,
+            data::AddressDataValue,
+            ledger::{ConfigBuilder, LedgerBuilder},
+            TestGen,
+        },
+    };
+    use chain_addr::Discrimination;
+    use std::cell::RefCell;
+
+    #[derive(Default)]
+    struct Counters {
+        applied: RefCell<Vec<&'static str>>,
+        rejected: RefCell<Vec<&'static str>>,
+        fees: RefCell<Value>,
+        blocks: RefCell<usize>,
+    }
+
+    impl LedgerMetrics for Counters {
+        fn fragment_applied(&self, kind: &'static str, fee: Value, _: Duration) {
+            self.applied.borrow_mut().push(kind);
+            let mut fees = self.fees.borrow_mut();
+            *fees = (*fees + fee).unwrap();
+        }
+
+        fn fragment_rejected(&self, kind: &'static str, _: &Error, _: Duration) {
+            self.rejected.borrow_mut().push(kind);
+        }
+
+        fn block_applied(&self, fragments: usize, _: Value, _: Duration) {
+            *self.blocks.borrow_mut() += fragments;
+        }
+    }
+
+    #[test]
+    fn block_application_is_reported_to_the_sink() {
+        let leader_pair = TestGen::leader_pair();
+        let faucet = AddressDataValue::account(Discrimination::Test, Value(1_000));
+        let receiver = AddressDataValue::account(Discrimination::Test, Value(0));
+        let mut test_ledger = LedgerBuilder::from_config(
+            ConfigBuilder::new()
+                .with_leaders(&[leader_pair.id()])
+                .with_fee(LinearFee::new(1, 1, 1)),
+        )
+        .faucet(&faucet)
+        .build()
+        .unwrap();
+        let transfer = TestTxBuilder::new(test_ledger.block0_hash)
+            .move_funds(&mut test_ledger, &faucet, &receiver, Value(100))
+            .get_fragment();
+        let block0_only = Fragment::Initial(ConfigBuilder::new().build());
+
+        let metrics = Counters::default();
+        let apply = |fragments: Vec<Fragment>| {
+            let block = test_ledger.forge_bft_block_with_fragments(
+                &leader_pair,
+                test_ledger.date(),
+                fragments,
+            );
+            let header_meta = block.header().get_content_eval_context();
+            test_ledger.ledger.apply_block_with_metrics(
+                test_ledger.ledger.get_ledger_parameters(),
+                block.contents(),
+                &header_meta,
+                &metrics,
+            )
+        };
+
+        let ledger = apply(vec![transfer.clone()]).unwrap();
+        assert_eq!(*metrics.applied.borrow(), vec!["transaction"]);
+        assert_eq!(*metrics.fees.borrow(), ledger.pots.fees_value());
+        assert!(*metrics.fees.borrow() > Value::zero());
+        assert_eq!(*metrics.blocks.borrow(), 1);
+
+        assert!(apply(vec![transfer, block0_only]).is_err());
+        assert_eq!(*metrics.rejected.borrow(), vec!["initial"]);
+        assert_eq!(metrics.applied.borrow().len(), 2);
+        assert_eq!(*metrics.blocks.borrow(), 1);
+    }
+}
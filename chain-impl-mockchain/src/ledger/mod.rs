@@ -1,22 +1,82 @@
+pub mod account_history;
+mod balance_watch;
+mod block0_report;
+mod block0_supply;
 pub mod check;
+mod epoch_transition;
+mod events;
 #[cfg(feature = "evm")]
 mod evm;
+mod extensions;
+mod fee_audit;
+mod finality;
+mod fragment_status;
 pub mod governance;
+mod governance_quota;
 mod info;
+pub mod initial_funds;
+mod input_resolution;
 pub mod iter;
 mod leaderlog;
 #[allow(clippy::module_inception)]
 pub mod ledger;
+pub mod metrics;
+mod parameter_sanity;
+mod pool_rewards;
 mod pots;
+mod preview;
+mod randomness;
 pub mod recovery;
 mod reward_info;
+mod reward_simulation;
+#[cfg(feature = "address-screening")]
+mod screening;
+pub mod settings_history;
 pub mod token_distribution;
+#[cfg(feature = "tracing")]
+mod trace;
+mod verification;
+mod view;
+mod witness_failure;
 
+pub use balance_watch::{BalanceThresholdReport, BalanceWatches, ThresholdCrossing};
+pub use block0_report::{
+    Block0ConfigReport, DefaultComparison, ExplicitSetting, MissingParameter, SuspiciousValue,
+};
+pub use block0_supply::Block0Supply;
+pub use epoch_transition::{EpochTransitionReport, EpochTransitionStage};
+pub use events::{
+    BlockRewardSummary, LedgerEvent, LedgerEvents, PledgeForfeitReport, PoolPerformanceReport,
+    RewardAccountFallbackReport, VotePlanCancelReport,
+};
+pub use extensions::{
+    CertificateExtension, CertificateExtensions, ExtensionContext, ExtensionRegistryError,
+    ExtensionRejection,
+};
+pub use fee_audit::{BlockFeeAudit, FragmentFeeAudit};
+pub use finality::{Finality, FinalityReport};
+pub use fragment_status::{FragmentStatus, FragmentStatusLog};
+pub use governance_quota::{GovernanceQuota, GovernanceQuotas, VotePlanSubmissions};
+pub use info::{EntryStats, LedgerStats};
+pub use initial_funds::{InitialFunds, InitialFundsError};
+pub use input_resolution::ResolvedInput;
 pub use iter::*;
 pub use leaderlog::LeadersParticipationRecord;
 pub use ledger::*;
+pub use metrics::{LedgerMetrics, NoopMetrics};
+pub use parameter_sanity::{ParameterSanityReport, ParameterWarning};
+pub(crate) use pool_rewards::RewardsComputation;
 pub use pots::Pots;
-pub use reward_info::{EpochRewardsInfo, RewardsInfoParameters};
+pub use preview::FragmentPreview;
+pub use randomness::{EpochRandomness, RandomnessProof};
+pub use reward_info::{EpochRewardsInfo, PledgeNotMet, RewardsInfoParameters};
+pub use reward_simulation::{simulate_rewards, SimulatedEpoch};
+#[cfg(feature = "address-screening")]
+pub use screening::{AddressScreening, ScreeningFlow, ScreeningVeto};
+pub use settings_history::{SettingsHistory, SETTINGS_HISTORY_DEPTH};
+pub use verification::{BlockVerificationError, VerificationChecks};
+pub use view::{LedgerSnapshot, LedgerView};
+pub use witness_failure::WitnessFailure;
 
 #[cfg(test)]
 pub mod tests;
@@ -0,0 +1,172 @@
+//! Sanity checks of the protocol parameters in force.
+//!
+//! Every parameter can be valid on its own while their combination makes
+//! the chain unusable. The checks of [`Ledger::parameter_warnings`] look at
+//! the parameters together with the state they apply to, and are run after
+//! every epoch transition changing the settings: the warnings, if any, are
+//! emitted in the events of the block with a [`ParameterSanityReport`]. They
+//! never make the block invalid.
+
+use super::ledger::Ledger;
+use crate::certificate::VotePlanId;
+use crate::chaintypes::ConsensusType;
+use crate::date::Epoch;
+use crate::fee::FeeAlgorithm;
+use crate::value::Value;
+use crate::vote::PayloadType;
+use std::fmt;
+
+/// A dangerous interaction of the parameters in force
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParameterWarning {
+    /// a transaction with one input and one output costs more than all the
+    /// value of the ledger, so no transaction can make it into a block
+    UnfillableBlocks {
+        minimal_fee: Value,
+        total_value: Value,
+    },
+    /// the KES keys of the stake pools evolve more than once per slot
+    KesFasterThanSlots {
+        slot_duration: u8,
+        kes_update_speed: u32,
+    },
+    /// there is no committee member to tally the private vote plans which
+    /// are not tallied yet
+    NoCommitteeForPrivateVotePlans { vote_plans: Vec<VotePlanId> },
+}
+
+/// The warnings of the parameters in force from the start of an epoch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParameterSanityReport {
+    pub epoch: Epoch,
+    pub warnings: Vec<ParameterWarning>,
+}
+
+impl Ledger {
+    /// The dangerous interactions of the parameters in force with each other
+    /// and with the state of the ledger, empty when the parameters are sane
+    pub fn parameter_warnings(&self) -> Vec<ParameterWarning> {
+        let mut warnings = Vec::new();
+
+        let minimal_fee = self.settings.linear_fees.calculate(None, 1, 1);
+        // a ledger which cannot sum its value has no fillable blocks either
+        let total_value = self.get_total_value().unwrap_or(Value(u64::MAX));
+        if minimal_fee > total_value {
+            warnings.push(ParameterWarning::UnfillableBlocks {
+                minimal_fee,
+                total_value,
+            });
+        }
+
+        let slot_duration = self.settings.slot_duration;
+        let kes_update_speed = self.static_params.kes_update_speed;
+        if self.settings.consensus_version == ConsensusType::GenesisPraos
+            && kes_update_speed < u32::from(slot_duration)
+        {
+            warnings.push(ParameterWarning::KesFasterThanSlots {
+                slot_duration,
+                kes_update_speed,
+            });
+        }
+
+        if self.settings.committees.is_empty() {
+            let vote_plans: Vec<_> = self
+                .votes
+                .plans
+                .iter()
+                .map(|(_, manager)| manager)
+                .filter(|manager| {
+                    manager.plan().payload_type() == PayloadType::Private
+                        && manager.cancelled().is_none()
+                        && self.date < manager.plan().committee_end()
+                })
+                .map(|manager| manager.id().clone())
+                .collect();
+            if !vote_plans.is_empty() {
+                warnings.push(ParameterWarning::NoCommitteeForPrivateVotePlans { vote_plans });
+            }
+        }
+
+        warnings
+    }
+}
+
+impl fmt::Display for ParameterWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParameterWarning::UnfillableBlocks {
+                minimal_fee,
+                total_value,
+            } => write!(
+                f,
+                "the minimal transaction fee {} exceeds the total value {} of the ledger",
+                minimal_fee, total_value
+            ),
+            ParameterWarning::KesFasterThanSlots {
+                slot_duration,
+                kes_update_speed,
+            } => write!(
+                f,
+                "KES keys evolve every {} seconds, faster than the slots of {} seconds",
+                kes_update_speed, slot_duration
+            ),
+            ParameterWarning::NoCommitteeForPrivateVotePlans { vote_plans } => write!(
+                f,
+                "no committee member to tally the {} private vote plans",
+                vote_plans.len()
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fee::LinearFee;
+    use crate::testing::{ConfigBuilder, LedgerBuilder};
+
+    #[test]
+    fn sane_parameters_have_no_warnings() {
+        let test_ledger = LedgerBuilder::from_config(ConfigBuilder::new())
+            .faucet_value(Value(1_000))
+            .build()
+            .unwrap();
+        assert_eq!(test_ledger.ledger.parameter_warnings(), Vec::new());
+    }
+
+    #[test]
+    fn fees_above_the_total_value_are_reported() {
+        let test_ledger = LedgerBuilder::from_config(
+            ConfigBuilder::new().with_fee(LinearFee::new(1_000_000, 1_000_000, 0)),
+        )
+        .faucet_value(Value(1_000))
+        .build()
+        .unwrap();
+        assert_eq!(
+            test_ledger.ledger.parameter_warnings(),
+            vec![ParameterWarning::UnfillableBlocks {
+                minimal_fee: Value(3_000_000),
+                total_value: test_ledger.ledger.get_total_value().unwrap(),
+            }]
+        );
+    }
+
+    #[test]
+    fn kes_faster_than_the_slots_is_reported() {
+        let test_ledger = LedgerBuilder::from_config(
+            ConfigBuilder::new()
+                .with_consensus_version(ConsensusType::GenesisPraos)
+                .with_slot_duration(20)
+                .with_kes_update_speed(10),
+        )
+        .build()
+        .unwrap();
+        assert_eq!(
+            test_ledger.ledger.parameter_warnings(),
+            vec![ParameterWarning::KesFasterThanSlots {
+                slot_duration: 20,
+                kes_update_speed: 10,
+            }]
+        );
+    }
+}
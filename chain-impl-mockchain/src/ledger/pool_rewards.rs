@@ -0,0 +1,224 @@
+//! Rewards of the stake pools for an epoch.
+//!
+//! Once the reward of the epoch is known, the share of every pool only
+//! depends on the blocks it created, its performance and its stake
+//! distribution. The shares are computed as independent tasks, one per pool,
+//! and the results are applied to the ledger in a single reduction step
+//! following the order of the leaders log, so the new ledger does not depend
+//! on how the tasks were scheduled.
+//!
+//! With the `parallel-rewards` feature the tasks run on the rayon thread pool.
+
+use super::reward_info::PledgeNotMet;
+use crate::account;
+use crate::certificate::{PoolId, RewardAccount};
+use crate::rewards;
+use crate::stake::{PercentStake, PoolPerformance, PoolStakeInformation, StakeDistribution};
+use crate::value::{Value, ValueError};
+#[cfg(feature = "parallel-rewards")]
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+
+/// How the tasks computing the rewards of the pools are run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RewardsComputation {
+    Sequential,
+    #[cfg(feature = "parallel-rewards")]
+    Parallel,
+}
+
+impl Default for RewardsComputation {
+    #[cfg(feature = "parallel-rewards")]
+    fn default() -> Self {
+        RewardsComputation::Parallel
+    }
+
+    #[cfg(not(feature = "parallel-rewards"))]
+    fn default() -> Self {
+        RewardsComputation::Sequential
+    }
+}
+
+/// Parameters shared by the tasks of all the pools
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PoolRewardsParams {
+    /// reward of a single block
+    pub reward_unit: Value,
+    /// the highest reward of a pool, if the participation is capped
+    pub pool_cap: Option<Value>,
+    pub pool_performance_rewards: bool,
+    pub minimum_pool_pledge: Value,
+}
+
+/// The rewards of a pool, to be applied to the ledger
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct PoolRewards {
+    /// the part of the rewards going back to the treasury
+    pub treasury: Value,
+    pub pledge_not_met: Option<PledgeNotMet>,
+    /// the rewards of the owners (taxed) and of the delegators (after tax)
+    pub stake_pool: Option<(Value, Value)>,
+    /// the rewards of every account, in the order they are credited
+    pub accounts: Vec<(account::Identifier, Value)>,
+    /// the rewards of the owners, when the reward account of the pool is not
+    /// a single account, credited after the accounts
+    pub reward_account: Option<(RewardAccount, Value)>,
+}
+
+impl PoolRewards {
+    fn forfeit(&mut self, value: Value) -> Result<(), ValueError> {
+        self.treasury = (self.treasury + value)?;
+        Ok(())
+    }
+}
+
+/// Run the task of every pool of the leaders log, the results are in the
+/// same order as the pools
+pub(crate) fn compute_pool_rewards(
+    computation: RewardsComputation,
+    params: &PoolRewardsParams,
+    leaders: &[(PoolId, u32)],
+    performances: &BTreeMap<PoolId, PoolPerformance>,
+    distribution: &StakeDistribution,
+) -> Result<Vec<PoolRewards>, ValueError> {
+    let task = |(pool_id, pool_blocks): &(PoolId, u32)| {
+        pool_rewards(
+            params,
+            *pool_blocks,
+            performances.get(pool_id),
+            distribution.to_pools.get(pool_id),
+        )
+    };
+    match computation {
+        RewardsComputation::Sequential => leaders.iter().map(task).collect(),
+        #[cfg(feature = "parallel-rewards")]
+        RewardsComputation::Parallel => leaders.par_iter().map(task).collect(),
+    }
+}
+
+/// The rewards of a pool which created `pool_blocks` blocks during the epoch
+fn pool_rewards(
+    params: &PoolRewardsParams,
+    pool_blocks: u32,
+    performance: Option<&PoolPerformance>,
+    distribution: Option<&PoolStakeInformation>,
+) -> Result<PoolRewards, ValueError> {
+    let mut rewards = PoolRewards::default();
+
+    // possibly cap the reward for a given pool.
+    // if this is capped, then the overflow amount is send to treasury
+    let uncapped = params.reward_unit.scale(pool_blocks)?;
+    let total_reward = match params.pool_cap {
+        None => uncapped,
+        Some(pool_cap) => {
+            let capped = std::cmp::min(pool_cap, uncapped);
+            rewards.forfeit((uncapped - capped)?)?;
+            capped
+        }
+    };
+
+    // reduce the reward of the pools which created less blocks
+    // than expected, the difference goes to the treasury
+    let total_reward = match performance {
+        Some(performance) if params.pool_performance_rewards => {
+            let scaled = performance.scale(total_reward);
+            rewards.forfeit((total_reward - scaled)?)?;
+            scaled
+        }
+        _ => total_reward,
+    };
+
+    let distribution = match distribution {
+        Some(distribution) => distribution,
+        None => {
+            // dump reward to treasury
+            rewards.forfeit(total_reward)?;
+            return Ok(rewards);
+        }
+    };
+
+    if let Some(pledge_not_met) =
+        pledge_not_met(distribution, params.minimum_pool_pledge, total_reward)
+    {
+        // the owners didn't delegate the pledged stake,
+        // the rewards of the pool go to the treasury
+        rewards.forfeit(total_reward)?;
+        rewards.pledge_not_met = Some(pledge_not_met);
+        return Ok(rewards);
+    }
+
+    let reg = match distribution.registration {
+        None => {
+            rewards.forfeit(total_reward)?;
+            return Ok(rewards);
+        }
+        Some(ref reg) => reg,
+    };
+
+    let distr = rewards::tax_cut(total_reward, &reg.rewards)?;
+    rewards.stake_pool = Some((distr.taxed, distr.after_tax));
+
+    // distribute to pool owners (or the reward account)
+    match &reg.reward_account {
+        Some(reward_account) => match reward_account.single_account() {
+            Some(single_account) => rewards.accounts.push((single_account, distr.taxed)),
+            None => rewards.reward_account = Some((reward_account.clone(), distr.taxed)),
+        },
+        None => {
+            if reg.owners.len() > 1 {
+                let splitted = distr.taxed.split_in(reg.owners.len() as u32);
+                for owner in &reg.owners {
+                    rewards
+                        .accounts
+                        .push((owner.clone().into(), splitted.parts));
+                }
+                // pool owners 0 get potentially an extra sweetener of value 1 to #owners - 1
+                if splitted.remaining > Value::zero() {
+                    rewards
+                        .accounts
+                        .push((reg.owners[0].clone().into(), splitted.remaining));
+                }
+            } else {
+                rewards
+                    .accounts
+                    .push((reg.owners[0].clone().into(), distr.taxed));
+            }
+        }
+    }
+
+    // distribute the rest to delegators
+    let mut leftover_reward = distr.after_tax;
+    if leftover_reward > Value::zero() {
+        for (account, stake) in distribution.stake.accounts.iter() {
+            let ps = PercentStake::new(*stake, distribution.stake.total);
+            let r = ps.scale_value(distr.after_tax);
+            leftover_reward = (leftover_reward - r)?;
+            rewards.accounts.push((account.clone(), r));
+        }
+    }
+
+    if leftover_reward > Value::zero() {
+        rewards.forfeit(leftover_reward)?;
+    }
+
+    Ok(rewards)
+}
+
+fn pledge_not_met(
+    distribution: &PoolStakeInformation,
+    minimum_pool_pledge: Value,
+    reward: Value,
+) -> Option<PledgeNotMet> {
+    let reg = distribution.registration.as_ref()?;
+    let pledge = std::cmp::max(reg.pledge, minimum_pool_pledge);
+    let owners_stake = Value(distribution.owners_stake()?.0);
+    if owners_stake < pledge {
+        Some(PledgeNotMet {
+            pledge,
+            owners_stake,
+            forfeited: reward,
+        })
+    } else {
+        None
+    }
+}
@@ -1,6 +1,6 @@
 use crate::ledger::Error;
 use crate::treasury::Treasury;
-use crate::value::{Value, ValueError};
+use crate::value::{SumOverflow, Value};
 use std::cmp;
 use std::fmt::Debug;
 
@@ -109,9 +109,10 @@ impl Pots {
         Values(self.entries())
     }
 
-    /// Sum the total values in the pots
-    pub fn total_value(&self) -> Result<Value, ValueError> {
-        Value::sum(self.values())
+    /// Sum the total values in the pots, the index of an overflow is the
+    /// one of the pot in the order of [`Pots::entries`]
+    pub fn total_value(&self) -> Result<Value, SumOverflow> {
+        Value::checked_sum(self.values())
     }
 
     /// Append some fees in the pots
@@ -0,0 +1,245 @@
+//! Dry-run of the application of a fragment.
+//!
+//! The ledger being persistent, the fragment is applied on a copy sharing
+//! its structure with the ledger, at the date and with the parameters of the
+//! ledger. The changes are read from the copy, which is then dropped, so the
+//! ledger is left untouched whatever the outcome.
+
+use super::account_history::{fragment_accounts, BalanceDelta};
+use super::ledger::{Error, Ledger};
+use crate::account;
+use crate::fragment::{Fragment, FragmentId};
+use crate::transaction::{InputEnum, Payload, TransactionSlice, UtxoPointer};
+use crate::utxo;
+use crate::value::Value;
+use chain_addr::Address;
+
+/// The changes the application of a fragment would make to the ledger
+#[derive(Debug, Clone, PartialEq)]
+pub struct FragmentPreview {
+    pub fragment_id: FragmentId,
+    /// the change of the balance of the accounts involved in the fragment,
+    /// the accounts left unchanged are omitted
+    pub accounts: Vec<(account::Identifier, BalanceDelta)>,
+    /// the unspent outputs spent by the fragment, the legacy ones excluded
+    pub utxos_spent: Vec<utxo::EntryOwned<Address>>,
+    /// the unspent outputs created by the fragment
+    pub utxos_created: Vec<utxo::EntryOwned<Address>>,
+    pub fee: Value,
+}
+
+impl Ledger {
+    /// Apply the fragment on a copy of the ledger, at the date of the ledger,
+    /// and return the changes it would make.
+    ///
+    /// The error is the one the application of the fragment would fail with.
+    pub fn preview_fragment(&self, fragment: &Fragment) -> Result<FragmentPreview, Error> {
+        let after = self.apply_fragment(&self.get_ledger_parameters(), fragment, self.date())?;
+
+        let mut accounts = fragment_accounts(fragment);
+        accounts.sort();
+        accounts.dedup();
+        let balance = |ledger: &Ledger, account: &account::Identifier| {
+            ledger
+                .accounts
+                .get_state(account)
+                .map(|state| state.value())
+                .unwrap_or_else(|_| Value::zero())
+        };
+        let accounts = accounts
+            .into_iter()
+            .filter_map(|account| {
+                BalanceDelta::between(balance(self, &account), balance(&after, &account))
+                    .map(|delta| (account, delta))
+            })
+            .collect();
+
+        let mut utxos_spent = Vec::new();
+        let mut utxos_created = Vec::new();
+        for (fragment_id, spent, nb_outputs) in fragment_utxos(fragment) {
+            utxos_spent.extend(spent.iter().filter_map(|pointer| {
+                get_utxo(self, &pointer.transaction_id, pointer.output_index)
+            }));
+            utxos_created
+                .extend((0..nb_outputs).filter_map(|index| get_utxo(&after, &fragment_id, index)));
+        }
+
+        let fee = after
+            .pots
+            .fees_value()
+            .checked_sub(self.pots.fees_value())
+            .unwrap_or_default();
+
+        Ok(FragmentPreview {
+            fragment_id: fragment.hash(),
+            accounts,
+            utxos_spent,
+            utxos_created,
+            fee,
+        })
+    }
+}
+
+fn get_utxo(
+    ledger: &Ledger,
+    fragment_id: &FragmentId,
+    index: u8,
+) -> Option<utxo::EntryOwned<Address>> {
+    ledger
+        .utxos
+        .get(fragment_id, index)
+        .map(|entry| utxo::EntryOwned {
+            fragment_id: entry.fragment_id,
+            output_index: entry.output_index,
+            output: entry.output.clone(),
+        })
+}
+
+/// the id, the utxo inputs and the number of outputs of every transaction
+/// of the fragment
+fn fragment_utxos(fragment: &Fragment) -> Vec<(FragmentId, Vec<UtxoPointer>, u8)> {
+    let id = fragment.hash();
+    match fragment {
+        Fragment::Initial(_) | Fragment::OldUtxoDeclaration(_) | Fragment::Evm(_) => Vec::new(),
+        Fragment::Transaction(tx) => vec![transaction_utxos(id, &tx.as_slice())],
+        Fragment::OwnerStakeDelegation(tx) => vec![transaction_utxos(id, &tx.as_slice())],
+        Fragment::StakeDelegation(tx) => vec![transaction_utxos(id, &tx.as_slice())],
+        Fragment::AmountStakeDelegation(tx) => vec![transaction_utxos(id, &tx.as_slice())],
+        Fragment::RewardsRedelegation(tx) => vec![transaction_utxos(id, &tx.as_slice())],
+        Fragment::ExtensionCertificate(tx) => vec![transaction_utxos(id, &tx.as_slice())],
+        Fragment::VotePlanCancel(tx) => vec![transaction_utxos(id, &tx.as_slice())],
+        // the legacy outputs spent are not reported, the destination is
+        // the only output
+        Fragment::LegacyMigration(_) => vec![(id, Vec::new(), 1)],
+        Fragment::PoolRegistration(tx) => vec![transaction_utxos(id, &tx.as_slice())],
+        Fragment::PoolRetirement(tx) => vec![transaction_utxos(id, &tx.as_slice())],
+        Fragment::PoolUpdate(tx) => vec![transaction_utxos(id, &tx.as_slice())],
+        Fragment::UpdateProposal(tx) => vec![transaction_utxos(id, &tx.as_slice())],
+        Fragment::UpdateVote(tx) => vec![transaction_utxos(id, &tx.as_slice())],
+        Fragment::VotePlan(tx) => vec![transaction_utxos(id, &tx.as_slice())],
+        Fragment::VoteCast(tx) => vec![transaction_utxos(id, &tx.as_slice())],
+        Fragment::VoteTally(tx) => vec![transaction_utxos(id, &tx.as_slice())],
+        Fragment::EncryptedVoteTally(tx) => vec![transaction_utxos(id, &tx.as_slice())],
+        Fragment::MintToken(tx) => vec![transaction_utxos(id, &tx.as_slice())],
+        Fragment::NameClaim(tx) => vec![transaction_utxos(id, &tx.as_slice())],
+        Fragment::NameRelease(tx) => vec![transaction_utxos(id, &tx.as_slice())],
+        Fragment::VotingPowerSnapshot(tx) => vec![transaction_utxos(id, &tx.as_slice())],
+        Fragment::SnapshotVoteCast(tx) => vec![transaction_utxos(id, &tx.as_slice())],
+        Fragment::VoteCastBatch(tx) => vec![transaction_utxos(id, &tx.as_slice())],
+        Fragment::TransactionBatch(batch) => batch
+            .transactions()
+            .iter()
+            .zip(batch.transaction_ids())
+            .map(|(tx, id)| transaction_utxos(id, &tx.as_slice()))
+            .collect(),
+    }
+}
+
+fn transaction_utxos<P: Payload>(
+    id: FragmentId,
+    tx: &TransactionSlice<P>,
+) -> (FragmentId, Vec<UtxoPointer>, u8) {
+    let spent = tx
+        .inputs()
+        .iter()
+        .filter_map(|input| match input.to_enum() {
+            InputEnum::UtxoInput(pointer) => Some(pointer),
+            InputEnum::AccountInput(..) => None,
+        })
+        .collect();
+    (id, spent, tx.nb_outputs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fee::LinearFee,
+        testing::{
+            builders::TestTxBuilder,
+            data::AddressDataValue,
+            ledger::{ConfigBuilder, LedgerBuilder},
+        },
+    };
+    use chain_addr::Discrimination;
+
+    #[test]
+    fn preview_of_an_account_transfer() {
+        let faucet = AddressDataValue::account(Discrimination::Test, Value(1_000));
+        let receiver = AddressDataValue::account(Discrimination::Test, Value(0));
+        let mut test_ledger =
+            LedgerBuilder::from_config(ConfigBuilder::new().with_fee(LinearFee::new(1, 1, 1)))
+                .faucet(&faucet)
+                .build()
+                .unwrap();
+        let fragment = TestTxBuilder::new(test_ledger.block0_hash)
+            .move_funds(&mut test_ledger, &faucet, &receiver, Value(100))
+            .get_fragment();
+        let ledger: Ledger = test_ledger.into();
+
+        let preview = ledger.preview_fragment(&fragment).unwrap();
+        assert_eq!(preview.fragment_id, fragment.hash());
+        assert!(preview.utxos_spent.is_empty());
+        assert!(preview.utxos_created.is_empty());
+
+        let delta = |account: &account::Identifier| {
+            preview
+                .accounts
+                .iter()
+                .find(|(id, _)| id == account)
+                .map(|(_, delta)| *delta)
+        };
+        let credited = match delta(&receiver.to_id()) {
+            Some(BalanceDelta::Credit(value)) => value,
+            delta => panic!("unexpected delta of the receiver: {:?}", delta),
+        };
+        assert_eq!(
+            delta(&faucet.to_id()),
+            Some(BalanceDelta::Debit(Value(100)))
+        );
+        assert_eq!(Some(preview.fee), Value(100).checked_sub(credited).ok());
+        assert!(preview.fee > Value::zero());
+
+        // the ledger is left untouched
+        assert_eq!(ledger.pots.fees_value(), Value::zero());
+        assert_eq!(
+            ledger.accounts.get_state(&faucet.to_id()).unwrap().value(),
+            Value(1_000)
+        );
+    }
+
+    #[test]
+    fn preview_of_a_utxo_transfer() {
+        let faucet = AddressDataValue::utxo(Discrimination::Test, Value(1_000));
+        let receiver = AddressDataValue::utxo(Discrimination::Test, Value(0));
+        let mut test_ledger = LedgerBuilder::from_config(ConfigBuilder::new())
+            .faucet(&faucet)
+            .build()
+            .unwrap();
+        let fragment = TestTxBuilder::new(test_ledger.block0_hash)
+            .move_funds(&mut test_ledger, &faucet, &receiver, Value(1_000))
+            .get_fragment();
+        let ledger: Ledger = test_ledger.into();
+        let spent = ledger.utxos().next().unwrap();
+
+        let preview = ledger.preview_fragment(&fragment).unwrap();
+        assert!(preview.accounts.is_empty());
+        assert_eq!(
+            preview.utxos_spent,
+            vec![utxo::EntryOwned {
+                fragment_id: spent.fragment_id,
+                output_index: spent.output_index,
+                output: spent.output.clone(),
+            }]
+        );
+        assert_eq!(preview.utxos_created.len(), 1);
+        assert_eq!(preview.utxos_created[0].fragment_id, fragment.hash());
+        assert_eq!(
+            preview.utxos_created[0].output.address,
+            receiver.address_data().address
+        );
+
+        // the ledger is left untouched
+        assert_eq!(ledger.utxos().collect::<Vec<_>>(), vec![spent]);
+    }
+}
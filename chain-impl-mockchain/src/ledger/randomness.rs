@@ -0,0 +1,153 @@
+//! Randomness beacon of the epochs.
+//!
+//! Every Praos block mixes the output of the VRF proof of its header into
+//! the consensus nonce of the ledger. The nonce reached at the end of an
+//! epoch is the output of the beacon for this epoch: it seeds the leader
+//! election of the next epoch and can't be known before the last block of
+//! the epoch is applied. The output of a past epoch is taken from the
+//! settings history, so it is only available for the last
+//! [`SETTINGS_HISTORY_DEPTH`] epochs.
+//!
+//! The applications needing randomness should not use the nonce directly,
+//! nor derive it from the bytes of a header, but go through
+//! [`EpochRandomness::derive`] with a domain of their own, so two
+//! applications never draw the same values.
+//!
+//! [`SETTINGS_HISTORY_DEPTH`]: super::settings_history::SETTINGS_HISTORY_DEPTH
+
+use super::ledger::Ledger;
+use crate::chaineval::{ConsensusEvalContext, PraosNonce};
+use crate::date::Epoch;
+use crate::header::Header;
+use crate::key::Hash;
+
+const DERIVATION_TAG: &[u8] = b"epoch-randomness";
+
+/// The output of the randomness beacon of an epoch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EpochRandomness {
+    pub epoch: Epoch,
+    pub nonce: PraosNonce,
+}
+
+impl EpochRandomness {
+    /// Derive the randomness of an application from the output of the beacon.
+    ///
+    /// The `domain` identifies the application (for example the name of a
+    /// lottery) and `input` what is drawn within it (for example the round
+    /// of the lottery). The derivation hashes, in this order, a fixed tag,
+    /// the length of the domain as a big endian u32, the domain, the epoch
+    /// as a big endian u32, the nonce and the input.
+    pub fn derive(&self, domain: &[u8], input: &[u8]) -> Hash {
+        let mut bytes =
+            Vec::with_capacity(DERIVATION_TAG.len() + 4 + domain.len() + 4 + 32 + input.len());
+        bytes.extend_from_slice(DERIVATION_TAG);
+        bytes.extend_from_slice(&(domain.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(domain);
+        bytes.extend_from_slice(&self.epoch.to_be_bytes());
+        bytes.extend_from_slice(self.nonce.as_ref());
+        bytes.extend_from_slice(input);
+        Hash::hash_bytes(&bytes)
+    }
+
+    /// Draw a number uniformly in `0..bound`, with the derivation of
+    /// [`derive`](Self::derive).
+    ///
+    /// A counter is appended to the input and incremented until the derived
+    /// value falls out of the biased range, so every number is equally
+    /// likely. Panics if `bound` is zero.
+    pub fn draw(&self, domain: &[u8], input: &[u8], bound: u64) -> u64 {
+        assert!(bound > 0, "cannot draw a number in an empty range");
+        // the values from `limit` have a modulo biased towards the low numbers
+        let limit = u64::MAX - u64::MAX % bound;
+        let mut counted_input = input.to_vec();
+        for counter in 0u32.. {
+            counted_input.truncate(input.len());
+            counted_input.extend_from_slice(&counter.to_be_bytes());
+            let hash = self.derive(domain, &counted_input);
+            let mut value = [0; 8];
+            value.copy_from_slice(&hash.as_ref()[0..8]);
+            let value = u64::from_be_bytes(value);
+            if value < limit {
+                return value % bound;
+            }
+        }
+        unreachable!("no value drawn in 2^32 attempts")
+    }
+}
+
+/// Proof of the output of the beacon of an epoch: the nonce at the end of
+/// the previous epoch and the VRF outputs of the Praos blocks of the epoch,
+/// in the order of the chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RandomnessProof {
+    pub previous: PraosNonce,
+    pub contributions: Vec<PraosNonce>,
+}
+
+impl RandomnessProof {
+    /// Collect the VRF outputs of the headers of the epoch, the headers of
+    /// the other epochs and the headers without VRF proof are skipped
+    pub fn from_headers<'a, I>(previous: PraosNonce, epoch: Epoch, headers: I) -> Self
+    where
+        I: IntoIterator<Item = &'a Header>,
+    {
+        let contributions = headers
+            .into_iter()
+            .filter(|header| header.block_date().epoch == epoch)
+            .filter_map(|header| match header.get_consensus_eval_context() {
+                ConsensusEvalContext::Praos { nonce, .. } => Some(nonce),
+                ConsensusEvalContext::Bft | ConsensusEvalContext::Genesis => None,
+            })
+            .collect();
+        RandomnessProof {
+            previous,
+            contributions,
+        }
+    }
+
+    /// Whether the contributions mix into the output of the beacon, the
+    /// same way the ledger mixes them when applying the blocks.
+    ///
+    /// This does not check the VRF proofs of the headers the contributions
+    /// are taken from, which are checked when the blocks are validated.
+    pub fn verify(&self, randomness: &EpochRandomness) -> bool {
+        let mut nonce = self.previous.clone();
+        for contribution in self.contributions.iter() {
+            nonce.hash_with(contribution);
+        }
+        nonce == randomness.nonce
+    }
+}
+
+impl Ledger {
+    /// The output of the beacon of a past epoch, `None` for the current
+    /// epoch, which is not over yet, and for the epochs older than the
+    /// settings history
+    pub fn epoch_randomness(&self, epoch: Epoch) -> Option<EpochRandomness> {
+        if epoch >= self.date.epoch {
+            return None;
+        }
+        self.settings_history
+            .get(epoch)
+            .map(|settings| EpochRandomness {
+                epoch,
+                nonce: settings.consensus_nonce.clone(),
+            })
+    }
+
+    /// The proof of the output of the beacon of a past epoch, from the
+    /// headers of the chain, see [`RandomnessProof::from_headers`]. `None`
+    /// when the output of the previous epoch is not known.
+    pub fn randomness_proof<'a, I>(&self, epoch: Epoch, headers: I) -> Option<RandomnessProof>
+    where
+        I: IntoIterator<Item = &'a Header>,
+    {
+        self.epoch_randomness(epoch)?;
+        let previous = match epoch.checked_sub(1) {
+            None => PraosNonce::zero(),
+            Some(previous_epoch) => self.epoch_randomness(previous_epoch)?.nonce,
+        };
+        Some(RandomnessProof::from_headers(previous, epoch, headers))
+    }
+}
@@ -34,51 +34,63 @@
 //! Notice that the `ledger::iter::Entry` type holds references to the data types but when loading
 //! them from the serialized object we need to hold them. That is why we use the `EntryOwned` type
 //! instead for deserializing. This data is then cloned as necessary into the final deserialized ledger.
+//!
+//! A snapshot can be written along with a [`SnapshotManifest`], see
+//! [`serialize_with_manifest`], which [`deserialize_with_manifest`] checks
+//! before rebuilding the ledger.
+//!
+//! A snapshot can be compressed, see [`crate::compression`], and is then
+//! decompressed as it is read by [`Deserialize`].
+//!
+//! A partial snapshot only holds the entries of some subsystems of the ledger, see
+//! [`serialize_partial`], and is merged into an existing ledger with [`PartialSnapshot::merge_into`].
 
 use super::pots;
 use super::{Entry, EntryOwned};
-use crate::account::AccountAlg;
 use crate::accounting::account::{
-    AccountState, DelegationRatio, DelegationType, LastRewards, SpendingCounter,
-    SpendingCounterIncreasing,
+    AccountState, DelegationAmounts, DelegationRatio, DelegationType, LastRewards, SpendingCounter,
+    SpendingCounterIncreasing, SpendingCycle,
 };
+use crate::audit::LedgerDigest;
 use crate::certificate::{
     PoolId, PoolRegistration, Proposal, Proposals, UpdateProposal, UpdateProposalId, UpdateVoterId,
     VoteAction, VotePlan,
 };
+use crate::compression;
 use crate::config::ConfigParam;
 use crate::date::BlockDate;
-use crate::fragment::FragmentId;
+use crate::fragment::{ConfigParams, FragmentId};
 use crate::header::{ChainLength, HeaderId};
-use crate::key::serialize_public_key;
-use crate::ledger::{Globals, Ledger, LedgerStaticParameters};
+use crate::key::{serialize_public_key, Hash};
+use crate::ledger::{
+    Globals, GovernanceQuotas, Ledger, LedgerStaticParameters, VotePlanSubmissions,
+};
 use crate::legacy;
 use crate::multisig::{DeclElement, Declaration};
-use crate::stake::{PoolLastRewards, PoolState};
+use crate::names::{Name, NameRecord};
+use crate::stake::{PoolLastRewards, PoolPerformance, PoolState, POOL_PERFORMANCE_HISTORY_DEPTH};
 use crate::tokens::identifier::TokenIdentifier;
-use crate::tokens::name::TokenName;
-use crate::tokens::policy_hash::{PolicyHash, POLICY_HASH_SIZE};
-use crate::transaction::Output;
+use crate::transaction::{Output, OutputExtra};
 use crate::update::UpdateProposalState;
 use crate::value::Value;
 use crate::vote;
 use crate::{config, key, multisig, utxo};
 use chain_addr::{Address, Discrimination};
-use chain_core::mempack::{ReadBuf, Readable};
 use chain_crypto::digest::{DigestAlg, DigestOf};
-use chain_crypto::AsymmetricPublicKey;
-use chain_ser::deser::{Deserialize, Serialize};
+use chain_ser::deser::{Deserialize, Pack, Serialize, Unpack};
 use chain_ser::packer::Codec;
 use chain_time::era::{pack_time_era, unpack_time_era};
+use cryptoxide::{blake2b::Blake2b, digest::Digest};
 use imhamt::Hamt;
 use std::io::{self, BufRead, ErrorKind, Read, Write};
 use std::sync::Arc;
+use thiserror::Error;
 
 #[cfg(test)]
 use crate::{
     chaintypes::ConsensusVersion,
+    config::ConfigParamVariant,
     fee::{LinearFee, PerCertificateFee, PerVoteCertificateFee},
-    fragment::ConfigParams,
     key::BftLeaderId,
 };
 
@@ -155,15 +167,7 @@ fn pack_account_identifier<W: std::io::Write>(
 fn unpack_account_identifier<R: std::io::BufRead>(
     codec: &mut Codec<R>,
 ) -> Result<crate::account::Identifier, std::io::Error> {
-    let bytes = codec.get_bytes(<AccountAlg as AsymmetricPublicKey>::PUBLIC_KEY_SIZE as usize)?;
-    let mut bytes_buff = ReadBuf::from(&bytes);
-    match crate::account::Identifier::read(&mut bytes_buff) {
-        Ok(identifier) => Ok(identifier),
-        Err(e) => Err(std::io::Error::new(
-            std::io::ErrorKind::InvalidData,
-            format!("Error reading Identifier: {}", e),
-        )),
-    }
+    crate::account::Identifier::unpack(codec).map_err(io::Error::from)
 }
 
 fn pack_spending_strategy<W: std::io::Write>(
@@ -203,7 +207,7 @@ fn pack_account_state<W: std::io::Write>(
     codec: &mut Codec<W>,
 ) -> Result<(), std::io::Error> {
     pack_spending_strategy(&account_state.spending, codec)?;
-    pack_delegation_type(&account_state.delegation, codec)?;
+    pack_account_delegation(account_state, codec)?;
     codec.put_be_u64(account_state.value.0)?;
     pack_last_rewards(&account_state.last_rewards, codec)?;
     Ok(())
@@ -213,19 +217,80 @@ fn unpack_account_state<R: std::io::BufRead>(
     codec: &mut Codec<R>,
 ) -> Result<AccountState<()>, std::io::Error> {
     let spending = unpack_spending_strategy(codec)?;
-    let delegation = unpack_delegation_type(codec)?;
+    let (delegation, delegation_amounts) = unpack_account_delegation(codec)?;
     let value = codec.get_be_u64()?;
     let last_rewards = unpack_last_rewards(codec)?;
     Ok(AccountState {
         spending,
         delegation,
+        delegation_amounts,
         value: Value(value),
         tokens: Hamt::new(),
         last_rewards,
+        redelegated_rewards: None,
         extra: (),
     })
 }
 
+// The cycles of the spending counters are only serialized for the accounts
+// where a counter wrapped around, with their own entry codes, so the entries
+// of the other accounts keep the format they had before the cycles.
+fn pack_account_state_with_cycles<W: std::io::Write>(
+    account_state: &AccountState<()>,
+    codec: &mut Codec<W>,
+) -> Result<(), std::io::Error> {
+    pack_account_state(account_state, codec)?;
+    for cycle in account_state.spending.get_cycles() {
+        codec.put_be_u32(cycle)?;
+    }
+    Ok(())
+}
+
+fn unpack_account_state_with_cycles<R: std::io::BufRead>(
+    codec: &mut Codec<R>,
+) -> Result<AccountState<()>, std::io::Error> {
+    let mut account_state = unpack_account_state(codec)?;
+    let mut cycles: Vec<SpendingCycle> = Vec::with_capacity(SpendingCounterIncreasing::LANES);
+    for _ in 0..SpendingCounterIncreasing::LANES {
+        cycles.push(codec.get_be_u32()?);
+    }
+    account_state.spending = SpendingCounterIncreasing::new_from_counters_and_cycles(
+        account_state.spending.get_valid_counters(),
+        cycles,
+    )
+    .ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "Error building the spending counters from their cycles",
+        )
+    })?;
+    Ok(account_state)
+}
+
+// The accounts redelegating their rewards have their own entry code, with the
+// cycles of the spending counters whether they wrapped or not.
+fn pack_account_state_with_redelegation<W: std::io::Write>(
+    account_state: &AccountState<()>,
+    codec: &mut Codec<W>,
+) -> Result<(), std::io::Error> {
+    pack_account_state_with_cycles(account_state, codec)?;
+    codec.put_be_u64(
+        account_state
+            .redelegated_rewards
+            .unwrap_or_else(Value::zero)
+            .0,
+    )?;
+    Ok(())
+}
+
+fn unpack_account_state_with_redelegation<R: std::io::BufRead>(
+    codec: &mut Codec<R>,
+) -> Result<AccountState<()>, std::io::Error> {
+    let mut account_state = unpack_account_state_with_cycles(codec)?;
+    account_state.redelegated_rewards = Some(Value(codec.get_be_u64()?));
+    Ok(account_state)
+}
+
 fn pack_delegation_ratio<W: std::io::Write>(
     delegation_ratio: &DelegationRatio,
     codec: &mut Codec<W>,
@@ -259,6 +324,37 @@ fn unpack_delegation_ratio<R: std::io::BufRead>(
     }
 }
 
+fn pack_delegation_amounts<W: std::io::Write>(
+    delegation_amounts: &DelegationAmounts,
+    codec: &mut Codec<W>,
+) -> Result<(), std::io::Error> {
+    // len of items in pools, for later use by the deserialize method
+    codec.put_be_u64(delegation_amounts.pools.len() as u64)?;
+    for (pool_id, value) in delegation_amounts.pools.iter() {
+        codec.put_be_u64(value.0)?;
+        pack_pool_id(pool_id, codec)?;
+    }
+    Ok(())
+}
+
+fn unpack_delegation_amounts<R: std::io::BufRead>(
+    codec: &mut Codec<R>,
+) -> Result<DelegationAmounts, std::io::Error> {
+    let pools_size = codec.get_be_u64()?;
+    let mut pools: Vec<(PoolId, Value)> = Vec::with_capacity(pools_size as usize);
+    for _ in 0..pools_size {
+        let value = Value(codec.get_be_u64()?);
+        pools.push((unpack_pool_id(codec)?, value));
+    }
+    match DelegationAmounts::new(pools) {
+        Some(delegation_amounts) => Ok(delegation_amounts),
+        None => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "Error building DelegationAmounts from serialized data",
+        )),
+    }
+}
+
 fn pack_delegation_type<W: std::io::Write>(
     delegation_type: &DelegationType,
     codec: &mut Codec<W>,
@@ -280,9 +376,10 @@ fn pack_delegation_type<W: std::io::Write>(
 }
 
 fn unpack_delegation_type<R: std::io::BufRead>(
+    code: u8,
     codec: &mut Codec<R>,
 ) -> Result<DelegationType, std::io::Error> {
-    match codec.get_u8()? {
+    match code {
         0 => Ok(DelegationType::NonDelegated),
         1 => {
             let pool_id = unpack_pool_id(codec)?;
@@ -299,6 +396,33 @@ fn unpack_delegation_type<R: std::io::BufRead>(
     }
 }
 
+// The delegation of an account is either a delegation type, or the explicit
+// amounts it delegates with their own code.
+fn pack_account_delegation<W: std::io::Write>(
+    account_state: &AccountState<()>,
+    codec: &mut Codec<W>,
+) -> Result<(), std::io::Error> {
+    match account_state.delegation_amounts() {
+        Some(delegation_amounts) => {
+            codec.put_u8(3)?;
+            pack_delegation_amounts(delegation_amounts, codec)
+        }
+        None => pack_delegation_type(account_state.delegation(), codec),
+    }
+}
+
+fn unpack_account_delegation<R: std::io::BufRead>(
+    codec: &mut Codec<R>,
+) -> Result<(DelegationType, Option<DelegationAmounts>), std::io::Error> {
+    match codec.get_u8()? {
+        3 => {
+            let delegation_amounts = unpack_delegation_amounts(codec)?;
+            Ok((DelegationType::NonDelegated, Some(delegation_amounts)))
+        }
+        code => Ok((unpack_delegation_type(code, codec)?, None)),
+    }
+}
+
 fn pack_last_rewards<W: std::io::Write>(
     last_rewards: &LastRewards,
     codec: &mut Codec<W>,
@@ -363,15 +487,9 @@ fn unpack_pool_registration<R: std::io::BufRead>(
     codec: &mut Codec<R>,
 ) -> Result<PoolRegistration, std::io::Error> {
     let size = codec.get_be_u64()? as usize;
-    let bytes_buff = codec.get_bytes(size)?;
-    let mut read_buff = ReadBuf::from(&bytes_buff);
-    match PoolRegistration::read(&mut read_buff) {
-        Ok(res) => Ok(res),
-        Err(err) => Err(std::io::Error::new(
-            std::io::ErrorKind::InvalidData,
-            format!("Error reading PoolRegistration data: {}", err),
-        )),
-    }
+    codec
+        .with_slice(size, PoolRegistration::unpack)
+        .map_err(io::Error::from)
 }
 
 fn pack_config_param<W: Write>(
@@ -404,14 +522,40 @@ fn unpack_block_date<R: std::io::BufRead>(
     Ok(BlockDate { epoch, slot_id })
 }
 
+// the fees are packed with the encoding of their config parameters, so the
+// two formats cannot drift apart
+
+#[cfg(test)]
+const LINEAR_FEE_PAYLOAD_SIZE: usize = 3 * 8;
+#[cfg(test)]
+const PER_CERTIFICATE_FEE_PAYLOAD_SIZE: usize = 3 * 8;
+#[cfg(test)]
+const PER_VOTE_CERTIFICATE_FEE_PAYLOAD_SIZE: usize = 2 * 8;
+
+#[cfg(test)]
+fn pack_config_param_variant<T: ConfigParamVariant, W: std::io::Write>(
+    variant: &T,
+    codec: &mut Codec<W>,
+) -> Result<(), std::io::Error> {
+    codec.put_bytes(&variant.to_payload())
+}
+
+#[cfg(test)]
+fn unpack_config_param_variant<T: ConfigParamVariant, R: std::io::BufRead>(
+    size: usize,
+    codec: &mut Codec<R>,
+) -> Result<T, std::io::Error> {
+    let payload = codec.get_bytes(size)?;
+    T::from_payload(&payload)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))
+}
+
 #[cfg(test)]
 fn pack_linear_fee<W: std::io::Write>(
     linear_fee: &LinearFee,
     codec: &mut Codec<W>,
 ) -> Result<(), std::io::Error> {
-    codec.put_be_u64(linear_fee.constant)?;
-    codec.put_be_u64(linear_fee.coefficient)?;
-    codec.put_be_u64(linear_fee.certificate)?;
+    pack_config_param_variant(linear_fee, codec)?;
     pack_per_certificate_fee(&linear_fee.per_certificate_fees, codec)?;
     pack_per_vote_certificate_fee(&linear_fee.per_vote_certificate_fees, codec)?;
     Ok(())
@@ -421,17 +565,11 @@ fn pack_linear_fee<W: std::io::Write>(
 fn unpack_linear_fee<R: std::io::BufRead>(
     codec: &mut Codec<R>,
 ) -> Result<LinearFee, std::io::Error> {
-    let constant = codec.get_be_u64()?;
-    let coefficient = codec.get_be_u64()?;
-    let certificate = codec.get_be_u64()?;
-    let per_certificate_fees = unpack_per_certificate_fee(codec)?;
-    let per_vote_certificate_fees = unpack_per_vote_certificate_fee(codec)?;
+    let linear_fee: LinearFee = unpack_config_param_variant(LINEAR_FEE_PAYLOAD_SIZE, codec)?;
     Ok(LinearFee {
-        constant,
-        coefficient,
-        certificate,
-        per_certificate_fees,
-        per_vote_certificate_fees,
+        per_certificate_fees: unpack_per_certificate_fee(codec)?,
+        per_vote_certificate_fees: unpack_per_vote_certificate_fee(codec)?,
+        ..linear_fee
     })
 }
 
@@ -440,25 +578,7 @@ fn pack_per_certificate_fee<W: std::io::Write>(
     per_certificate_fee: &PerCertificateFee,
     codec: &mut Codec<W>,
 ) -> Result<(), std::io::Error> {
-    codec.put_be_u64(
-        per_certificate_fee
-            .certificate_pool_registration
-            .map(|v| v.get())
-            .unwrap_or(0),
-    )?;
-    codec.put_be_u64(
-        per_certificate_fee
-            .certificate_stake_delegation
-            .map(|v| v.get())
-            .unwrap_or(0),
-    )?;
-    codec.put_be_u64(
-        per_certificate_fee
-            .certificate_owner_stake_delegation
-            .map(|v| v.get())
-            .unwrap_or(0),
-    )?;
-    Ok(())
+    pack_config_param_variant(per_certificate_fee, codec)
 }
 
 #[cfg(test)]
@@ -466,51 +586,23 @@ fn pack_per_vote_certificate_fee<W: std::io::Write>(
     per_vote_certificate_fee: &PerVoteCertificateFee,
     codec: &mut Codec<W>,
 ) -> Result<(), std::io::Error> {
-    codec.put_be_u64(
-        per_vote_certificate_fee
-            .certificate_vote_plan
-            .map(|v| v.get())
-            .unwrap_or(0),
-    )?;
-    codec.put_be_u64(
-        per_vote_certificate_fee
-            .certificate_vote_cast
-            .map(|v| v.get())
-            .unwrap_or(0),
-    )?;
-    Ok(())
+    pack_config_param_variant(per_vote_certificate_fee, codec)
 }
 
 #[cfg(test)]
 fn unpack_per_certificate_fee<R: std::io::BufRead>(
     codec: &mut Codec<R>,
 ) -> Result<PerCertificateFee, std::io::Error> {
-    let certificate_pool_registration = std::num::NonZeroU64::new(codec.get_be_u64()?);
-    let certificate_stake_delegation = std::num::NonZeroU64::new(codec.get_be_u64()?);
-    let certificate_owner_stake_delegation = std::num::NonZeroU64::new(codec.get_be_u64()?);
-
-    Ok(PerCertificateFee {
-        certificate_pool_registration,
-        certificate_stake_delegation,
-        certificate_owner_stake_delegation,
-    })
+    unpack_config_param_variant(PER_CERTIFICATE_FEE_PAYLOAD_SIZE, codec)
 }
 
 #[cfg(test)]
 fn unpack_per_vote_certificate_fee<R: std::io::BufRead>(
     codec: &mut Codec<R>,
 ) -> Result<PerVoteCertificateFee, std::io::Error> {
-    let certificate_vote_plan = std::num::NonZeroU64::new(codec.get_be_u64()?);
-    let certificate_vote_cast = std::num::NonZeroU64::new(codec.get_be_u64()?);
-
-    Ok(PerVoteCertificateFee {
-        certificate_vote_plan,
-        certificate_vote_cast,
-    })
+    unpack_config_param_variant(PER_VOTE_CERTIFICATE_FEE_PAYLOAD_SIZE, codec)
 }
 
-#[allow(dead_code)]
-#[cfg(test)]
 fn pack_config_params<W: std::io::Write>(
     config_params: &ConfigParams,
     codec: &mut Codec<W>,
@@ -518,8 +610,6 @@ fn pack_config_params<W: std::io::Write>(
     config_params.serialize(codec)
 }
 
-#[allow(dead_code)]
-#[cfg(test)]
 fn unpack_config_params<R: std::io::BufRead>(
     codec: &mut Codec<R>,
 ) -> Result<ConfigParams, std::io::Error> {
@@ -729,11 +819,39 @@ fn unpack_pool_last_rewards<R: std::io::BufRead>(
     })
 }
 
+fn pack_pool_performance<W: std::io::Write>(
+    pool_performance: &PoolPerformance,
+    codec: &mut Codec<W>,
+) -> Result<(), std::io::Error> {
+    codec.put_be_u32(pool_performance.epoch)?;
+    codec.put_be_u32(pool_performance.expected)?;
+    codec.put_be_u32(pool_performance.produced)?;
+    Ok(())
+}
+
+fn unpack_pool_performance<R: std::io::BufRead>(
+    codec: &mut Codec<R>,
+) -> Result<PoolPerformance, std::io::Error> {
+    let epoch = codec.get_be_u32()?;
+    let expected = codec.get_be_u32()?;
+    let produced = codec.get_be_u32()?;
+
+    Ok(PoolPerformance {
+        epoch,
+        expected,
+        produced,
+    })
+}
+
 fn pack_pool_state<W: std::io::Write>(
     pool_state: &PoolState,
     codec: &mut Codec<W>,
 ) -> Result<(), std::io::Error> {
     pack_pool_last_rewards(&pool_state.last_rewards, codec)?;
+    codec.put_u8(pool_state.performances().len() as u8)?;
+    for performance in pool_state.performances() {
+        pack_pool_performance(performance, codec)?;
+    }
     pack_pool_registration(&pool_state.registration, codec)?;
     Ok(())
 }
@@ -742,10 +860,21 @@ fn unpack_pool_state<R: std::io::BufRead>(
     codec: &mut Codec<R>,
 ) -> Result<PoolState, std::io::Error> {
     let last_rewards = unpack_pool_last_rewards(codec)?;
+    let performances_count = codec.get_u8()? as usize;
+    if performances_count > POOL_PERFORMANCE_HISTORY_DEPTH {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "pool performance history exceeds its maximum depth",
+        ));
+    }
+    let performances = (0..performances_count)
+        .map(|_| unpack_pool_performance(codec))
+        .collect::<Result<_, _>>()?;
     let registration = Arc::new(unpack_pool_registration(codec)?);
 
     Ok(PoolState {
         last_rewards,
+        performances,
         registration,
     })
 }
@@ -815,12 +944,13 @@ fn unpack_update_proposal_id<R: std::io::BufRead>(
     UpdateProposalId::deserialize(codec)
 }
 
-fn pack_utxo_entry<OutputAddress, F, W: std::io::Write>(
-    entry: &utxo::Entry<'_, OutputAddress>,
+fn pack_utxo_entry<OutputAddress, Extra, F, W: std::io::Write>(
+    entry: &utxo::Entry<'_, OutputAddress, Extra>,
     output_address_packer: &mut F,
     codec: &mut Codec<W>,
 ) -> Result<(), std::io::Error>
 where
+    Extra: OutputExtra,
     F: FnMut(&OutputAddress, &mut Codec<W>) -> Result<(), std::io::Error>,
 {
     let fragment_id_bytes = entry.fragment_id.as_ref();
@@ -830,18 +960,19 @@ where
     Ok(())
 }
 
-fn unpack_utxo_entry_owned<OutputAddress, F, R: std::io::BufRead>(
+fn unpack_utxo_entry_owned<OutputAddress, Extra, F, R: std::io::BufRead>(
     output_address_unpacker: &mut F,
     codec: &mut Codec<R>,
-) -> Result<utxo::EntryOwned<OutputAddress>, std::io::Error>
+) -> Result<utxo::EntryOwned<OutputAddress, Extra>, std::io::Error>
 where
+    Extra: OutputExtra,
     F: FnMut(&mut Codec<R>) -> Result<OutputAddress, std::io::Error>,
 {
     let mut fragment_id_bytes: [u8; 32] = [0; 32];
     codec.read_exact(&mut fragment_id_bytes)?;
     let fragment_id = FragmentId::from_bytes(fragment_id_bytes);
     let output_index = codec.get_u8()?;
-    let output: Output<OutputAddress> = unpack_output(output_address_unpacker, codec)?;
+    let output = unpack_output(output_address_unpacker, codec)?;
     Ok(utxo::EntryOwned {
         fragment_id,
         output_index,
@@ -849,29 +980,33 @@ where
     })
 }
 
-fn pack_output<OutputAddress, F, W: std::io::Write>(
-    output: &Output<OutputAddress>,
+fn pack_output<OutputAddress, Extra, F, W: std::io::Write>(
+    output: &Output<OutputAddress, Extra>,
     address_packer: &mut F,
     codec: &mut Codec<W>,
 ) -> Result<(), std::io::Error>
 where
+    Extra: OutputExtra,
     F: FnMut(&OutputAddress, &mut Codec<W>) -> Result<(), std::io::Error>,
 {
     address_packer(&output.address, codec)?;
     codec.put_be_u64(output.value.0)?;
+    output.extra.write_extra(codec)?;
     Ok(())
 }
 
-fn unpack_output<OutputAddress, F, R: std::io::BufRead>(
+fn unpack_output<OutputAddress, Extra, F, R: std::io::BufRead>(
     address_unpacker: &mut F,
     codec: &mut Codec<R>,
-) -> Result<Output<OutputAddress>, std::io::Error>
+) -> Result<Output<OutputAddress, Extra>, std::io::Error>
 where
+    Extra: OutputExtra,
     F: FnMut(&mut Codec<R>) -> Result<OutputAddress, std::io::Error>,
 {
     let address = address_unpacker(codec)?;
     let value = Value(codec.get_be_u64()?);
-    Ok(Output { address, value })
+    let extra = Extra::read_extra(codec)?;
+    Ok(Output::with_extra(address, value, extra))
 }
 
 fn pack_old_addr<W: std::io::Write>(
@@ -920,7 +1055,7 @@ fn pack_vote_proposal<W: std::io::Write>(
 ) -> Result<(), std::io::Error> {
     pack_digestof(proposal.external_id(), codec)?;
     codec.put_u8(proposal.options().as_byte())?;
-    Ok(())
+    pack_vote_action(proposal.action(), codec)
 }
 
 fn unpack_proposal<R: std::io::BufRead>(codec: &mut Codec<R>) -> Result<Proposal, std::io::Error> {
@@ -931,10 +1066,17 @@ fn unpack_proposal<R: std::io::BufRead>(codec: &mut Codec<R>) -> Result<Proposal
     Ok(Proposal::new(external_id, options, action))
 }
 
+fn pack_vote_action<W: std::io::Write>(
+    action: &VoteAction,
+    codec: &mut Codec<W>,
+) -> Result<(), std::io::Error> {
+    action.pack(codec)
+}
+
 fn unpack_vote_action<R: std::io::BufRead>(
-    _codec: &mut Codec<R>,
+    codec: &mut Codec<R>,
 ) -> Result<VoteAction, std::io::Error> {
-    todo!()
+    VoteAction::unpack(codec).map_err(io::Error::from)
 }
 
 fn pack_vote_proposals<W: std::io::Write>(
@@ -1009,27 +1151,11 @@ fn pack_voting_token<W: std::io::Write>(
     token_identifier: &TokenIdentifier,
     codec: &mut Codec<W>,
 ) -> Result<(), std::io::Error> {
-    codec.put_bytes(&token_identifier.bytes())?;
-
-    Ok(())
+    token_identifier.pack(codec)
 }
 
 fn unpack_voting_token<R: BufRead>(codec: &mut Codec<R>) -> Result<TokenIdentifier, io::Error> {
-    let policy_hash_bytes = codec.get_bytes(POLICY_HASH_SIZE)?;
-
-    let policy_hash = PolicyHash::try_from(policy_hash_bytes.as_ref())
-        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
-
-    let token_name_len = codec.get_u8()? as usize;
-    let token_name = codec.get_bytes(token_name_len)?;
-
-    let token_name = TokenName::try_from(token_name)
-        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
-
-    Ok(TokenIdentifier {
-        policy_hash,
-        token_name,
-    })
+    TokenIdentifier::unpack(codec).map_err(io::Error::from)
 }
 
 fn pack_vote_plan<W: std::io::Write>(
@@ -1065,6 +1191,60 @@ fn unpack_vote_plan<R: std::io::BufRead>(codec: &mut Codec<R>) -> Result<VotePla
     ))
 }
 
+fn pack_name<W: std::io::Write>(name: &Name, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+    name.pack(codec)
+}
+
+fn unpack_name<R: BufRead>(codec: &mut Codec<R>) -> Result<Name, io::Error> {
+    Name::unpack(codec).map_err(io::Error::from)
+}
+
+fn pack_name_record<W: std::io::Write>(
+    record: &NameRecord,
+    codec: &mut Codec<W>,
+) -> Result<(), std::io::Error> {
+    pack_account_identifier(&record.owner, codec)?;
+    codec.put_be_u32(record.expiry)?;
+    Ok(())
+}
+
+fn unpack_name_record<R: BufRead>(codec: &mut Codec<R>) -> Result<NameRecord, io::Error> {
+    let owner = unpack_account_identifier(codec)?;
+    let expiry = codec.get_be_u32()?;
+    Ok(NameRecord { owner, expiry })
+}
+
+fn pack_committee_id<W: std::io::Write>(
+    id: &vote::CommitteeId,
+    codec: &mut Codec<W>,
+) -> Result<(), std::io::Error> {
+    codec.put_bytes(id.as_ref())
+}
+
+fn unpack_committee_id<R: BufRead>(codec: &mut Codec<R>) -> Result<vote::CommitteeId, io::Error> {
+    let bytes = codec.get_bytes(vote::CommitteeId::COMMITTEE_ID_SIZE)?;
+    let mut id = [0u8; vote::CommitteeId::COMMITTEE_ID_SIZE];
+    id.copy_from_slice(&bytes);
+    Ok(vote::CommitteeId::from(id))
+}
+
+fn pack_vote_plan_submissions<W: std::io::Write>(
+    submissions: &VotePlanSubmissions,
+    codec: &mut Codec<W>,
+) -> Result<(), std::io::Error> {
+    codec.put_be_u32(submissions.epoch)?;
+    codec.put_be_u32(submissions.count)?;
+    Ok(())
+}
+
+fn unpack_vote_plan_submissions<R: BufRead>(
+    codec: &mut Codec<R>,
+) -> Result<VotePlanSubmissions, io::Error> {
+    let epoch = codec.get_be_u32()?;
+    let count = codec.get_be_u32()?;
+    Ok(VotePlanSubmissions { epoch, count })
+}
+
 #[derive(Debug, Eq, PartialEq)]
 enum EntrySerializeCode {
     Globals = 0,
@@ -1079,6 +1259,13 @@ enum EntrySerializeCode {
     StakePool = 9,
     LeaderParticipation = 10,
     VotePlan = 11,
+    Name = 12,
+    EpochSettings = 13,
+    AccountWithCycles = 14,
+    MultisigAccountWithCycles = 15,
+    PartialSnapshot = 16,
+    GovernanceQuota = 17,
+    AccountWithRewardsRedelegation = 18,
     SerializationEnd = 99,
 }
 
@@ -1097,6 +1284,13 @@ impl EntrySerializeCode {
             9 => Some(EntrySerializeCode::StakePool),
             10 => Some(EntrySerializeCode::LeaderParticipation),
             11 => Some(EntrySerializeCode::VotePlan),
+            12 => Some(EntrySerializeCode::Name),
+            13 => Some(EntrySerializeCode::EpochSettings),
+            14 => Some(EntrySerializeCode::AccountWithCycles),
+            15 => Some(EntrySerializeCode::MultisigAccountWithCycles),
+            16 => Some(EntrySerializeCode::PartialSnapshot),
+            17 => Some(EntrySerializeCode::GovernanceQuota),
+            18 => Some(EntrySerializeCode::AccountWithRewardsRedelegation),
             99 => Some(EntrySerializeCode::SerializationEnd),
             _ => None,
         }
@@ -1124,6 +1318,18 @@ fn pack_entry<W: std::io::Write>(
             codec.put_u8(EntrySerializeCode::OldUtxo as u8)?;
             pack_utxo_entry(entry, &mut pack_old_addr, codec)?;
         }
+        Entry::Account((identifier, account_state))
+            if account_state.redelegated_rewards.is_some() =>
+        {
+            codec.put_u8(EntrySerializeCode::AccountWithRewardsRedelegation as u8)?;
+            pack_account_identifier(identifier, codec)?;
+            pack_account_state_with_redelegation(account_state, codec)?;
+        }
+        Entry::Account((identifier, account_state)) if account_state.spending.has_wrapped() => {
+            codec.put_u8(EntrySerializeCode::AccountWithCycles as u8)?;
+            pack_account_identifier(identifier, codec)?;
+            pack_account_state_with_cycles(account_state, codec)?;
+        }
         Entry::Account((identifier, account_state)) => {
             codec.put_u8(EntrySerializeCode::Account as u8)?;
             pack_account_identifier(identifier, codec)?;
@@ -1138,6 +1344,13 @@ fn pack_entry<W: std::io::Write>(
             pack_update_proposal_id(proposal_id, codec)?;
             pack_update_proposal_state(proposal_state, codec)?;
         }
+        Entry::MultisigAccount((identifier, account_state))
+            if account_state.spending.has_wrapped() =>
+        {
+            codec.put_u8(EntrySerializeCode::MultisigAccountWithCycles as u8)?;
+            pack_multisig_identifier(identifier, codec)?;
+            pack_account_state_with_cycles(account_state, codec)?;
+        }
         Entry::MultisigAccount((identifier, account_state)) => {
             codec.put_u8(EntrySerializeCode::MultisigAccount as u8)?;
             pack_multisig_identifier(identifier, codec)?;
@@ -1162,6 +1375,21 @@ fn pack_entry<W: std::io::Write>(
             codec.put_u8(EntrySerializeCode::VotePlan as u8)?;
             pack_vote_plan(vote_plan, codec)?;
         }
+        Entry::Name((name, record)) => {
+            codec.put_u8(EntrySerializeCode::Name as u8)?;
+            pack_name(name, codec)?;
+            pack_name_record(record, codec)?;
+        }
+        Entry::EpochSettings((epoch, params)) => {
+            codec.put_u8(EntrySerializeCode::EpochSettings as u8)?;
+            codec.put_be_u32(*epoch)?;
+            pack_config_params(params, codec)?;
+        }
+        Entry::GovernanceQuota((member, submissions)) => {
+            codec.put_u8(EntrySerializeCode::GovernanceQuota as u8)?;
+            pack_committee_id(member, codec)?;
+            pack_vote_plan_submissions(submissions, codec)?;
+        }
     }
     Ok(())
 }
@@ -1203,6 +1431,21 @@ fn unpack_entry_owned<R: std::io::BufRead>(
             let account_state = unpack_account_state(codec)?;
             Ok(EntryOwned::MultisigAccount((identifier, account_state)))
         }
+        EntrySerializeCode::AccountWithCycles => {
+            let identifier = unpack_account_identifier(codec)?;
+            let account = unpack_account_state_with_cycles(codec)?;
+            Ok(EntryOwned::Account((identifier, account)))
+        }
+        EntrySerializeCode::AccountWithRewardsRedelegation => {
+            let identifier = unpack_account_identifier(codec)?;
+            let account = unpack_account_state_with_redelegation(codec)?;
+            Ok(EntryOwned::Account((identifier, account)))
+        }
+        EntrySerializeCode::MultisigAccountWithCycles => {
+            let identifier = unpack_multisig_identifier(codec)?;
+            let account_state = unpack_account_state_with_cycles(codec)?;
+            Ok(EntryOwned::MultisigAccount((identifier, account_state)))
+        }
         EntrySerializeCode::MultisigDeclaration => {
             let identifier = unpack_multisig_identifier(codec)?;
             let declaration = unpack_declaration(codec)?;
@@ -1222,6 +1465,25 @@ fn unpack_entry_owned<R: std::io::BufRead>(
             let vote_plan = unpack_vote_plan(codec)?;
             Ok(EntryOwned::VotePlan(vote_plan))
         }
+        EntrySerializeCode::Name => {
+            let name = unpack_name(codec)?;
+            let record = unpack_name_record(codec)?;
+            Ok(EntryOwned::Name((name, record)))
+        }
+        EntrySerializeCode::EpochSettings => {
+            let epoch = codec.get_be_u32()?;
+            let params = unpack_config_params(codec)?;
+            Ok(EntryOwned::EpochSettings((epoch, params)))
+        }
+        EntrySerializeCode::GovernanceQuota => {
+            let member = unpack_committee_id(codec)?;
+            let submissions = unpack_vote_plan_submissions(codec)?;
+            Ok(EntryOwned::GovernanceQuota((member, submissions)))
+        }
+        EntrySerializeCode::PartialSnapshot => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "Error reading Entry, partial snapshot marker in a full snapshot",
+        )),
         EntrySerializeCode::SerializationEnd => Ok(EntryOwned::StopEntry),
     }
 }
@@ -1247,7 +1509,7 @@ impl Serialize for Ledger {
 
     fn serialize<W: std::io::Write>(&self, writer: W) -> Result<(), Self::Error> {
         let mut codec = Codec::new(writer);
-        for entry in self.iter() {
+        for entry in self.iter_canonical() {
             pack_entry(&entry, &mut codec)?;
         }
         // Write finish flag
@@ -1260,7 +1522,7 @@ impl Deserialize for Ledger {
     type Error = std::io::Error;
 
     fn deserialize<R: std::io::BufRead>(reader: R) -> Result<Self, Self::Error> {
-        let owned_entries = unpack_entries(reader)?;
+        let owned_entries = unpack_entries(compression::decompressing_reader(reader)?)?;
         let entries = owned_entries
             .iter()
             .map(|entry_owned| entry_owned.to_entry().unwrap());
@@ -1275,11 +1537,746 @@ impl Deserialize for Ledger {
     }
 }
 
+/// A subsystem of the ledger state, which can be exported on its own in a
+/// partial snapshot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Subsystem {
+    /// the accounts, the multisig accounts and their declarations
+    Accounts,
+    /// the utxos and the legacy utxos
+    Utxos,
+    /// the update proposals, the vote plans and the governance quotas
+    Governance,
+}
+
+impl Subsystem {
+    const ALL: [Subsystem; 3] = [Subsystem::Accounts, Subsystem::Utxos, Subsystem::Governance];
+
+    fn bit(self) -> u8 {
+        match self {
+            Subsystem::Accounts => 0b001,
+            Subsystem::Utxos => 0b010,
+            Subsystem::Governance => 0b100,
+        }
+    }
+
+    /// The subsystem of the entry, `None` for the entries of the core state
+    /// (globals, pots, settings, stake pools...) which are never part of a
+    /// partial snapshot
+    pub fn of_entry(entry: &Entry<'_>) -> Option<Self> {
+        match entry {
+            Entry::Account(_) | Entry::MultisigAccount(_) | Entry::MultisigDeclaration(_) => {
+                Some(Subsystem::Accounts)
+            }
+            Entry::Utxo(_) | Entry::OldUtxo(_) => Some(Subsystem::Utxos),
+            Entry::UpdateProposal(_) | Entry::VotePlan(_) | Entry::GovernanceQuota(_) => {
+                Some(Subsystem::Governance)
+            }
+            Entry::Globals(_)
+            | Entry::Pot(_)
+            | Entry::ConfigParam(_)
+            | Entry::StakePool(_)
+            | Entry::LeaderParticipation(_)
+            | Entry::Name(_)
+            | Entry::EpochSettings(_) => None,
+        }
+    }
+}
+
+/// The set of subsystems exported in a partial snapshot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnapshotFilter(u8);
+
+impl SnapshotFilter {
+    pub fn only(subsystem: Subsystem) -> Self {
+        SnapshotFilter(subsystem.bit())
+    }
+
+    pub fn with(self, subsystem: Subsystem) -> Self {
+        SnapshotFilter(self.0 | subsystem.bit())
+    }
+
+    pub fn contains(&self, subsystem: Subsystem) -> bool {
+        self.0 & subsystem.bit() != 0
+    }
+
+    pub fn subsystems(&self) -> impl Iterator<Item = Subsystem> + '_ {
+        Subsystem::ALL
+            .into_iter()
+            .filter(move |subsystem| self.contains(*subsystem))
+    }
+
+    fn accepts(&self, entry: &Entry<'_>) -> bool {
+        Subsystem::of_entry(entry).map_or(false, |subsystem| self.contains(subsystem))
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum PartialSnapshotError {
+    #[error("not a partial snapshot")]
+    NotPartial,
+    #[error("invalid subsystems filter {0:#04x}")]
+    InvalidFilter(u8),
+    #[error("entry {index} is not part of the subsystems of the snapshot")]
+    UnexpectedEntry { index: usize },
+    #[error("entry {index} is a duplicate")]
+    DuplicateEntry { index: usize },
+    #[error("cannot read the partial snapshot")]
+    Io(#[from] std::io::Error),
+}
+
+/// Serialize the entries of the subsystems selected by the filter.
+///
+/// The partial snapshot starts with an explicit marker followed by the
+/// filter, so it cannot be mistaken for a full snapshot: loading it with
+/// [`Deserialize`] fails. The entries follow in the canonical order, see
+/// [`Ledger::iter_canonical`], and the snapshot ends as a full one.
+pub fn serialize_partial<W: std::io::Write>(
+    ledger: &Ledger,
+    filter: SnapshotFilter,
+    writer: W,
+) -> Result<W, std::io::Error> {
+    let mut codec = Codec::new(writer);
+    codec.put_u8(EntrySerializeCode::PartialSnapshot as u8)?;
+    codec.put_u8(filter.0)?;
+    for entry in ledger
+        .iter_canonical()
+        .filter(|entry| filter.accepts(entry))
+    {
+        pack_entry(&entry, &mut codec)?;
+    }
+    codec.put_u8(EntrySerializeCode::SerializationEnd as u8)?;
+    Ok(codec.into_inner())
+}
+
+/// Deserialize a snapshot serialized with [`serialize_partial`]
+pub fn deserialize_partial<R: std::io::BufRead>(
+    reader: R,
+) -> Result<PartialSnapshot, PartialSnapshotError> {
+    let mut codec = Codec::new(reader);
+    if codec.get_u8()? != EntrySerializeCode::PartialSnapshot as u8 {
+        return Err(PartialSnapshotError::NotPartial);
+    }
+    let mask = codec.get_u8()?;
+    let filter = SnapshotFilter(mask);
+    if mask == 0 || Subsystem::ALL.iter().fold(mask, |mask, s| mask & !s.bit()) != 0 {
+        return Err(PartialSnapshotError::InvalidFilter(mask));
+    }
+    let entries = unpack_entries(codec.into_inner())?;
+    for (index, entry) in entries.iter().enumerate() {
+        if !entry
+            .to_entry()
+            .map_or(false, |entry| filter.accepts(&entry))
+        {
+            return Err(PartialSnapshotError::UnexpectedEntry { index });
+        }
+    }
+    Ok(PartialSnapshot { filter, entries })
+}
+
+/// The subsystems of a ledger read from a partial snapshot
+pub struct PartialSnapshot {
+    filter: SnapshotFilter,
+    entries: Vec<EntryOwned>,
+}
+
+impl PartialSnapshot {
+    pub fn filter(&self) -> SnapshotFilter {
+        self.filter
+    }
+
+    pub fn entries(&self) -> &[EntryOwned] {
+        &self.entries
+    }
+
+    /// Replace the subsystems of the snapshot in the ledger, the rest of the
+    /// ledger is kept as is.
+    ///
+    /// Each subsystem of the filter is replaced as a whole, so the state of a
+    /// subsystem is the one of the snapshot even where the snapshot has no
+    /// entry. The vote plans already known by the ledger keep their votes and
+    /// their committee, the other ones start without any. Merging a snapshot
+    /// taken at another state of the chain can leave the values of the
+    /// ledger inconsistent with its pots, which is not checked.
+    pub fn merge_into(&self, ledger: &Ledger) -> Result<Ledger, PartialSnapshotError> {
+        use std::collections::{HashMap, HashSet};
+
+        let mut utxos: HashMap<FragmentId, Vec<_>> = HashMap::new();
+        let mut oldutxos: HashMap<FragmentId, Vec<_>> = HashMap::new();
+        let mut utxo_keys = HashSet::new();
+        let mut accounts = Vec::new();
+        let mut account_keys = HashSet::new();
+        let mut multisig_accounts = Vec::new();
+        let mut multisig_declarations = Vec::new();
+        let mut multisig_keys = HashSet::new();
+        let mut proposals = Hamt::new();
+        let mut plans = Hamt::new();
+        let mut quotas = GovernanceQuotas::new();
+
+        for (index, entry) in self.entries.iter().enumerate() {
+            let duplicate = PartialSnapshotError::DuplicateEntry { index };
+            match entry {
+                EntryOwned::Utxo(entry) => {
+                    if !utxo_keys.insert((false, entry.fragment_id, entry.output_index)) {
+                        return Err(duplicate);
+                    }
+                    utxos
+                        .entry(entry.fragment_id)
+                        .or_default()
+                        .push((entry.output_index, entry.output.clone()));
+                }
+                EntryOwned::OldUtxo(entry) => {
+                    if !utxo_keys.insert((true, entry.fragment_id, entry.output_index)) {
+                        return Err(duplicate);
+                    }
+                    oldutxos
+                        .entry(entry.fragment_id)
+                        .or_default()
+                        .push((entry.output_index, entry.output.clone()));
+                }
+                EntryOwned::Account((identifier, state)) => {
+                    if !account_keys.insert(identifier.clone()) {
+                        return Err(duplicate);
+                    }
+                    accounts.push((identifier.clone(), state.clone()));
+                }
+                EntryOwned::MultisigAccount((identifier, state)) => {
+                    if !multisig_keys.insert((false, identifier.clone())) {
+                        return Err(duplicate);
+                    }
+                    multisig_accounts.push((identifier.clone(), state.clone()));
+                }
+                EntryOwned::MultisigDeclaration((identifier, declaration)) => {
+                    if !multisig_keys.insert((true, identifier.clone())) {
+                        return Err(duplicate);
+                    }
+                    multisig_declarations.push((identifier.clone(), declaration.clone()));
+                }
+                EntryOwned::UpdateProposal((proposal_id, state)) => {
+                    proposals = proposals
+                        .insert(*proposal_id, state.clone())
+                        .map_err(|_| duplicate)?;
+                }
+                EntryOwned::VotePlan(plan) => {
+                    let id = plan.to_id();
+                    let manager = ledger.votes.plans.lookup(&id).cloned().unwrap_or_else(|| {
+                        vote::VotePlanManager::new(plan.clone(), Default::default())
+                    });
+                    plans = plans.insert(id, manager).map_err(|_| duplicate)?;
+                }
+                EntryOwned::GovernanceQuota((member, submissions)) => {
+                    quotas
+                        .set_for(*member, *submissions)
+                        .map_err(|_| duplicate)?;
+                }
+                _ => return Err(PartialSnapshotError::UnexpectedEntry { index }),
+            }
+        }
+
+        let mut merged = ledger.clone();
+        for subsystem in self.filter.subsystems() {
+            match subsystem {
+                Subsystem::Accounts => {
+                    merged.accounts = std::mem::take(&mut accounts).into_iter().collect();
+                    merged.multisig = multisig::Ledger::restore(
+                        std::mem::take(&mut multisig_accounts),
+                        std::mem::take(&mut multisig_declarations),
+                    );
+                }
+                Subsystem::Utxos => {
+                    merged.utxos = std::mem::take(&mut utxos).into_iter().collect();
+                    merged.oldutxos = std::mem::take(&mut oldutxos).into_iter().collect();
+                }
+                Subsystem::Governance => {
+                    merged.updates.proposals = std::mem::take(&mut proposals);
+                    merged.votes.plans = std::mem::take(&mut plans);
+                    merged.governance_quotas = std::mem::take(&mut quotas);
+                }
+            }
+        }
+        Ok(merged)
+    }
+}
+
+/// Maximum size of a framed entry, bigger sizes are considered corrupted
+pub const FRAMED_ENTRY_MAX_SIZE: u32 = 64 * 1024 * 1024;
+
+/// How the reader handles corrupted entries of a framed ledger
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecoveryMode {
+    /// fail on the first corrupted entry
+    Strict,
+    /// skip the corrupted entries, and report them
+    Salvage,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum EntryCorruption {
+    #[error("checksum mismatch, expected {expected:#010x} but computed {computed:#010x}")]
+    ChecksumMismatch { expected: u32, computed: u32 },
+    #[error("invalid entry: {0}")]
+    InvalidEntry(String),
+}
+
+/// A corrupted entry of a framed ledger
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("entry {index} at offset {offset} is corrupted: {corruption}")]
+pub struct CorruptedEntry {
+    /// index of the entry in the stream, starting from 0
+    pub index: u64,
+    /// offset in bytes of the frame of the entry in the stream
+    pub offset: u64,
+    pub corruption: EntryCorruption,
+}
+
+#[derive(Debug, Error)]
+pub enum FramedRecoveryError {
+    #[error("cannot read the frame of entry {index} at offset {offset}")]
+    Io {
+        index: u64,
+        offset: u64,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("entry {index} at offset {offset} has an invalid size {size}")]
+    InvalidSize { index: u64, offset: u64, size: u32 },
+    #[error(transparent)]
+    Corrupted(#[from] CorruptedEntry),
+    #[error("cannot rebuild the ledger from the recovered entries")]
+    Ledger(#[source] crate::ledger::Error),
+}
+
+/// Streaming writer of the ledger entries, where every entry is framed with
+/// its size and its CRC32 checksum:
+///
+/// ```text
+/// [ ENTRY_SIZE (u32) CRC32 (u32) ENTRY (ENTRY_SIZE bytes) ]* 0 (u32)
+/// ```
+///
+/// Integers are big endian, the entries are packed as in the unframed
+/// format and the stream ends with an empty frame.
+pub struct FramedWriter<W: std::io::Write> {
+    codec: Codec<W>,
+}
+
+impl<W: std::io::Write> FramedWriter<W> {
+    pub fn new(writer: W) -> Self {
+        FramedWriter {
+            codec: Codec::new(writer),
+        }
+    }
+
+    pub fn write_entry(&mut self, entry: &Entry<'_>) -> Result<(), std::io::Error> {
+        let mut entry_codec = Codec::new(Vec::new());
+        pack_entry(entry, &mut entry_codec)?;
+        let bytes = entry_codec.into_inner();
+        self.codec.put_be_u32(bytes.len() as u32)?;
+        self.codec.put_be_u32(crc32fast::hash(&bytes))?;
+        self.codec.put_bytes(&bytes)
+    }
+
+    /// Write the end of the stream
+    pub fn finish(mut self) -> Result<W, std::io::Error> {
+        self.codec.put_be_u32(0)?;
+        Ok(self.codec.into_inner())
+    }
+}
+
+/// The ledger read from a framed stream, along with the entries which were
+/// skipped because they are corrupted (only in salvage mode).
+#[derive(Debug)]
+pub struct Recovered {
+    pub ledger: Ledger,
+    pub skipped: Vec<CorruptedEntry>,
+}
+
+/// Serialize the ledger with every entry framed, see [`FramedWriter`]. The
+/// entries are written in the canonical order, see [`Ledger::iter_canonical`]
+pub fn serialize_framed<W: std::io::Write>(
+    ledger: &Ledger,
+    writer: W,
+) -> Result<W, std::io::Error> {
+    let mut writer = FramedWriter::new(writer);
+    for entry in ledger.iter_canonical() {
+        writer.write_entry(&entry)?;
+    }
+    writer.finish()
+}
+
+/// Deserialize a ledger serialized with [`serialize_framed`].
+///
+/// Every corrupted entry is reported with its index and offset in the stream.
+/// In salvage mode the corrupted entries are skipped, which can still fail
+/// if the remaining entries do not make a valid ledger. A corrupted size cannot
+/// be recovered from, as the start of the next frame is unknown.
+pub fn deserialize_framed<R: std::io::BufRead>(
+    reader: R,
+    mode: RecoveryMode,
+) -> Result<Recovered, FramedRecoveryError> {
+    let mut codec = Codec::new(reader);
+    let mut entries = Vec::new();
+    let mut skipped = Vec::new();
+    let mut index = 0u64;
+    let mut offset = 0u64;
+
+    loop {
+        let io_error = |source| FramedRecoveryError::Io {
+            index,
+            offset,
+            source,
+        };
+        let size = codec.get_be_u32().map_err(io_error)?;
+        if size == 0 {
+            break;
+        }
+        if size > FRAMED_ENTRY_MAX_SIZE {
+            return Err(FramedRecoveryError::InvalidSize {
+                index,
+                offset,
+                size,
+            });
+        }
+        let expected = codec.get_be_u32().map_err(io_error)?;
+        let bytes = codec.get_bytes(size as usize).map_err(io_error)?;
+
+        match unpack_framed_entry(&bytes, expected) {
+            Ok(entry) => entries.push(entry),
+            Err(corruption) => {
+                let corrupted = CorruptedEntry {
+                    index,
+                    offset,
+                    corruption,
+                };
+                match mode {
+                    RecoveryMode::Strict => return Err(corrupted.into()),
+                    RecoveryMode::Salvage => skipped.push(corrupted),
+                }
+            }
+        }
+
+        index += 1;
+        offset += 8 + size as u64;
+    }
+
+    let ledger = entries
+        .iter()
+        .filter_map(EntryOwned::to_entry)
+        .collect::<Result<Ledger, _>>()
+        .map_err(FramedRecoveryError::Ledger)?;
+    Ok(Recovered { ledger, skipped })
+}
+
+fn unpack_framed_entry(bytes: &[u8], expected: u32) -> Result<EntryOwned, EntryCorruption> {
+    let computed = crc32fast::hash(bytes);
+    if computed != expected {
+        return Err(EntryCorruption::ChecksumMismatch { expected, computed });
+    }
+    let mut codec = Codec::new(bytes);
+    let entry =
+        unpack_entry_owned(&mut codec).map_err(|e| EntryCorruption::InvalidEntry(e.to_string()))?;
+    if let EntryOwned::StopEntry = entry {
+        return Err(EntryCorruption::InvalidEntry(
+            "unexpected end of serialization marker".to_string(),
+        ));
+    }
+    if !codec.into_inner().is_empty() {
+        return Err(EntryCorruption::InvalidEntry(
+            "trailing bytes after the entry".to_string(),
+        ));
+    }
+    Ok(entry)
+}
+
+/// A section of a snapshot, the entries of one kind. The snapshot has a
+/// section per kind of entry present in the ledger, in the canonical order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestSection {
+    /// kind of the entries, see [`Entry::kind_rank`]
+    pub kind: u8,
+    pub entries: u64,
+    /// size of the packed entries of the section
+    pub bytes: u64,
+    /// hash of the previous chained hash (zero for the first section) and of
+    /// the packed entries of the section
+    pub chained_hash: Hash,
+}
+
+/// Integrity manifest of a snapshot written with [`serialize_with_manifest`].
+///
+/// The chained hash of the last section covers all the entries, the state
+/// digest covers the whole snapshot and is the digest of
+/// [`ledger_digest`](crate::audit::ledger_digest). The manifest serializes on
+/// its own, so it can be signed and distributed along with the snapshot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotManifest {
+    pub sections: Vec<ManifestSection>,
+    /// size of the snapshot, end of serialization marker included
+    pub total_bytes: u64,
+    pub state_digest: LedgerDigest,
+}
+
+impl SnapshotManifest {
+    pub fn total_entries(&self) -> u64 {
+        self.sections.iter().map(|section| section.entries).sum()
+    }
+
+    /// number of entries of the `kind`, see [`Entry::kind_rank`]
+    pub fn entries_of(&self, kind: u8) -> u64 {
+        self.sections
+            .iter()
+            .find(|section| section.kind == kind)
+            .map_or(0, |section| section.entries)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ManifestError {
+    #[error("cannot read the snapshot")]
+    Io(#[from] std::io::Error),
+    #[error("the manifest has {expected} sections, the snapshot has {actual}")]
+    SectionCountMismatch { expected: usize, actual: usize },
+    #[error("the section {index} (entries of kind {kind}) does not match the manifest")]
+    SectionMismatch { index: usize, kind: u8 },
+    #[error("the manifest declares {expected} bytes, the snapshot has {actual}")]
+    TotalBytesMismatch { expected: u64, actual: u64 },
+    #[error("the state digest {actual} does not match the digest {expected} of the manifest")]
+    StateDigestMismatch {
+        expected: LedgerDigest,
+        actual: LedgerDigest,
+    },
+    #[error("cannot rebuild the ledger from the entries")]
+    Ledger(#[source] crate::ledger::Error),
+}
+
+/// Builds the manifest from the packed entries, in the order of the snapshot
+struct ManifestBuilder {
+    sections: Vec<ManifestSection>,
+    section_hasher: Blake2b,
+    state_hasher: Blake2b,
+    total_bytes: u64,
+}
+
+impl ManifestBuilder {
+    fn new() -> Self {
+        ManifestBuilder {
+            sections: Vec::new(),
+            section_hasher: Blake2b::new(32),
+            state_hasher: Blake2b::new(32),
+            total_bytes: 0,
+        }
+    }
+
+    fn close_section(&mut self) {
+        if let Some(section) = self.sections.last_mut() {
+            let mut hash = [0; 32];
+            self.section_hasher.result(&mut hash);
+            section.chained_hash = Hash::from_bytes(hash);
+        }
+    }
+
+    fn push_entry(&mut self, entry: &Entry<'_>, bytes: &[u8]) {
+        let kind = entry.kind_rank();
+        if self.sections.last().map(|section| section.kind) != Some(kind) {
+            self.close_section();
+            let previous = self
+                .sections
+                .last()
+                .map_or_else(Hash::zero_hash, |section| section.chained_hash);
+            self.section_hasher = Blake2b::new(32);
+            self.section_hasher.input(previous.as_bytes());
+            self.sections.push(ManifestSection {
+                kind,
+                entries: 0,
+                bytes: 0,
+                chained_hash: Hash::zero_hash(),
+            });
+        }
+        let section = self.sections.last_mut().unwrap();
+        section.entries += 1;
+        section.bytes += bytes.len() as u64;
+        self.section_hasher.input(bytes);
+        self.push_bytes(bytes);
+    }
+
+    fn push_bytes(&mut self, bytes: &[u8]) {
+        self.state_hasher.input(bytes);
+        self.total_bytes += bytes.len() as u64;
+    }
+
+    fn finish(mut self) -> SnapshotManifest {
+        self.close_section();
+        self.push_bytes(&[EntrySerializeCode::SerializationEnd as u8]);
+        let mut digest = [0; 32];
+        self.state_hasher.result(&mut digest);
+        SnapshotManifest {
+            sections: self.sections,
+            total_bytes: self.total_bytes,
+            state_digest: Hash::from_bytes(digest),
+        }
+    }
+}
+
+/// Serialize the ledger as [`Serialize`] does, along with the manifest of the
+/// snapshot
+pub fn serialize_with_manifest<W: std::io::Write>(
+    ledger: &Ledger,
+    writer: W,
+) -> Result<(W, SnapshotManifest), std::io::Error> {
+    let mut codec = Codec::new(writer);
+    let mut manifest = ManifestBuilder::new();
+    for entry in ledger.iter_canonical() {
+        let mut entry_codec = Codec::new(Vec::new());
+        pack_entry(&entry, &mut entry_codec)?;
+        let bytes = entry_codec.into_inner();
+        manifest.push_entry(&entry, &bytes);
+        codec.put_bytes(&bytes)?;
+    }
+    codec.put_u8(EntrySerializeCode::SerializationEnd as u8)?;
+    Ok((codec.into_inner(), manifest.finish()))
+}
+
+/// Reader keeping the bytes read, to hash the entries as they are read
+struct CapturingReader<R> {
+    inner: R,
+    captured: Vec<u8>,
+}
+
+impl<R: std::io::Read> std::io::Read for CapturingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.captured.extend_from_slice(&buf[..read]);
+        Ok(read)
+    }
+}
+
+impl<R: std::io::BufRead> std::io::BufRead for CapturingReader<R> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        if let Ok(buf) = self.inner.fill_buf() {
+            let amt = amt.min(buf.len());
+            self.captured.extend_from_slice(&buf[..amt]);
+        }
+        self.inner.consume(amt)
+    }
+}
+
+/// Deserialize a ledger serialized with [`serialize_with_manifest`] (or with
+/// [`Serialize`]), checking the snapshot against the manifest before
+/// rebuilding the ledger. The manifest is of the uncompressed snapshot, a
+/// compressed snapshot is decompressed as it is checked.
+pub fn deserialize_with_manifest<R: std::io::BufRead>(
+    reader: R,
+    expected: &SnapshotManifest,
+) -> Result<Ledger, ManifestError> {
+    let mut reader = CapturingReader {
+        inner: compression::decompressing_reader(reader)?,
+        captured: Vec::new(),
+    };
+    let mut manifest = ManifestBuilder::new();
+    let mut entries = Vec::new();
+    loop {
+        let entry = unpack_entry_owned(&mut Codec::new(&mut reader))?;
+        let bytes = std::mem::take(&mut reader.captured);
+        match entry.to_entry() {
+            Some(entry) => manifest.push_entry(&entry, &bytes),
+            None => break,
+        }
+        entries.push(entry);
+    }
+    check_manifest(expected, &manifest.finish())?;
+
+    entries
+        .iter()
+        .filter_map(EntryOwned::to_entry)
+        .collect::<Result<Ledger, _>>()
+        .map_err(ManifestError::Ledger)
+}
+
+fn check_manifest(
+    expected: &SnapshotManifest,
+    actual: &SnapshotManifest,
+) -> Result<(), ManifestError> {
+    if expected.sections.len() != actual.sections.len() {
+        return Err(ManifestError::SectionCountMismatch {
+            expected: expected.sections.len(),
+            actual: actual.sections.len(),
+        });
+    }
+    for (index, (expected, actual)) in expected.sections.iter().zip(&actual.sections).enumerate() {
+        if expected != actual {
+            return Err(ManifestError::SectionMismatch {
+                index,
+                kind: actual.kind,
+            });
+        }
+    }
+    if expected.total_bytes != actual.total_bytes {
+        return Err(ManifestError::TotalBytesMismatch {
+            expected: expected.total_bytes,
+            actual: actual.total_bytes,
+        });
+    }
+    if expected.state_digest != actual.state_digest {
+        return Err(ManifestError::StateDigestMismatch {
+            expected: expected.state_digest,
+            actual: actual.state_digest,
+        });
+    }
+    Ok(())
+}
+
+impl Serialize for SnapshotManifest {
+    type Error = std::io::Error;
+
+    fn serialize<W: std::io::Write>(&self, writer: W) -> Result<(), Self::Error> {
+        let mut codec = Codec::new(writer);
+        codec.put_be_u32(self.sections.len() as u32)?;
+        for section in self.sections.iter() {
+            codec.put_u8(section.kind)?;
+            codec.put_be_u64(section.entries)?;
+            codec.put_be_u64(section.bytes)?;
+            section.chained_hash.serialize(&mut codec)?;
+        }
+        codec.put_be_u64(self.total_bytes)?;
+        self.state_digest.serialize(&mut codec)
+    }
+}
+
+impl Deserialize for SnapshotManifest {
+    type Error = std::io::Error;
+
+    fn deserialize<R: std::io::BufRead>(reader: R) -> Result<Self, Self::Error> {
+        let mut codec = Codec::new(reader);
+        let count = codec.get_be_u32()?;
+        let mut sections = Vec::new();
+        for _ in 0..count {
+            sections.push(ManifestSection {
+                kind: codec.get_u8()?,
+                entries: codec.get_be_u64()?,
+                bytes: codec.get_be_u64()?,
+                chained_hash: Hash::deserialize(&mut codec)?,
+            });
+        }
+        Ok(SnapshotManifest {
+            sections,
+            total_bytes: codec.get_be_u64()?,
+            state_digest: Hash::deserialize(&mut codec)?,
+        })
+    }
+}
+
 #[cfg(test)]
 pub mod test {
     use super::*;
-    use crate::testing::{ConfigBuilder, LedgerBuilder, StakePoolBuilder};
+    use crate::testing::{ConfigBuilder, LedgerBuilder, StakePoolBuilder, TestGen};
     use cardano_legacy_address::Addr;
+    use chain_core::{
+        mempack::{ReadBuf, ReadError, Readable},
+        property::ByteSource,
+    };
     use chain_crypto::Blake2b256;
     use quickcheck::{quickcheck, TestResult};
     use std::io::Cursor;
@@ -1383,7 +2380,8 @@ pub mod test {
             c = codec.into_inner();
             c.set_position(0);
             codec = Codec::new(c);
-            let deserialized_delegation_type = unpack_delegation_type(&mut codec)?;
+            let code = codec.get_u8()?;
+            let deserialized_delegation_type = unpack_delegation_type(code, &mut codec)?;
             assert_eq!(delegation_type, &deserialized_delegation_type);
         }
         Ok(())
@@ -1403,6 +2401,117 @@ pub mod test {
         Ok(())
     }
 
+    #[test]
+    pub fn account_state_with_amounts_pack_unpack_bijection() -> Result<(), std::io::Error> {
+        let amounts = DelegationAmounts::new(vec![
+            (StakePoolBuilder::new().build().id(), Value(100)),
+            (StakePoolBuilder::new().build().id(), Value(50)),
+        ])
+        .unwrap();
+        let account_state = AccountState::new(Value(256), ()).set_delegation_amounts(amounts);
+        let mut c: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let mut codec = Codec::new(c);
+        pack_account_state(&account_state, &mut codec)?;
+        c = codec.into_inner();
+        c.set_position(0);
+        codec = Codec::new(c);
+        let deserialized_account_state = unpack_account_state(&mut codec)?;
+        assert_eq!(account_state, deserialized_account_state);
+        assert_eq!(
+            deserialized_account_state.delegation(),
+            &DelegationType::NonDelegated
+        );
+        Ok(())
+    }
+
+    fn wrapped_account_state() -> AccountState<()> {
+        let mut account_state = AccountState::new(Value(256), ());
+        let last = SpendingCounter::new(3, u32::MAX);
+        account_state.spending = SpendingCounterIncreasing::new_from_counter(last);
+        account_state.spending.next_verify(last).unwrap();
+        account_state
+    }
+
+    #[test]
+    pub fn account_state_with_cycles_pack_unpack_bijection() -> Result<(), std::io::Error> {
+        let account_state = wrapped_account_state();
+        let mut c: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let mut codec = Codec::new(c);
+        pack_entry(
+            &Entry::Account((&TestGen::identifier(), &account_state)),
+            &mut codec,
+        )?;
+        c = codec.into_inner();
+        assert_eq!(c.get_ref()[0], EntrySerializeCode::AccountWithCycles as u8);
+        c.set_position(0);
+        codec = Codec::new(c);
+        match unpack_entry_owned(&mut codec)? {
+            EntryOwned::Account((_, deserialized_account_state)) => {
+                assert_eq!(deserialized_account_state.spending.get_cycles()[3], 1);
+                assert_eq!(account_state, deserialized_account_state);
+            }
+            _ => panic!("expecting an account entry"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    pub fn account_state_without_cycles_keeps_legacy_format() -> Result<(), std::io::Error> {
+        let account_state = AccountState::new(Value(256), ());
+        let mut c: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let mut codec = Codec::new(c);
+        pack_entry(
+            &Entry::Account((&TestGen::identifier(), &account_state)),
+            &mut codec,
+        )?;
+        c = codec.into_inner();
+        assert_eq!(c.get_ref()[0], EntrySerializeCode::Account as u8);
+
+        // a legacy entry is read with the first cycle on every lane
+        c.set_position(0);
+        codec = Codec::new(c);
+        match unpack_entry_owned(&mut codec)? {
+            EntryOwned::Account((_, deserialized_account_state)) => {
+                assert!(!deserialized_account_state.spending.has_wrapped());
+                assert_eq!(account_state, deserialized_account_state);
+            }
+            _ => panic!("expecting an account entry"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    pub fn account_state_with_redelegation_pack_unpack_bijection() -> Result<(), std::io::Error> {
+        let account_state = AccountState::new(Value(256), ())
+            .set_rewards_redelegation(true)
+            .add_rewards(1, Value(10))
+            .unwrap();
+        let mut c: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let mut codec = Codec::new(c);
+        pack_entry(
+            &Entry::Account((&TestGen::identifier(), &account_state)),
+            &mut codec,
+        )?;
+        c = codec.into_inner();
+        assert_eq!(
+            c.get_ref()[0],
+            EntrySerializeCode::AccountWithRewardsRedelegation as u8
+        );
+        c.set_position(0);
+        codec = Codec::new(c);
+        match unpack_entry_owned(&mut codec)? {
+            EntryOwned::Account((_, deserialized_account_state)) => {
+                assert_eq!(
+                    deserialized_account_state.redelegated_rewards,
+                    Some(Value(10))
+                );
+                assert_eq!(account_state, deserialized_account_state);
+            }
+            _ => panic!("expecting an account entry"),
+        }
+        Ok(())
+    }
+
     #[test]
     pub fn last_rewards_pack_unpack_bijection() -> Result<(), std::io::Error> {
         use std::io::Cursor;
@@ -1510,10 +2619,7 @@ pub mod test {
 
     #[test]
     pub fn output_pack_unpack_bijection() -> Result<(), std::io::Error> {
-        let output: Output<()> = Output {
-            address: (),
-            value: Value(1000),
-        };
+        let output: Output<()> = Output::from_address((), Value(1000));
 
         let mut c = std::io::Cursor::new(Vec::new());
         let mut codec = Codec::new(c);
@@ -1526,6 +2632,38 @@ pub mod test {
         Ok(())
     }
 
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct Color(u8);
+
+    impl OutputExtra for Color {
+        fn read_extra<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+            source.take_u8().map(Color)
+        }
+
+        fn write_extra<W: std::io::Write>(
+            &self,
+            codec: &mut Codec<W>,
+        ) -> Result<(), std::io::Error> {
+            codec.put_u8(self.0)
+        }
+    }
+
+    #[test]
+    pub fn output_with_extra_pack_unpack_bijection() -> Result<(), std::io::Error> {
+        let output = Output::with_extra((), Value(1000), Color(7));
+
+        let mut c = std::io::Cursor::new(Vec::new());
+        let mut codec = Codec::new(c);
+        pack_output(&output, &mut |_, _| Ok(()), &mut codec)?;
+        c = codec.into_inner();
+        assert_eq!(c.get_ref().len(), 9);
+        c.set_position(0);
+        codec = Codec::new(c);
+        let other_output: Output<(), Color> = unpack_output(&mut |_| Ok(()), &mut codec)?;
+        assert_eq!(output, other_output);
+        Ok(())
+    }
+
     #[test]
     pub fn ledger_serialize_deserialize_bijection() -> Result<(), std::io::Error> {
         let test_ledger = LedgerBuilder::from_config(ConfigBuilder::new())
@@ -1542,6 +2680,206 @@ pub mod test {
         Ok(())
     }
 
+    #[cfg(feature = "compression")]
+    #[test]
+    pub fn compressed_ledger_serialize_deserialize_bijection() -> Result<(), std::io::Error> {
+        use crate::compression::{serialize_compressed, CompressionLevel};
+        let faucets: Vec<_> = (0..64)
+            .map(|_| {
+                crate::testing::data::AddressDataValue::account(Discrimination::Test, Value(42000))
+            })
+            .collect();
+        let ledger: Ledger = LedgerBuilder::from_config(ConfigBuilder::new())
+            .faucets(&faucets)
+            .build()
+            .expect("cannot build test ledger")
+            .into();
+
+        let plain = ledger.serialize_as_vec()?;
+        let compressed = serialize_compressed(&ledger, Vec::new(), CompressionLevel::default())?;
+        assert!(compressed.len() < plain.len());
+        assert_eq!(Ledger::deserialize(compressed.as_slice())?, ledger);
+
+        let (_, manifest) = serialize_with_manifest(&ledger, Vec::new())?;
+        assert_eq!(
+            deserialize_with_manifest(compressed.as_slice(), &manifest).unwrap(),
+            ledger
+        );
+        Ok(())
+    }
+
+    #[test]
+    pub fn ledger_settings_history_serialize_deserialize_bijection() -> Result<(), std::io::Error> {
+        let mut ledger: Ledger = LedgerBuilder::from_config(ConfigBuilder::new())
+            .faucet_value(Value(42000))
+            .build()
+            .expect("cannot build test ledger")
+            .into();
+        let mut settings = ledger.settings.clone();
+        settings.slots_per_epoch += 1;
+        ledger.settings_history = ledger
+            .settings_history
+            .record(0, &ledger.settings, 1)
+            .record(1, &settings, 2);
+
+        let mut c = std::io::Cursor::new(Vec::new());
+        ledger.serialize(&mut c)?;
+        c.set_position(0);
+        let other_ledger = Ledger::deserialize(&mut c)?;
+        assert_eq!(other_ledger.settings_history().len(), 2);
+        assert_eq!(other_ledger.settings_history().get(1), Some(&settings));
+        assert_eq!(ledger, other_ledger);
+        Ok(())
+    }
+
+    fn framed_test_ledger() -> Ledger {
+        LedgerBuilder::from_config(ConfigBuilder::new())
+            .faucet_value(Value(42000))
+            .build()
+            .expect("cannot build test ledger")
+            .into()
+    }
+
+    #[test]
+    pub fn ledger_framed_serialize_deserialize_bijection() {
+        let ledger = framed_test_ledger();
+        let bytes = serialize_framed(&ledger, Vec::new()).unwrap();
+        let recovered = deserialize_framed(bytes.as_slice(), RecoveryMode::Strict).unwrap();
+        assert_eq!(ledger, recovered.ledger);
+        assert!(recovered.skipped.is_empty());
+    }
+
+    #[test]
+    pub fn ledger_framed_corrupted_entry_is_reported() {
+        let ledger = framed_test_ledger();
+        let mut bytes = serialize_framed(&ledger, Vec::new()).unwrap();
+
+        // corrupt the content of the entry following the globals
+        let globals_size = u32::from_be_bytes(bytes[0..4].try_into().unwrap()) as usize;
+        let second_frame = 8 + globals_size;
+        bytes[second_frame + 8] ^= 0xFF;
+
+        match deserialize_framed(bytes.as_slice(), RecoveryMode::Strict) {
+            Err(FramedRecoveryError::Corrupted(CorruptedEntry {
+                index,
+                offset,
+                corruption: EntryCorruption::ChecksumMismatch { .. },
+            })) => {
+                assert_eq!(index, 1);
+                assert_eq!(offset, second_frame as u64);
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        let recovered = deserialize_framed(bytes.as_slice(), RecoveryMode::Salvage).unwrap();
+        assert_eq!(recovered.skipped.len(), 1);
+        assert_eq!(recovered.skipped[0].index, 1);
+        assert_eq!(recovered.ledger.iter().count(), ledger.iter().count() - 1);
+    }
+
+    #[test]
+    pub fn ledger_manifest_matches_the_snapshot() {
+        let ledger = framed_test_ledger();
+        let (bytes, manifest) = serialize_with_manifest(&ledger, Vec::new()).unwrap();
+
+        let mut plain = Vec::new();
+        ledger.serialize(&mut plain).unwrap();
+        assert_eq!(bytes, plain);
+        assert_eq!(manifest.total_bytes, bytes.len() as u64);
+        assert_eq!(manifest.total_entries(), ledger.iter().count() as u64);
+        assert_eq!(
+            manifest.state_digest,
+            crate::audit::ledger_digest(&ledger).unwrap()
+        );
+
+        let mut manifest_bytes = Vec::new();
+        manifest.serialize(&mut manifest_bytes).unwrap();
+        let manifest = SnapshotManifest::deserialize(manifest_bytes.as_slice()).unwrap();
+        let loaded = deserialize_with_manifest(bytes.as_slice(), &manifest).unwrap();
+        assert_eq!(ledger, loaded);
+    }
+
+    #[test]
+    pub fn ledger_manifest_mismatch_is_reported() {
+        let ledger = framed_test_ledger();
+        let (bytes, manifest) = serialize_with_manifest(&ledger, Vec::new()).unwrap();
+
+        let mut tampered = manifest.clone();
+        tampered.sections[1].entries += 1;
+        assert!(matches!(
+            deserialize_with_manifest(bytes.as_slice(), &tampered),
+            Err(ManifestError::SectionMismatch { index: 1, .. })
+        ));
+
+        let mut tampered = manifest.clone();
+        tampered.sections.pop();
+        assert!(matches!(
+            deserialize_with_manifest(bytes.as_slice(), &tampered),
+            Err(ManifestError::SectionCountMismatch { .. })
+        ));
+
+        let mut tampered = manifest.clone();
+        tampered.state_digest = TestGen::hash();
+        assert!(matches!(
+            deserialize_with_manifest(bytes.as_slice(), &tampered),
+            Err(ManifestError::StateDigestMismatch { .. })
+        ));
+
+        // corrupt the last entry
+        let mut corrupted = bytes;
+        let last = corrupted.len() - 2;
+        corrupted[last] ^= 0xFF;
+        assert!(deserialize_with_manifest(corrupted.as_slice(), &manifest).is_err());
+    }
+
+    #[test]
+    pub fn ledger_partial_snapshot_merge() {
+        let ledger = framed_test_ledger();
+        let filter = SnapshotFilter::only(Subsystem::Accounts).with(Subsystem::Governance);
+        let bytes = serialize_partial(&ledger, filter, Vec::new()).unwrap();
+
+        let snapshot = deserialize_partial(bytes.as_slice()).unwrap();
+        assert_eq!(snapshot.filter(), filter);
+        assert_eq!(
+            snapshot.entries().len(),
+            ledger
+                .iter()
+                .filter(|entry| Subsystem::of_entry(entry).map_or(false, |s| filter.contains(s)))
+                .count()
+        );
+
+        let empty: Ledger = LedgerBuilder::from_config(ConfigBuilder::new())
+            .build()
+            .expect("cannot build test ledger")
+            .into();
+        let merged = snapshot.merge_into(&empty).unwrap();
+        assert!(merged.accounts == ledger.accounts);
+        assert!(merged.utxos == empty.utxos);
+        assert!(merged.pots == empty.pots);
+
+        // a partial snapshot is not a ledger
+        assert!(Ledger::deserialize(bytes.as_slice()).is_err());
+        let full = ledger.serialize_as_vec().unwrap();
+        assert!(matches!(
+            deserialize_partial(full.as_slice()),
+            Err(PartialSnapshotError::NotPartial)
+        ));
+
+        // the entries outside of the filter are rejected
+        let accounts = SnapshotFilter::only(Subsystem::Accounts);
+        let mut bytes = serialize_partial(&ledger, accounts, Vec::new()).unwrap();
+        bytes[1] = SnapshotFilter::only(Subsystem::Utxos).0;
+        assert!(matches!(
+            deserialize_partial(bytes.as_slice()),
+            Err(PartialSnapshotError::UnexpectedEntry { index: 0 })
+        ));
+        bytes[1] = 0xF0;
+        assert!(matches!(
+            deserialize_partial(bytes.as_slice()),
+            Err(PartialSnapshotError::InvalidFilter(0xF0))
+        ));
+    }
+
     #[cfg(test)]
     fn pack_unpack_bijection<T, Pack, Unpack>(
         pack_method: &Pack,
@@ -1594,6 +2932,14 @@ pub mod test {
             )
         }
 
+        fn vote_plan_pack_unpack_bijection(vote_plan: VotePlan) -> TestResult {
+            pack_unpack_bijection(
+                &pack_vote_plan,
+                &unpack_vote_plan,
+                vote_plan
+            )
+        }
+
         fn config_param_pack_unpack_bijection(config_param: ConfigParam) -> TestResult {
             pack_unpack_bijection(
                 &pack_config_param,
@@ -1602,6 +2948,43 @@ pub mod test {
             )
         }
 
+        fn config_param_entry_uses_the_config_codec(config_param: ConfigParam) -> TestResult {
+            let mut codec = Codec::new(Vec::new());
+            pack_entry(&Entry::ConfigParam(config_param.clone()), &mut codec).unwrap();
+            let packed = codec.into_inner();
+
+            let mut expected = vec![EntrySerializeCode::ConfigParam as u8];
+            expected.extend(config_param.serialize_as_vec().unwrap());
+            if packed != expected {
+                return TestResult::error("the entry is not packed with the config codec");
+            }
+            let read = ConfigParam::read(&mut ReadBuf::from(&packed[1..])).unwrap();
+            let unpacked = unpack_entry_owned(&mut Codec::new(expected.as_slice())).unwrap();
+            TestResult::from_bool(
+                read == config_param
+                    && matches!(unpacked, EntryOwned::ConfigParam(p) if p == config_param),
+            )
+        }
+
+        fn epoch_settings_entry_uses_the_config_codec(epoch: u32, params: ConfigParams) -> TestResult {
+            let mut codec = Codec::new(Vec::new());
+            pack_entry(&Entry::EpochSettings((epoch, params.clone())), &mut codec).unwrap();
+            let packed = codec.into_inner();
+
+            let mut expected = vec![EntrySerializeCode::EpochSettings as u8];
+            expected.extend(epoch.to_be_bytes());
+            expected.extend(params.serialize_as_vec().unwrap());
+            if packed != expected {
+                return TestResult::error("the entry is not packed with the config codec");
+            }
+            let read = ConfigParams::read(&mut ReadBuf::from(&packed[5..])).unwrap();
+            let unpacked = unpack_entry_owned(&mut Codec::new(expected.as_slice())).unwrap();
+            TestResult::from_bool(
+                read == params
+                    && matches!(unpacked, EntryOwned::EpochSettings((e, p)) if e == epoch && p == params),
+            )
+        }
+
         fn blockdate_pack_unpack_bijection(block_date: BlockDate) -> TestResult {
             pack_unpack_bijection(
                 &|v, p| pack_block_date(*v, p),
@@ -1674,6 +3057,14 @@ pub mod test {
             )
         }
 
+        fn pool_performance_pack_unpack_bijection(pool_performance: PoolPerformance) -> TestResult {
+            pack_unpack_bijection(
+                &pack_pool_performance,
+                &unpack_pool_performance,
+                pool_performance
+            )
+        }
+
         fn update_proposal_state_pack_unpack_bijection(update_proposal_state: UpdateProposalState) -> TestResult {
             pack_unpack_bijection(
                 &pack_update_proposal_state,
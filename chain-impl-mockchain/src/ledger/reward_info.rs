@@ -1,5 +1,6 @@
 use crate::account;
-use crate::certificate::PoolId;
+use crate::certificate::{PoolId, RewardAccount};
+use crate::stake::PoolPerformance;
 use crate::value::Value;
 use std::collections::BTreeMap;
 use std::default::Default;
@@ -43,6 +44,28 @@ pub struct EpochRewardsInfo {
     pub stake_pools: BTreeMap<PoolId, (Value, Value)>,
     /// Amount added to each account. structure can be empty.
     pub accounts: BTreeMap<account::Identifier, Value>,
+    /// Amount paid to the reward account of each pool, when it is not a
+    /// single account. structure can be empty.
+    pub reward_accounts: BTreeMap<PoolId, (RewardAccount, Value)>,
+    /// Pools which forfeited their rewards because their owners didn't
+    /// delegate the pledged stake. always filled up.
+    pub pledges_not_met: BTreeMap<PoolId, PledgeNotMet>,
+    /// Blocks expected and produced by each pool with stake for the epoch.
+    /// always filled up.
+    pub performances: BTreeMap<PoolId, PoolPerformance>,
+}
+
+/// A pool forfeiting its rewards for the epoch, the owners' stake being
+/// below the pledge
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PledgeNotMet {
+    /// the pledge enforced, the highest of the pool's declared pledge and the
+    /// minimum pledge of the ledger
+    pub pledge: Value,
+    /// the stake delegated to the pool by its owners
+    pub owners_stake: Value,
+    /// the rewards of the pool, sent to the treasury
+    pub forfeited: Value,
 }
 
 impl EpochRewardsInfo {
@@ -54,6 +77,9 @@ impl EpochRewardsInfo {
             treasury: Value::zero(),
             stake_pools: BTreeMap::new(),
             accounts: BTreeMap::new(),
+            reward_accounts: BTreeMap::new(),
+            pledges_not_met: BTreeMap::new(),
+            performances: BTreeMap::new(),
         }
     }
 
@@ -69,6 +95,26 @@ impl EpochRewardsInfo {
         }
     }
 
+    pub fn set_reward_account(
+        &mut self,
+        pool: &PoolId,
+        reward_account: RewardAccount,
+        value: Value,
+    ) {
+        if self.params.report_stake_pools {
+            self.reward_accounts
+                .insert(pool.clone(), (reward_account, value));
+        }
+    }
+
+    pub fn set_pledge_not_met(&mut self, pool: &PoolId, pledge_not_met: PledgeNotMet) {
+        self.pledges_not_met.insert(pool.clone(), pledge_not_met);
+    }
+
+    pub fn set_performance(&mut self, pool: &PoolId, performance: PoolPerformance) {
+        self.performances.insert(pool.clone(), performance);
+    }
+
     pub fn add_to_account(&mut self, account: &account::Identifier, value: Value) {
         if self.params.report_accounts {
             let ent = self.accounts.entry(account.clone()).or_default();
@@ -0,0 +1,202 @@
+//! Simulation of the rewards over the future epochs.
+//!
+//! The simulation runs the arithmetic of the rewards distribution at the end
+//! of every epoch of the horizon: the contribution drawn from the reward pot,
+//! the fees, the treasury tax, the pool capping and the split of the reward
+//! between the pools, with the same functions as the ledger. It assumes the
+//! stake distribution does not change over the horizon and every pool
+//! produces exactly the blocks expected from its stake, so the rewards of
+//! the accounts are not compounded into the stake.
+
+use super::ledger::{Error, Ledger, LedgerParameters};
+use super::pool_rewards::{compute_pool_rewards, PoolRewardsParams, RewardsComputation};
+use super::pots::Pots;
+use crate::certificate::PoolId;
+use crate::date::Epoch;
+use crate::milli::Milli;
+use crate::rewards;
+use crate::setting::FeesGoesTo;
+use crate::stake::{PoolPerformance, Stake, StakeDistribution};
+use crate::value::Value;
+use std::collections::BTreeMap;
+
+/// The simulated rewards distribution of an epoch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimulatedEpoch {
+    /// the epoch of the rewards, as passed to the reward contribution
+    pub epoch: Epoch,
+    /// drawn from the reward pot
+    pub drawn: Value,
+    /// fees distributed with the reward or moved to the treasury
+    pub fees: Value,
+    /// added to the treasury during the distribution
+    pub treasury: Value,
+    /// reward of every pool producing blocks, before the tax of the pool
+    pub pools: BTreeMap<PoolId, Value>,
+    /// value of the reward pot after the distribution
+    pub reward_pot: Value,
+    /// value of the treasury after the distribution
+    pub treasury_pot: Value,
+}
+
+/// Simulate the rewards distribution of `horizon` epochs, starting with the
+/// rewards of `first_epoch`.
+///
+/// The blocks of an epoch, `blocks_per_epoch`, are shared between the pools
+/// in proportion of their stake. The fees currently in the pots are
+/// distributed with the first epoch, the next ones have no fees.
+pub fn simulate_rewards(
+    pots: &Pots,
+    distribution: &StakeDistribution,
+    params: &LedgerParameters,
+    blocks_per_epoch: u32,
+    first_epoch: Epoch,
+    horizon: u32,
+) -> Result<Vec<SimulatedEpoch>, Error> {
+    let total_stake = Stake::checked_sum(
+        distribution.to_pools.values().map(|pool| pool.stake.total),
+    )
+    .map_err(|overflow| Error::ValueSumOverflow {
+        part: "stake distribution",
+        overflow: overflow.map(|stake| Value(stake.0)),
+    })?;
+    let system_info = rewards::SystemInformation {
+        declared_stake: total_stake,
+    };
+
+    let mut leaders = Vec::new();
+    let mut performances = BTreeMap::new();
+    for (pool_id, pool) in distribution.to_pools.iter() {
+        let blocks = pool_blocks(blocks_per_epoch, pool.stake.total, total_stake);
+        if blocks > 0 {
+            leaders.push((pool_id.clone(), blocks));
+        }
+        // the epoch of the performance is not used by the rewards
+        let performance = PoolPerformance {
+            epoch: first_epoch,
+            expected: blocks,
+            produced: blocks,
+        };
+        performances.insert(pool_id.clone(), performance);
+    }
+    leaders.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let total_blocks: u32 = leaders.iter().map(|(_, blocks)| blocks).sum();
+
+    let mut pots = pots.clone();
+    let mut table = Vec::with_capacity(horizon as usize);
+    for epoch in (first_epoch..).take(horizon as usize) {
+        let treasury_initial_value = pots.treasury_value();
+        let mut simulated = SimulatedEpoch {
+            epoch,
+            drawn: Value::zero(),
+            fees: Value::zero(),
+            treasury: Value::zero(),
+            pools: BTreeMap::new(),
+            reward_pot: Value::zero(),
+            treasury_pot: Value::zero(),
+        };
+
+        // without any block, the ledger distributes nothing
+        if total_blocks > 0 {
+            let expected_epoch_reward = rewards::rewards_contribution_calculation(
+                epoch,
+                &params.reward_params,
+                &system_info,
+            );
+            simulated.drawn = pots.draw_reward(expected_epoch_reward);
+            simulated.fees = pots.siphon_fees();
+
+            let mut total_reward = simulated.drawn;
+            match params.fees_goes_to {
+                FeesGoesTo::Rewards => {
+                    total_reward =
+                        Value::checked_sum([total_reward, simulated.fees]).map_err(|overflow| {
+                            Error::ValueSumOverflow {
+                                part: "epoch rewards",
+                                overflow,
+                            }
+                        })?;
+                }
+                FeesGoesTo::Treasury => pots.treasury_add(simulated.fees)?,
+            }
+
+            let treasury_distr = rewards::tax_cut(total_reward, &params.treasury_tax)?;
+            pots.treasury_add(treasury_distr.taxed)?;
+            let total_reward = treasury_distr.after_tax;
+
+            if total_reward > Value::zero() {
+                let pool_cap = params
+                    .reward_params
+                    .pool_participation_capping
+                    .filter(|(threshold, _)| leaders.len() >= threshold.get() as usize)
+                    .map(|(_, expected_nb_pools)| {
+                        Value(total_reward.0 / expected_nb_pools.get() as u64)
+                    });
+                let reward_unit = total_reward.split_in(total_blocks);
+                let pool_params = PoolRewardsParams {
+                    reward_unit: reward_unit.parts,
+                    pool_cap,
+                    pool_performance_rewards: params.pool_performance_rewards,
+                    minimum_pool_pledge: params.minimum_pool_pledge,
+                };
+                let pools_rewards = compute_pool_rewards(
+                    RewardsComputation::Sequential,
+                    &pool_params,
+                    &leaders,
+                    &performances,
+                    distribution,
+                )?;
+                for ((pool_id, _), pool_rewards) in leaders.iter().zip(pools_rewards) {
+                    pots.treasury_add(pool_rewards.treasury)?;
+                    let reward = pool_rewards
+                        .stake_pool
+                        .map_or(Ok(Value::zero()), |(taxed, after_tax)| taxed + after_tax)?;
+                    simulated.pools.insert(pool_id.clone(), reward);
+                }
+                pots.treasury_add(reward_unit.remaining)?;
+            }
+        }
+
+        simulated.treasury = (pots.treasury_value() - treasury_initial_value)?;
+        simulated.reward_pot = pots.rewards;
+        simulated.treasury_pot = pots.treasury_value();
+        table.push(simulated);
+    }
+    Ok(table)
+}
+
+/// blocks of a pool, rounded to the nearest
+fn pool_blocks(blocks_per_epoch: u32, pool_stake: Stake, total_stake: Stake) -> u32 {
+    if total_stake.0 == 0 {
+        return 0;
+    }
+    let total = total_stake.0 as u128;
+    ((blocks_per_epoch as u128 * pool_stake.0 as u128 + total / 2) / total) as u32
+}
+
+impl Ledger {
+    /// Simulate the rewards of the next `horizon` epochs with other reward
+    /// parameters, from the current pots and stake distribution of the
+    /// ledger, see [`simulate_rewards`]
+    pub fn simulate_reward_params(
+        &self,
+        reward_params: rewards::Parameters,
+        horizon: u32,
+    ) -> Result<Vec<SimulatedEpoch>, Error> {
+        let params = LedgerParameters {
+            reward_params,
+            ..self.get_ledger_parameters()
+        };
+        let active_slots_coeff = Milli::from(self.settings.active_slots_coeff).to_millis() as u64;
+        let blocks_per_epoch =
+            (self.era.slots_per_epoch() as u64 * active_slots_coeff + 500) / 1000;
+        simulate_rewards(
+            &self.pots,
+            &self.get_stake_distribution(),
+            &params,
+            blocks_per_epoch as u32,
+            self.date.epoch + 1,
+            horizon,
+        )
+    }
+}
@@ -0,0 +1,232 @@
+//! Screening of the addresses of the transactions.
+//!
+//! A permissioned deployment may need to restrict who can send or receive
+//! funds, for example to the addresses of an allowlist. The ledger calls an
+//! [`AddressScreening`] hook on every input spent and every output created
+//! when applying a fragment, and the first veto of the hook fails the
+//! application of the fragment with [`Error::AddressScreeningVeto`].
+//!
+//! The hook is not part of the state of the ledger: it is not serialized and
+//! has to be enabled again on a restored ledger. It is inherited by all the
+//! ledgers derived from the one it is enabled on. No hook is enabled by
+//! default.
+
+use super::input_resolution::ResolvedInput;
+use super::ledger::{Error, Ledger};
+use crate::transaction::{Output, Payload, TransactionSlice};
+use chain_addr::Address;
+use std::fmt;
+use std::sync::Arc;
+
+/// The reason given by the hook to refuse an input or an output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScreeningVeto {
+    pub reason: String,
+}
+
+impl ScreeningVeto {
+    pub fn new(reason: impl Into<String>) -> Self {
+        ScreeningVeto {
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for ScreeningVeto {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.reason)
+    }
+}
+
+/// Whether a vetoed address is spent from or paid to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScreeningFlow {
+    Input,
+    Output,
+}
+
+impl fmt::Display for ScreeningFlow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScreeningFlow::Input => f.write_str("input"),
+            ScreeningFlow::Output => f.write_str("output"),
+        }
+    }
+}
+
+/// The hook deciding which inputs and outputs the ledger accepts, see the
+/// module documentation. Both checks accept everything by default.
+pub trait AddressScreening: Send + Sync {
+    /// check an input of a transaction, as resolved in the ledger before the
+    /// transaction is applied
+    fn screen_input(&self, _input: &ResolvedInput) -> Result<(), ScreeningVeto> {
+        Ok(())
+    }
+
+    /// check an output of a transaction before it is added to the ledger
+    fn screen_output(&self, _output: &Output<Address>) -> Result<(), ScreeningVeto> {
+        Ok(())
+    }
+}
+
+/// The hook enabled on a ledger, two ledgers are equal only if they share the
+/// same hook
+#[derive(Clone)]
+pub(crate) struct ScreeningHook(Arc<dyn AddressScreening>);
+
+impl ScreeningHook {
+    fn same_as(&self, other: &Self) -> bool {
+        Arc::as_ptr(&self.0) as *const () == Arc::as_ptr(&other.0) as *const ()
+    }
+}
+
+impl PartialEq for ScreeningHook {
+    fn eq(&self, other: &Self) -> bool {
+        self.same_as(other)
+    }
+}
+
+impl Eq for ScreeningHook {}
+
+impl Ledger {
+    /// Screen the inputs and outputs of the fragments applied from now on to
+    /// this ledger (and to all the ledgers derived from it) with `hook`,
+    /// replacing the hook enabled before.
+    pub fn enable_address_screening(mut self, hook: Arc<dyn AddressScreening>) -> Self {
+        self.address_screening = Some(ScreeningHook(hook));
+        self
+    }
+
+    /// Stop screening the addresses of the fragments.
+    pub fn disable_address_screening(mut self) -> Self {
+        self.address_screening = None;
+        self
+    }
+
+    pub fn has_address_screening(&self) -> bool {
+        self.address_screening.is_some()
+    }
+
+    /// Check the inputs of the transaction which resolve in the ledger, the
+    /// inputs failing to resolve are reported when applying the transaction.
+    pub(super) fn screen_inputs<Extra: Payload>(
+        &self,
+        tx: &TransactionSlice<Extra>,
+    ) -> Result<(), Error> {
+        if self.address_screening.is_none() {
+            return Ok(());
+        }
+        for (index, input) in self.resolve_inputs(tx).into_iter().enumerate() {
+            if let Ok(input) = input {
+                self.screen_input(index, &input)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub(super) fn screen_input(&self, index: usize, input: &ResolvedInput) -> Result<(), Error> {
+        match &self.address_screening {
+            None => Ok(()),
+            Some(hook) => hook
+                .0
+                .screen_input(input)
+                .map_err(|veto| Error::AddressScreeningVeto {
+                    flow: ScreeningFlow::Input,
+                    index,
+                    veto,
+                }),
+        }
+    }
+
+    pub(super) fn screen_output(
+        &self,
+        index: usize,
+        output: &Output<Address>,
+    ) -> Result<(), Error> {
+        match &self.address_screening {
+            None => Ok(()),
+            Some(hook) => {
+                hook.0
+                    .screen_output(output)
+                    .map_err(|veto| Error::AddressScreeningVeto {
+                        flow: ScreeningFlow::Output,
+                        index,
+                        veto,
+                    })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        date::BlockDate,
+        testing::{
+            builders::TestTxBuilder,
+            data::AddressDataValue,
+            ledger::{ConfigBuilder, LedgerBuilder},
+        },
+        value::Value,
+    };
+    use chain_addr::Discrimination;
+
+    struct Allowlist(Vec<Address>);
+
+    impl AddressScreening for Allowlist {
+        fn screen_output(&self, output: &Output<Address>) -> Result<(), ScreeningVeto> {
+            if self.0.contains(&output.address) {
+                Ok(())
+            } else {
+                Err(ScreeningVeto::new("address not in the allowlist"))
+            }
+        }
+    }
+
+    #[test]
+    fn outputs_out_of_the_allowlist_are_vetoed() {
+        let faucet = AddressDataValue::account(Discrimination::Test, Value(1000));
+        let allowed = AddressDataValue::account(Discrimination::Test, Value(0));
+        let denied = AddressDataValue::account(Discrimination::Test, Value(0));
+        let mut test_ledger = LedgerBuilder::from_config(ConfigBuilder::new())
+            .faucet(&faucet)
+            .build()
+            .unwrap();
+        assert!(!test_ledger.ledger.has_address_screening());
+
+        let to_denied = TestTxBuilder::new(test_ledger.block0_hash)
+            .move_funds(&mut test_ledger, &faucet, &denied, Value(100))
+            .get_fragment();
+        let unscreened = test_ledger.ledger.clone().apply_fragment(
+            &test_ledger.parameters,
+            &to_denied,
+            BlockDate::first(),
+        );
+        assert!(unscreened.is_ok());
+
+        test_ledger.ledger = test_ledger
+            .ledger
+            .clone()
+            .enable_address_screening(Arc::new(Allowlist(vec![allowed.address()])));
+        let error = test_ledger
+            .apply_fragment(&to_denied, BlockDate::first())
+            .unwrap_err();
+        assert_eq!(
+            error,
+            Error::AddressScreeningVeto {
+                flow: ScreeningFlow::Output,
+                index: 0,
+                veto: ScreeningVeto::new("address not in the allowlist"),
+            }
+        );
+
+        let to_allowed = TestTxBuilder::new(test_ledger.block0_hash)
+            .move_funds(&mut test_ledger, &faucet, &allowed, Value(100))
+            .get_fragment();
+        test_ledger
+            .apply_fragment(&to_allowed, BlockDate::first())
+            .unwrap();
+        assert!(test_ledger.ledger.has_address_screening());
+    }
+}
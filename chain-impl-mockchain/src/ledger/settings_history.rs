@@ -0,0 +1,139 @@
+//! Bounded history of the settings effective during the past epochs.
+//!
+//! The settings of the ledger can change at every epoch transition, when
+//! the accepted update proposals are applied. The history keeps the settings
+//! of the last [`SETTINGS_HISTORY_DEPTH`] epochs so the blocks of these
+//! epochs which are received late (for example when switching to a fork) can
+//! be validated against the parameters of their own epoch.
+//!
+//! Unlike the account history, the settings history is part of the ledger
+//! state and of the ledger recovery format.
+
+use crate::date::Epoch;
+use crate::setting::Settings;
+use std::sync::Arc;
+
+/// Number of epochs, before the current one, for which the settings are kept
+pub const SETTINGS_HISTORY_DEPTH: u32 = 16;
+
+/// The settings of the past epochs, ordered by epoch. An entry is only
+/// recorded for the epochs in which a block was applied, an epoch without
+/// blocks shares the settings of the previous entry.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SettingsHistory(Arc<Vec<(Epoch, Settings)>>);
+
+impl SettingsHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// rebuild the history from its entries, in any order
+    pub(crate) fn from_entries(mut entries: Vec<(Epoch, Settings)>) -> Self {
+        entries.sort_by_key(|(epoch, _)| *epoch);
+        SettingsHistory(Arc::new(entries))
+    }
+
+    /// record the settings which were effective during `epoch`, when leaving
+    /// it for `new_epoch`, and forget the settings of the epochs older than
+    /// the depth of the history
+    ///
+    /// The newest entry recorded before the oldest epoch of the history is
+    /// kept if no entry is recorded for this epoch, as its settings are still
+    /// effective in the epochs without blocks which follow it.
+    pub(crate) fn record(&self, epoch: Epoch, settings: &Settings, new_epoch: Epoch) -> Self {
+        let oldest = new_epoch.saturating_sub(SETTINGS_HISTORY_DEPTH);
+        let mut entries: Vec<_> = self
+            .0
+            .iter()
+            .filter(|(recorded, _)| *recorded < epoch)
+            .cloned()
+            .chain(std::iter::once((epoch, settings.clone())))
+            .collect();
+        let effective_at_oldest = entries
+            .iter()
+            .rposition(|(recorded, _)| *recorded <= oldest)
+            .unwrap_or(0);
+        entries.drain(..effective_at_oldest);
+        SettingsHistory(Arc::new(entries))
+    }
+
+    /// the settings effective during the given past epoch, `None` if the
+    /// epoch is older than the first entry of the history
+    pub fn get(&self, epoch: Epoch) -> Option<&Settings> {
+        self.0
+            .iter()
+            .rev()
+            .find(|(recorded, _)| *recorded <= epoch)
+            .map(|(_, settings)| settings)
+    }
+
+    /// iterate over the recorded epochs and their settings, oldest first
+    pub fn iter(&self) -> std::slice::Iter<'_, (Epoch, Settings)> {
+        self.0.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(slots_per_epoch: u32) -> Settings {
+        let mut settings = Settings::new();
+        settings.slots_per_epoch = slots_per_epoch;
+        settings
+    }
+
+    #[test]
+    fn lookup_falls_back_to_the_previous_recorded_epoch() {
+        let history =
+            SettingsHistory::new()
+                .record(0, &settings(10), 1)
+                .record(1, &settings(20), 4);
+
+        assert_eq!(history.get(0).unwrap().slots_per_epoch, 10);
+        assert_eq!(history.get(1).unwrap().slots_per_epoch, 20);
+        // no block was applied in the epochs 2 and 3
+        assert_eq!(history.get(3).unwrap().slots_per_epoch, 20);
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        let history = (0..SETTINGS_HISTORY_DEPTH * 2)
+            .fold(SettingsHistory::new(), |history, epoch| {
+                history.record(epoch, &settings(epoch + 1), epoch + 1)
+            });
+
+        assert_eq!(history.len(), SETTINGS_HISTORY_DEPTH as usize);
+        assert!(history.get(SETTINGS_HISTORY_DEPTH - 1).is_none());
+        assert_eq!(
+            history.get(SETTINGS_HISTORY_DEPTH).unwrap().slots_per_epoch,
+            SETTINGS_HISTORY_DEPTH + 1
+        );
+    }
+
+    #[test]
+    fn settings_effective_across_a_gap_are_kept() {
+        // no block is applied from the epoch 2 to the epoch 39
+        let history =
+            SettingsHistory::new()
+                .record(0, &settings(10), 1)
+                .record(1, &settings(20), 40);
+
+        assert_eq!(history.len(), 1);
+        let oldest = 40 - SETTINGS_HISTORY_DEPTH;
+        assert_eq!(history.get(oldest).unwrap().slots_per_epoch, 20);
+        assert_eq!(history.get(39).unwrap().slots_per_epoch, 20);
+
+        let history = history.record(40, &settings(30), 41);
+        assert_eq!(history.get(oldest + 1).unwrap().slots_per_epoch, 20);
+        assert_eq!(history.get(40).unwrap().slots_per_epoch, 30);
+    }
+}
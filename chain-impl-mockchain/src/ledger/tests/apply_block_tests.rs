@@ -1,13 +1,17 @@
 #![cfg(test)]
 use crate::{
     accounting::account::LedgerError::ValueError,
+    chaineval::PraosNonce,
     chaintypes::ChainLength,
     date::BlockDate,
-    ledger::{ledger::Error::Account, Error as LedgerError},
+    fee::LinearFee,
+    header::BlockProducer,
+    ledger::{ledger::Error::Account, Error as LedgerError, ProducerRecord, RandomnessProof},
     testing::{
-        builders::{GenesisPraosBlockBuilder, TestTxBuilder},
+        builders::{BftBlockBuilder, GenesisPraosBlockBuilder, TestTxBuilder},
         ledger::ConfigBuilder,
         scenario::{prepare_scenario, wallet, FragmentFactory},
+        TestGen,
     },
     value::{Value, ValueError::NegativeAmount},
 };
@@ -39,6 +43,93 @@ pub fn apply_block_increases_leaders_log() {
     );
 }
 
+#[test]
+pub fn block_producer_is_resolved_by_the_ledger() {
+    let (ledger, controller) = prepare_scenario()
+        .with_initials(vec![wallet("Bob").with(1_000).owns("stake_pool")])
+        .build()
+        .unwrap();
+
+    let stake_pool = controller.stake_pool("stake_pool").unwrap();
+    let block = GenesisPraosBlockBuilder::new()
+        .with_date(BlockDate::first())
+        .with_chain_length(ledger.chain_length())
+        .with_parent_id(ledger.block0_hash)
+        .build(&stake_pool, ledger.era());
+
+    assert_eq!(
+        block.header().producer(),
+        Some(BlockProducer::Pool(stake_pool.id()))
+    );
+    match ledger.ledger.block_producer(block.header()) {
+        Some(ProducerRecord::Pool(pool)) => {
+            assert_eq!(pool.registration.to_id(), stake_pool.id())
+        }
+        record => panic!("the pool is not resolved: {:?}", record),
+    }
+
+    let intruder = TestGen::leader_pair();
+    let block = BftBlockBuilder::new()
+        .with_date(BlockDate::first())
+        .with_chain_length(ledger.chain_length())
+        .with_parent_id(ledger.block0_hash)
+        .build(&intruder, ledger.era());
+    assert_eq!(
+        block.header().producer(),
+        Some(BlockProducer::BftLeader(intruder.id()))
+    );
+    assert_eq!(ledger.ledger.block_producer(block.header()), None);
+}
+
+#[test]
+pub fn epoch_randomness_is_proven_by_the_headers() {
+    let (mut ledger, controller) = prepare_scenario()
+        .with_initials(vec![wallet("Bob").with(1_000).owns("stake_pool")])
+        .build()
+        .unwrap();
+
+    let stake_pool = controller.stake_pool("stake_pool").unwrap();
+    let mut headers = Vec::new();
+    for _ in 0..2 {
+        let block = ledger.forge_empty_block(&stake_pool);
+        headers.push(block.header().clone());
+        ledger.apply_block(block).unwrap();
+    }
+    assert_eq!(ledger.ledger.epoch_randomness(0), None);
+
+    ledger.distribute_rewards().unwrap();
+    let block = GenesisPraosBlockBuilder::new()
+        .with_date(BlockDate {
+            epoch: 1,
+            slot_id: 0,
+        })
+        .with_chain_length(ledger.chain_length())
+        .with_parent_id(ledger.block0_hash)
+        .build(&stake_pool, ledger.era());
+    headers.push(block.header().clone());
+    ledger.apply_block(block).unwrap();
+
+    let randomness = ledger.ledger.epoch_randomness(0).unwrap();
+    assert_ne!(randomness.nonce, PraosNonce::zero());
+    assert_eq!(ledger.ledger.epoch_randomness(1), None);
+
+    let proof = ledger.ledger.randomness_proof(0, &headers).unwrap();
+    assert_eq!(proof.contributions.len(), 2);
+    assert!(proof.verify(&randomness));
+    let partial = RandomnessProof::from_headers(PraosNonce::zero(), 0, &headers[..1]);
+    assert!(!partial.verify(&randomness));
+
+    assert_eq!(
+        randomness.derive(b"lottery", b"round 1"),
+        randomness.derive(b"lottery", b"round 1")
+    );
+    assert_ne!(
+        randomness.derive(b"lottery", b"round 1"),
+        randomness.derive(b"lotter", b"yround 1")
+    );
+    assert!(randomness.draw(b"lottery", b"round 1", 10) < 10);
+}
+
 #[test]
 pub fn apply_block_wrong_chain_length() {
     let (mut ledger, controller) = prepare_scenario()
@@ -202,3 +293,80 @@ pub fn apply_block_above_max_content_size() {
         ledger.apply_block(block)
     );
 }
+
+#[test]
+pub fn apply_block_emits_reward_summary() {
+    let (mut ledger, controller) = prepare_scenario()
+        .with_config(
+            ConfigBuilder::new()
+                .with_fee(LinearFee::new(10, 1, 0))
+                .with_fees_in_treasury(),
+        )
+        .with_initials(vec![
+            wallet("Alice").with(1_000).owns("stake_pool"),
+            wallet("Bob").with(1_000),
+        ])
+        .build()
+        .unwrap();
+    let alice = controller.wallet("Alice").unwrap();
+    let bob = controller.wallet("Bob").unwrap();
+    let stake_pool = controller.stake_pool("stake_pool").unwrap();
+
+    let fragment_factory = FragmentFactory::from_ledger(&ledger);
+    let fragment = fragment_factory.transaction(&alice, &bob, &mut ledger, 100);
+    let block = ledger.forge_praos_block_with_fragments(&stake_pool, vec![fragment]);
+    let fees_before = ledger.pots().fees_value();
+
+    let events = ledger.apply_block_with_events(block).unwrap();
+    let summary = events.block_reward().expect("block reward summary");
+
+    assert_eq!(summary.producer, Some(stake_pool.id()));
+    assert_eq!(summary.chain_length, ledger.chain_length());
+    assert_eq!(
+        summary.fees,
+        (ledger.pots().fees_value() - fees_before).unwrap()
+    );
+    assert!(summary.fees > Value::zero());
+    assert_eq!(summary.treasury_cut, summary.fees);
+    assert_eq!(summary.rewards(), Value::zero());
+}
+
+#[test]
+pub fn block_fee_audit_reports_the_fee_drift() {
+    let (mut ledger, controller) = prepare_scenario()
+        .with_config(ConfigBuilder::new().with_fee(LinearFee::new(10, 1, 0)))
+        .with_initials(vec![
+            wallet("Alice").with(1_000).owns("stake_pool"),
+            wallet("Bob").with(1_000),
+        ])
+        .build()
+        .unwrap();
+    let alice = controller.wallet("Alice").unwrap();
+    let bob = controller.wallet("Bob").unwrap();
+    let stake_pool = controller.stake_pool("stake_pool").unwrap();
+
+    let fragment =
+        FragmentFactory::from_ledger(&ledger).transaction(&alice, &bob, &mut ledger, 100);
+    let fragment_id = fragment.hash();
+    let block = ledger.forge_praos_block_with_fragments(&stake_pool, vec![fragment]);
+
+    let params = ledger.ledger.get_ledger_parameters();
+    let audit = ledger.ledger.audit_block_fees(&params, &block).unwrap();
+    assert_eq!(audit.fragments.len(), 1);
+    assert_eq!(audit.fragments[0].fragment_id, fragment_id);
+    assert!(audit.fragments[0].collected > Value::zero());
+    assert!(audit.is_consistent());
+
+    let mut outdated_params = params;
+    outdated_params.fees = LinearFee::new(11, 1, 0);
+    let audit = ledger
+        .ledger
+        .audit_block_fees(&outdated_params, &block)
+        .unwrap();
+    let discrepancies: Vec<_> = audit.discrepancies().collect();
+    assert_eq!(discrepancies.len(), 1);
+    assert_eq!(
+        discrepancies[0].expected,
+        (discrepancies[0].collected + Value(1)).unwrap()
+    );
+}
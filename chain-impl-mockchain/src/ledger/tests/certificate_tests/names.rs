@@ -0,0 +1,153 @@
+#![cfg(test)]
+
+use crate::{
+    account,
+    certificate::{Certificate, NameClaim, NameRelease},
+    date::BlockDate,
+    fee::LinearFee,
+    fragment::Fragment,
+    ledger::ledger::Error,
+    names::{Name, NameRegistryError, NameRegistryParams},
+    testing::{
+        builders::{make_witness, TestTxCertBuilder},
+        data::Wallet,
+        ConfigBuilder, LedgerBuilder, TestLedger,
+    },
+    transaction::{Payload, Transaction, TxBuilder},
+    value::*,
+};
+use std::num::NonZeroU32;
+
+fn name_registry() -> NameRegistryParams {
+    NameRegistryParams {
+        base_fee: 10,
+        short_name_fee: 2,
+        duration: NonZeroU32::new(2).unwrap(),
+    }
+}
+
+fn name_transaction<P: Payload<Auth = ()>>(
+    test_ledger: &TestLedger,
+    wallet: &Wallet,
+    payload: &P,
+    value: Value,
+) -> Transaction<P> {
+    let builder = TxBuilder::new()
+        .set_payload(payload)
+        .set_expiry_date(BlockDate::first().next_epoch())
+        .set_ios(&[wallet.make_input_with_value(value)], &[]);
+    let witness = make_witness(
+        test_ledger.block0_hash(),
+        &wallet.as_account_data(),
+        &builder.get_auth_data_for_witness().hash(),
+    );
+    builder.set_witnesses(&[witness]).set_payload_auth(&())
+}
+
+fn claim(test_ledger: &TestLedger, wallet: &Wallet, name: &Name) -> Fragment {
+    let claim = NameClaim::new(name.clone());
+    let fee = TestTxCertBuilder::new(*test_ledger.block0_hash(), test_ledger.fee())
+        .fee(&Certificate::NameClaim(claim.clone()))
+        .saturating_add(name_registry().claim_fee(name));
+    Fragment::NameClaim(name_transaction(test_ledger, wallet, &claim, fee))
+}
+
+fn release(test_ledger: &TestLedger, wallet: &Wallet, name: &Name) -> Fragment {
+    let release = NameRelease::new(name.clone());
+    let fee = TestTxCertBuilder::new(*test_ledger.block0_hash(), test_ledger.fee())
+        .fee(&Certificate::NameRelease(release.clone()));
+    Fragment::NameRelease(name_transaction(test_ledger, wallet, &release, fee))
+}
+
+#[test]
+pub fn name_claim_is_rejected_when_registry_is_disabled() {
+    let alice = Wallet::from_value(Value(1000));
+    let name: Name = "alice".parse().unwrap();
+    let mut test_ledger =
+        LedgerBuilder::from_config(ConfigBuilder::new().with_fee(LinearFee::new(1, 1, 1)))
+            .faucets_wallets(vec![&alice])
+            .build()
+            .unwrap();
+
+    let fragment = claim(&test_ledger, &alice, &name);
+    assert_eq!(
+        test_ledger.apply_fragment(&fragment, BlockDate::first()),
+        Err(Error::NameRegistry(NameRegistryError::Disabled))
+    );
+}
+
+#[test]
+pub fn name_claim_resolve_and_release() {
+    let mut alice = Wallet::from_value(Value(1000));
+    let bob = Wallet::from_value(Value(1000));
+    let name: Name = "alice".parse().unwrap();
+    let mut test_ledger = LedgerBuilder::from_config(
+        ConfigBuilder::new()
+            .with_fee(LinearFee::new(1, 1, 1))
+            .with_name_registry(name_registry()),
+    )
+    .faucets_wallets(vec![&alice, &bob])
+    .build()
+    .unwrap();
+
+    let fragment = claim(&test_ledger, &alice, &name);
+    test_ledger
+        .apply_fragment(&fragment, BlockDate::first())
+        .unwrap();
+    alice.confirm_transaction();
+
+    let alice_id: account::Identifier = alice.public_key().into();
+    assert_eq!(test_ledger.ledger.resolve_name(&name), Some(&alice_id));
+    // 3 for the transaction and the certificate, 10 + 2 * 27 for the name
+    assert_eq!(
+        test_ledger.accounts().get_state(&alice_id).unwrap().value,
+        Value(1000 - 3 - 64)
+    );
+
+    let fragment = claim(&test_ledger, &bob, &name);
+    assert_eq!(
+        test_ledger.apply_fragment(&fragment, BlockDate::first()),
+        Err(Error::NameRegistry(NameRegistryError::AlreadyClaimed(
+            name.clone()
+        )))
+    );
+    let fragment = release(&test_ledger, &bob, &name);
+    assert_eq!(
+        test_ledger.apply_fragment(&fragment, BlockDate::first()),
+        Err(Error::NameRegistry(NameRegistryError::NotOwner(
+            name.clone()
+        )))
+    );
+
+    let fragment = release(&test_ledger, &alice, &name);
+    test_ledger
+        .apply_fragment(&fragment, BlockDate::first())
+        .unwrap();
+    assert_eq!(test_ledger.ledger.resolve_name(&name), None);
+    assert!(test_ledger.ledger.names().is_empty());
+}
+
+#[test]
+pub fn claimed_name_expires() {
+    let alice = Wallet::from_value(Value(1000));
+    let name: Name = "alice".parse().unwrap();
+    let mut test_ledger = LedgerBuilder::from_config(
+        ConfigBuilder::new()
+            .with_fee(LinearFee::new(1, 1, 1))
+            .with_name_registry(name_registry()),
+    )
+    .faucets_wallets(vec![&alice])
+    .build()
+    .unwrap();
+
+    let fragment = claim(&test_ledger, &alice, &name);
+    test_ledger
+        .apply_fragment(&fragment, BlockDate::first())
+        .unwrap();
+
+    let names = test_ledger.ledger.names();
+    let alice_id: account::Identifier = alice.public_key().into();
+    assert_eq!(names.resolve(&name, 1), Some(&alice_id));
+    assert_eq!(names.resolve(&name, 2), None);
+    assert!(names.remove_expired(2).is_empty());
+}
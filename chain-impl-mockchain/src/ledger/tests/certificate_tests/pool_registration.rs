@@ -1,7 +1,7 @@
 #![cfg(test)]
 
 use crate::{
-    certificate::PoolPermissions,
+    certificate::{CertificateKind, CertificatePolicy, PoolPermissions},
     date::BlockDate,
     ledger::{
         check::{CHECK_POOL_REG_MAXIMUM_OPERATORS, CHECK_POOL_REG_MAXIMUM_OWNERS},
@@ -14,6 +14,7 @@ use crate::{
     },
     value::*,
 };
+use chain_addr::{Address, Discrimination, Kind, KindType};
 use chain_crypto::{Ed25519, PublicKey};
 use std::iter;
 
@@ -50,6 +51,38 @@ pub fn pool_registration_is_accepted() {
         .is_ok());
 }
 
+#[test]
+pub fn pool_registration_disabled_by_certificate_policy() {
+    let alice = Wallet::from_value(Value(100));
+
+    let mut test_ledger = LedgerBuilder::from_config(ConfigBuilder::new().with_certificate_policy(
+        CertificatePolicy::allow_all().disable(CertificateKind::PoolRegistration),
+    ))
+    .faucets_wallets(vec![&alice])
+    .build()
+    .expect("cannot build test ledger");
+
+    let stake_pool = StakePoolBuilder::new()
+        .with_owners(vec![alice.public_key()])
+        .with_pool_permissions(PoolPermissions::new(1))
+        .build();
+
+    let certificate = build_stake_pool_registration_cert(&stake_pool.info());
+    let fragment = TestTxCertBuilder::new(test_ledger.block0_hash, test_ledger.fee())
+        .make_transaction(
+            test_ledger.date(),
+            &[alice],
+            &certificate,
+            Default::default(),
+        );
+    assert_eq!(
+        test_ledger.apply_fragment(&fragment, test_ledger.date()),
+        Err(Error::CertificateDisabled {
+            kind: CertificateKind::PoolRegistration
+        })
+    );
+}
+
 #[test]
 pub fn pool_registration_zero_management_threshold() {
     let alice = Wallet::from_value(Value(100));
@@ -244,3 +277,82 @@ pub fn pool_registration_too_many_signatures() {
         test_ledger.apply_fragment(&fragment, test_ledger.date())
     );
 }
+
+#[test]
+pub fn pool_registration_pledge_below_minimum() {
+    let alice = Wallet::from_value(Value(100));
+
+    let mut test_ledger =
+        LedgerBuilder::from_config(ConfigBuilder::new().with_minimum_pool_pledge(Value(50)))
+            .faucets_wallets(vec![&alice])
+            .build()
+            .expect("cannot build test ledger");
+
+    let stake_pool = StakePoolBuilder::new()
+        .with_owners(vec![alice.public_key()])
+        .with_pool_permissions(PoolPermissions::new(1))
+        .with_pledge(Value(49))
+        .build();
+
+    let certificate = build_stake_pool_registration_cert(&stake_pool.info());
+    let fragment = TestTxCertBuilder::new(test_ledger.block0_hash, test_ledger.fee())
+        .make_transaction(
+            test_ledger.date(),
+            &[alice],
+            &certificate,
+            Default::default(),
+        );
+    assert_err!(
+        Error::PoolRegistrationPledgeBelowMinimum {
+            pledge: Value(49),
+            minimum: Value(50),
+        },
+        test_ledger.apply_fragment(&fragment, test_ledger.date())
+    );
+}
+
+#[test]
+pub fn pool_registration_reward_address_is_validated() {
+    let alice = Wallet::from_value(Value(100));
+
+    let mut test_ledger = LedgerBuilder::from_config(ConfigBuilder::new())
+        .faucets_wallets(vec![&alice])
+        .build()
+        .expect("cannot build test ledger");
+
+    let cases = [
+        (
+            Address(
+                Discrimination::Production,
+                Kind::Account(alice.public_key()),
+            ),
+            Error::PoolRegistrationRewardAddressInvalidDiscrimination,
+        ),
+        (
+            Address(Discrimination::Test, Kind::Script([0; 32])),
+            Error::PoolRegistrationRewardAddressInvalidKind {
+                kind: KindType::Script,
+            },
+        ),
+    ];
+    for (reward_address, error) in cases {
+        let stake_pool = StakePoolBuilder::new()
+            .with_owners(vec![alice.public_key()])
+            .with_pool_permissions(PoolPermissions::new(1))
+            .with_reward_address(reward_address)
+            .build();
+
+        let certificate = build_stake_pool_registration_cert(&stake_pool.info());
+        let fragment = TestTxCertBuilder::new(test_ledger.block0_hash, test_ledger.fee())
+            .make_transaction(
+                test_ledger.date(),
+                &[alice.clone()],
+                &certificate,
+                Default::default(),
+            );
+        assert_err!(
+            error,
+            test_ledger.apply_fragment(&fragment, test_ledger.date())
+        );
+    }
+}
@@ -1,11 +1,17 @@
 use crate::{
+    certificate::UpdateProposal,
+    config::ConfigParam,
+    fee::LinearFee,
+    fragment::ConfigParams,
     header::BlockDate,
+    ledger::Error,
     testing::arbitrary::update_proposal::UpdateProposalData,
     testing::{
         data::{AddressData, AddressDataValue, Wallet},
         scenario::FragmentFactory,
-        ConfigBuilder, LedgerBuilder,
+        ConfigBuilder, LedgerBuilder, TestGen,
     },
+    update,
     value::*,
 };
 use chain_addr::Discrimination;
@@ -84,3 +90,25 @@ pub fn ledger_adopt_settings_from_update_proposal(
                                 expected_params,actual_params))
     }
 }
+
+#[test]
+pub fn ledger_rejects_fees_update_outside_epoch_transition() {
+    let leader_pair = TestGen::leader_pair();
+    let cb = ConfigBuilder::new().with_leaders(&[leader_pair.id()]);
+    let testledger = LedgerBuilder::from_config(cb)
+        .build()
+        .expect("cannot build test ledger");
+
+    let fees = ConfigParam::LinearFee(LinearFee::new(10, 2, 1));
+    let update = UpdateProposal::new(ConfigParams(vec![fees]), leader_pair.id());
+    assert!(matches!(
+        testledger.ledger.clone().apply_update(&update),
+        Err(Error::Update(
+            update::Error::FeesChangeOutsideEpochTransition
+        ))
+    ));
+
+    let schedule = testledger.ledger.fee_schedule();
+    assert!(!schedule.changes_at_next_epoch());
+    assert_eq!(schedule.current, testledger.ledger.settings.linear_fees);
+}
@@ -1,19 +1,28 @@
 #![cfg(test)]
 
 use crate::{
+    certificate::{Certificate, VoteCast, VotePlan, VotePlanCancel},
     config::ConfigParam,
+    date::BlockDate,
     key::BftLeaderId,
-    ledger::ledger::{Block0Error, Error},
+    ledger::{
+        ledger::{Block0Error, Error},
+        VotePlanCancelReport,
+    },
     testing::{
         builders::{
             create_initial_update_proposal, create_initial_update_vote, create_initial_vote_cast,
             create_initial_vote_plan, create_initial_vote_tally, InitialFaultTolerantTxCertBuilder,
+            TestTxCertBuilder,
         },
         data::Wallet,
-        ConfigBuilder, LedgerBuilder, TestGen, VoteTestGen,
+        ConfigBuilder, LedgerBuilder, TestGen, TestLedger, VoteTestGen,
     },
     value::*,
+    vote::{CommitteeId, VoteError, VotePlanLedgerError},
 };
+use imhamt::UpdateError;
+use std::iter;
 
 #[test]
 pub fn vote_plan_in_block0() {
@@ -156,3 +165,186 @@ pub fn update_proposal_is_not_allowed_in_block0() {
         Error::Block0(Block0Error::HasUpdateProposal)
     );
 }
+
+fn submit_vote_plan(
+    test_ledger: &mut TestLedger,
+    member: &Wallet,
+    vote_plan: VotePlan,
+) -> Result<(), Error> {
+    let fragment = TestTxCertBuilder::new(test_ledger.block0_hash, test_ledger.fee())
+        .make_transaction(
+            test_ledger.date(),
+            iter::once(member),
+            &vote_plan.into(),
+            Default::default(),
+        );
+    test_ledger.apply_fragment(&fragment, test_ledger.date())
+}
+
+#[test]
+pub fn vote_plans_are_rate_limited_per_committee_member() {
+    let mut alice = Wallet::from_value(Value(100));
+    let bob = Wallet::from_value(Value(100));
+    let mut test_ledger = LedgerBuilder::from_config(
+        ConfigBuilder::new()
+            .with_max_vote_plans_per_epoch(2)
+            .with_max_proposals_per_vote_plan(3),
+    )
+    .faucets_wallets(vec![&alice, &bob])
+    .build()
+    .expect("cannot build test ledger");
+    let member = CommitteeId::from(alice.public_key());
+
+    assert_eq!(
+        submit_vote_plan(
+            &mut test_ledger,
+            &alice,
+            VoteTestGen::vote_plan_with_proposals(4)
+        ),
+        Err(Error::VotePlanTooManyProposals {
+            proposals: 4,
+            limit: 3
+        })
+    );
+
+    for _ in 0..2 {
+        submit_vote_plan(&mut test_ledger, &alice, VoteTestGen::vote_plan()).unwrap();
+        alice.confirm_transaction();
+    }
+    let quota = test_ledger.ledger.governance_quota(&member);
+    assert_eq!(quota.vote_plans_submitted, 2);
+    assert_eq!(quota.remaining_vote_plans(), Some(0));
+
+    assert_eq!(
+        submit_vote_plan(&mut test_ledger, &alice, VoteTestGen::vote_plan()),
+        Err(Error::VotePlanQuotaExceeded { member, limit: 2 })
+    );
+    // the quota is counted per committee member
+    submit_vote_plan(&mut test_ledger, &bob, VoteTestGen::vote_plan()).unwrap();
+    assert_eq!(
+        test_ledger
+            .ledger
+            .governance_quota(&CommitteeId::from(bob.public_key()))
+            .remaining_vote_plans(),
+        Some(1)
+    );
+}
+
+fn vote_error(vote_plan: &VotePlan, reason: VoteError) -> Error {
+    Error::VotePlan(VotePlanLedgerError::VoteError {
+        id: vote_plan.to_id(),
+        reason: UpdateError::ValueCallbackError(reason),
+    })
+}
+
+#[test]
+pub fn vote_plan_is_cancelled_by_a_majority_of_the_committee() {
+    let mut alice = Wallet::from_value(Value(100));
+    let bob = Wallet::from_value(Value(100));
+    let clarice = Wallet::from_value(Value(100));
+    let leader_pair = TestGen::leader_pair();
+    let mut config = ConfigBuilder::new().with_leaders(&[leader_pair.id()]);
+    for member in [&alice, &bob, &clarice] {
+        config = config.with_committee_id(CommitteeId::from(member.public_key()));
+    }
+    let mut test_ledger = LedgerBuilder::from_config(config)
+        .faucets_wallets(vec![&alice, &bob, &clarice])
+        .build()
+        .expect("cannot build test ledger");
+
+    let vote_plan = VoteTestGen::vote_plan();
+    submit_vote_plan(&mut test_ledger, &alice, vote_plan.clone()).unwrap();
+    alice.confirm_transaction();
+
+    let cancel = |test_ledger: &TestLedger, signers: Vec<&Wallet>| {
+        let certificate = Certificate::from(VotePlanCancel::new(vote_plan.to_id()));
+        TestTxCertBuilder::new(test_ledger.block0_hash, test_ledger.fee()).make_transaction(
+            test_ledger.date().next_epoch(),
+            signers,
+            &certificate,
+            Default::default(),
+        )
+    };
+
+    // 2 of the 3 members of the committee are required
+    assert_eq!(
+        test_ledger.apply_fragment(&cancel(&test_ledger, vec![&alice]), test_ledger.date()),
+        Err(vote_error(
+            &vote_plan,
+            VoteError::VotePlanCancelThresholdNotMet {
+                signers: 1,
+                threshold: 2
+            }
+        ))
+    );
+    let outsider = Wallet::from_value(Value(100));
+    assert_eq!(
+        test_ledger.apply_fragment(
+            &cancel(&test_ledger, vec![&alice, &outsider]),
+            test_ledger.date()
+        ),
+        Err(vote_error(&vote_plan, VoteError::InvalidCancelCommittee))
+    );
+
+    let block = test_ledger.forge_bft_block_with_fragments(
+        &leader_pair,
+        test_ledger.date(),
+        vec![cancel(&test_ledger, vec![&alice, &clarice])],
+    );
+    let cancel_date = block.header().block_date();
+    let events = test_ledger.apply_block_with_events(block).unwrap();
+    alice.confirm_transaction();
+    assert_eq!(
+        events.vote_plan_cancellations().collect::<Vec<_>>(),
+        vec![&VotePlanCancelReport {
+            vote_plan: vote_plan.to_id(),
+            date: cancel_date,
+            proposals: vote_plan
+                .proposals()
+                .iter()
+                .map(|proposal| proposal.external_id().clone())
+                .collect(),
+        }]
+    );
+
+    let status = test_ledger
+        .ledger
+        .active_vote_plans()
+        .into_iter()
+        .find(|status| status.id == vote_plan.to_id())
+        .expect("the cancelled vote plan is kept until its committee end");
+    assert_eq!(status.cancelled, Some(cancel_date));
+    assert!(status.proposals.iter().all(|proposal| proposal.cancelled));
+
+    // no vote is accepted during the vote time
+    test_ledger.fast_forward_to(vote_plan.vote_start());
+    let vote_cast = TestTxCertBuilder::new(test_ledger.block0_hash, test_ledger.fee())
+        .make_transaction(
+            test_ledger.date().next_epoch(),
+            iter::once(&bob),
+            &VoteCast::new(vote_plan.to_id(), 0, VoteTestGen::vote_cast_payload()).into(),
+            Default::default(),
+        );
+    assert_eq!(
+        test_ledger.apply_fragment(&vote_cast, test_ledger.date()),
+        Err(vote_error(
+            &vote_plan,
+            VoteError::VotePlanCancelled { date: cancel_date }
+        ))
+    );
+
+    // the committee time has started, too late to cancel
+    test_ledger.fast_forward_to(BlockDate::from_epoch_slot_id(2, 0));
+    assert_eq!(
+        test_ledger.apply_fragment(
+            &cancel(&test_ledger, vec![&alice, &bob]),
+            test_ledger.date()
+        ),
+        Err(vote_error(
+            &vote_plan,
+            VoteError::VotePlanCancelTooLate {
+                committee_start: vote_plan.committee_start()
+            }
+        ))
+    );
+}
@@ -0,0 +1,479 @@
+#![cfg(test)]
+//! Compatibility with the chains archived by the previous versions of the
+//! crate.
+//!
+//! Every fixture is a small chain in the formats of a version of the crate:
+//! its block0, the stream of the blocks applied after it and the serialized
+//! ledger once these blocks are applied. The tests check the current code
+//! reads them byte for byte, replays the blocks to the archived ledger and
+//! keeps extending the archived ledger with new blocks and transactions.
+//!
+//! The archived block0 holds the initial parameters and funds, a pool
+//! registration owned by a fixed key and the delegation of the archived
+//! account to this pool, so the encoding of the certificates in the blocks
+//! and of the pools and delegations in the ledger is covered as well.
+//!
+//! The `v1` fixture is in the formats of the version introducing these
+//! tests, there is no released version with the archiver to regenerate it
+//! from, and it predates the certificates of the archived chain: the
+//! certificates are only checked for the fixtures flagged with
+//! `certificates`.
+//!
+//! The chains are built from fixed keys, so the new blocks can be signed by
+//! the archived leader and the archived funds can be spent. The fixture of
+//! the current version is written by the ignored test
+//! `archive_current_version`, in the directory given by `COMPAT_ARCHIVE_DIR`,
+//! and is added to [`FIXTURES`] before the formats change.
+
+use crate::{
+    account::DelegationType,
+    block::{self, Block, BlockDate, BlockVersion, ContentsBuilder, Header},
+    certificate::{PoolRegistration, PoolSignature, StakeDelegation},
+    chaintypes::{ConsensusType, HeaderId},
+    config::{Block0Date, ConfigParam},
+    fee::{FeeAlgorithm, LinearFee},
+    fragment::{ConfigParams, Fragment},
+    key::EitherEd25519SecretKey,
+    leadership::Leadership,
+    ledger::{Error, Ledger},
+    testing::builders::{pool_owner_signed, StakePoolBuilder},
+    transaction::{
+        AccountBindingSignature, Input, NoExtra, Output, TxBuilder, UnspecifiedAccountIdentifier,
+        Witness,
+    },
+    value::Value,
+};
+use chain_addr::{Address, Discrimination, Kind};
+use chain_core::{
+    mempack::{ReadBuf, Readable},
+    property::{Deserialize, Serialize},
+};
+use chain_crypto::{Ed25519, PublicKey, SecretKey};
+
+/// seed of the key of the only BFT leader of the archived chains
+const LEADER_SEED: [u8; 32] = [1; 32];
+/// seed of the key of the account funded in the block0
+const ACCOUNT_SEED: [u8; 32] = [2; 32];
+/// seed of the key owning the UTxO created in the block0
+const UTXO_SEED: [u8; 32] = [3; 32];
+/// seed of the key of the owner of the pool registered in the block0
+const OWNER_SEED: [u8; 32] = [5; 32];
+
+const BLOCK0_DATE: u64 = 1_600_000_000;
+const SLOTS_PER_EPOCH: u32 = 10;
+const INITIAL_FUNDS: u64 = 10_000;
+const INITIAL_TREASURY: u64 = 1_000;
+const INITIAL_REWARDS: u64 = 500;
+
+/// number of blocks applied after the block0 in the archived chains
+const ARCHIVED_BLOCKS: u32 = 2;
+
+struct Fixture {
+    name: &'static str,
+    block0: &'static [u8],
+    blocks: &'static [u8],
+    ledger: &'static [u8],
+    /// whether the block0 registers a pool and delegates the archived
+    /// account to it
+    certificates: bool,
+}
+
+const FIXTURES: &[Fixture] = &[Fixture {
+    name: "v1",
+    block0: include_bytes!("v1/block0.bin"),
+    blocks: include_bytes!("v1/blocks.bin"),
+    ledger: include_bytes!("v1/ledger.bin"),
+    certificates: false,
+}];
+
+impl Fixture {
+    fn block0(&self) -> Block {
+        let mut buf = ReadBuf::from(self.block0);
+        let block0 = Block::read(&mut buf)
+            .unwrap_or_else(|e| panic!("{}: cannot read the block0: {}", self.name, e));
+        buf.expect_end().unwrap();
+        block0
+    }
+
+    fn blocks(&self) -> Vec<Block> {
+        let mut buf = ReadBuf::from(self.blocks);
+        let mut blocks = Vec::new();
+        while !buf.is_end() {
+            let block = Block::read(&mut buf).unwrap_or_else(|e| {
+                panic!(
+                    "{}: cannot read the block {}: {}",
+                    self.name,
+                    blocks.len(),
+                    e
+                )
+            });
+            blocks.push(block);
+        }
+        blocks
+    }
+
+    fn ledger(&self) -> Ledger {
+        Ledger::deserialize(self.ledger)
+            .unwrap_or_else(|e| panic!("{}: cannot read the ledger: {}", self.name, e))
+    }
+}
+
+fn secret_key(seed: &[u8; 32]) -> SecretKey<Ed25519> {
+    SecretKey::from_binary(seed).unwrap()
+}
+
+fn public_key(seed: &[u8; 32]) -> PublicKey<Ed25519> {
+    secret_key(seed).to_public()
+}
+
+fn account_address(seed: &[u8; 32]) -> Address {
+    Address(Discrimination::Test, Kind::Account(public_key(seed)))
+}
+
+fn utxo_address(seed: &[u8; 32]) -> Address {
+    Address(Discrimination::Test, Kind::Single(public_key(seed)))
+}
+
+/// verify the leader of the block and apply it
+fn apply_block(ledger: &Ledger, block: &Block) -> Result<Ledger, Error> {
+    let header = block.header();
+    assert!(
+        Leadership::new(header.block_date().epoch, ledger)
+            .verify(header)
+            .success(),
+        "invalid leader for the block {}",
+        header.id()
+    );
+    ledger.apply_block(
+        ledger.get_ledger_parameters(),
+        block.contents(),
+        &header.get_content_eval_context(),
+    )
+}
+
+fn bft_block(parent: &Header, date: BlockDate, fragments: Vec<Fragment>) -> Block {
+    let mut contents = ContentsBuilder::new();
+    contents.push_many(fragments);
+    block::builder(BlockVersion::Ed25519Signed, contents.into(), |hdr| {
+        Ok::<_, ()>(
+            hdr.set_parent(&parent.id(), parent.chain_length().increase())
+                .set_date(date)
+                .into_bft_builder()
+                .unwrap()
+                .sign_using(&secret_key(&LEADER_SEED))
+                .generalize(),
+        )
+    })
+    .unwrap()
+}
+
+/// spend the whole funds of the archived account
+fn account_transfer(
+    ledger: &Ledger,
+    block0: &HeaderId,
+    valid_until: BlockDate,
+    to: &Address,
+) -> Fragment {
+    let value = ledger
+        .accounts()
+        .get_state(&public_key(&ACCOUNT_SEED).into())
+        .unwrap()
+        .value;
+    let fee = ledger.get_ledger_parameters().fees.calculate(None, 1, 1);
+    let input = Input::from_account_public_key(public_key(&ACCOUNT_SEED), value);
+    let output = Output::from_address(to.clone(), (value - fee).unwrap());
+
+    let builder = TxBuilder::new()
+        .set_nopayload()
+        .set_expiry_date(valid_until)
+        .set_ios(&[input], &[output]);
+    let witness = Witness::new_account(
+        block0,
+        &builder.get_auth_data_for_witness().hash(),
+        ledger
+            .accounts()
+            .get_state(&public_key(&ACCOUNT_SEED).into())
+            .unwrap()
+            .spending
+            .get_valid_counter(),
+        |data| secret_key(&ACCOUNT_SEED).sign(data),
+    );
+    Fragment::Transaction(builder.set_witnesses(&[witness]).set_payload_auth(&()))
+}
+
+#[test]
+fn archived_blocks_are_read_byte_for_byte() {
+    for fixture in FIXTURES {
+        assert_eq!(
+            fixture.block0().serialize_as_vec().unwrap(),
+            fixture.block0,
+            "{}: block0",
+            fixture.name
+        );
+        let blocks = fixture.blocks();
+        assert_eq!(blocks.len(), ARCHIVED_BLOCKS as usize, "{}", fixture.name);
+        let reserialized: Vec<u8> = blocks
+            .iter()
+            .flat_map(|block| block.serialize_as_vec().unwrap())
+            .collect();
+        assert_eq!(reserialized, fixture.blocks, "{}: blocks", fixture.name);
+    }
+}
+
+#[test]
+fn archived_blocks_replay_to_the_archived_ledger() {
+    for fixture in FIXTURES {
+        let block0 = fixture.block0();
+        let ledger = Ledger::new(block0.header().id(), block0.contents().iter())
+            .unwrap_or_else(|e| panic!("{}: invalid block0: {}", fixture.name, e));
+        let ledger = fixture
+            .blocks()
+            .iter()
+            .try_fold(ledger, |ledger, block| apply_block(&ledger, block))
+            .unwrap_or_else(|e| panic!("{}: cannot apply the blocks: {}", fixture.name, e));
+
+        // the parts of the ledger which are not serialized (like the EVM
+        // state) may differ, compare what the current code serializes
+        assert_eq!(
+            ledger.serialize_as_vec().unwrap(),
+            fixture.ledger().serialize_as_vec().unwrap(),
+            "{}",
+            fixture.name
+        );
+    }
+}
+
+#[test]
+fn archived_ledger_is_restored() {
+    for fixture in FIXTURES {
+        let block0 = fixture.block0();
+        let ledger = fixture.ledger();
+
+        assert_eq!(ledger.chain_length().0, ARCHIVED_BLOCKS, "{}", fixture.name);
+        assert_eq!(
+            ledger.static_params.block0_initial_hash,
+            block0.header().id(),
+            "{}",
+            fixture.name
+        );
+        assert_eq!(ledger.era.slots_per_epoch(), SLOTS_PER_EPOCH);
+        assert_eq!(ledger.treasury_value(), Value(INITIAL_TREASURY));
+        assert_eq!(ledger.pots.rewards, Value(INITIAL_REWARDS));
+        assert_eq!(
+            ledger
+                .accounts()
+                .get_state(&public_key(&ACCOUNT_SEED).into())
+                .unwrap()
+                .value,
+            Value(INITIAL_FUNDS),
+            "{}",
+            fixture.name
+        );
+        assert_eq!(
+            ledger
+                .utxos()
+                .map(|entry| (entry.output.address.clone(), entry.output.value))
+                .collect::<Vec<_>>(),
+            vec![(utxo_address(&UTXO_SEED), Value(INITIAL_FUNDS))],
+            "{}",
+            fixture.name
+        );
+    }
+}
+
+#[test]
+fn archived_certificates_are_restored() {
+    for fixture in FIXTURES.iter().filter(|fixture| fixture.certificates) {
+        let ledger = fixture.ledger();
+
+        let pools: Vec<_> = ledger.delegation().stake_pool_ids().collect();
+        assert_eq!(pools.len(), 1, "{}", fixture.name);
+        let registration = ledger.delegation().lookup_reg(&pools[0]).unwrap();
+        assert_eq!(registration.owners, vec![public_key(&OWNER_SEED)]);
+        assert_eq!(
+            ledger
+                .accounts()
+                .get_state(&public_key(&ACCOUNT_SEED).into())
+                .unwrap()
+                .delegation(),
+            &DelegationType::Full(pools[0].clone()),
+            "{}",
+            fixture.name
+        );
+    }
+}
+
+#[test]
+fn archived_ledger_is_extended() {
+    for fixture in FIXTURES {
+        let block0_id = fixture.block0().header().id();
+        let tip = fixture.blocks().pop().unwrap();
+        let ledger = fixture.ledger();
+        let destination = account_address(&[4; 32]);
+
+        // a block in the archived epoch, then a block in the next epoch
+        // spending the archived account
+        let next_epoch = tip.header().block_date().next_epoch();
+        let block = bft_block(
+            tip.header(),
+            BlockDate {
+                epoch: 0,
+                slot_id: SLOTS_PER_EPOCH - 1,
+            },
+            Vec::new(),
+        );
+        let ledger = apply_block(&ledger, &block)
+            .unwrap_or_else(|e| panic!("{}: cannot extend the ledger: {}", fixture.name, e));
+        let transfer = account_transfer(&ledger, &block0_id, next_epoch, &destination);
+        let block = bft_block(block.header(), next_epoch, vec![transfer]);
+        let ledger = apply_block(&ledger, &block)
+            .unwrap_or_else(|e| panic!("{}: cannot extend the ledger: {}", fixture.name, e));
+
+        assert_eq!(ledger.date(), next_epoch);
+        assert_eq!(ledger.chain_length().0, ARCHIVED_BLOCKS + 2);
+        // the settings of the archived epoch are kept
+        assert!(ledger.settings_at(0).is_some());
+
+        let fee = ledger.get_ledger_parameters().fees.calculate(None, 1, 1);
+        assert_eq!(
+            ledger
+                .accounts()
+                .get_state(&public_key(&[4; 32]).into())
+                .unwrap()
+                .value,
+            (Value(INITIAL_FUNDS) - fee).unwrap(),
+            "{}",
+            fixture.name
+        );
+        assert_eq!(
+            ledger
+                .accounts()
+                .get_state(&public_key(&ACCOUNT_SEED).into())
+                .unwrap()
+                .value,
+            Value::zero(),
+            "{}",
+            fixture.name
+        );
+
+        // the extended ledger goes through the current format
+        let restored = Ledger::deserialize(ledger.serialize_as_vec().unwrap().as_slice()).unwrap();
+        assert_eq!(
+            restored.serialize_as_vec().unwrap(),
+            ledger.serialize_as_vec().unwrap(),
+            "{}",
+            fixture.name
+        );
+    }
+}
+
+/// register the pool of the owner, without fees as it is for the block0
+fn pool_registration(registration: &PoolRegistration) -> Fragment {
+    let builder = TxBuilder::new()
+        .set_payload(registration)
+        .set_expiry_date(BlockDate::first())
+        .set_ios(&[], &[])
+        .set_witnesses(&[]);
+    let signature = pool_owner_signed(
+        &[EitherEd25519SecretKey::Normal(secret_key(&OWNER_SEED))],
+        &builder,
+    );
+    Fragment::PoolRegistration(builder.set_payload_auth(&PoolSignature::Owners(signature)))
+}
+
+/// delegate the archived account to the pool, without fees as it is for
+/// the block0
+fn account_delegation(registration: &PoolRegistration) -> Fragment {
+    let delegation = StakeDelegation {
+        account_id: UnspecifiedAccountIdentifier::from_single_account(
+            public_key(&ACCOUNT_SEED).into(),
+        ),
+        delegation: DelegationType::Full(registration.to_id()),
+    };
+    let builder = TxBuilder::new()
+        .set_payload(&delegation)
+        .set_expiry_date(BlockDate::first())
+        .set_ios(&[], &[])
+        .set_witnesses(&[]);
+    let signature = AccountBindingSignature::new_single(&builder.get_auth_data(), |data| {
+        secret_key(&ACCOUNT_SEED).sign_slice(data.0)
+    });
+    Fragment::StakeDelegation(builder.set_payload_auth(&signature))
+}
+
+/// The chain archived by every version, built with the current code
+fn current_chain() -> (Block, Vec<Block>, Ledger) {
+    let mut params = ConfigParams::new();
+    params.push(ConfigParam::Block0Date(Block0Date(BLOCK0_DATE)));
+    params.push(ConfigParam::Discrimination(Discrimination::Test));
+    params.push(ConfigParam::ConsensusVersion(ConsensusType::Bft));
+    params.push(ConfigParam::SlotsPerEpoch(SLOTS_PER_EPOCH));
+    params.push(ConfigParam::SlotDuration(10));
+    params.push(ConfigParam::KesUpdateSpeed(12 * 3600));
+    params.push(ConfigParam::AddBftLeader(public_key(&LEADER_SEED).into()));
+    params.push(ConfigParam::LinearFee(LinearFee::new(2, 1, 0)));
+    params.push(ConfigParam::TreasuryAdd(Value(INITIAL_TREASURY)));
+    params.push(ConfigParam::RewardPot(Value(INITIAL_REWARDS)));
+
+    let funds = TxBuilder::new()
+        .set_payload(&NoExtra)
+        .set_expiry_date(BlockDate::first())
+        .set_ios(
+            &[],
+            &[
+                Output::from_address(utxo_address(&UTXO_SEED), Value(INITIAL_FUNDS)),
+                Output::from_address(account_address(&ACCOUNT_SEED), Value(INITIAL_FUNDS)),
+            ],
+        )
+        .set_witnesses(&[])
+        .set_payload_auth(&());
+
+    let registration = StakePoolBuilder::new()
+        .with_owners(vec![public_key(&OWNER_SEED)])
+        .build()
+        .info();
+
+    let mut contents = ContentsBuilder::new();
+    contents.push(Fragment::Initial(params));
+    contents.push(Fragment::Transaction(funds));
+    contents.push(pool_registration(&registration));
+    contents.push(account_delegation(&registration));
+    let block0 = block::builder(BlockVersion::Genesis, contents.into(), |hdr| {
+        Ok::<_, ()>(
+            hdr.set_genesis()
+                .set_date(BlockDate::first())
+                .into_unsigned_header()
+                .unwrap()
+                .generalize(),
+        )
+    })
+    .unwrap();
+
+    let mut ledger = Ledger::new(block0.header().id(), block0.contents().iter()).unwrap();
+    let mut blocks: Vec<Block> = Vec::new();
+    for slot_id in 1..=ARCHIVED_BLOCKS {
+        let parent = blocks.last().unwrap_or(&block0).header();
+        let block = bft_block(parent, BlockDate { epoch: 0, slot_id }, Vec::new());
+        ledger = apply_block(&ledger, &block).unwrap();
+        blocks.push(block);
+    }
+    (block0, blocks, ledger)
+}
+
+#[test]
+#[ignore]
+fn archive_current_version() {
+    let dir = std::path::PathBuf::from(
+        std::env::var("COMPAT_ARCHIVE_DIR").expect("COMPAT_ARCHIVE_DIR is not set"),
+    );
+    let (block0, blocks, ledger) = current_chain();
+    let blocks: Vec<u8> = blocks
+        .iter()
+        .flat_map(|block| block.serialize_as_vec().unwrap())
+        .collect();
+
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("block0.bin"), block0.serialize_as_vec().unwrap()).unwrap();
+    std::fs::write(dir.join("blocks.bin"), blocks).unwrap();
+    std::fs::write(dir.join("ledger.bin"), ledger.serialize_as_vec().unwrap()).unwrap();
+}
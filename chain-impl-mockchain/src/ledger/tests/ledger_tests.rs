@@ -17,42 +17,38 @@ use crate::{
         arbitrary::{AccountStatesVerifier, ArbitraryValidTransactionData, UtxoVerifier},
         builders::{OldAddressBuilder, TestTxBuilder},
         data::AddressDataValue,
-        ledger::{ConfigBuilder, LedgerBuilder},
+        ledger::{ConfigBuilder, LedgerBuilder, TestLedger},
+        scenario::{prepare_scenario, stake_pool, wallet},
+        strategy::test_ledger_strategy,
         TestGen,
     },
 };
 
+use crate::value::Value;
 use chain_addr::Discrimination;
+use proptest::prelude::*;
 use quickcheck::TestResult;
 use quickcheck_macros::quickcheck;
+use test_strategy::proptest;
 
-#[quickcheck]
-pub fn ledger_accepts_correct_transaction(
-    faucet: AddressDataValue,
-    receiver: AddressDataValue,
-) -> TestResult {
-    let mut ledger = LedgerBuilder::from_config(ConfigBuilder::new())
-        .initial_fund(&faucet)
-        .build()
-        .unwrap();
+#[proptest]
+fn ledger_accepts_correct_transaction(#[strategy(test_ledger_strategy())] ledger: TestLedger) {
+    let mut ledger = ledger;
+    let faucet = ledger.faucets.first().unwrap().clone();
+    let receiver = ledger.faucets.last().unwrap().clone();
     let fragment = TestTxBuilder::new(ledger.block0_hash)
         .move_funds(&mut ledger, &faucet, &receiver, faucet.value)
         .get_fragment();
     let total_funds_before = ledger.total_funds();
-    let result = ledger.apply_transaction(fragment, BlockDate::first());
-
-    if result.is_err() {
-        return TestResult::error(format!("Error from ledger: {}", result.err().unwrap()));
-    }
-    let total_funds_after = ledger.total_funds();
-    if total_funds_before == total_funds_after {
-        TestResult::passed()
-    } else {
-        TestResult::error(format!(
-            "Total funds in ledger before and after transaction is not equal {} <> {} ",
-            total_funds_before, total_funds_after
-        ))
-    }
+    ledger
+        .apply_transaction(fragment, BlockDate::first())
+        .map_err(|error| TestCaseError::fail(format!("Error from ledger: {}", error)))?;
+
+    prop_assert_eq!(
+        total_funds_before,
+        ledger.total_funds(),
+        "Total funds in ledger before and after transaction is not equal"
+    );
 }
 
 #[quickcheck]
@@ -372,6 +368,8 @@ pub fn wrong_fragment_at_block0(fragment: Fragment) -> TestResult {
         Fragment::OldUtxoDeclaration(_) => return TestResult::discard(),
         Fragment::Transaction(_) => return TestResult::discard(),
         Fragment::StakeDelegation(_) => return TestResult::discard(),
+        Fragment::AmountStakeDelegation(_) => return TestResult::discard(),
+        Fragment::RewardsRedelegation(_) => return TestResult::discard(),
         Fragment::PoolRegistration(_) => return TestResult::discard(),
         Fragment::VotePlan(_) => return TestResult::discard(),
         Fragment::Evm(_) => return TestResult::discard(),
@@ -390,3 +388,94 @@ pub fn wrong_fragment_at_block0(fragment: Fragment) -> TestResult {
 
     TestResult::from_bool(Ledger::new(header_id, vec![&Fragment::Initial(ie), &fragment]).is_err())
 }
+
+#[test]
+pub fn ledger_stats_counts_entries() {
+    let (ledger, _controller) = prepare_scenario()
+        .with_initials(vec![
+            wallet("Alice").with(1_000).owns("stake_pool"),
+            wallet("Bob").with(500),
+        ])
+        .with_stake_pools(vec![stake_pool("stake_pool")])
+        .build()
+        .unwrap();
+    let utxo = AddressDataValue::utxo(Discrimination::Test, Value(42));
+    let utxo_ledger = LedgerBuilder::from_config(ConfigBuilder::new())
+        .faucet(&utxo)
+        .build()
+        .unwrap();
+
+    let stats = ledger.ledger.stats();
+    assert_eq!(stats.accounts.count, 2);
+    assert_eq!(stats.accounts_total, Value(1_500));
+    assert_eq!(stats.pools.count, 1);
+    assert_eq!(stats.utxos.count, 0);
+    assert_eq!(stats.vote_plans.count, 0);
+    assert!(stats.accounts.approximate_size > 0);
+
+    let stats = utxo_ledger.ledger.stats();
+    assert_eq!(stats.utxos.count, 1);
+    assert_eq!(stats.utxos_total, Value(42));
+    assert_eq!(stats.approximate_size(), stats.utxos.approximate_size);
+}
+
+#[test]
+pub fn ledger_new_pots_exceed_total_supply() {
+    let leader_pair = TestGen::leader_pair();
+    let header_id = TestGen::hash();
+    let mut ie = ConfigParams::new();
+    ie.push(ConfigParam::Discrimination(Discrimination::Test));
+    ie.push(ConfigParam::AddBftLeader(leader_pair.id()));
+    ie.push(ConfigParam::Block0Date(crate::config::Block0Date(0)));
+    ie.push(ConfigParam::SlotDuration(10u8));
+    ie.push(ConfigParam::SlotsPerEpoch(10u32));
+    ie.push(ConfigParam::KesUpdateSpeed(3600));
+    ie.push(ConfigParam::TreasuryAdd(Value(100)));
+    ie.push(ConfigParam::RewardPot(Value(100)));
+    ie.push(ConfigParam::TotalSupply(Value(150)));
+
+    assert_eq!(
+        Ledger::new(header_id, vec![&Fragment::Initial(ie)])
+            .err()
+            .unwrap(),
+        Block0(Block0Error::PotsExceedTotalSupply {
+            treasury: Value(100),
+            rewards: Value(100),
+            total_supply: Value(150),
+        })
+    );
+}
+
+#[test]
+pub fn ledger_new_initial_funds_exceed_circulating_supply() {
+    let config = ConfigBuilder::new()
+        .with_treasury(Value(100))
+        .with_rewards(Value(100))
+        .with_total_supply(Value(1_000));
+
+    assert_eq!(
+        LedgerBuilder::from_config(config)
+            .faucet_value(Value(900))
+            .build()
+            .err()
+            .unwrap(),
+        Block0(Block0Error::InitialFundsExceedCirculatingSupply {
+            initial_funds: Value(900),
+            circulating_supply: Value(800),
+        })
+    );
+}
+
+#[test]
+pub fn ledger_new_total_supply_fully_allocated() {
+    let config = ConfigBuilder::new()
+        .with_treasury(Value(100))
+        .with_rewards(Value(100))
+        .with_total_supply(Value(1_000));
+
+    let ledger = LedgerBuilder::from_config(config)
+        .faucet_value(Value(800))
+        .build()
+        .unwrap();
+    assert_eq!(ledger.ledger.get_total_value().unwrap(), Value(1_000));
+}
@@ -0,0 +1,166 @@
+#![cfg(test)]
+
+use crate::{
+    chaintypes::HeaderId,
+    date::BlockDate,
+    fee::LinearFee,
+    fragment::Fragment,
+    ledger::{Block0Error, Error},
+    legacy::{MigrationCertificate, OldAddress, UtxoDeclaration},
+    testing::{
+        data::AddressData,
+        ledger::{ConfigBuilder, LedgerBuilder, TestLedger},
+        TestGen,
+    },
+    value::Value,
+};
+use cardano_legacy_address::ExtendedAddr;
+use chain_addr::{Address, Discrimination};
+use chain_crypto::{Ed25519, Ed25519Bip32, PublicKey, SecretKey, Signature};
+use ed25519_bip32::XPub;
+
+struct LegacyKey(SecretKey<Ed25519Bip32>);
+
+impl LegacyKey {
+    fn generate() -> Self {
+        LegacyKey(SecretKey::generate(TestGen::rand()))
+    }
+
+    fn xpub(&self) -> Vec<u8> {
+        self.0.to_public().as_ref().to_vec()
+    }
+
+    fn address(&self) -> OldAddress {
+        let xpub = XPub::from_slice(&self.xpub()).unwrap();
+        ExtendedAddr::new_simple(&xpub, None).to_address()
+    }
+
+    fn migrate(
+        &self,
+        block0: &HeaderId,
+        old_address: OldAddress,
+        destination: Address,
+    ) -> MigrationCertificate {
+        let xpub = self.xpub();
+        let mut chain_code = [0u8; 32];
+        chain_code.copy_from_slice(&xpub[32..]);
+        MigrationCertificate::new(
+            block0,
+            old_address,
+            destination,
+            BlockDate::first().next_epoch(),
+            chain_code,
+            |challenge| {
+                let signature = self.0.sign(challenge);
+                (
+                    PublicKey::<Ed25519>::from_binary(&xpub[..32]).unwrap(),
+                    Signature::from_binary(signature.as_ref()).unwrap(),
+                )
+            },
+        )
+    }
+}
+
+fn ledger_with_legacy_funds(declaration: Vec<(OldAddress, Value)>) -> TestLedger {
+    LedgerBuilder::from_config(ConfigBuilder::new().with_fee(LinearFee::new(1, 1, 1)))
+        .fragments(&[Fragment::OldUtxoDeclaration(UtxoDeclaration {
+            addrs: declaration,
+        })])
+        .build()
+        .unwrap()
+}
+
+fn legacy_funds(test_ledger: &TestLedger, old_address: &OldAddress) -> Vec<Value> {
+    test_ledger
+        .ledger
+        .oldutxos
+        .iter()
+        .filter(|entry| &entry.output.address == old_address)
+        .map(|entry| entry.output.value)
+        .collect()
+}
+
+#[test]
+pub fn migration_moves_all_the_legacy_funds() {
+    let key = LegacyKey::generate();
+    let other = LegacyKey::generate();
+    let mut test_ledger = ledger_with_legacy_funds(vec![
+        (key.address(), Value(100)),
+        (other.address(), Value(10)),
+        (key.address(), Value(50)),
+    ]);
+    let receiver = AddressData::account(Discrimination::Test);
+
+    let migration = Fragment::LegacyMigration(key.migrate(
+        &test_ledger.block0_hash,
+        key.address(),
+        receiver.address.clone(),
+    ));
+    test_ledger
+        .apply_fragment(&migration, test_ledger.date())
+        .unwrap();
+
+    assert!(legacy_funds(&test_ledger, &key.address()).is_empty());
+    assert_eq!(
+        legacy_funds(&test_ledger, &other.address()),
+        vec![Value(10)]
+    );
+    let account = test_ledger
+        .accounts()
+        .get_state(&receiver.to_id())
+        .unwrap()
+        .value();
+    assert_eq!(account, Value(147));
+    assert_eq!(test_ledger.ledger.pots.fees_value(), Value(3));
+
+    assert_eq!(
+        test_ledger.apply_fragment(&migration, test_ledger.date()),
+        Err(Error::LegacyMigrationNoFunds {
+            old_address: key.address()
+        })
+    );
+}
+
+#[test]
+pub fn migration_requires_the_legacy_key() {
+    let key = LegacyKey::generate();
+    let other = LegacyKey::generate();
+    let mut test_ledger = ledger_with_legacy_funds(vec![(key.address(), Value(100))]);
+    let block0_hash = test_ledger.block0_hash;
+    let destination = AddressData::utxo(Discrimination::Test).address;
+
+    let forged = other.migrate(&block0_hash, key.address(), destination.clone());
+    assert_eq!(
+        test_ledger.apply_fragment(&Fragment::LegacyMigration(forged), test_ledger.date()),
+        Err(Error::LegacyMigrationInvalidPublicKey {
+            old_address: key.address()
+        })
+    );
+
+    let mut redirected = key.migrate(&block0_hash, key.address(), destination);
+    redirected.destination = AddressData::utxo(Discrimination::Test).address;
+    assert_eq!(
+        test_ledger.apply_fragment(&Fragment::LegacyMigration(redirected), test_ledger.date()),
+        Err(Error::LegacyMigrationInvalidSignature {
+            old_address: key.address()
+        })
+    );
+    assert_eq!(legacy_funds(&test_ledger, &key.address()), vec![Value(100)]);
+}
+
+#[test]
+pub fn migration_is_rejected_in_block0() {
+    let key = LegacyKey::generate();
+    let migration = key.migrate(
+        &TestGen::hash(),
+        key.address(),
+        AddressData::utxo(Discrimination::Test).address,
+    );
+    assert_eq!(
+        LedgerBuilder::from_config(ConfigBuilder::new())
+            .fragments(&[Fragment::LegacyMigration(migration)])
+            .build()
+            .err(),
+        Some(Error::Block0(Block0Error::HasLegacyMigration))
+    );
+}
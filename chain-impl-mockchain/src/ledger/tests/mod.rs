@@ -2,7 +2,9 @@
 mod macros;
 pub mod apply_block_tests;
 pub mod certificate_tests;
+pub mod compat;
 pub mod discrimination_tests;
 pub mod initial_funds_tests;
 pub mod ledger_tests;
+pub mod legacy_migration_tests;
 pub mod transaction_tests;
@@ -1,16 +1,19 @@
 #![cfg(test)]
 
 use crate::{
-    accounting::account::LedgerError::NonExistent,
+    accounting::account::LedgerError::{NonExistent, ValueError as AccountValueError},
     date::BlockDate,
+    fee::LinearFee,
+    fragment::Fragment,
     ledger::{
         self,
         check::{TxValidityError, TxVerifyError},
         Error::{Account, InvalidTransactionValidity, TransactionMalformed},
+        WitnessFailure,
     },
     testing::{
         data::{AddressData, AddressDataValue},
-        ConfigBuilder, LedgerBuilder, TestTxBuilder,
+        ConfigBuilder, LedgerBuilder, TestGen, TestLedger, TestTxBuilder,
     },
     transaction::*,
     value::*,
@@ -26,10 +29,7 @@ pub fn transaction_fail_when_255_outputs() {
 
     // random output repeated 255 times.
     let receiver = AddressData::utxo(Discrimination::Test);
-    let output = Output {
-        address: receiver.address,
-        value: Value(1),
-    };
+    let output = Output::from_address(receiver.address, Value(1));
     let outputs: Vec<_> = std::iter::repeat(output).take(255).collect();
 
     let fragment = TestTxBuilder::new(test_ledger.block0_hash)
@@ -53,10 +53,7 @@ pub fn transaction_fail_when_validity_out_of_range() {
         .expect("cannot build test ledger");
 
     let receiver = AddressData::utxo(Discrimination::Test);
-    let output = Output {
-        address: receiver.address,
-        value: Value(1),
-    };
+    let output = Output::from_address(receiver.address, Value(1));
     let outputs = [output];
 
     let valid_until = Some(BlockDate {
@@ -92,10 +89,7 @@ pub fn transaction_fail_when_validity_too_far() {
     .expect("cannot build test ledger");
 
     let receiver = AddressData::utxo(Discrimination::Test);
-    let output = Output {
-        address: receiver.address,
-        value: Value(1),
-    };
+    let output = Output::from_address(receiver.address, Value(1));
 
     let valid_until = BlockDate {
         epoch: MAX_EXPIRY_EPOCHS as u32 + 1,
@@ -131,7 +125,11 @@ pub fn duplicated_account_transaction() {
         Err(err) => panic!("first transaction should be succesful but {}", err),
         Ok(_) => {
             assert_err_match!(
-                ledger::Error::Account(crate::account::LedgerError::SpendingCredentialInvalid),
+                ledger::Error::InputWitnessInvalid {
+                    index: 0,
+                    failure: WitnessFailure::SpendingCounterMismatch { .. },
+                    ..
+                },
                 test_ledger.apply_transaction(fragment2, BlockDate::first())
             );
         }
@@ -172,11 +170,16 @@ pub fn transaction_with_incorrect_account_spending_counter() {
     let fragment = TestTxBuilder::new(test_ledger.block0_hash)
         .move_from_faucet(&mut test_ledger, &receiver.into(), Value(1000))
         .get_fragment();
-    assert!(
-        test_ledger
-            .apply_transaction(fragment, BlockDate::first())
-            .is_err(),
-        "first transaction should be successful"
+    assert_err!(
+        ledger::Error::InputWitnessInvalid {
+            index: 0,
+            witness: WitnessKind::Account(1.into()),
+            failure: WitnessFailure::SpendingCounterMismatch {
+                expected: Some(0.into()),
+                provided: 1.into(),
+            },
+        },
+        test_ledger.apply_transaction(fragment, BlockDate::first())
     );
 }
 
@@ -204,3 +207,229 @@ pub fn repeated_account_transaction() {
         .apply_transaction(fragment, BlockDate::first())
         .is_err());
 }
+
+fn batch_ledger() -> (
+    TestLedger,
+    AddressDataValue,
+    AddressDataValue,
+    AddressDataValue,
+) {
+    let alice = AddressDataValue::account(Discrimination::Test, Value(100));
+    let bob = AddressDataValue::account(Discrimination::Test, Value(100));
+    let clarice = AddressDataValue::account(Discrimination::Test, Value(0));
+    let test_ledger =
+        LedgerBuilder::from_config(ConfigBuilder::new().with_fee(LinearFee::new(10, 1, 0)))
+            .faucets(&[alice.clone(), bob.clone()])
+            .build()
+            .expect("cannot build test ledger");
+    (test_ledger, alice, bob, clarice)
+}
+
+fn batch_transfer(
+    test_ledger: &mut TestLedger,
+    source: &AddressDataValue,
+    input: u64,
+    destination: &AddressDataValue,
+    output: u64,
+) -> Transaction<NoExtra> {
+    TestTxBuilder::new(test_ledger.block0_hash)
+        .move_funds_multiple(
+            test_ledger,
+            &[AddressDataValue::new(source.address_data(), Value(input))],
+            &[AddressDataValue::new(
+                destination.address_data(),
+                Value(output),
+            )],
+        )
+        .get_tx()
+}
+
+#[test]
+pub fn transaction_batch_pays_baseline_fee_once() {
+    let (mut test_ledger, alice, bob, clarice) = batch_ledger();
+
+    // each transaction pays 2 for its input and output, the batch pays the
+    // constant fee of 10 once: 200 = 2 * 93 + 14
+    let batch = TransactionBatch::new(vec![
+        batch_transfer(&mut test_ledger, &alice, 100, &clarice, 93),
+        batch_transfer(&mut test_ledger, &bob, 100, &clarice, 93),
+    ])
+    .unwrap();
+
+    test_ledger
+        .apply_fragment(&Fragment::TransactionBatch(batch), BlockDate::first())
+        .unwrap();
+    assert_eq!(test_ledger.pots().fees_value(), Value(14));
+}
+
+#[test]
+pub fn transaction_batch_is_atomic() {
+    let (mut test_ledger, alice, bob, clarice) = batch_ledger();
+    let total_funds = test_ledger.total_funds();
+
+    // the batch is balanced, but bob does not have enough funds
+    let batch = TransactionBatch::new(vec![
+        batch_transfer(&mut test_ledger, &alice, 100, &clarice, 93),
+        batch_transfer(&mut test_ledger, &bob, 2_000, &clarice, 1_993),
+    ])
+    .unwrap();
+
+    assert_err!(
+        Account(AccountValueError(ValueError::NegativeAmount)),
+        test_ledger.apply_fragment(&Fragment::TransactionBatch(batch), BlockDate::first())
+    );
+    assert_eq!(test_ledger.total_funds(), total_funds);
+    assert_eq!(test_ledger.pots().fees_value(), Value::zero());
+}
+
+#[test]
+pub fn transaction_deposit_to_account_then_withdraw_to_utxo() {
+    let mut utxo_owner = AddressDataValue::utxo(Discrimination::Test, Value(1000));
+    let mut account = AddressData::account(Discrimination::Test);
+    let receiver = AddressData::utxo(Discrimination::Test);
+
+    let mut test_ledger =
+        LedgerBuilder::from_config(ConfigBuilder::new().with_fee(LinearFee::new(1, 1, 0)))
+            .faucets(&[utxo_owner.clone()])
+            .build()
+            .expect("cannot build test ledger");
+
+    let utxo = test_ledger
+        .find_utxo_for_address(&utxo_owner.address_data)
+        .expect("no utxo for the owner");
+    let utxo = UtxoPointer::new(utxo.fragment_id, utxo.output_index, utxo.output.value);
+
+    let deposit =
+        InputOutputBuilder::deposit_to_account(&[utxo], account.address(), &test_ledger.fee())
+            .unwrap();
+    assert_eq!(deposit.outputs[0].value, Value(997));
+
+    let tx_builder = TxBuilder::new()
+        .set_nopayload()
+        .set_expiry_date(BlockDate::first().next_epoch())
+        .set_ios(&deposit.inputs, &deposit.outputs);
+    let witness = utxo_owner.address_data.make_witness(
+        &test_ledger.block0_hash,
+        tx_builder.get_auth_data_for_witness(),
+    );
+    let fragment =
+        Fragment::Transaction(tx_builder.set_witnesses(&[witness]).set_payload_auth(&()));
+    assert!(test_ledger
+        .apply_transaction(fragment, BlockDate::first())
+        .is_ok());
+
+    let withdrawal = InputOutputBuilder::withdraw_to_utxo(
+        account.to_id(),
+        Value(500),
+        receiver.address(),
+        &test_ledger.fee(),
+    )
+    .unwrap();
+    assert_eq!(withdrawal.inputs[0].value(), Value(503));
+
+    let tx_builder = TxBuilder::new()
+        .set_nopayload()
+        .set_expiry_date(BlockDate::first().next_epoch())
+        .set_ios(&withdrawal.inputs, &withdrawal.outputs);
+    let witness = account.make_witness(
+        &test_ledger.block0_hash,
+        tx_builder.get_auth_data_for_witness(),
+    );
+    let fragment =
+        Fragment::Transaction(tx_builder.set_witnesses(&[witness]).set_payload_auth(&()));
+    assert!(test_ledger
+        .apply_transaction(fragment, BlockDate::first())
+        .is_ok());
+
+    let account_state = test_ledger.accounts().get_state(&account.to_id()).unwrap();
+    assert_eq!(account_state.value, Value(494));
+    let received = test_ledger.find_utxo_for_address(&receiver).unwrap();
+    assert_eq!(received.output.value, Value(500));
+}
+
+#[test]
+pub fn transaction_conversions_reject_wrong_address_kinds() {
+    let fee = LinearFee::new(1, 1, 0);
+    let utxo = UtxoPointer::new(TestGen::hash(), 0, Value(100));
+    let utxo_address = AddressData::utxo(Discrimination::Test).address();
+    let account = AddressData::account(Discrimination::Test);
+
+    assert!(matches!(
+        InputOutputBuilder::deposit_to_account(&[utxo], utxo_address.clone(), &fee),
+        Err(Error::NotAnAccountAddress)
+    ));
+    assert!(matches!(
+        InputOutputBuilder::deposit_to_account(&[], account.address(), &fee),
+        Err(Error::TxInvalidNoInput)
+    ));
+    assert!(matches!(
+        InputOutputBuilder::deposit_to_account(
+            &[UtxoPointer::new(TestGen::hash(), 0, Value(3))],
+            account.address(),
+            &fee
+        ),
+        Err(Error::TxZeroValueOutput)
+    ));
+    assert!(matches!(
+        InputOutputBuilder::deposit_to_account(
+            &[UtxoPointer::new(TestGen::hash(), 0, Value(2))],
+            account.address(),
+            &fee
+        ),
+        Err(Error::TxNotEnoughTotalInput)
+    ));
+    assert!(matches!(
+        InputOutputBuilder::withdraw_to_utxo(account.to_id(), Value(10), account.address(), &fee),
+        Err(Error::NotAUtxoAddress)
+    ));
+    assert!(matches!(
+        InputOutputBuilder::withdraw_to_utxo(account.to_id(), Value::zero(), utxo_address, &fee),
+        Err(Error::TxZeroValueOutput)
+    ));
+}
+
+#[test]
+pub fn transaction_with_a_witness_of_the_wrong_key_reports_the_input() {
+    let mut alice = AddressDataValue::account(Discrimination::Test, Value(100));
+    let bob = AddressDataValue::account(Discrimination::Test, Value(100));
+    let mut mallory = AddressDataValue::account(Discrimination::Test, Value(0));
+    let receiver = AddressData::utxo(Discrimination::Test);
+
+    let mut test_ledger = LedgerBuilder::from_config(ConfigBuilder::new())
+        .faucets(&[alice.clone(), bob.clone()])
+        .build()
+        .expect("cannot build test ledger");
+
+    let inputs = [
+        Input::from_account_single(alice.to_id(), Value(100)),
+        Input::from_account_single(bob.to_id(), Value(100)),
+    ];
+    let outputs = [Output::from_address(receiver.address(), Value(200))];
+    let tx_builder = TxBuilder::new()
+        .set_nopayload()
+        .set_expiry_date(BlockDate::first().next_epoch())
+        .set_ios(&inputs, &outputs);
+    let witnesses = [
+        alice.make_witness(
+            &test_ledger.block0_hash,
+            tx_builder.get_auth_data_for_witness(),
+        ),
+        mallory.make_witness(
+            &test_ledger.block0_hash,
+            tx_builder.get_auth_data_for_witness(),
+        ),
+    ];
+    let fragment =
+        Fragment::Transaction(tx_builder.set_witnesses(&witnesses).set_payload_auth(&()));
+
+    assert_err!(
+        ledger::Error::InputWitnessInvalid {
+            index: 1,
+            witness: WitnessKind::Account(0.into()),
+            failure: WitnessFailure::InvalidSignature {
+                key: crate::key::Hash::hash_bytes(bob.public_key().as_ref()),
+            },
+        },
+        test_ledger.apply_transaction(fragment, BlockDate::first())
+    );
+}
@@ -0,0 +1,63 @@
+//! `tracing` instrumentation of the ledger application.
+//!
+//! Only compiled with the `tracing` feature, so the application code
+//! stays free of any telemetry overhead by default.
+
+use super::{Error, Ledger};
+use crate::chaineval::HeaderContentEvalContext;
+use crate::fragment::{BlockContentSize, Fragment};
+use tracing::{span::EnteredSpan, Level};
+
+/// Enter the span covering the application of a whole block.
+pub(super) fn block_span(
+    metadata: &HeaderContentEvalContext,
+    content_size: BlockContentSize,
+) -> EnteredSpan {
+    tracing::span!(
+        Level::INFO,
+        "apply_block",
+        chain_length = %metadata.chain_length,
+        block_date = %metadata.block_date,
+        content_hash = %metadata.content_hash,
+        content_size,
+    )
+    .entered()
+}
+
+/// Report the outcome of the application of a single fragment.
+///
+/// The fee is recovered from the fee pot, which the ledger credits with
+/// exactly the fee of the fragment on success.
+pub(super) fn fragment_applied(
+    before: &Ledger,
+    fragment: &Fragment,
+    result: &Result<Ledger, Error>,
+) {
+    let fragment_id = fragment.hash();
+    let fragment_type = fragment.get_tag();
+    match result {
+        Ok(after) => {
+            let fee = after
+                .pots
+                .fees_value()
+                .checked_sub(before.pots.fees_value())
+                .unwrap_or_default();
+            tracing::event!(
+                Level::DEBUG,
+                %fragment_id,
+                ?fragment_type,
+                %fee,
+                outcome = "applied",
+            );
+        }
+        Err(error) => {
+            tracing::event!(
+                Level::DEBUG,
+                %fragment_id,
+                ?fragment_type,
+                outcome = "rejected",
+                %error,
+            );
+        }
+    }
+}
@@ -0,0 +1,242 @@
+//! Verification of a block with a selected set of checks.
+//!
+//! The consumers of the blocks do not all need the same guarantees:
+//!
+//! * a node receiving a block from the network runs every check, see
+//!   [`VerificationChecks::FULL`];
+//! * an explorer following a trusted node only checks the structure of the
+//!   blocks, see [`VerificationChecks::LIGHT`];
+//! * a block producer checking the block it just forged doesn't need to
+//!   verify its own leadership proof, see [`VerificationChecks::PRODUCER`].
+//!
+//! [`Ledger::verify_block`] runs the checks of the profile in a fixed order,
+//! so the consumers don't have to maintain their own sequence of calls.
+
+use super::ledger::{Error, Ledger};
+use crate::block::Block;
+use crate::date::Epoch;
+use crate::leadership::{self, Leadership};
+use std::ops::{BitAnd, BitOr};
+use thiserror::Error;
+
+/// Set of checks run on a block, combined with `|`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerificationChecks(u32);
+
+impl VerificationChecks {
+    /// the block follows the ledger (chain length and date), and its
+    /// contents match the hash declared by the header, within the maximum
+    /// size of the contents
+    pub const STRUCTURE: Self = VerificationChecks(1 << 0);
+    /// the header is produced by a leader of its slot, signatures and VRF
+    /// proof included
+    pub const LEADERSHIP: Self = VerificationChecks(1 << 1);
+    /// the fragments apply on the ledger, witnesses and balances included
+    pub const LEDGER: Self = VerificationChecks(1 << 2);
+
+    pub const FULL: Self =
+        VerificationChecks(Self::STRUCTURE.0 | Self::LEADERSHIP.0 | Self::LEDGER.0);
+    pub const LIGHT: Self = Self::STRUCTURE;
+    pub const PRODUCER: Self = VerificationChecks(Self::STRUCTURE.0 | Self::LEDGER.0);
+
+    pub const fn empty() -> Self {
+        VerificationChecks(0)
+    }
+
+    pub const fn contains(self, checks: Self) -> bool {
+        self.0 & checks.0 == checks.0
+    }
+
+    pub const fn to_bits(self) -> u32 {
+        self.0
+    }
+
+    /// `None` if a bit does not stand for a known check
+    pub const fn from_bits(bits: u32) -> Option<Self> {
+        if bits & !Self::FULL.0 == 0 {
+            Some(VerificationChecks(bits))
+        } else {
+            None
+        }
+    }
+}
+
+impl BitOr for VerificationChecks {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        VerificationChecks(self.0 | rhs.0)
+    }
+}
+
+impl BitAnd for VerificationChecks {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        VerificationChecks(self.0 & rhs.0)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum BlockVerificationError {
+    #[error("Invalid block structure")]
+    Structure(#[source] Error),
+    #[error("No leadership to verify the block header with")]
+    MissingLeadership,
+    #[error("The leadership is the one of epoch {leadership}, the block is in epoch {block}")]
+    LeadershipEpochMismatch { leadership: Epoch, block: Epoch },
+    #[error("Invalid leadership of the block header")]
+    Leadership(#[source] leadership::Error),
+    #[error("The block does not apply on the ledger")]
+    Ledger(#[source] Error),
+}
+
+impl Ledger {
+    /// Run the `checks` on the block, the block being the next one of the
+    /// ledger. The leadership of the epoch of the block is only needed for
+    /// [`VerificationChecks::LEADERSHIP`].
+    ///
+    /// Returns the ledger with the block applied when the checks include
+    /// [`VerificationChecks::LEDGER`], `None` otherwise.
+    pub fn verify_block(
+        &self,
+        block: &Block,
+        leadership: Option<&Leadership>,
+        checks: VerificationChecks,
+    ) -> Result<Option<Ledger>, BlockVerificationError> {
+        let header = block.header();
+
+        if checks.contains(VerificationChecks::STRUCTURE) {
+            let expected = self.chain_length.increase();
+            if header.chain_length() != expected {
+                return Err(BlockVerificationError::Structure(Error::WrongChainLength {
+                    actual: header.chain_length(),
+                    expected,
+                }));
+            }
+            if header.block_date() <= self.date {
+                return Err(BlockVerificationError::Structure(Error::NonMonotonicDate {
+                    block_date: header.block_date(),
+                    chain_date: self.date,
+                }));
+            }
+            let (actual, size) = block
+                .contents()
+                .compute_hash_size_with(header.block_version().content_hash_scheme());
+            let max = self.settings.block_content_max_size;
+            if size > max {
+                return Err(BlockVerificationError::Structure(
+                    Error::InvalidContentSize { actual: size, max },
+                ));
+            }
+            if actual != header.block_content_hash() {
+                return Err(BlockVerificationError::Structure(
+                    Error::InvalidContentHash {
+                        actual,
+                        expected: header.block_content_hash(),
+                    },
+                ));
+            }
+        }
+
+        if checks.contains(VerificationChecks::LEADERSHIP) {
+            let leadership = leadership.ok_or(BlockVerificationError::MissingLeadership)?;
+            if leadership.epoch() != header.block_date().epoch {
+                return Err(BlockVerificationError::LeadershipEpochMismatch {
+                    leadership: leadership.epoch(),
+                    block: header.block_date().epoch,
+                });
+            }
+            leadership
+                .verify(header)
+                .into_error()
+                .map_err(BlockVerificationError::Leadership)?;
+        }
+
+        if !checks.contains(VerificationChecks::LEDGER) {
+            return Ok(None);
+        }
+        let ledger_params = match leadership {
+            Some(leadership) => leadership.ledger_parameters().clone(),
+            None => self.get_ledger_parameters(),
+        };
+        self.apply_block(
+            ledger_params,
+            block.contents(),
+            &header.get_content_eval_context(),
+        )
+        .map(Some)
+        .map_err(BlockVerificationError::Ledger)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chaintypes::ChainLength;
+    use crate::testing::{ConfigBuilder, LedgerBuilder, TestGen};
+
+    #[test]
+    fn checks_bits_roundtrip() {
+        assert!(VerificationChecks::FULL.contains(VerificationChecks::PRODUCER));
+        assert!(!VerificationChecks::LIGHT.contains(VerificationChecks::LEDGER));
+        assert_eq!(
+            VerificationChecks::LIGHT | VerificationChecks::LEADERSHIP | VerificationChecks::LEDGER,
+            VerificationChecks::FULL
+        );
+        assert_eq!(
+            VerificationChecks::from_bits(VerificationChecks::PRODUCER.to_bits()),
+            Some(VerificationChecks::PRODUCER)
+        );
+        assert_eq!(VerificationChecks::from_bits(1 << 3), None);
+    }
+
+    #[test]
+    fn profiles_run_their_checks() {
+        let leader_pair = TestGen::leader_pair();
+        let test_ledger =
+            LedgerBuilder::from_config(ConfigBuilder::new().with_leaders(&[leader_pair.id()]))
+                .build()
+                .unwrap();
+        let ledger = &test_ledger.ledger;
+        let leadership = Leadership::new(ledger.date().epoch, ledger);
+
+        let block =
+            test_ledger.forge_bft_block_with_fragments(&leader_pair, ledger.date(), Vec::new());
+        let verified = ledger
+            .verify_block(&block, Some(&leadership), VerificationChecks::FULL)
+            .unwrap()
+            .unwrap();
+        assert_eq!(verified.chain_length(), ChainLength(1));
+
+        // a block of a leader unknown to the ledger
+        let intruder = TestGen::leader_pair();
+        let block =
+            test_ledger.forge_bft_block_with_fragments(&intruder, ledger.date(), Vec::new());
+        assert!(matches!(
+            ledger.verify_block(&block, None, VerificationChecks::LIGHT),
+            Ok(None)
+        ));
+        assert!(matches!(
+            ledger.verify_block(&block, None, VerificationChecks::PRODUCER),
+            Ok(Some(_))
+        ));
+        assert!(matches!(
+            ledger.verify_block(&block, None, VerificationChecks::FULL),
+            Err(BlockVerificationError::MissingLeadership)
+        ));
+        assert!(matches!(
+            ledger.verify_block(&block, Some(&leadership), VerificationChecks::FULL),
+            Err(BlockVerificationError::Leadership(_))
+        ));
+
+        // a block which doesn't follow the ledger
+        let verified = verified.verify_block(&block, None, VerificationChecks::LIGHT);
+        assert!(matches!(
+            verified,
+            Err(BlockVerificationError::Structure(
+                Error::WrongChainLength { .. }
+            ))
+        ));
+    }
+}
@@ -0,0 +1,284 @@
+//! Read-only views of the ledger.
+//!
+//! The ledger is a persistent structure: applying a block or a fragment
+//! never modifies a ledger, it returns a new one sharing most of its state
+//! with the previous one. A view of the ledger is therefore consistent by
+//! construction:
+//!
+//! * a view always answers for the state of a single ledger, between two
+//!   applications of blocks, never for a block partially applied;
+//! * the answers of a view never change, whatever is applied by the writer
+//!   after the view was taken;
+//! * a view does not block the writer, the writer only has to publish the
+//!   new ledger (for example by swapping the [`LedgerSnapshot`] handed out to
+//!   the readers) once a block is applied.
+//!
+//! [`LedgerSnapshot`] is the view meant to be shared between threads, its
+//! clone is a reference count increment.
+
+use super::account_history::AccountHistory;
+use super::governance_quota::GovernanceQuota;
+use super::info::LedgerStats;
+use super::ledger::{Error, Ledger, LedgerParameters, LedgerStaticParameters, ProducerRecord};
+use super::preview::FragmentPreview;
+use super::randomness::EpochRandomness;
+use super::settings_history::SettingsHistory;
+use super::token_distribution::{TokenDistribution, TokenTotals};
+use crate::certificate::PoolId;
+use crate::chaintypes::{ChainLength, ConsensusType};
+use crate::date::{BlockDate, Epoch};
+use crate::fee::FeeSchedule;
+use crate::fragment::{Fragment, FragmentId};
+use crate::header::{BlockProducer, Header};
+use crate::names::{Name, NameRegistry};
+use crate::stake::{PoolPerformance, PoolsState, StakeDistribution};
+use crate::transaction::{Output, TransactionIndex};
+use crate::value::Value;
+use crate::vote::{CommitteeId, VotePlanStatus};
+use crate::{account, setting, utxo};
+use chain_addr::Address;
+use chain_time::TimeEra;
+use std::sync::Arc;
+
+/// The read-only queries of the ledger, see the module documentation for the
+/// consistency of the answers.
+///
+/// Only [`as_ledger`](Self::as_ledger) has to be implemented, every query
+/// answers as the ledger method of the same name.
+pub trait LedgerView {
+    /// the ledger state the view answers for
+    fn as_ledger(&self) -> &Ledger;
+
+    fn date(&self) -> BlockDate {
+        self.as_ledger().date()
+    }
+
+    fn chain_length(&self) -> ChainLength {
+        self.as_ledger().chain_length()
+    }
+
+    fn era(&self) -> &TimeEra {
+        self.as_ledger().era()
+    }
+
+    fn settings(&self) -> &setting::Settings {
+        self.as_ledger().settings()
+    }
+
+    fn settings_at(&self, epoch: Epoch) -> Option<&setting::Settings> {
+        self.as_ledger().settings_at(epoch)
+    }
+
+    fn settings_history(&self) -> &SettingsHistory {
+        self.as_ledger().settings_history()
+    }
+
+    fn get_static_parameters(&self) -> &LedgerStaticParameters {
+        self.as_ledger().get_static_parameters()
+    }
+
+    fn get_ledger_parameters(&self) -> LedgerParameters {
+        self.as_ledger().get_ledger_parameters()
+    }
+
+    fn get_ledger_parameters_at(&self, epoch: Epoch) -> Option<LedgerParameters> {
+        self.as_ledger().get_ledger_parameters_at(epoch)
+    }
+
+    fn fee_schedule(&self) -> FeeSchedule {
+        self.as_ledger().fee_schedule()
+    }
+
+    fn consensus_version(&self) -> ConsensusType {
+        self.as_ledger().consensus_version()
+    }
+
+    fn accounts(&self) -> &account::Ledger {
+        self.as_ledger().accounts()
+    }
+
+    fn account_history(&self) -> Option<&AccountHistory> {
+        self.as_ledger().account_history()
+    }
+
+    fn utxos(&self) -> utxo::Iter<'_, Address> {
+        self.as_ledger().utxos()
+    }
+
+    fn utxo_out(
+        &self,
+        fragment_id: FragmentId,
+        index: TransactionIndex,
+    ) -> Option<&Output<Address>> {
+        self.as_ledger().utxo_out(fragment_id, index)
+    }
+
+    fn delegation(&self) -> &PoolsState {
+        self.as_ledger().delegation()
+    }
+
+    fn get_stake_distribution(&self) -> StakeDistribution {
+        self.as_ledger().get_stake_distribution()
+    }
+
+    fn pool_performance(&self, pool_id: &PoolId) -> Option<&PoolPerformance> {
+        self.as_ledger().pool_performance(pool_id)
+    }
+
+    fn pool_performance_at(&self, pool_id: &PoolId, epoch: Epoch) -> Option<&PoolPerformance> {
+        self.as_ledger().pool_performance_at(pool_id, epoch)
+    }
+
+    fn resolve_producer(&self, producer: &BlockProducer) -> Option<ProducerRecord<'_>> {
+        self.as_ledger().resolve_producer(producer)
+    }
+
+    fn block_producer(&self, header: &Header) -> Option<ProducerRecord<'_>> {
+        self.as_ledger().block_producer(header)
+    }
+
+    fn token_totals(&self) -> &TokenTotals {
+        self.as_ledger().token_totals()
+    }
+
+    fn token_distribution(&self) -> TokenDistribution<()> {
+        self.as_ledger().token_distribution()
+    }
+
+    fn names(&self) -> &NameRegistry {
+        self.as_ledger().names()
+    }
+
+    fn resolve_name(&self, name: &Name) -> Option<&account::Identifier> {
+        self.as_ledger().resolve_name(name)
+    }
+
+    fn active_vote_plans(&self) -> Vec<VotePlanStatus> {
+        self.as_ledger().active_vote_plans()
+    }
+
+    fn governance_quota(&self, member: &CommitteeId) -> GovernanceQuota {
+        self.as_ledger().governance_quota(member)
+    }
+
+    fn epoch_randomness(&self, epoch: Epoch) -> Option<EpochRandomness> {
+        self.as_ledger().epoch_randomness(epoch)
+    }
+
+    fn remaining_rewards(&self) -> Value {
+        self.as_ledger().remaining_rewards()
+    }
+
+    fn treasury_value(&self) -> Value {
+        self.as_ledger().treasury_value()
+    }
+
+    fn get_total_value(&self) -> Result<Value, Error> {
+        self.as_ledger().get_total_value()
+    }
+
+    fn preview_fragment(&self, fragment: &Fragment) -> Result<FragmentPreview, Error> {
+        self.as_ledger().preview_fragment(fragment)
+    }
+
+    fn stats(&self) -> LedgerStats {
+        self.as_ledger().stats()
+    }
+}
+
+impl LedgerView for Ledger {
+    fn as_ledger(&self) -> &Ledger {
+        self
+    }
+}
+
+/// A view of the ledger which can be shared between threads and cloned for
+/// the cost of a reference count
+#[derive(Debug, Clone)]
+pub struct LedgerSnapshot(Arc<Ledger>);
+
+impl LedgerSnapshot {
+    pub fn new(ledger: Ledger) -> Self {
+        LedgerSnapshot(Arc::new(ledger))
+    }
+
+    /// Whether the two snapshots are views of the same ledger, without
+    /// comparing the states
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl From<Ledger> for LedgerSnapshot {
+    fn from(ledger: Ledger) -> Self {
+        LedgerSnapshot::new(ledger)
+    }
+}
+
+impl LedgerView for LedgerSnapshot {
+    fn as_ledger(&self) -> &Ledger {
+        &self.0
+    }
+}
+
+impl Ledger {
+    /// Take a snapshot of the ledger to hand out to the readers, see
+    /// [`LedgerSnapshot`]
+    pub fn snapshot(&self) -> LedgerSnapshot {
+        LedgerSnapshot::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::scenario::{prepare_scenario, wallet};
+    use std::sync::RwLock;
+    use std::thread;
+
+    #[test]
+    fn snapshots_are_stable_while_blocks_are_applied() {
+        let (mut ledger, controller) = prepare_scenario()
+            .with_initials(vec![wallet("Alice").with(1_000).owns("stake_pool")])
+            .build()
+            .unwrap();
+        let stake_pool = controller.stake_pool("stake_pool").unwrap();
+
+        let first = ledger.ledger.snapshot();
+        let published = Arc::new(RwLock::new(first.clone()));
+        const BLOCKS: u32 = 20;
+
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let published = Arc::clone(&published);
+                thread::spawn(move || {
+                    let mut last_length = 0;
+                    while last_length < BLOCKS {
+                        let view = published.read().unwrap().clone();
+                        let state = view.as_ledger().clone();
+                        let length = view.chain_length().0;
+                        assert!(length >= last_length);
+                        thread::yield_now();
+                        assert_eq!(view.chain_length().0, length);
+                        assert_eq!(view.as_ledger(), &state);
+                        last_length = length;
+                    }
+                })
+            })
+            .collect();
+
+        for _ in 0..BLOCKS {
+            ledger.apply_empty_praos_block(&stake_pool).unwrap();
+            *published.write().unwrap() = ledger.ledger.snapshot();
+        }
+        for reader in readers {
+            reader.join().unwrap();
+        }
+
+        assert_eq!(first.chain_length(), ChainLength(0));
+        assert_eq!(first.as_ledger().leaders_log.total(), 0);
+        let last = published.read().unwrap().clone();
+        assert_eq!(last.chain_length(), ChainLength(BLOCKS));
+        assert!(!last.ptr_eq(&first));
+    }
+}
@@ -0,0 +1,213 @@
+//! Diagnosis of the witnesses of the transaction inputs rejected by the
+//! ledger.
+//!
+//! The inputs of a transaction are applied in order, and the first input
+//! which cannot be spent with its witness makes the whole transaction fail.
+//! [`Error::InputWitnessInvalid`] reports the index of this input, the kind
+//! of its witness and a [`WitnessFailure`] telling why it is rejected. The
+//! keys are reported by their hash, which is enough for a wallet to tell
+//! which of its keys is expected.
+//!
+//! The errors of an input which are not about its witness, like a missing
+//! utxo or an account without enough funds, are reported as is.
+
+use super::ledger::Error;
+use crate::account::{self, SpendingCounter};
+use crate::chaintypes::HeaderId;
+use crate::key::Hash;
+use crate::multisig;
+use crate::transaction::{
+    TransactionSignDataHash, Witness, WitnessKind, WitnessUtxoData, WitnessUtxoVersion,
+};
+use chain_crypto::{Ed25519, PublicKey, Verification};
+use std::fmt;
+
+/// Why the witness of an input cannot spend it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WitnessFailure {
+    /// a utxo witness for an account input, or an account witness for a
+    /// utxo input
+    UnexpectedKind,
+    /// the spending counter of the witness is not the next counter of its
+    /// lane, `expected` is unknown for a lane the account does not have
+    SpendingCounterMismatch {
+        expected: Option<SpendingCounter>,
+        provided: SpendingCounter,
+    },
+    /// the public key of the legacy witness is not the key of the legacy
+    /// address of the spent output
+    PublicKeyMismatch { provided: Hash },
+    /// the signature is valid, but signs the transaction as another kind of
+    /// witness
+    WrongDomain { signed_as: WitnessKind },
+    /// the signature does not verify with the key of the spent input
+    InvalidSignature { key: Hash },
+    /// the signatures do not meet the declaration of the multisig account
+    DeclarationNotSatisfied,
+}
+
+/// What the diagnosis needs to know about the transaction and the ledger
+pub(super) struct WitnessContext<'a> {
+    pub block0_hash: &'a HeaderId,
+    pub sign_data_hash: &'a TransactionSignDataHash,
+    /// the ledger of the accounts before the input is applied
+    pub accounts: &'a account::Ledger,
+}
+
+impl From<&Witness> for WitnessKind {
+    fn from(witness: &Witness) -> Self {
+        match witness {
+            Witness::OldUtxo(..) => WitnessKind::OldUtxo,
+            Witness::Utxo(_) => WitnessKind::Utxo,
+            Witness::Account(counter, _) => WitnessKind::Account(*counter),
+            Witness::Multisig(counter, _) => WitnessKind::Multisig(*counter),
+        }
+    }
+}
+
+fn key_hash(key: &PublicKey<Ed25519>) -> Hash {
+    Hash::hash_bytes(key.as_ref())
+}
+
+fn utxo_data(context: &WitnessContext, version: WitnessUtxoVersion) -> WitnessUtxoData {
+    WitnessUtxoData::new(context.block0_hash, context.sign_data_hash, version)
+}
+
+/// the failure of a witness signing the transaction as a utxo witness of the
+/// given version, with the key expected to sign it
+fn signed_as_utxo(
+    key: &PublicKey<Ed25519>,
+    signature: &[u8],
+    context: &WitnessContext,
+    version: WitnessUtxoVersion,
+) -> WitnessFailure {
+    let verified = chain_crypto::Signature::<WitnessUtxoData, Ed25519>::from_binary(signature)
+        .map(|signature| signature.verify(key, &utxo_data(context, version)));
+    match (verified, version) {
+        (Ok(Verification::Success), WitnessUtxoVersion::Legacy) => WitnessFailure::WrongDomain {
+            signed_as: WitnessKind::OldUtxo,
+        },
+        (Ok(Verification::Success), WitnessUtxoVersion::Normal) => WitnessFailure::WrongDomain {
+            signed_as: WitnessKind::Utxo,
+        },
+        _ => WitnessFailure::InvalidSignature { key: key_hash(key) },
+    }
+}
+
+fn expected_counter(
+    accounts: &account::Ledger,
+    account: &account::Identifier,
+    provided: SpendingCounter,
+) -> Option<SpendingCounter> {
+    let state = accounts.get_state(account).ok()?;
+    state
+        .spending
+        .get_valid_counters()
+        .get(provided.lane())
+        .copied()
+}
+
+/// Report the error of the input at `index` with the failure of its
+/// witness, the errors which are not about the witness are returned as is
+pub(super) fn diagnose(
+    index: usize,
+    witness: &Witness,
+    input_account: Option<&account::Identifier>,
+    context: &WitnessContext,
+    error: Error,
+) -> Error {
+    let spending_counter_mismatch = |provided| WitnessFailure::SpendingCounterMismatch {
+        expected: input_account
+            .and_then(|account| expected_counter(context.accounts, account, provided)),
+        provided,
+    };
+    let failure = match (&error, witness) {
+        (Error::ExpectingUtxoWitness | Error::ExpectingAccountWitness, _) => {
+            WitnessFailure::UnexpectedKind
+        }
+        (Error::OldUtxoInvalidPublicKey { .. }, Witness::OldUtxo(key, _, _)) => {
+            WitnessFailure::PublicKeyMismatch {
+                provided: key_hash(key),
+            }
+        }
+        (Error::OldUtxoInvalidSignature { .. }, Witness::OldUtxo(key, _, signature)) => {
+            signed_as_utxo(key, signature.as_ref(), context, WitnessUtxoVersion::Normal)
+        }
+        (Error::UtxoInvalidSignature { output, .. }, Witness::Utxo(signature)) => {
+            match output.address.public_key() {
+                Some(key) => {
+                    signed_as_utxo(key, signature.as_ref(), context, WitnessUtxoVersion::Legacy)
+                }
+                None => return error,
+            }
+        }
+        (Error::AccountInvalidSignature { account, .. }, Witness::Account(_, signature)) => {
+            signed_as_utxo(
+                account.as_ref(),
+                signature.as_ref(),
+                context,
+                WitnessUtxoVersion::Normal,
+            )
+        }
+        (Error::MultisigInvalidSignature { .. }, _) => WitnessFailure::DeclarationNotSatisfied,
+        (
+            Error::Account(account::LedgerError::SpendingCredentialInvalid),
+            Witness::Account(provided, _),
+        )
+        | (
+            Error::Multisig(multisig::LedgerError::AccountError(
+                account::LedgerError::SpendingCredentialInvalid,
+            )),
+            Witness::Multisig(provided, _),
+        ) => spending_counter_mismatch(*provided),
+        _ => return error,
+    };
+    Error::InputWitnessInvalid {
+        index,
+        witness: WitnessKind::from(witness),
+        failure,
+    }
+}
+
+impl fmt::Display for WitnessFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WitnessFailure::UnexpectedKind => {
+                write!(f, "the witness cannot spend this kind of input")
+            }
+            WitnessFailure::SpendingCounterMismatch {
+                expected: Some(expected),
+                provided,
+            } => write!(
+                f,
+                "spending counter {} of lane {} provided, {} expected",
+                provided.unlaned_counter(),
+                provided.lane(),
+                expected.unlaned_counter()
+            ),
+            WitnessFailure::SpendingCounterMismatch {
+                expected: None,
+                provided,
+            } => write!(
+                f,
+                "spending counter {} of the unknown lane {} provided",
+                provided.unlaned_counter(),
+                provided.lane()
+            ),
+            WitnessFailure::PublicKeyMismatch { provided } => write!(
+                f,
+                "the public key {} does not match the legacy address",
+                provided
+            ),
+            WitnessFailure::WrongDomain { signed_as } => {
+                write!(f, "the transaction is signed as a {:?} witness", signed_as)
+            }
+            WitnessFailure::InvalidSignature { key } => {
+                write!(f, "the signature does not verify with the key {}", key)
+            }
+            WitnessFailure::DeclarationNotSatisfied => {
+                write!(f, "the signatures do not meet the multisig declaration")
+            }
+        }
+    }
+}
@@ -1,11 +1,17 @@
+use crate::chaintypes::HeaderId;
+use crate::date::BlockDate;
+use crate::key::{
+    deserialize_public_key, deserialize_signature, serialize_public_key, serialize_signature,
+};
 use crate::value::Value;
 
 pub use cardano_legacy_address::Addr as OldAddress;
 pub use cardano_legacy_address::AddressMatchXPub as OldAddressMatchXPub;
 
+use chain_addr::Address;
 use chain_core::mempack::{ReadBuf, ReadError, Readable};
 use chain_core::property;
-use chain_crypto::{Ed25519, PublicKey};
+use chain_crypto::{Ed25519, PublicKey, Signature};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct UtxoDeclaration {
@@ -26,7 +32,7 @@ impl Readable for UtxoDeclaration {
     fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
         let nb_entries = buf.get_u8()? as usize;
         if nb_entries >= 0xff {
-            return Err(ReadError::StructureInvalid("nb entries".to_string()));
+            return Err(ReadError::StructureInvalid("nb entries".into()));
         }
 
         let mut addrs = Vec::with_capacity(nb_entries);
@@ -34,7 +40,7 @@ impl Readable for UtxoDeclaration {
             let value = Value::read(buf)?;
             let addr_size = buf.get_u16()? as usize;
             let addr = OldAddress::try_from(buf.get_slice(addr_size)?)
-                .map_err(|err| ReadError::StructureInvalid(format!("{}", err)))?;
+                .map_err(|err| ReadError::StructureInvalid(err.to_string().into()))?;
             addrs.push((addr, value))
         }
 
@@ -62,10 +68,154 @@ impl property::Serialize for UtxoDeclaration {
     }
 }
 
+/// Moves all the funds of a legacy address to a modern address in one
+/// operation, the control of the legacy address being proven by a legacy
+/// signature over a [`MigrationChallenge`] instead of the legacy witnesses
+/// of every legacy output spent.
+///
+/// The fee is the one of a transaction with one input and one output, and
+/// is taken from the migrated funds.
+#[derive(Debug, Clone)]
+pub struct MigrationCertificate {
+    pub old_address: OldAddress,
+    pub destination: Address,
+    pub valid_until: BlockDate,
+    pub public_key: PublicKey<Ed25519>,
+    pub chain_code: [u8; 32],
+    pub signature: Signature<MigrationChallenge, Ed25519>,
+}
+
+/// The data signed by the legacy key in a [`MigrationCertificate`], binding
+/// the migration to the chain, the destination and the validity
+pub struct MigrationChallenge(Vec<u8>);
+
+pub(crate) const MIGRATION_CHALLENGE_DOMAIN: &[u8] = b"legacy-migration";
+
+impl MigrationChallenge {
+    pub fn new(
+        block0: &HeaderId,
+        old_address: &OldAddress,
+        destination: &Address,
+        valid_until: BlockDate,
+    ) -> Self {
+        let mut data = MIGRATION_CHALLENGE_DOMAIN.to_vec();
+        data.extend_from_slice(block0.as_ref());
+        data.extend_from_slice(old_address.as_ref());
+        data.extend_from_slice(&destination.to_bytes());
+        data.extend_from_slice(&valid_until.epoch.to_be_bytes());
+        data.extend_from_slice(&valid_until.slot_id.to_be_bytes());
+        MigrationChallenge(data)
+    }
+}
+
+impl AsRef<[u8]> for MigrationChallenge {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+}
+
+impl MigrationCertificate {
+    /// Create the certificate, `sign` signing the challenge with the legacy
+    /// key and returning its public key with the signature
+    pub fn new<F>(
+        block0: &HeaderId,
+        old_address: OldAddress,
+        destination: Address,
+        valid_until: BlockDate,
+        chain_code: [u8; 32],
+        sign: F,
+    ) -> Self
+    where
+        F: FnOnce(
+            &MigrationChallenge,
+        ) -> (PublicKey<Ed25519>, Signature<MigrationChallenge, Ed25519>),
+    {
+        let challenge = MigrationChallenge::new(block0, &old_address, &destination, valid_until);
+        let (public_key, signature) = sign(&challenge);
+        MigrationCertificate {
+            old_address,
+            destination,
+            valid_until,
+            public_key,
+            chain_code,
+            signature,
+        }
+    }
+
+    pub fn challenge(&self, block0: &HeaderId) -> MigrationChallenge {
+        MigrationChallenge::new(
+            block0,
+            &self.old_address,
+            &self.destination,
+            self.valid_until,
+        )
+    }
+
+    /// Whether the key of the certificate is the one of the legacy address
+    pub fn matches_old_address(&self) -> bool {
+        oldaddress_from_xpub(&self.old_address, &self.public_key, &self.chain_code)
+            != OldAddressMatchXPub::No
+    }
+}
+
+impl PartialEq for MigrationCertificate {
+    fn eq(&self, other: &Self) -> bool {
+        self.old_address == other.old_address
+            && self.destination == other.destination
+            && self.valid_until == other.valid_until
+            && self.public_key == other.public_key
+            && self.chain_code == other.chain_code
+            && self.signature.as_ref() == other.signature.as_ref()
+    }
+}
+impl Eq for MigrationCertificate {}
+
+impl Readable for MigrationCertificate {
+    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
+        let addr_size = buf.get_u16()? as usize;
+        let old_address = OldAddress::try_from(buf.get_slice(addr_size)?)
+            .map_err(|err| ReadError::StructureInvalid(err.to_string().into()))?;
+        let destination = Address::read(buf)?;
+        let epoch = buf.get_u32()?;
+        let slot_id = buf.get_u32()?;
+        let public_key = deserialize_public_key(buf)?;
+        let chain_code = <[u8; 32]>::read(buf)?;
+        let signature = deserialize_signature(buf)?;
+        Ok(MigrationCertificate {
+            old_address,
+            destination,
+            valid_until: BlockDate { epoch, slot_id },
+            public_key,
+            chain_code,
+            signature,
+        })
+    }
+}
+
+impl property::Serialize for MigrationCertificate {
+    type Error = std::io::Error;
+    fn serialize<W: std::io::Write>(&self, writer: W) -> Result<(), Self::Error> {
+        use chain_core::packer::*;
+        use std::io::Write;
+
+        let mut codec = Codec::new(writer);
+        let old_address = self.old_address.as_ref();
+        codec.put_be_u16(old_address.len() as u16)?;
+        codec.write_all(old_address)?;
+        codec.write_all(&self.destination.to_bytes())?;
+        codec.put_be_u32(self.valid_until.epoch)?;
+        codec.put_be_u32(self.valid_until.slot_id)?;
+        serialize_public_key(&self.public_key, &mut codec)?;
+        codec.write_all(&self.chain_code)?;
+        serialize_signature(&self.signature, &mut codec)
+    }
+}
+
 #[cfg(any(test, feature = "property-test-api"))]
 mod tests {
     use super::*;
     use cardano_legacy_address::ExtendedAddr;
+    use chain_crypto::{testing::arbitrary_secret_key, SecretKey};
     use ed25519_bip32::{XPub, XPUB_SIZE};
     use quickcheck::{Arbitrary, Gen};
 
@@ -77,23 +227,43 @@ mod tests {
             for _ in 0..nb {
                 let value = Arbitrary::arbitrary(g);
 
-                let xpub = {
-                    let mut buf = [0u8; XPUB_SIZE];
-                    for o in buf.iter_mut() {
-                        *o = u8::arbitrary(g)
-                    }
-                    match XPub::from_slice(&buf) {
-                        Ok(xpub) => xpub,
-                        Err(err) => panic!("xpub not built correctly, {:?}", err),
-                    }
-                };
-                let ea = ExtendedAddr::new_simple(&xpub, None);
-                let addr = ea.to_address();
-
-                addrs.push((addr, value))
+                addrs.push((arbitrary_old_address(g), value))
             }
 
             UtxoDeclaration { addrs }
         }
     }
+
+    fn arbitrary_old_address<G: Gen>(g: &mut G) -> OldAddress {
+        let xpub = {
+            let mut buf = [0u8; XPUB_SIZE];
+            for o in buf.iter_mut() {
+                *o = u8::arbitrary(g)
+            }
+            match XPub::from_slice(&buf) {
+                Ok(xpub) => xpub,
+                Err(err) => panic!("xpub not built correctly, {:?}", err),
+            }
+        };
+        let ea = ExtendedAddr::new_simple(&xpub, None);
+        ea.to_address()
+    }
+
+    impl Arbitrary for MigrationCertificate {
+        fn arbitrary<G: Gen>(g: &mut G) -> Self {
+            let sk: SecretKey<Ed25519> = arbitrary_secret_key(g);
+            let mut chain_code = [0u8; 32];
+            for o in chain_code.iter_mut() {
+                *o = u8::arbitrary(g)
+            }
+            MigrationCertificate {
+                old_address: arbitrary_old_address(g),
+                destination: Arbitrary::arbitrary(g),
+                valid_until: Arbitrary::arbitrary(g),
+                public_key: sk.to_public(),
+                chain_code,
+                signature: Arbitrary::arbitrary(g),
+            }
+        }
+    }
 }
@@ -6,10 +6,12 @@ extern crate quickcheck;
 
 pub mod account;
 pub mod accounting;
+pub mod audit;
 pub mod block;
 pub mod certificate;
 pub mod chaineval;
 pub mod chaintypes;
+pub mod compression;
 pub mod config;
 mod date;
 pub mod error;
@@ -24,8 +26,10 @@ pub mod legacy;
 pub mod milli;
 pub mod multisig;
 pub mod multiverse;
+pub mod names;
 pub mod rewards;
 pub mod setting;
+pub mod signing;
 pub mod stake;
 pub mod tokens;
 pub mod transaction;
@@ -34,6 +38,7 @@ pub mod update;
 pub mod utxo;
 pub mod value;
 pub mod vote;
+pub mod wire_format;
 
 #[cfg(any(test, feature = "property-test-api", feature = "with-bench"))]
 pub mod testing;
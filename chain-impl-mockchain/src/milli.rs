@@ -1,3 +1,6 @@
+use crate::rewards::Ratio;
+use std::cmp::Ordering;
+use std::num::NonZeroU64;
 use std::str::FromStr;
 use std::{fmt, iter};
 
@@ -19,9 +22,89 @@ impl Milli {
         self.0
     }
 
+    /// Convert to a float. The result is an approximation, it must not be
+    /// used in consensus computations.
     pub fn to_float(self) -> f64 {
         self.0 as f64 / MILLI_MULTIPLIER as f64
     }
+
+    /// Convert a float to the nearest milli, returns None if the float is
+    /// negative, not finite or too big.
+    ///
+    /// The conversion is an approximation, it must not be used in consensus
+    /// computations.
+    pub fn from_float_lossy(value: f64) -> Option<Self> {
+        let millis = (value * MILLI_MULTIPLIER as f64).round();
+        if millis.is_finite() && millis >= 0.0 && millis < u64::MAX as f64 {
+            Some(Milli(millis as u64))
+        } else {
+            None
+        }
+    }
+
+    pub fn checked_mul(self, rhs: u64) -> Option<Self> {
+        self.0.checked_mul(rhs).map(Milli)
+    }
+
+    pub fn saturating_mul(self, rhs: u64) -> Self {
+        Milli(self.0.saturating_mul(rhs))
+    }
+
+    /// Division rounded down, returns None if `rhs` is zero
+    pub fn checked_div(self, rhs: u64) -> Option<Self> {
+        self.0.checked_div(rhs).map(Milli)
+    }
+
+    /// Division rounded down, saturating to the maximum value if `rhs` is zero
+    pub fn saturating_div(self, rhs: u64) -> Self {
+        self.checked_div(rhs).unwrap_or(Milli(u64::MAX))
+    }
+
+    /// Apply the milli to an integer amount, i.e. `value * self` rounded down.
+    /// Returns None if the result does not fit in a u64.
+    pub fn checked_apply(self, value: u64) -> Option<u64> {
+        let result = value as u128 * self.0 as u128 / MILLI_MULTIPLIER as u128;
+        u64::try_from(result).ok()
+    }
+
+    /// Apply the milli to an integer amount, i.e. `value * self` rounded down,
+    /// saturating to `u64::MAX`.
+    pub fn saturating_apply(self, value: u64) -> u64 {
+        self.checked_apply(value).unwrap_or(u64::MAX)
+    }
+
+    pub fn to_ratio(self) -> Ratio {
+        Ratio {
+            numerator: self.0,
+            denominator: NonZeroU64::new(MILLI_MULTIPLIER).unwrap(),
+        }
+    }
+
+    /// Convert the ratio to a milli rounded down, returns None if the result
+    /// does not fit.
+    pub fn from_ratio_floor(ratio: Ratio) -> Option<Self> {
+        let millis =
+            ratio.numerator as u128 * MILLI_MULTIPLIER as u128 / ratio.denominator.get() as u128;
+        u64::try_from(millis).ok().map(Milli)
+    }
+}
+
+impl From<Milli> for Ratio {
+    fn from(milli: Milli) -> Ratio {
+        milli.to_ratio()
+    }
+}
+
+impl PartialEq<Ratio> for Milli {
+    fn eq(&self, other: &Ratio) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl PartialOrd<Ratio> for Milli {
+    fn partial_cmp(&self, other: &Ratio) -> Option<Ordering> {
+        Some(self.to_ratio().cmp(other))
+    }
 }
 
 impl FromStr for Milli {
@@ -121,7 +204,67 @@ mod tests {
         assert_display(1001, "1.001");
     }
 
+    #[test]
+    fn arithmetic() {
+        let milli = Milli::from_millis(1500);
+        assert_eq!(milli.checked_mul(3), Some(Milli::from_millis(4500)));
+        assert_eq!(Milli::from_millis(u64::MAX).checked_mul(2), None);
+        assert_eq!(
+            Milli::from_millis(u64::MAX).saturating_mul(2),
+            Milli::from_millis(u64::MAX)
+        );
+        assert_eq!(milli.checked_div(4), Some(Milli::from_millis(375)));
+        assert_eq!(milli.checked_div(0), None);
+        assert_eq!(milli.saturating_div(0), Milli::from_millis(u64::MAX));
+        assert_eq!(milli.checked_apply(1001), Some(1501));
+        assert_eq!(Milli::HALF.checked_apply(u64::MAX), Some(u64::MAX / 2));
+        assert_eq!(milli.checked_apply(u64::MAX), None);
+        assert_eq!(milli.saturating_apply(u64::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn ratio() {
+        let third = Ratio {
+            numerator: 1,
+            denominator: NonZeroU64::new(3).unwrap(),
+        };
+        let half = Ratio {
+            numerator: 2,
+            denominator: NonZeroU64::new(4).unwrap(),
+        };
+        assert_eq!(
+            Milli::from_ratio_floor(third),
+            Some(Milli::from_millis(333))
+        );
+        assert_eq!(Milli::from_ratio_floor(half), Some(Milli::HALF));
+        assert!(Milli::HALF == half);
+        assert!(Milli::from_millis(333) < third);
+        assert!(Milli::from_millis(334) > third);
+        assert_eq!(
+            Milli::from_ratio_floor(Ratio {
+                numerator: u64::MAX,
+                denominator: NonZeroU64::new(1).unwrap(),
+            }),
+            None
+        );
+    }
+
+    #[test]
+    fn float() {
+        assert_eq!(Milli::from_float_lossy(0.5), Some(Milli::HALF));
+        assert_eq!(Milli::from_float_lossy(0.0004), Some(Milli::ZERO));
+        assert_eq!(Milli::from_float_lossy(0.0006), Some(Milli::from_millis(1)));
+        assert_eq!(Milli::from_float_lossy(-0.1), None);
+        assert_eq!(Milli::from_float_lossy(f64::NAN), None);
+        assert_eq!(Milli::from_float_lossy(f64::INFINITY), None);
+        assert_eq!(Milli::from_float_lossy(1e30), None);
+    }
+
     quickcheck! {
+        fn milli_ratio_cycle(milli: Milli) -> bool {
+            Milli::from_ratio_floor(milli.to_ratio()) == Some(milli) && milli == milli.to_ratio()
+        }
+
         fn milli_print_parse_cycle(milli: Milli) -> TestResult {
             let result = milli.to_string().parse();
 
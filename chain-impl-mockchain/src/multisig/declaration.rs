@@ -1,5 +1,6 @@
 use crate::{account, key};
-use chain_crypto::{PublicKey, Signature};
+use chain_addr::{Address, Discrimination, Kind};
+use chain_crypto::{AsymmetricPublicKey, PublicKey, Signature, VerificationAlgorithm};
 
 use super::index::{Index, TreeIndex, LEVEL_MAXLIMIT};
 pub use crate::transaction::WitnessMultisigData;
@@ -56,6 +57,13 @@ pub struct Declaration {
 }
 
 impl Declaration {
+    /// Create a declaration, checking the threshold and the number of owners
+    pub fn new(threshold: u8, owners: Vec<DeclElement>) -> Result<Self, DeclarationError> {
+        let declaration = Declaration { threshold, owners };
+        declaration.is_valid()?;
+        Ok(declaration)
+    }
+
     pub fn threshold(&self) -> usize {
         self.threshold as usize
     }
@@ -63,6 +71,25 @@ impl Declaration {
     pub fn total(&self) -> usize {
         self.owners.len()
     }
+
+    pub fn owners(&self) -> &[DeclElement] {
+        &self.owners
+    }
+}
+
+/// The owners of a declaration, or of one of its sub declarations, a
+/// threshold of which need to sign
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignerGroup {
+    /// the index of the sub declaration among the owners of the declaration,
+    /// `None` for the declaration itself
+    pub sub_index: Option<Index>,
+    pub threshold: usize,
+    /// the number of owners, the sub declarations included
+    pub total: usize,
+    /// the hash of the key of the owners which are not sub declarations, with
+    /// the index of their signature in the witness
+    pub signers: Vec<(TreeIndex, key::Hash)>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -114,6 +141,77 @@ impl Declaration {
         Ok(())
     }
 
+    /// the address of the multisig account of the declaration
+    pub fn to_address(&self, discrimination: Discrimination) -> Address {
+        Address(discrimination, Kind::Multisig(self.to_identifier().into()))
+    }
+
+    /// The signers of the declaration, then of each of its sub declarations.
+    ///
+    /// A witness can only index the owners of the declaration and of its
+    /// direct sub declarations, a deeper sub declaration is an error.
+    pub fn signer_groups(&self) -> Result<Vec<SignerGroup>, DeclarationError> {
+        let mut groups = vec![SignerGroup {
+            sub_index: None,
+            threshold: self.threshold(),
+            total: self.total(),
+            signers: Vec::new(),
+        }];
+        for (i, owner) in self.owners.iter().enumerate() {
+            let index = owner_index(i)?;
+            match owner {
+                DeclElement::Owner(hash) => groups[0].signers.push((TreeIndex::D1(index), *hash)),
+                DeclElement::Sub(sub) => {
+                    let mut signers = Vec::new();
+                    for (j, sub_owner) in sub.owners.iter().enumerate() {
+                        match sub_owner {
+                            DeclElement::Owner(hash) => {
+                                signers.push((TreeIndex::D2(index, owner_index(j)?), *hash))
+                            }
+                            DeclElement::Sub(_) => return Err(DeclarationError::SubNotImplemented),
+                        }
+                    }
+                    groups.push(SignerGroup {
+                        sub_index: Some(index),
+                        threshold: sub.threshold(),
+                        total: sub.total(),
+                        signers,
+                    });
+                }
+            }
+        }
+        Ok(groups)
+    }
+
+    /// the minimum number of signatures meeting the threshold of the
+    /// declaration, the signatures of a sub declaration counting for one
+    /// owner once its own threshold is met
+    pub fn min_signatures(&self) -> usize {
+        let mut costs: Vec<usize> = self
+            .owners
+            .iter()
+            .map(|owner| match owner {
+                DeclElement::Owner(_) => 1,
+                DeclElement::Sub(sub) => sub.min_signatures(),
+            })
+            .collect();
+        costs.sort_unstable();
+        costs.iter().take(self.threshold()).sum()
+    }
+
+    /// The size in bytes of the transaction witness spending from the
+    /// account of the declaration with [`min_signatures`](Self::min_signatures)
+    /// signatures, to estimate the size of a transaction before it is signed
+    pub fn witness_size(&self) -> usize {
+        // witness tag and spending counter, then the number of signatures
+        const HEADER_SIZE: usize = 1 + 4 + 1;
+        // tree index, public key and signature
+        const SIGNATURE_SIZE: usize = 2
+            + <account::AccountAlg as AsymmetricPublicKey>::PUBLIC_KEY_SIZE
+            + <account::AccountAlg as VerificationAlgorithm>::SIGNATURE_SIZE;
+        HEADER_SIZE + self.min_signatures() * SIGNATURE_SIZE
+    }
+
     pub fn get_path(&self, ti: TreeIndex) -> Option<(&Declaration, Index)> {
         match ti {
             TreeIndex::D1(idx) => Some((self, idx)),
@@ -128,5 +226,12 @@ impl Declaration {
     }
 }
 
+fn owner_index(i: usize) -> Result<Index, DeclarationError> {
+    u8::try_from(i)
+        .ok()
+        .and_then(Index::from_u8)
+        .ok_or(DeclarationError::HasTooManyOwners)
+}
+
 pub type Pk = PublicKey<account::AccountAlg>;
 pub type Sig = Signature<WitnessMultisigData, account::AccountAlg>;
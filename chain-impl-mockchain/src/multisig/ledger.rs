@@ -3,8 +3,8 @@ use std::collections::hash_map::DefaultHasher;
 use thiserror::Error;
 
 use super::declaration::{Declaration, DeclarationError, Identifier};
-use crate::accounting::account::{self, DelegationType, Iter, SpendingCounter};
-use crate::value::{Value, ValueError};
+use crate::accounting::account::{self, DelegationType, Iter, SpendingCounter, SpendingCycle};
+use crate::value::{SumOverflow, Value};
 
 #[derive(Clone, PartialEq, Eq, Default)]
 pub struct Ledger {
@@ -107,6 +107,17 @@ impl Ledger {
         self.declarations.iter()
     }
 
+    /// Get the cycle of the lane of the spending counter of an account
+    pub fn get_spending_cycle(
+        &self,
+        identifier: &Identifier,
+        spending_counter: SpendingCounter,
+    ) -> Result<SpendingCycle, LedgerError> {
+        Ok(self
+            .accounts
+            .get_spending_cycle(identifier, spending_counter)?)
+    }
+
     /// If the account doesn't exist, or that the value would become negative, errors out.
     pub fn remove_value(
         &self,
@@ -143,7 +154,7 @@ impl Ledger {
         })
     }
 
-    pub fn get_total_value(&self) -> Result<Value, ValueError> {
+    pub fn get_total_value(&self) -> Result<Value, SumOverflow> {
         self.accounts.get_total_value()
     }
 }
@@ -4,7 +4,7 @@ mod ledger;
 mod witness;
 
 pub use declaration::{
-    DeclElement, Declaration, DeclarationError, Identifier, WitnessMultisigData,
+    DeclElement, Declaration, DeclarationError, Identifier, SignerGroup, WitnessMultisigData,
 };
 pub use ledger::{Ledger, LedgerError};
 pub use witness::{Witness, WitnessBuilder};
@@ -137,6 +137,60 @@ mod test {
         }
     }
 
+    #[test]
+    fn declaration_introspection() {
+        let mut rng = rand_core::OsRng;
+        let (_, _, o1, i1) = make_participant(&mut rng, 0);
+        let (_, _, o2, i2) = make_participant(&mut rng, 1);
+        let (_, _, o3, i3) = make_participant(&mut rng, 0);
+        let (_, _, o4, i4) = make_participant(&mut rng, 1);
+        let sub_index = Index::from_u8(2).unwrap();
+
+        let sub =
+            Declaration::new(1, vec![DeclElement::Owner(o3), DeclElement::Owner(o4)]).unwrap();
+        let decl = Declaration::new(
+            2,
+            vec![
+                DeclElement::Owner(o1),
+                DeclElement::Owner(o2),
+                DeclElement::Sub(sub),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            decl.signer_groups().unwrap(),
+            vec![
+                SignerGroup {
+                    sub_index: None,
+                    threshold: 2,
+                    total: 3,
+                    signers: vec![(TreeIndex::D1(i1), o1), (TreeIndex::D1(i2), o2)],
+                },
+                SignerGroup {
+                    sub_index: Some(sub_index),
+                    threshold: 1,
+                    total: 2,
+                    signers: vec![
+                        (TreeIndex::D2(sub_index, i3), o3),
+                        (TreeIndex::D2(sub_index, i4), o4)
+                    ],
+                },
+            ]
+        );
+        assert_eq!(decl.min_signatures(), 2);
+        assert_eq!(decl.witness_size(), 6 + 2 * (2 + 32 + 64));
+        assert_eq!(
+            decl.to_address(chain_addr::Discrimination::Test).kind(),
+            &chain_addr::Kind::Multisig(decl.to_identifier().into())
+        );
+
+        assert_eq!(
+            Declaration::new(3, vec![DeclElement::Owner(o1)]),
+            Err(DeclarationError::ThresholdInvalid)
+        );
+    }
+
     impl Arbitrary for Identifier {
         fn arbitrary<G: Gen>(g: &mut G) -> Self {
             let mut b = [0u8; 32];
@@ -60,7 +60,7 @@ impl Witness {
 fn deserialize_index(buf: &mut ReadBuf) -> Result<TreeIndex, ReadError> {
     let idx = buf.get_u16()?;
     match TreeIndex::unpack(idx) {
-        None => Err(ReadError::StructureInvalid("invalid index".to_string())),
+        None => Err(ReadError::StructureInvalid("invalid index".into())),
         Some(ti) => Ok(ti),
     }
 }
@@ -88,7 +88,7 @@ impl Readable for Witness {
 
         if len == 0 {
             return Err(ReadError::StructureInvalid(
-                "zero length not permitted".to_string(),
+                "zero length not permitted".into(),
             ));
         }
 
@@ -102,9 +102,7 @@ impl Readable for Witness {
         for _ in 0..len {
             let ti = deserialize_index(buf)?;
             if ti <= prev_index {
-                return Err(ReadError::StructureInvalid(
-                    "index not in order".to_string(),
-                ));
+                return Err(ReadError::StructureInvalid("index not in order".into()));
             }
             let pk = deserialize_public_key(buf)?;
             let sig = deserialize_signature(buf)?;
@@ -184,6 +184,25 @@ impl Multiverse<Ledger> {
     pub fn add(&mut self, k: HeaderId, st: Ledger) -> Ref<Ledger> {
         self.insert(st.chain_length(), k, st)
     }
+
+    /// Collect the states of the final blocks only: the depth of the
+    /// collection is the epoch stability depth of the states at the tip of
+    /// the longest chain, so the states of the blocks which can still be
+    /// rolled back are kept.
+    pub fn gc_final_states(&mut self) {
+        let depth = self
+            .states_by_chain_length
+            .values()
+            .next_back()
+            .into_iter()
+            .flatten()
+            .filter_map(|k| self.get(k))
+            .map(|state| state.settings.epoch_stability_depth)
+            .max();
+        if let Some(depth) = depth {
+            self.gc(depth);
+        }
+    }
 }
 
 impl<S> Default for Multiverse<S> {
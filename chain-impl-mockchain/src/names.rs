@@ -0,0 +1,404 @@
+//! Registry of human readable names bound to accounts.
+//!
+//! An account claims a short name with a
+//! [`NameClaim`](crate::certificate::NameClaim) certificate, paying a fee
+//! which is higher for shorter names. The name stays bound to the account for
+//! a fixed number of epochs: claiming it again from the same account extends
+//! the binding, and the account can give it up before its expiry with a
+//! [`NameRelease`](crate::certificate::NameRelease) certificate.
+//!
+//! The registry is disabled, and the certificates rejected, unless the chain
+//! sets the [`NameRegistryParams`].
+
+use crate::account;
+use crate::date::Epoch;
+use crate::value::Value;
+use chain_core::{
+    mempack::ReadError,
+    packer::Codec,
+    property::{ByteSource, Pack, Unpack},
+};
+use imhamt::{Hamt, HamtIter};
+use std::collections::hash_map::DefaultHasher;
+use std::convert::TryFrom;
+use std::fmt;
+use std::num::NonZeroU32;
+use std::str::FromStr;
+use thiserror::Error;
+use typed_bytes::ByteBuilder;
+
+pub const NAME_MAX_SIZE: usize = 32;
+
+/// A name of 1 to 32 bytes, made of lowercase ASCII letters, digits and `-`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Name(String);
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum InvalidName {
+    #[error("Name is empty")]
+    Empty,
+    #[error("Name can be no more than {} bytes long; got {} bytes", NAME_MAX_SIZE, .actual)]
+    TooLong { actual: usize },
+    #[error("Name contains the invalid character {0:?}")]
+    InvalidCharacter(char),
+}
+
+impl Name {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// size of the name in bytes, which is also its number of characters
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn bytes(&self) -> Vec<u8> {
+        let bb: ByteBuilder<Self> = ByteBuilder::new();
+        bb.u8(self.0.len() as u8)
+            .bytes(self.0.as_bytes())
+            .finalize_as_vec()
+    }
+}
+
+impl TryFrom<String> for Name {
+    type Error = InvalidName;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        if name.is_empty() {
+            return Err(InvalidName::Empty);
+        }
+        if name.len() > NAME_MAX_SIZE {
+            return Err(InvalidName::TooLong { actual: name.len() });
+        }
+        if let Some(c) = name
+            .chars()
+            .find(|c| !(c.is_ascii_lowercase() || c.is_ascii_digit() || *c == '-'))
+        {
+            return Err(InvalidName::InvalidCharacter(c));
+        }
+        Ok(Name(name))
+    }
+}
+
+impl FromStr for Name {
+    type Err = InvalidName;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Name::try_from(s.to_owned())
+    }
+}
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Pack for Name {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_u8(self.0.len() as u8)?;
+        codec.put_bytes(self.0.as_bytes())
+    }
+}
+
+impl Unpack for Name {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let size = source.take_u8()? as usize;
+        if size > NAME_MAX_SIZE {
+            return Err(ReadError::SizeTooBig {
+                size,
+                limit: NAME_MAX_SIZE,
+            });
+        }
+        let bytes = source.take_vec(size)?;
+        let name = String::from_utf8(bytes)
+            .map_err(|err| ReadError::InvalidData(err.to_string().into()))?;
+        Name::try_from(name).map_err(|err| ReadError::InvalidData(err.to_string().into()))
+    }
+}
+
+chain_ser::impl_legacy_codecs!(Name);
+
+/// Settings of the name registry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NameRegistryParams {
+    /// fee of a claim of a name of the maximum size
+    pub base_fee: u64,
+    /// fee added to the base fee for every byte below the maximum size
+    pub short_name_fee: u64,
+    /// number of epochs a name stays bound to its account after a claim
+    pub duration: NonZeroU32,
+}
+
+impl NameRegistryParams {
+    /// The fee of a claim of the given name, paid on top of the fee of the
+    /// transaction carrying the claim.
+    pub fn claim_fee(&self, name: &Name) -> Value {
+        let missing = (NAME_MAX_SIZE - name.len()) as u64;
+        Value(
+            self.base_fee
+                .saturating_add(self.short_name_fee.saturating_mul(missing)),
+        )
+    }
+}
+
+/// The account a name is bound to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameRecord {
+    pub owner: account::Identifier,
+    /// the first epoch at which the name is no longer bound
+    pub expiry: Epoch,
+}
+
+impl NameRecord {
+    pub fn is_active(&self, epoch: Epoch) -> bool {
+        epoch < self.expiry
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum NameRegistryError {
+    #[error("The name registry is not enabled")]
+    Disabled,
+    #[error("Name {0} is already claimed by another account")]
+    AlreadyClaimed(Name),
+    #[error("Name {0} is not claimed")]
+    NotClaimed(Name),
+    #[error("Name {0} is claimed by another account")]
+    NotOwner(Name),
+}
+
+/// The names claimed so far, including the expired ones which have not been
+/// purged yet.
+#[derive(Clone, PartialEq, Eq, Default)]
+pub struct NameRegistry(Hamt<DefaultHasher, Name, NameRecord>);
+
+impl fmt::Debug for NameRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.0.iter()).finish()
+    }
+}
+
+impl NameRegistry {
+    pub fn new() -> Self {
+        NameRegistry(Hamt::new())
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.size()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn get(&self, name: &Name) -> Option<&NameRecord> {
+        self.0.lookup(name)
+    }
+
+    /// The account the name is bound to at the given epoch
+    pub fn resolve(&self, name: &Name, epoch: Epoch) -> Option<&account::Identifier> {
+        self.get(name)
+            .filter(|record| record.is_active(epoch))
+            .map(|record| &record.owner)
+    }
+
+    /// The names bound to the account at the given epoch
+    pub fn names_of<'a>(
+        &'a self,
+        owner: &'a account::Identifier,
+        epoch: Epoch,
+    ) -> impl Iterator<Item = &'a Name> + 'a {
+        self.0
+            .iter()
+            .filter(move |(_, record)| record.owner == *owner && record.is_active(epoch))
+            .map(|(name, _)| name)
+    }
+
+    pub fn iter(&self) -> HamtIter<'_, Name, NameRecord> {
+        self.0.iter()
+    }
+
+    /// Bind the name to the account for `duration` epochs.
+    ///
+    /// A name bound to another account cannot be claimed until it expires,
+    /// a name already bound to the account is renewed from its current expiry.
+    pub fn claim(
+        &self,
+        name: Name,
+        owner: account::Identifier,
+        epoch: Epoch,
+        duration: NonZeroU32,
+    ) -> Result<Self, NameRegistryError> {
+        let start = match self.get(&name) {
+            Some(record) if record.is_active(epoch) && record.owner != owner => {
+                return Err(NameRegistryError::AlreadyClaimed(name));
+            }
+            Some(record) if record.is_active(epoch) => record.expiry,
+            _ => epoch,
+        };
+        let record = NameRecord {
+            owner,
+            expiry: start.saturating_add(duration.get()),
+        };
+        Ok(NameRegistry(self.0.insert_or_update_simple(
+            name,
+            record.clone(),
+            |_| Some(record),
+        )))
+    }
+
+    /// Unbind the name from the account before its expiry
+    pub fn release(
+        &self,
+        name: &Name,
+        owner: &account::Identifier,
+        epoch: Epoch,
+    ) -> Result<Self, NameRegistryError> {
+        match self.get(name) {
+            Some(record) if record.is_active(epoch) && record.owner == *owner => {
+                // the name was just looked up, the removal cannot fail
+                Ok(NameRegistry(self.0.remove(name).unwrap()))
+            }
+            Some(record) if record.is_active(epoch) => {
+                Err(NameRegistryError::NotOwner(name.clone()))
+            }
+            _ => Err(NameRegistryError::NotClaimed(name.clone())),
+        }
+    }
+
+    /// Drop the names expired at the given epoch
+    pub fn remove_expired(&self, epoch: Epoch) -> Self {
+        let mut names = self.0.clone();
+        for (name, _) in self.0.iter().filter(|(_, record)| !record.is_active(epoch)) {
+            names = names.remove(name).unwrap();
+        }
+        NameRegistry(names)
+    }
+
+    pub(crate) fn insert_record(&self, name: Name, record: NameRecord) -> Self {
+        NameRegistry(
+            self.0
+                .insert_or_update_simple(name, record.clone(), |_| Some(record)),
+        )
+    }
+}
+
+#[cfg(any(test, feature = "property-test-api"))]
+mod tests {
+    use super::*;
+    #[cfg(test)]
+    use crate::testing::TestGen;
+    #[cfg(test)]
+    use chain_core::mempack::{ReadBuf, Readable as _};
+    #[allow(unused_imports)]
+    use quickcheck::TestResult;
+    use quickcheck::{Arbitrary, Gen};
+
+    impl Arbitrary for Name {
+        fn arbitrary<G: Gen>(g: &mut G) -> Self {
+            const CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789-";
+            let len = usize::arbitrary(g) % NAME_MAX_SIZE + 1;
+            let name = (0..len)
+                .map(|_| CHARS[usize::arbitrary(g) % CHARS.len()] as char)
+                .collect();
+            Name(name)
+        }
+    }
+
+    impl Arbitrary for NameRegistryParams {
+        fn arbitrary<G: Gen>(g: &mut G) -> Self {
+            NameRegistryParams {
+                base_fee: Arbitrary::arbitrary(g),
+                short_name_fee: Arbitrary::arbitrary(g),
+                duration: NonZeroU32::new(u32::arbitrary(g)).unwrap_or(NonZeroU32::new(1).unwrap()),
+            }
+        }
+    }
+
+    #[quickcheck_macros::quickcheck]
+    fn name_serialization_bijection(name: Name) -> TestResult {
+        let bytes = name.bytes();
+        let mut buf = ReadBuf::from(bytes.as_ref());
+        let result = Name::read(&mut buf);
+        TestResult::from_bool(result == Ok(name) && buf.get_slice_end().is_empty())
+    }
+
+    #[test]
+    pub fn invalid_names_are_rejected() {
+        assert_eq!("".parse::<Name>(), Err(InvalidName::Empty));
+        assert_eq!(
+            "a".repeat(NAME_MAX_SIZE + 1).parse::<Name>(),
+            Err(InvalidName::TooLong {
+                actual: NAME_MAX_SIZE + 1
+            })
+        );
+        assert_eq!(
+            "Alice".parse::<Name>(),
+            Err(InvalidName::InvalidCharacter('A'))
+        );
+        assert!("alice-42".parse::<Name>().is_ok());
+    }
+
+    #[test]
+    pub fn shorter_names_cost_more() {
+        let params = NameRegistryParams {
+            base_fee: 10,
+            short_name_fee: 5,
+            duration: NonZeroU32::new(10).unwrap(),
+        };
+        let long: Name = "a".repeat(NAME_MAX_SIZE).parse().unwrap();
+        let short: Name = "ab".parse().unwrap();
+        assert_eq!(params.claim_fee(&long), Value(10));
+        assert_eq!(
+            params.claim_fee(&short),
+            Value(10 + 5 * (NAME_MAX_SIZE as u64 - 2))
+        );
+    }
+
+    #[test]
+    pub fn claim_renew_release_and_expire() {
+        let alice = TestGen::identifier();
+        let bob = TestGen::identifier();
+        let name: Name = "alice".parse().unwrap();
+        let duration = NonZeroU32::new(2).unwrap();
+
+        let registry = NameRegistry::new()
+            .claim(name.clone(), alice.clone(), 0, duration)
+            .unwrap();
+        assert_eq!(registry.resolve(&name, 1), Some(&alice));
+        assert_eq!(registry.resolve(&name, 2), None);
+        assert_eq!(
+            registry.claim(name.clone(), bob.clone(), 1, duration),
+            Err(NameRegistryError::AlreadyClaimed(name.clone()))
+        );
+
+        // renewal extends from the current expiry
+        let registry = registry
+            .claim(name.clone(), alice.clone(), 1, duration)
+            .unwrap();
+        assert_eq!(registry.get(&name).unwrap().expiry, 4);
+        assert_eq!(
+            registry.names_of(&alice, 3).collect::<Vec<_>>(),
+            vec![&name]
+        );
+
+        assert_eq!(
+            registry.release(&name, &bob, 3),
+            Err(NameRegistryError::NotOwner(name.clone()))
+        );
+        assert!(registry.release(&name, &alice, 3).unwrap().is_empty());
+
+        // once expired, the name can be claimed by another account
+        let registry = registry
+            .claim(name.clone(), bob.clone(), 4, duration)
+            .unwrap();
+        assert_eq!(registry.resolve(&name, 4), Some(&bob));
+        assert!(registry.remove_expired(6).is_empty());
+    }
+}
@@ -1,7 +1,11 @@
 use crate::date::Epoch;
 use crate::stake::Stake;
 use crate::value::{Value, ValueError};
-use chain_core::mempack::{ReadBuf, ReadError};
+use chain_core::{
+    mempack::ReadError,
+    packer::Codec,
+    property::{ByteSource, Pack, Unpack},
+};
 use std::num::{NonZeroU32, NonZeroU64};
 use typed_bytes::ByteBuilder;
 
@@ -68,23 +72,31 @@ impl TaxType {
             .u64(self.ratio.denominator.get())
             .u64(self.max_limit.map_or(0, |v| v.get()))
     }
+}
 
-    pub fn read_frombuf(rb: &mut ReadBuf) -> Result<Self, ReadError> {
-        let fixed = rb.get_u64().map(Value)?;
-        let num = rb.get_u64()?;
-        let denom = rb.get_u64()?;
-        let limit = rb.get_u64()?;
+impl Pack for TaxType {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(self.serialize_in(ByteBuilder::new()).finalize().as_slice())
+    }
+}
+
+impl Unpack for TaxType {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let fixed = source.take_u64().map(Value)?;
+        let num = source.take_u64()?;
+        let denom = source.take_u64()?;
+        let limit = source.take_u64()?;
         let denominator = NonZeroU64::new(denom).map_or_else(
             || {
                 Err(ReadError::StructureInvalid(
-                    "ratio fraction divisor invalid".to_string(),
+                    "ratio fraction divisor invalid".into(),
                 ))
             },
             Ok,
         )?;
         if num > denom {
             return Err(ReadError::StructureInvalid(
-                "ratio fraction invalid bigger than 1".to_string(),
+                "ratio fraction invalid bigger than 1".into(),
             ));
         }
 
@@ -99,6 +111,8 @@ impl TaxType {
     }
 }
 
+chain_ser::impl_legacy_codecs!(TaxType);
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Limit {
     /// the drawn value will not be limited
@@ -320,7 +334,7 @@ mod tests {
         params.epoch_start = 1;
         let epoch = 0;
         let system_info = SystemInformation {
-            declared_stake: Stake::from_value(Value(100)),
+            declared_stake: Stake(100),
         };
         assert_eq!(
             rewards_contribution_calculation(epoch, &params, &system_info),
@@ -344,7 +358,7 @@ mod tests {
         };
         let epoch = 1;
         let system_info = SystemInformation {
-            declared_stake: Stake::from_value(Value(100)),
+            declared_stake: Stake(100),
         };
         assert_eq!(
             rewards_contribution_calculation(epoch, &params, &system_info),
@@ -7,17 +7,20 @@ use crate::fragment::{config::ConfigParams, BlockContentSize};
 use crate::milli::Milli;
 use crate::update;
 use crate::{
+    certificate::CertificatePolicy,
     chaineval::PraosNonce,
     chaintypes::ConsensusType,
     config::{ConfigParam, RewardParams},
     fee::LinearFee,
     key::BftLeaderId,
+    names::NameRegistryParams,
     rewards,
+    value::Value,
     vote::CommitteeId,
 };
 use std::error::Error;
 use std::fmt;
-use std::num::NonZeroU32;
+use std::num::{NonZeroU32, NonZeroU64};
 use std::sync::Arc;
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -28,8 +31,15 @@ pub struct Settings {
     pub slot_duration: u8,
     pub epoch_stability_depth: u32,
     pub active_slots_coeff: ActiveSlotsCoeff,
+    /// slots at the start of an epoch over which a change of the active
+    /// slots coefficient is phased in, see
+    /// [`ActiveSlotsCoeff::in_transition`]
+    pub active_slots_coeff_transition: u32,
     pub block_content_max_size: BlockContentSize,
     pub bft_leaders: Arc<[BftLeaderId]>,
+    /// number of BFT leaders which need to sign a block, the slot leader
+    /// included. 0 and 1 only require the signature of the slot leader
+    pub bft_quorum: u8,
     pub linear_fees: LinearFee,
     /// The number of epochs that a proposal remains valid. To be
     /// precise, if a proposal is made at date (epoch_p, slot), then
@@ -43,6 +53,21 @@ pub struct Settings {
     pub pool_participation_capping: Option<(NonZeroU32, NonZeroU32)>,
     pub committees: Arc<[CommitteeId]>,
     pub transaction_max_expiry_epochs: u8,
+    /// the name registry is disabled when not set
+    pub name_registry: Option<NameRegistryParams>,
+    /// minimum pledge of the stake pools, zero when not enforced
+    pub minimum_pool_pledge: Value,
+    /// scale the rewards of the pools by their performance over the epoch
+    pub pool_performance_rewards: bool,
+    /// version of the ledger rules, see the `PROTOCOL_VERSION_` constants
+    pub protocol_version: u32,
+    /// the certificate kinds which cannot be applied on the chain
+    pub certificate_policy: CertificatePolicy,
+    /// vote plans a committee member can submit in an epoch, not limited
+    /// when not set
+    pub max_vote_plans_per_epoch: Option<NonZeroU32>,
+    /// proposals of a vote plan, not limited beyond the encoding when not set
+    pub max_proposals_per_vote_plan: Option<NonZeroU32>,
     #[cfg(feature = "evm")]
     pub evm_config: EvmConfig,
     #[cfg(feature = "evm")]
@@ -84,6 +109,23 @@ impl TryFrom<Milli> for ActiveSlotsCoeff {
     }
 }
 
+impl ActiveSlotsCoeff {
+    /// The coefficient at `slot_id` of an epoch whose coefficient moves from
+    /// `from` to `to` over the first `transition_slots` slots, linearly. The
+    /// coefficient is `from` at the first slot and `to` from the slot
+    /// `transition_slots` onward.
+    pub fn in_transition(from: Self, to: Self, slot_id: u32, transition_slots: u32) -> Self {
+        if slot_id >= transition_slots {
+            return to;
+        }
+        let from = from.0.to_millis() as i64;
+        let to = to.0.to_millis() as i64;
+        let millis = from + (to - from) * slot_id as i64 / transition_slots as i64;
+        // between two valid coefficients, so valid as well
+        ActiveSlotsCoeff(Milli::from_millis(millis as u64))
+    }
+}
+
 impl From<ActiveSlotsCoeff> for Milli {
     fn from(coeff: ActiveSlotsCoeff) -> Milli {
         coeff.0
@@ -114,6 +156,14 @@ impl Default for FeesGoesTo {
 
 pub const SLOTS_PERCENTAGE_RANGE: u8 = 100;
 
+/// Protocol version of the chains started before the protocol versions
+pub const PROTOCOL_VERSION_INITIAL: u32 = 0;
+
+/// Protocol version from which a private tally is done with a single
+/// `VoteTally` fragment carrying the decrypted shares, the
+/// `EncryptedVoteTally` fragments are rejected
+pub const PROTOCOL_VERSION_UNIFIED_VOTE_TALLY: u32 = 1;
+
 impl Settings {
     pub fn new() -> Self {
         Self {
@@ -125,6 +175,7 @@ impl Settings {
             active_slots_coeff: ActiveSlotsCoeff::try_from(Milli::HALF).unwrap(),
             block_content_max_size: 102_400,
             bft_leaders: Arc::new([]),
+            bft_quorum: 1,
             linear_fees: LinearFee::new(0, 0, 0),
             proposal_expiration: 100,
             reward_params: None,
@@ -134,6 +185,14 @@ impl Settings {
             pool_participation_capping: None,
             committees: Arc::new([]),
             transaction_max_expiry_epochs: 1,
+            name_registry: None,
+            minimum_pool_pledge: Value::zero(),
+            pool_performance_rewards: false,
+            protocol_version: PROTOCOL_VERSION_INITIAL,
+            certificate_policy: CertificatePolicy::allow_all(),
+            max_vote_plans_per_epoch: None,
+            max_proposals_per_vote_plan: None,
+            active_slots_coeff_transition: 0,
             #[cfg(feature = "evm")]
             evm_config: EvmConfig::default(),
             #[cfg(feature = "evm")]
@@ -149,6 +208,7 @@ impl Settings {
         let mut new_state = self.clone();
         let mut per_certificate_fees = None;
         let mut per_vote_certificate_fees = None;
+        let mut per_certificate_byte_fee = None;
 
         for param in changes.iter() {
             match param {
@@ -156,6 +216,7 @@ impl Settings {
                 | ConfigParam::Discrimination(_)
                 | ConfigParam::TreasuryAdd(_)
                 | ConfigParam::RewardPot(_)
+                | ConfigParam::TotalSupply(_)
                 | ConfigParam::KesUpdateSpeed(_) => {
                     return Err(update::Error::ReadOnlySetting);
                 }
@@ -198,6 +259,9 @@ impl Settings {
                         .collect::<Vec<_>>()
                         .into();
                 }
+                ConfigParam::BftQuorum(d) => {
+                    new_state.bft_quorum = *d;
+                }
                 ConfigParam::LinearFee(d) => {
                     new_state.linear_fees = *d;
                 }
@@ -216,6 +280,9 @@ impl Settings {
                 ConfigParam::PerVoteCertificateFees(pcf) => {
                     per_vote_certificate_fees = Some(pcf);
                 }
+                ConfigParam::PerCertificateByteFee(fee) => {
+                    per_certificate_byte_fee = Some(*fee);
+                }
                 ConfigParam::FeesInTreasury(value) => {
                     new_state.fees_goes_to = if *value {
                         FeesGoesTo::Treasury
@@ -248,6 +315,36 @@ impl Settings {
                 ConfigParam::TransactionMaxExpiryEpochs(max_expiry_epochs) => {
                     new_state.transaction_max_expiry_epochs = *max_expiry_epochs;
                 }
+                ConfigParam::NameRegistryParams(name_registry) => {
+                    new_state.name_registry = Some(*name_registry);
+                }
+                ConfigParam::MinimumPoolPledge(pledge) => {
+                    new_state.minimum_pool_pledge = *pledge;
+                }
+                ConfigParam::PoolPerformanceRewards(value) => {
+                    new_state.pool_performance_rewards = *value;
+                }
+                ConfigParam::ProtocolVersion(version) => {
+                    if *version < new_state.protocol_version {
+                        return Err(update::Error::ProtocolVersionDowngrade(
+                            new_state.protocol_version,
+                            *version,
+                        ));
+                    }
+                    new_state.protocol_version = *version;
+                }
+                ConfigParam::CertificatePolicy(policy) => {
+                    new_state.certificate_policy = *policy;
+                }
+                ConfigParam::MaxVotePlansPerEpoch(limit) => {
+                    new_state.max_vote_plans_per_epoch = NonZeroU32::new(*limit);
+                }
+                ConfigParam::MaxProposalsPerVotePlan(limit) => {
+                    new_state.max_proposals_per_vote_plan = NonZeroU32::new(*limit);
+                }
+                ConfigParam::ActiveSlotsCoeffTransition(slots) => {
+                    new_state.active_slots_coeff_transition = *slots;
+                }
                 #[cfg(feature = "evm")]
                 ConfigParam::EvmConfiguration(evm_config_params) => {
                     new_state.evm_config = *evm_config_params;
@@ -267,6 +364,12 @@ impl Settings {
             new_state.linear_fees.per_vote_certificate_fees(*pcf);
         }
 
+        if let Some(fee) = per_certificate_byte_fee {
+            new_state
+                .linear_fees
+                .per_certificate_byte_fee(NonZeroU64::new(fee));
+        }
+
         Ok(new_state)
     }
 
@@ -286,7 +389,13 @@ impl Settings {
         for bft_leader in self.bft_leaders.iter() {
             params.push(ConfigParam::AddBftLeader(bft_leader.clone()));
         }
+        if self.bft_quorum != 1 {
+            params.push(ConfigParam::BftQuorum(self.bft_quorum));
+        }
         params.push(ConfigParam::LinearFee(self.linear_fees));
+        if let Some(fee) = self.linear_fees.per_certificate_byte_fee {
+            params.push(ConfigParam::PerCertificateByteFee(fee.get()));
+        }
         params.push(ConfigParam::ProposalExpiration(self.proposal_expiration));
         params.push(ConfigParam::TransactionMaxExpiryEpochs(
             self.transaction_max_expiry_epochs,
@@ -300,6 +409,32 @@ impl Settings {
             Some(p) => params.push(ConfigParam::TreasuryParams(*p)),
             None => (),
         };
+        if let Some(name_registry) = self.name_registry {
+            params.push(ConfigParam::NameRegistryParams(name_registry));
+        }
+        if self.minimum_pool_pledge != Value::zero() {
+            params.push(ConfigParam::MinimumPoolPledge(self.minimum_pool_pledge));
+        }
+        if self.pool_performance_rewards {
+            params.push(ConfigParam::PoolPerformanceRewards(true));
+        }
+        if self.protocol_version != PROTOCOL_VERSION_INITIAL {
+            params.push(ConfigParam::ProtocolVersion(self.protocol_version));
+        }
+        if self.certificate_policy != CertificatePolicy::allow_all() {
+            params.push(ConfigParam::CertificatePolicy(self.certificate_policy));
+        }
+        if let Some(limit) = self.max_vote_plans_per_epoch {
+            params.push(ConfigParam::MaxVotePlansPerEpoch(limit.get()));
+        }
+        if let Some(limit) = self.max_proposals_per_vote_plan {
+            params.push(ConfigParam::MaxProposalsPerVotePlan(limit.get()));
+        }
+        if self.active_slots_coeff_transition != 0 {
+            params.push(ConfigParam::ActiveSlotsCoeffTransition(
+                self.active_slots_coeff_transition,
+            ));
+        }
         #[cfg(feature = "evm")]
         params.push(ConfigParam::EvmConfiguration(self.evm_config));
         #[cfg(feature = "evm")]
@@ -356,7 +491,8 @@ impl Default for Settings {
 
 #[cfg(test)]
 mod tests {
-    use super::{FeesGoesTo, Settings};
+    use super::{ActiveSlotsCoeff, FeesGoesTo, Settings};
+    use crate::{config::ConfigParam, fragment::ConfigParams, milli::Milli, update};
     use quickcheck::{Arbitrary, Gen};
 
     impl Arbitrary for FeesGoesTo {
@@ -374,4 +510,37 @@ mod tests {
             Settings::new()
         }
     }
+
+    #[test]
+    fn active_slots_coeff_moves_across_the_transition() {
+        let coeff = |millis| ActiveSlotsCoeff::try_from(Milli::from_millis(millis)).unwrap();
+        let at = |from, to, slot_id| ActiveSlotsCoeff::in_transition(from, to, slot_id, 10);
+
+        assert_eq!(at(coeff(100), coeff(600), 0), coeff(100));
+        assert_eq!(at(coeff(100), coeff(600), 5), coeff(350));
+        assert_eq!(at(coeff(100), coeff(600), 9), coeff(550));
+        assert_eq!(at(coeff(100), coeff(600), 10), coeff(600));
+        assert_eq!(at(coeff(600), coeff(100), 9), coeff(150));
+        assert_eq!(at(coeff(600), coeff(100), 100), coeff(100));
+        // no transition
+        assert_eq!(
+            ActiveSlotsCoeff::in_transition(coeff(100), coeff(600), 0, 0),
+            coeff(600)
+        );
+    }
+
+    #[test]
+    fn protocol_version_cannot_be_downgraded() {
+        let mut params = ConfigParams::new();
+        params.push(ConfigParam::ProtocolVersion(2));
+        let settings = Settings::new().try_apply(&params).unwrap();
+        assert_eq!(settings.protocol_version, 2);
+
+        let mut params = ConfigParams::new();
+        params.push(ConfigParam::ProtocolVersion(1));
+        assert_eq!(
+            settings.try_apply(&params),
+            Err(update::Error::ProtocolVersionDowngrade(2, 1))
+        );
+    }
 }
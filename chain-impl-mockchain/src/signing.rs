@@ -0,0 +1,572 @@
+//! Registry of the data signed throughout the chain.
+//!
+//! Every signature verified by the ledger is made over a specific byte layout,
+//! this module describes these layouts and what separates the data signed in
+//! one context from the data signed in another one, so they can be audited
+//! programmatically instead of by reading the serialization code.
+//!
+//! The transaction witnesses, the block headers, the BFT endorsements and the
+//! legacy migration challenges carry an explicit domain tag at the start of
+//! the signed data. The certificate authentications do
+//! not: they sign the transaction binding data (the transaction bytes up to
+//! the payload authentication, see
+//! [`TransactionBindingAuthData`](crate::transaction::TransactionBindingAuthData)),
+//! which is separated from the other contexts by its structure only.
+
+use crate::certificate::Certificate;
+use crate::header::BftEndorsement;
+use crate::legacy::MIGRATION_CHALLENGE_DOMAIN;
+
+/// Algorithm of the key producing the signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SignatureAlgorithm {
+    Ed25519,
+    SumEd25519_12,
+}
+
+/// Every context in which a signature is made
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SigningContext {
+    LegacyUtxoWitness,
+    UtxoWitness,
+    AccountWitness,
+    MultisigWitness,
+    StakeDelegationAuth,
+    PoolRegistrationAuth,
+    PoolRetirementAuth,
+    PoolUpdateAuth,
+    UpdateProposalAuth,
+    UpdateVoteAuth,
+    VotePlanAuth,
+    VoteTallyAuth,
+    EncryptedVoteTallyAuth,
+    VotingPowerSnapshotAuth,
+    AmountStakeDelegationAuth,
+    RewardsRedelegationAuth,
+    ExtensionAuth,
+    VotePlanCancelAuth,
+    BftHeader,
+    GenesisPraosHeader,
+    BftMerkleHeader,
+    GenesisPraosMerkleHeader,
+    BftEndorsement,
+    MigrationChallenge,
+}
+
+/// Bytes identifying the signing context within the signed data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DomainTag {
+    /// the signed data contains `bytes` at `offset`
+    Explicit { offset: usize, bytes: &'static [u8] },
+    /// the context is only identified by the structure of the signed data
+    None,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldSize {
+    Fixed(usize),
+    Variable,
+}
+
+/// A field of the signed data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignedField {
+    pub name: &'static str,
+    pub size: FieldSize,
+}
+
+/// Description of the data signed in a given context
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SigningLayout {
+    pub context: SigningContext,
+    pub algorithm: SignatureAlgorithm,
+    pub tag: DomainTag,
+    /// the fields of the signed data, in order
+    pub fields: &'static [SignedField],
+}
+
+const fn fixed(name: &'static str, size: usize) -> SignedField {
+    SignedField {
+        name,
+        size: FieldSize::Fixed(size),
+    }
+}
+
+const fn variable(name: &'static str) -> SignedField {
+    SignedField {
+        name,
+        size: FieldSize::Variable,
+    }
+}
+
+const UTXO_WITNESS_FIELDS: &[SignedField] = &[
+    fixed("witness tag", 1),
+    fixed("block0 hash", 32),
+    fixed("transaction sign data hash", 32),
+];
+
+const ACCOUNT_WITNESS_FIELDS: &[SignedField] = &[
+    fixed("witness tag", 1),
+    fixed("block0 hash", 32),
+    fixed("transaction sign data hash", 32),
+    fixed("spending counter", 4),
+    // 4 bytes, only present after the first cycle of the counter lane
+    variable("spending counter cycle"),
+];
+
+const TRANSACTION_BINDING_FIELDS: &[SignedField] = &[
+    variable("payload"),
+    fixed("valid until epoch", 4),
+    fixed("valid until slot", 4),
+    fixed("number of inputs", 1),
+    fixed("number of outputs", 1),
+    variable("inputs"),
+    variable("outputs"),
+    variable("witnesses"),
+];
+
+const BFT_HEADER_FIELDS: &[SignedField] = &[
+    fixed("version", 2),
+    fixed("content size", 4),
+    fixed("epoch", 4),
+    fixed("slot", 4),
+    fixed("chain length", 4),
+    fixed("content hash", 32),
+    fixed("parent hash", 32),
+    fixed("leader id", 32),
+];
+
+const BFT_ENDORSEMENT_FIELDS: &[SignedField] = &[
+    fixed("domain tag", 15),
+    fixed("version", 2),
+    fixed("content size", 4),
+    fixed("epoch", 4),
+    fixed("slot", 4),
+    fixed("chain length", 4),
+    fixed("content hash", 32),
+    fixed("parent hash", 32),
+    fixed("leader id", 32),
+];
+
+const MIGRATION_CHALLENGE_FIELDS: &[SignedField] = &[
+    fixed("domain tag", 16),
+    fixed("block0 hash", 32),
+    variable("old address"),
+    variable("destination"),
+    fixed("valid until epoch", 4),
+    fixed("valid until slot", 4),
+];
+
+const GENESIS_PRAOS_HEADER_FIELDS: &[SignedField] = &[
+    fixed("version", 2),
+    fixed("content size", 4),
+    fixed("epoch", 4),
+    fixed("slot", 4),
+    fixed("chain length", 4),
+    fixed("content hash", 32),
+    fixed("parent hash", 32),
+    fixed("pool id", 32),
+    fixed("vrf proof", 96),
+];
+
+impl SigningContext {
+    pub const ALL: [SigningContext; 24] = [
+        SigningContext::LegacyUtxoWitness,
+        SigningContext::UtxoWitness,
+        SigningContext::AccountWitness,
+        SigningContext::MultisigWitness,
+        SigningContext::StakeDelegationAuth,
+        SigningContext::PoolRegistrationAuth,
+        SigningContext::PoolRetirementAuth,
+        SigningContext::PoolUpdateAuth,
+        SigningContext::UpdateProposalAuth,
+        SigningContext::UpdateVoteAuth,
+        SigningContext::VotePlanAuth,
+        SigningContext::VoteTallyAuth,
+        SigningContext::EncryptedVoteTallyAuth,
+        SigningContext::VotingPowerSnapshotAuth,
+        SigningContext::AmountStakeDelegationAuth,
+        SigningContext::RewardsRedelegationAuth,
+        SigningContext::ExtensionAuth,
+        SigningContext::VotePlanCancelAuth,
+        SigningContext::BftHeader,
+        SigningContext::GenesisPraosHeader,
+        SigningContext::BftMerkleHeader,
+        SigningContext::GenesisPraosMerkleHeader,
+        SigningContext::BftEndorsement,
+        SigningContext::MigrationChallenge,
+    ];
+
+    pub fn layout(self) -> SigningLayout {
+        let witness = |tag: &'static [u8], fields| SigningLayout {
+            context: self,
+            algorithm: SignatureAlgorithm::Ed25519,
+            tag: DomainTag::Explicit {
+                offset: 0,
+                bytes: tag,
+            },
+            fields,
+        };
+        let binding = SigningLayout {
+            context: self,
+            algorithm: SignatureAlgorithm::Ed25519,
+            tag: DomainTag::None,
+            fields: TRANSACTION_BINDING_FIELDS,
+        };
+        match self {
+            SigningContext::LegacyUtxoWitness => witness(&[0], UTXO_WITNESS_FIELDS),
+            SigningContext::UtxoWitness => witness(&[1], UTXO_WITNESS_FIELDS),
+            SigningContext::AccountWitness => witness(&[2], ACCOUNT_WITNESS_FIELDS),
+            SigningContext::MultisigWitness => witness(&[3], ACCOUNT_WITNESS_FIELDS),
+            SigningContext::StakeDelegationAuth
+            | SigningContext::PoolRegistrationAuth
+            | SigningContext::PoolRetirementAuth
+            | SigningContext::PoolUpdateAuth
+            | SigningContext::UpdateProposalAuth
+            | SigningContext::UpdateVoteAuth
+            | SigningContext::VotePlanAuth
+            | SigningContext::VoteTallyAuth
+            | SigningContext::EncryptedVoteTallyAuth
+            | SigningContext::VotingPowerSnapshotAuth
+            | SigningContext::AmountStakeDelegationAuth
+            | SigningContext::RewardsRedelegationAuth
+            | SigningContext::ExtensionAuth
+            | SigningContext::VotePlanCancelAuth => binding,
+            // the header version is a big endian u16
+            SigningContext::BftHeader => SigningLayout {
+                context: self,
+                algorithm: SignatureAlgorithm::Ed25519,
+                tag: DomainTag::Explicit {
+                    offset: 0,
+                    bytes: &[0, 1],
+                },
+                fields: BFT_HEADER_FIELDS,
+            },
+            SigningContext::GenesisPraosHeader => SigningLayout {
+                context: self,
+                algorithm: SignatureAlgorithm::SumEd25519_12,
+                tag: DomainTag::Explicit {
+                    offset: 0,
+                    bytes: &[0, 2],
+                },
+                fields: GENESIS_PRAOS_HEADER_FIELDS,
+            },
+            SigningContext::BftMerkleHeader => SigningLayout {
+                context: self,
+                algorithm: SignatureAlgorithm::Ed25519,
+                tag: DomainTag::Explicit {
+                    offset: 0,
+                    bytes: &[0, 3],
+                },
+                fields: BFT_HEADER_FIELDS,
+            },
+            SigningContext::GenesisPraosMerkleHeader => SigningLayout {
+                context: self,
+                algorithm: SignatureAlgorithm::SumEd25519_12,
+                tag: DomainTag::Explicit {
+                    offset: 0,
+                    bytes: &[0, 4],
+                },
+                fields: GENESIS_PRAOS_HEADER_FIELDS,
+            },
+            // the auth data of the endorsed header, both BFT header versions
+            // have the same layout
+            SigningContext::BftEndorsement => SigningLayout {
+                context: self,
+                algorithm: SignatureAlgorithm::Ed25519,
+                tag: DomainTag::Explicit {
+                    offset: 0,
+                    bytes: BftEndorsement::DOMAIN_TAG,
+                },
+                fields: BFT_ENDORSEMENT_FIELDS,
+            },
+            // signed with the key of the legacy address, see
+            // `legacy::MigrationChallenge`
+            SigningContext::MigrationChallenge => SigningLayout {
+                context: self,
+                algorithm: SignatureAlgorithm::Ed25519,
+                tag: DomainTag::Explicit {
+                    offset: 0,
+                    bytes: MIGRATION_CHALLENGE_DOMAIN,
+                },
+                fields: MIGRATION_CHALLENGE_FIELDS,
+            },
+        }
+    }
+
+    /// The context of the payload authentication of the certificate, `None`
+    /// if the certificate is only authenticated by the witnesses of the
+    /// transaction carrying it
+    pub fn of_certificate(certificate: &Certificate) -> Option<SigningContext> {
+        match certificate {
+            Certificate::StakeDelegation(_) => Some(SigningContext::StakeDelegationAuth),
+            Certificate::PoolRegistration(_) => Some(SigningContext::PoolRegistrationAuth),
+            Certificate::PoolRetirement(_) => Some(SigningContext::PoolRetirementAuth),
+            Certificate::PoolUpdate(_) => Some(SigningContext::PoolUpdateAuth),
+            Certificate::UpdateProposal(_) => Some(SigningContext::UpdateProposalAuth),
+            Certificate::UpdateVote(_) => Some(SigningContext::UpdateVoteAuth),
+            Certificate::VotePlan(_) => Some(SigningContext::VotePlanAuth),
+            Certificate::VoteTally(_) => Some(SigningContext::VoteTallyAuth),
+            Certificate::EncryptedVoteTally(_) => Some(SigningContext::EncryptedVoteTallyAuth),
+            Certificate::VotingPowerSnapshot(_) => Some(SigningContext::VotingPowerSnapshotAuth),
+            Certificate::AmountStakeDelegation(_) => {
+                Some(SigningContext::AmountStakeDelegationAuth)
+            }
+            Certificate::RewardsRedelegation(_) => Some(SigningContext::RewardsRedelegationAuth),
+            Certificate::Extension(_) => Some(SigningContext::ExtensionAuth),
+            Certificate::VotePlanCancel(_) => Some(SigningContext::VotePlanCancelAuth),
+            Certificate::OwnerStakeDelegation(_)
+            | Certificate::VoteCast(_)
+            | Certificate::MintToken(_)
+            | Certificate::NameClaim(_)
+            | Certificate::NameRelease(_)
+            | Certificate::SnapshotVoteCast(_)
+            | Certificate::VoteCastBatch(_) => None,
+        }
+    }
+}
+
+/// The layouts of all the signing contexts
+pub fn registry() -> impl Iterator<Item = SigningLayout> {
+    SigningContext::ALL.into_iter().map(SigningContext::layout)
+}
+
+impl SigningLayout {
+    /// size of the signed data, if all its fields have a fixed size
+    pub fn signed_size(&self) -> Option<usize> {
+        self.fields
+            .iter()
+            .try_fold(0, |total, field| match field.size {
+                FieldSize::Fixed(size) => Some(total + size),
+                FieldSize::Variable => None,
+            })
+    }
+
+    /// Whether the same key could produce a signature valid in both contexts,
+    /// i.e. the data signed in one context could be mistaken for the data
+    /// signed in the other.
+    ///
+    /// The contexts are separated if they use different key algorithms, if
+    /// their domain tags differ, or if their signed data always have a
+    /// different size.
+    pub fn may_collide(&self, other: &SigningLayout) -> bool {
+        if self.algorithm != other.algorithm {
+            return false;
+        }
+        if let (
+            DomainTag::Explicit { offset, bytes },
+            DomainTag::Explicit {
+                offset: other_offset,
+                bytes: other_bytes,
+            },
+        ) = (self.tag, other.tag)
+        {
+            let start = offset.max(other_offset);
+            let end = (offset + bytes.len()).min(other_offset + other_bytes.len());
+            if start < end
+                && bytes[start - offset..end - offset]
+                    != other_bytes[start - other_offset..end - other_offset]
+            {
+                return false;
+            }
+        }
+        match (self.signed_size(), other.signed_size()) {
+            (Some(size), Some(other_size)) => size == other_size,
+            _ => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::SpendingCounter;
+    use crate::date::BlockDate;
+    use crate::header::{BlockVersion, Header};
+    use crate::key::Hash;
+    use crate::legacy::{MigrationChallenge, OldAddress};
+    use crate::testing::TestGen;
+    use crate::transaction::{
+        TransactionSignData, TransactionSignDataHash, WitnessAccountData, WitnessMultisigData,
+        WitnessUtxoData, WitnessUtxoVersion,
+    };
+    use chain_addr::{Address, Discrimination, Kind};
+    use quickcheck_macros::quickcheck;
+
+    // The certificate authentications carry no domain tag: they all sign the
+    // transaction binding data, so they may collide with each other and with
+    // every other Ed25519 context. They are only told apart by the structure
+    // of the signed data, see the module documentation, any other context has
+    // to be separated by its tag, its algorithm or its size.
+    const SHARED_CONTEXTS: &[SigningContext] = &[
+        SigningContext::StakeDelegationAuth,
+        SigningContext::PoolRegistrationAuth,
+        SigningContext::PoolRetirementAuth,
+        SigningContext::PoolUpdateAuth,
+        SigningContext::UpdateProposalAuth,
+        SigningContext::UpdateVoteAuth,
+        SigningContext::VotePlanAuth,
+        SigningContext::VoteTallyAuth,
+        SigningContext::EncryptedVoteTallyAuth,
+        SigningContext::VotingPowerSnapshotAuth,
+        SigningContext::AmountStakeDelegationAuth,
+        SigningContext::RewardsRedelegationAuth,
+        SigningContext::ExtensionAuth,
+        SigningContext::VotePlanCancelAuth,
+    ];
+
+    #[test]
+    pub fn contexts_do_not_collide() {
+        let layouts: Vec<_> = registry().collect();
+        for (i, layout) in layouts.iter().enumerate() {
+            for other in layouts.iter().skip(i + 1) {
+                let shared = SHARED_CONTEXTS.contains(&layout.context)
+                    || SHARED_CONTEXTS.contains(&other.context);
+                assert!(
+                    shared || !layout.may_collide(other),
+                    "{:?} and {:?} collide",
+                    layout.context,
+                    other.context
+                );
+            }
+        }
+    }
+
+    #[test]
+    pub fn only_the_shared_contexts_are_untagged() {
+        for layout in registry() {
+            assert_eq!(
+                layout.tag == DomainTag::None,
+                SHARED_CONTEXTS.contains(&layout.context),
+                "{:?}: the untagged contexts are exactly the shared ones",
+                layout.context
+            );
+        }
+    }
+
+    /// the untagged contexts are only told apart by the structure of the
+    /// transaction binding data, they are all certificate authentications
+    #[test]
+    pub fn untagged_contexts_sign_the_transaction_binding_data() {
+        for layout in registry().filter(|layout| layout.tag == DomainTag::None) {
+            assert_eq!(
+                layout.fields, TRANSACTION_BINDING_FIELDS,
+                "{:?} is not tagged",
+                layout.context
+            );
+        }
+    }
+
+    #[quickcheck]
+    fn every_authenticated_certificate_is_registered(certificate: Certificate) -> bool {
+        match SigningContext::of_certificate(&certificate) {
+            None => !certificate.need_auth(),
+            Some(context) => {
+                certificate.need_auth()
+                    && SigningContext::ALL.contains(&context)
+                    && context.layout().tag == DomainTag::None
+            }
+        }
+    }
+
+    #[test]
+    pub fn registry_matches_migration_challenge() {
+        let destination = Address(Discrimination::Test, Kind::Single(TestGen::public_key()));
+        let challenge = MigrationChallenge::new(
+            &TestGen::hash(),
+            &OldAddress::new(vec![1, 2, 3]),
+            &destination,
+            BlockDate::first(),
+        );
+        let layout = SigningContext::MigrationChallenge.layout();
+        let data = challenge.as_ref();
+        match layout.tag {
+            DomainTag::Explicit { offset, bytes } => {
+                assert_eq!(&data[offset..offset + bytes.len()], bytes)
+            }
+            DomainTag::None => panic!("the migration challenge is not tagged"),
+        }
+        let fixed_size = 16 + 32 + 4 + 4;
+        assert_eq!(data.len(), fixed_size + 3 + destination.to_bytes().len());
+    }
+
+    #[test]
+    pub fn registry_matches_witness_data() {
+        let block0 = Hash::hash_bytes(&[1, 2, 3]);
+        let sign_data: TransactionSignData = vec![4, 5, 6].into();
+        let sign_data_hash = TransactionSignDataHash::digest(&sign_data);
+        let counter = SpendingCounter::zero();
+
+        let signed = [
+            (
+                SigningContext::LegacyUtxoWitness,
+                WitnessUtxoData::new(&block0, &sign_data_hash, WitnessUtxoVersion::Legacy)
+                    .as_ref()
+                    .to_vec(),
+            ),
+            (
+                SigningContext::UtxoWitness,
+                WitnessUtxoData::new(&block0, &sign_data_hash, WitnessUtxoVersion::Normal)
+                    .as_ref()
+                    .to_vec(),
+            ),
+            (
+                SigningContext::AccountWitness,
+                WitnessAccountData::new(&block0, &sign_data_hash, counter)
+                    .as_ref()
+                    .to_vec(),
+            ),
+            (
+                SigningContext::MultisigWitness,
+                WitnessMultisigData::new(&block0, &sign_data_hash, counter)
+                    .as_ref()
+                    .to_vec(),
+            ),
+        ];
+        for (context, data) in signed.iter() {
+            let layout = context.layout();
+            if let Some(size) = layout.signed_size() {
+                assert_eq!(size, data.len());
+            }
+            match layout.tag {
+                DomainTag::Explicit { offset, bytes } => {
+                    assert_eq!(&data[offset..offset + bytes.len()], bytes)
+                }
+                DomainTag::None => panic!("witness context {:?} is not tagged", context),
+            }
+        }
+    }
+
+    #[quickcheck]
+    fn registry_matches_header_auth_data(header: Header) -> bool {
+        let context = match header.block_version() {
+            BlockVersion::Genesis => return true,
+            BlockVersion::Ed25519Signed => SigningContext::BftHeader,
+            BlockVersion::KesVrfproof => SigningContext::GenesisPraosHeader,
+            BlockVersion::Ed25519SignedMerkle => SigningContext::BftMerkleHeader,
+            BlockVersion::KesVrfproofMerkle => SigningContext::GenesisPraosMerkleHeader,
+        };
+        let layout = context.layout();
+        let data = header.as_auth_slice();
+        let version = header.block_version().to_u16().to_be_bytes();
+        let tagged = match layout.tag {
+            DomainTag::Explicit { offset, bytes } => offset == 0 && bytes == version,
+            DomainTag::None => false,
+        };
+        tagged && layout.signed_size() == Some(data.len()) && data[0..2] == version
+    }
+
+    #[quickcheck]
+    fn registry_matches_bft_endorsement_data(header: Header) -> bool {
+        if header.get_bft_leader_id().is_none() {
+            return true;
+        }
+        let layout = SigningContext::BftEndorsement.layout();
+        let data = BftEndorsement::signed_data(header.as_auth_slice());
+        let tagged = match layout.tag {
+            DomainTag::Explicit { offset, bytes } => data[offset..].starts_with(bytes),
+            DomainTag::None => false,
+        };
+        tagged && layout.signed_size() == Some(data.len())
+    }
+}
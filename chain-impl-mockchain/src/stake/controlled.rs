@@ -1,5 +1,6 @@
 use crate::{
     account::{self, Identifier},
+    date::Epoch,
     rewards::Ratio,
     stake::Stake,
     utxo,
@@ -21,17 +22,20 @@ impl StakeControl {
         Self::default()
     }
 
-    fn update_accounts(&self, accounts: &account::Ledger) -> Self {
+    fn update_accounts(&self, accounts: &account::Ledger, epoch: Epoch) -> Self {
         accounts
             .iter()
             .fold(self.clone(), |sc, (identifier, account)| {
-                sc.add_to(identifier.clone(), Stake::from_value(account.value()))
+                sc.add_to(
+                    identifier.clone(),
+                    Stake::from_value_at(account.value(), epoch),
+                )
             })
     }
 
-    fn update_utxos(&self, utxos: &utxo::Ledger<Address>) -> Self {
+    fn update_utxos(&self, utxos: &utxo::Ledger<Address>, epoch: Epoch) -> Self {
         utxos.values().fold(self.clone(), |sc, output| {
-            let stake = Stake::from_value(output.value);
+            let stake = Stake::from_value_at(output.value, epoch);
 
             // We're only interested in "group" addresses
             // (i.e. containing a spending key and a stake key).
@@ -53,8 +57,16 @@ impl StakeControl {
         })
     }
 
-    pub fn new_with(accounts: &account::Ledger, utxos: &utxo::Ledger<Address>) -> Self {
-        Self::new().update_accounts(accounts).update_utxos(utxos)
+    /// the stake controlled by the balances of the accounts and utxos,
+    /// snapshotted at the given epoch
+    pub fn new_with(
+        accounts: &account::Ledger,
+        utxos: &utxo::Ledger<Address>,
+        epoch: Epoch,
+    ) -> Self {
+        Self::new()
+            .update_accounts(accounts, epoch)
+            .update_utxos(utxos, epoch)
     }
 
     pub fn total(&self) -> Stake {
@@ -181,6 +193,7 @@ mod tests {
     use super::StakeControl;
     use crate::{
         account::{self, Identifier},
+        date::Epoch,
         rewards::Ratio,
         stake::Stake,
         testing::{utxo::ArbitaryLedgerUtxo, TestGen},
@@ -415,7 +428,7 @@ mod tests {
 
     #[quickcheck]
     pub fn stake_control_from_ledger(accounts: account::Ledger, utxos: ArbitaryLedgerUtxo) {
-        let stake_control = StakeControl::new_with(&accounts, &utxos.0);
+        let stake_control = StakeControl::new_with(&accounts, &utxos.0, 0);
         //verify sum
         let accounts = accounts.get_total_value().unwrap();
         let utxo_or_group = utxos.0.values().map(|x| x.value).sum();
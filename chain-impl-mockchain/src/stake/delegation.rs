@@ -35,9 +35,38 @@ impl PoolLastRewards {
     }
 }
 
+/// Number of epochs for which the performance of a pool is kept in its
+/// state, the oldest record is dropped when a new one exceeds it
+pub const POOL_PERFORMANCE_HISTORY_DEPTH: usize = 16;
+
+/// Blocks produced by a pool during an epoch, against the blocks it was
+/// expected to produce given its share of the stake
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PoolPerformance {
+    pub epoch: Epoch,
+    pub expected: u32,
+    pub produced: u32,
+}
+
+impl PoolPerformance {
+    /// Scale the value by the ratio of produced to expected blocks, capped
+    /// to 1. A pool which was not expected to produce any block keeps the
+    /// full value.
+    pub fn scale(&self, value: Value) -> Value {
+        if self.produced >= self.expected {
+            value
+        } else {
+            let scaled = value.0 as u128 * self.produced as u128 / self.expected as u128;
+            Value(scaled as u64)
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PoolState {
     pub last_rewards: PoolLastRewards,
+    /// performances of the pool over the last epochs, oldest first
+    pub(crate) performances: Vec<PoolPerformance>,
     pub registration: Arc<PoolRegistration>,
 }
 
@@ -45,10 +74,37 @@ impl PoolState {
     pub fn new(reg: PoolRegistration) -> Self {
         PoolState {
             last_rewards: PoolLastRewards::default(),
+            performances: Vec::new(),
             registration: Arc::new(reg),
         }
     }
 
+    /// The performance of the pool over the last epoch for which it was
+    /// recorded
+    pub fn last_performance(&self) -> Option<&PoolPerformance> {
+        self.performances.last()
+    }
+
+    /// The performance of the pool during the given epoch, if it is still
+    /// in the history
+    pub fn performance_at(&self, epoch: Epoch) -> Option<&PoolPerformance> {
+        self.performances.iter().find(|p| p.epoch == epoch)
+    }
+
+    /// The performances of the pool over at most the last
+    /// [`POOL_PERFORMANCE_HISTORY_DEPTH`] epochs, oldest first
+    pub fn performances(&self) -> &[PoolPerformance] {
+        &self.performances
+    }
+
+    pub(crate) fn record_performance(&mut self, performance: PoolPerformance) {
+        self.performances.push(performance);
+        if self.performances.len() > POOL_PERFORMANCE_HISTORY_DEPTH {
+            let excess = self.performances.len() - POOL_PERFORMANCE_HISTORY_DEPTH;
+            self.performances.drain(..excess);
+        }
+    }
+
     pub fn current_pool_registration_hash(&self) -> PoolRegistrationHash {
         self.registration.to_id()
     }
@@ -160,6 +216,22 @@ impl PoolsState {
         Ok(())
     }
 
+    pub fn stake_pool_set_performance(
+        &mut self,
+        pool_id: &PoolId,
+        performance: PoolPerformance,
+    ) -> Result<(), PoolError> {
+        self.stake_pools = self
+            .stake_pools
+            .replace_with(pool_id, |st| {
+                let mut st = st.clone();
+                st.record_performance(performance);
+                st
+            })
+            .map_err(|_| PoolError::NotFound(pool_id.clone()))?;
+        Ok(())
+    }
+
     pub fn register_stake_pool(&self, owner: PoolRegistration) -> Result<Self, PoolError> {
         let id = owner.to_id();
         let new_pools = self
@@ -208,10 +280,16 @@ mod tests {
     impl Arbitrary for PoolState {
         fn arbitrary<G: Gen>(gen: &mut G) -> Self {
             let registration = Arc::new(PoolRegistration::arbitrary(gen));
-            PoolState {
+            let mut state = PoolState {
                 last_rewards: PoolLastRewards::arbitrary(gen),
+                performances: Vec::new(),
                 registration,
+            };
+            let size = usize::arbitrary(gen) % (POOL_PERFORMANCE_HISTORY_DEPTH + 1);
+            for _ in 0..size {
+                state.record_performance(PoolPerformance::arbitrary(gen));
             }
+            state
         }
     }
 
@@ -225,6 +303,41 @@ mod tests {
         }
     }
 
+    impl Arbitrary for PoolPerformance {
+        fn arbitrary<G: Gen>(gen: &mut G) -> Self {
+            PoolPerformance {
+                epoch: u32::arbitrary(gen),
+                expected: u32::arbitrary(gen),
+                produced: u32::arbitrary(gen),
+            }
+        }
+    }
+
+    #[quickcheck]
+    pub fn pool_performance_scale_is_capped(performance: PoolPerformance, value: Value) -> bool {
+        let scaled = performance.scale(value);
+        scaled <= value && (performance.produced < performance.expected || scaled == value)
+    }
+
+    #[test]
+    pub fn pool_performance_history_is_bounded() {
+        let mut state = PoolState::new(PoolRegistration::arbitrary(
+            &mut quickcheck::StdThreadGen::new(10),
+        ));
+        let depth = POOL_PERFORMANCE_HISTORY_DEPTH as u32;
+        for epoch in 0..depth * 2 {
+            state.record_performance(PoolPerformance {
+                epoch,
+                expected: 2,
+                produced: 1,
+            });
+        }
+        assert_eq!(state.performances().len(), POOL_PERFORMANCE_HISTORY_DEPTH);
+        assert!(state.performance_at(depth - 1).is_none());
+        assert_eq!(state.performance_at(depth).unwrap().epoch, depth);
+        assert_eq!(state.last_performance().unwrap().epoch, depth * 2 - 1);
+    }
+
     #[quickcheck]
     pub fn delegation_state_tests(
         delegation_state: PoolsState,
@@ -1,9 +1,11 @@
 use super::stake::Stake;
 use crate::{
     account,
-    accounting::account::DelegationType,
+    accounting::account::{DelegationAmounts, DelegationType},
     certificate::{PoolId, PoolRegistration},
+    date::Epoch,
     utxo,
+    value::Value,
 };
 use chain_addr::{Address, Kind};
 use std::collections::{hash_map, HashMap};
@@ -24,6 +26,9 @@ pub struct StakeDistribution {
     /// For each stake pool, the total stake value, and the value for the
     /// stake pool members.
     pub to_pools: HashMap<PoolId, PoolStakeInformation>,
+    /// For each stake pool, the stake delegated by the accounts its
+    /// delegator policy does not allow, which is not part of its stake
+    pub excluded: HashMap<PoolId, Stake>,
 }
 
 impl StakeDistribution {
@@ -42,6 +47,25 @@ impl PoolStakeInformation {
     pub fn add_value(&mut self, id: &account::Identifier, s: Stake) {
         self.stake.add(id.clone(), s)
     }
+
+    /// whether the delegator policy of the pool allows the account
+    pub fn allows_delegator(&self, id: &account::Identifier) -> bool {
+        self.registration
+            .as_ref()
+            .map_or(true, |reg| reg.allows_delegator(id))
+    }
+
+    /// stake delegated to the pool by its owners, None if the pool is not
+    /// registered anymore
+    pub fn owners_stake(&self) -> Option<Stake> {
+        self.registration.as_ref().map(|reg| {
+            Stake::sum(
+                reg.owners
+                    .iter()
+                    .filter_map(|owner| self.stake.accounts.get(&owner.clone().into()).copied()),
+            )
+        })
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -85,6 +109,7 @@ impl StakeDistribution {
             unassigned: Stake::zero(),
             dangling: Stake::zero(),
             to_pools: HashMap::new(),
+            excluded: HashMap::new(),
         }
     }
 
@@ -105,6 +130,43 @@ impl StakeDistribution {
     pub fn get_distribution(&self, pool_id: &PoolId) -> Option<&PoolStakeInformation> {
         self.to_pools.get(pool_id)
     }
+
+    /// Return the stake excluded from the pool by its delegator policy.
+    pub fn get_excluded_stake_for(&self, pool_id: &PoolId) -> Stake {
+        self.excluded
+            .get(pool_id)
+            .copied()
+            .unwrap_or_else(Stake::zero)
+    }
+
+    /// Return the stake excluded from all the pools by their delegator policy.
+    pub fn excluded_stake(&self) -> Stake {
+        Stake::sum(self.excluded.values().copied())
+    }
+}
+
+/// Add the stake delegated by the account to the pool, or to the excluded stake
+/// of the pool if its delegator policy doesn't allow the account. Return false
+/// if the pool doesn't exist.
+fn delegate_to_pool(
+    sd: &mut StakeDistribution,
+    pool_id: &PoolId,
+    account_identifier: &account::Identifier,
+    value: Stake,
+) -> bool {
+    match sd.to_pools.get_mut(pool_id) {
+        None => false,
+        Some(pool_info) if pool_info.allows_delegator(account_identifier) => {
+            pool_info.add_value(account_identifier, value);
+            true
+        }
+        Some(_) => {
+            *sd.excluded
+                .entry(pool_id.clone())
+                .or_insert_with(Stake::zero) += value;
+            true
+        }
+    }
 }
 
 fn assign_account_value(
@@ -118,9 +180,8 @@ fn assign_account_value(
         DelegationType::Full(ref pool_id) => {
             // if the pool exists, we add value to this pool distribution,
             // otherwise it get added to the dangling sum
-            match sd.to_pools.get_mut(pool_id) {
-                None => sd.dangling += value,
-                Some(pool_info) => pool_info.add_value(account_identifier, value),
+            if !delegate_to_pool(sd, pool_id, account_identifier, value) {
+                sd.dangling += value
             }
         }
         DelegationType::Ratio(dr) => {
@@ -132,12 +193,10 @@ fn assign_account_value(
                 let mut r = sin.remaining;
                 for (pool_id, ratio) in dr.pools().iter() {
                     let pool_value = sin.parts.scale(*ratio as u32);
-                    match sd.to_pools.get_mut(pool_id) {
-                        None => sd.dangling += pool_value,
-                        Some(pool_info) => {
-                            pool_info.add_value(account_identifier, pool_value + r);
-                            r = Stake::zero();
-                        }
+                    if delegate_to_pool(sd, pool_id, account_identifier, pool_value + r) {
+                        r = Stake::zero();
+                    } else {
+                        sd.dangling += pool_value
                     }
                 }
                 // if r is not zero already, then we failed to assign it to anything, so just consider it as dangling
@@ -151,16 +210,61 @@ fn assign_account_value(
     }
 }
 
+/// Assign the stake of an account delegating explicit amounts. The
+/// redelegated rewards are the rewards received by the account since it
+/// enabled their redelegation, the amounts do not already cover them.
+fn assign_account_amounts(
+    sd: &mut StakeDistribution,
+    account_identifier: &account::Identifier,
+    amounts: &DelegationAmounts,
+    value: Stake,
+    redelegated_rewards: Stake,
+    epoch: Epoch,
+) {
+    // the amounts need to be covered by the stake at the time of the snapshot,
+    // otherwise none of it is delegated. What is left once the amounts are
+    // assigned is not delegated, apart from the redelegated rewards still in
+    // the balance, shared in the proportions of the amounts.
+    let total = Stake::from_value_at(amounts.total(), epoch);
+    match value.checked_sub(total) {
+        None => sd.unassigned += value,
+        Some(remaining) => {
+            let redelegated = std::cmp::min(remaining, redelegated_rewards);
+            let shares: Vec<Stake> = amounts
+                .pools()
+                .iter()
+                .map(|(_, amount)| {
+                    let share = redelegated.0 as u128 * amount.0 as u128 / total.0 as u128;
+                    Stake(share as u64)
+                })
+                .collect();
+            // the first pool takes the rounding of the shares
+            let mut r = Stake(redelegated.0 - Stake::sum(shares.iter().copied()).0);
+            for ((pool_id, amount), share) in amounts.pools().iter().zip(shares) {
+                let pool_value = Stake::from_value_at(*amount, epoch) + share + r;
+                r = Stake::zero();
+                if !delegate_to_pool(sd, pool_id, account_identifier, pool_value) {
+                    sd.dangling += pool_value
+                }
+            }
+            sd.unassigned += Stake(remaining.0 - redelegated.0)
+        }
+    }
+}
+
 /// Calculate the Stake Distribution where the source of stake is coming from utxos and accounts,
 /// and where the main targets is to calculate each value associated with *known* stake pools.
 ///
 /// Everything that is linked to a stake pool that doesn't exist, will be added to dangling stake,
 /// whereas all the utxo / accounts that doesn't have any delegation setup, will be counted towards
 /// the unassigned stake.
+/// The stake delegated to a pool by accounts its delegator policy doesn't allow is counted
+/// towards the excluded stake of the pool.
 pub fn get_distribution(
     accounts: &account::Ledger,
     dstate: &PoolsState,
     utxos: &utxo::Ledger<Address>,
+    epoch: Epoch,
 ) -> StakeDistribution {
     let mut distribution = StakeDistribution {
         unassigned: Stake::zero(),
@@ -178,15 +282,32 @@ pub fn get_distribution(
                 )
             })
             .collect(),
+        excluded: HashMap::new(),
     };
 
     for (identifier, account_state) in accounts.iter() {
-        assign_account_value(
-            &mut distribution,
-            identifier,
-            account_state.delegation(),
-            Stake::from_value(account_state.value()),
-        )
+        let value = Stake::from_value_at(account_state.value(), epoch);
+        match account_state.delegation_amounts() {
+            Some(amounts) => assign_account_amounts(
+                &mut distribution,
+                identifier,
+                amounts,
+                value,
+                Stake::from_value_at(
+                    account_state
+                        .redelegated_rewards
+                        .unwrap_or_else(Value::zero),
+                    epoch,
+                ),
+                epoch,
+            ),
+            None => assign_account_value(
+                &mut distribution,
+                identifier,
+                account_state.delegation(),
+                value,
+            ),
+        }
     }
 
     for output in utxos.values() {
@@ -209,15 +330,19 @@ pub fn get_distribution(
                         "internal error: group's account should always be created: {:?}",
                         err
                     ),
+                    // the amounts are delegated from the balance of the account only
+                    Ok(st) if st.delegation_amounts().is_some() => {
+                        distribution.unassigned += Stake::from_value_at(output.value, epoch)
+                    }
                     Ok(st) => assign_account_value(
                         &mut distribution,
                         &identifier,
                         st.delegation(),
-                        Stake::from_value(output.value),
+                        Stake::from_value_at(output.value, epoch),
                     ),
                 }
             }
-            Kind::Single(_) => distribution.unassigned += Stake::from_value(output.value),
+            Kind::Single(_) => distribution.unassigned += Stake::from_value_at(output.value, epoch),
         }
     }
 
@@ -231,8 +356,8 @@ mod tests {
     use crate::accounting::account::DelegationType;
     use crate::stake::{delegation::PoolsState, Stake};
     use crate::{
-        account::{AccountAlg, DelegationRatio, Identifier},
-        certificate::PoolRegistration,
+        account::{AccountAlg, DelegationAmounts, DelegationRatio, Identifier},
+        certificate::{DelegatorPattern, DelegatorPolicy, PoolRegistration},
         fragment::FragmentId,
         testing::{
             arbitrary::{utils as arbitrary_utils, ArbitraryAddressDataValueVec},
@@ -344,7 +469,8 @@ mod tests {
                 })
                 .collect();
 
-            let active_stake_pool = PoolRegistration::arbitrary(gen);
+            let mut active_stake_pool = PoolRegistration::arbitrary(gen);
+            active_stake_pool.delegator_policy = None;
             let retired_stake_pool = PoolRegistration::arbitrary(gen);
 
             StakeDistributionArbitraryData {
@@ -375,18 +501,18 @@ mod tests {
             self.get_sum_from_account_type(&self.assigned_accounts)
                 + self.get_sum_from_utxo_type(&self.groups)
                 + self.get_sum_from_utxo_type(&self.groups_single_account)
-                + Stake::from_value(self.single_account.1)
+                + Stake::from(self.single_account.1)
         }
 
         fn get_sum_from_utxo_type(
             &self,
             utxos: &[(FragmentId, TransactionIndex, Output<Address>)],
         ) -> Stake {
-            Stake::sum(utxos.iter().map(|(_, _, x)| Stake::from_value(x.value)))
+            Stake::sum(utxos.iter().map(|(_, _, x)| Stake::from(x.value)))
         }
 
         fn get_sum_from_account_type(&self, accounts: &[(Identifier, Value)]) -> Stake {
-            Stake::sum(accounts.iter().map(|(_, x)| Stake::from_value(*x)))
+            Stake::sum(accounts.iter().map(|(_, x)| Stake::from(*x)))
         }
     }
 
@@ -474,7 +600,7 @@ mod tests {
         }
 
         // verify
-        let distribution = super::get_distribution(&accounts, &dstate, &utxos);
+        let distribution = super::get_distribution(&accounts, &dstate, &utxos, 0);
 
         if distribution.unassigned != stake_distribution_data.calculate_unassigned() {
             return TestResult::error(format!(
@@ -621,7 +747,7 @@ mod tests {
     pub fn dangling_stake_multiplied() {
         let mut stake_distribution = StakeDistribution::empty();
         let value = Value(10);
-        let stake = Stake::from_value(value);
+        let stake = Stake::from(value);
         let account_identifier = TestGen::identifier();
         let no_of_parts = 8u8;
         let parts = [1, 2, 2, 3];
@@ -642,4 +768,138 @@ mod tests {
         );
         assert_eq!(stake_distribution.dangling, stake);
     }
+
+    #[test]
+    pub fn delegated_amounts_are_covered_by_the_stake() {
+        let account_identifier = TestGen::identifier();
+        let active_pool = StakePoolBuilder::new().build();
+        let retired_pool = StakePoolBuilder::new().build();
+        let amounts = DelegationAmounts::new(vec![
+            (active_pool.id(), Value(60)),
+            (retired_pool.id(), Value(30)),
+        ])
+        .unwrap();
+        let distribution = |stake: u64| {
+            let mut stake_distribution = StakeDistribution::empty();
+            stake_distribution.to_pools.insert(
+                active_pool.id(),
+                PoolStakeInformation {
+                    registration: None,
+                    stake: PoolStakeDistribution::new(),
+                },
+            );
+            assign_account_amounts(
+                &mut stake_distribution,
+                &account_identifier,
+                &amounts,
+                Stake(stake),
+                Stake::zero(),
+                0,
+            );
+            stake_distribution
+        };
+
+        // the remaining stake is not delegated
+        let covered = distribution(100);
+        assert_eq!(covered.get_stake_for(&active_pool.id()), Some(Stake(60)));
+        assert_eq!(covered.dangling, Stake(30));
+        assert_eq!(covered.unassigned, Stake(10));
+
+        // the amounts are not covered, nothing is delegated
+        let uncovered = distribution(80);
+        assert_eq!(uncovered.get_stake_for(&active_pool.id()), Some(Stake(0)));
+        assert_eq!(uncovered.dangling, Stake::zero());
+        assert_eq!(uncovered.unassigned, Stake(80));
+    }
+
+    #[test]
+    pub fn redelegated_rewards_follow_the_amounts() {
+        let account_identifier = TestGen::identifier();
+        let first_pool = StakePoolBuilder::new().build();
+        let second_pool = StakePoolBuilder::new().build();
+        let amounts = DelegationAmounts::new(vec![
+            (first_pool.id(), Value(60)),
+            (second_pool.id(), Value(30)),
+        ])
+        .unwrap();
+        let distribution = |stake: u64, redelegated_rewards: u64| {
+            let mut stake_distribution = StakeDistribution::empty();
+            for pool in [&first_pool, &second_pool] {
+                stake_distribution.to_pools.insert(
+                    pool.id(),
+                    PoolStakeInformation {
+                        registration: None,
+                        stake: PoolStakeDistribution::new(),
+                    },
+                );
+            }
+            assign_account_amounts(
+                &mut stake_distribution,
+                &account_identifier,
+                &amounts,
+                Stake(stake),
+                Stake(redelegated_rewards),
+                0,
+            );
+            stake_distribution
+        };
+
+        // the first pool takes the rounding of the shares
+        let rewarded = distribution(120, 20);
+        assert_eq!(rewarded.get_stake_for(&first_pool.id()), Some(Stake(74)));
+        assert_eq!(rewarded.get_stake_for(&second_pool.id()), Some(Stake(36)));
+        assert_eq!(rewarded.unassigned, Stake(10));
+
+        // only the rewards still in the balance are redelegated
+        let spent = distribution(95, 20);
+        assert_eq!(spent.get_stake_for(&first_pool.id()), Some(Stake(64)));
+        assert_eq!(spent.get_stake_for(&second_pool.id()), Some(Stake(31)));
+        assert_eq!(spent.unassigned, Stake::zero());
+    }
+
+    #[test]
+    pub fn delegator_policy_excludes_stake() {
+        let owner = TestGen::public_key();
+        let allowed = TestGen::identifier();
+        let denied = TestGen::identifier();
+        let stake_pool = StakePoolBuilder::new()
+            .with_owners(vec![owner.clone()])
+            .with_delegator_policy(
+                DelegatorPolicy::allow(vec![DelegatorPattern::Account(allowed.clone())]).unwrap(),
+            )
+            .build();
+
+        let mut stake_distribution = StakeDistribution::empty();
+        stake_distribution.to_pools.insert(
+            stake_pool.id(),
+            PoolStakeInformation {
+                registration: Some(Arc::new(stake_pool.info())),
+                stake: PoolStakeDistribution::new(),
+            },
+        );
+        let delegation_type = DelegationType::Full(stake_pool.id());
+        for (account_identifier, stake) in [
+            (Identifier::from(owner), Stake(100)),
+            (allowed, Stake(20)),
+            (denied, Stake(3)),
+        ] {
+            assign_account_value(
+                &mut stake_distribution,
+                &account_identifier,
+                &delegation_type,
+                stake,
+            );
+        }
+
+        assert_eq!(
+            stake_distribution.get_stake_for(&stake_pool.id()),
+            Some(Stake(120))
+        );
+        assert_eq!(
+            stake_distribution.get_excluded_stake_for(&stake_pool.id()),
+            Stake(3)
+        );
+        assert_eq!(stake_distribution.excluded_stake(), Stake(3));
+        assert_eq!(stake_distribution.dangling, Stake::zero());
+    }
 }
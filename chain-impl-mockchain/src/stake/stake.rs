@@ -1,4 +1,5 @@
-use crate::value::Value;
+use crate::date::Epoch;
+use crate::value::{checked_sum, SumOverflow, Value};
 use std::ops::{Add, AddAssign};
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
@@ -20,10 +21,28 @@ pub struct SplitValueIn {
 }
 
 impl Stake {
+    /// Raw conversion of a value, which doesn't tell when the value
+    /// became stake
+    #[deprecated(note = "use `Stake::from_value_at` with the epoch of the snapshot")]
     pub fn from_value(v: Value) -> Self {
         Stake(v.0)
     }
 
+    /// The stake of a balance snapshotted at the given epoch.
+    ///
+    /// A value is a live balance, changed by every fragment applied to the
+    /// ledger, while the stake is the balance at the time the stake
+    /// distribution is computed. The balances of the stake distribution are
+    /// all converted here, and the conversions are traced with their epoch
+    /// when the `tracing` feature is enabled.
+    pub fn from_value_at(value: Value, epoch: Epoch) -> Self {
+        #[cfg(feature = "tracing")]
+        tracing::trace!(epoch, value = value.0, "balance snapshotted as stake");
+        #[cfg(not(feature = "tracing"))]
+        let _ = epoch;
+        Stake(value.0)
+    }
+
     pub fn zero() -> Self {
         Stake(0)
     }
@@ -35,6 +54,15 @@ impl Stake {
         values.fold(Stake(0), |acc, v| acc + v)
     }
 
+    /// Sum the stakes, reporting the operand which overflowed, see
+    /// [`Value::checked_sum`]
+    pub fn checked_sum<I>(values: I) -> Result<Self, SumOverflow<Self>>
+    where
+        I: IntoIterator<Item = Self>,
+    {
+        checked_sum(values, Stake::zero(), |a, b| a.checked_add(b))
+    }
+
     #[must_use = "internal state is not modified"]
     pub fn checked_add(&self, rhs: Self) -> Option<Self> {
         self.0.checked_add(rhs.0).map(Self)
@@ -132,10 +160,16 @@ impl std::fmt::Display for Stake {
 mod tests {
     use super::*;
     use quickcheck::{Arbitrary, Gen};
+    use quickcheck_macros::quickcheck;
 
     impl Arbitrary for Stake {
         fn arbitrary<G: Gen>(g: &mut G) -> Self {
-            Stake::from_value(Arbitrary::arbitrary(g))
+            Stake::from(Value::arbitrary(g))
         }
     }
+
+    #[quickcheck]
+    fn stake_snapshot_keeps_the_value(value: Value, epoch: Epoch) -> bool {
+        u64::from(Stake::from_value_at(value, epoch)) == value.0
+    }
 }
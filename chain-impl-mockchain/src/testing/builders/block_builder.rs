@@ -2,7 +2,7 @@ use crate::{
     block::{self, Block},
     chaintypes::ChainLength,
     date::BlockDate,
-    fragment::{Contents, ContentsBuilder, Fragment},
+    fragment::{ContentsBuilder, Fragment},
     header::{BlockVersion, Header},
     key::Hash,
     testing::data::LeaderPair,
@@ -72,8 +72,8 @@ impl GenesisPraosBlockBuilder {
             panic!("date,chain_length or hash is not set");
         }
         let vrf_proof = TestGen::vrf_proof(stake_pool);
-        let contents: Contents = self.contents_builder.clone().into();
-        block::builder(BlockVersion::KesVrfproof, contents, |builder| {
+        let contents = self.contents_builder.clone();
+        block::builder_from_contents(BlockVersion::KesVrfproof, contents, |builder| {
             Ok::<_, ()>(
                 builder
                     .set_parent(
@@ -153,8 +153,8 @@ impl BftBlockBuilder {
         if self.date.is_none() || self.chain_length.is_none() || self.parent_id.is_none() {
             panic!("date,chain_length or hash is not set");
         }
-        let contents: Contents = self.contents_builder.clone().into();
-        block::builder(BlockVersion::Ed25519Signed, contents, |header_builder| {
+        let contents = self.contents_builder.clone();
+        block::builder_from_contents(BlockVersion::Ed25519Signed, contents, |header_builder| {
             Ok::<_, ()>(
                 header_builder
                     .set_parent(&self.parent_id.unwrap(), self.chain_length.unwrap())
@@ -1,14 +1,15 @@
 use crate::{
-    account::{DelegationType, Identifier},
+    account::{DelegationAmounts, DelegationType, Identifier},
     certificate::{
-        Certificate, OwnerStakeDelegation, PoolId, PoolRegistration, PoolRetirement, PoolUpdate,
-        StakeDelegation, UpdateProposal, UpdateProposalId, UpdateProposerId, UpdateVote,
-        UpdateVoterId, VotePlanId, VoteTally,
+        AmountStakeDelegation, Certificate, OwnerStakeDelegation, PoolId, PoolRegistration,
+        PoolRetirement, PoolUpdate, RewardsRedelegation, StakeDelegation, UpdateProposal,
+        UpdateProposalId, UpdateProposerId, UpdateVote, UpdateVoterId, VotePlanId, VoteTally,
     },
     config::ConfigParam,
     fragment::ConfigParams,
     testing::data::AddressData,
     transaction::UnspecifiedAccountIdentifier,
+    value::Value,
 };
 use chain_time::units::DurationSeconds;
 
@@ -25,6 +26,29 @@ pub fn build_stake_delegation_cert(
     })
 }
 
+pub fn build_amount_stake_delegation_cert(
+    pools: Vec<(PoolId, Value)>,
+    delegate_from: &AddressData,
+) -> Certificate {
+    let account_id = UnspecifiedAccountIdentifier::from_single_account(Identifier::from(
+        delegate_from.delegation_key(),
+    ));
+    Certificate::AmountStakeDelegation(AmountStakeDelegation {
+        account_id,
+        delegation: DelegationAmounts::new(pools).unwrap(),
+    })
+}
+
+pub fn build_rewards_redelegation_cert(enabled: bool, delegate_from: &AddressData) -> Certificate {
+    let account_id = UnspecifiedAccountIdentifier::from_single_account(Identifier::from(
+        delegate_from.delegation_key(),
+    ));
+    Certificate::RewardsRedelegation(RewardsRedelegation {
+        account_id,
+        enabled,
+    })
+}
+
 pub fn build_stake_pool_registration_cert(stake_pool: &PoolRegistration) -> Certificate {
     Certificate::PoolRegistration(stake_pool.clone())
 }
@@ -149,6 +149,22 @@ fn fragment(
             let tx = builder.set_payload_auth(&signature);
             Fragment::StakeDelegation(tx)
         }
+        Certificate::AmountStakeDelegation(s) => {
+            let builder = set_initial_ios(TxBuilder::new().set_payload(&s), inputs, outputs);
+            let signature = AccountBindingSignature::new_single(&builder.get_auth_data(), |d| {
+                keys[0].sign_slice(d.0)
+            });
+            let tx = builder.set_payload_auth(&signature);
+            Fragment::AmountStakeDelegation(tx)
+        }
+        Certificate::RewardsRedelegation(s) => {
+            let builder = set_initial_ios(TxBuilder::new().set_payload(&s), inputs, outputs);
+            let signature = AccountBindingSignature::new_single(&builder.get_auth_data(), |d| {
+                keys[0].sign_slice(d.0)
+            });
+            let tx = builder.set_payload_auth(&signature);
+            Fragment::RewardsRedelegation(tx)
+        }
         Certificate::PoolRegistration(s) => {
             let builder = set_initial_ios(TxBuilder::new().set_payload(&s), inputs, outputs);
             let signature = pool_owner_sign(&keys, &builder);
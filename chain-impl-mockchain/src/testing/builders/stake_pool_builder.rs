@@ -1,12 +1,12 @@
 use crate::{
-    certificate::{PoolPermissions, PoolRegistration},
+    certificate::{DelegatorPolicy, PoolPermissions, PoolRegistration, RewardAccount},
     key::GenesisPraosLeader,
     rewards::{Ratio, TaxType},
     testing::data::{AddressData, StakePool},
     transaction::AccountIdentifier,
     value::Value,
 };
-use chain_addr::Discrimination;
+use chain_addr::{Address, Discrimination};
 use chain_crypto::{Ed25519, KeyPair, PublicKey, RistrettoGroup2HashDh, SumEd25519_12};
 use chain_time::DurationSeconds;
 use std::num::NonZeroU64;
@@ -16,7 +16,10 @@ pub struct StakePoolBuilder {
     operators: Vec<PublicKey<Ed25519>>,
     pool_permissions: Option<PoolPermissions>,
     reward_account: bool,
+    reward_address: Option<Address>,
     tax_type: TaxType,
+    pledge: Value,
+    delegator_policy: Option<DelegatorPolicy>,
     alias: String,
 }
 
@@ -34,6 +37,7 @@ impl StakePoolBuilder {
             alias: "".to_owned(),
             pool_permissions: None,
             reward_account: false,
+            reward_address: None,
             tax_type: TaxType {
                 fixed: Value(1),
                 ratio: Ratio {
@@ -42,6 +46,8 @@ impl StakePoolBuilder {
                 },
                 max_limit: None,
             },
+            pledge: Value::zero(),
+            delegator_policy: None,
         }
     }
 
@@ -70,6 +76,13 @@ impl StakePoolBuilder {
         self
     }
 
+    /// designate an address to receive the rewards, instead of a new reward
+    /// account
+    pub fn with_reward_address(&mut self, reward_address: Address) -> &mut Self {
+        self.reward_address = Some(reward_address);
+        self
+    }
+
     pub fn with_ratio_tax_type(
         &mut self,
         numerator: u64,
@@ -91,6 +104,16 @@ impl StakePoolBuilder {
         self
     }
 
+    pub fn with_pledge(&mut self, pledge: Value) -> &mut Self {
+        self.pledge = pledge;
+        self
+    }
+
+    pub fn with_delegator_policy(&mut self, delegator_policy: DelegatorPolicy) -> &mut Self {
+        self.delegator_policy = Some(delegator_policy);
+        self
+    }
+
     pub fn build(&self) -> StakePool {
         let mut rng = rand_core::OsRng;
 
@@ -102,10 +125,12 @@ impl StakePoolBuilder {
             None => PoolPermissions::new(std::cmp::max(self.owners.len() as u8 / 2, 1)),
         };
 
-        let (reward_account, reward_identifier) = if self.reward_account {
+        let (reward_account, reward_identifier) = if let Some(address) = &self.reward_address {
+            (None, Some(RewardAccount::Address(address.clone())))
+        } else if self.reward_account {
             let account = AddressData::account(Discrimination::Test);
             let transaction_account = AccountIdentifier::Single(account.to_id());
-            (Some(account), Some(transaction_account))
+            (Some(account), Some(transaction_account.into()))
         } else {
             (None, None)
         };
@@ -118,6 +143,8 @@ impl StakePoolBuilder {
             permissions,
             rewards: self.tax_type,
             reward_account: reward_identifier,
+            pledge: self.pledge,
+            delegator_policy: self.delegator_policy.clone(),
             keys: GenesisPraosLeader {
                 vrf_public_key: pool_vrf.public_key().clone(),
                 kes_public_key: pool_kes.public_key().clone(),
@@ -1,8 +1,9 @@
 use crate::{
     certificate::{
         BftLeaderBindingSignature, Certificate, CertificatePayload, EncryptedVoteTally,
-        EncryptedVoteTallyProof, PoolOwnersSigned, PoolSignature, TallyProof, UpdateProposal,
-        UpdateVote, VotePlan, VotePlanProof, VoteTally,
+        EncryptedVoteTallyProof, ExtensionCertificateAuth, PoolOwnersSigned, PoolSignature,
+        TallyProof, UpdateProposal, UpdateVote, VotePlan, VotePlanCancel, VotePlanCancelProof,
+        VotePlanProof, VoteTally, VotingPowerSnapshot, VotingPowerSnapshotProof,
     },
     chaintypes::HeaderId,
     date::BlockDate,
@@ -116,6 +117,38 @@ impl TestTxCertBuilder {
                 let tx = builder.set_payload_auth(&signature);
                 Fragment::StakeDelegation(tx)
             }
+            Certificate::AmountStakeDelegation(s) => {
+                let builder = self.set_initial_ios(
+                    valid_until,
+                    TxBuilder::new().set_payload(s),
+                    funder,
+                    inputs,
+                    outputs,
+                    make_witness,
+                );
+                let signature =
+                    AccountBindingSignature::new_single(&builder.get_auth_data(), |d| {
+                        keys[0].sign_slice(d.0)
+                    });
+                let tx = builder.set_payload_auth(&signature);
+                Fragment::AmountStakeDelegation(tx)
+            }
+            Certificate::RewardsRedelegation(s) => {
+                let builder = self.set_initial_ios(
+                    valid_until,
+                    TxBuilder::new().set_payload(s),
+                    funder,
+                    inputs,
+                    outputs,
+                    make_witness,
+                );
+                let signature =
+                    AccountBindingSignature::new_single(&builder.get_auth_data(), |d| {
+                        keys[0].sign_slice(d.0)
+                    });
+                let tx = builder.set_payload_auth(&signature);
+                Fragment::RewardsRedelegation(tx)
+            }
             Certificate::PoolRegistration(s) => {
                 let builder = self.set_initial_ios(
                     valid_until,
@@ -256,6 +289,93 @@ impl TestTxCertBuilder {
                 let tx = builder.set_payload_auth(&());
                 Fragment::MintToken(tx)
             }
+            Certificate::NameClaim(name_claim) => {
+                let builder = self.set_initial_ios(
+                    valid_until,
+                    TxBuilder::new().set_payload(name_claim),
+                    funder,
+                    inputs,
+                    outputs,
+                    make_witness,
+                );
+                let tx = builder.set_payload_auth(&());
+                Fragment::NameClaim(tx)
+            }
+            Certificate::NameRelease(name_release) => {
+                let builder = self.set_initial_ios(
+                    valid_until,
+                    TxBuilder::new().set_payload(name_release),
+                    funder,
+                    inputs,
+                    outputs,
+                    make_witness,
+                );
+                let tx = builder.set_payload_auth(&());
+                Fragment::NameRelease(tx)
+            }
+            Certificate::Extension(extension) => {
+                let builder = self.set_initial_ios(
+                    valid_until,
+                    TxBuilder::new().set_payload(extension),
+                    funder,
+                    inputs,
+                    outputs,
+                    make_witness,
+                );
+                // the authentication is specific to the extension
+                let tx = builder.set_payload_auth(&ExtensionCertificateAuth::default());
+                Fragment::ExtensionCertificate(tx)
+            }
+            Certificate::VotingPowerSnapshot(snapshot) => {
+                let builder = self.set_initial_ios(
+                    valid_until,
+                    TxBuilder::new().set_payload(snapshot),
+                    funder,
+                    inputs,
+                    outputs,
+                    make_witness,
+                );
+                let committee_signature = voting_power_snapshot_sign(&keys, &builder);
+                let tx = builder.set_payload_auth(&committee_signature);
+                Fragment::VotingPowerSnapshot(tx)
+            }
+            Certificate::SnapshotVoteCast(vote_cast) => {
+                let builder = self.set_initial_ios(
+                    valid_until,
+                    TxBuilder::new().set_payload(vote_cast),
+                    funder,
+                    inputs,
+                    outputs,
+                    make_witness,
+                );
+                let tx = builder.set_payload_auth(&());
+                Fragment::SnapshotVoteCast(tx)
+            }
+            Certificate::VoteCastBatch(batch) => {
+                let builder = self.set_initial_ios(
+                    valid_until,
+                    TxBuilder::new().set_payload(batch),
+                    funder,
+                    inputs,
+                    outputs,
+                    make_witness,
+                );
+                let tx = builder.set_payload_auth(&());
+                Fragment::VoteCastBatch(tx)
+            }
+            Certificate::VotePlanCancel(cancel) => {
+                let builder = self.set_initial_ios(
+                    valid_until,
+                    TxBuilder::new().set_payload(cancel),
+                    funder,
+                    inputs,
+                    outputs,
+                    make_witness,
+                );
+                let committee_signatures = vote_plan_cancel_sign(&keys, &builder);
+                let tx = builder.set_payload_auth(&committee_signatures);
+                Fragment::VotePlanCancel(tx)
+            }
         }
     }
 
@@ -336,6 +456,35 @@ pub fn encrypted_tally_sign(
     EncryptedVoteTallyProof { id, signature }
 }
 
+pub fn voting_power_snapshot_sign(
+    keys: &[EitherEd25519SecretKey],
+    builder: &TxBuilderState<SetAuthData<VotingPowerSnapshot>>,
+) -> VotingPowerSnapshotProof {
+    let key: EitherEd25519SecretKey = keys[0].clone();
+    let id = key.to_public().into();
+
+    let auth_data = builder.get_auth_data();
+    let signature = SingleAccountBindingSignature::new(&auth_data, |d| key.sign_slice(d.0));
+    VotingPowerSnapshotProof { id, signature }
+}
+
+/// every key signs the cancellation, as a member of the committee
+pub fn vote_plan_cancel_sign(
+    keys: &[EitherEd25519SecretKey],
+    builder: &TxBuilderState<SetAuthData<VotePlanCancel>>,
+) -> VotePlanCancelProof {
+    let auth_data = builder.get_auth_data();
+    let signatures = keys
+        .iter()
+        .map(|key| {
+            let id = key.to_public().into();
+            let signature = SingleAccountBindingSignature::new(&auth_data, |d| key.sign_slice(d.0));
+            (id, signature)
+        })
+        .collect();
+    VotePlanCancelProof { signatures }
+}
+
 pub fn plan_sign(
     keys: &[EitherEd25519SecretKey],
     builder: &TxBuilderState<SetAuthData<VotePlan>>,
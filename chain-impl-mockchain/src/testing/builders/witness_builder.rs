@@ -22,12 +22,16 @@ pub fn make_witness(
     transaction_hash: &TransactionSignDataHash,
 ) -> Witness {
     match addres_data.address.kind() {
-        Kind::Account(_) => Witness::new_account(
-            block0,
-            transaction_hash,
-            addres_data.spending_counter.get_valid_counter(),
-            |d| addres_data.private_key().sign(d),
-        ),
+        Kind::Account(_) => {
+            let spending_counter = addres_data.spending_counter.get_valid_counter();
+            Witness::new_account_in_cycle(
+                block0,
+                transaction_hash,
+                spending_counter,
+                addres_data.spending_counter.get_cycle(spending_counter),
+                |d| addres_data.private_key().sign(d),
+            )
+        }
         _ => Witness::new_utxo(block0, transaction_hash, |d| {
             addres_data.private_key().sign(d)
         }),
@@ -41,12 +45,16 @@ pub fn make_witness_with_lane(
     transaction_hash: &TransactionSignDataHash,
 ) -> Witness {
     match addres_data.address.kind() {
-        Kind::Account(_) => Witness::new_account(
-            block0,
-            transaction_hash,
-            addres_data.spending_counter_at_lane(lane).unwrap(),
-            |d| addres_data.private_key().sign(d),
-        ),
+        Kind::Account(_) => {
+            let spending_counter = addres_data.spending_counter_at_lane(lane).unwrap();
+            Witness::new_account_in_cycle(
+                block0,
+                transaction_hash,
+                spending_counter,
+                addres_data.spending_counter.get_cycle(spending_counter),
+                |d| addres_data.private_key().sign(d),
+            )
+        }
         _ => Witness::new_utxo(block0, transaction_hash, |d| {
             addres_data.private_key().sign(d)
         }),
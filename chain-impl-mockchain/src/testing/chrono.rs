@@ -0,0 +1,204 @@
+//! Simulated clock for moving a [`TestLedger`] forward in time.
+//!
+//! The simulator takes care of the bookkeeping needed when time passes on a
+//! real chain: empty blocks are produced on behalf of a leader (if any) and
+//! rewards are distributed before the first block of every new epoch.
+
+use crate::{
+    date::BlockDate,
+    ledger::Error,
+    testing::{
+        data::{LeaderPair, StakePool},
+        ledger::TestLedger,
+    },
+};
+
+/// Who is producing the empty blocks while the clock is ticking.
+#[derive(Clone, Debug)]
+pub enum BlockProducer {
+    /// no block is produced, only the ledger date is moved forward
+    None,
+    /// empty BFT blocks signed by the given leader
+    Bft(LeaderPair),
+    /// empty Genesis-Praos blocks created by the given stake pool
+    Praos(StakePool),
+}
+
+#[derive(Clone, Debug)]
+pub struct ClockSimulator {
+    producer: BlockProducer,
+    distribute_rewards: bool,
+}
+
+impl Default for ClockSimulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClockSimulator {
+    pub fn new() -> Self {
+        Self {
+            producer: BlockProducer::None,
+            distribute_rewards: true,
+        }
+    }
+
+    pub fn with_bft_leader(mut self, leader: LeaderPair) -> Self {
+        self.producer = BlockProducer::Bft(leader);
+        self
+    }
+
+    pub fn with_stake_pool(mut self, stake_pool: StakePool) -> Self {
+        self.producer = BlockProducer::Praos(stake_pool);
+        self
+    }
+
+    /// do not distribute rewards when crossing an epoch boundary
+    pub fn without_rewards_distribution(mut self) -> Self {
+        self.distribute_rewards = false;
+        self
+    }
+
+    pub fn producer(&self) -> &BlockProducer {
+        &self.producer
+    }
+
+    /// Move the ledger forward by exactly one slot, producing a block
+    /// in the new slot if a producer is set.
+    pub fn tick(&self, ledger: &mut TestLedger) -> Result<(), Error> {
+        let next = ledger.date().next(ledger.era());
+        if next.epoch > ledger.date().epoch {
+            self.on_epoch_transition(ledger)?;
+        }
+
+        match &self.producer {
+            BlockProducer::None => ledger.forward_date(),
+            BlockProducer::Bft(leader) => ledger.apply_empty_bft_block(leader)?,
+            BlockProducer::Praos(stake_pool) => ledger.apply_empty_praos_block(stake_pool)?,
+        }
+        Ok(())
+    }
+
+    /// Move the ledger forward by `slots` slots, one block per slot.
+    pub fn advance_slots(&self, ledger: &mut TestLedger, slots: u32) -> Result<(), Error> {
+        for _ in 0..slots {
+            self.tick(ledger)?;
+        }
+        Ok(())
+    }
+
+    /// Move the ledger to the first slot of the epoch `epochs` ahead of the
+    /// current one.
+    ///
+    /// The slots in between are skipped and only one block is produced at the
+    /// beginning of each traversed epoch, which keeps epochs with a large number
+    /// of slots cheap to go through.
+    pub fn advance_epochs(&self, ledger: &mut TestLedger, epochs: u32) -> Result<(), Error> {
+        for _ in 0..epochs {
+            let last_slot = BlockDate {
+                epoch: ledger.date().epoch,
+                slot_id: ledger.era().slots_per_epoch() - 1,
+            };
+            if ledger.date() < last_slot {
+                ledger.set_date(last_slot);
+            }
+            self.tick(ledger)?;
+        }
+        Ok(())
+    }
+
+    /// Move the ledger up to the given date.
+    ///
+    /// Whole epochs are traversed with [`ClockSimulator::advance_epochs`], the
+    /// remaining slots of the target epoch are produced one by one.
+    pub fn advance_to(&self, ledger: &mut TestLedger, date: BlockDate) -> Result<(), Error> {
+        if date <= ledger.date() {
+            return Ok(());
+        }
+        let epochs = date.epoch - ledger.date().epoch;
+        self.advance_epochs(ledger, epochs)?;
+        self.advance_slots(ledger, date.slot_id - ledger.date().slot_id)
+    }
+
+    fn on_epoch_transition(&self, ledger: &mut TestLedger) -> Result<(), Error> {
+        if self.distribute_rewards && ledger.can_distribute_reward() {
+            ledger.distribute_rewards()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{
+            ledger::ConfigBuilder,
+            scenario::{prepare_scenario, stake_pool, wallet},
+        },
+        value::Value,
+    };
+
+    #[test]
+    pub fn advance_without_producer_moves_date_only() {
+        let (mut ledger, _) = prepare_scenario()
+            .with_config(ConfigBuilder::new().with_slots_per_epoch(10))
+            .with_initials(vec![wallet("Alice").with(1_000)])
+            .build()
+            .unwrap();
+
+        let clock = ClockSimulator::new();
+        clock.advance_slots(&mut ledger, 3).unwrap();
+        assert_eq!(
+            ledger.date(),
+            BlockDate {
+                epoch: 0,
+                slot_id: 3
+            }
+        );
+
+        clock
+            .advance_to(
+                &mut ledger,
+                BlockDate {
+                    epoch: 2,
+                    slot_id: 5,
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            ledger.date(),
+            BlockDate {
+                epoch: 2,
+                slot_id: 5
+            }
+        );
+        assert_eq!(ledger.chain_length().0, 0);
+    }
+
+    #[test]
+    pub fn advance_epochs_distributes_rewards() {
+        let (mut ledger, controller) = prepare_scenario()
+            .with_config(
+                ConfigBuilder::new()
+                    .with_slots_per_epoch(10)
+                    .with_rewards(Value(100))
+                    .with_treasury(Value(0)),
+            )
+            .with_initials(vec![wallet("Alice").with(1_000).owns("stake_pool")])
+            .with_stake_pools(vec![stake_pool("stake_pool").tax_ratio(1, 1)])
+            .build()
+            .unwrap();
+
+        let clock = controller.clock("stake_pool").unwrap();
+
+        clock.advance_slots(&mut ledger, 1).unwrap();
+        assert!(ledger.can_distribute_reward());
+        let rewards_before = ledger.pots().rewards;
+
+        clock.advance_epochs(&mut ledger, 1).unwrap();
+        assert_eq!(ledger.date(), BlockDate::epoch_start(1));
+        assert!(ledger.pots().rewards < rewards_before);
+    }
+}
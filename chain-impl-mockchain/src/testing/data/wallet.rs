@@ -1,20 +1,35 @@
 use std::collections::HashMap;
+use std::iter;
 
 use crate::{
+    accounting::account::DelegationType,
+    certificate::{VoteCast, VotePlan},
     chaintypes::HeaderId,
+    fragment::Fragment,
     key::EitherEd25519SecretKey,
-    testing::data::{AddressData, AddressDataValue},
+    ledger::Ledger,
+    testing::{
+        builders::{build_stake_delegation_cert, TestTxBuilder, TestTxCertBuilder},
+        data::{AddressData, AddressDataValue, StakePool},
+        ledger::TestLedger,
+    },
     tokens::name::TokenName,
-    transaction::{Input, Output, TransactionAuthData, Witness},
+    transaction::{Input, Output, TransactionAuthData, UtxoPointer, Witness},
     value::Value,
+    vote,
 };
 use chain_addr::{Address, Discrimination};
 use chain_crypto::{Ed25519, PublicKey};
 
+/// A test wallet, its spending counters, value, utxos and delegation
+/// follow the ledger with [`sync`](Wallet::sync): the fragments made by
+/// its helpers after a sync do not need a `confirm_transaction`.
 #[derive(Clone, Debug)]
 pub struct Wallet {
     alias: String,
     account: AddressDataValue,
+    utxos: Vec<UtxoPointer>,
+    delegation: DelegationType,
 }
 
 impl Wallet {
@@ -25,6 +40,8 @@ impl Wallet {
         Wallet {
             alias: alias.into(),
             account,
+            utxos: Vec::new(),
+            delegation: DelegationType::NonDelegated,
         }
     }
 
@@ -32,6 +49,8 @@ impl Wallet {
         Wallet {
             alias: "".to_owned(),
             account,
+            utxos: Vec::new(),
+            delegation: DelegationType::NonDelegated,
         }
     }
 
@@ -43,6 +62,8 @@ impl Wallet {
         Wallet {
             alias: alias.to_owned(),
             account: AddressDataValue::account(Discrimination::Test, initial_value),
+            utxos: Vec::new(),
+            delegation: DelegationType::NonDelegated,
         }
     }
 
@@ -58,6 +79,8 @@ impl Wallet {
                 initial_value,
                 tokens,
             ),
+            utxos: Vec::new(),
+            delegation: DelegationType::NonDelegated,
         }
     }
 
@@ -126,4 +149,128 @@ impl Wallet {
         self.as_account()
             .make_witness_with_lane(block0_hash, lane, tad)
     }
+
+    /// the unspent outputs of the address of the wallet, as of the last sync
+    pub fn utxos(&self) -> &[UtxoPointer] {
+        &self.utxos
+    }
+
+    /// the delegation of the account of the wallet, as of the last sync
+    pub fn delegation(&self) -> &DelegationType {
+        &self.delegation
+    }
+
+    /// Update the spending counters, the value, the utxos and the delegation
+    /// of the wallet from the ledger. An account unknown to the ledger is
+    /// left untouched.
+    pub fn sync(&mut self, ledger: &Ledger) {
+        let address = self.as_address();
+        self.utxos = ledger
+            .utxos()
+            .filter(|entry| entry.output.address == address)
+            .map(|entry| {
+                UtxoPointer::new(entry.fragment_id, entry.output_index, entry.output.value)
+            })
+            .collect();
+
+        if self.account.is_utxo() {
+            self.account.value = self.utxos.iter().fold(Value::zero(), |total, utxo| {
+                total.saturating_add(utxo.value)
+            });
+        } else if let Ok(state) = ledger.accounts().get_state(&self.account.to_id()) {
+            *self.account.address_data.spending_counter_mut() = state.spending.clone();
+            self.account.value = state.value();
+            self.delegation = state.delegation().clone();
+        }
+    }
+
+    /// A transaction moving `value` to the wallet `to`, the fee is taken
+    /// from the moved value as with [`TestTxBuilder::move_funds`]
+    pub fn transfer_to(&self, to: &Wallet, value: Value, test_ledger: &mut TestLedger) -> Fragment {
+        TestTxBuilder::new(test_ledger.block0_hash)
+            .move_funds(test_ledger, &self.as_account(), &to.as_account(), value)
+            .get_fragment()
+    }
+
+    /// A certificate delegating the whole stake of the wallet to the pool
+    pub fn delegate_to(&self, stake_pool: &StakePool, test_ledger: &TestLedger) -> Fragment {
+        let certificate = build_stake_delegation_cert(&stake_pool.info(), &self.as_account_data());
+        TestTxCertBuilder::new(test_ledger.block0_hash, test_ledger.fee()).make_transaction(
+            test_ledger.date().next_epoch(),
+            iter::once(self),
+            &certificate,
+            Default::default(),
+        )
+    }
+
+    /// A vote of the wallet for the proposal of the vote plan
+    pub fn cast_vote(
+        &self,
+        vote_plan: &VotePlan,
+        proposal_index: u8,
+        payload: vote::Payload,
+        test_ledger: &TestLedger,
+    ) -> Fragment {
+        let vote_cast = VoteCast::new(vote_plan.to_id(), proposal_index, payload);
+        TestTxCertBuilder::new(test_ledger.block0_hash, test_ledger.fee()).make_transaction(
+            test_ledger.date().next_epoch(),
+            iter::once(self),
+            &vote_cast.into(),
+            Default::default(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{
+        builders::{create_initial_stake_pool_registration, StakePoolBuilder},
+        ConfigBuilder, LedgerBuilder, TestGen,
+    };
+
+    #[test]
+    fn wallets_follow_the_applied_blocks() {
+        let mut alice = Wallet::from_value(Value(1_000));
+        let mut bob = Wallet::from_value(Value(100));
+        let leader_pair = TestGen::leader_pair();
+        let stake_pool = StakePoolBuilder::new()
+            .with_owners(vec![alice.public_key()])
+            .build();
+        let mut test_ledger =
+            LedgerBuilder::from_config(ConfigBuilder::new().with_leaders(&[leader_pair.id()]))
+                .faucets_wallets(vec![&alice, &bob])
+                .certs(&[create_initial_stake_pool_registration(
+                    &stake_pool,
+                    &[alice.clone()],
+                )])
+                .build()
+                .unwrap();
+
+        let transfer = alice.transfer_to(&bob, Value(300), &mut test_ledger);
+        let block = test_ledger.forge_bft_block_with_fragments(
+            &leader_pair,
+            test_ledger.date(),
+            vec![transfer],
+        );
+        test_ledger
+            .apply_block_and_sync(block, &mut [&mut alice, &mut bob])
+            .unwrap();
+        assert_eq!(alice.value(), Value(700));
+        assert_eq!(bob.value(), Value(400));
+        assert!(alice.utxos().is_empty());
+
+        // the spending counter of alice has been synced, no confirmation
+        let delegation = alice.delegate_to(&stake_pool, &test_ledger);
+        let block = test_ledger.forge_bft_block_with_fragments(
+            &leader_pair,
+            test_ledger.date(),
+            vec![delegation],
+        );
+        test_ledger
+            .apply_block_and_sync(block, &mut [&mut alice])
+            .unwrap();
+        assert_eq!(alice.delegation(), &DelegationType::Full(stake_pool.id()));
+        assert_eq!(bob.delegation(), &DelegationType::NonDelegated);
+    }
 }
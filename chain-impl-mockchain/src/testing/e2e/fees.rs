@@ -1,9 +1,10 @@
 use crate::{
+    certificate::SerializedSize,
     fee::{LinearFee, PerCertificateFee},
     testing::{
         builders::StakePoolBuilder,
         ledger::ConfigBuilder,
-        scenario::{prepare_scenario, wallet},
+        scenario::{prepare_scenario, wallet, FragmentFactory},
         verifiers::LedgerStateVerifier,
     },
     value::Value,
@@ -143,6 +144,52 @@ pub fn per_certificate_fees() {
         .has_value(&Value(alice_funds));
 }
 
+#[test]
+pub fn per_certificate_byte_fee() {
+    let certificate_fee = 10;
+    let certificate_byte_fee = 2;
+    let alice_funds = 1_000;
+
+    let (mut ledger, controller) = prepare_scenario()
+        .with_config(
+            ConfigBuilder::new()
+                .with_discrimination(Discrimination::Test)
+                .with_fee(LinearFee::new(1, 1, certificate_fee))
+                .with_per_certificate_byte_fee(NonZeroU64::new(certificate_byte_fee).unwrap()),
+        )
+        .with_initials(vec![wallet(ALICE).with(alice_funds)])
+        .build()
+        .unwrap();
+
+    let mut alice = controller.wallet(ALICE).unwrap();
+    let stake_pool = StakePoolBuilder::new()
+        .with_owners(vec![alice.public_key()])
+        .build();
+
+    // a transaction paying only the flat certificate fee is rejected
+    let fragment = FragmentFactory::new(ledger.block0_hash, LinearFee::new(1, 1, certificate_fee))
+        .stake_pool_registration(ledger.date(), &alice, &stake_pool);
+    assert!(ledger.apply_fragment(&fragment, ledger.date()).is_err());
+
+    controller
+        .register(&alice, &stake_pool, &mut ledger)
+        .unwrap();
+    alice.confirm_transaction();
+
+    let expected_fee =
+        1 + 1 + certificate_fee + certificate_byte_fee * stake_pool.info().serialized_size() as u64;
+
+    let mut ledger_verifier = LedgerStateVerifier::new(ledger.into());
+    ledger_verifier
+        .info("after register")
+        .pots()
+        .has_fee_equals_to(&Value(expected_fee));
+    ledger_verifier
+        .info("after register")
+        .account(alice.as_account_data())
+        .has_value(&Value(alice_funds - expected_fee));
+}
+
 #[test]
 pub fn owner_delegates_fee() {
     let reward_value = 1_000_000;
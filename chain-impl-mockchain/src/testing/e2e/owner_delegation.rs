@@ -36,11 +36,11 @@ pub fn owner_delegation() {
     LedgerStateVerifier::new(ledger.clone().into())
         .info("after owner delegation")
         .distribution()
-        .unassigned_is(Stake::from_value(Value(1000)))
+        .unassigned_is(Stake(1000))
         .and()
-        .dangling_is(Stake::from_value(Value::zero()))
+        .dangling_is(Stake::zero())
         .and()
-        .pools_total_stake_is(Stake::from_value(Value(997)));
+        .pools_total_stake_is(Stake(997));
 
     controller.removes_delegation(&alice, &mut ledger).unwrap();
     alice.confirm_transaction();
@@ -48,11 +48,11 @@ pub fn owner_delegation() {
     LedgerStateVerifier::new(ledger.into())
         .info("after owner delegation removal")
         .distribution()
-        .unassigned_is(Stake::from_value(Value(1994)))
+        .unassigned_is(Stake(1994))
         .and()
-        .dangling_is(Stake::from_value(Value::zero()))
+        .dangling_is(Stake::zero())
         .and()
-        .pools_total_stake_is(Stake::from_value(Value::zero()));
+        .pools_total_stake_is(Stake::zero());
 }
 
 #[test]
@@ -73,9 +73,9 @@ pub fn delegation_for_non_existing_account() {
     LedgerStateVerifier::new(ledger.into())
         .info("after owner delegation removal")
         .distribution()
-        .unassigned_is(Stake::from_value(Value(1_000)))
+        .unassigned_is(Stake(1_000))
         .and()
-        .dangling_is(Stake::from_value(Value::zero()))
+        .dangling_is(Stake::zero())
         .and()
-        .pools_total_stake_is(Stake::from_value(Value::zero()));
+        .pools_total_stake_is(Stake::zero());
 }
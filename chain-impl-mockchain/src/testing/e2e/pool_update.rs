@@ -6,7 +6,6 @@ use crate::{
         scenario::{prepare_scenario, stake_pool, wallet},
         verifiers::LedgerStateVerifier,
     },
-    value::Value,
 };
 
 #[test]
@@ -39,11 +38,11 @@ pub fn delegations_are_preserved_after_pool_update() {
     LedgerStateVerifier::new(ledger.clone().into())
         .info("distribution is ok")
         .distribution()
-        .unassigned_is(Stake::from_value(Value(997)))
+        .unassigned_is(Stake(997))
         .and()
-        .dangling_is(Stake::from_value(Value::zero()))
+        .dangling_is(Stake::zero())
         .and()
-        .pools_total_stake_is(Stake::from_value(Value(1000)));
+        .pools_total_stake_is(Stake(1000));
 
     LedgerStateVerifier::new(ledger.into())
         .info("delegation is the same")
@@ -1,16 +1,19 @@
 use crate::{
-    certificate::PoolId,
+    certificate::{PoolId, RewardAccount},
     config::RewardParams,
     fee::LinearFee,
+    ledger::{simulate_rewards, PledgeNotMet, RewardAccountFallbackReport, RewardsInfoParameters},
     rewards::Ratio,
     testing::{
         builders::StakePoolBuilder,
+        data::{AddressData, StakePool},
         ledger::{ConfigBuilder, TestLedger},
         scenario::{prepare_scenario, stake_pool, wallet},
         verifiers::LedgerStateVerifier,
     },
     value::Value,
 };
+use chain_addr::{Address, Discrimination, Kind};
 
 use std::num::{NonZeroU32, NonZeroU64};
 
@@ -283,6 +286,83 @@ pub fn rewards_reward_account() {
         .has_last_reward(&Value(9));
 }
 
+fn reward_address_scenario(reward_address: Address) -> (TestLedger, StakePool) {
+    let (ledger, controller) = prepare_scenario()
+        .with_config(
+            ConfigBuilder::new()
+                .with_rewards(Value(1000))
+                .with_treasury(Value(0))
+                .with_rewards_params(RewardParams::Linear {
+                    constant: 100,
+                    ratio: Ratio {
+                        numerator: 1,
+                        denominator: NonZeroU64::new(1).unwrap(),
+                    },
+                    epoch_start: 0,
+                    epoch_rate: NonZeroU32::new(1).unwrap(),
+                }),
+        )
+        .with_initials(vec![wallet("Alice").with(1_000).owns("stake_pool")])
+        .with_stake_pools(vec![stake_pool("stake_pool")
+            .with_reward_address(reward_address)
+            .tax_ratio(1, 10)])
+        .build()
+        .unwrap();
+    let stake_pool = controller.stake_pool("stake_pool").unwrap();
+    (ledger, stake_pool)
+}
+
+#[test]
+pub fn rewards_reward_address_utxo() {
+    let reward_address = AddressData::utxo(Discrimination::Test).address;
+    let (mut ledger, stake_pool) = reward_address_scenario(reward_address.clone());
+
+    assert!(ledger.apply_empty_praos_block(&stake_pool).is_ok());
+    ledger.distribute_rewards().unwrap();
+
+    let paid: Vec<_> = ledger
+        .utxos()
+        .filter(|entry| entry.output.address == reward_address)
+        .map(|entry| entry.output.value)
+        .collect();
+    assert_eq!(paid, vec![Value(9)]);
+
+    LedgerStateVerifier::new(ledger.into())
+        .info("after rewards distribution to reward address")
+        .pots()
+        .has_treasury_equals_to(&Value(90));
+}
+
+#[test]
+pub fn rewards_stale_reward_account_goes_to_treasury() {
+    // a multisig account which is not declared cannot be credited
+    let reward_address = Address(Discrimination::Test, Kind::Multisig([7; 32]));
+    let (mut ledger, stake_pool) = reward_address_scenario(reward_address.clone());
+
+    assert!(ledger.apply_empty_praos_block(&stake_pool).is_ok());
+    let (new_ledger, _, events) = ledger
+        .ledger
+        .distribute_rewards_with_events(
+            &ledger.ledger.get_stake_distribution(),
+            &ledger.ledger.get_ledger_parameters(),
+            RewardsInfoParameters::report_all(),
+        )
+        .unwrap();
+
+    assert_eq!(
+        events.reward_account_fallbacks().collect::<Vec<_>>(),
+        vec![&RewardAccountFallbackReport {
+            pool_id: stake_pool.id(),
+            reward_account: RewardAccount::Address(reward_address),
+            value: Value(9),
+        }]
+    );
+    LedgerStateVerifier::new(new_ledger)
+        .info("after rewards distribution to a stale reward account")
+        .pots()
+        .has_treasury_equals_to(&Value(99));
+}
+
 #[test]
 pub fn rewards_goes_to_treasury_if_stake_pool_is_retired() {
     let (mut ledger, controller) = prepare_scenario()
@@ -764,3 +844,373 @@ pub fn rewards_delegators_of_many_stake_pool() {
         .account(eve.as_account_data())
         .has_value(&Value(1093));
 }
+
+#[test]
+pub fn rewards_forfeited_when_owners_stake_is_below_pledge() {
+    let (mut ledger, controller) = prepare_scenario()
+        .with_config(
+            ConfigBuilder::new()
+                .with_rewards(Value(100))
+                .with_treasury(Value(0))
+                .with_rewards_params(RewardParams::Linear {
+                    constant: 10,
+                    ratio: Ratio {
+                        numerator: 1,
+                        denominator: NonZeroU64::new(1).unwrap(),
+                    },
+                    epoch_start: 0,
+                    epoch_rate: NonZeroU32::new(1).unwrap(),
+                }),
+        )
+        .with_initials(vec![
+            wallet("Alice")
+                .with(1_000)
+                .owns_and_delegates_to("stake_pool"),
+            wallet("Bob").with(5_000).delegates_to("stake_pool"),
+        ])
+        .with_stake_pools(vec![stake_pool("stake_pool").tax_ratio(1, 1).pledge(2_000)])
+        .build()
+        .unwrap();
+
+    let stake_pool = controller.stake_pool("stake_pool").unwrap();
+    let alice = controller.wallet("Alice").unwrap();
+
+    assert!(ledger.apply_empty_praos_block(&stake_pool).is_ok());
+    let (_, _, events) = ledger
+        .ledger
+        .distribute_rewards_with_events(
+            &ledger.ledger.get_stake_distribution(),
+            &ledger.ledger.get_ledger_parameters(),
+            RewardsInfoParameters::report_all(),
+        )
+        .unwrap();
+    let rewards_info = ledger.distribute_rewards().unwrap();
+
+    // the delegators cover the pledge but only the owners' stake counts
+    let pledge_not_met = PledgeNotMet {
+        pledge: Value(2_000),
+        owners_stake: Value(1_000),
+        forfeited: Value(9),
+    };
+    assert_eq!(
+        rewards_info.pledges_not_met.get(&stake_pool.id()),
+        Some(&pledge_not_met)
+    );
+    assert_eq!(
+        events.pledge_forfeits().collect::<Vec<_>>(),
+        vec![&PledgeForfeitReport {
+            pool_id: stake_pool.id(),
+            pledge_not_met,
+        }]
+    );
+
+    let mut ledger_verifier = LedgerStateVerifier::new(ledger.into());
+    ledger_verifier.info("after rewards distribution with the pledge not met");
+
+    ledger_verifier
+        .pots()
+        .has_fee_equals_to(&Value::zero())
+        .and()
+        .has_treasury_equals_to(&Value(9))
+        .and()
+        .has_remaining_rewards_equals_to(&Value(91));
+
+    ledger_verifier
+        .account(alice.as_account_data())
+        .has_value(&Value(1_000));
+}
+
+#[test]
+pub fn rewards_distributed_when_owners_stake_meets_pledge() {
+    let (mut ledger, controller) = prepare_scenario()
+        .with_config(
+            ConfigBuilder::new()
+                .with_rewards(Value(100))
+                .with_treasury(Value(0))
+                .with_rewards_params(RewardParams::Linear {
+                    constant: 10,
+                    ratio: Ratio {
+                        numerator: 1,
+                        denominator: NonZeroU64::new(1).unwrap(),
+                    },
+                    epoch_start: 0,
+                    epoch_rate: NonZeroU32::new(1).unwrap(),
+                }),
+        )
+        .with_initials(vec![wallet("Alice")
+            .with(1_000)
+            .owns_and_delegates_to("stake_pool")])
+        .with_stake_pools(vec![stake_pool("stake_pool").tax_ratio(1, 1).pledge(1_000)])
+        .build()
+        .unwrap();
+
+    let stake_pool = controller.stake_pool("stake_pool").unwrap();
+    let alice = controller.wallet("Alice").unwrap();
+
+    assert!(ledger.apply_empty_praos_block(&stake_pool).is_ok());
+    let rewards_info = ledger.distribute_rewards().unwrap();
+    assert!(rewards_info.pledges_not_met.is_empty());
+
+    let mut ledger_verifier = LedgerStateVerifier::new(ledger.into());
+    ledger_verifier.info("after rewards distribution with the pledge met");
+
+    ledger_verifier
+        .pots()
+        .has_treasury_equals_to(&Value::zero())
+        .and()
+        .has_remaining_rewards_equals_to(&Value(91));
+
+    ledger_verifier
+        .account(alice.as_account_data())
+        .has_value(&Value(1_009));
+}
+
+#[test]
+pub fn pool_performance_recorded_without_scaling_rewards() {
+    let (mut ledger, controller) = prepare_scenario()
+        .with_config(
+            ConfigBuilder::new()
+                .with_rewards(Value(100))
+                .with_treasury(Value(0))
+                .with_slots_per_epoch(4)
+                .with_rewards_params(RewardParams::Linear {
+                    constant: 10,
+                    ratio: Ratio {
+                        numerator: 1,
+                        denominator: NonZeroU64::new(1).unwrap(),
+                    },
+                    epoch_start: 0,
+                    epoch_rate: NonZeroU32::new(1).unwrap(),
+                }),
+        )
+        .with_initials(vec![wallet("Alice")
+            .with(1_000)
+            .owns_and_delegates_to("stake_pool")])
+        .with_stake_pools(vec![stake_pool("stake_pool").tax_ratio(1, 1)])
+        .build()
+        .unwrap();
+
+    let stake_pool = controller.stake_pool("stake_pool").unwrap();
+    let alice = controller.wallet("Alice").unwrap();
+
+    assert!(ledger.apply_empty_praos_block(&stake_pool).is_ok());
+    let rewards_info = ledger.distribute_rewards().unwrap();
+
+    // 4 slots with an active slots coefficient of 0.5
+    let performance = rewards_info.performances.get(&stake_pool.id()).unwrap();
+    assert_eq!(performance.expected, 2);
+    assert_eq!(performance.produced, 1);
+    assert_eq!(
+        ledger.ledger.pool_performance(&stake_pool.id()),
+        Some(performance)
+    );
+    assert_eq!(
+        ledger
+            .ledger
+            .pool_performance_at(&stake_pool.id(), performance.epoch),
+        Some(performance)
+    );
+
+    let mut ledger_verifier = LedgerStateVerifier::new(ledger.into());
+    ledger_verifier.info("after rewards distribution without performance rewards");
+
+    ledger_verifier
+        .pots()
+        .has_treasury_equals_to(&Value::zero())
+        .and()
+        .has_remaining_rewards_equals_to(&Value(91));
+
+    ledger_verifier
+        .account(alice.as_account_data())
+        .has_value(&Value(1_009));
+}
+
+#[test]
+pub fn pool_performance_scales_rewards() {
+    let (mut ledger, controller) = prepare_scenario()
+        .with_config(
+            ConfigBuilder::new()
+                .with_rewards(Value(100))
+                .with_treasury(Value(0))
+                .with_slots_per_epoch(4)
+                .with_pool_performance_rewards(true)
+                .with_rewards_params(RewardParams::Linear {
+                    constant: 10,
+                    ratio: Ratio {
+                        numerator: 1,
+                        denominator: NonZeroU64::new(1).unwrap(),
+                    },
+                    epoch_start: 0,
+                    epoch_rate: NonZeroU32::new(1).unwrap(),
+                }),
+        )
+        .with_initials(vec![wallet("Alice")
+            .with(1_000)
+            .owns_and_delegates_to("stake_pool")])
+        .with_stake_pools(vec![stake_pool("stake_pool").tax_ratio(1, 1)])
+        .build()
+        .unwrap();
+
+    let stake_pool = controller.stake_pool("stake_pool").unwrap();
+    let alice = controller.wallet("Alice").unwrap();
+
+    assert!(ledger.apply_empty_praos_block(&stake_pool).is_ok());
+    let rewards_info = ledger.distribute_rewards().unwrap();
+    assert_eq!(rewards_info.performances.len(), 1);
+
+    // half of the expected blocks were created, half of the rewards are
+    // sent to the treasury
+    let mut ledger_verifier = LedgerStateVerifier::new(ledger.into());
+    ledger_verifier.info("after rewards distribution with performance rewards");
+
+    ledger_verifier
+        .pots()
+        .has_treasury_equals_to(&Value(5))
+        .and()
+        .has_remaining_rewards_equals_to(&Value(91));
+
+    ledger_verifier
+        .account(alice.as_account_data())
+        .has_value(&Value(1_004));
+}
+
+#[test]
+#[cfg(feature = "parallel-rewards")]
+pub fn rewards_computed_in_parallel_are_the_same_as_sequential() {
+    use crate::ledger::RewardsComputation;
+
+    let (mut ledger, controller) = prepare_scenario()
+        .with_config(
+            ConfigBuilder::new()
+                .with_rewards(Value(10_000))
+                .with_treasury(Value(0))
+                .with_pool_performance_rewards(true)
+                .with_rewards_params(RewardParams::Linear {
+                    constant: 1_000,
+                    ratio: Ratio {
+                        numerator: 1,
+                        denominator: NonZeroU64::new(1).unwrap(),
+                    },
+                    epoch_start: 0,
+                    epoch_rate: NonZeroU32::new(1).unwrap(),
+                }),
+        )
+        .with_initials(vec![
+            wallet("Alice")
+                .with(1_000)
+                .owns_and_delegates_to("alice_stake_pool"),
+            wallet("Bob").with(3_000).delegates_to("alice_stake_pool"),
+            wallet("Clarice")
+                .with(2_000)
+                .owns_and_delegates_to("clarice_stake_pool"),
+            wallet("David")
+                .with(1_500)
+                .delegates_to("clarice_stake_pool"),
+            wallet("Eve")
+                .with(1_000)
+                .owns_and_delegates_to("eve_stake_pool"),
+        ])
+        .with_stake_pools(vec![
+            stake_pool("alice_stake_pool")
+                .with_reward_account(true)
+                .tax_ratio(1, 10),
+            stake_pool("clarice_stake_pool").tax_ratio(1, 3),
+            stake_pool("eve_stake_pool").tax_ratio(1, 10).pledge(5_000),
+        ])
+        .build()
+        .unwrap();
+
+    let alice_stake_pool = controller.stake_pool("alice_stake_pool").unwrap();
+    let clarice_stake_pool = controller.stake_pool("clarice_stake_pool").unwrap();
+    let eve_stake_pool = controller.stake_pool("eve_stake_pool").unwrap();
+
+    for pool in [
+        &alice_stake_pool,
+        &clarice_stake_pool,
+        &alice_stake_pool,
+        &eve_stake_pool,
+        &alice_stake_pool,
+    ] {
+        assert!(ledger.apply_empty_praos_block(pool).is_ok());
+    }
+
+    let distribute = |computation| {
+        ledger
+            .ledger
+            .distribute_rewards_using(
+                &ledger.ledger.get_stake_distribution(),
+                &ledger.ledger.get_ledger_parameters(),
+                RewardsInfoParameters::report_all(),
+                computation,
+            )
+            .unwrap()
+    };
+    let (sequential_ledger, sequential_info, sequential_events) =
+        distribute(RewardsComputation::Sequential);
+    let (parallel_ledger, parallel_info, parallel_events) =
+        distribute(RewardsComputation::Parallel);
+
+    assert!(sequential_ledger == parallel_ledger);
+    assert_eq!(sequential_info.treasury, parallel_info.treasury);
+    assert_eq!(sequential_info.stake_pools, parallel_info.stake_pools);
+    assert_eq!(sequential_info.accounts, parallel_info.accounts);
+    assert_eq!(
+        sequential_info.pledges_not_met,
+        parallel_info.pledges_not_met
+    );
+    assert_eq!(sequential_events, parallel_events);
+
+    // all the pools were rewarded, except the one missing its pledge
+    assert_eq!(sequential_info.stake_pools.len(), 2);
+    assert!(sequential_info
+        .pledges_not_met
+        .contains_key(&eve_stake_pool.id()));
+}
+
+#[test]
+pub fn rewards_simulation_matches_the_distribution() {
+    let (mut ledger, controller) = prepare_scenario()
+        .with_config(
+            ConfigBuilder::new()
+                .with_rewards(Value(100))
+                .with_treasury(Value(0))
+                .with_rewards_params(RewardParams::Linear {
+                    constant: 20,
+                    ratio: Ratio {
+                        numerator: 1,
+                        denominator: NonZeroU64::new(1).unwrap(),
+                    },
+                    epoch_start: 0,
+                    epoch_rate: NonZeroU32::new(1).unwrap(),
+                }),
+        )
+        .with_initials(vec![wallet("Alice").with(1_000).owns("stake_pool")])
+        .with_stake_pools(vec![stake_pool("stake_pool").tax_ratio(1, 1)])
+        .build()
+        .unwrap();
+    let stake_pool = controller.stake_pool("stake_pool").unwrap();
+
+    let table = simulate_rewards(
+        &ledger.ledger.pots,
+        &ledger.ledger.get_stake_distribution(),
+        &ledger.ledger.get_ledger_parameters(),
+        1,
+        ledger.ledger.date().epoch + 1,
+        3,
+    )
+    .unwrap();
+    assert_eq!(
+        table.iter().map(|epoch| epoch.drawn).collect::<Vec<_>>(),
+        vec![Value(19), Value(18), Value(17)]
+    );
+    assert_eq!(table[2].reward_pot, Value(46));
+    assert!(table.iter().all(|epoch| epoch.treasury == Value::zero()));
+
+    // the simulation does not touch the ledger
+    assert!(ledger.apply_empty_praos_block(&stake_pool).is_ok());
+    ledger.distribute_rewards().unwrap();
+
+    assert_eq!(table[0].pools.get(&stake_pool.id()), Some(&Value(19)));
+    assert_eq!(ledger.ledger.remaining_rewards(), table[0].reward_pot);
+    assert_eq!(ledger.ledger.treasury_value(), table[0].treasury_pot);
+}
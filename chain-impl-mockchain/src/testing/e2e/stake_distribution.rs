@@ -58,6 +58,48 @@ pub fn stake_distribution_to_many_stake_pools() {
         .pools_distribution_is(expected_distribution);
 }
 
+#[test]
+pub fn stake_distribution_by_amounts() {
+    let (mut ledger, controller) = prepare_scenario()
+        .with_config(
+            ConfigBuilder::new()
+                .with_discrimination(Discrimination::Test)
+                .with_fee(LinearFee::new(1, 1, 1)),
+        )
+        .with_initials(vec![
+            wallet("Alice").with(1_000).owns("alice_stake_pool"),
+            wallet("Bob").with(1_000).owns("bob_stake_pool"),
+            wallet("David").with(1_003),
+        ])
+        .build()
+        .unwrap();
+
+    let alice_stake_pool = controller.stake_pool("alice_stake_pool").unwrap();
+    let bob_stake_pool = controller.stake_pool("bob_stake_pool").unwrap();
+
+    let david = controller.wallet("David").unwrap();
+
+    controller
+        .delegates_by_amounts(
+            &david,
+            &[
+                (&alice_stake_pool, Value(250)),
+                (&bob_stake_pool, Value(600)),
+            ],
+            &mut ledger,
+        )
+        .unwrap();
+
+    LedgerStateVerifier::new(ledger.into())
+        .info("after delegation by amounts")
+        .distribution()
+        .unassigned_is(Stake(2150))
+        .pools_distribution_is(vec![
+            (alice_stake_pool.id(), Value(250)),
+            (bob_stake_pool.id(), Value(600)),
+        ]);
+}
+
 #[test]
 pub fn stake_distribution_changes_after_rewards_are_collected() {
     let (mut ledger, controller) = prepare_scenario()
@@ -94,7 +136,7 @@ pub fn stake_distribution_changes_after_rewards_are_collected() {
     LedgerStateVerifier::new(ledger.clone().into())
         .info("before rewards collection")
         .distribution()
-        .unassigned_is(Stake::from_value(Value(2000)))
+        .unassigned_is(Stake(2000))
         .pools_distribution_is(vec![(alice_stake_pool.id(), Value(1000))]);
 
     assert!(ledger.apply_empty_praos_block(&alice_stake_pool).is_ok());
@@ -103,6 +145,138 @@ pub fn stake_distribution_changes_after_rewards_are_collected() {
     LedgerStateVerifier::new(ledger.into())
         .info("after rewards collection")
         .distribution()
-        .unassigned_is(Stake::from_value(Value(2000)))
+        .unassigned_is(Stake(2000))
         .pools_distribution_is(vec![(alice_stake_pool.id(), Value(1009))]);
 }
+
+#[test]
+pub fn redelegated_rewards_follow_the_delegation_ratio() {
+    let (mut ledger, controller) = prepare_scenario()
+        .with_config(
+            ConfigBuilder::new()
+                .with_rewards(Value(100))
+                .with_treasury(Value(0))
+                .with_rewards_params(RewardParams::Linear {
+                    constant: 10,
+                    ratio: Ratio {
+                        numerator: 1,
+                        denominator: NonZeroU64::new(1).unwrap(),
+                    },
+                    epoch_start: 0,
+                    epoch_rate: NonZeroU32::new(1).unwrap(),
+                }),
+        )
+        .with_initials(vec![
+            wallet("Alice").with(1_001).owns("alice_stake_pool"),
+            wallet("Bob").with(1_000).owns("bob_stake_pool"),
+        ])
+        .with_stake_pools(vec![
+            stake_pool("alice_stake_pool").tax_ratio(1, 1),
+            stake_pool("bob_stake_pool").tax_ratio(1, 1),
+        ])
+        .build()
+        .unwrap();
+
+    let alice_stake_pool = controller.stake_pool("alice_stake_pool").unwrap();
+    let bob_stake_pool = controller.stake_pool("bob_stake_pool").unwrap();
+    let mut alice = controller.wallet("Alice").unwrap();
+
+    controller
+        .delegates_to_many(
+            &alice,
+            &[(&alice_stake_pool, 1u8), (&bob_stake_pool, 1u8)],
+            &mut ledger,
+        )
+        .unwrap();
+    alice.confirm_transaction();
+    controller
+        .redelegates_rewards(&alice, true, &mut ledger)
+        .unwrap();
+
+    assert!(ledger.apply_empty_praos_block(&alice_stake_pool).is_ok());
+    ledger.distribute_rewards().unwrap();
+
+    LedgerStateVerifier::new(ledger.into())
+        .info("after rewards collection")
+        .distribution()
+        .unassigned_is(Stake(1000))
+        .pools_distribution_is(vec![
+            (alice_stake_pool.id(), Value(505)),
+            (bob_stake_pool.id(), Value(505)),
+        ]);
+}
+
+#[test]
+pub fn redelegated_rewards_follow_the_delegated_amounts() {
+    let (mut ledger, controller) = prepare_scenario()
+        .with_config(
+            ConfigBuilder::new()
+                .with_rewards(Value(100))
+                .with_treasury(Value(0))
+                .with_rewards_params(RewardParams::Linear {
+                    constant: 10,
+                    ratio: Ratio {
+                        numerator: 1,
+                        denominator: NonZeroU64::new(1).unwrap(),
+                    },
+                    epoch_start: 0,
+                    epoch_rate: NonZeroU32::new(1).unwrap(),
+                }),
+        )
+        .with_initials(vec![
+            wallet("Alice").with(1_000).owns("alice_stake_pool"),
+            wallet("Bob").with(1_000).owns("bob_stake_pool"),
+        ])
+        .with_stake_pools(vec![
+            stake_pool("alice_stake_pool").tax_ratio(1, 1),
+            stake_pool("bob_stake_pool").tax_ratio(1, 1),
+        ])
+        .build()
+        .unwrap();
+
+    let alice_stake_pool = controller.stake_pool("alice_stake_pool").unwrap();
+    let bob_stake_pool = controller.stake_pool("bob_stake_pool").unwrap();
+    let mut alice = controller.wallet("Alice").unwrap();
+
+    controller
+        .delegates_by_amounts(
+            &alice,
+            &[
+                (&alice_stake_pool, Value(500)),
+                (&bob_stake_pool, Value(250)),
+            ],
+            &mut ledger,
+        )
+        .unwrap();
+    alice.confirm_transaction();
+    controller
+        .redelegates_rewards(&alice, true, &mut ledger)
+        .unwrap();
+
+    assert!(ledger.apply_empty_praos_block(&alice_stake_pool).is_ok());
+    ledger.distribute_rewards().unwrap();
+
+    // the 9 of rewards are shared in the proportions of the amounts
+    LedgerStateVerifier::new(ledger.clone().into())
+        .info("after rewards collection")
+        .distribution()
+        .unassigned_is(Stake(1250))
+        .pools_distribution_is(vec![
+            (alice_stake_pool.id(), Value(506)),
+            (bob_stake_pool.id(), Value(253)),
+        ]);
+
+    alice.confirm_transaction();
+    controller
+        .redelegates_rewards(&alice, false, &mut ledger)
+        .unwrap();
+
+    LedgerStateVerifier::new(ledger.into())
+        .info("after the redelegation is disabled")
+        .distribution()
+        .unassigned_is(Stake(1259))
+        .pools_distribution_is(vec![
+            (alice_stake_pool.id(), Value(500)),
+            (bob_stake_pool.id(), Value(250)),
+        ]);
+}
@@ -71,3 +71,63 @@ pub fn update_slot_duration() {
 
     assert_eq!(final_slot_duration, ledger.settings().slot_duration);
 }
+
+#[test]
+pub fn settings_of_past_epochs_are_kept() {
+    let initial_slot_duration = 10;
+    let final_slot_duration = 100;
+    let leader_pair = TestGen::leader_pair();
+    let (mut ledger, controller) = prepare_scenario()
+        .with_config(
+            ConfigBuilder::new()
+                .with_slot_duration(initial_slot_duration)
+                .with_discrimination(Discrimination::Test)
+                .with_leaders(&[leader_pair.id()]),
+        )
+        .with_initials(vec![wallet(ALICE)
+            .key(EitherEd25519SecretKey::Normal(leader_pair.key()))
+            .with(1_000)])
+        .build()
+        .unwrap();
+
+    let mut alice = controller.wallet(ALICE).unwrap();
+    let mut config_params = ConfigParams::new();
+    config_params.push(ConfigParam::SlotDuration(final_slot_duration));
+
+    let proposal_id = controller
+        .update_proposal(
+            &alice,
+            UpdateProposal::new(config_params, leader_pair.id()),
+            &mut ledger,
+        )
+        .unwrap();
+    alice.confirm_transaction();
+    controller
+        .update_vote(
+            &alice,
+            UpdateVote::new(proposal_id, leader_pair.id()),
+            &mut ledger,
+        )
+        .unwrap();
+    alice.confirm_transaction();
+
+    ledger
+        .apply_empty_bft_block_with_date(
+            &leader_pair,
+            BlockDate {
+                epoch: 1,
+                slot_id: 0,
+            },
+        )
+        .unwrap();
+
+    let slot_duration_at = |epoch| {
+        ledger
+            .ledger
+            .settings_at(epoch)
+            .map(|settings| settings.slot_duration)
+    };
+    assert_eq!(slot_duration_at(0), Some(initial_slot_duration));
+    assert_eq!(slot_duration_at(1), Some(final_slot_duration));
+    assert_eq!(slot_duration_at(2), None);
+}
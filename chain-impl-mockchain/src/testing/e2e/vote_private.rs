@@ -1,3 +1,5 @@
+use crate::ledger::Error as LedgerError;
+use crate::setting::PROTOCOL_VERSION_UNIFIED_VOTE_TALLY;
 use crate::testing::decrypt_tally;
 use crate::testing::TestGen;
 use crate::testing::VoteTestGen;
@@ -5,6 +7,7 @@ use crate::tokens::name::TokenName;
 use crate::tokens::name::TOKEN_NAME_MAX_SIZE;
 use crate::vote::VoteError::AlreadyVoted;
 use crate::vote::VotePlanLedgerError::VoteError;
+use crate::vote::{PrivateTallyWorkflow, TallyStage, TallyWorkflowError};
 use crate::{
     certificate::VotePlan,
     fee::LinearFee,
@@ -17,6 +20,7 @@ use crate::{
     value::Value,
     vote::{Choice, PayloadType},
 };
+use chain_core::mempack::{ReadBuf, Readable};
 use imhamt::UpdateError::ValueCallbackError;
 
 const ALICE: &str = "Alice";
@@ -112,6 +116,83 @@ pub fn private_vote_cast_action_transfer_to_rewards_all_shares() {
         .has_remaining_rewards_equals_to(&Value(1100));
 }
 
+#[test]
+pub fn private_vote_tallied_with_a_single_vote_tally() {
+    let mut rng = TestGen::rand();
+    let favorable = Choice::new(1);
+    let members = VoteTestGen::committee_members_manager(MEMBERS_NO, THRESHOLD);
+
+    let voting_token = TokenName::try_from(vec![0u8; TOKEN_NAME_MAX_SIZE]).unwrap();
+
+    let (mut ledger, controller) = prepare_scenario()
+        .with_config(
+            ConfigBuilder::new()
+                .with_fee(LinearFee::new(1, 1, 1))
+                .with_rewards(Value(1000))
+                .with_protocol_version(PROTOCOL_VERSION_UNIFIED_VOTE_TALLY),
+        )
+        .with_initials(vec![wallet(ALICE)
+            .with(1_000)
+            .with_token(voting_token, 1_000)
+            .owns(STAKE_POOL)
+            .committee_member()])
+        .with_vote_plans(vec![vote_plan(VOTE_PLAN)
+            .owner(ALICE)
+            .consecutive_epoch_dates()
+            .payload_type(PayloadType::Private)
+            .committee_keys(members.members_keys())
+            .with_proposal(
+                proposal(VoteTestGen::external_proposal_id())
+                    .options(3)
+                    .action_transfer_to_rewards(100),
+            )])
+        .build()
+        .unwrap();
+
+    let mut alice = controller.wallet(ALICE).unwrap();
+    let vote_plan = controller.vote_plan(VOTE_PLAN).unwrap();
+    let proposal = vote_plan.proposal(0);
+
+    controller
+        .cast_vote_private(
+            &alice,
+            &vote_plan,
+            &proposal.id(),
+            favorable,
+            &mut ledger,
+            &mut rng,
+        )
+        .unwrap();
+    alice.confirm_transaction();
+
+    ledger.fast_forward_to(BlockDate {
+        epoch: 1,
+        slot_id: 1,
+    });
+
+    assert_eq!(
+        controller.encrypted_tally(&alice, &vote_plan, &mut ledger),
+        Err(LedgerError::EncryptedVoteTallyDeprecated {
+            since: PROTOCOL_VERSION_UNIFIED_VOTE_TALLY
+        })
+    );
+
+    let id = VotePlan::from(vote_plan.clone()).to_id();
+    let vote_plan_status = ledger.ledger.preview_private_tally(&id).unwrap();
+    let shares = decrypt_tally(&vote_plan_status, &members).unwrap();
+
+    controller
+        .tally_vote_private(&alice, &vote_plan, shares, &mut ledger)
+        .unwrap();
+
+    ledger.apply_protocol_changes().unwrap();
+
+    LedgerStateVerifier::new(ledger.into())
+        .info("rewards pot is increased")
+        .pots()
+        .has_remaining_rewards_equals_to(&Value(1100));
+}
+
 #[test]
 #[should_panic]
 pub fn private_vote_plan_without_keys() {
@@ -275,3 +356,161 @@ pub fn vote_on_different_proposal() {
         )
         .is_ok());
 }
+
+#[test]
+pub fn private_vote_tallied_with_a_resumed_tally_workflow() {
+    let mut rng = TestGen::rand();
+    let favorable = Choice::new(1);
+    let members = VoteTestGen::committee_members_manager(MEMBERS_NO, THRESHOLD);
+
+    let voting_token = TokenName::try_from(vec![0u8; TOKEN_NAME_MAX_SIZE]).unwrap();
+
+    let (mut ledger, controller) = prepare_scenario()
+        .with_config(
+            ConfigBuilder::new()
+                .with_fee(LinearFee::new(1, 1, 1))
+                .with_rewards(Value(1000)),
+        )
+        .with_initials(vec![wallet(ALICE)
+            .with(1_000)
+            .with_token(voting_token, 1_000)
+            .owns(STAKE_POOL)
+            .committee_member()])
+        .with_vote_plans(vec![vote_plan(VOTE_PLAN)
+            .owner(ALICE)
+            .consecutive_epoch_dates()
+            .payload_type(PayloadType::Private)
+            .committee_keys(members.members_keys())
+            .with_proposal(
+                proposal(VoteTestGen::external_proposal_id())
+                    .options(3)
+                    .action_transfer_to_rewards(100),
+            )])
+        .build()
+        .unwrap();
+
+    let mut alice = controller.wallet(ALICE).unwrap();
+    let vote_plan = controller.vote_plan(VOTE_PLAN).unwrap();
+    let proposal = vote_plan.proposal(0);
+
+    controller
+        .cast_vote_private(
+            &alice,
+            &vote_plan,
+            &proposal.id(),
+            favorable,
+            &mut ledger,
+            &mut rng,
+        )
+        .unwrap();
+    alice.confirm_transaction();
+
+    ledger.fast_forward_to(BlockDate {
+        epoch: 1,
+        slot_id: 1,
+    });
+
+    controller
+        .encrypted_tally(&alice, &vote_plan, &mut ledger)
+        .unwrap();
+    alice.confirm_transaction();
+
+    let vote_plan_id = VotePlan::from(vote_plan.clone()).to_id();
+    let status = |ledger: &crate::testing::TestLedger| {
+        ledger
+            .ledger
+            .active_vote_plans()
+            .into_iter()
+            .find(|status| status.id == vote_plan_id)
+            .unwrap()
+    };
+    let vote_plan_status = status(&ledger);
+
+    let mut workflow = PrivateTallyWorkflow::new(&vote_plan_status).unwrap();
+    let committee = members.members();
+
+    // the first member sends the shares of the second one
+    workflow
+        .add_shares(
+            &committee[0].public_key(),
+            committee[1].produce_decrypt_shares(&vote_plan_status),
+        )
+        .unwrap();
+    workflow
+        .add_shares(
+            &committee[1].public_key(),
+            committee[1].produce_decrypt_shares(&vote_plan_status),
+        )
+        .unwrap();
+    assert_eq!(
+        workflow.validate(&vote_plan_status),
+        Err(TallyWorkflowError::MissingShares { missing: 1 })
+    );
+
+    // restart while the shares are collected
+    let serialized = workflow.serialize();
+    let mut workflow = PrivateTallyWorkflow::read(&mut ReadBuf::from(serialized.as_ref())).unwrap();
+    assert_eq!(workflow.stage(), &TallyStage::CollectingShares);
+    assert_eq!(workflow.missing_members().count(), 1);
+
+    workflow
+        .add_shares(
+            &committee[2].public_key(),
+            committee[2].produce_decrypt_shares(&vote_plan_status),
+        )
+        .unwrap();
+    assert_eq!(
+        workflow.validate(&vote_plan_status),
+        Err(TallyWorkflowError::InvalidShare {
+            member: 0,
+            proposal: 0
+        })
+    );
+    assert_eq!(
+        workflow.missing_members().collect::<Vec<_>>(),
+        vec![&committee[0].public_key()]
+    );
+
+    workflow
+        .add_shares(
+            &committee[0].public_key(),
+            committee[0].produce_decrypt_shares(&vote_plan_status),
+        )
+        .unwrap();
+    workflow.validate(&vote_plan_status).unwrap();
+
+    let table = chain_vote::TallyOptimizationTable::generate_with_balance(
+        vote_plan_status.proposals[0]
+            .tally
+            .as_ref()
+            .unwrap()
+            .private_total_power()
+            .unwrap(),
+        1,
+    );
+    workflow.decrypt(&vote_plan_status, &table).unwrap();
+
+    // restart before the certificate is submitted
+    let serialized = workflow.serialize();
+    let mut workflow = PrivateTallyWorkflow::read(&mut ReadBuf::from(serialized.as_ref())).unwrap();
+    let decrypted_tally = workflow.decrypted_tally().unwrap();
+    let expected_tally = decrypt_tally(&vote_plan_status, &members).unwrap();
+    assert_eq!(
+        decrypted_tally
+            .iter()
+            .map(|proposal| proposal.tally_result.clone())
+            .collect::<Vec<_>>(),
+        expected_tally
+            .iter()
+            .map(|proposal| proposal.tally_result.clone())
+            .collect::<Vec<_>>()
+    );
+    assert!(!workflow.is_applied(&vote_plan_status));
+
+    controller
+        .tally_vote_private(&alice, &vote_plan, decrypted_tally, &mut ledger)
+        .unwrap();
+    workflow.submitted(TestGen::hash()).unwrap();
+    assert!(matches!(workflow.stage(), TallyStage::Submitted { .. }));
+    assert!(workflow.is_applied(&status(&ledger)));
+}
@@ -0,0 +1,236 @@
+//! Competing chains built on top of a common ancestor.
+//!
+//! A [`Fork`] captures the ledger state at the fork point, every [`Branch`]
+//! taken from it extends its own copy of that state with properly chained
+//! blocks (parent hash, chain length and date follow the branch tip). The
+//! branches can then be compared with the chain selection rule and the
+//! losing branch rolled back to the ancestor before adopting the winner.
+
+use crate::{
+    block::Block,
+    chaintypes::{ChainLength, HeaderId},
+    date::BlockDate,
+    fragment::Fragment,
+    ledger::Error,
+    testing::{
+        builders::{BftBlockBuilder, GenesisPraosBlockBuilder},
+        data::{LeaderPair, StakePool},
+        ledger::TestLedger,
+    },
+};
+
+/// The common ancestor of competing branches.
+#[derive(Clone)]
+pub struct Fork {
+    ancestor: TestLedger,
+    ancestor_id: HeaderId,
+}
+
+/// A chain of blocks built on top of the fork point, along with the ledger
+/// resulting from their application.
+#[derive(Clone)]
+pub struct Branch {
+    ledger: TestLedger,
+    tip: HeaderId,
+    blocks: Vec<Block>,
+}
+
+/// Outcome of the chain selection between the current branch and a candidate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Selection {
+    /// the current branch is kept, the candidate is discarded
+    KeepCurrent,
+    /// the candidate is adopted: `rollback` blocks of the current branch are
+    /// rolled back and `apply` blocks of the candidate are applied
+    SwitchToCandidate { rollback: usize, apply: usize },
+}
+
+impl Fork {
+    /// Fork from a ledger on which no block has been applied yet, the
+    /// ancestor is block0.
+    pub fn from_block0(ledger: &TestLedger) -> Self {
+        Self::new(ledger, ledger.block0_hash)
+    }
+
+    /// Fork from the ledger state resulting of the application of the block
+    /// `ancestor_id`.
+    pub fn new(ledger: &TestLedger, ancestor_id: HeaderId) -> Self {
+        Fork {
+            ancestor: ledger.clone(),
+            ancestor_id,
+        }
+    }
+
+    pub fn ancestor(&self) -> &TestLedger {
+        &self.ancestor
+    }
+
+    pub fn ancestor_id(&self) -> HeaderId {
+        self.ancestor_id
+    }
+
+    /// Start a new empty branch from the fork point.
+    pub fn branch(&self) -> Branch {
+        Branch {
+            ledger: self.ancestor.clone(),
+            tip: self.ancestor_id,
+            blocks: Vec::new(),
+        }
+    }
+
+    /// Chain selection between two branches of this fork: the longest chain
+    /// wins, on equal length the current branch is kept.
+    pub fn select(&self, current: &Branch, candidate: &Branch) -> Selection {
+        if candidate.chain_length() > current.chain_length() {
+            Selection::SwitchToCandidate {
+                rollback: current.blocks.len(),
+                apply: candidate.blocks.len(),
+            }
+        } else {
+            Selection::KeepCurrent
+        }
+    }
+
+    /// Roll back to the fork point and apply the blocks of `candidate`, as a
+    /// node would do when switching to a better chain.
+    pub fn reorg(&self, candidate: &Branch) -> Result<Branch, Error> {
+        let mut branch = self.branch();
+        for block in candidate.blocks() {
+            branch.apply(block.clone())?;
+        }
+        Ok(branch)
+    }
+}
+
+impl Branch {
+    pub fn ledger(&self) -> &TestLedger {
+        &self.ledger
+    }
+
+    pub fn blocks(&self) -> &[Block] {
+        &self.blocks
+    }
+
+    pub fn tip(&self) -> HeaderId {
+        self.tip
+    }
+
+    pub fn chain_length(&self) -> ChainLength {
+        self.ledger.chain_length()
+    }
+
+    pub fn date(&self) -> BlockDate {
+        self.ledger.date()
+    }
+
+    /// Leave the next `slots` slots of this branch empty.
+    pub fn skip_slots(&mut self, slots: u32) {
+        for _ in 0..slots {
+            self.ledger.forward_date();
+        }
+    }
+
+    /// Create a Genesis-Praos block on top of the branch tip and apply it.
+    pub fn produce_praos_block(
+        &mut self,
+        stake_pool: &StakePool,
+        fragments: Vec<Fragment>,
+    ) -> Result<&Block, Error> {
+        let block = GenesisPraosBlockBuilder::new()
+            .with_date(self.date())
+            .with_chain_length(self.chain_length())
+            .with_parent_id(self.tip)
+            .with_fragments(fragments)
+            .build(stake_pool, self.ledger.era());
+        self.apply(block)
+    }
+
+    /// Create a BFT block on top of the branch tip and apply it.
+    pub fn produce_bft_block(
+        &mut self,
+        leader: &LeaderPair,
+        fragments: Vec<Fragment>,
+    ) -> Result<&Block, Error> {
+        let block = BftBlockBuilder::new()
+            .with_date(self.date())
+            .with_chain_length(self.chain_length().increase())
+            .with_parent_id(self.tip)
+            .with_fragments(fragments)
+            .build(leader, self.ledger.era());
+        self.apply(block)
+    }
+
+    /// Create `count` empty Genesis-Praos blocks in consecutive slots.
+    pub fn produce_empty_praos_blocks(
+        &mut self,
+        stake_pool: &StakePool,
+        count: usize,
+    ) -> Result<(), Error> {
+        for _ in 0..count {
+            self.produce_praos_block(stake_pool, Vec::new())?;
+        }
+        Ok(())
+    }
+
+    /// Apply a block on top of the branch, distributing the rewards first if
+    /// the block is the first one of a new epoch.
+    pub fn apply(&mut self, block: Block) -> Result<&Block, Error> {
+        let header = block.header();
+        if header.block_date().epoch > self.date().epoch && self.ledger.can_distribute_reward() {
+            self.ledger.distribute_rewards()?;
+        }
+        let id = header.hash();
+        self.ledger.apply_block(block.clone())?;
+        self.tip = id;
+        self.blocks.push(block);
+        Ok(self.blocks.last().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::scenario::{prepare_scenario, stake_pool, wallet};
+
+    #[test]
+    pub fn longest_branch_is_selected_and_replayed_from_ancestor() {
+        let (ledger, controller) = prepare_scenario()
+            .with_initials(vec![
+                wallet("Alice").with(1_000).owns("alice_pool"),
+                wallet("Bob").with(1_000).owns("bob_pool"),
+            ])
+            .with_stake_pools(vec![stake_pool("alice_pool"), stake_pool("bob_pool")])
+            .build()
+            .unwrap();
+        let alice_pool = controller.stake_pool("alice_pool").unwrap();
+        let bob_pool = controller.stake_pool("bob_pool").unwrap();
+
+        let fork = Fork::from_block0(&ledger);
+        let mut current = fork.branch();
+        let mut candidate = fork.branch();
+
+        current.produce_empty_praos_blocks(&alice_pool, 2).unwrap();
+        candidate.skip_slots(1);
+        candidate.produce_empty_praos_blocks(&bob_pool, 2).unwrap();
+        assert_eq!(fork.select(&current, &candidate), Selection::KeepCurrent);
+
+        candidate.produce_empty_praos_blocks(&bob_pool, 1).unwrap();
+        assert_eq!(
+            fork.select(&current, &candidate),
+            Selection::SwitchToCandidate {
+                rollback: 2,
+                apply: 3
+            }
+        );
+
+        let adopted = fork.reorg(&candidate).unwrap();
+        assert_eq!(adopted.tip(), candidate.tip());
+        assert_eq!(adopted.chain_length(), candidate.chain_length());
+        assert!(adopted.ledger().ledger == candidate.ledger().ledger);
+        assert!(current.ledger().ledger != candidate.ledger().ledger);
+
+        // the blocks of the losing branch do not chain on the adopted one
+        let mut adopted = adopted;
+        assert!(adopted.apply(current.blocks()[1].clone()).is_err());
+    }
+}
@@ -82,11 +82,15 @@ impl VoteTestGen {
     }
 
     pub fn vote_plan() -> VotePlan {
+        Self::vote_plan_with_proposals(3)
+    }
+
+    pub fn vote_plan_with_proposals(count: usize) -> VotePlan {
         VotePlan::new(
             BlockDate::from_epoch_slot_id(1, 0),
             BlockDate::from_epoch_slot_id(2, 0),
             BlockDate::from_epoch_slot_id(3, 0),
-            VoteTestGen::proposals(3),
+            VoteTestGen::proposals(count),
             vote::PayloadType::Public,
             Vec::new(),
             TokenIdentifier {
@@ -5,7 +5,7 @@ use crate::config::EvmConfig;
 use crate::{
     account::Ledger as AccountLedger,
     block::Block,
-    certificate::{MintToken, PoolId},
+    certificate::{CertificatePolicy, MintToken, PoolId},
     chaintypes::{ChainLength, ConsensusType, ConsensusVersion, HeaderId},
     config::{Block0Date, ConfigParam, RewardParams},
     date::BlockDate,
@@ -14,10 +14,12 @@ use crate::{
     key::BftLeaderId,
     leadership::genesis::LeadershipData,
     ledger::{
-        check::CHECK_TX_MAXIMUM_INPUTS, Error, LeadersParticipationRecord, Ledger,
-        LedgerParameters, Pots, RewardsInfoParameters,
+        check::CHECK_TX_MAXIMUM_INPUTS, Block0Supply, EpochRewardsInfo, Error,
+        LeadersParticipationRecord, Ledger, LedgerEvents, LedgerParameters, Pots,
+        RewardsInfoParameters,
     },
     milli::Milli,
+    names::NameRegistryParams,
     rewards::{Ratio, TaxType},
     setting::Settings,
     stake::PoolsState,
@@ -48,7 +50,14 @@ pub struct ConfigBuilder {
     linear_fee: Option<LinearFee>,
     per_certificate_fee: Option<PerCertificateFee>,
     per_vote_certificate_fee: Option<PerVoteCertificateFee>,
+    per_certificate_byte_fee: Option<NonZeroU64>,
     leaders: Vec<BftLeaderId>,
+    bft_quorum: Option<u8>,
+    protocol_version: Option<u32>,
+    certificate_policy: Option<CertificatePolicy>,
+    max_vote_plans_per_epoch: Option<u32>,
+    max_proposals_per_vote_plan: Option<u32>,
+    epoch_stability_depth: Option<u32>,
     committees_ids: Vec<CommitteeId>,
     rewards: Value,
     treasury: Value,
@@ -61,6 +70,10 @@ pub struct ConfigBuilder {
     consensus_version: ConsensusVersion,
     pool_capping_ratio: Ratio,
     transaction_max_expiry_epochs: Option<u8>,
+    name_registry: Option<NameRegistryParams>,
+    minimum_pool_pledge: Option<Value>,
+    pool_performance_rewards: bool,
+    total_supply: Option<Value>,
     #[cfg(feature = "evm")]
     evm_params: EvmConfig,
 }
@@ -79,9 +92,16 @@ impl ConfigBuilder {
             active_slots_coeff: Milli::HALF,
             discrimination: Discrimination::Test,
             leaders: Vec::new(),
+            bft_quorum: None,
+            protocol_version: None,
+            certificate_policy: None,
+            max_vote_plans_per_epoch: None,
+            max_proposals_per_vote_plan: None,
+            epoch_stability_depth: None,
             linear_fee: None,
             per_certificate_fee: None,
             per_vote_certificate_fee: None,
+            per_certificate_byte_fee: None,
             committees_ids: Vec::new(),
             pool_capping_ratio: Ratio {
                 numerator: 0,
@@ -105,6 +125,10 @@ impl ConfigBuilder {
             block0_date: Block0Date(0),
             consensus_version: ConsensusVersion::Bft,
             transaction_max_expiry_epochs: None,
+            name_registry: None,
+            minimum_pool_pledge: None,
+            pool_performance_rewards: false,
+            total_supply: None,
             #[cfg(feature = "evm")]
             evm_params: EvmConfig::Istanbul,
         }
@@ -167,6 +191,36 @@ impl ConfigBuilder {
         self
     }
 
+    pub fn with_bft_quorum(mut self, bft_quorum: u8) -> Self {
+        self.bft_quorum = Some(bft_quorum);
+        self
+    }
+
+    pub fn with_protocol_version(mut self, protocol_version: u32) -> Self {
+        self.protocol_version = Some(protocol_version);
+        self
+    }
+
+    pub fn with_certificate_policy(mut self, certificate_policy: CertificatePolicy) -> Self {
+        self.certificate_policy = Some(certificate_policy);
+        self
+    }
+
+    pub fn with_max_vote_plans_per_epoch(mut self, max_vote_plans_per_epoch: u32) -> Self {
+        self.max_vote_plans_per_epoch = Some(max_vote_plans_per_epoch);
+        self
+    }
+
+    pub fn with_max_proposals_per_vote_plan(mut self, max_proposals_per_vote_plan: u32) -> Self {
+        self.max_proposals_per_vote_plan = Some(max_proposals_per_vote_plan);
+        self
+    }
+
+    pub fn with_epoch_stability_depth(mut self, epoch_stability_depth: u32) -> Self {
+        self.epoch_stability_depth = Some(epoch_stability_depth);
+        self
+    }
+
     pub fn with_fee(mut self, linear_fee: LinearFee) -> Self {
         self.linear_fee = Some(linear_fee);
         self
@@ -185,6 +239,26 @@ impl ConfigBuilder {
         self
     }
 
+    pub fn with_per_certificate_byte_fee(mut self, per_certificate_byte_fee: NonZeroU64) -> Self {
+        self.per_certificate_byte_fee = Some(per_certificate_byte_fee);
+        self
+    }
+
+    pub fn with_minimum_pool_pledge(mut self, minimum_pool_pledge: Value) -> Self {
+        self.minimum_pool_pledge = Some(minimum_pool_pledge);
+        self
+    }
+
+    pub fn with_pool_performance_rewards(mut self, pool_performance_rewards: bool) -> Self {
+        self.pool_performance_rewards = pool_performance_rewards;
+        self
+    }
+
+    pub fn with_total_supply(mut self, total_supply: Value) -> Self {
+        self.total_supply = Some(total_supply);
+        self
+    }
+
     pub fn with_slots_per_epoch(mut self, slots_per_epoch: u32) -> Self {
         self.slots_per_epoch = slots_per_epoch;
         self
@@ -220,6 +294,11 @@ impl ConfigBuilder {
         self
     }
 
+    pub fn with_name_registry(mut self, params: NameRegistryParams) -> Self {
+        self.name_registry = Some(params);
+        self
+    }
+
     #[cfg(feature = "evm")]
     pub fn with_evm_params(mut self, params: EvmConfig) -> Self {
         self.evm_params = params;
@@ -248,6 +327,30 @@ impl ConfigBuilder {
             ie.push(ConfigParam::AddBftLeader(leader_id));
         }
 
+        if let Some(bft_quorum) = self.bft_quorum {
+            ie.push(ConfigParam::BftQuorum(bft_quorum));
+        }
+
+        if let Some(protocol_version) = self.protocol_version {
+            ie.push(ConfigParam::ProtocolVersion(protocol_version));
+        }
+
+        if let Some(certificate_policy) = self.certificate_policy {
+            ie.push(ConfigParam::CertificatePolicy(certificate_policy));
+        }
+
+        if let Some(limit) = self.max_vote_plans_per_epoch {
+            ie.push(ConfigParam::MaxVotePlansPerEpoch(limit));
+        }
+
+        if let Some(limit) = self.max_proposals_per_vote_plan {
+            ie.push(ConfigParam::MaxProposalsPerVotePlan(limit));
+        }
+
+        if let Some(epoch_stability_depth) = self.epoch_stability_depth {
+            ie.push(ConfigParam::EpochStabilityDepth(epoch_stability_depth));
+        }
+
         ie.push(ConfigParam::RewardPot(self.rewards));
         ie.push(ConfigParam::TreasuryAdd(self.treasury));
         ie.push(ConfigParam::TreasuryParams(self.treasury_params));
@@ -279,12 +382,34 @@ impl ConfigBuilder {
             ));
         }
 
+        if let Some(per_certificate_byte_fee) = self.per_certificate_byte_fee {
+            ie.push(ConfigParam::PerCertificateByteFee(
+                per_certificate_byte_fee.get(),
+            ));
+        }
+
         if let Some(transaction_max_expiry_epochs) = self.transaction_max_expiry_epochs {
             ie.push(ConfigParam::TransactionMaxExpiryEpochs(
                 transaction_max_expiry_epochs,
             ));
         }
 
+        if let Some(name_registry) = self.name_registry {
+            ie.push(ConfigParam::NameRegistryParams(name_registry));
+        }
+
+        if let Some(minimum_pool_pledge) = self.minimum_pool_pledge {
+            ie.push(ConfigParam::MinimumPoolPledge(minimum_pool_pledge));
+        }
+
+        if self.pool_performance_rewards {
+            ie.push(ConfigParam::PoolPerformanceRewards(true));
+        }
+
+        if let Some(total_supply) = self.total_supply {
+            ie.push(ConfigParam::TotalSupply(total_supply));
+        }
+
         for committee_id in self.committees_ids {
             ie.push(ConfigParam::AddCommitteeId(committee_id));
         }
@@ -362,7 +487,7 @@ impl LedgerBuilder {
 
     // add a fragment that pre-fill the address with a specific value at ledger start
     pub fn prefill_address(self, address: Address, value: Value) -> Self {
-        self.prefill_output(Output { address, value })
+        self.prefill_output(Output::from_address(address, value))
     }
 
     pub fn prefill_output(self, output: Output<Address>) -> Self {
@@ -497,6 +622,9 @@ impl LedgerBuilder {
         fragments.extend_from_slice(&self.fragments);
         fragments.extend_from_slice(&self.certs);
 
+        // reject the broken supplies before applying anything
+        Block0Supply::from_fragments(&fragments)?.validate()?;
+
         let faucets = self.faucets;
         Ledger::new(block0_hash, &fragments).map(|ledger| {
             let parameters = ledger.get_ledger_parameters();
@@ -562,6 +690,32 @@ impl TestLedger {
         Ok(())
     }
 
+    pub fn apply_block_with_events(&mut self, block: Block) -> Result<LedgerEvents, Error> {
+        let header_meta = block.header().get_content_eval_context();
+        let (ledger, events) = self.ledger.apply_block_with_events(
+            self.ledger.get_ledger_parameters(),
+            block.contents(),
+            &header_meta,
+        )?;
+        self.ledger = ledger;
+        Ok(events)
+    }
+
+    /// Apply the block and [`sync`](Wallet::sync) the wallets with the
+    /// resulting ledger, the wallets are left untouched if the block is
+    /// rejected
+    pub fn apply_block_and_sync(
+        &mut self,
+        block: Block,
+        wallets: &mut [&mut Wallet],
+    ) -> Result<LedgerEvents, Error> {
+        let events = self.apply_block_with_events(block)?;
+        for wallet in wallets.iter_mut() {
+            wallet.sync(&self.ledger);
+        }
+        Ok(events)
+    }
+
     pub fn apply_protocol_changes(&mut self) -> Result<(), Error> {
         self.ledger = self.ledger.apply_protocol_changes()?;
         Ok(())
@@ -640,16 +794,16 @@ impl TestLedger {
         self.ledger.leaders_log.increase_for(pool_id);
     }
 
-    pub fn distribute_rewards(&mut self) -> Result<(), Error> {
+    pub fn distribute_rewards(&mut self) -> Result<EpochRewardsInfo, Error> {
         match self.ledger.distribute_rewards(
             &self.ledger.get_stake_distribution(),
             &self.ledger.get_ledger_parameters(),
             RewardsInfoParameters::default(),
         ) {
             Err(err) => Err(err),
-            Ok((ledger, _)) => {
+            Ok((ledger, rewards_info)) => {
                 self.ledger = ledger;
-                Ok(())
+                Ok(rewards_info)
             }
         }
     }
@@ -1,15 +1,22 @@
 pub mod arbitrary;
 pub mod builders;
+pub mod chrono;
 pub mod data;
 #[cfg(test)]
 pub mod e2e;
+pub mod fork;
 mod gen;
 pub mod ledger;
+pub mod network;
 pub mod scenario;
+pub mod strategy;
 pub mod verifiers;
 pub use arbitrary::*;
 pub use builders::*;
+pub use chrono::ClockSimulator;
 pub use data::KeysDb;
+pub use fork::{Branch, Fork, Selection};
 pub use gen::{TestGen, VoteTestGen};
 pub use ledger::{ConfigBuilder, LedgerBuilder, TestLedger, UtxoDb};
+pub use network::Network;
 pub mod serialization;
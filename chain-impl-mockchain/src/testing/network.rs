@@ -0,0 +1,659 @@
+//! Deterministic simulation of a network of nodes.
+//!
+//! Every [`Node`] follows its own chain, built on top of a common block0, and
+//! keeps a mempool of the fragments waiting to be included in a block. The
+//! nodes exchange blocks and fragments through an in-memory transport where
+//! every link has a latency (in slots) and can be cut by partitioning the
+//! network. Time only passes when the [`Network`] clock ticks: the messages
+//! due at the new slot are delivered, then the nodes elected for the slot
+//! produce a block with the content of their mempool.
+//!
+//! Nothing depends on the wall clock or on the iteration order of a hash
+//! map: running the same scenario twice results in the same chains.
+
+use crate::{
+    block::Block,
+    chaintypes::{ChainLength, HeaderId},
+    date::BlockDate,
+    fragment::{Fragment, FragmentId},
+    leadership::{bft, genesis},
+    ledger::Error,
+    testing::{
+        builders::{BftBlockBuilder, GenesisPraosBlockBuilder},
+        chrono::BlockProducer,
+        fork::{Branch, Fork},
+        ledger::TestLedger,
+    },
+};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Index of a node in the [`Network`]
+pub type NodeId = usize;
+
+/// What the nodes send to each other.
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// a block adopted by the sender
+    Block(Block),
+    /// request for a block the sender announced but the receiver does not know
+    GetBlock(HeaderId),
+    /// a fragment accepted in the mempool of the sender
+    Fragment(Fragment),
+}
+
+#[derive(Debug, Clone)]
+struct Envelope {
+    from: NodeId,
+    to: NodeId,
+    message: Message,
+}
+
+/// Outcome of the reception of a block by a node
+enum Reception {
+    /// the block is already known, invalid, or not better than the current
+    /// chain
+    Ignored,
+    /// the block cannot be evaluated before this ancestor is received
+    MissingAncestor(HeaderId),
+    /// the block, or a block waiting for it, is the new tip
+    Adopted,
+}
+
+#[derive(Clone)]
+pub struct Node {
+    producer: BlockProducer,
+    branch: Branch,
+    blocks: HashMap<HeaderId, Block>,
+    /// blocks received before one of their ancestors
+    orphans: Vec<HeaderId>,
+    mempool: Vec<Fragment>,
+}
+
+impl Node {
+    fn new(fork: &Fork, producer: BlockProducer) -> Self {
+        Node {
+            producer,
+            branch: fork.branch(),
+            blocks: HashMap::new(),
+            orphans: Vec::new(),
+            mempool: Vec::new(),
+        }
+    }
+
+    pub fn producer(&self) -> &BlockProducer {
+        &self.producer
+    }
+
+    pub fn tip(&self) -> HeaderId {
+        self.branch.tip()
+    }
+
+    pub fn chain_length(&self) -> ChainLength {
+        self.branch.chain_length()
+    }
+
+    /// the ledger resulting of the application of the adopted chain
+    pub fn ledger(&self) -> &TestLedger {
+        self.branch.ledger()
+    }
+
+    /// the blocks of the adopted chain, from the oldest to the tip
+    pub fn chain(&self) -> &[Block] {
+        self.branch.blocks()
+    }
+
+    pub fn mempool(&self) -> &[Fragment] {
+        &self.mempool
+    }
+
+    /// whether the fragment is in one of the blocks of the adopted chain
+    pub fn has_in_chain(&self, fragment_id: &FragmentId) -> bool {
+        self.chain()
+            .iter()
+            .flat_map(|block| block.fragments())
+            .any(|fragment| fragment.hash() == *fragment_id)
+    }
+
+    fn block(&self, id: &HeaderId) -> Option<&Block> {
+        self.blocks.get(id)
+    }
+
+    fn is_leader(&self, date: BlockDate) -> bool {
+        let ledger = &self.ledger().ledger;
+        match &self.producer {
+            BlockProducer::None => false,
+            BlockProducer::Bft(leader) => match bft::LeadershipData::new(ledger) {
+                Some(leadership) => leadership.get_leader_at(date) == leader.id(),
+                None => false,
+            },
+            BlockProducer::Praos(stake_pool) => matches!(
+                genesis::LeadershipData::new(date.epoch, ledger).leader(
+                    &stake_pool.id(),
+                    stake_pool.vrf().private_key(),
+                    date
+                ),
+                Ok(Some(_))
+            ),
+        }
+    }
+
+    /// Add the fragment to the mempool if it is new and applies on top of
+    /// the tip.
+    fn accept_fragment(&mut self, fragment: &Fragment, date: BlockDate) -> bool {
+        let id = fragment.hash();
+        if self.mempool.iter().any(|pending| pending.hash() == id) || self.has_in_chain(&id) {
+            return false;
+        }
+        if self
+            .ledger()
+            .clone()
+            .apply_fragment(fragment, date)
+            .is_err()
+        {
+            return false;
+        }
+        self.mempool.push(fragment.clone());
+        true
+    }
+
+    /// Drop the fragments of the mempool which no longer apply on top of the
+    /// tip (e.g. expired), returning the remaining ones.
+    fn revalidate_mempool(&mut self, date: BlockDate) -> Vec<Fragment> {
+        let mut ledger = self.ledger().clone();
+        let mut valid = Vec::new();
+        self.mempool.retain(|fragment| {
+            let is_valid = ledger.apply_fragment(fragment, date).is_ok();
+            if is_valid {
+                valid.push(fragment.clone());
+            }
+            is_valid
+        });
+        valid
+    }
+
+    /// Produce a block at `date` if the node is elected for it, the block
+    /// following the one of `previous_slot`.
+    fn produce(
+        &mut self,
+        fragments: Vec<Fragment>,
+        previous_slot: BlockDate,
+        date: BlockDate,
+    ) -> Result<Option<Block>, Error> {
+        if !self.is_leader(date) {
+            return Ok(None);
+        }
+        let era = self.ledger().era();
+        let block = match &self.producer {
+            BlockProducer::None => return Ok(None),
+            BlockProducer::Bft(leader) => BftBlockBuilder::new()
+                .with_date(previous_slot)
+                .with_chain_length(self.chain_length().increase())
+                .with_parent_id(self.tip())
+                .with_fragments(fragments)
+                .build(leader, era),
+            BlockProducer::Praos(stake_pool) => GenesisPraosBlockBuilder::new()
+                .with_date(previous_slot)
+                .with_chain_length(self.chain_length())
+                .with_parent_id(self.tip())
+                .with_fragments(fragments)
+                .build(stake_pool, era),
+        };
+
+        let mut branch = self.branch.clone();
+        branch.apply(block.clone())?;
+        self.blocks.insert(block.header().hash(), block.clone());
+        self.adopt(branch);
+        Ok(Some(block))
+    }
+
+    fn receive_block(&mut self, fork: &Fork, block: Block) -> Reception {
+        let id = block.header().hash();
+        if id == fork.ancestor_id() || self.blocks.contains_key(&id) {
+            return Reception::Ignored;
+        }
+        self.blocks.insert(id, block);
+        self.orphans.push(id);
+
+        let mut missing = None;
+        let mut candidates = Vec::new();
+        for orphan in std::mem::take(&mut self.orphans) {
+            match self.missing_ancestor(fork, orphan) {
+                Some(ancestor) => {
+                    if orphan == id {
+                        missing = Some(ancestor);
+                    }
+                    self.orphans.push(orphan);
+                }
+                None => candidates.push(orphan),
+            }
+        }
+        if let Some(ancestor) = missing {
+            return Reception::MissingAncestor(ancestor);
+        }
+
+        let mut adopted = false;
+        for candidate in candidates {
+            adopted |= self.try_switch(fork, candidate);
+        }
+        if adopted {
+            Reception::Adopted
+        } else {
+            Reception::Ignored
+        }
+    }
+
+    fn missing_ancestor(&self, fork: &Fork, mut id: HeaderId) -> Option<HeaderId> {
+        while id != fork.ancestor_id() {
+            match self.blocks.get(&id) {
+                Some(block) => id = block.header().block_parent_hash(),
+                None => return Some(id),
+            }
+        }
+        None
+    }
+
+    /// Switch to the chain ending with `tip` if it is longer than the current
+    /// one, following the selection rule of [`Fork::select`].
+    fn try_switch(&mut self, fork: &Fork, tip: HeaderId) -> bool {
+        if self.blocks[&tip].header().chain_length() <= self.chain_length() {
+            return false;
+        }
+
+        // walk back to the current tip, or to the fork point if the candidate
+        // is on another branch
+        let mut blocks = Vec::new();
+        let mut id = tip;
+        while id != self.tip() && id != fork.ancestor_id() {
+            let block = self.blocks[&id].clone();
+            id = block.header().block_parent_hash();
+            blocks.push(block);
+        }
+        let mut candidate = if id == self.tip() {
+            self.branch.clone()
+        } else {
+            fork.branch()
+        };
+        for block in blocks.into_iter().rev() {
+            if candidate.apply(block).is_err() {
+                return false;
+            }
+        }
+        self.adopt(candidate);
+        true
+    }
+
+    /// Adopt the branch, the fragments of the rolled back blocks go back to
+    /// the mempool unless they are in the adopted chain.
+    fn adopt(&mut self, branch: Branch) {
+        let common = self
+            .chain()
+            .iter()
+            .zip(branch.blocks())
+            .take_while(|(current, adopted)| current.header().hash() == adopted.header().hash())
+            .count();
+        let included: HashSet<FragmentId> = branch.blocks()[common..]
+            .iter()
+            .flat_map(|block| block.fragments())
+            .map(|fragment| fragment.hash())
+            .collect();
+
+        let mut mempool: Vec<Fragment> = self.chain()[common..]
+            .iter()
+            .flat_map(|block| block.fragments())
+            .filter(|fragment| !included.contains(&fragment.hash()))
+            .cloned()
+            .collect();
+        mempool.extend(
+            self.mempool
+                .drain(..)
+                .filter(|fragment| !included.contains(&fragment.hash())),
+        );
+        self.mempool = mempool;
+        self.branch = branch;
+    }
+}
+
+/// A network of nodes started from the same block0, driven by a simulated
+/// clock.
+pub struct Network {
+    fork: Fork,
+    nodes: Vec<Node>,
+    date: BlockDate,
+    /// number of slots elapsed since the start of the simulation
+    slot: u64,
+    latency: u32,
+    link_latencies: HashMap<(NodeId, NodeId), u32>,
+    /// group of every node when the network is partitioned
+    partition: Option<Vec<Option<usize>>>,
+    /// messages keyed by their delivery slot and sending order
+    in_flight: BTreeMap<(u64, u64), Envelope>,
+    sent: u64,
+}
+
+impl Network {
+    /// Create a network of one node per producer, every node starting from
+    /// the block0 of the ledger. Nodes with [`BlockProducer::None`] only
+    /// relay blocks and fragments.
+    pub fn new(ledger: &TestLedger, producers: Vec<BlockProducer>) -> Self {
+        let fork = Fork::from_block0(ledger);
+        let nodes = producers
+            .into_iter()
+            .map(|producer| Node::new(&fork, producer))
+            .collect();
+        Network {
+            fork,
+            nodes,
+            date: ledger.date(),
+            slot: 0,
+            latency: 1,
+            link_latencies: HashMap::new(),
+            partition: None,
+            in_flight: BTreeMap::new(),
+            sent: 0,
+        }
+    }
+
+    /// Number of slots a message takes to reach its destination, 1 by
+    /// default. A message is received at the beginning of a slot, before
+    /// the blocks of the slot are produced, so a latency of 0 behaves as 1.
+    pub fn with_latency(mut self, latency: u32) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Set the latency of the link between two nodes, in both directions.
+    pub fn set_link_latency(&mut self, a: NodeId, b: NodeId, latency: u32) {
+        self.link_latencies.insert((a.min(b), a.max(b)), latency);
+    }
+
+    /// Split the network: nodes only exchange messages within their group,
+    /// the messages between groups (including those already in flight) are
+    /// lost. Nodes in no group are isolated.
+    pub fn partition(&mut self, groups: &[&[NodeId]]) {
+        let mut membership = vec![None; self.nodes.len()];
+        for (group, nodes) in groups.iter().enumerate() {
+            for node in nodes.iter() {
+                membership[*node] = Some(group);
+            }
+        }
+        self.partition = Some(membership);
+    }
+
+    /// Reconnect all the nodes, each of them announcing its tip to the
+    /// others.
+    pub fn heal(&mut self) {
+        self.partition = None;
+        for id in 0..self.nodes.len() {
+            if let Some(tip) = self.nodes[id].chain().last().cloned() {
+                self.broadcast(id, Message::Block(tip));
+            }
+        }
+    }
+
+    pub fn date(&self) -> BlockDate {
+        self.date
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id]
+    }
+
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    /// number of messages sent and not yet delivered
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    /// whether all the nodes have adopted the same tip
+    pub fn is_synchronized(&self) -> bool {
+        self.nodes
+            .windows(2)
+            .all(|pair| pair[0].tip() == pair[1].tip())
+    }
+
+    /// Submit a fragment to a node, as a client would. The fragment is
+    /// gossiped to the peers if the node accepts it in its mempool.
+    pub fn submit(&mut self, node: NodeId, fragment: Fragment) -> bool {
+        let accepted = self.nodes[node].accept_fragment(&fragment, self.date);
+        if accepted {
+            self.broadcast(node, Message::Fragment(fragment));
+        }
+        accepted
+    }
+
+    /// Move to the next slot: the messages due are delivered, the expired
+    /// fragments are dropped from the mempools and the leaders of the slot
+    /// produce their block.
+    pub fn tick(&mut self) -> Result<(), Error> {
+        let previous_slot = self.date;
+        self.date = self.date.next(self.fork.ancestor().era());
+        self.slot += 1;
+        self.deliver(self.slot);
+
+        for id in 0..self.nodes.len() {
+            let node = &mut self.nodes[id];
+            let fragments = node.revalidate_mempool(self.date);
+            if let Some(block) = node.produce(fragments, previous_slot, self.date)? {
+                self.broadcast(id, Message::Block(block));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn advance_slots(&mut self, slots: u32) -> Result<(), Error> {
+        for _ in 0..slots {
+            self.tick()?;
+        }
+        Ok(())
+    }
+
+    /// Tick until the given date is reached.
+    pub fn advance_to(&mut self, date: BlockDate) -> Result<(), Error> {
+        while self.date < date {
+            self.tick()?;
+        }
+        Ok(())
+    }
+
+    /// Deliver all the messages in flight, including the ones sent in reply,
+    /// without moving the clock: as if the network was given enough time to
+    /// settle before the next slot.
+    pub fn flush(&mut self) {
+        self.deliver(u64::MAX);
+    }
+
+    fn deliver(&mut self, until: u64) {
+        loop {
+            let key = match self.in_flight.keys().next() {
+                Some(key) if key.0 <= until => *key,
+                _ => break,
+            };
+            let envelope = self.in_flight.remove(&key).unwrap();
+            if self.connected(envelope.from, envelope.to) {
+                self.receive(envelope);
+            }
+        }
+    }
+
+    fn receive(&mut self, envelope: Envelope) {
+        let Envelope { from, to, message } = envelope;
+        match message {
+            Message::Block(block) => match self.nodes[to].receive_block(&self.fork, block) {
+                Reception::Ignored => (),
+                Reception::MissingAncestor(ancestor) => {
+                    self.send(to, from, Message::GetBlock(ancestor))
+                }
+                Reception::Adopted => {
+                    let tip = self.nodes[to].chain().last().cloned().unwrap();
+                    self.broadcast(to, Message::Block(tip));
+                }
+            },
+            Message::GetBlock(id) => {
+                if let Some(block) = self.nodes[to].block(&id).cloned() {
+                    self.send(to, from, Message::Block(block));
+                }
+            }
+            Message::Fragment(fragment) => {
+                if self.nodes[to].accept_fragment(&fragment, self.date) {
+                    self.broadcast(to, Message::Fragment(fragment));
+                }
+            }
+        }
+    }
+
+    fn latency(&self, from: NodeId, to: NodeId) -> u32 {
+        self.link_latencies
+            .get(&(from.min(to), from.max(to)))
+            .copied()
+            .unwrap_or(self.latency)
+    }
+
+    fn connected(&self, from: NodeId, to: NodeId) -> bool {
+        match &self.partition {
+            None => true,
+            Some(membership) => membership[from].is_some() && membership[from] == membership[to],
+        }
+    }
+
+    fn send(&mut self, from: NodeId, to: NodeId, message: Message) {
+        if !self.connected(from, to) {
+            return;
+        }
+        let delivery = self.slot + u64::from(self.latency(from, to).max(1));
+        self.in_flight
+            .insert((delivery, self.sent), Envelope { from, to, message });
+        self.sent += 1;
+    }
+
+    fn broadcast(&mut self, from: NodeId, message: Message) {
+        for to in 0..self.nodes.len() {
+            if to != from {
+                self.send(from, to, message.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{
+            builders::TestTxBuilder,
+            data::{LeaderPair, Wallet},
+            ledger::{ConfigBuilder, LedgerBuilder},
+            TestGen,
+        },
+        value::Value,
+    };
+
+    fn bft_network(leaders: &[LeaderPair], relays: usize) -> (TestLedger, Network) {
+        let leader_ids: Vec<_> = leaders.iter().map(LeaderPair::id).collect();
+        let ledger = LedgerBuilder::from_config(
+            ConfigBuilder::new()
+                .with_slots_per_epoch(60)
+                .with_leaders(&leader_ids),
+        )
+        .faucet_value(Value(1000))
+        .build()
+        .unwrap();
+
+        let producers = leaders
+            .iter()
+            .cloned()
+            .map(BlockProducer::Bft)
+            .chain(std::iter::repeat(BlockProducer::None).take(relays))
+            .collect();
+        let network = Network::new(&ledger, producers);
+        (ledger, network)
+    }
+
+    fn transfer(ledger: &mut TestLedger, valid_until: BlockDate) -> Fragment {
+        let output = Wallet::from_value(Value(0)).make_output_with_value(Value(1));
+        TestTxBuilder::new(ledger.block0_hash)
+            .move_to_outputs_from_faucet_with_validity(ledger, Some(valid_until), &[output])
+            .get_fragment()
+    }
+
+    #[test]
+    pub fn nodes_converge_and_include_gossiped_fragments() {
+        let leaders = [
+            TestGen::leader_pair(),
+            TestGen::leader_pair(),
+            TestGen::leader_pair(),
+        ];
+        let (mut ledger, mut network) = bft_network(&leaders, 1);
+
+        let fragment = transfer(&mut ledger, BlockDate::first().next_epoch());
+        assert!(network.submit(3, fragment.clone()));
+        assert!(!network.submit(3, fragment.clone()));
+
+        network.advance_slots(9).unwrap();
+        network.flush();
+
+        assert!(network.is_synchronized());
+        for node in network.nodes() {
+            assert_eq!(node.chain_length(), ChainLength(9));
+            assert!(node.has_in_chain(&fragment.hash()));
+            assert!(node.mempool().is_empty());
+        }
+    }
+
+    #[test]
+    pub fn longest_chain_wins_when_partition_heals() {
+        let leaders = [
+            TestGen::leader_pair(),
+            TestGen::leader_pair(),
+            TestGen::leader_pair(),
+        ];
+        let (_, mut network) = bft_network(&leaders, 0);
+
+        network.partition(&[&[0, 1], &[2]]);
+        network.advance_slots(9).unwrap();
+        network.flush();
+
+        // the majority side leads 2 slots out of 3
+        assert_eq!(network.node(0).tip(), network.node(1).tip());
+        assert_eq!(network.node(0).chain_length(), ChainLength(6));
+        assert_eq!(network.node(2).chain_length(), ChainLength(3));
+
+        network.heal();
+        network.flush();
+        assert!(network.is_synchronized());
+        assert_eq!(network.node(2).chain_length(), ChainLength(6));
+        assert!(network.node(2).ledger().ledger == network.node(0).ledger().ledger);
+    }
+
+    #[test]
+    pub fn fragment_expires_in_partition_without_leader() {
+        let leaders = [TestGen::leader_pair()];
+        let (mut ledger, mut network) = bft_network(&leaders, 1);
+
+        let valid_until = BlockDate {
+            epoch: 0,
+            slot_id: 5,
+        };
+        let fragment = transfer(&mut ledger, valid_until);
+
+        network.partition(&[&[0], &[1]]);
+        assert!(network.submit(1, fragment.clone()));
+        network.advance_to(valid_until).unwrap();
+        assert_eq!(network.node(1).mempool().len(), 1);
+
+        network.tick().unwrap();
+        assert!(network.node(1).mempool().is_empty());
+
+        network.heal();
+        network.flush();
+        network.advance_slots(2).unwrap();
+        network.flush();
+
+        assert!(network.is_synchronized());
+        assert_eq!(network.node(0).chain_length(), ChainLength(8));
+        assert!(!network.node(0).has_in_chain(&fragment.hash()));
+        assert_eq!(network.in_flight(), 0);
+    }
+}
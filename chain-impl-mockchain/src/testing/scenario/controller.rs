@@ -1,18 +1,20 @@
 use crate::{
     certificate::{
         DecryptedPrivateTally, EncryptedVoteTally, ExternalProposalId, MintToken, Proposal,
-        UpdateProposal, UpdateVote, VoteCast, VotePlan, VoteTally,
+        UpdateProposal, UpdateVote, VoteCast, VoteCastBatch, VotePlan, VoteTally,
     },
     date::BlockDate,
     fee::LinearFee,
     key::Hash,
     ledger::Error as LedgerError,
     testing::{
+        chrono::ClockSimulator,
         data::{StakePool, Wallet},
         ledger::TestLedger,
         scenario::template::VotePlanDef,
         VoteTestGen,
     },
+    value::Value,
     vote::{Choice, Payload, PayloadType},
 };
 
@@ -103,6 +105,12 @@ impl Controller {
             })
     }
 
+    /// clock producing empty blocks on behalf of the stake pool with the given alias
+    pub fn clock(&self, stake_pool_alias: &str) -> Result<ClockSimulator, ControllerError> {
+        self.stake_pool(stake_pool_alias)
+            .map(|stake_pool| ClockSimulator::new().with_stake_pool(stake_pool))
+    }
+
     pub fn fragment_factory(&self) -> FragmentFactory {
         self.fragment_factory.clone()
     }
@@ -183,6 +191,30 @@ impl Controller {
         test_ledger.apply_fragment(&fragment, test_ledger.date())
     }
 
+    pub fn delegates_by_amounts(
+        &self,
+        from: &Wallet,
+        distribution: &[(&StakePool, Value)],
+        test_ledger: &mut TestLedger,
+    ) -> Result<(), LedgerError> {
+        let fragment =
+            self.fragment_factory
+                .delegation_by_amounts(test_ledger.date(), from, distribution);
+        test_ledger.apply_fragment(&fragment, test_ledger.date())
+    }
+
+    pub fn redelegates_rewards(
+        &self,
+        from: &Wallet,
+        enabled: bool,
+        test_ledger: &mut TestLedger,
+    ) -> Result<(), LedgerError> {
+        let fragment =
+            self.fragment_factory
+                .rewards_redelegation(test_ledger.date(), from, enabled);
+        test_ledger.apply_fragment(&fragment, test_ledger.date())
+    }
+
     pub fn owner_delegates(
         &self,
         from: &Wallet,
@@ -266,6 +298,38 @@ impl Controller {
         )
     }
 
+    /// cast public votes on several proposals of the vote plan with a single
+    /// vote cast batch fragment
+    pub fn cast_votes_public(
+        &self,
+        owner: &Wallet,
+        vote_plan_def: &VotePlanDef,
+        votes: &[(ExternalProposalId, Choice)],
+        test_ledger: &mut TestLedger,
+    ) -> Result<(), LedgerError> {
+        let vote_plan: VotePlan = vote_plan_def.clone().into();
+        let casts = votes
+            .iter()
+            .map(|(id, choice)| {
+                let index = vote_plan
+                    .proposals()
+                    .iter()
+                    .position(|x| *x.external_id() == *id)
+                    .expect("cannot find proposal");
+                VoteCast::new(
+                    vote_plan.to_id(),
+                    index as u8,
+                    Payload::Public { choice: *choice },
+                )
+            })
+            .collect();
+        let batch = VoteCastBatch::new(casts).expect("invalid vote cast batch");
+        let fragment = self
+            .fragment_factory
+            .vote_cast_batch(test_ledger.date(), owner, batch);
+        test_ledger.apply_fragment(&fragment, test_ledger.date())
+    }
+
     fn cast_vote<F>(
         &self,
         owner: &Wallet,
@@ -387,7 +451,6 @@ mod tests {
         fee::LinearFee,
         stake::Stake,
         testing::{ledger::ConfigBuilder, verifiers::LedgerStateVerifier},
-        value::Value,
     };
 
     #[test]
@@ -431,9 +494,9 @@ mod tests {
 
         LedgerStateVerifier::new(ledger.into())
             .distribution()
-            .unassigned_is(Stake::from_value(Value(997)))
+            .unassigned_is(Stake(997))
             .and()
-            .dangling_is(Stake::from_value(Value(1994)))
+            .dangling_is(Stake(1994))
             .and()
             .pools_total_stake_is(Stake::zero());
     }
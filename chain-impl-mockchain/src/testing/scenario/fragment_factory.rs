@@ -2,7 +2,7 @@ use crate::{
     accounting::account::{DelegationRatio, DelegationType},
     certificate::{
         Certificate, EncryptedVoteTally, MintToken, PoolId, PoolUpdate, UpdateProposal, UpdateVote,
-        VoteCast, VotePlan, VoteTally,
+        VoteCast, VoteCastBatch, VotePlan, VoteTally,
     },
     date::BlockDate,
     fee::LinearFee,
@@ -10,8 +10,9 @@ use crate::{
     key::Hash,
     testing::{
         builders::{
-            build_no_stake_delegation, build_owner_stake_delegation,
-            build_owner_stake_full_delegation, build_stake_delegation_cert,
+            build_amount_stake_delegation_cert, build_no_stake_delegation,
+            build_owner_stake_delegation, build_owner_stake_full_delegation,
+            build_rewards_redelegation_cert, build_stake_delegation_cert,
             build_stake_pool_registration_cert, build_stake_pool_retirement_cert,
             build_stake_pool_update_cert, TestTxBuilder, TestTxCertBuilder,
         },
@@ -119,6 +120,30 @@ impl FragmentFactory {
         self.transaction_with_cert(valid_until, Some(from), &cert)
     }
 
+    pub fn delegation_by_amounts(
+        &self,
+        valid_until: BlockDate,
+        from: &Wallet,
+        distribution: &[(&StakePool, Value)],
+    ) -> Fragment {
+        let pools = distribution
+            .iter()
+            .map(|(st, value)| (st.info().to_id(), *value))
+            .collect();
+        let cert = build_amount_stake_delegation_cert(pools, &from.as_account_data());
+        self.transaction_with_cert(valid_until, Some(from), &cert)
+    }
+
+    pub fn rewards_redelegation(
+        &self,
+        valid_until: BlockDate,
+        from: &Wallet,
+        enabled: bool,
+    ) -> Fragment {
+        let cert = build_rewards_redelegation_cert(enabled, &from.as_account_data());
+        self.transaction_with_cert(valid_until, Some(from), &cert)
+    }
+
     pub fn owner_delegation(
         &self,
         valid_until: BlockDate,
@@ -173,6 +198,15 @@ impl FragmentFactory {
         self.transaction_with_cert(valid_until, Some(owner), &vote_cast.into())
     }
 
+    pub fn vote_cast_batch(
+        &self,
+        valid_until: BlockDate,
+        owner: &Wallet,
+        batch: VoteCastBatch,
+    ) -> Fragment {
+        self.transaction_with_cert(valid_until, Some(owner), &batch.into())
+    }
+
     pub fn vote_encrypted_tally(
         &self,
         valid_until: BlockDate,
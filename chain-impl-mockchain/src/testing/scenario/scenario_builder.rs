@@ -223,6 +223,10 @@ impl ScenarioBuilder {
                     builder.with_tax_type(tax_type);
                 }
                 builder.with_reward_account(stake_pool_def.has_reward_account);
+                if let Some(reward_address) = stake_pool_def.reward_address {
+                    builder.with_reward_address(reward_address);
+                }
+                builder.with_pledge(stake_pool_def.pledge);
             }
         }
         builder.build()
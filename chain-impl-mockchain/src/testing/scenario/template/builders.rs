@@ -16,6 +16,7 @@ use crate::{
     value::Value,
     vote::PayloadType,
 };
+use chain_addr::Address;
 use chain_vote::MemberPublicKey;
 use std::{
     collections::{HashMap, HashSet},
@@ -187,7 +188,9 @@ pub struct StakePoolDefBuilder {
     alias: String,
     permissions_threshold: u8,
     reward_account: bool,
+    reward_address: Option<Address>,
     tax_type: Option<TaxType>,
+    pledge: Value,
 }
 
 impl StakePoolDefBuilder {
@@ -196,7 +199,9 @@ impl StakePoolDefBuilder {
             alias: alias.to_owned(),
             permissions_threshold: 1u8,
             reward_account: false,
+            reward_address: None,
             tax_type: None,
+            pledge: Value::zero(),
         }
     }
 
@@ -210,6 +215,11 @@ impl StakePoolDefBuilder {
         self
     }
 
+    pub fn with_reward_address(&mut self, reward_address: Address) -> &mut Self {
+        self.reward_address = Some(reward_address);
+        self
+    }
+
     pub fn tax_ratio(&mut self, numerator: u64, denominator: u64) -> &mut Self {
         self.tax_type = Some(TaxType {
             fixed: Value(0),
@@ -244,12 +254,19 @@ impl StakePoolDefBuilder {
         self
     }
 
+    pub fn pledge(&mut self, value: u64) -> &mut Self {
+        self.pledge = Value(value);
+        self
+    }
+
     pub fn build(&self) -> StakePoolDef {
         StakePoolDef {
             alias: self.alias.clone(),
             permissions_threshold: Some(self.permissions_threshold),
             has_reward_account: self.reward_account,
+            reward_address: self.reward_address.clone(),
             tax_type: self.tax_type,
+            pledge: self.pledge,
         }
     }
 }
@@ -301,34 +318,16 @@ impl VotePlanDefBuilder {
     }
 
     pub fn vote_phases(&mut self, start_epoch: u32, tally_epoch: u32, end_epoch: u32) -> &mut Self {
-        self.vote_date = Some(BlockDate {
-            epoch: start_epoch,
-            slot_id: 0,
-        });
-        self.tally_date = Some(BlockDate {
-            epoch: tally_epoch,
-            slot_id: 0,
-        });
-        self.end_tally_date = Some(BlockDate {
-            epoch: end_epoch,
-            slot_id: 0,
-        });
+        self.vote_date = Some(BlockDate::epoch_start(start_epoch));
+        self.tally_date = Some(BlockDate::epoch_start(tally_epoch));
+        self.end_tally_date = Some(BlockDate::epoch_start(end_epoch));
         self
     }
 
     pub fn consecutive_epoch_dates(&mut self) -> &mut Self {
-        self.vote_date = Some(BlockDate {
-            epoch: 0,
-            slot_id: 0,
-        });
-        self.tally_date = Some(BlockDate {
-            epoch: 1,
-            slot_id: 0,
-        });
-        self.end_tally_date = Some(BlockDate {
-            epoch: 2,
-            slot_id: 0,
-        });
+        self.vote_date = Some(BlockDate::epoch_start(0));
+        self.tally_date = Some(BlockDate::epoch_start(1));
+        self.end_tally_date = Some(BlockDate::epoch_start(2));
         self
     }
 
@@ -124,7 +124,9 @@ pub struct StakePoolDef {
     pub alias: String,
     pub permissions_threshold: Option<u8>,
     pub has_reward_account: bool,
+    pub reward_address: Option<Address>,
     pub tax_type: Option<TaxType>,
+    pub pledge: Value,
 }
 
 impl StakePoolDef {
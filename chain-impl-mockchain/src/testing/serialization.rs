@@ -1,4 +1,5 @@
-use chain_core::mempack::{ReadBuf, Readable};
+use crate::fragment::{Fragment, FragmentRaw};
+use chain_core::mempack::{ReadBuf, ReadError, Readable};
 use chain_core::property::{Deserialize, Serialize};
 use quickcheck::{Arbitrary, TestResult};
 
@@ -40,3 +41,129 @@ where
     };
     TestResult::from_bool(buf.expect_end().is_ok() && decoded_t == t)
 }
+
+/// Alteration applied to a valid encoding in order to look for
+/// malleability issues in the decoders.
+#[derive(Debug, Clone)]
+pub enum Perturbation {
+    /// append the given bytes at the end of the encoding
+    TrailingBytes(Vec<u8>),
+    /// shift the big endian `u16` length prefix found at the start of the
+    /// encoding by `delta`
+    LengthPrefix { delta: i16 },
+    /// swap the two sections of `len` bytes starting at `first` and `second`
+    SwapSections {
+        first: usize,
+        second: usize,
+        len: usize,
+    },
+}
+
+impl Perturbation {
+    /// apply the perturbation to the given bytes, returning `None` if the
+    /// perturbation does not apply (encoding too short, overlapping sections)
+    /// or leaves the bytes unchanged.
+    pub fn apply(&self, bytes: &[u8]) -> Option<Vec<u8>> {
+        let mut perturbed = bytes.to_vec();
+        match self {
+            Perturbation::TrailingBytes(trailing) => perturbed.extend_from_slice(trailing),
+            Perturbation::LengthPrefix { delta } if bytes.len() >= 2 => {
+                let len = u16::from_be_bytes([bytes[0], bytes[1]]);
+                let len = len.wrapping_add(*delta as u16);
+                perturbed[..2].copy_from_slice(&len.to_be_bytes());
+            }
+            Perturbation::LengthPrefix { .. } => return None,
+            Perturbation::SwapSections { first, second, len } => {
+                let (first, second) = (*first.min(second), *first.max(second));
+                if first + len > second || second + len > bytes.len() {
+                    return None;
+                }
+                let (head, tail) = perturbed.split_at_mut(second);
+                head[first..first + len].swap_with_slice(&mut tail[..*len]);
+            }
+        }
+        if perturbed == bytes {
+            None
+        } else {
+            Some(perturbed)
+        }
+    }
+}
+
+/// test that a perturbed encoding of a valid object is either rejected by
+/// the decoder or decodes into an object whose canonical encoding is
+/// exactly the perturbed bytes.
+///
+/// Any other outcome means two different byte strings decode to the same
+/// object, so anything hashed over the bytes (e.g. fragment ids) is malleable.
+pub fn canonical_encoding<T, E, D>(
+    t: &T,
+    perturbation: &Perturbation,
+    encode: E,
+    decode: D,
+) -> TestResult
+where
+    E: Fn(&T) -> Vec<u8>,
+    D: Fn(&[u8]) -> Result<T, ReadError>,
+{
+    let bytes = encode(t);
+    let perturbed = match perturbation.apply(&bytes) {
+        None => return TestResult::discard(),
+        Some(perturbed) => perturbed,
+    };
+    match decode(&perturbed) {
+        Err(_) => TestResult::passed(),
+        Ok(decoded) => {
+            let reencoded = encode(&decoded);
+            if reencoded == perturbed {
+                TestResult::passed()
+            } else {
+                TestResult::error(format!(
+                    "perturbation {:?} accepted with a non canonical encoding",
+                    perturbation
+                ))
+            }
+        }
+    }
+}
+
+/// [`canonical_encoding`] for types which are (de)serialized through
+/// `Serialize` and `Readable`, the whole input has to be consumed.
+pub fn canonical_encoding_r<T>(t: &T, perturbation: &Perturbation) -> TestResult
+where
+    T: Serialize + Readable,
+{
+    canonical_encoding(
+        t,
+        perturbation,
+        |t| {
+            t.serialize_as_vec()
+                .expect("serialization of a valid object")
+        },
+        |bytes| {
+            let mut buf = ReadBuf::from(bytes);
+            let t = T::read(&mut buf)?;
+            buf.expect_end()?;
+            Ok(t)
+        },
+    )
+}
+
+/// [`canonical_encoding`] applied to the raw bytes of a fragment, which are
+/// the bytes the fragment id is computed from, decoded by
+/// [`Fragment::from_raw`].
+pub fn fragment_raw_canonical_encoding(
+    fragment: &Fragment,
+    perturbation: &Perturbation,
+) -> TestResult {
+    canonical_encoding(
+        fragment,
+        perturbation,
+        |fragment| fragment.to_raw().as_ref().to_vec(),
+        |bytes| {
+            let framed = [&(bytes.len() as u32).to_be_bytes()[..], bytes].concat();
+            let raw = FragmentRaw::read(&mut ReadBuf::from(&framed))?;
+            Fragment::from_raw(&raw)
+        },
+    )
+}
@@ -0,0 +1,237 @@
+//! `proptest` strategies for the blocks, their contents and the test ledgers.
+//!
+//! The quickcheck `Arbitrary` implementations of these types generate large
+//! values which cannot be shrunk, so a failing property reports a case too
+//! big to be read. The strategies below are composed from bounded `proptest`
+//! strategies instead: a failing case shrinks to fewer fragments, fewer
+//! inputs and outputs, smaller values and the simplest kind of fragment.
+//!
+//! The keys are derived from a [`TestCryptoGen`] seed so that a case can be
+//! replayed, and the signatures are not valid: the strategies are meant for
+//! the properties of the encoding and of the ledger application of the
+//! transactions built with [`TestTxBuilder`](super::TestTxBuilder).
+
+use crate::{
+    block::{self, Block, BlockVersion},
+    certificate::{VoteCast, VotePlanId},
+    config::{Block0Date, ConfigParam},
+    date::BlockDate,
+    fee::LinearFee,
+    fragment::{ConfigParams, Contents, ContentsBuilder, Fragment},
+    key::{EitherEd25519SecretKey, Hash},
+    testing::{
+        data::{AddressData, AddressDataValue},
+        ledger::{ConfigBuilder, LedgerBuilder, TestLedger},
+    },
+    transaction::{Input, NoExtra, Output, Payload, Transaction, TxBuilder, UtxoPointer, Witness},
+    value::Value,
+    vote::{self, Choice},
+};
+use chain_addr::{Address, Discrimination, Kind};
+use chain_crypto::{testing::TestCryptoGen, Ed25519, Ed25519Extended, SecretKey, Signature};
+use proptest::{collection::vec, prelude::*};
+
+/// maximum number of fragments in the generated contents
+pub const MAX_FRAGMENTS: usize = 12;
+/// maximum number of inputs, and of outputs, of the generated transactions
+pub const MAX_IOS: usize = 4;
+/// maximum number of faucets of the generated test ledgers
+pub const MAX_FAUCETS: usize = 4;
+
+pub fn value_strategy() -> impl Strategy<Value = Value> {
+    (0u64..1_000_000).prop_map(Value)
+}
+
+pub fn hash_strategy() -> impl Strategy<Value = Hash> {
+    any::<[u8; 32]>().prop_map(Hash::from_bytes)
+}
+
+pub fn block_date_strategy() -> impl Strategy<Value = BlockDate> {
+    (any::<u32>(), any::<u32>()).prop_map(|(epoch, slot_id)| BlockDate { epoch, slot_id })
+}
+
+fn signature<T: ?Sized>(gen: &TestCryptoGen) -> Signature<T, Ed25519> {
+    SecretKey::<Ed25519>::generate(gen.get_rng(0))
+        .sign(&[])
+        .coerce()
+}
+
+/// an input of a transaction with a witness of the same kind
+pub fn input_strategy() -> impl Strategy<Value = (Input, Witness)> {
+    prop_oneof![
+        (
+            hash_strategy(),
+            any::<u8>(),
+            value_strategy(),
+            any::<TestCryptoGen>()
+        )
+            .prop_map(|(fragment_id, output_index, value, gen)| {
+                let pointer = UtxoPointer::new(fragment_id, output_index, value);
+                (Input::from_utxo(pointer), Witness::Utxo(signature(&gen)))
+            }),
+        (any::<TestCryptoGen>(), value_strategy(), any::<u32>()).prop_map(
+            |(gen, value, counter)| {
+                let public_key = SecretKey::<Ed25519>::generate(gen.get_rng(1)).to_public();
+                (
+                    Input::from_account_public_key(public_key, value),
+                    Witness::Account(counter.into(), signature(&gen)),
+                )
+            }
+        ),
+    ]
+}
+
+pub fn output_strategy() -> impl Strategy<Value = Output<Address>> {
+    (any::<Address>(), value_strategy())
+        .prop_map(|(address, value)| Output::from_address(address, value))
+}
+
+fn transaction<P: Payload>(
+    payload: &P,
+    expiry_date: BlockDate,
+    inputs: Vec<(Input, Witness)>,
+    outputs: &[Output<Address>],
+    payload_auth: &P::Auth,
+) -> Transaction<P> {
+    let (inputs, witnesses): (Vec<_>, Vec<_>) = inputs.into_iter().unzip();
+    TxBuilder::new()
+        .set_payload(payload)
+        .set_expiry_date(expiry_date)
+        .set_ios(&inputs, outputs)
+        .set_witnesses(&witnesses)
+        .set_payload_auth(payload_auth)
+}
+
+/// the expiry date, the inputs with their witnesses and the outputs of a
+/// transaction
+fn ios_strategy() -> impl Strategy<Value = (BlockDate, Vec<(Input, Witness)>, Vec<Output<Address>>)>
+{
+    (
+        block_date_strategy(),
+        vec(input_strategy(), 0..=MAX_IOS),
+        vec(output_strategy(), 0..=MAX_IOS),
+    )
+}
+
+pub fn config_param_strategy() -> impl Strategy<Value = ConfigParam> {
+    prop_oneof![
+        any::<Discrimination>().prop_map(ConfigParam::Discrimination),
+        any::<u64>().prop_map(|date| ConfigParam::Block0Date(Block0Date(date))),
+        (1u32..=1_000).prop_map(ConfigParam::SlotsPerEpoch),
+        any::<u8>().prop_map(ConfigParam::SlotDuration),
+        any::<u32>().prop_map(ConfigParam::ProposalExpiration),
+    ]
+}
+
+/// a fragment of one of the kinds which can be built from bounded values:
+/// the initial fragment, a transaction and a public vote cast. The
+/// fragments shrink to an initial fragment.
+pub fn fragment_strategy() -> impl Strategy<Value = Fragment> {
+    prop_oneof![
+        vec(config_param_strategy(), 0..8).prop_map(|params| {
+            let mut config_params = ConfigParams::new();
+            for param in params {
+                config_params.push(param);
+            }
+            Fragment::Initial(config_params)
+        }),
+        ios_strategy().prop_map(|(expiry_date, inputs, outputs)| Fragment::Transaction(
+            transaction(&NoExtra, expiry_date, inputs, &outputs, &())
+        )),
+        (any::<[u8; 32]>(), any::<u8>(), any::<u8>(), ios_strategy()).prop_map(
+            |(vote_plan, proposal_index, choice, (expiry_date, inputs, outputs))| {
+                let vote_cast = VoteCast::new(
+                    VotePlanId::from(vote_plan),
+                    proposal_index,
+                    vote::Payload::public(Choice::new(choice)),
+                );
+                Fragment::VoteCast(transaction(&vote_cast, expiry_date, inputs, &outputs, &()))
+            }
+        ),
+    ]
+}
+
+fn contents_builder(fragments: Vec<Fragment>) -> ContentsBuilder {
+    let mut builder = ContentsBuilder::new();
+    builder.push_many(fragments);
+    builder
+}
+
+/// contents of up to [`MAX_FRAGMENTS`] fragments, shrinking by removing
+/// fragments then by shrinking the remaining ones
+pub fn contents_strategy() -> impl Strategy<Value = Contents> {
+    vec(fragment_strategy(), 0..=MAX_FRAGMENTS)
+        .prop_map(|fragments| contents_builder(fragments).into())
+}
+
+/// a block with a header consistent with its contents, either unsigned or
+/// signed by a BFT leader
+pub fn block_strategy() -> impl Strategy<Value = Block> {
+    (
+        vec(fragment_strategy(), 0..=MAX_FRAGMENTS),
+        prop_oneof![
+            Just(BlockVersion::Genesis),
+            Just(BlockVersion::Ed25519Signed),
+            Just(BlockVersion::Ed25519SignedMerkle),
+        ],
+        any::<TestCryptoGen>(),
+        hash_strategy(),
+        any::<u32>(),
+        block_date_strategy(),
+    )
+        .prop_map(|(fragments, version, gen, parent, chain_length, date)| {
+            block::builder_from_contents(version, contents_builder(fragments), |header_builder| {
+                let header_builder = header_builder
+                    .set_parent(&parent, chain_length.into())
+                    .set_date(date);
+                let header = if version != BlockVersion::Genesis {
+                    let leader_key = SecretKey::<Ed25519>::generate(gen.get_rng(0));
+                    header_builder
+                        .into_bft_builder()
+                        .unwrap()
+                        .sign_using(&leader_key)
+                        .generalize()
+                } else {
+                    header_builder.into_unsigned_header().unwrap().generalize()
+                };
+                Ok::<_, ()>(header)
+            })
+            .unwrap()
+        })
+}
+
+fn faucet(gen: &TestCryptoGen, index: u32, value: u64) -> AddressDataValue {
+    let secret_key = SecretKey::<Ed25519Extended>::generate(gen.get_rng(index));
+    let address = Address(Discrimination::Test, Kind::Account(secret_key.to_public()));
+    let address_data = AddressData::new(
+        EitherEd25519SecretKey::Extended(secret_key),
+        Default::default(),
+        address,
+    );
+    AddressDataValue::new(address_data, Value(value))
+}
+
+/// a test ledger with up to [`MAX_FAUCETS`] account faucets, each with
+/// enough funds to pay the fees of a transaction of one input and one
+/// output. The keys of the faucets are derived from a single seed so they
+/// are distinct.
+pub fn test_ledger_strategy() -> impl Strategy<Value = TestLedger> {
+    (
+        any::<TestCryptoGen>(),
+        vec(100u64..1_000_000, 1..=MAX_FAUCETS),
+        0u64..10,
+        0u64..10,
+    )
+        .prop_map(|(gen, values, constant, coefficient)| {
+            let faucets: Vec<_> = values
+                .into_iter()
+                .enumerate()
+                .map(|(index, value)| faucet(&gen, index as u32, value))
+                .collect();
+            let config = ConfigBuilder::new().with_fee(LinearFee::new(constant, coefficient, 0));
+            LedgerBuilder::from_config(config)
+                .faucets(&faucets)
+                .build()
+                .expect("cannot build test ledger")
+        })
+}
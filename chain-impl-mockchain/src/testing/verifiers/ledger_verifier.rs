@@ -1,6 +1,6 @@
 use crate::{
     account::{self, Identifier, Ledger as AccountLedger},
-    accounting::account::{account_state::AccountState, DelegationType},
+    accounting::account::{account_state::AccountState, DelegationAmounts, DelegationType},
     certificate::{PoolId, PoolRegistration},
     ledger::{ledger::Ledger, Pots},
     stake::PoolsState,
@@ -281,7 +281,11 @@ impl AccountVerifier {
             .accounts
             .get_state(&self.address.to_id())
             .expect("account does not exists");
-        DelegationVerifier::new(account_state.delegation().clone(), self.info.clone())
+        DelegationVerifier::new(
+            account_state.delegation().clone(),
+            account_state.delegation_amounts().cloned(),
+            self.info.clone(),
+        )
     }
 
     pub fn has_value(&self, value: &Value) -> &Self {
@@ -302,13 +306,19 @@ impl AccountVerifier {
 
 pub struct DelegationVerifier {
     delegation_type: DelegationType,
+    delegation_amounts: Option<DelegationAmounts>,
     info: Info,
 }
 
 impl DelegationVerifier {
-    pub fn new(delegation_type: DelegationType, info: Info) -> Self {
+    pub fn new(
+        delegation_type: DelegationType,
+        delegation_amounts: Option<DelegationAmounts>,
+        info: Info,
+    ) -> Self {
         Self {
             delegation_type,
+            delegation_amounts,
             info,
         }
     }
@@ -331,6 +341,22 @@ impl DelegationVerifier {
         };
         self
     }
+
+    pub fn is_delegated_by_amounts(&self, expected_pools: &[(PoolId, Value)]) -> &Self {
+        match &self.delegation_amounts {
+            Some(amounts) => assert_eq!(
+                amounts.pools(),
+                expected_pools,
+                "{}: wrong delegated amounts",
+                self.info
+            ),
+            None => panic!(
+                "{}: wrong delegation type: {:?}, Expected: Amounts",
+                self.info, self.delegation_type
+            ),
+        };
+        self
+    }
 }
 
 pub struct PotsVerifier {
@@ -496,7 +522,7 @@ impl DistributionVerifier {
             let stake = stake.unwrap();
             assert_eq!(
                 stake,
-                Stake::from_value(value),
+                Stake::from(value),
                 "wrong total stake for pool with id {} {}",
                 pool_id,
                 self.info
@@ -5,7 +5,11 @@ use crate::tokens::{
 
 use std::{fmt, str::FromStr};
 
-use chain_core::mempack::{ReadBuf, ReadError, Readable};
+use chain_core::{
+    mempack::ReadError,
+    packer::Codec,
+    property::{ByteSource, Pack, Unpack},
+};
 use thiserror::Error;
 use typed_bytes::ByteBuilder;
 
@@ -52,10 +56,17 @@ impl TokenIdentifier {
     }
 }
 
-impl Readable for TokenIdentifier {
-    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        let policy_hash = PolicyHash::read(buf)?;
-        let token_name = TokenName::read(buf)?;
+impl Pack for TokenIdentifier {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        self.policy_hash.pack(codec)?;
+        self.token_name.pack(codec)
+    }
+}
+
+impl Unpack for TokenIdentifier {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let policy_hash = PolicyHash::unpack(source)?;
+        let token_name = TokenName::unpack(source)?;
         Ok(Self {
             policy_hash,
             token_name,
@@ -63,6 +74,8 @@ impl Readable for TokenIdentifier {
     }
 }
 
+chain_ser::impl_legacy_codecs!(TokenIdentifier);
+
 impl fmt::Display for TokenIdentifier {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", hex::encode(&self.policy_hash.as_ref()))?;
@@ -109,6 +122,8 @@ impl FromStr for TokenIdentifier {
 #[cfg(any(test, feature = "property-test-api"))]
 mod tests {
     use super::*;
+    #[cfg(test)]
+    use chain_core::mempack::{ReadBuf, Readable as _};
     #[allow(unused_imports)]
     use quickcheck::TestResult;
     use quickcheck::{Arbitrary, Gen};
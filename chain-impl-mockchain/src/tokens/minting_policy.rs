@@ -1,9 +1,9 @@
 use crate::tokens::policy_hash::{PolicyHash, POLICY_HASH_SIZE};
 
 use chain_core::{
-    mempack::{ReadBuf, ReadError, Readable},
+    mempack::ReadError,
     packer::Codec,
-    property::Serialize,
+    property::{ByteSource, Pack, Unpack},
 };
 use cryptoxide::{blake2b::Blake2b, digest::Digest};
 use thiserror::Error;
@@ -72,27 +72,27 @@ impl Default for MintingPolicy {
     }
 }
 
-impl Serialize for MintingPolicy {
-    type Error = std::io::Error;
-    fn serialize<W: std::io::Write>(&self, writer: W) -> Result<(), Self::Error> {
-        let mut codec = Codec::new(writer);
+impl Pack for MintingPolicy {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
         codec.put_u8(0_u8)
     }
 }
 
-impl Readable for MintingPolicy {
-    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        let no_entries = buf.get_u8()?;
+impl Unpack for MintingPolicy {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let no_entries = source.take_u8()?;
         if no_entries != 0 {
             return Err(ReadError::InvalidData(
                 "non-zero number of minting policy entries, but they are currently unimplemented"
-                    .to_string(),
+                    .into(),
             ));
         }
         Ok(Self::new())
     }
 }
 
+chain_ser::impl_legacy_codecs!(MintingPolicy);
+
 #[cfg(any(test, feature = "property-test-api"))]
 mod tests {
     use super::*;
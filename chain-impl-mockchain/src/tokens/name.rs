@@ -1,9 +1,9 @@
 use std::convert::TryFrom;
 
 use chain_core::{
-    mempack::{ReadBuf, ReadError, Readable},
+    mempack::ReadError,
     packer::Codec,
-    property::Serialize,
+    property::{ByteSource, Pack, Unpack},
 };
 use thiserror::Error;
 use typed_bytes::ByteBuilder;
@@ -50,29 +50,34 @@ impl TryFrom<Vec<u8>> for TokenName {
     }
 }
 
-impl Serialize for TokenName {
-    type Error = std::io::Error;
-    fn serialize<W: std::io::Write>(&self, writer: W) -> Result<(), Self::Error> {
-        let mut codec = Codec::new(writer);
+impl Pack for TokenName {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
         codec.put_u8(self.0.len() as u8)?;
         codec.put_bytes(self.0.as_slice())
     }
 }
 
-impl Readable for TokenName {
-    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        let name_length = buf.get_u8()? as usize;
+impl Unpack for TokenName {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let name_length = source.take_u8()? as usize;
         if name_length > TOKEN_NAME_MAX_SIZE {
-            return Err(ReadError::SizeTooBig(TOKEN_NAME_MAX_SIZE, name_length));
+            return Err(ReadError::SizeTooBig {
+                size: name_length,
+                limit: TOKEN_NAME_MAX_SIZE,
+            });
         }
-        let bytes = buf.get_slice(name_length)?.into();
+        let bytes = source.take_vec(name_length)?;
         Ok(Self(bytes))
     }
 }
 
+chain_ser::impl_legacy_codecs!(TokenName);
+
 #[cfg(any(test, feature = "property-test-api"))]
 mod tests {
     use super::*;
+    #[cfg(test)]
+    use chain_core::mempack::{ReadBuf, Readable as _};
     #[allow(unused_imports)]
     use quickcheck::TestResult;
     use quickcheck::{Arbitrary, Gen};
@@ -1,4 +1,8 @@
-use chain_core::mempack::{ReadBuf, ReadError, Readable};
+use chain_core::{
+    mempack::{ReadBuf, ReadError, Readable},
+    packer::Codec,
+    property::{ByteSource, Pack, Unpack},
+};
 
 pub const POLICY_HASH_SIZE: usize = 28;
 
@@ -26,16 +30,22 @@ impl TryFrom<&[u8]> for PolicyHash {
     }
 }
 
-impl Readable for PolicyHash {
-    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        let bytes = buf
-            .get_slice(POLICY_HASH_SIZE)?
-            .try_into()
-            .unwrap_or_else(|_| panic!("already read {} bytes", POLICY_HASH_SIZE));
+impl Pack for PolicyHash {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(&self.0)
+    }
+}
+
+impl Unpack for PolicyHash {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let mut bytes = [0; POLICY_HASH_SIZE];
+        source.take_into(&mut bytes)?;
         Ok(Self(bytes))
     }
 }
 
+chain_ser::impl_legacy_codecs!(PolicyHash);
+
 #[cfg(any(test, feature = "property-test-api"))]
 mod tests {
     use super::*;
@@ -0,0 +1,125 @@
+use super::element::BalanceError;
+use super::payload::NoExtra;
+use super::transaction::Transaction;
+use crate::fragment::{Fragment, FragmentId};
+use crate::value::{Value, ValueError};
+use chain_core::{
+    mempack::{ReadBuf, ReadError, Readable},
+    packer::Codec,
+    property,
+};
+use thiserror::Error;
+
+/// Maximum number of transactions in a batch
+pub const TRANSACTION_BATCH_MAX_TRANSACTIONS: usize = 64;
+
+/// Several transactions applied atomically: either all the transactions
+/// of the batch are applied, or none of them.
+///
+/// The fee is computed and verified for the batch as a whole, so the
+/// transactions of the batch do not need to be balanced individually,
+/// they only must not create value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionBatch {
+    transactions: Vec<Transaction<NoExtra>>,
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum TransactionBatchError {
+    #[error("Transaction batch is empty")]
+    Empty,
+    #[error("Transaction batch has {count} transactions, only {max} are allowed")]
+    TooManyTransactions { count: usize, max: usize },
+}
+
+impl TransactionBatch {
+    pub fn new(transactions: Vec<Transaction<NoExtra>>) -> Result<Self, TransactionBatchError> {
+        if transactions.is_empty() {
+            return Err(TransactionBatchError::Empty);
+        }
+        if transactions.len() > TRANSACTION_BATCH_MAX_TRANSACTIONS {
+            return Err(TransactionBatchError::TooManyTransactions {
+                count: transactions.len(),
+                max: TRANSACTION_BATCH_MAX_TRANSACTIONS,
+            });
+        }
+        Ok(TransactionBatch { transactions })
+    }
+
+    pub fn transactions(&self) -> &[Transaction<NoExtra>] {
+        &self.transactions
+    }
+
+    pub fn nb_transactions(&self) -> usize {
+        self.transactions.len()
+    }
+
+    /// The identifiers of the transactions of the batch, i.e. the identifiers
+    /// they would have as standalone fragments. The outputs of the transactions
+    /// are referenced with these identifiers.
+    pub fn transaction_ids(&self) -> impl Iterator<Item = FragmentId> + '_ {
+        self.transactions
+            .iter()
+            .map(|tx| Fragment::Transaction(tx.clone()).hash())
+    }
+
+    pub fn total_input(&self) -> Result<Value, ValueError> {
+        self.transactions
+            .iter()
+            .try_fold(Value::zero(), |total, tx| total + tx.total_input()?)
+    }
+
+    pub fn total_output(&self) -> Result<Value, ValueError> {
+        self.transactions
+            .iter()
+            .try_fold(Value::zero(), |total, tx| total + tx.total_output()?)
+    }
+
+    /// Verify that none of the transactions create value and that the
+    /// batch as a whole is balanced with the given fee.
+    pub fn verify_strictly_balanced(&self, fee: Value) -> Result<(), BalanceError> {
+        for tx in self.transactions.iter() {
+            tx.verify_possibly_balanced()?;
+        }
+        let inputs = self
+            .total_input()
+            .map_err(BalanceError::InputsTotalFailed)?;
+        let outputs = self
+            .total_output()
+            .and_then(|out| out + fee)
+            .map_err(BalanceError::OutputsTotalFailed)?;
+        if inputs != outputs {
+            return Err(BalanceError::NotBalanced { inputs, outputs });
+        };
+        Ok(())
+    }
+}
+
+impl property::Serialize for TransactionBatch {
+    type Error = std::io::Error;
+
+    fn serialize<W: std::io::Write>(&self, writer: W) -> Result<(), Self::Error> {
+        let mut codec = Codec::new(writer);
+        codec.put_u8(self.transactions.len() as u8)?;
+        for tx in self.transactions.iter() {
+            let bytes = tx.as_ref();
+            codec.put_be_u32(bytes.len() as u32)?;
+            codec.put_bytes(bytes)?;
+        }
+        Ok(())
+    }
+}
+
+impl Readable for TransactionBatch {
+    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
+        let nb_transactions = buf.get_u8()? as usize;
+        let mut transactions = Vec::with_capacity(nb_transactions);
+        for _ in 0..nb_transactions {
+            let size = buf.get_u32()? as usize;
+            let mut tx_buf = buf.split_to(size)?;
+            transactions.push(Transaction::read(&mut tx_buf)?);
+        }
+        TransactionBatch::new(transactions)
+            .map_err(|e| ReadError::InvalidData(e.to_string().into()))
+    }
+}
@@ -7,6 +7,7 @@ use super::transfer::Output;
 use super::witness::Witness;
 use crate::date::BlockDate;
 use chain_addr::Address;
+use chain_core::property::Serialize as _;
 use std::marker::PhantomData;
 
 /// A Transaction builder with an associated state machine
@@ -140,8 +141,8 @@ impl<P> TxBuilderState<SetIOs<P>> {
         self.tstruct.outputs = self.current_pos();
 
         for o in outputs {
-            self.data.extend_from_slice(&o.address.to_bytes());
-            self.data.extend_from_slice(&o.value.bytes());
+            o.serialize(&mut self.data)
+                .expect("in memory serialization of the output");
         }
 
         TxBuilderState {
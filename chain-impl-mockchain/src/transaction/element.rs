@@ -1,7 +1,11 @@
-use crate::key::deserialize_signature;
+use crate::key::unpack_signature;
 use crate::transaction::TransactionBindingAuthData;
 use crate::value::{Value, ValueError};
-use chain_core::mempack::{ReadBuf, ReadError, Readable};
+use chain_core::{
+    mempack::ReadError,
+    packer::Codec,
+    property::{ByteSource, Pack, Unpack},
+};
 use chain_crypto::{digest::DigestOf, Blake2b256, Ed25519, PublicKey, Signature, Verification};
 use thiserror::Error;
 use typed_bytes::ByteBuilder;
@@ -63,12 +67,20 @@ impl AsRef<[u8]> for SingleAccountBindingSignature {
     }
 }
 
-impl Readable for SingleAccountBindingSignature {
-    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        deserialize_signature(buf).map(SingleAccountBindingSignature)
+impl Pack for SingleAccountBindingSignature {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(self.as_ref())
     }
 }
 
+impl Unpack for SingleAccountBindingSignature {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        unpack_signature(source).map(SingleAccountBindingSignature)
+    }
+}
+
+chain_ser::impl_legacy_codecs!(SingleAccountBindingSignature);
+
 #[derive(Debug, Clone)]
 pub enum AccountBindingSignature {
     Single(SingleAccountBindingSignature),
@@ -96,19 +108,30 @@ impl AccountBindingSignature {
     }
 }
 
-impl Readable for AccountBindingSignature {
-    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        match buf.get_u8()? {
+impl Pack for AccountBindingSignature {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(self.serialize_in(ByteBuilder::new()).finalize().as_slice())
+    }
+}
+
+impl Unpack for AccountBindingSignature {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        match source.take_u8()? {
             1 => {
-                let sig = deserialize_signature(buf).map(SingleAccountBindingSignature)?;
+                let sig = SingleAccountBindingSignature::unpack(source)?;
                 Ok(AccountBindingSignature::Single(sig))
             }
             2 => unimplemented!(),
-            n => Err(ReadError::UnknownTag(n as u32)),
+            n => Err(ReadError::UnknownTag {
+                kind: "account binding signature",
+                tag: n as u32,
+            }),
         }
     }
 }
 
+chain_ser::impl_legacy_codecs!(AccountBindingSignature);
+
 /// Amount of the balance in the transaction.
 pub enum Balance {
     /// Balance is positive.
@@ -6,8 +6,9 @@ use crate::utxo::Entry;
 use crate::value::Value;
 use crate::{account, multisig};
 use chain_addr::Address;
-use chain_core::mempack::{ReadBuf, ReadError, Readable};
-use chain_core::property;
+use chain_core::mempack::ReadError;
+use chain_core::packer::Codec;
+use chain_core::property::{ByteSource, Pack, Unpack};
 use chain_crypto::PublicKey;
 
 pub const INPUT_SIZE: usize = 41;
@@ -194,31 +195,20 @@ impl Input {
     }
 }
 
-impl property::Serialize for Input {
-    type Error = std::io::Error;
-
-    fn serialize<W: std::io::Write>(&self, writer: W) -> Result<(), Self::Error> {
-        use chain_core::packer::*;
-
-        let mut codec = Codec::new(writer);
+impl Pack for Input {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
         codec.put_u8(self.index_or_account)?;
-        self.value.serialize(&mut codec)?;
-        codec.into_inner().write_all(&self.input_ptr)?;
-        Ok(())
+        self.value.pack(codec)?;
+        codec.put_bytes(&self.input_ptr)
     }
 }
 
-impl property::Deserialize for Input {
-    type Error = std::io::Error;
-
-    fn deserialize<R: std::io::BufRead>(reader: R) -> Result<Self, Self::Error> {
-        use chain_core::packer::*;
-
-        let mut codec = Codec::new(reader);
-        let index_or_account = codec.get_u8()?;
-        let value = Value::deserialize(&mut codec)?;
+impl Unpack for Input {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let index_or_account = source.take_u8()?;
+        let value = Value::unpack(source)?;
         let mut input_ptr = [0; INPUT_PTR_SIZE];
-        codec.into_inner().read_exact(&mut input_ptr)?;
+        source.take_into(&mut input_ptr)?;
         Ok(Input {
             index_or_account,
             value,
@@ -227,15 +217,4 @@ impl property::Deserialize for Input {
     }
 }
 
-impl Readable for Input {
-    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        let index_or_account = buf.get_u8()?;
-        let value = Value::read(buf)?;
-        let input_ptr = <[u8; INPUT_PTR_SIZE]>::read(buf)?;
-        Ok(Input {
-            index_or_account,
-            value,
-            input_ptr,
-        })
-    }
-}
+chain_ser::impl_legacy_codecs!(Input);
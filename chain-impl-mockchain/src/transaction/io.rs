@@ -1,7 +1,8 @@
-use super::{Balance, Input, Output, Payload, PayloadSlice};
+use super::{Balance, Input, Output, Payload, PayloadSlice, UtxoPointer};
+use crate::account;
 use crate::fee::FeeAlgorithm;
 use crate::value::{Value, ValueError};
-use chain_addr::Address;
+use chain_addr::{Address, Kind};
 use std::error;
 use std::fmt;
 
@@ -26,6 +27,9 @@ pub enum Error {
     TxTooManyOutputs,
     TxNotEnoughTotalInput,
     TxTooMuchTotalInput,
+    TxZeroValueOutput,
+    NotAnAccountAddress,
+    NotAUtxoAddress,
     MathErr(ValueError),
 }
 
@@ -38,6 +42,9 @@ impl fmt::Display for Error {
             Error::TxTooManyOutputs => write!(f, "transaction has too many outputs"),
             Error::TxNotEnoughTotalInput => write!(f, "not enough input for making transaction"),
             Error::TxTooMuchTotalInput => write!(f, "too muny input value for making transaction"),
+            Error::TxZeroValueOutput => write!(f, "transaction has an output of value zero"),
+            Error::NotAnAccountAddress => write!(f, "address is not an account address"),
+            Error::NotAUtxoAddress => write!(f, "address is not a utxo address"),
             Error::MathErr(v) => write!(f, "error in arithmetics {:?}", v),
         }
     }
@@ -87,6 +94,78 @@ impl InputOutputBuilder {
         Ok(InputOutputBuilder { inputs, outputs })
     }
 
+    /// Inputs & outputs moving the whole value of the utxos to an account,
+    /// the fee being taken from the deposited value.
+    ///
+    /// The account is the single output and the utxos are the inputs, in the
+    /// same order. The transaction needs a utxo witness for every input, they
+    /// are left to the caller. The account is created by the deposit if it
+    /// doesn't exist yet.
+    pub fn deposit_to_account<F: FeeAlgorithm>(
+        utxos: &[UtxoPointer],
+        account: Address,
+        fee_algorithm: &F,
+    ) -> Result<InputOutput, Error> {
+        match account.kind() {
+            Kind::Account(_) | Kind::Multisig(_) => (),
+            Kind::Single(_) | Kind::Group(_, _) | Kind::Script(_) => {
+                return Err(Error::NotAnAccountAddress)
+            }
+        }
+        if utxos.is_empty() {
+            return Err(Error::TxInvalidNoInput);
+        }
+        if utxos.len() > 255 {
+            return Err(Error::TxTooManyInputs);
+        }
+
+        let inputs: Vec<_> = utxos.iter().copied().map(Input::from_utxo).collect();
+        let total = Value::sum(utxos.iter().map(|utxo| utxo.value)).map_err(Error::MathErr)?;
+        let fee = fee_algorithm.calculate(None, inputs.len() as u8, 1);
+        let value = total
+            .checked_sub(fee)
+            .map_err(|_| Error::TxNotEnoughTotalInput)?;
+        if value == Value::zero() {
+            return Err(Error::TxZeroValueOutput);
+        }
+
+        Ok(InputOutput {
+            inputs: inputs.into(),
+            outputs: vec![Output::from_address(account, value)].into(),
+        })
+    }
+
+    /// Inputs & outputs moving the given value from a single account to a
+    /// utxo address, the account paying the fee on top of the value.
+    ///
+    /// The account is the single input. No witness is built here: the caller
+    /// signs the transaction with an account witness, made with the current
+    /// spending counter of the account.
+    pub fn withdraw_to_utxo<F: FeeAlgorithm>(
+        account: account::Identifier,
+        value: Value,
+        address: Address,
+        fee_algorithm: &F,
+    ) -> Result<InputOutput, Error> {
+        match address.kind() {
+            Kind::Single(_) | Kind::Group(_, _) => (),
+            Kind::Account(_) | Kind::Multisig(_) | Kind::Script(_) => {
+                return Err(Error::NotAUtxoAddress)
+            }
+        }
+        if value == Value::zero() {
+            return Err(Error::TxZeroValueOutput);
+        }
+
+        let fee = fee_algorithm.calculate(None, 1, 1);
+        let spent = value.checked_add(fee).map_err(Error::MathErr)?;
+
+        Ok(InputOutput {
+            inputs: vec![Input::from_account_single(account, spent)].into(),
+            outputs: vec![Output::from_address(address, value)].into(),
+        })
+    }
+
     /// Build the InputOutput from the Builder
     pub fn build(self) -> InputOutput {
         InputOutput {
@@ -113,7 +192,7 @@ impl InputOutputBuilder {
         if self.outputs.len() == 255 {
             return Err(Error::TxTooManyOutputs);
         }
-        self.outputs.push(Output { address, value });
+        self.outputs.push(Output::from_address(address, value));
         Ok(())
     }
 
@@ -250,7 +329,7 @@ impl InputOutputBuilder {
                 match self.balance(fee) {
                     Ok(Balance::Positive(value)) => {
                         let _ = self.outputs.pop();
-                        let output = Output { address, value };
+                        let output = Output::from_address(address, value);
                         self.outputs.push(output.clone());
                         Ok((Balance::Zero, vec![output], self.build()))
                     }
@@ -1,3 +1,4 @@
+mod batch;
 mod builder;
 mod element;
 mod input;
@@ -6,6 +7,7 @@ mod payload;
 #[allow(clippy::module_inception)]
 mod transaction;
 mod transfer;
+mod unsigned;
 mod utxo;
 mod witness;
 
@@ -14,7 +16,9 @@ pub mod test;
 
 use chain_core::mempack::{ReadBuf, ReadError, Readable};
 use chain_core::property;
+use std::marker::PhantomData;
 
+pub use batch::{TransactionBatch, TransactionBatchError, TRANSACTION_BATCH_MAX_TRANSACTIONS};
 // to remove..
 pub use builder::{
     SetAuthData, SetIOs, SetPayload, SetTtl, SetWitnesses, TxBuilder, TxBuilderState,
@@ -25,6 +29,10 @@ pub use io::{Error, InputOutput, InputOutputBuilder, OutputPolicy};
 pub use payload::{NoExtra, Payload, PayloadAuthData, PayloadAuthSlice, PayloadData, PayloadSlice};
 pub use transaction::*;
 pub use transfer::*;
+pub use unsigned::{
+    DerivationHint, UnsignedInput, UnsignedTransaction, UnsignedTransactionError, WitnessKind,
+    UNSIGNED_TRANSACTION_VERSION,
+};
 pub use utxo::*;
 pub use witness::*;
 
@@ -37,11 +45,17 @@ impl<Extra: Payload> property::Serialize for Transaction<Extra> {
 
 impl<Extra: Payload> Readable for Transaction<Extra> {
     fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        let utx = UnverifiedTransactionSlice::from(buf.get_slice_end());
-        match utx.check() {
-            Ok(tx) => Ok(tx.to_owned()),
-            Err(e) => Err(ReadError::StructureInvalid(e.to_string())),
-        }
+        // the transaction keeps a slice of the buffer if it is shared
+        let data = buf.get_shared_slice_end();
+        let tstruct = UnverifiedTransactionSlice::<Extra>::from(data.as_ref())
+            .check()
+            .map_err(|e| ReadError::StructureInvalid(e.to_string().into()))?
+            .tstruct;
+        Ok(Transaction {
+            data,
+            tstruct,
+            phantom: PhantomData,
+        })
     }
 }
 
@@ -1,6 +1,7 @@
 use chain_core::{
     mempack::{ReadBuf, ReadError, Readable},
-    property,
+    packer::Codec,
+    property::{ByteSource, Pack, Unpack},
 };
 
 use crate::certificate::CertificateSlice;
@@ -123,24 +124,19 @@ impl<'a, P: ?Sized> PayloadAuthSlice<'a, P> {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct NoExtra;
 
-impl property::Serialize for NoExtra {
-    type Error = std::io::Error;
-    fn serialize<W: std::io::Write>(&self, _: W) -> Result<(), Self::Error> {
+impl Pack for NoExtra {
+    fn pack<W: std::io::Write>(&self, _: &mut Codec<W>) -> Result<(), std::io::Error> {
         Ok(())
     }
 }
 
-impl property::Deserialize for NoExtra {
-    type Error = std::io::Error;
-    fn deserialize<R: std::io::BufRead>(_: R) -> Result<Self, Self::Error> {
-        Ok(NoExtra)
-    }
-}
-impl Readable for NoExtra {
-    fn read(_: &mut ReadBuf) -> Result<Self, ReadError> {
+impl Unpack for NoExtra {
+    fn unpack<S: ByteSource>(_: &mut S) -> Result<Self, ReadError> {
         Ok(NoExtra)
     }
 }
+
+chain_ser::impl_legacy_codecs!(NoExtra);
 impl Payload for NoExtra {
     const HAS_DATA: bool = false;
     const HAS_AUTH: bool = false;
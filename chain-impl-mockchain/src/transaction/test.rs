@@ -1,14 +1,33 @@
 use super::{
     element::SingleAccountBindingSignature, AccountBindingSignature, AccountIdentifier, Input,
-    NoExtra, Payload, Transaction, TxBuilder, UnspecifiedAccountIdentifier, UtxoPointer, Witness,
+    NoExtra, Payload, Transaction, TransactionBatch, TxBuilder, UnspecifiedAccountIdentifier,
+    UtxoPointer, Witness,
+};
+#[cfg(test)]
+use super::{
+    DerivationHint, UnsignedInput, UnsignedTransaction, UnsignedTransactionError, WitnessKind,
 };
 use crate::account::SpendingCounter;
 #[cfg(test)]
 use crate::certificate::OwnerStakeDelegation;
+#[cfg(test)]
+use crate::chaintypes::HeaderId;
 use crate::date::BlockDate;
 use crate::key::{EitherEd25519SecretKey, SpendingSignature};
 #[cfg(test)]
-use crate::testing::serialization::serialization_bijection_r;
+use crate::testing::{
+    builders::witness_builder::make_witness, data::AddressData,
+    serialization::serialization_bijection_r, TestGen,
+};
+#[cfg(test)]
+use crate::value::Value;
+#[cfg(test)]
+use chain_addr::Discrimination;
+#[cfg(test)]
+use chain_core::{
+    mempack::{ReadBuf, Readable},
+    shared::SharedBytes,
+};
 use chain_crypto::{testing::arbitrary_secret_key, Ed25519, SecretKey, Signature};
 #[cfg(test)]
 use quickcheck::TestResult;
@@ -30,6 +49,9 @@ quickcheck! {
     fn signed_transaction_encode_decode(transaction: Transaction<NoExtra>) -> TestResult {
         serialization_bijection_r(transaction)
     }
+    fn transaction_batch_encode_decode(batch: TransactionBatch) -> TestResult {
+        serialization_bijection_r(batch)
+    }
 }
 
 #[cfg(test)]
@@ -47,6 +69,71 @@ where
     }
 }
 
+#[quickcheck]
+fn transaction_read_from_shared_buffer_is_not_copied(tx: Transaction<NoExtra>) -> bool {
+    let bytes = SharedBytes::from(tx.as_ref());
+    let read = Transaction::<NoExtra>::read(&mut ReadBuf::from_shared(&bytes)).unwrap();
+    read == tx && read.as_ref().as_ptr() == bytes.as_ptr()
+}
+
+#[cfg(test)]
+fn unsigned_transaction(
+    block0: HeaderId,
+    sender: &AddressData,
+    fee: Value,
+) -> Result<UnsignedTransaction<NoExtra>, UnsignedTransactionError> {
+    let receiver = AddressData::utxo(Discrimination::Test);
+    UnsignedTransaction::new(
+        block0,
+        NoExtra,
+        BlockDate::first().next_epoch(),
+        vec![UnsignedInput {
+            input: sender.make_input(Value(100), None),
+            witness: WitnessKind::Account(sender.spending_counter().get_valid_counter()),
+            derivation_hint: DerivationHint::new(vec![0x8000_002C, 0x8000_0717, 0x8000_0000, 0, 0]),
+        }],
+        vec![receiver.make_output(Value(90))],
+        fee,
+    )
+}
+
+#[test]
+fn unsigned_transaction_encode_decode_and_sign() {
+    let block0 = TestGen::hash();
+    let sender = AddressData::account(Discrimination::Test);
+    let unsigned = unsigned_transaction(block0, &sender, Value(10)).unwrap();
+
+    let decoded = UnsignedTransaction::<NoExtra>::decode(&unsigned.encode()).unwrap();
+    assert_eq!(decoded, unsigned);
+    assert!(!decoded.needs_payload_auth());
+
+    let sign_data_hash = decoded.sign_data_hash();
+    let utxo_witness = Witness::new_utxo(decoded.block0(), &sign_data_hash, |d| {
+        sender.private_key().sign(d)
+    });
+    assert_eq!(
+        decoded.set_witnesses(&[utxo_witness]).err(),
+        Some(UnsignedTransactionError::WitnessMismatch { index: 0 })
+    );
+
+    let witness = make_witness(decoded.block0(), &sender, &sign_data_hash);
+    let tx = decoded
+        .set_witnesses(&[witness])
+        .unwrap()
+        .set_payload_auth(&());
+    assert_eq!(tx.hash(), sign_data_hash);
+    assert!(tx.verify_strictly_balanced(unsigned.fee()).is_ok());
+}
+
+#[test]
+fn unsigned_transaction_fee_must_balance() {
+    let sender = AddressData::account(Discrimination::Test);
+    assert!(matches!(
+        unsigned_transaction(TestGen::hash(), &sender, Value(5)),
+        Err(UnsignedTransactionError::NotBalanced(_))
+    ));
+}
+
 #[quickcheck]
 pub fn check_transaction_accessor_consistent(tx: Transaction<NoExtra>) -> TestResult {
     let slice = tx.as_slice();
@@ -169,6 +256,16 @@ where
     }
 }
 
+impl Arbitrary for TransactionBatch {
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        let nb_transactions = 1 + usize::arbitrary(g) % 4;
+        let transactions = std::iter::repeat_with(|| Arbitrary::arbitrary(g))
+            .take(nb_transactions)
+            .collect();
+        TransactionBatch::new(transactions).unwrap()
+    }
+}
+
 impl Arbitrary for SingleAccountBindingSignature {
     fn arbitrary<G: Gen>(g: &mut G) -> Self {
         SingleAccountBindingSignature(Arbitrary::arbitrary(g))
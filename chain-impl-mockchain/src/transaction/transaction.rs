@@ -8,12 +8,20 @@ use crate::date::BlockDate;
 use crate::value::{Value, ValueError};
 use chain_addr::Address;
 use chain_core::mempack::{ReadBuf, Readable};
+use chain_core::shared::SharedBytes;
 use chain_crypto::digest::Digest;
 use std::fmt::{self, Debug};
 use std::marker::PhantomData;
 
+/// A transaction, holding the bytes of its serialization.
+///
+/// When the transaction is read from a shared buffer (see
+/// [`ReadBuf::from_shared`](chain_core::mempack::ReadBuf::from_shared)),
+/// the bytes are a slice of the buffer and are not copied. The payload, e.g.
+/// a certificate, is decoded from these bytes into an owned value each time
+/// it is accessed.
 pub struct Transaction<P> {
-    pub(super) data: Box<[u8]>,
+    pub(super) data: SharedBytes,
     pub(super) tstruct: TransactionStruct,
     pub(super) phantom: PhantomData<P>,
 }
@@ -1,26 +1,107 @@
 use crate::legacy::OldAddress;
 use crate::value::*;
-use chain_core::mempack::{ReadBuf, ReadError, Readable};
+use chain_core::{
+    mempack::{ReadBuf, ReadError, Readable},
+    packer::Codec,
+    property::{ByteSource, Serialize},
+};
 
 /// Information how tokens are spent.
 /// A value of tokens is sent to the address.
+///
+/// Similarly to the `Extra` of an account state, an output can carry an
+/// extension payload, which the UTXO ledger stores along the output without
+/// looking at it. The outputs of the mockchain transactions carry none.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct Output<Address> {
+pub struct Output<Address, Extra = ()> {
     pub address: Address,
     pub value: Value,
+    pub extra: Extra,
 }
 
-impl<Address: Readable> Output<Address> {
+impl<Address> Output<Address> {
     pub fn from_address(address: Address, value: Value) -> Self {
-        Output { address, value }
+        Output {
+            address,
+            value,
+            extra: (),
+        }
     }
 }
 
+impl<Address, Extra> Output<Address, Extra> {
+    pub fn with_extra(address: Address, value: Value, extra: Extra) -> Self {
+        Output {
+            address,
+            value,
+            extra,
+        }
+    }
+}
+
+/// The binary encoding of the extension payload of an output, written right
+/// after the value of the output.
+///
+/// The unit payload is encoded as no bytes at all, so the encoding of the
+/// outputs without extension is unchanged. The transactions only carry the
+/// payload with the `utxo-extra` feature, the ledger snapshots always do.
+pub trait OutputExtra: Sized {
+    fn read_extra<S: ByteSource>(source: &mut S) -> Result<Self, ReadError>;
+
+    fn write_extra<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error>;
+}
+
+impl OutputExtra for () {
+    fn read_extra<S: ByteSource>(_: &mut S) -> Result<Self, ReadError> {
+        Ok(())
+    }
+
+    fn write_extra<W: std::io::Write>(&self, _: &mut Codec<W>) -> Result<(), std::io::Error> {
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "utxo-extra"))]
 impl<Address: Readable> Readable for Output<Address> {
     fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
         let address = Address::read(buf)?;
         let value = Value::read(buf)?;
-        Ok(Output { address, value })
+        Ok(Output::from_address(address, value))
+    }
+}
+
+#[cfg(feature = "utxo-extra")]
+impl<Address: Readable, Extra: OutputExtra> Readable for Output<Address, Extra> {
+    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
+        let address = Address::read(buf)?;
+        let value = Value::read(buf)?;
+        let extra = Extra::read_extra(buf)?;
+        Ok(Output::with_extra(address, value, extra))
+    }
+}
+
+#[cfg(not(feature = "utxo-extra"))]
+impl<Address: Serialize<Error = std::io::Error>> Serialize for Output<Address> {
+    type Error = std::io::Error;
+
+    fn serialize<W: std::io::Write>(&self, mut writer: W) -> Result<(), Self::Error> {
+        self.address.serialize(&mut writer)?;
+        writer.write_all(&self.value.bytes())
+    }
+}
+
+#[cfg(feature = "utxo-extra")]
+impl<Address, Extra> Serialize for Output<Address, Extra>
+where
+    Address: Serialize<Error = std::io::Error>,
+    Extra: OutputExtra,
+{
+    type Error = std::io::Error;
+
+    fn serialize<W: std::io::Write>(&self, mut writer: W) -> Result<(), Self::Error> {
+        self.address.serialize(&mut writer)?;
+        writer.write_all(&self.value.bytes())?;
+        self.extra.write_extra(&mut Codec::new(writer))
     }
 }
 
@@ -35,3 +116,49 @@ impl std::fmt::Display for Output<OldAddress> {
         write!(f, "{}.{}", self.address, self.value)
     }
 }
+
+#[cfg(all(test, feature = "utxo-extra"))]
+mod tests {
+    use super::*;
+    use crate::{testing::TestGen, utxo};
+    use chain_addr::{Address, Discrimination, Kind};
+
+    /// a colored coin, as a sidechain would tag its outputs
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct Color(u8);
+
+    impl OutputExtra for Color {
+        fn read_extra<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+            source.take_u8().map(Color)
+        }
+
+        fn write_extra<W: std::io::Write>(
+            &self,
+            codec: &mut Codec<W>,
+        ) -> Result<(), std::io::Error> {
+            codec.put_u8(self.0)
+        }
+    }
+
+    #[test]
+    fn colored_outputs_in_the_utxo_ledger() {
+        let address = Address(Discrimination::Test, Kind::Single(TestGen::public_key()));
+        let plain = Output::from_address(address.clone(), Value(10));
+        let colored = Output::with_extra(address, Value(10), Color(3));
+
+        let bytes = colored.serialize_as_vec().unwrap();
+        assert_eq!(bytes.len(), plain.serialize_as_vec().unwrap().len() + 1);
+        let mut buf = ReadBuf::from(&bytes);
+        assert_eq!(Output::<Address, Color>::read(&mut buf).unwrap(), colored);
+        buf.expect_end().unwrap();
+
+        let fragment_id = TestGen::hash();
+        let ledger = utxo::Ledger::<Address, Color>::new()
+            .add(&fragment_id, &[(0, colored.clone())])
+            .unwrap();
+        assert_eq!(ledger.get(&fragment_id, 0).unwrap().output, &colored);
+        let (ledger, spent) = ledger.remove(&fragment_id, 0).unwrap();
+        assert_eq!(spent.extra, Color(3));
+        assert!(ledger.iter().next().is_none());
+    }
+}
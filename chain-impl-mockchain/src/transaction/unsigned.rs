@@ -0,0 +1,334 @@
+//! Interchange format for transactions waiting to be signed.
+//!
+//! An [`UnsignedTransaction`] carries everything a signing tool needs to
+//! check and sign a transaction without access to the ledger: the inputs
+//! with their values, the outputs, the fee, and for every input the kind of
+//! witness expected and a hint to find the signing key. It is meant to be
+//! passed between the tools taking part in the signing, like a wallet and a
+//! hardware signer or the members of a multisig account.
+
+use super::builder::{SetAuthData, SetWitnesses, TxBuilder, TxBuilderState};
+use super::element::{BalanceError, TransactionSignDataHash};
+use super::input::{Input, InputType, INPUT_SIZE};
+use super::payload::Payload;
+use super::transfer::Output;
+use super::witness::Witness;
+use crate::account::SpendingCounter;
+use crate::chaintypes::HeaderId;
+use crate::date::BlockDate;
+use crate::value::Value;
+use chain_addr::Address;
+use chain_core::mempack::{ReadBuf, ReadError, Readable};
+use thiserror::Error;
+use typed_bytes::ByteBuilder;
+
+/// Version of the encoding, written as the first byte
+pub const UNSIGNED_TRANSACTION_VERSION: u8 = 1;
+
+const WITNESS_KIND_OLDUTXO: u8 = 0;
+const WITNESS_KIND_UTXO: u8 = 1;
+const WITNESS_KIND_ACCOUNT: u8 = 2;
+const WITNESS_KIND_MULTISIG: u8 = 3;
+
+/// The witness expected for an input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WitnessKind {
+    OldUtxo,
+    Utxo,
+    Account(SpendingCounter),
+    Multisig(SpendingCounter),
+}
+
+impl WitnessKind {
+    fn matches_input(&self, input: &Input) -> bool {
+        matches!(
+            (self, input.get_type()),
+            (WitnessKind::OldUtxo | WitnessKind::Utxo, InputType::Utxo)
+                | (
+                    WitnessKind::Account(_) | WitnessKind::Multisig(_),
+                    InputType::Account
+                )
+        )
+    }
+
+    fn matches_witness(&self, witness: &Witness) -> bool {
+        match (self, witness) {
+            (WitnessKind::OldUtxo, Witness::OldUtxo(..)) => true,
+            (WitnessKind::Utxo, Witness::Utxo(_)) => true,
+            (WitnessKind::Account(expected), Witness::Account(counter, _)) => expected == counter,
+            (WitnessKind::Multisig(expected), Witness::Multisig(counter, _)) => expected == counter,
+            _ => false,
+        }
+    }
+}
+
+/// Derivation path of the key signing an input, as understood by the
+/// signing tool. The ledger does not interpret it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DerivationHint(Vec<u32>);
+
+impl DerivationHint {
+    pub fn new(path: Vec<u32>) -> Self {
+        DerivationHint(path)
+    }
+
+    pub fn path(&self) -> &[u32] {
+        &self.0
+    }
+}
+
+/// An input of the transaction along with the instructions to sign it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsignedInput {
+    pub input: Input,
+    pub witness: WitnessKind,
+    pub derivation_hint: DerivationHint,
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum UnsignedTransactionError {
+    #[error("the transaction has {0} inputs, at most 255 are supported")]
+    TooManyInputs(usize),
+    #[error("the transaction has {0} outputs, at most 255 are supported")]
+    TooManyOutputs(usize),
+    #[error("the derivation hint of the input {index} is too long")]
+    DerivationHintTooLong { index: usize },
+    #[error("the witness expected for the input {index} cannot spend this kind of input")]
+    InvalidWitnessKind { index: usize },
+    #[error("the fee does not balance the inputs and outputs")]
+    NotBalanced(#[from] BalanceError),
+    #[error("expected {expected} witnesses, {actual} were given")]
+    WitnessCountMismatch { expected: usize, actual: usize },
+    #[error("the witness of the input {index} is not of the expected kind")]
+    WitnessMismatch { index: usize },
+    #[error("unsupported unsigned transaction version {0}")]
+    UnsupportedVersion(u8),
+    #[error("invalid unsigned transaction encoding")]
+    Read(#[from] ReadError),
+}
+
+/// A transaction waiting for its witnesses, with the instructions to sign it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsignedTransaction<P> {
+    block0: HeaderId,
+    payload: P,
+    valid_until: BlockDate,
+    inputs: Vec<UnsignedInput>,
+    outputs: Vec<Output<Address>>,
+    fee: Value,
+}
+
+impl<P: Payload> UnsignedTransaction<P> {
+    /// Create the envelope of a transaction, the fee needs to balance the
+    /// inputs and outputs exactly
+    pub fn new(
+        block0: HeaderId,
+        payload: P,
+        valid_until: BlockDate,
+        inputs: Vec<UnsignedInput>,
+        outputs: Vec<Output<Address>>,
+        fee: Value,
+    ) -> Result<Self, UnsignedTransactionError> {
+        if inputs.len() > u8::MAX as usize {
+            return Err(UnsignedTransactionError::TooManyInputs(inputs.len()));
+        }
+        if outputs.len() > u8::MAX as usize {
+            return Err(UnsignedTransactionError::TooManyOutputs(outputs.len()));
+        }
+        for (index, input) in inputs.iter().enumerate() {
+            if input.derivation_hint.path().len() > u8::MAX as usize {
+                return Err(UnsignedTransactionError::DerivationHintTooLong { index });
+            }
+            if !input.witness.matches_input(&input.input) {
+                return Err(UnsignedTransactionError::InvalidWitnessKind { index });
+            }
+        }
+
+        let total_input = Value::sum(inputs.iter().map(|input| input.input.value()))
+            .map_err(BalanceError::InputsTotalFailed)?;
+        let total_output = Value::sum(outputs.iter().map(|output| output.value))
+            .and_then(|total| total + fee)
+            .map_err(BalanceError::OutputsTotalFailed)?;
+        if total_input != total_output {
+            return Err(BalanceError::NotBalanced {
+                inputs: total_input,
+                outputs: total_output,
+            }
+            .into());
+        }
+
+        Ok(UnsignedTransaction {
+            block0,
+            payload,
+            valid_until,
+            inputs,
+            outputs,
+            fee,
+        })
+    }
+
+    pub fn block0(&self) -> &HeaderId {
+        &self.block0
+    }
+
+    pub fn payload(&self) -> &P {
+        &self.payload
+    }
+
+    pub fn valid_until(&self) -> BlockDate {
+        self.valid_until
+    }
+
+    pub fn inputs(&self) -> &[UnsignedInput] {
+        &self.inputs
+    }
+
+    pub fn outputs(&self) -> &[Output<Address>] {
+        &self.outputs
+    }
+
+    pub fn fee(&self) -> Value {
+        self.fee
+    }
+
+    /// Whether the payload needs to be authenticated once the witnesses are
+    /// set, see [`TxBuilderState::set_payload_auth`]
+    pub fn needs_payload_auth(&self) -> bool {
+        P::HAS_DATA && P::HAS_AUTH
+    }
+
+    /// The transaction builder, ready to receive the witnesses
+    pub fn builder(&self) -> TxBuilderState<SetWitnesses<P>> {
+        let inputs: Vec<Input> = self
+            .inputs
+            .iter()
+            .map(|input| input.input.clone())
+            .collect();
+        TxBuilder::new()
+            .set_payload(&self.payload)
+            .set_expiry_date(self.valid_until)
+            .set_ios(&inputs, &self.outputs)
+    }
+
+    /// The hash signed by the witnesses of all the inputs
+    pub fn sign_data_hash(&self) -> TransactionSignDataHash {
+        self.builder().get_auth_data_for_witness().hash()
+    }
+
+    /// Set the witnesses collected for every input, checking they are of the
+    /// expected kind
+    pub fn set_witnesses(
+        &self,
+        witnesses: &[Witness],
+    ) -> Result<TxBuilderState<SetAuthData<P>>, UnsignedTransactionError> {
+        if witnesses.len() != self.inputs.len() {
+            return Err(UnsignedTransactionError::WitnessCountMismatch {
+                expected: self.inputs.len(),
+                actual: witnesses.len(),
+            });
+        }
+        if let Some(index) = self
+            .inputs
+            .iter()
+            .zip(witnesses)
+            .position(|(input, witness)| !input.witness.matches_witness(witness))
+        {
+            return Err(UnsignedTransactionError::WitnessMismatch { index });
+        }
+        Ok(self.builder().set_witnesses(witnesses))
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let payload_data = self.payload.payload_data();
+        ByteBuilder::<Self>::new()
+            .u8(UNSIGNED_TRANSACTION_VERSION)
+            .bytes(self.block0.as_ref())
+            .u32(self.valid_until.epoch)
+            .u32(self.valid_until.slot_id)
+            .u32(payload_data.as_ref().len() as u32)
+            .bytes(payload_data.as_ref())
+            .u64(self.fee.0)
+            .iter8(self.inputs.iter(), |bb, input| {
+                let bb = bb.bytes(&input.input.bytes());
+                let bb = match input.witness {
+                    WitnessKind::OldUtxo => bb.u8(WITNESS_KIND_OLDUTXO),
+                    WitnessKind::Utxo => bb.u8(WITNESS_KIND_UTXO),
+                    WitnessKind::Account(counter) => {
+                        bb.u8(WITNESS_KIND_ACCOUNT).u32(counter.into())
+                    }
+                    WitnessKind::Multisig(counter) => {
+                        bb.u8(WITNESS_KIND_MULTISIG).u32(counter.into())
+                    }
+                };
+                bb.iter8(input.derivation_hint.path().iter(), |bb, index| {
+                    bb.u32(*index)
+                })
+            })
+            .iter8(self.outputs.iter(), |bb, output| {
+                bb.bytes(&output.address.to_bytes()).u64(output.value.0)
+            })
+            .finalize_as_vec()
+    }
+}
+
+impl<P: Payload + Readable> UnsignedTransaction<P> {
+    pub fn decode(bytes: &[u8]) -> Result<Self, UnsignedTransactionError> {
+        let mut buf = ReadBuf::from(bytes);
+        match buf.get_u8()? {
+            UNSIGNED_TRANSACTION_VERSION => {}
+            version => return Err(UnsignedTransactionError::UnsupportedVersion(version)),
+        }
+        let block0 = <[u8; 32]>::read(&mut buf)?.into();
+        let epoch = buf.get_u32()?;
+        let slot_id = buf.get_u32()?;
+        let payload_size = buf.get_u32()? as usize;
+        let mut payload_buf = buf.split_to(payload_size)?;
+        let payload = P::read(&mut payload_buf)?;
+        payload_buf.expect_end()?;
+        let fee = Value::read(&mut buf)?;
+
+        let nb_inputs = buf.get_u8()?;
+        let mut inputs = Vec::with_capacity(nb_inputs as usize);
+        for _ in 0..nb_inputs {
+            let mut input = [0u8; INPUT_SIZE];
+            input.copy_from_slice(buf.get_slice(INPUT_SIZE)?);
+            let witness = match buf.get_u8()? {
+                WITNESS_KIND_OLDUTXO => WitnessKind::OldUtxo,
+                WITNESS_KIND_UTXO => WitnessKind::Utxo,
+                WITNESS_KIND_ACCOUNT => WitnessKind::Account(buf.get_u32()?.into()),
+                WITNESS_KIND_MULTISIG => WitnessKind::Multisig(buf.get_u32()?.into()),
+                tag => {
+                    return Err(ReadError::UnknownTag {
+                        kind: "witness kind",
+                        tag: tag as u32,
+                    }
+                    .into())
+                }
+            };
+            let path_len = buf.get_u8()?;
+            let path = (0..path_len)
+                .map(|_| buf.get_u32())
+                .collect::<Result<_, _>>()?;
+            inputs.push(UnsignedInput {
+                input: input.into(),
+                witness,
+                derivation_hint: DerivationHint::new(path),
+            });
+        }
+
+        let nb_outputs = buf.get_u8()?;
+        let outputs = (0..nb_outputs)
+            .map(|_| Output::read(&mut buf))
+            .collect::<Result<_, _>>()?;
+        buf.expect_end()?;
+
+        Self::new(
+            block0,
+            payload,
+            BlockDate { epoch, slot_id },
+            inputs,
+            outputs,
+            fee,
+        )
+    }
+}
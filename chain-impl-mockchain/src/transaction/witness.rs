@@ -6,6 +6,7 @@ use crate::key::{
     SpendingSignature,
 };
 use crate::multisig;
+use chain_core::layout::{DescribeLayout, Field, Int, Layout, Variant};
 use chain_core::mempack::{ReadBuf, ReadError, Readable};
 use chain_core::property;
 use chain_crypto::{Ed25519, PublicKey, Signature};
@@ -64,6 +65,14 @@ pub enum WitnessUtxoVersion {
     Normal,
 }
 
+/// append the cycle of the spending counter to the signed data, only after
+/// the first cycle so the data of the first cycle is unchanged
+fn witness_data_cycle(data: &mut Vec<u8>, cycle: account::SpendingCycle) {
+    if cycle != 0 {
+        data.extend_from_slice(&cycle.to_le_bytes());
+    }
+}
+
 fn witness_data_common(
     data: &mut Vec<u8>,
     tag: u8,
@@ -105,9 +114,20 @@ impl WitnessAccountData {
         transaction_id: &TransactionSignDataHash,
         spending_counter: account::SpendingCounter,
     ) -> Self {
-        let mut v = Vec::with_capacity(69);
+        Self::new_in_cycle(block0, transaction_id, spending_counter, 0)
+    }
+
+    /// the data to sign for a spending counter of the given cycle of its lane
+    pub fn new_in_cycle(
+        block0: &HeaderId,
+        transaction_id: &TransactionSignDataHash,
+        spending_counter: account::SpendingCounter,
+        cycle: account::SpendingCycle,
+    ) -> Self {
+        let mut v = Vec::with_capacity(73);
         witness_data_common(&mut v, WITNESS_TAG_ACCOUNT, block0, transaction_id);
         v.extend_from_slice(&spending_counter.to_bytes());
+        witness_data_cycle(&mut v, cycle);
         WitnessAccountData(v)
     }
 }
@@ -126,9 +146,20 @@ impl WitnessMultisigData {
         transaction_id: &TransactionSignDataHash,
         spending_counter: account::SpendingCounter,
     ) -> Self {
-        let mut v = Vec::with_capacity(69);
+        Self::new_in_cycle(block0, transaction_id, spending_counter, 0)
+    }
+
+    /// the data to sign for a spending counter of the given cycle of its lane
+    pub fn new_in_cycle(
+        block0: &HeaderId,
+        transaction_id: &TransactionSignDataHash,
+        spending_counter: account::SpendingCounter,
+        cycle: account::SpendingCycle,
+    ) -> Self {
+        let mut v = Vec::with_capacity(73);
         witness_data_common(&mut v, WITNESS_TAG_MULTISIG, block0, transaction_id);
         v.extend_from_slice(&spending_counter.to_bytes());
+        witness_data_cycle(&mut v, cycle);
         Self(v)
     }
 }
@@ -172,7 +203,22 @@ impl Witness {
     where
         F: FnOnce(&WitnessAccountData) -> account::Witness,
     {
-        let wud = WitnessAccountData::new(block0, sign_data_hash, spending_counter);
+        Self::new_account_in_cycle(block0, sign_data_hash, spending_counter, 0, sign)
+    }
+
+    /// Creates an account witness for a spending counter of the given cycle
+    /// of its lane
+    pub fn new_account_in_cycle<F>(
+        block0: &HeaderId,
+        sign_data_hash: &TransactionSignDataHash,
+        spending_counter: account::SpendingCounter,
+        cycle: account::SpendingCycle,
+        sign: F,
+    ) -> Self
+    where
+        F: FnOnce(&WitnessAccountData) -> account::Witness,
+    {
+        let wud = WitnessAccountData::new_in_cycle(block0, sign_data_hash, spending_counter, cycle);
         let sig = sign(&wud);
         Witness::Account(spending_counter, sig)
     }
@@ -222,6 +268,44 @@ impl property::Serialize for Witness {
     }
 }
 
+impl DescribeLayout for Witness {
+    const NAME: &'static str = "Witness";
+
+    fn layout() -> Layout {
+        let signature = || Field::fixed("signature", 64).describe("ed25519 signature");
+        let spending_counter =
+            || Field::int("spending_counter", Int::U32).describe("lane and counter of the lane");
+        Layout::Tagged {
+            tag: Int::U8,
+            variants: vec![
+                Variant::new(
+                    WITNESS_TAG_OLDUTXO.into(),
+                    "OldUtxo",
+                    vec![
+                        Field::fixed("public_key", 32).describe("ed25519 public key"),
+                        Field::fixed("chain_code", 32),
+                        signature(),
+                    ],
+                ),
+                Variant::new(WITNESS_TAG_UTXO.into(), "Utxo", vec![signature()]),
+                Variant::new(
+                    WITNESS_TAG_ACCOUNT.into(),
+                    "Account",
+                    vec![spending_counter(), signature()],
+                ),
+                Variant::new(
+                    WITNESS_TAG_MULTISIG.into(),
+                    "Multisig",
+                    vec![
+                        spending_counter(),
+                        Field::remaining("witness").describe("the multisig witness"),
+                    ],
+                ),
+            ],
+        }
+    }
+}
+
 impl Readable for Witness {
     fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
         match buf.get_u8()? {
@@ -242,7 +326,65 @@ impl Readable for Witness {
                 let msig = multisig::Witness::read(buf)?;
                 Ok(Witness::Multisig(nonce, msig))
             }
-            i => Err(ReadError::UnknownTag(i as u32)),
+            i => Err(ReadError::UnknownTag {
+                kind: "witness",
+                tag: i as u32,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck::TestResult;
+    use quickcheck_macros::quickcheck;
+
+    #[quickcheck]
+    fn witness_data_of_first_cycle_is_unchanged(
+        block0: HeaderId,
+        transaction_id: TransactionSignDataHash,
+        spending_counter: account::SpendingCounter,
+    ) -> TestResult {
+        let account = WitnessAccountData::new(&block0, &transaction_id, spending_counter);
+        let multisig = WitnessMultisigData::new(&block0, &transaction_id, spending_counter);
+        assert_eq!(account.as_ref().len(), 69);
+        assert_eq!(account.as_ref()[65..], spending_counter.to_bytes());
+        TestResult::from_bool(
+            account.as_ref()
+                == WitnessAccountData::new_in_cycle(&block0, &transaction_id, spending_counter, 0)
+                    .as_ref()
+                && multisig.as_ref()
+                    == WitnessMultisigData::new_in_cycle(
+                        &block0,
+                        &transaction_id,
+                        spending_counter,
+                        0,
+                    )
+                    .as_ref(),
+        )
+    }
+
+    #[quickcheck]
+    fn witness_data_depends_on_the_cycle(
+        block0: HeaderId,
+        transaction_id: TransactionSignDataHash,
+        spending_counter: account::SpendingCounter,
+        cycle1: account::SpendingCycle,
+        cycle2: account::SpendingCycle,
+    ) -> TestResult {
+        if cycle1 == cycle2 {
+            return TestResult::discard();
         }
+        let account = |cycle| {
+            WitnessAccountData::new_in_cycle(&block0, &transaction_id, spending_counter, cycle)
+        };
+        let multisig = |cycle| {
+            WitnessMultisigData::new_in_cycle(&block0, &transaction_id, spending_counter, cycle)
+        };
+        TestResult::from_bool(
+            account(cycle1).as_ref() != account(cycle2).as_ref()
+                && multisig(cycle1).as_ref() != multisig(cycle2).as_ref(),
+        )
     }
 }
@@ -67,7 +67,7 @@ impl<Extra: Clone> TransactionBuilder<Extra> {
     ///
     /// Each output may extend the size of the required fee.
     pub fn add_output(&mut self, address: Address, value: Value) {
-        self.tx.outputs.push(tx::Output { address, value })
+        self.tx.outputs.push(tx::Output::from_address(address, value))
     }
 
     /// Calculate the fees on a given fee algorithm for the current transaction
@@ -154,16 +154,13 @@ impl<Extra: Clone> TransactionBuilder<Extra> {
                 //
                 // Otherwise better estimation algorithm is needed.
                 let mut tx = self.tx.clone();
-                tx.outputs.push(tx::Output {
-                    address: address.clone(),
-                    value: Value(0),
-                });
+                tx.outputs.push(tx::Output::from_address(address.clone(), Value(0)));
                 let fee = fee_algorithm
                     .calculate(&self.tx.extra, &self.tx.inputs, &self.tx.outputs)
                     .ok_or(Error::MathErr(ValueError::Overflow))?;
                 match tx.balance(fee) {
                     Ok(Balance::Positive(value)) => {
-                        self.tx.outputs.push(tx::Output { address, value });
+                        self.tx.outputs.push(tx::Output::from_address(address, value));
                         Ok((Balance::Zero, self.tx))
                     }
                     _ => Ok((Balance::Positive(pos), self.tx)),
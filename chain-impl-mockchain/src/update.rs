@@ -1,6 +1,8 @@
 //use crate::certificate::{verify_certificate, HasPublicKeys, SignatureRaw};
 use crate::certificate::{UpdateProposal, UpdateProposalId, UpdateVote, UpdateVoterId};
+use crate::config::ConfigParam;
 use crate::date::BlockDate;
+use crate::fragment::ConfigParams;
 use crate::setting::{ActiveSlotsCoeffError, Settings};
 use imhamt::Hamt;
 use std::collections::hash_map::DefaultHasher;
@@ -127,6 +129,30 @@ impl UpdateState {
 
         (self, settings)
     }
+
+    /// The settings which will be in use at the start of the epoch following
+    /// `date`, given the votes received so far.
+    pub fn next_epoch_settings(&self, settings: &Settings, date: BlockDate) -> Settings {
+        let next_epoch = date.next_epoch();
+        self.clone()
+            .process_proposals(settings.clone(), date, next_epoch)
+            .1
+    }
+}
+
+/// Whether the changes modify the fees, which may only happen at an epoch
+/// transition so the fees of the pending fragments stay valid until the end
+/// of the epoch.
+pub(crate) fn changes_fees(changes: &ConfigParams) -> bool {
+    changes.iter().any(|param| {
+        matches!(
+            param,
+            ConfigParam::LinearFee(_)
+                | ConfigParam::PerCertificateFees(_)
+                | ConfigParam::PerVoteCertificateFees(_)
+                | ConfigParam::PerCertificateByteFee(_)
+        )
+    })
 }
 
 impl Default for UpdateState {
@@ -156,8 +182,10 @@ pub enum Error {
     BadVoter(UpdateProposalId, UpdateVoterId),
     DuplicateVote(UpdateProposalId, UpdateVoterId),
     ReadOnlySetting,
+    FeesChangeOutsideEpochTransition,
     BadBftSlotsRatio(crate::milli::Milli),
     BadConsensusGenesisPraosActiveSlotsCoeff(ActiveSlotsCoeffError),
+    ProtocolVersionDowngrade(u32, u32),
 }
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
@@ -208,6 +236,10 @@ impl std::fmt::Display for Error {
                 f,
                 "Received a proposal to modify a chain parameter that can only be set in block 0"
             ),
+            Error::FeesChangeOutsideEpochTransition => write!(
+                f,
+                "Fees can only be changed by an accepted proposal at an epoch transition"
+            ),
             Error::BadBftSlotsRatio(m) => {
                 write!(f, "Cannot set BFT slots ratio to invalid value {}", m)
             }
@@ -216,6 +248,11 @@ impl std::fmt::Display for Error {
                 "Cannot set consensus genesis praos active slots coefficient: {}",
                 err
             ),
+            Error::ProtocolVersionDowngrade(current, proposed) => write!(
+                f,
+                "Cannot downgrade the protocol version from {} to {}",
+                current, proposed
+            ),
         }
     }
 }
@@ -233,6 +270,8 @@ mod tests {
     use super::*;
     use crate::certificate::UpdateProposal;
     #[cfg(test)]
+    use crate::fee::LinearFee;
+    #[cfg(test)]
     use crate::milli::Milli;
     #[cfg(test)]
     use crate::testing::serialization::serialization_bijection;
@@ -646,6 +685,61 @@ mod tests {
         assert_eq!(update_state.proposals.size(), 0);
     }
 
+    #[test]
+    pub fn accepted_fees_change_is_only_applied_at_next_epoch() {
+        let mut update_state = UpdateState::new();
+        let proposal_id = TestGen::hash();
+        let proposer = TestGen::leader_pair();
+        let block_date = BlockDate::first();
+        let new_fees = LinearFee::new(10, 2, 1);
+
+        let settings = TestGen::settings(vec![proposer.clone()]);
+        assert!(changes_fees(&ConfigParams(vec![ConfigParam::LinearFee(
+            new_fees
+        )])));
+
+        update_state = apply_update_proposal(
+            update_state,
+            proposal_id,
+            ConfigParam::LinearFee(new_fees),
+            &proposer,
+            &settings,
+            block_date,
+        )
+        .expect("failed while applying proposal");
+        assert_eq!(
+            update_state
+                .next_epoch_settings(&settings, block_date)
+                .linear_fees,
+            settings.linear_fees
+        );
+
+        update_state = apply_update_vote(update_state, proposal_id, &proposer, &settings)
+            .expect("failed while applying vote");
+        assert_eq!(
+            update_state
+                .next_epoch_settings(&settings, block_date)
+                .linear_fees,
+            new_fees
+        );
+
+        // no epoch transition, the proposal stays pending
+        let (update_state, current) = update_state.process_proposals(
+            settings.clone(),
+            block_date,
+            BlockDate {
+                slot_id: block_date.slot_id + 1,
+                ..block_date
+            },
+        );
+        assert_eq!(current.linear_fees, settings.linear_fees);
+        assert_eq!(update_state.proposals.size(), 1);
+
+        let (_, next) =
+            update_state.process_proposals(settings, block_date, block_date.next_epoch());
+        assert_eq!(next.linear_fees, new_fees);
+    }
+
     #[cfg(test)]
     #[derive(Debug, Copy, Clone)]
     struct ExpiryBlockDate {
@@ -3,6 +3,10 @@
 //! The UTXO works similarly to cash where the demoninations are of arbitrary values,
 //! and each demonination get permanantly consumed by the system once spent.
 //!
+//! The outputs can carry an extension payload, `()` by default, so the ledger
+//! can be reused as is by the chains tagging their outputs (e.g. colored
+//! coins). The encoding of the payload in the transactions is enabled by the
+//! `utxo-extra` feature.
 
 use crate::fragment::FragmentId;
 use crate::transaction::{Output, TransactionIndex};
@@ -51,10 +55,10 @@ impl From<RemoveError> for Error {
 
 /// Hold all the individual outputs that remain unspent
 #[derive(Clone, PartialEq, Eq, Debug)]
-struct TransactionUnspents<OutAddress>(FastSparseArray<Output<OutAddress>>);
+struct TransactionUnspents<OutAddress, Extra = ()>(FastSparseArray<Output<OutAddress, Extra>>);
 
-impl<OutAddress: Clone> TransactionUnspents<OutAddress> {
-    pub fn from_outputs(outs: &[(TransactionIndex, Output<OutAddress>)]) -> Self {
+impl<OutAddress: Clone, Extra: Clone> TransactionUnspents<OutAddress, Extra> {
+    pub fn from_outputs(outs: &[(TransactionIndex, Output<OutAddress, Extra>)]) -> Self {
         assert!(outs.len() < 255);
         let mut sa = FastSparseArrayBuilder::with_capacity(outs.len() as u8);
         for (index, output) in outs.iter() {
@@ -66,7 +70,7 @@ impl<OutAddress: Clone> TransactionUnspents<OutAddress> {
     pub fn remove_input(
         &self,
         index: TransactionIndex,
-    ) -> Result<(Self, Output<OutAddress>), Error> {
+    ) -> Result<(Self, Output<OutAddress, Extra>), Error> {
         assert!(index < 255);
         let mut t = self.0.clone();
         match t.remove(index) {
@@ -78,16 +82,18 @@ impl<OutAddress: Clone> TransactionUnspents<OutAddress> {
 
 /// Ledger of UTXO
 #[derive(Clone, PartialEq, Eq)]
-pub struct Ledger<OutAddress>(Hamt<DefaultHasher, FragmentId, TransactionUnspents<OutAddress>>);
+pub struct Ledger<OutAddress, Extra = ()>(
+    Hamt<DefaultHasher, FragmentId, TransactionUnspents<OutAddress, Extra>>,
+);
 
-pub struct Iter<'a, V> {
-    hamt_iter: HamtIter<'a, FragmentId, TransactionUnspents<V>>,
-    unspents_iter: Option<(&'a FragmentId, FastSparseArrayIter<'a, Output<V>>)>,
+pub struct Iter<'a, V, Extra = ()> {
+    hamt_iter: HamtIter<'a, FragmentId, TransactionUnspents<V, Extra>>,
+    unspents_iter: Option<(&'a FragmentId, FastSparseArrayIter<'a, Output<V, Extra>>)>,
 }
 
-pub struct Values<'a, V> {
-    hamt_iter: HamtIter<'a, FragmentId, TransactionUnspents<V>>,
-    unspents_iter: Option<FastSparseArrayIter<'a, Output<V>>>,
+pub struct Values<'a, V, Extra = ()> {
+    hamt_iter: HamtIter<'a, FragmentId, TransactionUnspents<V, Extra>>,
+    unspents_iter: Option<FastSparseArrayIter<'a, Output<V, Extra>>>,
 }
 
 impl fmt::Debug for Ledger<Address> {
@@ -99,30 +105,30 @@ impl fmt::Debug for Ledger<Address> {
 /// structure used by the iterator or the getter of the UTxO `Ledger`
 ///
 #[derive(Debug, PartialEq, Clone)]
-pub struct Entry<'a, OutputAddress> {
+pub struct Entry<'a, OutputAddress, Extra = ()> {
     pub fragment_id: FragmentId,
     pub output_index: u8,
-    pub output: &'a Output<OutputAddress>,
+    pub output: &'a Output<OutputAddress, Extra>,
 }
 
 /// structure used by the iterator or the getter of the UTxO `Ledger`
 ///
 #[derive(Debug, PartialEq, Clone)]
-pub struct EntryOwned<OutputAddress> {
+pub struct EntryOwned<OutputAddress, Extra = ()> {
     pub fragment_id: FragmentId,
     pub output_index: u8,
-    pub output: Output<OutputAddress>,
+    pub output: Output<OutputAddress, Extra>,
 }
 
-impl<OutAddress> Ledger<OutAddress> {
-    pub fn iter(&self) -> Iter<'_, OutAddress> {
+impl<OutAddress, Extra> Ledger<OutAddress, Extra> {
+    pub fn iter(&self) -> Iter<'_, OutAddress, Extra> {
         Iter {
             hamt_iter: self.0.iter(),
             unspents_iter: None,
         }
     }
 
-    pub fn values(&self) -> Values<'_, OutAddress> {
+    pub fn values(&self) -> Values<'_, OutAddress, Extra> {
         Values {
             hamt_iter: self.0.iter(),
             unspents_iter: None,
@@ -133,7 +139,7 @@ impl<OutAddress> Ledger<OutAddress> {
         &'a self,
         tid: &FragmentId,
         index: TransactionIndex,
-    ) -> Option<Entry<'a, OutAddress>> {
+    ) -> Option<Entry<'a, OutAddress, Extra>> {
         self.0
             .lookup(tid)
             .and_then(|unspent| unspent.0.get(index))
@@ -149,8 +155,8 @@ impl<OutAddress> Ledger<OutAddress> {
     }
 }
 
-impl<'a, V> Iterator for Values<'a, V> {
-    type Item = &'a Output<V>;
+impl<'a, V, Extra> Iterator for Values<'a, V, Extra> {
+    type Item = &'a Output<V, Extra>;
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
@@ -168,8 +174,8 @@ impl<'a, V> Iterator for Values<'a, V> {
     }
 }
 
-impl<'a, V> Iterator for Iter<'a, V> {
-    type Item = Entry<'a, V>;
+impl<'a, V, Extra> Iterator for Iter<'a, V, Extra> {
+    type Item = Entry<'a, V, Extra>;
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
@@ -193,13 +199,13 @@ impl<'a, V> Iterator for Iter<'a, V> {
     }
 }
 
-impl<OutAddress: Clone> Default for Ledger<OutAddress> {
+impl<OutAddress: Clone, Extra: Clone> Default for Ledger<OutAddress, Extra> {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl<OutAddress: Clone> Ledger<OutAddress> {
+impl<OutAddress: Clone, Extra: Clone> Ledger<OutAddress, Extra> {
     /// Create a new empty UTXO Ledger
     pub fn new() -> Self {
         Ledger(Hamt::new())
@@ -211,7 +217,7 @@ impl<OutAddress: Clone> Ledger<OutAddress> {
     pub fn add(
         &self,
         tid: &FragmentId,
-        outs: &[(TransactionIndex, Output<OutAddress>)],
+        outs: &[(TransactionIndex, Output<OutAddress, Extra>)],
     ) -> Result<Self, Error> {
         assert!(!outs.is_empty());
         assert!(outs.len() < 255);
@@ -226,7 +232,7 @@ impl<OutAddress: Clone> Ledger<OutAddress> {
         &self,
         tid: &FragmentId,
         index: TransactionIndex,
-    ) -> Result<(Self, Output<OutAddress>), Error> {
+    ) -> Result<(Self, Output<OutAddress, Extra>), Error> {
         let (treemap, output) = match self.0.lookup(tid) {
             None => Err(Error::TransactionNotFound),
             Some(out) => out.remove_input(index),
@@ -243,7 +249,7 @@ impl<OutAddress: Clone> Ledger<OutAddress> {
         &self,
         tid: &FragmentId,
         indices: &[TransactionIndex],
-    ) -> Result<(Self, Vec<Output<OutAddress>>), Error> {
+    ) -> Result<(Self, Vec<Output<OutAddress, Extra>>), Error> {
         let (treemap, outputs) = match self.0.lookup(tid) {
             None => Err(Error::TransactionNotFound),
             Some(out) => {
@@ -266,12 +272,19 @@ impl<OutAddress: Clone> Ledger<OutAddress> {
     }
 }
 
-impl<OutAddress: Clone>
-    std::iter::FromIterator<(FragmentId, Vec<(TransactionIndex, Output<OutAddress>)>)>
-    for Ledger<OutAddress>
+impl<OutAddress: Clone, Extra: Clone>
+    std::iter::FromIterator<(
+        FragmentId,
+        Vec<(TransactionIndex, Output<OutAddress, Extra>)>,
+    )> for Ledger<OutAddress, Extra>
 {
     fn from_iter<
-        I: IntoIterator<Item = (FragmentId, Vec<(TransactionIndex, Output<OutAddress>)>)>,
+        I: IntoIterator<
+            Item = (
+                FragmentId,
+                Vec<(TransactionIndex, Output<OutAddress, Extra>)>,
+            ),
+        >,
     >(
         iter: I,
     ) -> Self {
@@ -1,7 +1,8 @@
 use crate::stake::Stake;
-use chain_core::mempack::{ReadBuf, ReadError, Readable};
-use chain_core::property;
-use std::{iter::Sum, ops};
+use chain_core::mempack::ReadError;
+use chain_core::packer::Codec;
+use chain_core::property::{ByteSource, Pack, Unpack};
+use std::{fmt, iter::Sum, ops, str::FromStr};
 use thiserror::Error;
 
 /// Unspent transaction value.
@@ -25,7 +26,18 @@ impl Value {
     where
         I: Iterator<Item = Self>,
     {
-        values.fold(Ok(Value::zero()), |acc, v| acc? + v)
+        Value::checked_sum(values).map_err(ValueError::from)
+    }
+
+    /// Sum the values, reporting the operand which overflowed along with
+    /// the sum of the operands before it
+    pub fn checked_sum<I>(values: I) -> Result<Self, SumOverflow>
+    where
+        I: IntoIterator<Item = Self>,
+    {
+        checked_sum(values, Value::zero(), |a, b| {
+            a.0.checked_add(b.0).map(Value)
+        })
     }
 
     #[inline]
@@ -68,6 +80,114 @@ impl Value {
     pub fn bytes(self) -> [u8; VALUE_SERIALIZED_SIZE] {
         self.0.to_be_bytes()
     }
+
+    /// Parse a value expressed in a unit having `decimals` decimal places,
+    /// e.g. `"1_000.5"` with 6 decimals is `Value(1_000_500_000)`.
+    ///
+    /// Underscores are allowed between digits. The conversion is lossless:
+    /// a value which cannot be represented exactly (too many significant
+    /// decimals, or overflowing) is rejected rather than rounded.
+    pub fn from_str_decimals(s: &str, decimals: u32) -> Result<Self, ValueParseError> {
+        let (integer, fraction) = match s.split_once('.') {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (s, None),
+        };
+
+        let integer = parse_units(&strip_underscores(integer)?)?;
+        let mut units = scale_units(integer, decimals)?;
+
+        if let Some(fraction) = fraction {
+            let fraction = strip_underscores(fraction)?;
+            let (significant, extra) = fraction.split_at(fraction.len().min(decimals as usize));
+            if extra.chars().any(|c| c != '0') {
+                return Err(ValueParseError::TooManyDecimals { decimals });
+            }
+            let fraction = parse_units(significant)?;
+            let fraction = scale_units(fraction, decimals - significant.len() as u32)?;
+            units = units
+                .checked_add(fraction)
+                .ok_or(ValueParseError::Overflow)?;
+        }
+        Ok(Value(units))
+    }
+
+    /// Display the value in a unit having `decimals` decimal places, all the
+    /// decimal places are printed so the output can be parsed back with
+    /// [`Value::from_str_decimals`].
+    pub fn display_decimals(self, decimals: u32) -> ValueDecimals {
+        ValueDecimals {
+            value: self,
+            decimals,
+        }
+    }
+}
+
+/// Formatter of a value with decimal places, see [`Value::display_decimals`]
+#[derive(Debug, Clone, Copy)]
+pub struct ValueDecimals {
+    value: Value,
+    decimals: u32,
+}
+
+impl fmt::Display for ValueDecimals {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let units = self.value.0;
+        let (integer, fraction) = match 10u64.checked_pow(self.decimals) {
+            Some(scale) => (units / scale, units % scale),
+            None => (0, units),
+        };
+        write!(f, "{}", integer)?;
+        if self.decimals > 0 {
+            write!(f, ".{:0width$}", fraction, width = self.decimals as usize)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum ValueParseError {
+    #[error("Value is empty")]
+    Empty,
+    #[error("Invalid character {0:?} in value")]
+    InvalidCharacter(char),
+    #[error("Underscores are only allowed between digits")]
+    MisplacedUnderscore,
+    #[error("Value has more than {decimals} significant decimals")]
+    TooManyDecimals { decimals: u32 },
+    #[error("Value overflowed its maximum value")]
+    Overflow,
+}
+
+fn strip_underscores(digits: &str) -> Result<String, ValueParseError> {
+    if digits.is_empty() {
+        return Err(ValueParseError::Empty);
+    }
+    if let Some(c) = digits.chars().find(|c| !c.is_ascii_digit() && *c != '_') {
+        return Err(ValueParseError::InvalidCharacter(c));
+    }
+    if digits.starts_with('_') || digits.ends_with('_') || digits.contains("__") {
+        return Err(ValueParseError::MisplacedUnderscore);
+    }
+    Ok(digits.chars().filter(|c| *c != '_').collect())
+}
+
+fn parse_units(digits: &str) -> Result<u64, ValueParseError> {
+    digits
+        .bytes()
+        .try_fold(0u64, |units, digit| {
+            units.checked_mul(10)?.checked_add((digit - b'0') as u64)
+        })
+        .ok_or(ValueParseError::Overflow)
+}
+
+fn scale_units(units: u64, decimals: u32) -> Result<u64, ValueParseError> {
+    if units == 0 {
+        return Ok(0);
+    }
+    10u64
+        .checked_pow(decimals)
+        .and_then(|scale| units.checked_mul(scale))
+        .ok_or(ValueParseError::Overflow)
 }
 
 #[derive(Debug, Error, Clone, PartialEq, Eq)]
@@ -82,6 +202,62 @@ pub enum ValueError {
     FromSliceTooBig,
 }
 
+/// The overflow of a sum, see [`Value::checked_sum`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SumOverflow<T = Value> {
+    /// index of the operand which overflowed, starting from 0
+    pub index: usize,
+    /// sum of the operands before the one which overflowed
+    pub partial_sum: T,
+    pub operand: T,
+}
+
+impl<T> SumOverflow<T> {
+    pub fn map<U, F: Fn(T) -> U>(self, f: F) -> SumOverflow<U> {
+        SumOverflow {
+            index: self.index,
+            partial_sum: f(self.partial_sum),
+            operand: f(self.operand),
+        }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for SumOverflow<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "sum overflowed adding the operand {} ({}) to the partial sum {}",
+            self.index, self.operand, self.partial_sum
+        )
+    }
+}
+
+impl<T: fmt::Display + fmt::Debug> std::error::Error for SumOverflow<T> {}
+
+impl<T> From<SumOverflow<T>> for ValueError {
+    fn from(_: SumOverflow<T>) -> Self {
+        ValueError::Overflow
+    }
+}
+
+pub(crate) fn checked_sum<T, I, F>(values: I, zero: T, checked_add: F) -> Result<T, SumOverflow<T>>
+where
+    T: Copy,
+    I: IntoIterator<Item = T>,
+    F: Fn(T, T) -> Option<T>,
+{
+    values
+        .into_iter()
+        .enumerate()
+        .try_fold(zero, |partial_sum, (index, operand)| {
+            checked_add(partial_sum, operand).ok_or(SumOverflow {
+                index,
+                partial_sum,
+                operand,
+            })
+        })
+}
+
 impl Sum for Value {
     fn sum<I>(iter: I) -> Self
     where
@@ -113,36 +289,35 @@ impl AsRef<u64> for Value {
     }
 }
 
-impl property::Deserialize for Value {
-    type Error = std::io::Error;
-
-    fn deserialize<R: std::io::BufRead>(reader: R) -> Result<Self, Self::Error> {
-        use chain_core::packer::*;
-        let mut codec = Codec::new(reader);
-        codec.get_be_u64().map(Value)
-    }
-}
-impl Readable for Value {
-    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        buf.get_u64().map(Value)
+impl Pack for Value {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_be_u64(self.0)
     }
 }
 
-impl property::Serialize for Value {
-    type Error = std::io::Error;
-    fn serialize<W: std::io::Write>(&self, writer: W) -> Result<(), Self::Error> {
-        use chain_core::packer::*;
-        let mut codec = Codec::new(writer);
-        codec.put_be_u64(self.0)
+impl Unpack for Value {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        source.take_u64().map(Value)
     }
 }
 
+chain_ser::impl_legacy_codecs!(Value);
+
 impl std::fmt::Display for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "{}", self.0)
     }
 }
 
+/// Parse a value in its smallest unit, see [`Value::from_str_decimals`]
+impl FromStr for Value {
+    type Err = ValueParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Value::from_str_decimals(s, 0)
+    }
+}
+
 impl TryFrom<&[u8]> for Value {
     type Error = ValueError;
     fn try_from(slice: &[u8]) -> Result<Value, ValueError> {
@@ -160,8 +335,117 @@ impl TryFrom<&[u8]> for Value {
     }
 }
 
+/// Raw conversion, prefer [`Stake::from_value_at`] which tells when the
+/// value became stake
 impl From<Value> for Stake {
     fn from(value: Value) -> Stake {
-        Stake::from_value(value)
+        Stake(value.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_from_str_decimals(input: &str, decimals: u32, expected: u64) {
+        let actual = Value::from_str_decimals(input, decimals)
+            .unwrap_or_else(|err| panic!("Failed to parse for input {}: {:?}", input, err));
+        assert_eq!(
+            actual,
+            Value(expected),
+            "Invalid result for input {}",
+            input
+        );
+    }
+
+    fn refute_from_str_decimals(input: &str, decimals: u32, expected: ValueParseError) {
+        assert_eq!(
+            Value::from_str_decimals(input, decimals),
+            Err(expected),
+            "Invalid result for input {}",
+            input
+        );
+    }
+
+    #[test]
+    fn from_str() {
+        assert_eq!("0".parse(), Ok(Value(0)));
+        assert_eq!("1_000_000".parse(), Ok(Value(1_000_000)));
+        assert_eq!("18446744073709551615".parse(), Ok(Value(u64::MAX)));
+        assert_eq!("12.000".parse(), Ok(Value(12)));
+        assert_eq!(
+            "18446744073709551616".parse::<Value>(),
+            Err(ValueParseError::Overflow)
+        );
+        assert_eq!(
+            "12.5".parse::<Value>(),
+            Err(ValueParseError::TooManyDecimals { decimals: 0 })
+        );
+    }
+
+    #[test]
+    fn from_str_decimals() {
+        assert_from_str_decimals("1", 6, 1_000_000);
+        assert_from_str_decimals("1.5", 6, 1_500_000);
+        assert_from_str_decimals("0.000001", 6, 1);
+        assert_from_str_decimals("1_000.000_001", 6, 1_000_000_001);
+        assert_from_str_decimals("1.1000000", 6, 1_100_000);
+        assert_from_str_decimals("18446744073709.551615", 6, u64::MAX);
+        assert_from_str_decimals("0.00000000000000000000001", 30, 10_000_000);
+
+        refute_from_str_decimals("", 6, ValueParseError::Empty);
+        refute_from_str_decimals("1.", 6, ValueParseError::Empty);
+        refute_from_str_decimals(".1", 6, ValueParseError::Empty);
+        refute_from_str_decimals("-1", 6, ValueParseError::InvalidCharacter('-'));
+        refute_from_str_decimals("1.2.3", 6, ValueParseError::InvalidCharacter('.'));
+        refute_from_str_decimals("1__000", 6, ValueParseError::MisplacedUnderscore);
+        refute_from_str_decimals("_1", 6, ValueParseError::MisplacedUnderscore);
+        refute_from_str_decimals("1_.5", 6, ValueParseError::MisplacedUnderscore);
+        refute_from_str_decimals(
+            "0.0000001",
+            6,
+            ValueParseError::TooManyDecimals { decimals: 6 },
+        );
+        refute_from_str_decimals("18446744073709.551616", 6, ValueParseError::Overflow);
+        refute_from_str_decimals("1", 20, ValueParseError::Overflow);
+    }
+
+    #[test]
+    fn display_decimals() {
+        assert_eq!(Value(0).display_decimals(6).to_string(), "0.000000");
+        assert_eq!(Value(1).display_decimals(6).to_string(), "0.000001");
+        assert_eq!(Value(1_500_000).display_decimals(6).to_string(), "1.500000");
+        assert_eq!(Value(42).display_decimals(0).to_string(), "42");
+        assert_eq!(
+            Value(u64::MAX).display_decimals(25).to_string(),
+            "0.0000018446744073709551615"
+        );
+    }
+
+    #[test]
+    fn checked_sum_reports_the_overflowing_operand() {
+        let values = [Value(1), Value(u64::MAX - 3), Value(2), Value(1), Value(5)];
+        assert_eq!(
+            Value::checked_sum(values),
+            Err(SumOverflow {
+                index: 3,
+                partial_sum: Value(u64::MAX),
+                operand: Value(1),
+            })
+        );
+        assert_eq!(Value::sum(values.into_iter()), Err(ValueError::Overflow));
+        assert_eq!(
+            Value::checked_sum(values[..3].to_vec()),
+            Ok(Value(u64::MAX))
+        );
+        assert_eq!(Value::checked_sum(Vec::new()), Ok(Value::zero()));
+    }
+
+    quickcheck! {
+        fn value_display_parse_decimals_cycle(value: Value, decimals: u8) -> bool {
+            let decimals = decimals as u32 % 32;
+            let displayed = value.display_decimals(decimals).to_string();
+            Value::from_str_decimals(&displayed, decimals) == Ok(value)
+        }
     }
 }
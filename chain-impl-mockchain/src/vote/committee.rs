@@ -1,8 +1,9 @@
 use chain_core::{
-    mempack::{ReadBuf, ReadError, Readable},
-    property,
+    mempack::ReadError,
+    packer::Codec,
+    property::{ByteSource, Pack, Unpack},
 };
-use chain_crypto::{Ed25519, PublicKey};
+use chain_crypto::{ConstantTimeEq, Ed25519, PublicKey};
 use std::{
     fmt::{self, Debug, Display},
     str::FromStr,
@@ -35,6 +36,26 @@ impl CommitteeId {
     pub fn public_key(&self) -> PublicKey<Ed25519> {
         (*self).into()
     }
+
+    /// check if the identifier is one of the members of the committee,
+    /// comparing it with every member in constant time
+    pub fn is_member_of<'a, I>(&self, committee: I) -> bool
+    where
+        I: IntoIterator<Item = &'a CommitteeId>,
+    {
+        committee
+            .into_iter()
+            .fold(chain_crypto::Choice::from(0), |found, member| {
+                found | member.ct_eq(self)
+            })
+            .into()
+    }
+}
+
+impl ConstantTimeEq for CommitteeId {
+    fn ct_eq(&self, other: &Self) -> chain_crypto::Choice {
+        self.0.ct_eq(&other.0)
+    }
 }
 
 /* Conversion ************************************************************** */
@@ -125,25 +146,30 @@ impl FromStr for CommitteeId {
 
 /* Ser/De ****************************************************************** */
 
-impl property::Serialize for CommitteeId {
-    type Error = std::io::Error;
-    fn serialize<W: std::io::Write>(&self, mut writer: W) -> Result<(), Self::Error> {
-        writer.write_all(self.as_ref())
+impl Pack for CommitteeId {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(self.as_ref())
     }
 }
 
-impl Readable for CommitteeId {
-    fn read(reader: &mut ReadBuf) -> Result<Self, ReadError> {
-        let slice = reader.get_slice(Self::COMMITTEE_ID_SIZE)?;
-        Self::try_from(slice).map_err(|err| ReadError::StructureInvalid(err.to_string()))
+impl Unpack for CommitteeId {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let mut bytes = [0; Self::COMMITTEE_ID_SIZE];
+        source.take_into(&mut bytes)?;
+        Ok(CommitteeId(bytes))
     }
 }
 
+chain_ser::impl_legacy_codecs!(CommitteeId);
+
 #[cfg(any(test, feature = "property-test-api"))]
 mod tests {
     use super::*;
     #[cfg(test)]
-    use chain_core::property::Serialize as _;
+    use chain_core::{
+        mempack::{ReadBuf, Readable as _},
+        property::Serialize as _,
+    };
     use quickcheck::{Arbitrary, Gen};
     use quickcheck_macros::quickcheck;
 
@@ -178,4 +204,12 @@ mod tests {
         let result = CommitteeId::read(&mut buf).expect("decode the committee ID");
         committee_id == result
     }
+
+    #[quickcheck]
+    fn ct_eq_and_membership(committee: Vec<CommitteeId>, other: CommitteeId) -> bool {
+        committee
+            .iter()
+            .all(|member| bool::from(member.ct_eq(member)) && member.is_member_of(&committee))
+            && other.is_member_of(&committee) == committee.contains(&other)
+    }
 }
@@ -0,0 +1,440 @@
+//! Canonical JSON representation of the governance artifacts.
+//!
+//! The types below are the external representation of the vote plans, the
+//! vote casts, the tallies and the committees, so that the backends consuming
+//! this crate agree on a single mapping. They are plain data converted from
+//! the chain types, and their encoding is fixed:
+//!
+//! * the identifiers, keys, ciphertexts and proofs are lowercase hex of their
+//!   binary encoding on chain;
+//! * the dates are `{ "epoch", "slot_id" }` objects;
+//! * the values and the weights are integers;
+//! * the enumerations are objects tagged by a `type` field in snake case.
+//!
+//! Changing the representation bumps [`SCHEMA_VERSION`], and [`schema`]
+//! returns the JSON Schema of every type, to be published with the backends.
+
+use crate::{
+    certificate::{VoteAction, VoteCast, VotePlan},
+    date::BlockDate,
+    ledger::governance::{ParametersGovernanceAction, TreasuryGovernanceAction},
+    vote::{CommitteeId, Payload, PayloadType, PrivateTallyState, Tally, TallyResult},
+};
+use chain_vote::MemberPublicKey;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value as JsonValue};
+
+/// version of the representation, part of the `$id` of the schema
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BlockDateJson {
+    pub epoch: u32,
+    pub slot_id: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadTypeJson {
+    Public,
+    Private,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum VoteActionJson {
+    OffChain,
+    TreasuryNoOp,
+    TransferToRewards { value: u64 },
+    ParametersNoOp,
+    RewardAdd { value: u64 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProposalJson {
+    pub external_id: String,
+    /// the number of options, the choices being `0..options`
+    pub options: u8,
+    pub action: VoteActionJson,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VotePlanJson {
+    pub id: String,
+    pub vote_start: BlockDateJson,
+    pub vote_end: BlockDateJson,
+    pub committee_end: BlockDateJson,
+    pub payload_type: PayloadTypeJson,
+    pub proposals: Vec<ProposalJson>,
+    pub committee_public_keys: Vec<String>,
+    /// the policy hash, then the token name if any, separated by a `.`
+    pub voting_token: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum PayloadJson {
+    Public {
+        choice: u8,
+    },
+    Private {
+        encrypted_vote: String,
+        proof: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VoteCastJson {
+    pub vote_plan: String,
+    pub proposal_index: u8,
+    pub payload: PayloadJson,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TallyResultJson {
+    pub options: u8,
+    /// the weight of every choice, in the order of the choices
+    pub results: Vec<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum TallyJson {
+    Public {
+        result: TallyResultJson,
+    },
+    /// the votes are tallied but not decrypted by the committee yet
+    Encrypted {
+        encrypted_tally: String,
+        total_stake: u64,
+    },
+    Decrypted {
+        result: TallyResultJson,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CommitteeJson {
+    /// the identifiers of the committee members allowed to submit the vote
+    /// plans and the tallies
+    pub members: Vec<String>,
+    /// the keys the private votes are encrypted with
+    pub member_public_keys: Vec<String>,
+}
+
+impl From<BlockDate> for BlockDateJson {
+    fn from(date: BlockDate) -> Self {
+        BlockDateJson {
+            epoch: date.epoch,
+            slot_id: date.slot_id,
+        }
+    }
+}
+
+impl From<PayloadType> for PayloadTypeJson {
+    fn from(payload_type: PayloadType) -> Self {
+        match payload_type {
+            PayloadType::Public => PayloadTypeJson::Public,
+            PayloadType::Private => PayloadTypeJson::Private,
+        }
+    }
+}
+
+impl From<&VoteAction> for VoteActionJson {
+    fn from(action: &VoteAction) -> Self {
+        match action {
+            VoteAction::OffChain => VoteActionJson::OffChain,
+            VoteAction::Treasury { action } => match action {
+                TreasuryGovernanceAction::NoOp => VoteActionJson::TreasuryNoOp,
+                TreasuryGovernanceAction::TransferToRewards { value } => {
+                    VoteActionJson::TransferToRewards { value: value.0 }
+                }
+            },
+            VoteAction::Parameters { action } => match action {
+                ParametersGovernanceAction::NoOp => VoteActionJson::ParametersNoOp,
+                ParametersGovernanceAction::RewardAdd { value } => {
+                    VoteActionJson::RewardAdd { value: value.0 }
+                }
+            },
+        }
+    }
+}
+
+impl From<&VotePlan> for VotePlanJson {
+    fn from(vote_plan: &VotePlan) -> Self {
+        VotePlanJson {
+            id: vote_plan.to_id().to_string(),
+            vote_start: vote_plan.vote_start().into(),
+            vote_end: vote_plan.vote_end().into(),
+            committee_end: vote_plan.committee_end().into(),
+            payload_type: vote_plan.payload_type().into(),
+            proposals: vote_plan
+                .proposals()
+                .iter()
+                .map(|proposal| ProposalJson {
+                    external_id: proposal.external_id().to_string(),
+                    options: proposal.options().choice_range().end,
+                    action: proposal.action().into(),
+                })
+                .collect(),
+            committee_public_keys: member_public_keys(vote_plan.committee_public_keys()),
+            voting_token: vote_plan.voting_token().to_string(),
+        }
+    }
+}
+
+impl From<&Payload> for PayloadJson {
+    fn from(payload: &Payload) -> Self {
+        match payload {
+            Payload::Public { choice } => PayloadJson::Public {
+                choice: choice.as_byte(),
+            },
+            Payload::Private {
+                encrypted_vote,
+                proof,
+            } => PayloadJson::Private {
+                encrypted_vote: hex::encode(encrypted_vote.serialize()),
+                proof: hex::encode(proof.serialize()),
+            },
+        }
+    }
+}
+
+impl From<&VoteCast> for VoteCastJson {
+    fn from(vote_cast: &VoteCast) -> Self {
+        VoteCastJson {
+            vote_plan: vote_cast.vote_plan().to_string(),
+            proposal_index: vote_cast.proposal_index(),
+            payload: vote_cast.payload().into(),
+        }
+    }
+}
+
+impl From<&TallyResult> for TallyResultJson {
+    fn from(result: &TallyResult) -> Self {
+        TallyResultJson {
+            options: result.options().choice_range().end,
+            results: result.results().iter().map(|w| (*w).into()).collect(),
+        }
+    }
+}
+
+impl From<&Tally> for TallyJson {
+    fn from(tally: &Tally) -> Self {
+        match tally {
+            Tally::Public { result } => TallyJson::Public {
+                result: result.into(),
+            },
+            Tally::Private {
+                state:
+                    PrivateTallyState::Encrypted {
+                        encrypted_tally,
+                        total_stake,
+                    },
+            } => TallyJson::Encrypted {
+                encrypted_tally: hex::encode(encrypted_tally.to_bytes()),
+                total_stake: total_stake.0,
+            },
+            Tally::Private {
+                state: PrivateTallyState::Decrypted { result },
+            } => TallyJson::Decrypted {
+                result: result.into(),
+            },
+        }
+    }
+}
+
+fn member_public_keys(keys: &[MemberPublicKey]) -> Vec<String> {
+    keys.iter().map(|key| hex::encode(key.to_bytes())).collect()
+}
+
+impl CommitteeJson {
+    pub fn new(members: &[CommitteeId], member_public_keys: &[MemberPublicKey]) -> Self {
+        CommitteeJson {
+            members: members.iter().map(|id| id.to_hex()).collect(),
+            member_public_keys: self::member_public_keys(member_public_keys),
+        }
+    }
+}
+
+fn object(properties: JsonValue) -> JsonValue {
+    let required: Vec<_> = properties
+        .as_object()
+        .expect("properties of an object")
+        .keys()
+        .cloned()
+        .collect();
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+fn tagged(tag: &str, properties: JsonValue) -> JsonValue {
+    let mut properties = properties;
+    properties
+        .as_object_mut()
+        .expect("properties of an object")
+        .insert("type".to_owned(), json!({ "const": tag }));
+    object(properties)
+}
+
+fn hex_string() -> JsonValue {
+    json!({ "type": "string", "pattern": "^([0-9a-f]{2})*$" })
+}
+
+fn integer(max: u64) -> JsonValue {
+    json!({ "type": "integer", "minimum": 0, "maximum": max })
+}
+
+/// The JSON Schema (draft 2020-12) of the representation, every type being
+/// defined in `$defs` under the name of its Rust type
+pub fn schema() -> JsonValue {
+    let u8_max = u8::MAX as u64;
+    let u32_max = u32::MAX as u64;
+    let value = integer(u64::MAX);
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "$id": format!("chain-impl-mockchain/governance/v{}", SCHEMA_VERSION),
+        "$defs": {
+            "BlockDateJson": object(json!({
+                "epoch": integer(u32_max),
+                "slot_id": integer(u32_max),
+            })),
+            "PayloadTypeJson": { "enum": ["public", "private"] },
+            "VoteActionJson": { "oneOf": [
+                tagged("off_chain", json!({})),
+                tagged("treasury_no_op", json!({})),
+                tagged("transfer_to_rewards", json!({ "value": value })),
+                tagged("parameters_no_op", json!({})),
+                tagged("reward_add", json!({ "value": value })),
+            ]},
+            "ProposalJson": object(json!({
+                "external_id": hex_string(),
+                "options": integer(u8_max),
+                "action": { "$ref": "#/$defs/VoteActionJson" },
+            })),
+            "VotePlanJson": object(json!({
+                "id": hex_string(),
+                "vote_start": { "$ref": "#/$defs/BlockDateJson" },
+                "vote_end": { "$ref": "#/$defs/BlockDateJson" },
+                "committee_end": { "$ref": "#/$defs/BlockDateJson" },
+                "payload_type": { "$ref": "#/$defs/PayloadTypeJson" },
+                "proposals": { "type": "array", "items": { "$ref": "#/$defs/ProposalJson" } },
+                "committee_public_keys": { "type": "array", "items": hex_string() },
+                "voting_token": { "type": "string", "pattern": "^[0-9a-f]+(\\.[0-9a-f]+)?$" },
+            })),
+            "PayloadJson": { "oneOf": [
+                tagged("public", json!({ "choice": integer(u8_max) })),
+                tagged("private", json!({
+                    "encrypted_vote": hex_string(),
+                    "proof": hex_string(),
+                })),
+            ]},
+            "VoteCastJson": object(json!({
+                "vote_plan": hex_string(),
+                "proposal_index": integer(u8_max),
+                "payload": { "$ref": "#/$defs/PayloadJson" },
+            })),
+            "TallyResultJson": object(json!({
+                "options": integer(u8_max),
+                "results": { "type": "array", "items": value },
+            })),
+            "TallyJson": { "oneOf": [
+                tagged("public", json!({ "result": { "$ref": "#/$defs/TallyResultJson" } })),
+                tagged("encrypted", json!({
+                    "encrypted_tally": hex_string(),
+                    "total_stake": value,
+                })),
+                tagged("decrypted", json!({ "result": { "$ref": "#/$defs/TallyResultJson" } })),
+            ]},
+            "CommitteeJson": object(json!({
+                "members": { "type": "array", "items": hex_string() },
+                "member_public_keys": { "type": "array", "items": hex_string() },
+            })),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vote::{Choice, Options};
+    use quickcheck_macros::quickcheck;
+    use std::collections::BTreeSet;
+
+    /// the fields of the serialized value are the ones the schema requires
+    fn matches_schema<T: Serialize>(definition: &str, value: &T) -> bool {
+        let value = serde_json::to_value(value).unwrap();
+        let schema = schema();
+        let definition = &schema["$defs"][definition];
+        let definitions = match definition["oneOf"].as_array() {
+            Some(definitions) => definitions.clone(),
+            None => vec![definition.clone()],
+        };
+        let fields: BTreeSet<_> = value.as_object().unwrap().keys().cloned().collect();
+        definitions.iter().any(|definition| {
+            let required: BTreeSet<_> = definition["required"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|field| field.as_str().unwrap().to_owned())
+                .collect();
+            required == fields
+        })
+    }
+
+    #[quickcheck]
+    fn vote_plan_json_matches_the_schema(vote_plan: VotePlan) -> bool {
+        let json = VotePlanJson::from(&vote_plan);
+        let encoded = serde_json::to_string(&json).unwrap();
+        matches_schema("VotePlanJson", &json)
+            && json
+                .proposals
+                .iter()
+                .all(|proposal| matches_schema("VoteActionJson", &proposal.action))
+            && serde_json::from_str::<VotePlanJson>(&encoded).unwrap() == json
+    }
+
+    #[quickcheck]
+    fn vote_cast_json_matches_the_schema(vote_cast: VoteCast) -> bool {
+        let json = VoteCastJson::from(&vote_cast);
+        let encoded = serde_json::to_string(&json).unwrap();
+        matches_schema("VoteCastJson", &json)
+            && matches_schema("PayloadJson", &json.payload)
+            && serde_json::from_str::<VoteCastJson>(&encoded).unwrap() == json
+    }
+
+    #[test]
+    fn tally_json_is_canonical() {
+        let mut result = TallyResult::new(Options::new_length(3).unwrap());
+        result.add_vote(Choice::new(1), 42).unwrap();
+        let tally = TallyJson::from(&Tally::new_public(result));
+        assert!(matches_schema("TallyJson", &tally));
+        assert_eq!(
+            serde_json::to_value(&tally).unwrap(),
+            json!({
+                "type": "public",
+                "result": { "options": 3, "results": [0, 42, 0] },
+            })
+        );
+
+        let committee = CommitteeJson::new(&[CommitteeId::from([1; 32])], &[]);
+        assert!(matches_schema("CommitteeJson", &committee));
+        assert_eq!(
+            serde_json::to_value(&committee).unwrap(),
+            json!({
+                "members": ["01".repeat(32)],
+                "member_public_keys": [],
+            })
+        );
+    }
+}
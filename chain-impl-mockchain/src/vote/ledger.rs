@@ -2,10 +2,13 @@ use crate::certificate::EncryptedVoteTally;
 use crate::ledger::token_distribution::TokenDistribution;
 use crate::{
     account,
-    certificate::{TallyProof, VoteAction, VoteCast, VotePlan, VotePlanId, VoteTally},
+    certificate::{
+        SnapshotVoteCast, TallyProof, VoteAction, VoteCast, VotePlan, VotePlanCancel, VotePlanId,
+        VoteTally, VotingPowerSnapshot,
+    },
     date::BlockDate,
     ledger::governance::Governance,
-    vote::{CommitteeId, PayloadType, VoteError, VotePlanManager},
+    vote::{CommitteeId, PayloadType, TallyError, VoteError, VotePlanManager, VotePlanStatus},
 };
 use imhamt::{Hamt, InsertError, UpdateError};
 use std::collections::{hash_map::DefaultHasher, HashSet};
@@ -69,12 +72,99 @@ impl VotePlanLedger {
         block_date: BlockDate,
         identifier: account::Identifier,
         vote: VoteCast,
+        token_distribution: TokenDistribution<()>,
     ) -> Result<Self, VotePlanLedgerError> {
         let id = vote.vote_plan().clone();
 
+        let r = self.plans.update(&id, move |v| {
+            v.vote(block_date, identifier, vote, token_distribution)
+                .map(Some)
+        });
+
+        match r {
+            Err(reason) => Err(VotePlanLedgerError::VoteError { reason, id }),
+            Ok(plans) => Ok(Self { plans }),
+        }
+    }
+
+    /// attempt to apply the vote, weighted with the voting power proven
+    /// against the snapshot of the vote plan
+    ///
+    /// # errors
+    ///
+    /// can fail for the same reasons as `apply_vote` and if the vote plan
+    /// has no voting power snapshot or the proof is invalid
+    ///
+    pub fn apply_snapshot_vote(
+        &self,
+        block_date: BlockDate,
+        identifier: account::Identifier,
+        vote: SnapshotVoteCast,
+    ) -> Result<Self, VotePlanLedgerError> {
+        let id = vote.cast().vote_plan().clone();
+
+        let r = self.plans.update(&id, move |v| {
+            v.vote_with_power_proof(block_date, identifier, vote)
+                .map(Some)
+        });
+
+        match r {
+            Err(reason) => Err(VotePlanLedgerError::VoteError { reason, id }),
+            Ok(plans) => Ok(Self { plans }),
+        }
+    }
+
+    /// register the voting power snapshot of the associated vote plan
+    ///
+    /// # errors
+    ///
+    /// can fail if the vote plan id does not exist, if the committee member
+    /// is not one of the vote plan, if the vote has already started or if a
+    /// snapshot is already registered
+    ///
+    pub fn apply_voting_power_snapshot(
+        &self,
+        block_date: BlockDate,
+        snapshot: &VotingPowerSnapshot,
+        committee_id: CommitteeId,
+    ) -> Result<Self, VotePlanLedgerError> {
+        let id = snapshot.vote_plan().clone();
+
+        let r = self.plans.update(&id, move |v| {
+            v.register_voting_power_snapshot(block_date, snapshot.clone(), committee_id)
+                .map(Some)
+        });
+
+        match r {
+            Err(reason) => Err(VotePlanLedgerError::VoteError { reason, id }),
+            Ok(plans) => Ok(Self { plans }),
+        }
+    }
+
+    /// cancel the associated vote plan, the vote plan stays in the ledger
+    /// until the end of its committee time but accepts no more votes nor
+    /// tally
+    ///
+    /// # errors
+    ///
+    /// can fail if the vote plan id does not exist, if the signers are not
+    /// distinct members of the committee or not enough of them, if the
+    /// committee time has started or if the vote plan is already cancelled
+    ///
+    pub fn apply_vote_plan_cancel<I>(
+        &self,
+        block_date: BlockDate,
+        cancel: &VotePlanCancel,
+        signers: I,
+    ) -> Result<Self, VotePlanLedgerError>
+    where
+        I: IntoIterator<Item = CommitteeId>,
+    {
+        let id = cancel.vote_plan().clone();
+
         let r = self
             .plans
-            .update(&id, move |v| v.vote(block_date, identifier, vote).map(Some));
+            .update(&id, move |v| v.cancel(block_date, signers).map(Some));
 
         match r {
             Err(reason) => Err(VotePlanLedgerError::VoteError { reason, id }),
@@ -170,6 +260,93 @@ impl VotePlanLedger {
         }
     }
 
+    /// apply the private tally of the associated vote plan in a single step,
+    /// the encrypted tally is computed if it has not been started by an
+    /// `EncryptedVoteTally` fragment, then decrypted with the shares of the
+    /// tally
+    ///
+    /// # Errors
+    ///
+    /// This function may fail:
+    ///
+    /// * if the Committee time has elapsed
+    /// * if the tally is not a private tally
+    /// * if the shares do not decrypt the encrypted tally
+    ///
+    pub fn apply_private_tally<F>(
+        &self,
+        block_date: BlockDate,
+        token_distribution: TokenDistribution<()>,
+        governance: &Governance,
+        tally: &VoteTally,
+        committee_id: CommitteeId,
+        f: F,
+    ) -> Result<Self, VotePlanLedgerError>
+    where
+        F: FnMut(&VoteAction),
+    {
+        let id = tally.id().clone();
+        let decrypted_tally = match tally.tally_decrypted() {
+            Some(decrypted_tally) => decrypted_tally,
+            None => {
+                return Err(VotePlanLedgerError::VoteError {
+                    reason: TallyError::InvalidPrivacy.into(),
+                    id,
+                })
+            }
+        };
+
+        let r = self.plans.update(&id, move |v| {
+            v.private_tally(
+                token_distribution,
+                block_date,
+                governance,
+                decrypted_tally,
+                committee_id,
+                f,
+            )
+            .map(Some)
+        });
+
+        match r {
+            Err(reason) => Err(VotePlanLedgerError::VoteError { reason, id }),
+            Ok(plans) => Ok(Self { plans }),
+        }
+    }
+
+    /// the status of the vote plan with the encrypted tally a private tally
+    /// started at the given date would compute, for the committee members to
+    /// compute their decryption shares. The ledger is left untouched.
+    ///
+    /// # Errors
+    ///
+    /// This function may fail:
+    ///
+    /// * if the vote plan does not exist
+    /// * if the date is not within the committee time of the vote plan
+    /// * if the vote plan is not private
+    ///
+    pub fn preview_private_tally(
+        &self,
+        block_date: BlockDate,
+        token_distribution: TokenDistribution<()>,
+        id: &VotePlanId,
+    ) -> Result<VotePlanStatus, VotePlanLedgerError> {
+        let plan = self
+            .plans
+            .lookup(id)
+            .ok_or_else(|| VotePlanLedgerError::VoteError {
+                id: id.clone(),
+                reason: UpdateError::KeyNotFound,
+            })?;
+        plan.preview_private_tally(token_distribution, block_date)
+            .map(|plan| plan.statuses())
+            .map_err(|reason| VotePlanLedgerError::VoteError {
+                id: id.clone(),
+                reason: UpdateError::ValueCallbackError(reason),
+            })
+    }
+
     /// apply the committee result for the associated vote plan
     ///
     /// # Errors
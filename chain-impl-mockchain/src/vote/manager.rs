@@ -1,6 +1,9 @@
 use crate::{
     account,
-    certificate::{DecryptedPrivateTally, Proposal, VoteAction, VoteCast, VotePlan, VotePlanId},
+    certificate::{
+        DecryptedPrivateTally, Phase, Proposal, SnapshotVoteCast, VoteAction, VoteCast, VotePlan,
+        VotePlanCancelProof, VotePlanId, VotingPowerSnapshot,
+    },
     date::BlockDate,
     ledger::{
         governance::{Governance, GovernanceAcceptanceCriteria},
@@ -9,7 +12,11 @@ use crate::{
     rewards::Ratio,
     stake::Stake,
     tokens::identifier::TokenIdentifier,
-    vote::{self, CommitteeId, Options, Tally, TallyResult, VotePlanStatus, VoteProposalStatus},
+    value::Value,
+    vote::{
+        self, CommitteeId, Options, ProposalParticipation, Tally, TallyResult, VotePlanStatus,
+        VoteProposalStatus,
+    },
 };
 use crate::{
     certificate::DecryptedPrivateTallyProposal,
@@ -33,6 +40,50 @@ pub struct VotePlanManager {
     plan: Arc<VotePlan>,
     committee: Arc<HashSet<CommitteeId>>,
     proposal_managers: ProposalManagers,
+    /// total amount of the voting token, as of the last vote or tally
+    eligible_power: Value,
+    /// number of distinct accounts which voted for at least one proposal
+    unique_voters: u64,
+    /// voting power snapshot registered by the committee, when set the votes
+    /// are weighted with the voting power proven against it
+    snapshot: Option<Arc<VotingPowerSnapshot>>,
+    /// voting power of the voters, as proven against the snapshot
+    snapshot_powers: Hamt<DefaultHasher, account::Identifier, Value>,
+    /// date of the cancellation of the vote plan by the committee, no
+    /// vote nor tally is accepted once set
+    cancelled: Option<BlockDate>,
+}
+
+/// Voting power of the accounts used to weight the votes in the tally
+pub trait VotingPowers {
+    fn get_account(&self, account: &account::Identifier) -> Option<Value>;
+
+    fn get_total(&self) -> Value;
+}
+
+impl VotingPowers for TokenDistribution<TokenIdentifier> {
+    fn get_account(&self, account: &account::Identifier) -> Option<Value> {
+        TokenDistribution::get_account(self, account)
+    }
+
+    fn get_total(&self) -> Value {
+        TokenDistribution::get_total(self)
+    }
+}
+
+struct SnapshotVotingPowers<'a> {
+    powers: &'a Hamt<DefaultHasher, account::Identifier, Value>,
+    total: Value,
+}
+
+impl<'a> VotingPowers for SnapshotVotingPowers<'a> {
+    fn get_account(&self, account: &account::Identifier) -> Option<Value> {
+        self.powers.lookup(account).copied()
+    }
+
+    fn get_total(&self) -> Value {
+        self.total
+    }
 }
 
 #[derive(Clone, Eq, PartialEq, Debug)]
@@ -41,6 +92,32 @@ pub enum ValidatedPayload {
     Private(Ballot),
 }
 
+/// The private ballot of a voter with its voting power, `None` when the voter
+/// has no voting power
+fn private_ballot_with_stake<'a, P: VotingPowers>(
+    token_distribution: &P,
+    (account_id, payload): (&account::Identifier, &'a ValidatedPayload),
+) -> Option<Result<(&'a Ballot, u64), VoteError>> {
+    let stake = token_distribution.get_account(account_id)?;
+    match payload {
+        ValidatedPayload::Public(_) => Some(Err(VoteError::InvalidPayloadType {
+            expected: PayloadType::Private,
+            received: PayloadType::Public,
+        })),
+        ValidatedPayload::Private(ballot) => Some(Ok((ballot, stake.0))),
+    }
+}
+
+fn add_private_ballot(
+    mut tally: EncryptedTally,
+    vote_with_stake: Result<(&Ballot, u64), VoteError>,
+) -> Result<EncryptedTally, VoteError> {
+    vote_with_stake.map(|(ballot, stake)| {
+        tally.add(ballot, stake);
+        tally
+    })
+}
+
 #[derive(Clone, Eq, PartialEq, Debug)]
 struct ValidatedVoteCast {
     payload: ValidatedPayload,
@@ -65,6 +142,7 @@ struct ProposalManager {
     options: Options,
     tally: Option<Tally>,
     action: VoteAction,
+    participation: ProposalParticipation,
 }
 
 #[derive(Debug, Error, Clone, PartialEq, Eq)]
@@ -117,6 +195,38 @@ pub enum VoteError {
 
     #[error("Error during private tallying {0}")]
     PrivateTallyError(String),
+
+    #[error("The vote plan has a voting power snapshot, the vote must prove the voting power of the voter")]
+    VotingPowerProofRequired,
+
+    #[error("The vote plan has no voting power snapshot")]
+    NoVotingPowerSnapshot,
+
+    #[error("Invalid proof of the voting power against the snapshot")]
+    InvalidVotingPowerProof,
+
+    #[error(
+        "Unexpected committee ID for the voting power snapshot, expected one of the committee"
+    )]
+    InvalidSnapshotCommittee,
+
+    #[error("A voting power snapshot is already registered for the vote plan")]
+    VotingPowerSnapshotAlreadyRegistered,
+
+    #[error("The voting power snapshot must be registered before the vote starts ({start})")]
+    VotingPowerSnapshotTooLate { start: BlockDate },
+
+    #[error("The vote plan has been cancelled at {date}")]
+    VotePlanCancelled { date: BlockDate },
+
+    #[error("The vote plan can only be cancelled before the tally starts ({committee_start})")]
+    VotePlanCancelTooLate { committee_start: BlockDate },
+
+    #[error("Unexpected committee ID in the vote plan cancellation, expected distinct members of the committee")]
+    InvalidCancelCommittee,
+
+    #[error("The vote plan cancellation is signed by {signers} committee members, {threshold} are required")]
+    VotePlanCancelThresholdNotMet { signers: usize, threshold: usize },
 }
 
 impl ProposalManager {
@@ -133,6 +243,7 @@ impl ProposalManager {
             options: proposal.options().clone(),
             tally: None,
             action: proposal.action().clone(),
+            participation: ProposalParticipation::default(),
         }
     }
 
@@ -141,11 +252,15 @@ impl ProposalManager {
     /// if there is already a vote present for this proposal it will
     /// simply replace the previously set one
     ///
+    /// `voting_power` is the voting power of the voter at the time of the
+    /// vote, it is only used to keep track of the participation.
+    ///
     #[must_use = "Add the vote in a new ProposalManager, does not modify self"]
     pub fn vote(
         &self,
         identifier: account::Identifier,
         payload: ValidatedPayload,
+        voting_power: Value,
     ) -> Result<Self, VoteError> {
         // Part of DDoS protection: do not record a new ballot if the account already voted for this
         // proposal. This protects the system from flooding in a system with cheap/free voting
@@ -159,6 +274,7 @@ impl ProposalManager {
             tally: self.tally.clone(),
             options: self.options.clone(),
             action: self.action.clone(),
+            participation: self.participation.add_voter(voting_power),
         })
     }
 
@@ -228,13 +344,14 @@ impl ProposalManager {
     }
 
     #[must_use = "Compute the PublicTally in a new ProposalManager, does not modify self"]
-    pub fn public_tally<F>(
+    pub fn public_tally<P, F>(
         &self,
-        token_distribution: &TokenDistribution<TokenIdentifier>,
+        token_distribution: &P,
         governance: &Governance,
         mut f: F,
     ) -> Result<Self, VoteError>
     where
+        P: VotingPowers,
         F: FnMut(&VoteAction),
     {
         let mut results = TallyResult::new(self.options.clone());
@@ -264,50 +381,44 @@ impl ProposalManager {
             options: self.options.clone(),
             tally: Some(Tally::new_public(results)),
             action: self.action.clone(),
+            participation: self.participation,
         })
     }
 
     #[must_use = "Compute the PrivateTally in a new ProposalManager, does not modify self"]
-    pub fn private_tally(
+    pub fn private_tally<P: VotingPowers + Sync>(
         &self,
-        token_distribution: &TokenDistribution<TokenIdentifier>,
+        token_distribution: &P,
         election_pk: &ElectionPublicKey,
         crs: &Crs,
     ) -> Result<Self, VoteError> {
-        use rayon::prelude::*;
-
         let tally_size = self.options.choice_range().clone().max().unwrap() as usize + 1;
 
+        #[cfg(feature = "rayon")]
+        let tally = {
+            use rayon::prelude::*;
+
+            self.votes_by_voters
+                .iter()
+                .par_bridge()
+                .filter_map(|vote| private_ballot_with_stake(token_distribution, vote))
+                .try_fold_with(
+                    EncryptedTally::new(tally_size, election_pk.clone(), crs.clone()),
+                    add_private_ballot,
+                )
+                .try_reduce(
+                    || EncryptedTally::new(tally_size, election_pk.clone(), crs.clone()),
+                    |a, b| Ok(a + b),
+                )?
+        };
+        #[cfg(not(feature = "rayon"))]
         let tally = self
             .votes_by_voters
             .iter()
-            .par_bridge()
-            .filter_map(|(account_id, payload)| {
-                if let Some(stake) = token_distribution.get_account(account_id) {
-                    match payload {
-                        ValidatedPayload::Public(_) => {
-                            return Some(Err(VoteError::InvalidPayloadType {
-                                expected: PayloadType::Private,
-                                received: PayloadType::Public,
-                            }))
-                        }
-                        ValidatedPayload::Private(ballot) => return Some(Ok((ballot, stake.0))),
-                    }
-                }
-                None
-            })
-            .try_fold_with(
+            .filter_map(|vote| private_ballot_with_stake(token_distribution, vote))
+            .try_fold(
                 EncryptedTally::new(tally_size, election_pk.clone(), crs.clone()),
-                |mut tally, vote_with_stake| {
-                    vote_with_stake.map(|(ballot, stake)| {
-                        tally.add(ballot, stake);
-                        tally
-                    })
-                },
-            )
-            .try_reduce(
-                || EncryptedTally::new(tally_size, election_pk.clone(), crs.clone()),
-                |a, b| Ok(a + b),
+                add_private_ballot,
             )?;
 
         Ok(Self {
@@ -315,6 +426,7 @@ impl ProposalManager {
             options: self.options.clone(),
             tally: Some(Tally::new_private(tally, token_distribution.get_total())),
             action: self.action.clone(),
+            participation: self.participation,
         })
     }
 
@@ -358,6 +470,7 @@ impl ProposalManager {
             options: self.options.clone(),
             tally: Some(tally),
             action: self.action.clone(),
+            participation: self.participation,
         })
     }
 
@@ -483,10 +596,11 @@ impl ProposalManagers {
         &self,
         identifier: account::Identifier,
         vote_cast: ValidatedVoteCast,
+        voting_power: Value,
     ) -> Result<Self, VoteError> {
         let proposal_index = vote_cast.proposal_index;
         if let Some(manager) = self.managers().get(proposal_index) {
-            let updated_manager = manager.vote(identifier, vote_cast.payload)?;
+            let updated_manager = manager.vote(identifier, vote_cast.payload, voting_power)?;
             // only clone the array if it does make sens to do so:
             //
             // * the index exist
@@ -501,13 +615,22 @@ impl ProposalManagers {
         }
     }
 
-    pub fn public_tally<F>(
+    /// true if the encrypted tally of the private proposals has been computed
+    fn private_tally_started(&self) -> bool {
+        match self {
+            Self::Public { .. } => false,
+            Self::Private { managers, .. } => managers.iter().any(|m| m.tally.is_some()),
+        }
+    }
+
+    pub fn public_tally<P, F>(
         &self,
-        token_distribution: &TokenDistribution<TokenIdentifier>,
+        token_distribution: &P,
         governance: &Governance,
         mut f: F,
     ) -> Result<Self, VoteError>
     where
+        P: VotingPowers,
         F: FnMut(&VoteAction),
     {
         match self {
@@ -531,6 +654,12 @@ impl ProposalManagers {
         }
     }
 
+    fn has_voted(&self, identifier: &account::Identifier) -> bool {
+        self.managers()
+            .iter()
+            .any(|manager| manager.votes_by_voters.contains_key(identifier))
+    }
+
     /// validate the vote against the proposal: verify that the proposal exists
     /// and the the length of the ciphertext is correct (if applicable)
     pub fn validate_vote(
@@ -566,22 +695,27 @@ impl ProposalManagers {
         })
     }
 
-    pub fn start_private_tally(
+    pub fn start_private_tally<P: VotingPowers + Sync>(
         &self,
-        token_distribution: &TokenDistribution<TokenIdentifier>,
+        token_distribution: &P,
     ) -> Result<Self, VoteError> {
-        use rayon::prelude::*;
-
         match self {
             Self::Private {
                 managers,
                 crs,
                 election_pk,
             } => {
-                let proposals = managers
-                    .par_iter()
-                    .map(|proposal| proposal.private_tally(token_distribution, election_pk, crs))
-                    .collect::<Result<_, _>>()?;
+                let tally = |proposal: &ProposalManager| {
+                    proposal.private_tally(token_distribution, election_pk, crs)
+                };
+                #[cfg(feature = "rayon")]
+                let proposals = {
+                    use rayon::prelude::*;
+
+                    managers.par_iter().map(tally).collect::<Result<_, _>>()?
+                };
+                #[cfg(not(feature = "rayon"))]
+                let proposals = managers.iter().map(tally).collect::<Result<_, _>>()?;
                 Ok(Self::Private {
                     managers: proposals,
                     crs: crs.clone(),
@@ -646,6 +780,11 @@ impl VotePlanManager {
             plan: Arc::new(plan),
             proposal_managers,
             committee: Arc::new(committee),
+            eligible_power: Value::zero(),
+            unique_voters: 0,
+            snapshot: None,
+            snapshot_powers: Hamt::new(),
+            cancelled: None,
         }
     }
 
@@ -670,6 +809,8 @@ impl VotePlanManager {
                 options: proposal.options().clone(),
                 tally: manager.tally.clone(),
                 votes: manager.votes_by_voters.clone(),
+                participation: manager.participation,
+                cancelled: self.cancelled.is_some(),
             })
             .collect();
 
@@ -684,15 +825,28 @@ impl VotePlanManager {
             committee_public_keys,
             proposals,
             voting_token: self.plan().voting_token().clone(),
+            eligible_power: self.eligible_power,
+            unique_voters: self.unique_voters,
+            voting_power_snapshot: self.snapshot.as_ref().map(|snapshot| *snapshot.root()),
+            cancelled: self.cancelled,
         }
     }
 
+    /// total number of votes recorded, over all the proposals of the plan
+    pub fn nb_votes(&self) -> usize {
+        self.proposal_managers
+            .managers()
+            .iter()
+            .map(|manager| manager.votes_by_voters.size())
+            .sum()
+    }
+
     pub fn can_vote(&self, date: BlockDate) -> bool {
-        self.plan().can_vote(date)
+        self.plan().can_vote_at(date)
     }
 
     pub fn can_committee(&self, date: BlockDate) -> bool {
-        self.plan().committee_time(date)
+        self.plan().can_tally_at(date)
     }
 
     pub fn committee_set(&self) -> &HashSet<CommitteeId> {
@@ -707,7 +861,74 @@ impl VotePlanManager {
     }
 
     fn valid_committee(&self, id: &CommitteeId) -> bool {
-        self.committee_set().contains(id)
+        id.is_member_of(self.committee_set())
+    }
+
+    /// date of the cancellation of the vote plan, if it has been cancelled
+    pub fn cancelled(&self) -> Option<BlockDate> {
+        self.cancelled
+    }
+
+    fn check_not_cancelled(&self) -> Result<(), VoteError> {
+        match self.cancelled {
+            Some(date) => Err(VoteError::VotePlanCancelled { date }),
+            None => Ok(()),
+        }
+    }
+
+    fn validate_vote(&self, block_date: BlockDate, cast: VoteCast) -> Result<VoteCast, VoteError> {
+        self.check_not_cancelled()?;
+
+        if cast.vote_plan() != self.id() {
+            return Err(VoteError::InvalidVotePlan {
+                expected: self.id().clone(),
+                vote: cast,
+            });
+        }
+
+        if !self.can_vote(block_date) {
+            return Err(VoteError::NotVoteTime {
+                start: self.plan().vote_start(),
+                end: self.plan().vote_end(),
+                vote: cast,
+            });
+        }
+        if self.plan().payload_type() != cast.payload().payload_type() {
+            return Err(VoteError::InvalidPayloadType {
+                expected: self.plan().payload_type(),
+                received: cast.payload().payload_type(),
+            });
+        }
+
+        Ok(cast)
+    }
+
+    fn record_vote(
+        &self,
+        identifier: account::Identifier,
+        cast: VoteCast,
+        voting_power: Value,
+        eligible_power: Value,
+        snapshot_powers: Hamt<DefaultHasher, account::Identifier, Value>,
+    ) -> Result<Self, VoteError> {
+        let vote = self.proposal_managers.validate_vote(&identifier, cast)?;
+        let new_voter = !self.proposal_managers.has_voted(&identifier);
+
+        let proposal_managers = self
+            .proposal_managers
+            .vote(identifier, vote, voting_power)?;
+
+        Ok(Self {
+            proposal_managers,
+            plan: Arc::clone(&self.plan),
+            id: self.id.clone(),
+            committee: Arc::clone(&self.committee),
+            eligible_power,
+            unique_voters: self.unique_voters + u64::from(new_voter),
+            snapshot: self.snapshot.clone(),
+            snapshot_powers,
+            cancelled: self.cancelled,
+        })
     }
 
     /// attempt to apply the vote to one of the proposals
@@ -722,43 +943,177 @@ impl VotePlanManager {
     /// * if the block_date show it is no longer valid to cast a vote for any
     ///   of the managed proposals
     /// * if the payload type of the vote is not the expected one
+    /// * if the vote plan has a voting power snapshot, the vote must then
+    ///   be cast with [`vote_with_power_proof`](Self::vote_with_power_proof)
     ///
     pub fn vote(
         &self,
         block_date: BlockDate,
         identifier: account::Identifier,
         cast: VoteCast,
+        token_distribution: TokenDistribution<()>,
     ) -> Result<Self, VoteError> {
-        if cast.vote_plan() != self.id() {
-            return Err(VoteError::InvalidVotePlan {
-                expected: self.id().clone(),
-                vote: cast,
-            });
+        let cast = self.validate_vote(block_date, cast)?;
+
+        if self.snapshot.is_some() {
+            return Err(VoteError::VotingPowerProofRequired);
         }
 
-        if !self.can_vote(block_date) {
-            return Err(VoteError::NotVoteTime {
+        let token_distribution = token_distribution.token(self.plan.voting_token().clone());
+        let voting_power = token_distribution
+            .get_account(&identifier)
+            .unwrap_or_else(Value::zero);
+
+        self.record_vote(
+            identifier,
+            cast,
+            voting_power,
+            token_distribution.get_total(),
+            self.snapshot_powers.clone(),
+        )
+    }
+
+    /// attempt to apply a vote weighted with the voting power proven
+    /// against the voting power snapshot of the vote plan
+    ///
+    /// # errors
+    ///
+    /// * the same errors as [`vote`](Self::vote)
+    /// * if the vote plan has no voting power snapshot
+    /// * if the proof does not match the root of the snapshot
+    ///
+    pub fn vote_with_power_proof(
+        &self,
+        block_date: BlockDate,
+        identifier: account::Identifier,
+        cast: SnapshotVoteCast,
+    ) -> Result<Self, VoteError> {
+        let (cast, voting_power, proof) = cast.into_parts();
+        let cast = self.validate_vote(block_date, cast)?;
+
+        let snapshot = self
+            .snapshot
+            .as_ref()
+            .ok_or(VoteError::NoVotingPowerSnapshot)?;
+
+        if !proof.verify(snapshot.root(), &identifier, voting_power) {
+            return Err(VoteError::InvalidVotingPowerProof);
+        }
+
+        let snapshot_powers =
+            self.snapshot_powers
+                .insert_or_update_simple(identifier.clone(), voting_power, |_| Some(voting_power));
+
+        self.record_vote(
+            identifier,
+            cast,
+            voting_power,
+            snapshot.total_power(),
+            snapshot_powers,
+        )
+    }
+
+    /// register the voting power snapshot of the vote plan, the votes are
+    /// then weighted with the voting power proven against it instead of the
+    /// stake of the voters
+    ///
+    /// # errors
+    ///
+    /// * if the committee member is not one of the vote plan
+    /// * if the vote has already started
+    /// * if a snapshot is already registered for the vote plan
+    ///
+    pub fn register_voting_power_snapshot(
+        &self,
+        block_date: BlockDate,
+        snapshot: VotingPowerSnapshot,
+        sig: CommitteeId,
+    ) -> Result<Self, VoteError> {
+        if !self.valid_committee(&sig) {
+            return Err(VoteError::InvalidSnapshotCommittee);
+        }
+
+        self.check_not_cancelled()?;
+
+        if self.plan().phase_at(block_date) != Phase::Before {
+            return Err(VoteError::VotingPowerSnapshotTooLate {
                 start: self.plan().vote_start(),
-                end: self.plan().vote_end(),
-                vote: cast,
             });
         }
-        if self.plan().payload_type() != cast.payload().payload_type() {
-            return Err(VoteError::InvalidPayloadType {
-                expected: self.plan().payload_type(),
-                received: cast.payload().payload_type(),
+
+        if self.snapshot.is_some() {
+            return Err(VoteError::VotingPowerSnapshotAlreadyRegistered);
+        }
+
+        Ok(Self {
+            proposal_managers: self.proposal_managers.clone(),
+            plan: Arc::clone(&self.plan),
+            id: self.id.clone(),
+            committee: Arc::clone(&self.committee),
+            eligible_power: snapshot.total_power(),
+            unique_voters: self.unique_voters,
+            snapshot: Some(Arc::new(snapshot)),
+            snapshot_powers: self.snapshot_powers.clone(),
+            cancelled: self.cancelled,
+        })
+    }
+
+    /// cancel the vote plan with the signatures of a majority of the
+    /// committee, see
+    /// [`VotePlanCancelProof::threshold`](crate::certificate::VotePlanCancelProof::threshold).
+    /// The votes already cast are kept but never tallied.
+    ///
+    /// # errors
+    ///
+    /// * if a signer is not a member of the committee, or signs twice
+    /// * if there are fewer signers than the threshold
+    /// * if the committee time of the vote plan has started
+    /// * if the vote plan is already cancelled
+    ///
+    pub fn cancel<I>(&self, block_date: BlockDate, signers: I) -> Result<Self, VoteError>
+    where
+        I: IntoIterator<Item = CommitteeId>,
+    {
+        let mut distinct = HashSet::new();
+        for signer in signers {
+            if !self.valid_committee(&signer) || !distinct.insert(signer) {
+                return Err(VoteError::InvalidCancelCommittee);
+            }
+        }
+
+        let threshold = VotePlanCancelProof::threshold(self.committee_set().len());
+        if distinct.len() < threshold {
+            return Err(VoteError::VotePlanCancelThresholdNotMet {
+                signers: distinct.len(),
+                threshold,
             });
         }
 
-        let vote = self.proposal_managers.validate_vote(&identifier, cast)?;
+        if block_date >= self.plan().committee_start() {
+            return Err(VoteError::VotePlanCancelTooLate {
+                committee_start: self.plan().committee_start(),
+            });
+        }
 
-        let proposal_managers = self.proposal_managers.vote(identifier, vote)?;
+        self.check_not_cancelled()?;
 
         Ok(Self {
-            proposal_managers,
+            proposal_managers: self.proposal_managers.clone(),
             plan: Arc::clone(&self.plan),
             id: self.id.clone(),
             committee: Arc::clone(&self.committee),
+            eligible_power: self.eligible_power,
+            unique_voters: self.unique_voters,
+            snapshot: self.snapshot.clone(),
+            snapshot_powers: self.snapshot_powers.clone(),
+            cancelled: Some(block_date),
+        })
+    }
+
+    fn snapshot_voting_powers(&self) -> Option<SnapshotVotingPowers<'_>> {
+        self.snapshot.as_ref().map(|snapshot| SnapshotVotingPowers {
+            powers: &self.snapshot_powers,
+            total: snapshot.total_power(),
         })
     }
 
@@ -773,6 +1128,8 @@ impl VotePlanManager {
     where
         F: FnMut(&VoteAction),
     {
+        self.check_not_cancelled()?;
+
         if !self.can_committee(block_date) {
             return Err(VoteError::NotCommitteeTime {
                 start: self.plan().committee_start(),
@@ -788,17 +1145,32 @@ impl VotePlanManager {
             return Err(TallyError::InvalidPrivacy.into());
         }
 
-        let token_distribution = token_distribution.token(self.plan.voting_token().clone());
-
-        let proposal_managers =
-            self.proposal_managers
-                .public_tally(&token_distribution, governance, f)?;
+        let (proposal_managers, eligible_power) = match self.snapshot_voting_powers() {
+            Some(powers) => (
+                self.proposal_managers
+                    .public_tally(&powers, governance, f)?,
+                powers.get_total(),
+            ),
+            None => {
+                let token_distribution = token_distribution.token(self.plan.voting_token().clone());
+                (
+                    self.proposal_managers
+                        .public_tally(&token_distribution, governance, f)?,
+                    token_distribution.get_total(),
+                )
+            }
+        };
 
         Ok(Self {
             proposal_managers,
             plan: Arc::clone(&self.plan),
             id: self.id.clone(),
             committee: Arc::clone(&self.committee),
+            eligible_power,
+            unique_voters: self.unique_voters,
+            snapshot: self.snapshot.clone(),
+            snapshot_powers: self.snapshot_powers.clone(),
+            cancelled: self.cancelled,
         })
     }
 
@@ -808,6 +1180,8 @@ impl VotePlanManager {
         block_date: BlockDate,
         sig: CommitteeId,
     ) -> Result<Self, VoteError> {
+        self.check_not_cancelled()?;
+
         if !self.can_committee(block_date) {
             return Err(VoteError::NotCommitteeTime {
                 start: self.plan().committee_start(),
@@ -819,24 +1193,100 @@ impl VotePlanManager {
             return Err(VoteError::InvalidTallyCommittee);
         }
 
+        self.encrypted_tally(token_distribution)
+    }
+
+    /// compute the encrypted tally the committee members decrypt their
+    /// shares of, as a private tally started at the given date would
+    ///
+    /// # errors
+    ///
+    /// * if the date is not within the committee time of the vote plan
+    /// * if the vote plan is not private
+    ///
+    pub fn preview_private_tally(
+        &self,
+        token_distribution: TokenDistribution<()>,
+        block_date: BlockDate,
+    ) -> Result<Self, VoteError> {
+        self.check_not_cancelled()?;
+
+        if !self.can_committee(block_date) {
+            return Err(VoteError::NotCommitteeTime {
+                start: self.plan().committee_start(),
+                end: self.plan().committee_end(),
+            });
+        }
+
+        self.encrypted_tally(token_distribution)
+    }
+
+    fn encrypted_tally(
+        &self,
+        token_distribution: TokenDistribution<()>,
+    ) -> Result<Self, VoteError> {
         if self.plan.payload_type() != PayloadType::Private {
             return Err(TallyError::InvalidPrivacy.into());
         }
 
-        let token_distribution = token_distribution.token(self.plan.voting_token().clone());
-
-        let proposal_managers = self
-            .proposal_managers
-            .start_private_tally(&token_distribution)?;
+        let (proposal_managers, eligible_power) = match self.snapshot_voting_powers() {
+            Some(powers) => (
+                self.proposal_managers.start_private_tally(&powers)?,
+                powers.get_total(),
+            ),
+            None => {
+                let token_distribution = token_distribution.token(self.plan.voting_token().clone());
+                (
+                    self.proposal_managers
+                        .start_private_tally(&token_distribution)?,
+                    token_distribution.get_total(),
+                )
+            }
+        };
 
         Ok(Self {
             proposal_managers,
             plan: Arc::clone(&self.plan),
             id: self.id.clone(),
             committee: Arc::clone(&self.committee),
+            eligible_power,
+            unique_voters: self.unique_voters,
+            snapshot: self.snapshot.clone(),
+            snapshot_powers: self.snapshot_powers.clone(),
+            cancelled: self.cancelled,
         })
     }
 
+    /// tally a private vote plan with the decrypted shares of the
+    /// committee, computing the encrypted tally first unless it has been
+    /// started with an `EncryptedVoteTally` fragment
+    ///
+    /// # errors
+    ///
+    /// * the same errors as [`start_private_tally`](Self::start_private_tally)
+    ///   when the encrypted tally is computed
+    /// * if the shares do not decrypt the encrypted tally
+    ///
+    pub fn private_tally<F>(
+        &self,
+        token_distribution: TokenDistribution<()>,
+        block_date: BlockDate,
+        governance: &Governance,
+        decrypted_tally: &DecryptedPrivateTally,
+        sig: CommitteeId,
+        f: F,
+    ) -> Result<Self, VoteError>
+    where
+        F: FnMut(&VoteAction),
+    {
+        if self.proposal_managers.private_tally_started() {
+            self.finalize_private_tally(decrypted_tally, governance, f)
+        } else {
+            self.start_private_tally(token_distribution, block_date, sig)?
+                .finalize_private_tally(decrypted_tally, governance, f)
+        }
+    }
+
     pub fn finalize_private_tally<F>(
         &self,
         decrypted_tally: &DecryptedPrivateTally,
@@ -858,6 +1308,11 @@ impl VotePlanManager {
             plan: Arc::clone(&self.plan),
             id: self.id.clone(),
             committee: Arc::clone(&self.committee),
+            eligible_power: self.eligible_power,
+            unique_voters: self.unique_voters,
+            snapshot: self.snapshot.clone(),
+            snapshot_powers: self.snapshot_powers.clone(),
+            cancelled: self.cancelled,
         })
     }
 }
@@ -893,7 +1348,9 @@ mod tests {
             .validate_public_vote(&identifier, vote_cast)
             .unwrap();
 
-        proposal_manager = proposal_manager.vote(identifier.clone(), vote).unwrap();
+        proposal_manager = proposal_manager
+            .vote(identifier.clone(), vote, Value::zero())
+            .unwrap();
 
         let (_, actual_vote_cast_payload) = proposal_manager
             .votes_by_voters
@@ -1057,7 +1514,12 @@ mod tests {
         );
 
         vote_plan_manager = vote_plan_manager
-            .vote(vote_block_date, committee.public_key().into(), vote_cast)
+            .vote(
+                vote_block_date,
+                committee.public_key().into(),
+                vote_cast,
+                empty_token_distribution(),
+            )
             .unwrap();
 
         let tally_proof = get_tally_proof(vote_start, &committee, vote_plan.to_id());
@@ -1360,7 +1822,11 @@ mod tests {
             )
             .unwrap();
         first_proposal_manager = first_proposal_manager
-            .vote(identifier.clone(), first_vote_cast.payload.clone())
+            .vote(
+                identifier.clone(),
+                first_vote_cast.payload.clone(),
+                Value::zero(),
+            )
             .unwrap();
 
         let second_vote_cast = proposals
@@ -1374,14 +1840,18 @@ mod tests {
             )
             .unwrap();
         second_proposal_manager = second_proposal_manager
-            .vote(identifier.clone(), second_vote_cast.payload.clone())
+            .vote(
+                identifier.clone(),
+                second_vote_cast.payload.clone(),
+                Value::zero(),
+            )
             .unwrap();
 
         let (token_distribution, token) = ledger_with_tokens(identifier.clone());
         let token_distribution = token_distribution.token(token);
 
-        let _ = proposals.vote(identifier.clone(), first_vote_cast);
-        let _ = proposals.vote(identifier, second_vote_cast);
+        let _ = proposals.vote(identifier.clone(), first_vote_cast, Value::zero());
+        let _ = proposals.vote(identifier, second_vote_cast, Value::zero());
 
         let governance = governance_50_percent(blank, favorable, rejection);
 
@@ -1464,6 +1934,176 @@ mod tests {
         (TokenDistribution::new(token_totals, account_ledger), token)
     }
 
+    fn empty_token_distribution() -> TokenDistribution<()> {
+        TokenDistribution::new(TokenTotals::default(), account::Ledger::new())
+    }
+
+    #[test]
+    pub fn vote_plan_manager_participation() {
+        let favorable = Choice::new(1);
+        let voter = Wallet::from_value(Value(100));
+        let vote_plan = VotePlan::new(
+            BlockDate::from_epoch_slot_id(1, 0),
+            BlockDate::from_epoch_slot_id(2, 0),
+            BlockDate::from_epoch_slot_id(3, 0),
+            VoteTestGen::proposals(3),
+            PayloadType::Public,
+            Vec::new(),
+            TokenIdentifier {
+                policy_hash: PolicyHash::from([0u8; POLICY_HASH_SIZE]),
+                token_name: TokenName::try_from(vec![0u8; TOKEN_NAME_MAX_SIZE]).unwrap(),
+            },
+        );
+        let mut vote_plan_manager = VotePlanManager::new(vote_plan.clone(), HashSet::new());
+
+        for proposal_index in 0..2 {
+            let vote_cast = VoteCast::new(
+                vote_plan.to_id(),
+                proposal_index,
+                VoteTestGen::vote_cast_payload_for(&favorable),
+            );
+            let (token_distribution, _) = ledger_with_tokens(voter.public_key());
+            vote_plan_manager = vote_plan_manager
+                .vote(
+                    BlockDate::from_epoch_slot_id(1, 1),
+                    voter.public_key().into(),
+                    vote_cast,
+                    token_distribution,
+                )
+                .unwrap();
+        }
+
+        let participation = vote_plan_manager.statuses().participation();
+        assert_eq!(participation.eligible_power, Value(51));
+        assert_eq!(participation.unique_voters, 1);
+        assert_eq!(
+            participation.proposals,
+            vec![
+                ProposalParticipation {
+                    voters: 1,
+                    voting_power: Value(51),
+                },
+                ProposalParticipation {
+                    voters: 1,
+                    voting_power: Value(51),
+                },
+                ProposalParticipation::default(),
+            ]
+        );
+    }
+
+    #[test]
+    pub fn vote_plan_manager_snapshot_voting_power() {
+        let favorable = Choice::new(1);
+        let committee = Wallet::from_value(Value(100));
+        let voter = Wallet::from_value(Value(100));
+        let vote_plan = VotePlan::new(
+            BlockDate::from_epoch_slot_id(1, 0),
+            BlockDate::from_epoch_slot_id(2, 0),
+            BlockDate::from_epoch_slot_id(3, 0),
+            VoteTestGen::proposals(1),
+            PayloadType::Public,
+            Vec::new(),
+            TokenIdentifier {
+                policy_hash: PolicyHash::from([0u8; POLICY_HASH_SIZE]),
+                token_name: TokenName::try_from(vec![0u8; TOKEN_NAME_MAX_SIZE]).unwrap(),
+            },
+        );
+        let committee_id: CommitteeId = committee.public_key().into();
+        let mut committee_ids = HashSet::new();
+        committee_ids.insert(committee_id);
+        let mut vote_plan_manager = VotePlanManager::new(vote_plan.clone(), committee_ids);
+
+        let voter_id: account::Identifier = voter.public_key().into();
+        let tree = vote::VotingPowerTree::new(&[
+            (voter_id.clone(), Value(40)),
+            (TestGen::identifier(), Value(60)),
+        ]);
+        let snapshot = VotingPowerSnapshot::new(vote_plan.to_id(), tree.root(), Value(100));
+
+        assert_eq!(
+            vote_plan_manager
+                .register_voting_power_snapshot(
+                    vote_plan.vote_start(),
+                    snapshot.clone(),
+                    committee_id
+                )
+                .err()
+                .unwrap(),
+            VoteError::VotingPowerSnapshotTooLate {
+                start: vote_plan.vote_start()
+            }
+        );
+        vote_plan_manager = vote_plan_manager
+            .register_voting_power_snapshot(BlockDate::first(), snapshot.clone(), committee_id)
+            .unwrap();
+        assert_eq!(
+            vote_plan_manager
+                .register_voting_power_snapshot(BlockDate::first(), snapshot, committee_id)
+                .err()
+                .unwrap(),
+            VoteError::VotingPowerSnapshotAlreadyRegistered
+        );
+
+        let vote_cast = VoteCast::new(
+            vote_plan.to_id(),
+            0,
+            VoteTestGen::vote_cast_payload_for(&favorable),
+        );
+        let vote_date = BlockDate::from_epoch_slot_id(1, 1);
+        let (token_distribution, _) = ledger_with_tokens(voter.public_key());
+        assert_eq!(
+            vote_plan_manager
+                .vote(
+                    vote_date,
+                    voter_id.clone(),
+                    vote_cast.clone(),
+                    token_distribution
+                )
+                .err()
+                .unwrap(),
+            VoteError::VotingPowerProofRequired
+        );
+
+        let proof = tree.proof(0).unwrap();
+        let cast = SnapshotVoteCast::new(vote_cast.clone(), Value(50), proof.clone());
+        assert_eq!(
+            vote_plan_manager
+                .vote_with_power_proof(vote_date, voter_id.clone(), cast)
+                .err()
+                .unwrap(),
+            VoteError::InvalidVotingPowerProof
+        );
+
+        let cast = SnapshotVoteCast::new(vote_cast, Value(40), proof);
+        vote_plan_manager = vote_plan_manager
+            .vote_with_power_proof(vote_date, voter_id, cast)
+            .unwrap();
+        let participation = vote_plan_manager.statuses().participation();
+        assert_eq!(participation.eligible_power, Value(100));
+        assert_eq!(participation.proposals[0].voting_power, Value(40));
+
+        // the stake of the voter at the time of the tally is not used
+        let (token_distribution, _) = ledger_with_tokens(voter.public_key());
+        vote_plan_manager = vote_plan_manager
+            .public_tally(
+                token_distribution,
+                BlockDate::from_epoch_slot_id(2, 1),
+                &Governance::default(),
+                committee_id,
+                |_| (),
+            )
+            .unwrap();
+        let statuses = vote_plan_manager.statuses();
+        let result = statuses.proposals[0]
+            .tally
+            .as_ref()
+            .and_then(Tally::result)
+            .unwrap();
+        assert_eq!(u64::from(result.results()[1]), 40);
+        assert_eq!(statuses.voting_power_snapshot, Some(tree.root()));
+    }
+
     #[test]
     pub fn proposal_managers_many_votes() {
         let vote_plan = VoteTestGen::vote_plan_with_proposals(2);
@@ -1486,10 +2126,14 @@ mod tests {
             .unwrap();
 
         proposal_managers = proposal_managers
-            .vote(identifier.clone(), first_vote_cast_validated)
+            .vote(identifier.clone(), first_vote_cast_validated, Value::zero())
             .unwrap();
         proposal_managers = proposal_managers
-            .vote(identifier.clone(), second_vote_cast_validated)
+            .vote(
+                identifier.clone(),
+                second_vote_cast_validated,
+                Value::zero(),
+            )
             .unwrap();
 
         let (_, actual_vote_cast_payload) = proposal_managers
@@ -1552,11 +2196,11 @@ mod tests {
             .unwrap();
 
         proposal_managers = proposal_managers
-            .vote(identifier.clone(), first_vote_cast)
+            .vote(identifier.clone(), first_vote_cast, Value::zero())
             .unwrap();
 
         assert!(proposal_managers
-            .vote(identifier.clone(), second_vote_cast)
+            .vote(identifier.clone(), second_vote_cast, Value::zero())
             .is_err());
 
         let (_, actual_vote_cast_payload) = proposal_managers
@@ -1622,7 +2266,12 @@ mod tests {
 
         assert_eq!(
             vote_plan_manager
-                .vote(BlockDate::first(), TestGen::identifier(), vote_cast.clone())
+                .vote(
+                    BlockDate::first(),
+                    TestGen::identifier(),
+                    vote_cast.clone(),
+                    empty_token_distribution()
+                )
                 .err()
                 .unwrap(),
             VoteError::InvalidVotePlan {
@@ -1643,7 +2292,8 @@ mod tests {
                 .vote(
                     vote_plan.vote_end().next_epoch(),
                     TestGen::identifier(),
-                    vote_cast.clone()
+                    vote_cast.clone(),
+                    empty_token_distribution()
                 )
                 .err()
                 .unwrap(),
@@ -1675,7 +2325,12 @@ mod tests {
 
         assert_eq!(
             vote_plan_manager
-                .vote(BlockDate::first(), TestGen::identifier(), vote_cast.clone())
+                .vote(
+                    BlockDate::first(),
+                    TestGen::identifier(),
+                    vote_cast.clone(),
+                    empty_token_distribution()
+                )
                 .err()
                 .unwrap(),
             VoteError::NotVoteTime {
@@ -1708,7 +2363,8 @@ mod tests {
             .vote(
                 BlockDate::from_epoch_slot_id(1, 1),
                 TestGen::identifier(),
-                vote_cast
+                vote_cast,
+                empty_token_distribution()
             )
             .is_ok());
     }
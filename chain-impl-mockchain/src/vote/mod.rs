@@ -5,12 +5,16 @@
 
 mod choice;
 mod committee;
+#[cfg(feature = "serde")]
+pub mod json;
 mod ledger;
 mod manager;
 mod payload;
 mod privacy;
+mod snapshot;
 mod status;
 mod tally;
+mod tally_workflow;
 
 pub use self::{
     choice::{Choice, Options},
@@ -19,6 +23,10 @@ pub use self::{
     manager::{ValidatedPayload, VoteError, VotePlanManager},
     payload::{EncryptedVote, Payload, PayloadType, ProofOfCorrectVote, TryFromIntError},
     privacy::encrypt_vote,
-    status::{VotePlanStatus, VoteProposalStatus},
+    snapshot::{
+        Sibling, VotingPowerProof, VotingPowerRoot, VotingPowerTree, VOTING_POWER_PROOF_MAX_DEPTH,
+    },
+    status::{ProposalParticipation, VotePlanParticipation, VotePlanStatus, VoteProposalStatus},
     tally::{PrivateTallyState, Tally, TallyError, TallyResult, Weight},
+    tally_workflow::{PrivateTallyWorkflow, TallyStage, TallyWorkflowError},
 };
@@ -1,5 +1,9 @@
 use crate::vote::Choice;
-use chain_core::mempack::{ReadBuf, ReadError};
+use chain_core::{
+    mempack::{ReadBuf, ReadError},
+    packer::Codec,
+    property::{ByteSource, Pack, Unpack},
+};
 use chain_vote::Ciphertext;
 use std::hash::Hash;
 use thiserror::Error;
@@ -81,18 +85,30 @@ impl Payload {
                 .sub(|bb| proof.serialize_in(bb)),
         }
     }
+}
+
+impl Pack for Payload {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(
+            self.serialize_in(ByteBuilder::<Self>::new())
+                .finalize()
+                .as_slice(),
+        )
+    }
+}
 
-    pub(crate) fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        let t = buf
-            .get_u8()?
+impl Unpack for Payload {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let t = source
+            .take_u8()?
             .try_into()
-            .map_err(|e: TryFromIntError| ReadError::StructureInvalid(e.to_string()))?;
+            .map_err(|e: TryFromIntError| ReadError::StructureInvalid(e.to_string().into()))?;
 
         match t {
-            PayloadType::Public => buf.get_u8().map(Choice::new).map(Self::public),
+            PayloadType::Public => source.take_u8().map(Choice::new).map(Self::public),
             PayloadType::Private => {
-                let encrypted_vote = EncryptedVote::read(buf)?;
-                let proof = ProofOfCorrectVote::read(buf)?;
+                let encrypted_vote = EncryptedVote::unpack(source)?;
+                let proof = ProofOfCorrectVote::unpack(source)?;
                 Ok(Self::Private {
                     encrypted_vote,
                     proof,
@@ -102,6 +118,8 @@ impl Payload {
     }
 }
 
+chain_ser::impl_legacy_codecs!(Payload);
+
 impl ProofOfCorrectVote {
     pub(crate) fn from_inner(proof: chain_vote::ProofOfCorrectVote) -> Self {
         assert!(
@@ -127,12 +145,28 @@ impl ProofOfCorrectVote {
     pub fn serialize(&self) -> ByteArray<Self> {
         self.serialize_in(ByteBuilder::new()).finalize()
     }
+}
 
-    pub(crate) fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        chain_vote::ProofOfCorrectVote::from_buffer(buf).map(Self)
+impl Pack for ProofOfCorrectVote {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(self.serialize().as_slice())
     }
 }
 
+impl Unpack for ProofOfCorrectVote {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        // the size of the proof is only known from its number of bits, the
+        // proof is then parsed from its bytes
+        let bits = source.take_u8()?;
+        let mut bytes = vec![0; chain_vote::ProofOfCorrectVote::bytes_len(bits as usize)];
+        bytes[0] = bits;
+        source.take_into(&mut bytes[1..])?;
+        chain_vote::ProofOfCorrectVote::from_buffer(&mut ReadBuf::from(&bytes)).map(Self)
+    }
+}
+
+chain_ser::impl_legacy_codecs!(ProofOfCorrectVote);
+
 impl EncryptedVote {
     pub(crate) fn from_inner(vote: chain_vote::EncryptedVote) -> Self {
         Self(vote)
@@ -152,22 +186,32 @@ impl EncryptedVote {
     pub fn serialize(&self) -> ByteArray<Self> {
         self.serialize_in(ByteBuilder::new()).finalize()
     }
+}
 
-    pub(crate) fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
-        let len: usize = buf.get_u8()? as usize;
+impl Pack for EncryptedVote {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(self.serialize().as_slice())
+    }
+}
+
+impl Unpack for EncryptedVote {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let len: usize = source.take_u8()? as usize;
         let mut cypher_texts: Vec<Ciphertext> = Vec::new();
+        let mut ct_buf = [0; Ciphertext::BYTES_LEN];
         for _ in 0..len {
-            let ct_buf = buf.get_slice(Ciphertext::BYTES_LEN)?;
+            source.take_into(&mut ct_buf)?;
             cypher_texts.push(
-                Ciphertext::from_bytes(ct_buf).ok_or_else(|| {
-                    ReadError::StructureInvalid("Invalid private vote".to_string())
-                })?,
+                Ciphertext::from_bytes(&ct_buf)
+                    .ok_or_else(|| ReadError::StructureInvalid("Invalid private vote".into()))?,
             );
         }
         Ok(Self(cypher_texts))
     }
 }
 
+chain_ser::impl_legacy_codecs!(EncryptedVote);
+
 impl TryFrom<u8> for PayloadType {
     type Error = TryFromIntError;
     fn try_from(value: u8) -> Result<Self, Self::Error> {
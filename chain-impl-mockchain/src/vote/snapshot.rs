@@ -0,0 +1,252 @@
+//! Snapshot of the voting power of the accounts, registered by the committee
+//! for a vote plan before the vote starts.
+//!
+//! The snapshot is the root of a Merkle tree over the `(account, power)`
+//! pairs. Once a snapshot is registered for a vote plan, the voters prove
+//! their voting power with the path from their leaf to the root and their
+//! stake at the time of the vote or of the tally is not used.
+
+use crate::{account, key::Hash, value::Value};
+use chain_core::{
+    mempack::ReadError,
+    packer::Codec,
+    property::{ByteSource, Pack, Unpack},
+};
+use typed_bytes::ByteBuilder;
+
+/// Root of the Merkle tree of the voting powers
+pub type VotingPowerRoot = Hash;
+
+/// maximum number of steps in a proof, more than enough for any number of
+/// accounts
+pub const VOTING_POWER_PROOF_MAX_DEPTH: usize = 64;
+
+const LEAF_PREFIX: u8 = 0;
+const NODE_PREFIX: u8 = 1;
+
+const SIBLING_LEFT: u8 = 0;
+const SIBLING_RIGHT: u8 = 1;
+
+/// Sibling of a node on the path from a leaf to the root
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Sibling {
+    Left(Hash),
+    Right(Hash),
+}
+
+/// Proof that an account has the given voting power in a snapshot
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VotingPowerProof(Vec<Sibling>);
+
+/// Merkle tree of the voting powers, to compute the root of a snapshot and
+/// the proofs of the voters
+#[derive(Debug, Clone)]
+pub struct VotingPowerTree {
+    levels: Vec<Vec<Hash>>,
+}
+
+fn leaf_hash(account: &account::Identifier, power: Value) -> Hash {
+    let bytes = ByteBuilder::<()>::new()
+        .u8(LEAF_PREFIX)
+        .bytes(account.as_ref().as_ref())
+        .u64(power.0)
+        .finalize_as_vec();
+    Hash::hash_bytes(&bytes)
+}
+
+fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    let bytes = ByteBuilder::<()>::new()
+        .u8(NODE_PREFIX)
+        .bytes(left.as_ref())
+        .bytes(right.as_ref())
+        .finalize_as_vec();
+    Hash::hash_bytes(&bytes)
+}
+
+impl VotingPowerProof {
+    pub fn new(path: Vec<Sibling>) -> Self {
+        Self(path)
+    }
+
+    pub fn path(&self) -> &[Sibling] {
+        &self.0
+    }
+
+    /// root of the tree containing the given leaf, if the proof is valid
+    pub fn root(&self, account: &account::Identifier, power: Value) -> VotingPowerRoot {
+        self.0
+            .iter()
+            .fold(leaf_hash(account, power), |node, sibling| match sibling {
+                Sibling::Left(left) => node_hash(left, &node),
+                Sibling::Right(right) => node_hash(&node, right),
+            })
+    }
+
+    pub fn verify(
+        &self,
+        root: &VotingPowerRoot,
+        account: &account::Identifier,
+        power: Value,
+    ) -> bool {
+        &self.root(account, power) == root
+    }
+
+    pub fn serialize_in<T>(&self, bb: ByteBuilder<T>) -> ByteBuilder<T> {
+        bb.iter8(self.0.iter(), |bb, sibling| match sibling {
+            Sibling::Left(hash) => bb.u8(SIBLING_LEFT).bytes(hash.as_ref()),
+            Sibling::Right(hash) => bb.u8(SIBLING_RIGHT).bytes(hash.as_ref()),
+        })
+    }
+}
+
+impl Pack for VotingPowerProof {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+        codec.put_bytes(
+            self.serialize_in(ByteBuilder::<Self>::new())
+                .finalize()
+                .as_slice(),
+        )
+    }
+}
+
+impl Unpack for VotingPowerProof {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let len = source.take_u8()? as usize;
+        if len > VOTING_POWER_PROOF_MAX_DEPTH {
+            return Err(ReadError::SizeTooBig {
+                size: len,
+                limit: VOTING_POWER_PROOF_MAX_DEPTH,
+            });
+        }
+        let mut path = Vec::with_capacity(len);
+        for _ in 0..len {
+            let side = source.take_u8()?;
+            let hash = Hash::unpack(source)?;
+            path.push(match side {
+                SIBLING_LEFT => Sibling::Left(hash),
+                SIBLING_RIGHT => Sibling::Right(hash),
+                tag => {
+                    return Err(ReadError::UnknownTag {
+                        kind: "voting power proof sibling",
+                        tag: tag as u32,
+                    })
+                }
+            });
+        }
+        Ok(Self(path))
+    }
+}
+
+chain_ser::impl_legacy_codecs!(VotingPowerProof);
+
+impl VotingPowerTree {
+    /// build the tree of the given voting powers, the order of the accounts
+    /// matters and the proofs are retrieved by index
+    pub fn new(powers: &[(account::Identifier, Value)]) -> Self {
+        let mut levels = vec![powers
+            .iter()
+            .map(|(account, power)| leaf_hash(account, *power))
+            .collect::<Vec<_>>()];
+
+        while levels.last().unwrap().len() > 1 {
+            let level = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => node_hash(left, right),
+                    // the last node of a level with an odd number of nodes
+                    // is promoted as is
+                    [node] => *node,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(level);
+        }
+
+        Self { levels }
+    }
+
+    /// root of the tree, the zero hash for an empty snapshot
+    pub fn root(&self) -> VotingPowerRoot {
+        self.levels
+            .last()
+            .and_then(|level| level.first())
+            .copied()
+            .unwrap_or_else(Hash::zero_hash)
+    }
+
+    /// proof of the voting power of the account at the given index
+    pub fn proof(&self, mut index: usize) -> Option<VotingPowerProof> {
+        if index >= self.levels[0].len() {
+            return None;
+        }
+
+        let mut path = Vec::new();
+        for level in &self.levels[..self.levels.len() - 1] {
+            let sibling = index ^ 1;
+            if let Some(hash) = level.get(sibling) {
+                path.push(if sibling < index {
+                    Sibling::Left(*hash)
+                } else {
+                    Sibling::Right(*hash)
+                });
+            }
+            index /= 2;
+        }
+        Some(VotingPowerProof(path))
+    }
+}
+
+#[cfg(any(test, feature = "property-test-api"))]
+mod tests {
+    use super::*;
+    #[cfg(test)]
+    use quickcheck::TestResult;
+    use quickcheck::{Arbitrary, Gen};
+    #[cfg(test)]
+    use quickcheck_macros::quickcheck;
+
+    impl Arbitrary for Sibling {
+        fn arbitrary<G: Gen>(g: &mut G) -> Self {
+            if bool::arbitrary(g) {
+                Sibling::Left(Arbitrary::arbitrary(g))
+            } else {
+                Sibling::Right(Arbitrary::arbitrary(g))
+            }
+        }
+    }
+
+    impl Arbitrary for VotingPowerProof {
+        fn arbitrary<G: Gen>(g: &mut G) -> Self {
+            let len = usize::arbitrary(g) % 20;
+            Self(
+                std::iter::repeat_with(|| Arbitrary::arbitrary(g))
+                    .take(len)
+                    .collect(),
+            )
+        }
+    }
+
+    #[quickcheck]
+    fn every_voting_power_is_proven(powers: Vec<(account::Identifier, Value)>) -> TestResult {
+        if powers.is_empty() {
+            return TestResult::discard();
+        }
+
+        let tree = VotingPowerTree::new(&powers);
+        let root = tree.root();
+        let all_proven = powers.iter().enumerate().all(|(index, (account, power))| {
+            let proof = tree.proof(index).unwrap();
+            proof.verify(&root, account, *power)
+                && !proof.verify(&root, account, Value(power.0.wrapping_add(1)))
+        });
+        TestResult::from_bool(all_proven && tree.proof(powers.len()).is_none())
+    }
+
+    #[quickcheck]
+    fn single_voting_power_root_is_the_leaf(account: account::Identifier, power: Value) -> bool {
+        let tree = VotingPowerTree::new(&[(account.clone(), power)]);
+        tree.root() == leaf_hash(&account, power) && tree.proof(0).unwrap().path().is_empty()
+    }
+}
@@ -3,7 +3,8 @@ use crate::{
     certificate::{ExternalProposalId, VotePlanId},
     date::BlockDate,
     tokens::identifier::TokenIdentifier,
-    vote::{Options, PayloadType, Tally, ValidatedPayload},
+    value::Value,
+    vote::{Options, PayloadType, Tally, ValidatedPayload, VotingPowerRoot},
 };
 use chain_vote::MemberPublicKey;
 use imhamt::Hamt;
@@ -18,6 +19,15 @@ pub struct VotePlanStatus {
     pub committee_public_keys: Vec<MemberPublicKey>,
     pub proposals: Vec<VoteProposalStatus>,
     pub voting_token: TokenIdentifier,
+    /// total amount of the voting token, as of the last vote or tally
+    pub eligible_power: Value,
+    /// number of distinct accounts which voted for at least one proposal
+    pub unique_voters: u64,
+    /// root of the voting power snapshot registered for the vote plan, if any
+    pub voting_power_snapshot: Option<VotingPowerRoot>,
+    /// date of the `VotePlanCancel` certificate closing the vote plan, if
+    /// it has been cancelled
+    pub cancelled: Option<BlockDate>,
 }
 
 pub struct VoteProposalStatus {
@@ -26,4 +36,48 @@ pub struct VoteProposalStatus {
     pub options: Options,
     pub tally: Option<Tally>,
     pub votes: Hamt<DefaultHasher, account::Identifier, ValidatedPayload>,
+    pub participation: ProposalParticipation,
+    /// the vote plan of the proposal has been cancelled, the proposal is
+    /// never tallied
+    pub cancelled: bool,
+}
+
+/// Participation to a proposal, updated as the votes are cast.
+///
+/// The voting power is the one of the voters at the time they cast their
+/// vote, the tally uses the voting power at the time of the tally.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProposalParticipation {
+    pub voters: u64,
+    pub voting_power: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VotePlanParticipation {
+    pub eligible_power: Value,
+    pub unique_voters: u64,
+    pub proposals: Vec<ProposalParticipation>,
+}
+
+impl ProposalParticipation {
+    pub(crate) fn add_voter(self, voting_power: Value) -> Self {
+        Self {
+            voters: self.voters + 1,
+            voting_power: self.voting_power.saturating_add(voting_power),
+        }
+    }
+}
+
+impl VotePlanStatus {
+    pub fn participation(&self) -> VotePlanParticipation {
+        VotePlanParticipation {
+            eligible_power: self.eligible_power,
+            unique_voters: self.unique_voters,
+            proposals: self
+                .proposals
+                .iter()
+                .map(|proposal| proposal.participation)
+                .collect(),
+        }
+    }
 }
@@ -153,7 +153,7 @@ impl TallyResult {
 
     pub fn participation(&self) -> Stake {
         let s: u64 = self.results.iter().map(|w| w.0).sum();
-        Stake::from_value(Value(s))
+        Stake(s)
     }
 
     pub fn options(&self) -> &Options {
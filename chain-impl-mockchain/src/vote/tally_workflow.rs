@@ -0,0 +1,412 @@
+//! Orchestration of the private tally of a vote plan by the committee.
+//!
+//! Once the encrypted tally of a private vote plan is recorded by the
+//! ledger, the committee goes through the following stages:
+//!
+//! 1. every member produces a decryption share for every proposal, the
+//!    shares are collected until the whole committee answered;
+//! 2. the shares are validated against the encrypted tallies of the vote
+//!    plan status, the shares of a member failing the validation are
+//!    dropped so the member can send them again;
+//! 3. the result of every proposal is decrypted from the shares;
+//! 4. the tally certificate is submitted to the chain.
+//!
+//! The workflow can be serialized at any stage and resumed after a restart,
+//! the encrypted tallies are not part of the serialized state, they are read
+//! again from the [`VotePlanStatus`] of the ledger.
+
+use crate::{
+    certificate::{DecryptedPrivateTally, DecryptedPrivateTallyProposal, VotePlanId, VoteTally},
+    fragment::FragmentId,
+    vote::{PayloadType, TallyError, VotePlanStatus},
+};
+use chain_core::mempack::{ReadBuf, ReadError, Readable};
+use chain_vote::{EncryptedTally, MemberPublicKey, TallyDecryptShare, TallyOptimizationTable};
+use thiserror::Error;
+use typed_bytes::ByteBuilder;
+
+/// The stage of the private tally of a vote plan, see the module
+/// documentation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TallyStage {
+    CollectingShares,
+    Validated,
+    Decrypted { results: Vec<Vec<u64>> },
+    Submitted { fragment_id: FragmentId },
+}
+
+impl TallyStage {
+    fn name(&self) -> &'static str {
+        match self {
+            TallyStage::CollectingShares => "collecting shares",
+            TallyStage::Validated => "validated",
+            TallyStage::Decrypted { .. } => "decrypted",
+            TallyStage::Submitted { .. } => "submitted",
+        }
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum TallyWorkflowError {
+    #[error("the vote plan is not private")]
+    NotPrivate,
+    #[error("the vote plan has no encrypted tally")]
+    NotTallied(#[source] TallyError),
+    #[error("the status is not the one of the vote plan of the tally")]
+    VotePlanMismatch,
+    #[error("the operation is not possible at the '{actual}' stage, expected '{expected}'")]
+    UnexpectedStage {
+        expected: &'static str,
+        actual: &'static str,
+    },
+    #[error("the key is not the one of a member of the committee")]
+    UnknownMember,
+    #[error("expected a share for each of the {expected} proposals, received {actual}")]
+    SharesCountMismatch { expected: usize, actual: usize },
+    #[error("the shares of {missing} committee members are missing")]
+    MissingShares { missing: usize },
+    #[error("invalid share of the committee member {member} for the proposal {proposal}")]
+    InvalidShare { member: usize, proposal: usize },
+    #[error("cannot decrypt the tally of the proposal {proposal}")]
+    Decryption { proposal: usize },
+}
+
+/// The state of the private tally of a vote plan, see the module
+/// documentation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrivateTallyWorkflow {
+    vote_plan: VotePlanId,
+    committee: Vec<MemberPublicKey>,
+    /// number of options of every proposal
+    options: Vec<u8>,
+    /// the shares of every member, one per proposal
+    shares: Vec<Option<Vec<TallyDecryptShare>>>,
+    stage: TallyStage,
+}
+
+impl PrivateTallyWorkflow {
+    /// Start the tally of a private vote plan whose encrypted tally is
+    /// recorded in the status
+    pub fn new(status: &VotePlanStatus) -> Result<Self, TallyWorkflowError> {
+        if status.payload != PayloadType::Private {
+            return Err(TallyWorkflowError::NotPrivate);
+        }
+        encrypted_tallies(status)?;
+        Ok(PrivateTallyWorkflow {
+            vote_plan: status.id.clone(),
+            committee: status.committee_public_keys.clone(),
+            options: status
+                .proposals
+                .iter()
+                .map(|proposal| proposal.options.choice_range().end)
+                .collect(),
+            shares: vec![None; status.committee_public_keys.len()],
+            stage: TallyStage::CollectingShares,
+        })
+    }
+
+    pub fn vote_plan(&self) -> &VotePlanId {
+        &self.vote_plan
+    }
+
+    pub fn stage(&self) -> &TallyStage {
+        &self.stage
+    }
+
+    /// The committee members whose shares were not collected yet
+    pub fn missing_members(&self) -> impl Iterator<Item = &MemberPublicKey> {
+        self.committee
+            .iter()
+            .zip(self.shares.iter())
+            .filter(|(_, shares)| shares.is_none())
+            .map(|(member, _)| member)
+    }
+
+    /// Collect the shares of a member, one per proposal in the order of the
+    /// vote plan. Shares sent again by a member replace the previous ones.
+    pub fn add_shares(
+        &mut self,
+        member: &MemberPublicKey,
+        shares: Vec<TallyDecryptShare>,
+    ) -> Result<(), TallyWorkflowError> {
+        self.expect_stage(TallyStage::CollectingShares)?;
+        let index = self
+            .committee
+            .iter()
+            .position(|key| key == member)
+            .ok_or(TallyWorkflowError::UnknownMember)?;
+        if shares.len() != self.options.len() {
+            return Err(TallyWorkflowError::SharesCountMismatch {
+                expected: self.options.len(),
+                actual: shares.len(),
+            });
+        }
+        if let Some(proposal) = shares
+            .iter()
+            .zip(self.options.iter())
+            .position(|(share, options)| share.options() != *options as usize)
+        {
+            return Err(TallyWorkflowError::InvalidShare {
+                member: index,
+                proposal,
+            });
+        }
+        self.shares[index] = Some(shares);
+        Ok(())
+    }
+
+    /// Check the shares of the whole committee against the encrypted
+    /// tallies. The shares of the first member found with an invalid share
+    /// are dropped and the workflow keeps collecting shares.
+    pub fn validate(&mut self, status: &VotePlanStatus) -> Result<(), TallyWorkflowError> {
+        self.expect_stage(TallyStage::CollectingShares)?;
+        let encrypted = self.encrypted_tallies(status)?;
+        let missing = self.missing_members().count();
+        if missing > 0 {
+            return Err(TallyWorkflowError::MissingShares { missing });
+        }
+        for (member, (key, shares)) in self.committee.iter().zip(self.shares.iter()).enumerate() {
+            let shares = shares.as_ref().expect("all the shares are collected");
+            let invalid = shares
+                .iter()
+                .zip(encrypted.iter())
+                .position(|(share, (encrypted_tally, _))| !share.verify(encrypted_tally, key));
+            if let Some(proposal) = invalid {
+                self.shares[member] = None;
+                return Err(TallyWorkflowError::InvalidShare { member, proposal });
+            }
+        }
+        self.stage = TallyStage::Validated;
+        Ok(())
+    }
+
+    /// Decrypt the result of every proposal from the validated shares
+    pub fn decrypt(
+        &mut self,
+        status: &VotePlanStatus,
+        table: &TallyOptimizationTable,
+    ) -> Result<(), TallyWorkflowError> {
+        self.expect_stage(TallyStage::Validated)?;
+        let encrypted = self.encrypted_tallies(status)?;
+        let results = encrypted
+            .iter()
+            .enumerate()
+            .map(|(proposal, (encrypted_tally, max_votes))| {
+                let shares = self.proposal_shares(proposal);
+                encrypted_tally
+                    .validate_partial_decryptions(&self.committee, &shares)
+                    .map_err(|_| TallyWorkflowError::Decryption { proposal })?
+                    .decrypt_tally(*max_votes, table)
+                    .map(|tally| tally.votes)
+                    .map_err(|_| TallyWorkflowError::Decryption { proposal })
+            })
+            .collect::<Result<_, _>>()?;
+        self.stage = TallyStage::Decrypted { results };
+        Ok(())
+    }
+
+    /// The decrypted tally of the vote plan, with the shares of the whole
+    /// committee, `None` until the tally is decrypted
+    pub fn decrypted_tally(&self) -> Option<DecryptedPrivateTally> {
+        let results = match &self.stage {
+            TallyStage::Decrypted { results } => results,
+            _ => return None,
+        };
+        let proposals = results
+            .iter()
+            .enumerate()
+            .map(|(proposal, result)| DecryptedPrivateTallyProposal {
+                decrypt_shares: self.proposal_shares(proposal).into_boxed_slice(),
+                tally_result: result.clone().into_boxed_slice(),
+            })
+            .collect();
+        DecryptedPrivateTally::new(proposals).ok()
+    }
+
+    /// The tally certificate of the vote plan, to be signed by a member of
+    /// the committee, `None` until the tally is decrypted
+    pub fn certificate(&self) -> Option<VoteTally> {
+        self.decrypted_tally()
+            .map(|tally| VoteTally::new_private(self.vote_plan.clone(), tally))
+    }
+
+    /// Record the fragment the tally certificate was submitted with
+    pub fn submitted(&mut self, fragment_id: FragmentId) -> Result<(), TallyWorkflowError> {
+        match self.stage {
+            TallyStage::Decrypted { .. } => {
+                self.stage = TallyStage::Submitted { fragment_id };
+                Ok(())
+            }
+            _ => Err(TallyWorkflowError::UnexpectedStage {
+                expected: "decrypted",
+                actual: self.stage.name(),
+            }),
+        }
+    }
+
+    /// Whether the ledger recorded the result of every proposal of the vote
+    /// plan, that is the submitted certificate was applied
+    pub fn is_applied(&self, status: &VotePlanStatus) -> bool {
+        status.id == self.vote_plan
+            && status.proposals.iter().all(|proposal| {
+                proposal
+                    .tally
+                    .as_ref()
+                    .map_or(false, |tally| tally.result().is_some())
+            })
+    }
+
+    fn expect_stage(&self, expected: TallyStage) -> Result<(), TallyWorkflowError> {
+        if self.stage == expected {
+            Ok(())
+        } else {
+            Err(TallyWorkflowError::UnexpectedStage {
+                expected: expected.name(),
+                actual: self.stage.name(),
+            })
+        }
+    }
+
+    fn encrypted_tallies(
+        &self,
+        status: &VotePlanStatus,
+    ) -> Result<Vec<(EncryptedTally, u64)>, TallyWorkflowError> {
+        if status.id != self.vote_plan {
+            return Err(TallyWorkflowError::VotePlanMismatch);
+        }
+        encrypted_tallies(status)
+    }
+
+    /// the shares of the whole committee for the proposal
+    fn proposal_shares(&self, proposal: usize) -> Vec<TallyDecryptShare> {
+        self.shares
+            .iter()
+            .flatten()
+            .map(|shares| shares[proposal].clone())
+            .collect()
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        let bb = ByteBuilder::<Self>::new()
+            .bytes(self.vote_plan.as_ref())
+            .u8(self.committee.len() as u8)
+            .fold(self.committee.iter(), |bb, key| bb.bytes(&key.to_bytes()))
+            .u8(self.options.len() as u8)
+            .bytes(&self.options)
+            .fold(self.shares.iter(), |bb, shares| match shares {
+                None => bb.u8(0),
+                Some(shares) => bb
+                    .u8(1)
+                    .fold(shares.iter(), |bb, share| bb.bytes(&share.to_bytes())),
+            });
+        let bb = match &self.stage {
+            TallyStage::CollectingShares => bb.u8(0),
+            TallyStage::Validated => bb.u8(1),
+            TallyStage::Decrypted { results } => bb.u8(2).fold(results.iter(), |bb, result| {
+                bb.fold(result.iter(), |bb, votes| bb.u64(*votes))
+            }),
+            TallyStage::Submitted { fragment_id } => bb.u8(3).bytes(fragment_id.as_ref()),
+        };
+        bb.finalize_as_vec()
+    }
+}
+
+impl Readable for PrivateTallyWorkflow {
+    fn read(buf: &mut ReadBuf) -> Result<Self, ReadError> {
+        let vote_plan = <[u8; 32]>::read(buf)?.into();
+        let committee_len = buf.get_u8()?;
+        let mut committee = Vec::with_capacity(committee_len as usize);
+        for _ in 0..committee_len {
+            let key_buf = buf.get_slice(MemberPublicKey::BYTES_LEN)?;
+            committee.push(
+                MemberPublicKey::from_bytes(key_buf).ok_or_else(|| {
+                    ReadError::StructureInvalid("invalid public key format".into())
+                })?,
+            );
+        }
+        let proposals_len = buf.get_u8()?;
+        let options = buf.get_slice(proposals_len as usize)?.to_vec();
+        let mut shares = Vec::with_capacity(committee.len());
+        for _ in 0..committee.len() {
+            let member_shares = match buf.get_u8()? {
+                0 => None,
+                1 => {
+                    let mut member_shares = Vec::with_capacity(options.len());
+                    for options in options.iter() {
+                        let share_buf =
+                            buf.get_slice(TallyDecryptShare::bytes_len(*options as usize))?;
+                        member_shares.push(TallyDecryptShare::from_bytes(share_buf).ok_or_else(
+                            || {
+                                ReadError::StructureInvalid(
+                                    "invalid decrypt share structure".into(),
+                                )
+                            },
+                        )?);
+                    }
+                    Some(member_shares)
+                }
+                tag => {
+                    return Err(ReadError::UnknownTag {
+                        kind: "tally shares",
+                        tag: tag as u32,
+                    })
+                }
+            };
+            shares.push(member_shares);
+        }
+        let stage = match buf.get_u8()? {
+            0 => TallyStage::CollectingShares,
+            1 => TallyStage::Validated,
+            2 => {
+                let mut results = Vec::with_capacity(options.len());
+                for options in options.iter() {
+                    let mut result = Vec::with_capacity(*options as usize);
+                    for _ in 0..*options {
+                        result.push(buf.get_u64()?);
+                    }
+                    results.push(result);
+                }
+                TallyStage::Decrypted { results }
+            }
+            3 => TallyStage::Submitted {
+                fragment_id: <[u8; 32]>::read(buf)?.into(),
+            },
+            tag => {
+                return Err(ReadError::UnknownTag {
+                    kind: "tally stage",
+                    tag: tag as u32,
+                })
+            }
+        };
+        Ok(PrivateTallyWorkflow {
+            vote_plan,
+            committee,
+            options,
+            shares,
+            stage,
+        })
+    }
+}
+
+/// the encrypted tally and the maximum of votes of every proposal
+fn encrypted_tallies(
+    status: &VotePlanStatus,
+) -> Result<Vec<(EncryptedTally, u64)>, TallyWorkflowError> {
+    status
+        .proposals
+        .iter()
+        .map(|proposal| {
+            let tally = proposal
+                .tally
+                .as_ref()
+                .ok_or(TallyWorkflowError::NotTallied(TallyError::NoEncryptedTally))?;
+            let (encrypted_tally, _) = tally
+                .private_encrypted()
+                .map_err(TallyWorkflowError::NotTallied)?;
+            let max_votes = tally
+                .private_total_power()
+                .map_err(TallyWorkflowError::NotTallied)?;
+            Ok((encrypted_tally.clone(), max_votes))
+        })
+        .collect()
+}
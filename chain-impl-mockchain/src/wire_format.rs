@@ -0,0 +1,70 @@
+//! The wire format specification of the types of the chain, generated from
+//! their [`DescribeLayout`](chain_core::layout::DescribeLayout)
+//! implementations. The layouts are checked against the serialization of
+//! arbitrary values in the tests of this module, so the specification does
+//! not drift from the code.
+
+use crate::certificate::ExtensionCertificate;
+use crate::fragment::Fragment;
+use crate::transaction::Witness;
+use chain_core::layout::Specification;
+
+/// The specification of the described types and of the types they refer to,
+/// see [`Specification::render`] for the markdown
+pub fn specification() -> Specification {
+    Specification::new("Wire format")
+        .with::<Fragment>()
+        .with::<Witness>()
+        .with::<ExtensionCertificate>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chain_core::layout::check_layout;
+    use quickcheck::TestResult;
+    use quickcheck_macros::quickcheck;
+
+    #[quickcheck]
+    fn fragment_follows_its_layout(fragment: Fragment) -> TestResult {
+        match check_layout(&fragment) {
+            Ok(()) => TestResult::passed(),
+            Err(mismatch) => TestResult::error(mismatch.to_string()),
+        }
+    }
+
+    #[quickcheck]
+    fn witness_follows_its_layout(witness: Witness) -> TestResult {
+        match check_layout(&witness) {
+            Ok(()) => TestResult::passed(),
+            Err(mismatch) => TestResult::error(mismatch.to_string()),
+        }
+    }
+
+    #[quickcheck]
+    fn extension_certificate_follows_its_layout(certificate: ExtensionCertificate) -> TestResult {
+        match check_layout(&certificate) {
+            Ok(()) => TestResult::passed(),
+            Err(mismatch) => TestResult::error(mismatch.to_string()),
+        }
+    }
+
+    #[test]
+    fn specification_covers_every_fragment_kind() {
+        let specification = specification();
+        assert_eq!(
+            specification.type_names().collect::<Vec<_>>(),
+            vec![
+                "Fragment",
+                "Fragment raw",
+                "Fragment content",
+                "Witness",
+                "Extension certificate"
+            ]
+        );
+        let rendered = specification.render();
+        assert!(rendered.contains("### initial (tag 0)"));
+        assert!(rendered.contains("### extension_certificate (tag 25)"));
+        assert!(rendered.contains("| 0 | raw | u32 length, then [Fragment raw](#fragment-raw) |"));
+    }
+}
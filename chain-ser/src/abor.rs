@@ -1,3 +1,5 @@
+use std::fmt;
+
 /// ABOR Encoder
 #[derive(Default)]
 pub struct Encoder {
@@ -165,6 +167,30 @@ pub enum DecodeError {
     TypeMismatch { got: Tag, expected: Tag },
 }
 
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::EndOfStream => write!(f, "End of stream"),
+            DecodeError::StreamTooSmall { want, has } => write!(
+                f,
+                "Stream too small: demanded {} bytes but got {}",
+                want, has
+            ),
+            DecodeError::StreamPending { left } => {
+                write!(f, "Stream pending: {} bytes left", left)
+            }
+            DecodeError::TypeUnknown(t) => write!(f, "Unknown type tag: {}", t),
+            DecodeError::TypeMismatch { got, expected } => write!(
+                f,
+                "Type mismatch: expected {:?} but got {:?}",
+                expected, got
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
 impl<'a> Decoder<'a> {
     pub fn new(data: &'a [u8]) -> Self {
         Decoder { slice: data }
@@ -183,7 +209,7 @@ impl<'a> Decoder<'a> {
     fn expect_tag(&mut self, tag: Tag) -> Result<(), DecodeError> {
         let t = self.pop()?;
         match Tag::from_u8(t) {
-            None => Err(DecodeError::TypeUnknown(self.slice[0])),
+            None => Err(DecodeError::TypeUnknown(t)),
             Some(got) if got == tag => Ok(()),
             Some(got) => Err(DecodeError::TypeMismatch { got, expected: tag }),
         }
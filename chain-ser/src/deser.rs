@@ -1,3 +1,7 @@
+use crate::mempack::{ReadBuf, ReadError};
+use crate::packer::Codec;
+use crate::shared::SharedBytes;
+
 /// Define that an object can be written to a `Write` object.
 pub trait Serialize {
     type Error: std::error::Error + From<std::io::Error>;
@@ -26,3 +30,294 @@ impl<T: Serialize> Serialize for &T {
         (**self).serialize(writer)
     }
 }
+
+/// Source of bytes for [`Unpack`], implemented both for in-memory buffers
+/// ([`ReadBuf`]) and for streams ([`Codec`] over a `BufRead`), so a type
+/// only has to describe its decoding once.
+///
+/// All the integers are read big endian.
+pub trait ByteSource {
+    fn take_u8(&mut self) -> Result<u8, ReadError>;
+    fn take_u16(&mut self) -> Result<u16, ReadError>;
+    fn take_u32(&mut self) -> Result<u32, ReadError>;
+    fn take_u64(&mut self) -> Result<u64, ReadError>;
+    fn take_u128(&mut self) -> Result<u128, ReadError>;
+
+    /// fill the given slice with the next bytes of the source
+    fn take_into(&mut self, bytes: &mut [u8]) -> Result<(), ReadError>;
+
+    fn take_vec(&mut self, len: usize) -> Result<Vec<u8>, ReadError> {
+        let mut bytes = vec![0u8; len];
+        self.take_into(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Call `f` with a buffer over the next `len` bytes of the source.
+    ///
+    /// This is the bridge to the types only implementing
+    /// [`Readable`](crate::mempack::Readable): an in-memory buffer lends a
+    /// sub-slice of itself, a stream reads the bytes in a temporary buffer.
+    fn with_slice<T, F>(&mut self, len: usize, f: F) -> Result<T, ReadError>
+    where
+        F: FnOnce(&mut ReadBuf<'_>) -> Result<T, ReadError>;
+}
+
+impl<'a> ByteSource for ReadBuf<'a> {
+    fn take_u8(&mut self) -> Result<u8, ReadError> {
+        self.get_u8()
+    }
+    fn take_u16(&mut self) -> Result<u16, ReadError> {
+        self.get_u16()
+    }
+    fn take_u32(&mut self) -> Result<u32, ReadError> {
+        self.get_u32()
+    }
+    fn take_u64(&mut self) -> Result<u64, ReadError> {
+        self.get_u64()
+    }
+    fn take_u128(&mut self) -> Result<u128, ReadError> {
+        self.get_u128()
+    }
+    fn take_into(&mut self, bytes: &mut [u8]) -> Result<(), ReadError> {
+        self.copy_to_slice_mut(bytes)
+    }
+    fn with_slice<T, F>(&mut self, len: usize, f: F) -> Result<T, ReadError>
+    where
+        F: FnOnce(&mut ReadBuf<'_>) -> Result<T, ReadError>,
+    {
+        f(&mut self.split_to(len)?)
+    }
+}
+
+/// The position in a stream and the number of bytes left in it are not
+/// known, its errors are only reported with their kind.
+fn stream_error(error: std::io::Error) -> ReadError {
+    ReadError::Stream(error.kind())
+}
+
+impl<R: std::io::BufRead> ByteSource for Codec<R> {
+    fn take_u8(&mut self) -> Result<u8, ReadError> {
+        self.get_u8().map_err(stream_error)
+    }
+    fn take_u16(&mut self) -> Result<u16, ReadError> {
+        self.get_be_u16().map_err(stream_error)
+    }
+    fn take_u32(&mut self) -> Result<u32, ReadError> {
+        self.get_be_u32().map_err(stream_error)
+    }
+    fn take_u64(&mut self) -> Result<u64, ReadError> {
+        self.get_be_u64().map_err(stream_error)
+    }
+    fn take_u128(&mut self) -> Result<u128, ReadError> {
+        self.get_be_u128().map_err(stream_error)
+    }
+    fn take_into(&mut self, bytes: &mut [u8]) -> Result<(), ReadError> {
+        std::io::Read::read_exact(self, bytes).map_err(stream_error)
+    }
+    fn with_slice<T, F>(&mut self, len: usize, f: F) -> Result<T, ReadError>
+    where
+        F: FnOnce(&mut ReadBuf<'_>) -> Result<T, ReadError>,
+    {
+        // the bytes are copied once from the reader into a shared buffer,
+        // so the decoded value can keep slices of it instead of copying them
+        // again
+        let bytes = SharedBytes::from(self.take_vec(len)?);
+        f(&mut ReadBuf::from_shared(&bytes))
+    }
+}
+
+/// Encoding of a type, see [`impl_legacy_codecs`](crate::impl_legacy_codecs).
+pub trait Pack {
+    fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error>;
+}
+
+/// Decoding of a type from any [`ByteSource`], see
+/// [`impl_legacy_codecs`](crate::impl_legacy_codecs).
+pub trait Unpack: Sized {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError>;
+}
+
+impl<const N: usize> Unpack for [u8; N] {
+    fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+        let mut bytes = [0; N];
+        source.take_into(&mut bytes)?;
+        Ok(bytes)
+    }
+}
+
+/// Decode a `T` from a stream, for the [`Deserialize`] adapter.
+///
+/// A missing end of the stream is reported as `UnexpectedEof`, any other
+/// decoding error as `InvalidData`.
+pub fn unpack_from_reader<T: Unpack, R: std::io::BufRead>(reader: R) -> Result<T, std::io::Error> {
+    T::unpack(&mut Codec::new(reader)).map_err(std::io::Error::from)
+}
+
+/// Implement [`Serialize`], [`Deserialize`] and
+/// [`Readable`](crate::mempack::Readable) for types implementing [`Pack`]
+/// and [`Unpack`].
+///
+/// The three legacy codecs are then guaranteed to read and write the
+/// same format, instead of being maintained side by side.
+#[macro_export]
+macro_rules! impl_legacy_codecs {
+    ($($ty:ty),+ $(,)?) => {
+        $(
+        impl $crate::deser::Serialize for $ty {
+            type Error = std::io::Error;
+
+            fn serialize<W: std::io::Write>(&self, writer: W) -> Result<(), Self::Error> {
+                $crate::deser::Pack::pack(self, &mut $crate::packer::Codec::new(writer))
+            }
+        }
+
+        impl $crate::deser::Deserialize for $ty {
+            type Error = std::io::Error;
+
+            fn deserialize<R: std::io::BufRead>(reader: R) -> Result<Self, Self::Error> {
+                $crate::deser::unpack_from_reader(reader)
+            }
+        }
+
+        impl $crate::mempack::Readable for $ty {
+            fn read(
+                buf: &mut $crate::mempack::ReadBuf,
+            ) -> Result<Self, $crate::mempack::ReadError> {
+                <$ty as $crate::deser::Unpack>::unpack(buf)
+            }
+        }
+        )+
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mempack::Readable;
+    use crate::packer::WriteError;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Framed {
+        tag: u8,
+        counter: u64,
+        bytes: Vec<u8>,
+    }
+
+    impl Pack for Framed {
+        fn pack<W: std::io::Write>(&self, codec: &mut Codec<W>) -> Result<(), std::io::Error> {
+            codec.put_u8(self.tag)?;
+            codec.put_be_u64(self.counter)?;
+            codec.put_size_be_u16(self.bytes.len())?;
+            codec.put_bytes(&self.bytes)
+        }
+    }
+
+    impl Unpack for Framed {
+        fn unpack<S: ByteSource>(source: &mut S) -> Result<Self, ReadError> {
+            let tag = source.take_u8()?;
+            let counter = source.take_u64()?;
+            let len = source.take_u16()? as usize;
+            let bytes = source.with_slice(len, |buf| Ok(buf.get_slice_end().to_vec()))?;
+            Ok(Framed {
+                tag,
+                counter,
+                bytes,
+            })
+        }
+    }
+
+    crate::impl_legacy_codecs!(Framed);
+
+    fn framed() -> Framed {
+        Framed {
+            tag: 7,
+            counter: 0x0102_0304_0506_0708,
+            bytes: vec![1, 2, 3],
+        }
+    }
+
+    #[test]
+    pub fn legacy_codecs_agree() {
+        let value = framed();
+        let bytes = value.serialize_as_vec().unwrap();
+        assert_eq!(bytes, [7, 1, 2, 3, 4, 5, 6, 7, 8, 0, 3, 1, 2, 3]);
+
+        let mut buf = ReadBuf::from(&bytes);
+        assert_eq!(Framed::read(&mut buf).unwrap(), value);
+        assert!(buf.expect_end().is_ok());
+
+        let mut reader = bytes.as_slice();
+        assert_eq!(Framed::deserialize(&mut reader).unwrap(), value);
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    pub fn legacy_codecs_report_truncation() {
+        let bytes = framed().serialize_as_vec().unwrap();
+        let truncated = &bytes[..bytes.len() - 1];
+
+        assert_eq!(
+            Framed::read(&mut ReadBuf::from(truncated)),
+            Err(ReadError::NotEnoughBytes {
+                offset: 11,
+                left: 2,
+                demanded: 3
+            })
+        );
+        assert_eq!(
+            Framed::unpack(&mut Codec::new(truncated)),
+            Err(ReadError::Stream(std::io::ErrorKind::UnexpectedEof))
+        );
+        assert_eq!(
+            Framed::deserialize(truncated).unwrap_err().kind(),
+            std::io::ErrorKind::UnexpectedEof
+        );
+    }
+
+    #[test]
+    pub fn legacy_codecs_keep_the_error() {
+        let mut bytes = framed().serialize_as_vec().unwrap();
+        bytes.push(0);
+
+        let error = crate::mempack::read_from_raw::<Framed>(&bytes).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        let error = error.into_inner().unwrap().downcast::<ReadError>().unwrap();
+        assert_eq!(
+            *error,
+            ReadError::UnconsumedData {
+                offset: bytes.len() - 1,
+                left: 1
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Unconsumed data at offset {}: 1 bytes left",
+                bytes.len() - 1
+            )
+        );
+    }
+
+    #[test]
+    pub fn oversized_prefix_is_not_truncated() {
+        let value = Framed {
+            bytes: vec![0; u16::MAX as usize + 1],
+            ..framed()
+        };
+
+        let error = value.serialize_as_vec().unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        let error = error
+            .into_inner()
+            .unwrap()
+            .downcast::<WriteError>()
+            .unwrap();
+        assert!(matches!(
+            *error,
+            WriteError::SizeTooBig {
+                size: 0x10000,
+                limit: 0xffff
+            }
+        ));
+    }
+}
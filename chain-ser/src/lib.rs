@@ -2,3 +2,4 @@ pub mod abor;
 pub mod deser;
 pub mod mempack;
 pub mod packer;
+pub mod shared;
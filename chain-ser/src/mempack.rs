@@ -1,3 +1,5 @@
+use crate::shared::SharedBytes;
+use std::borrow::Cow;
 use std::error::Error;
 use std::fmt;
 use std::num::{NonZeroU32, NonZeroU64};
@@ -31,50 +33,119 @@ impl WriteBuf {
     }
 }
 
+/// Error of the decoding of a structure.
+///
+/// The variants carry the sizes, offsets and tags involved instead of a
+/// formatted description, so the errors can be matched on and reporting one
+/// does not allocate.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ReadError {
-    /// Return the number of bytes left and the number of bytes demanded
-    NotEnoughBytes(usize, usize),
-    /// Data is left in the buffer
-    UnconsumedData(usize),
+    /// At `offset`, the buffer has `left` bytes left while `demanded` bytes
+    /// are needed
+    NotEnoughBytes {
+        offset: usize,
+        left: usize,
+        demanded: usize,
+    },
+    /// Data is left in the buffer from `offset`
+    UnconsumedData { offset: usize, left: usize },
     /// Expecting a size that is above the limit
-    SizeTooBig(usize, usize),
+    SizeTooBig { size: usize, limit: usize },
+    /// A size is not the one the structure is made of
+    SizeMismatch { expected: usize, actual: usize },
+    /// Unknown tag of the enumeration `kind`
+    UnknownTag { kind: &'static str, tag: u32 },
     /// Structure of data is not what it should be
-    StructureInvalid(String),
-    /// Unknown enumeration tag
-    UnknownTag(u32),
+    StructureInvalid(Cow<'static, str>),
     /// Structure is correct but data is not valid,
     /// for example because an invariant does not hold
-    InvalidData(String),
+    InvalidData(Cow<'static, str>),
+    /// The content hash in the header of a block is not the hash of its
+    /// contents
+    ContentHashMismatch {
+        header: [u8; 32],
+        computed: [u8; 32],
+    },
+    /// The stream read from failed, with the `UnexpectedEof` kind if it
+    /// ended early: the position and the bytes left of a stream are not
+    /// known
+    Stream(std::io::ErrorKind),
+}
+
+struct Hex<'a>(&'a [u8]);
+
+impl fmt::Display for Hex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.iter().try_for_each(|byte| write!(f, "{:02x}", byte))
+    }
 }
 
 impl fmt::Display for ReadError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            ReadError::NotEnoughBytes(left, demanded) => write!(
+            ReadError::NotEnoughBytes {
+                offset,
+                left,
+                demanded,
+            } => write!(
                 f,
-                "NotEnoughBytes: demanded {} bytes but got {}",
-                demanded, left
+                "NotEnoughBytes at offset {}: demanded {} bytes but got {}",
+                offset, demanded, left
             ),
-            ReadError::UnconsumedData(len) => write!(f, "Unconsumed data: {} bytes left", len),
-            ReadError::SizeTooBig(e, limit) => write!(
+            ReadError::UnconsumedData { offset, left } => {
+                write!(
+                    f,
+                    "Unconsumed data at offset {}: {} bytes left",
+                    offset, left
+                )
+            }
+            ReadError::SizeTooBig { size, limit } => write!(
                 f,
                 "Ask for number of elements {} above expected limit value: {}",
-                e, limit
+                size, limit
             ),
+            ReadError::SizeMismatch { expected, actual } => {
+                write!(f, "Size mismatch: expected {} but got {}", expected, actual)
+            }
+            ReadError::UnknownTag { kind, tag } => write!(f, "Unknown tag of {}: {}", kind, tag),
             ReadError::StructureInvalid(s) => write!(f, "Structure invalid: {}", s),
-            ReadError::UnknownTag(t) => write!(f, "Unknown tag: {}", t),
             ReadError::InvalidData(s) => write!(f, "Invalid data: {}", s),
+            ReadError::ContentHashMismatch { header, computed } => write!(
+                f,
+                "Content hash mismatch: the header has {} but the contents hash to {}",
+                Hex(header),
+                Hex(computed)
+            ),
+            ReadError::Stream(kind) => write!(f, "Stream error: {:?}", kind),
         }
     }
 }
 
 impl Error for ReadError {}
 
+/// A missing end of the data is reported as `UnexpectedEof`, a stream error
+/// with its own kind, any other decoding error as `InvalidData`.
+impl From<ReadError> for std::io::Error {
+    fn from(error: ReadError) -> Self {
+        let kind = match error {
+            ReadError::NotEnoughBytes { .. } => std::io::ErrorKind::UnexpectedEof,
+            ReadError::Stream(kind) => kind,
+            _ => std::io::ErrorKind::InvalidData,
+        };
+        std::io::Error::new(kind, error)
+    }
+}
+
 /// A local memory slice to read from memory
 pub struct ReadBuf<'a> {
     offset: usize,
     data: &'a [u8],
+    // the position of `data` in the buffer the readbuf was split from, to
+    // report the offsets of the errors in that buffer
+    base: usize,
+    // the shared buffer `data` is a slice of, with the position of `data`
+    // in it
+    shared: Option<(&'a SharedBytes, usize)>,
     //trace: Vec<(usize, String)>,
 }
 
@@ -84,10 +155,24 @@ impl<'a> ReadBuf<'a> {
         ReadBuf {
             offset: 0,
             data: slice,
+            base: 0,
+            shared: None,
             //trace: Vec::new(),
         }
     }
 
+    /// Create a readbuf from a shared buffer: the slices returned by
+    /// `get_shared_slice` and `get_shared_slice_end` then reference the
+    /// buffer instead of copying the bytes.
+    pub fn from_shared(bytes: &'a SharedBytes) -> Self {
+        ReadBuf {
+            offset: 0,
+            data: bytes.as_ref(),
+            base: 0,
+            shared: Some((bytes, 0)),
+        }
+    }
+
     pub fn position(&self) -> usize {
         self.offset
     }
@@ -101,7 +186,11 @@ impl<'a> ReadBuf<'a> {
         if left >= expected {
             Ok(())
         } else {
-            Err(ReadError::NotEnoughBytes(left, expected))
+            Err(ReadError::NotEnoughBytes {
+                offset: self.base + self.offset,
+                left,
+                demanded: expected,
+            })
         }
     }
 
@@ -111,7 +200,10 @@ impl<'a> ReadBuf<'a> {
         if l == 0 {
             Ok(())
         } else {
-            Err(ReadError::UnconsumedData(l))
+            Err(ReadError::UnconsumedData {
+                offset: self.base + self.offset,
+                left: l,
+            })
         }
     }
 
@@ -147,10 +239,38 @@ impl<'a> ReadBuf<'a> {
         Ok(())
     }
 
+    /// Return a slice of the next bytes from the buffer, referencing the
+    /// shared buffer if the readbuf was created from one, copied otherwise
+    pub fn get_shared_slice(&mut self, sz: usize) -> Result<SharedBytes, ReadError> {
+        let start = self.offset;
+        let s = self.get_slice(sz)?;
+        Ok(self.shared_or_copy(start, s))
+    }
+
+    /// Return the remaining bytes of the buffer, see `get_shared_slice`
+    pub fn get_shared_slice_end(&mut self) -> SharedBytes {
+        let start = self.offset;
+        let s = self.get_slice_end();
+        self.shared_or_copy(start, s)
+    }
+
+    fn shared_or_copy(&self, start: usize, s: &[u8]) -> SharedBytes {
+        match self.shared {
+            Some((bytes, base)) => bytes.slice(base + start..base + start + s.len()),
+            None => SharedBytes::from(s),
+        }
+    }
+
     /// Return a sub-buffer ending at the given byte offset
     pub fn split_to(&mut self, sz: usize) -> Result<ReadBuf<'a>, ReadError> {
+        let start = self.offset;
         let slice = self.get_slice(sz)?;
-        Ok(ReadBuf::from(slice))
+        Ok(ReadBuf {
+            offset: 0,
+            data: slice,
+            base: self.base + start,
+            shared: self.shared.map(|(bytes, base)| (bytes, base + start)),
+        })
     }
 
     /// Peek at the next u8 from the buffer. the cursor is **not** advanced to the next byte.
@@ -186,8 +306,7 @@ impl<'a> ReadBuf<'a> {
 
     pub fn get_nz_u32(&mut self) -> Result<NonZeroU32, ReadError> {
         let v = self.get_u32()?;
-        NonZeroU32::new(v)
-            .ok_or_else(|| ReadError::StructureInvalid("received zero u32".to_string()))
+        NonZeroU32::new(v).ok_or_else(|| ReadError::StructureInvalid("received zero u32".into()))
     }
 
     /// Return the next u64 from the buffer
@@ -200,8 +319,7 @@ impl<'a> ReadBuf<'a> {
 
     pub fn get_nz_u64(&mut self) -> Result<NonZeroU64, ReadError> {
         let v = self.get_u64()?;
-        NonZeroU64::new(v)
-            .ok_or_else(|| ReadError::StructureInvalid("received zero u64".to_string()))
+        NonZeroU64::new(v).ok_or_else(|| ReadError::StructureInvalid("received zero u64".into()))
     }
 
     /// Return the next u128 from the buffer
@@ -307,17 +425,7 @@ pub fn read_mut_slice<'a, T: Readable>(
 /// Transform a raw buffer into a Header
 pub fn read_from_raw<T: Readable>(raw: &[u8]) -> Result<T, std::io::Error> {
     let mut rbuf = ReadBuf::from(raw);
-    match T::read(&mut rbuf) {
-        Err(e) => Err(std::io::Error::new(
-            std::io::ErrorKind::InvalidData,
-            format!("invalid data {:?} {:?}", e, raw),
-        )),
-        Ok(h) => match rbuf.expect_end() {
-            Err(e) => Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                format!("end of data {:?}", e),
-            )),
-            Ok(()) => Ok(h),
-        },
-    }
+    let h = T::read(&mut rbuf)?;
+    rbuf.expect_end()?;
+    Ok(h)
 }
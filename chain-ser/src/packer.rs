@@ -3,8 +3,56 @@
 //! This will allow us to expose some standard way of serializing
 //! data.
 
+use std::fmt;
+
 const INITIAL_BUFFERED_CAPACITY: usize = 2048;
 
+/// Error of the encoding of a structure
+#[derive(Debug)]
+pub enum WriteError {
+    /// A size is above the limit of its prefix
+    SizeTooBig { size: usize, limit: usize },
+    /// The writer failed
+    Io(std::io::Error),
+}
+
+impl fmt::Display for WriteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WriteError::SizeTooBig { size, limit } => {
+                write!(f, "Size {} above the limit of its prefix: {}", size, limit)
+            }
+            WriteError::Io(error) => write!(f, "Write error: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for WriteError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WriteError::SizeTooBig { .. } => None,
+            WriteError::Io(error) => Some(error),
+        }
+    }
+}
+
+impl From<std::io::Error> for WriteError {
+    fn from(error: std::io::Error) -> Self {
+        WriteError::Io(error)
+    }
+}
+
+/// A size above its limit is reported as `InvalidInput`, the error of the
+/// writer as is.
+impl From<WriteError> for std::io::Error {
+    fn from(error: WriteError) -> Self {
+        match error {
+            WriteError::Io(error) => error,
+            error => std::io::Error::new(std::io::ErrorKind::InvalidInput, error),
+        }
+    }
+}
+
 pub struct Codec<I>(I);
 impl<I> Codec<I> {
     pub fn new(inner: I) -> Self {
@@ -133,6 +181,27 @@ impl<W: std::io::Write> Codec<W> {
     pub fn put_bytes(&mut self, v: &[u8]) -> std::io::Result<()> {
         self.0.write_all(v)
     }
+    /// Write a size as a `u8` prefix, failing if it is above `u8::MAX`
+    pub fn put_size_u8(&mut self, size: usize) -> Result<(), WriteError> {
+        let v = u8::try_from(size).map_err(|_| size_too_big(size, u8::MAX as usize))?;
+        Ok(self.put_u8(v)?)
+    }
+    /// Write a size as a big endian `u16` prefix, failing if it is above
+    /// `u16::MAX`
+    pub fn put_size_be_u16(&mut self, size: usize) -> Result<(), WriteError> {
+        let v = u16::try_from(size).map_err(|_| size_too_big(size, u16::MAX as usize))?;
+        Ok(self.put_be_u16(v)?)
+    }
+    /// Write a size as a big endian `u32` prefix, failing if it is above
+    /// `u32::MAX`
+    pub fn put_size_be_u32(&mut self, size: usize) -> Result<(), WriteError> {
+        let v = u32::try_from(size).map_err(|_| size_too_big(size, u32::MAX as usize))?;
+        Ok(self.put_be_u32(v)?)
+    }
+}
+
+fn size_too_big(size: usize, limit: usize) -> WriteError {
+    WriteError::SizeTooBig { size, limit }
 }
 impl<W: std::io::Write> Buffered<W> {
     #[inline]
@@ -0,0 +1,168 @@
+//! Reference counted byte buffers, to parse structures that keep cheap
+//! slices of the buffer they were read from instead of copies.
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::{Deref, Range};
+use std::sync::Arc;
+
+/// A slice of a reference counted buffer.
+///
+/// Cloning and slicing do not copy the bytes, the buffer is freed when
+/// the last slice referencing it is dropped.
+#[derive(Clone)]
+pub struct SharedBytes {
+    buffer: Arc<[u8]>,
+    start: usize,
+    end: usize,
+}
+
+impl SharedBytes {
+    pub fn new(buffer: Arc<[u8]>) -> Self {
+        let end = buffer.len();
+        SharedBytes {
+            buffer,
+            start: 0,
+            end,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Return a slice of the given range of this slice, sharing the same
+    /// buffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of the bounds of this slice.
+    pub fn slice(&self, range: Range<usize>) -> Self {
+        assert!(
+            range.start <= range.end && range.end <= self.len(),
+            "range {:?} out of the bounds of a slice of {} bytes",
+            range,
+            self.len()
+        );
+        SharedBytes {
+            buffer: Arc::clone(&self.buffer),
+            start: self.start + range.start,
+            end: self.start + range.end,
+        }
+    }
+
+    /// Check if both slices reference the same buffer, regardless of the
+    /// range of the buffer they cover
+    pub fn shares_buffer_with(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.buffer, &other.buffer)
+    }
+}
+
+impl Deref for SharedBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.buffer[self.start..self.end]
+    }
+}
+
+impl AsRef<[u8]> for SharedBytes {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl PartialEq for SharedBytes {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_ref() == other.as_ref()
+    }
+}
+
+impl Eq for SharedBytes {}
+
+impl Hash for SharedBytes {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_ref().hash(state)
+    }
+}
+
+impl fmt::Debug for SharedBytes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("SharedBytes").field(&self.as_ref()).finish()
+    }
+}
+
+impl From<Arc<[u8]>> for SharedBytes {
+    fn from(buffer: Arc<[u8]>) -> Self {
+        Self::new(buffer)
+    }
+}
+
+impl From<Box<[u8]>> for SharedBytes {
+    fn from(bytes: Box<[u8]>) -> Self {
+        Self::new(bytes.into())
+    }
+}
+
+impl From<Vec<u8>> for SharedBytes {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self::new(bytes.into())
+    }
+}
+
+impl<'a> From<&'a [u8]> for SharedBytes {
+    fn from(bytes: &'a [u8]) -> Self {
+        Self::new(bytes.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mempack::ReadBuf;
+
+    #[test]
+    fn slices_share_the_buffer() {
+        let bytes = SharedBytes::from(vec![0, 1, 2, 3, 4, 5, 6, 7]);
+        let slice = bytes.slice(2..6);
+        assert_eq!(slice.as_ref(), &[2, 3, 4, 5]);
+        let sub_slice = slice.slice(1..3);
+        assert_eq!(sub_slice.as_ref(), &[3, 4]);
+        assert!(sub_slice.shares_buffer_with(&bytes));
+        assert!(slice.slice(4..4).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn slice_out_of_bounds() {
+        SharedBytes::from(vec![0, 1, 2, 3]).slice(2..5);
+    }
+
+    #[test]
+    fn read_buf_slices_share_the_buffer() {
+        let bytes = SharedBytes::from(vec![0, 1, 2, 3, 4, 5, 6, 7]);
+        let mut buf = ReadBuf::from_shared(&bytes);
+        assert_eq!(buf.get_u8().unwrap(), 0);
+        let mut sub_buf = buf.split_to(4).unwrap();
+        assert_eq!(sub_buf.get_u8().unwrap(), 1);
+        let slice = sub_buf.get_shared_slice(2).unwrap();
+        assert_eq!(slice.as_ref(), &[2, 3]);
+        assert!(slice.shares_buffer_with(&bytes));
+        let end = buf.get_shared_slice_end();
+        assert_eq!(end.as_ref(), &[5, 6, 7]);
+        assert!(end.shares_buffer_with(&bytes));
+    }
+
+    #[test]
+    fn read_buf_slices_are_copied_without_shared_buffer() {
+        let bytes = vec![0, 1, 2, 3];
+        let mut buf = ReadBuf::from(&bytes);
+        assert_eq!(buf.get_shared_slice(3).unwrap().as_ref(), &[0, 1, 2]);
+        assert_eq!(buf.get_shared_slice_end().as_ref(), &[3]);
+        assert!(buf.get_shared_slice(1).is_err());
+    }
+}
@@ -0,0 +1,108 @@
+use crate::{BlockInfo, BlockStore, Error};
+
+/// The point where two branches of the block chain diverge.
+#[derive(Clone)]
+pub struct ForkPoint {
+    /// The deepest block both branches have in common.
+    pub ancestor: BlockInfo,
+    /// The number of blocks of the first branch after the common ancestor.
+    pub first_branch_length: u32,
+    /// The number of blocks of the second branch after the common ancestor.
+    pub second_branch_length: u32,
+}
+
+/// Sparse list of the ancestors of a block: the block itself, then the
+/// ancestors at distance 1, 2, 4, 8, ... and finally the first block of the
+/// chain. The checkpoints are ordered by decreasing chain length.
+#[derive(Clone)]
+pub struct Checkpoints(Vec<BlockInfo>);
+
+impl Checkpoints {
+    pub fn iter(&self) -> std::slice::Iter<'_, BlockInfo> {
+        self.0.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl BlockStore {
+    /// Get the sparse checkpoints of the chain ending with the block
+    /// identified by `tip`, see `Checkpoints`.
+    pub fn checkpoints(&self, tip: &[u8]) -> Result<Checkpoints, Error> {
+        let tip = self.get_block_info(tip)?;
+        let chain_length = tip.chain_length();
+        let mut checkpoints = vec![tip.clone()];
+        let mut distance = 1;
+        while distance < chain_length {
+            checkpoints.push(self.get_nth_ancestor(tip.id().as_ref(), distance)?);
+            distance = distance.saturating_mul(2);
+        }
+        if chain_length > 0 {
+            checkpoints.push(self.get_nth_ancestor(tip.id().as_ref(), chain_length)?);
+        }
+        Ok(Checkpoints(checkpoints))
+    }
+
+    /// Find the point where the chains ending with the blocks `tip1` and
+    /// `tip2` diverge.
+    ///
+    /// The checkpoints of the second chain give a range of chain lengths
+    /// between a common block and a block only in the second chain, the fork
+    /// point is then found by a binary search by chain length within this
+    /// range. Each step only needs an ancestor of the tips at a given chain
+    /// length, which is a direct lookup for the blocks of the permanent store.
+    ///
+    /// Returns `Ok(None)` when the chains have no common block, that is when
+    /// they start from different blocks of chain length 0.
+    pub fn find_fork_point(&self, tip1: &[u8], tip2: &[u8]) -> Result<Option<ForkPoint>, Error> {
+        let tip1 = self.get_block_info(tip1)?;
+        let tip2 = self.get_block_info(tip2)?;
+
+        // bring both chains to the same chain length
+        let height = std::cmp::min(tip1.chain_length(), tip2.chain_length());
+        let top1 = self.get_nth_ancestor(tip1.id().as_ref(), tip1.chain_length() - height)?;
+        let top2 = self.get_nth_ancestor(tip2.id().as_ref(), tip2.chain_length() - height)?;
+
+        let common_at = |chain_length: u32| -> Result<Option<BlockInfo>, Error> {
+            let block1 = self.get_nth_ancestor(top1.id().as_ref(), height - chain_length)?;
+            let block2 = self.get_nth_ancestor(top2.id().as_ref(), height - chain_length)?;
+            Ok(Some(block1).filter(|block1| block1.id() == block2.id()))
+        };
+
+        // the deepest checkpoint which is common to both chains, and the
+        // chain length of the checkpoint before it, which is not common
+        let mut not_common = height + 1;
+        let mut common = None;
+        for checkpoint in self.checkpoints(top2.id().as_ref())?.iter() {
+            if let Some(block) = common_at(checkpoint.chain_length())? {
+                common = Some(block);
+                break;
+            }
+            not_common = checkpoint.chain_length();
+        }
+        let mut common = match common {
+            Some(common) => common,
+            None => return Ok(None),
+        };
+
+        while not_common - common.chain_length() > 1 {
+            let middle = common.chain_length() + (not_common - common.chain_length()) / 2;
+            match common_at(middle)? {
+                Some(block) => common = block,
+                None => not_common = middle,
+            }
+        }
+
+        Ok(Some(ForkPoint {
+            first_branch_length: tip1.chain_length() - common.chain_length(),
+            second_branch_length: tip2.chain_length() - common.chain_length(),
+            ancestor: common,
+        }))
+    }
+}
@@ -138,6 +138,7 @@
 mod block_info;
 mod block_store;
 mod error;
+mod fork;
 mod iterator;
 mod permanent_store;
 #[cfg(any(test, feature = "with-bench"))]
@@ -149,5 +150,6 @@ mod value;
 pub use block_info::BlockInfo;
 pub use block_store::BlockStore;
 pub use error::{ConsistencyFailure, Error};
+pub use fork::{Checkpoints, ForkPoint};
 pub use iterator::StorageIterator;
 pub use value::Value;
@@ -552,6 +552,118 @@ fn lca_genesis() {
         .is_none())
 }
 
+#[test]
+fn checkpoints_distances() {
+    let (_, store, main_branch, _) =
+        generate_two_branches(MAIN_BRANCH_LEN, SECOND_BRANCH_LEN, BIFURCATION_POINT);
+    let tip = main_branch.last().unwrap();
+    let chain_lengths: Vec<u32> = store
+        .checkpoints(&tip.id.serialize_as_vec())
+        .unwrap()
+        .iter()
+        .map(|checkpoint| checkpoint.chain_length())
+        .collect();
+    assert_eq!(chain_lengths, vec![99, 98, 97, 95, 91, 83, 67, 35, 0]);
+}
+
+#[test]
+fn fork_point_different_branches() {
+    let (_, store, main_branch, second_branch) =
+        generate_two_branches(MAIN_BRANCH_LEN, SECOND_BRANCH_LEN, BIFURCATION_POINT);
+    let fork_point = store
+        .find_fork_point(
+            &main_branch.last().unwrap().id.serialize_as_vec(),
+            &second_branch.last().unwrap().id.serialize_as_vec(),
+        )
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        fork_point.ancestor.id().as_ref(),
+        &main_branch[BIFURCATION_POINT].id.serialize_as_vec()[..]
+    );
+    assert_eq!(
+        fork_point.first_branch_length as usize,
+        MAIN_BRANCH_LEN - 1 - BIFURCATION_POINT
+    );
+    assert_eq!(
+        fork_point.second_branch_length as usize,
+        SECOND_BRANCH_LEN - 1
+    );
+}
+
+#[test]
+fn fork_point_ancestor() {
+    const ANCESTOR: usize = 10;
+
+    let (_, store, main_branch, _) =
+        generate_two_branches(MAIN_BRANCH_LEN, SECOND_BRANCH_LEN, BIFURCATION_POINT);
+    let fork_point = store
+        .find_fork_point(
+            &main_branch[ANCESTOR].id.serialize_as_vec(),
+            &main_branch.last().unwrap().id.serialize_as_vec(),
+        )
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        fork_point.ancestor.id().as_ref(),
+        &main_branch[ANCESTOR].id.serialize_as_vec()[..]
+    );
+    assert_eq!(fork_point.first_branch_length, 0);
+    assert_eq!(
+        fork_point.second_branch_length as usize,
+        MAIN_BRANCH_LEN - 1 - ANCESTOR
+    );
+}
+
+#[test]
+fn fork_point_permanent_volatile() {
+    let (_, store, main_branch, second_branch) =
+        generate_two_branches(MAIN_BRANCH_LEN, SECOND_BRANCH_LEN, BIFURCATION_POINT);
+    store
+        .flush_to_permanent_store(
+            &main_branch[BIFURCATION_POINT - 10].id.serialize_as_vec(),
+            1,
+        )
+        .unwrap();
+    let fork_point = store
+        .find_fork_point(
+            &second_branch.last().unwrap().id.serialize_as_vec(),
+            &main_branch.last().unwrap().id.serialize_as_vec(),
+        )
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        fork_point.ancestor.id().as_ref(),
+        &main_branch[BIFURCATION_POINT].id.serialize_as_vec()[..]
+    );
+    assert_eq!(
+        fork_point.first_branch_length as usize,
+        SECOND_BRANCH_LEN - 1
+    );
+}
+
+#[test]
+fn fork_point_different_genesis() {
+    let (_, store, main_branch, _) =
+        generate_two_branches(MAIN_BRANCH_LEN, SECOND_BRANCH_LEN, BIFURCATION_POINT);
+    let genesis_block = Block::genesis(None);
+    let genesis_block_info = BlockInfo::new(
+        genesis_block.id.serialize_as_vec(),
+        genesis_block.parent.serialize_as_vec(),
+        genesis_block.chain_length,
+    );
+    store
+        .put_block(&genesis_block.serialize_as_vec(), genesis_block_info)
+        .unwrap();
+    assert!(store
+        .find_fork_point(
+            &main_branch.last().unwrap().id.serialize_as_vec(),
+            &genesis_block.id.serialize_as_vec(),
+        )
+        .unwrap()
+        .is_none());
+}
+
 #[test]
 fn is_ancestor_same_branch() {
     const FIRST: usize = 20;
@@ -0,0 +1,256 @@
+//! Map the slots to the wall clock with a tolerance.
+//!
+//! The timeline is rooted on the UNIX time, which does not count the leap
+//! seconds: a slot maps to the same UNIX time on every node, but around a leap
+//! second the clocks of the nodes are stepped or smeared and disagree by up to
+//! a second, on top of their usual skew. The slots are thus compared to the
+//! wall clock with an explicit tolerance instead of exactly.
+
+use crate::era::{EpochPosition, TimeEra};
+use crate::timeframe::{Slot, TimeFrame};
+use std::collections::VecDeque;
+use std::time::{Duration, SystemTime};
+
+/// Tolerance covering the disagreement of the clocks around a leap second
+pub const LEAP_SECOND_TOLERANCE: Duration = Duration::from_secs(1);
+
+/// Position of a slot relative to the wall clock
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlotVerdict {
+    /// the slot ended before the wall clock, beyond the tolerance
+    Past,
+    /// the slot is the current one, within the tolerance
+    Current,
+    /// the slot starts after the wall clock, beyond the tolerance
+    Future { ahead: Duration },
+    /// the slot is not part of the time frame
+    OutOfFrame,
+}
+
+impl TimeFrame {
+    /// The wall clock window in which the slot can be current, its start and
+    /// its end widened by the tolerance
+    pub fn slot_window(&self, slot: Slot, tolerance: Duration) -> Option<(SystemTime, SystemTime)> {
+        let start = self.slot_to_systemtime(slot)?;
+        let end = start + Duration::from_secs(self.slot_duration());
+        Some((
+            start.checked_sub(tolerance).unwrap_or(start),
+            end + tolerance,
+        ))
+    }
+
+    /// Position of the slot relative to the wall clock `now`
+    pub fn check_slot(&self, slot: Slot, now: &SystemTime, tolerance: Duration) -> SlotVerdict {
+        match self.slot_window(slot, tolerance) {
+            None => SlotVerdict::OutOfFrame,
+            Some((start, _)) if start > *now => SlotVerdict::Future {
+                ahead: start.duration_since(*now).unwrap_or_default(),
+            },
+            Some((_, end)) if end <= *now => SlotVerdict::Past,
+            Some(_) => SlotVerdict::Current,
+        }
+    }
+
+    /// Position of the epoch position of the era relative to the wall clock
+    /// `now`, a position out of the era is out of the frame
+    pub fn check_position(
+        &self,
+        era: &TimeEra,
+        position: EpochPosition,
+        now: &SystemTime,
+        tolerance: Duration,
+    ) -> SlotVerdict {
+        match era.checked_from_era_to_slot(position) {
+            Some(slot) => self.check_slot(slot, now, tolerance),
+            None => SlotVerdict::OutOfFrame,
+        }
+    }
+}
+
+/// Consistency of the time frame with the observed wall clock
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DriftVerdict {
+    /// not enough observations to decide
+    Unknown,
+    /// the headers are received at the time of their slot, within the
+    /// tolerance
+    Consistent,
+    /// the headers are received consistently early (negative) or late
+    /// (positive): the local clock or the start of the timeline is off
+    ClockOffset { offset_millis: i64 },
+    /// the time between the headers does not match the slot duration of the
+    /// time frame
+    SlotDurationMismatch {
+        observed_millis_per_slot: u64,
+        configured_millis_per_slot: u64,
+    },
+}
+
+/// Detect a drift between the time frame and the wall clock from the time
+/// the headers of fresh blocks are received at, over a sliding window of
+/// observations
+#[derive(Debug, Clone)]
+pub struct DriftDetector {
+    time_frame: TimeFrame,
+    window: usize,
+    /// the slot of the header and the offset in milliseconds of its reception
+    /// from the start of the slot
+    observations: VecDeque<(Slot, i64)>,
+}
+
+fn signed_millis(from: SystemTime, to: SystemTime) -> i64 {
+    match to.duration_since(from) {
+        Ok(d) => d.as_millis() as i64,
+        Err(e) => -(e.duration().as_millis() as i64),
+    }
+}
+
+impl DriftDetector {
+    pub fn new(time_frame: TimeFrame, window: usize) -> Self {
+        assert!(window >= 2);
+        DriftDetector {
+            time_frame,
+            window,
+            observations: VecDeque::with_capacity(window),
+        }
+    }
+
+    /// Record the reception of the header of a block of `slot`, the slots out
+    /// of the time frame are ignored
+    pub fn observe(&mut self, slot: Slot, received_at: SystemTime) {
+        if let Some(start) = self.time_frame.slot_to_systemtime(slot) {
+            if self.observations.len() == self.window {
+                self.observations.pop_front();
+            }
+            self.observations
+                .push_back((slot, signed_millis(start, received_at)));
+        }
+    }
+
+    /// The drift is a change of the reception offset over the window larger
+    /// than the tolerance, an offset is the median offset being larger than
+    /// the tolerance
+    pub fn verdict(&self, tolerance: Duration) -> DriftVerdict {
+        let tolerance = tolerance.as_millis() as i64;
+        let first = self.observations.iter().min_by_key(|(slot, _)| *slot);
+        let last = self.observations.iter().max_by_key(|(slot, _)| *slot);
+        let ((first_slot, first_offset), (last_slot, last_offset)) = match (first, last) {
+            (Some(first), Some(last)) if first.0 < last.0 => (*first, *last),
+            _ => return DriftVerdict::Unknown,
+        };
+
+        if (last_offset - first_offset).abs() > tolerance {
+            let slots = u64::from(last_slot) - u64::from(first_slot);
+            let configured_millis_per_slot = self.time_frame.slot_duration() * 1000;
+            let observed =
+                configured_millis_per_slot as i64 * slots as i64 + last_offset - first_offset;
+            return DriftVerdict::SlotDurationMismatch {
+                observed_millis_per_slot: observed.max(0) as u64 / slots,
+                configured_millis_per_slot,
+            };
+        }
+
+        let mut offsets: Vec<i64> = self.observations.iter().map(|(_, o)| *o).collect();
+        offsets.sort_unstable();
+        let median = offsets[offsets.len() / 2];
+        if median.abs() > tolerance {
+            DriftVerdict::ClockOffset {
+                offset_millis: median,
+            }
+        } else {
+            DriftVerdict::Consistent
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::era::{Epoch, EpochSlotOffset};
+    use crate::timeframe::SlotDuration;
+    use crate::timeline::Timeline;
+
+    fn time_frame(start: SystemTime) -> TimeFrame {
+        TimeFrame::new(Timeline::new(start), SlotDuration::from_secs(10))
+    }
+
+    #[test]
+    pub fn slots_are_checked_against_the_wall_clock() {
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+        let tf = time_frame(start);
+        let now = start + Duration::from_secs(25);
+
+        assert_eq!(
+            tf.check_slot(Slot(2), &now, LEAP_SECOND_TOLERANCE),
+            SlotVerdict::Current
+        );
+        assert_eq!(
+            tf.check_slot(Slot(1), &now, LEAP_SECOND_TOLERANCE),
+            SlotVerdict::Past
+        );
+        // the next slot starts within 5 seconds
+        assert_eq!(
+            tf.check_slot(Slot(3), &now, Duration::from_secs(5)),
+            SlotVerdict::Current
+        );
+        assert_eq!(
+            tf.check_slot(Slot(10), &now, LEAP_SECOND_TOLERANCE),
+            SlotVerdict::Future {
+                ahead: Duration::from_secs(74)
+            }
+        );
+
+        let era = TimeEra::new(Slot(0), Epoch(0), 4);
+        let position = |epoch, slot| EpochPosition {
+            epoch: Epoch(epoch),
+            slot: EpochSlotOffset(slot),
+        };
+        assert_eq!(
+            tf.check_position(&era, position(0, 2), &now, LEAP_SECOND_TOLERANCE),
+            SlotVerdict::Current
+        );
+        assert_eq!(
+            tf.check_position(&era, position(0, 4), &now, LEAP_SECOND_TOLERANCE),
+            SlotVerdict::OutOfFrame
+        );
+
+        let later = tf.change_frame(Slot(5), SlotDuration::from_secs(10));
+        assert_eq!(
+            later.check_slot(Slot(1), &now, LEAP_SECOND_TOLERANCE),
+            SlotVerdict::OutOfFrame
+        );
+    }
+
+    #[test]
+    pub fn drift_is_detected_from_the_received_headers() {
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+        let tolerance = Duration::from_secs(2);
+        let observe = |secs_per_slot: u64, delay: u64| {
+            let mut detector = DriftDetector::new(time_frame(start), 8);
+            for slot in 0..10 {
+                let received = start + Duration::from_secs(slot * secs_per_slot + delay);
+                detector.observe(Slot(slot), received);
+            }
+            detector.verdict(tolerance)
+        };
+
+        assert_eq!(
+            DriftDetector::new(time_frame(start), 8).verdict(tolerance),
+            DriftVerdict::Unknown
+        );
+        assert_eq!(observe(10, 1), DriftVerdict::Consistent);
+        assert_eq!(
+            observe(10, 5),
+            DriftVerdict::ClockOffset {
+                offset_millis: 5_000
+            }
+        );
+        assert_eq!(
+            observe(12, 0),
+            DriftVerdict::SlotDurationMismatch {
+                observed_millis_per_slot: 12_000,
+                configured_millis_per_slot: 10_000,
+            }
+        );
+    }
+}
@@ -107,6 +107,16 @@ impl TimeEra {
         let slot_offset = (pos.epoch.0 as u64) * (self.slots_per_epoch as u64) + pos.slot.0 as u64;
         Slot(self.slot_start.0 + slot_offset)
     }
+
+    /// Same as [`from_era_to_slot`](Self::from_era_to_slot), but returns
+    /// None instead of panicking if the position is not part of this era
+    pub fn checked_from_era_to_slot(&self, pos: EpochPosition) -> Option<Slot> {
+        if pos.epoch < self.epoch_start || pos.slot.0 >= self.slots_per_epoch {
+            None
+        } else {
+            Some(self.from_era_to_slot(pos))
+        }
+    }
 }
 
 #[cfg(any(test))]
@@ -1,8 +1,10 @@
+pub mod clock;
 pub mod era;
 pub mod timeframe;
 pub mod timeline;
 pub mod units;
 
+pub use clock::{DriftDetector, DriftVerdict, SlotVerdict, LEAP_SECOND_TOLERANCE};
 pub use era::{Epoch, TimeEra};
 pub use timeframe::{Slot, SlotDuration, TimeFrame};
 pub use timeline::{TimeOffsetSeconds, Timeline};
@@ -0,0 +1,318 @@
+//! Encrypted backup of the secret key of a committee member.
+//!
+//! The secret key is encrypted with ChaCha20-Poly1305 under a key derived
+//! from a password with scrypt, in the spirit of the scrypt recipient of
+//! `age`. The backup is a self-describing binary blob:
+//!
+//! ```text
+//!     MAGIC (8) || VERSION (1) || LOG_N (1) || R (4) || P (4) || SALT (16)
+//!     || NONCE (12) || MEMBER PUBLIC KEY || ENCRYPTED SECRET KEY (32) || TAG (16)
+//! ```
+//!
+//! Everything before the encrypted secret key is authenticated as the
+//! associated data, so the scrypt parameters and the public key cannot be
+//! altered without the decryption failing. The public key is left in clear
+//! so the tooling can tell which member a backup belongs to without the
+//! password, and is checked against the decrypted secret key.
+//!
+//! The derived key and the secret key bytes are zeroized once used.
+
+use crate::committee::{MemberPublicKey, MemberSecretKey};
+use cryptoxide::chacha20poly1305::ChaCha20Poly1305;
+use rand_core::{CryptoRng, RngCore};
+use zeroize::Zeroizing;
+
+/// Magic written as the first bytes of every backup
+pub const BACKUP_MAGIC: [u8; 8] = *b"CVMSKBK\0";
+/// Version of the backup format
+pub const BACKUP_VERSION: u8 = 1;
+
+const SALT_SIZE: usize = 16;
+const NONCE_SIZE: usize = 12;
+const SECRET_KEY_SIZE: usize = 32;
+const TAG_SIZE: usize = 16;
+const PARAMS_SIZE: usize = 1 + 4 + 4;
+const HEADER_SIZE: usize =
+    BACKUP_MAGIC.len() + 1 + PARAMS_SIZE + SALT_SIZE + NONCE_SIZE + MemberPublicKey::BYTES_LEN;
+
+/// Maximum scrypt work factor `log2(N)` accepted in a backup, like the
+/// default limit of `age`: the parameters are read from the backup before
+/// the password is checked, so they must not be allowed to make the key
+/// derivation run for hours or exhaust the memory
+pub const MAX_LOG_N: u8 = 22;
+/// Maximum `r * p` accepted in a backup, the one of the default parameters,
+/// so with `MAX_LOG_N` the key derivation uses at most 4GiB of memory
+pub const MAX_R_P: u64 = 8;
+
+/// Size of a backup
+pub const BACKUP_SIZE: usize = HEADER_SIZE + SECRET_KEY_SIZE + TAG_SIZE;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BackupError {
+    #[error("not a member secret key backup")]
+    InvalidMagic,
+    #[error("unsupported backup version {0}")]
+    UnsupportedVersion(u8),
+    #[error("a backup is {expected} bytes, not {actual}")]
+    InvalidSize { expected: usize, actual: usize },
+    #[error("invalid scrypt parameters")]
+    InvalidParams,
+    #[error("invalid member public key")]
+    InvalidPublicKey,
+    #[error("wrong password or corrupted backup")]
+    DecryptionFailed,
+    #[error("the decrypted secret key is invalid")]
+    InvalidSecretKey,
+    #[error("the decrypted secret key does not match the public key of the backup")]
+    PublicKeyMismatch,
+}
+
+/// The scrypt parameters of the derivation of the encryption key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BackupParams {
+    log_n: u8,
+    r: u32,
+    p: u32,
+}
+
+impl BackupParams {
+    /// The parameters beyond the maximum work factor (`MAX_LOG_N` and
+    /// `MAX_R_P`) are invalid
+    pub fn new(log_n: u8, r: u32, p: u32) -> Result<Self, BackupError> {
+        if log_n > MAX_LOG_N || u64::from(r) * u64::from(p) > MAX_R_P {
+            return Err(BackupError::InvalidParams);
+        }
+        scrypt::Params::new(log_n, r, p).map_err(|_| BackupError::InvalidParams)?;
+        Ok(BackupParams { log_n, r, p })
+    }
+
+    pub fn log_n(&self) -> u8 {
+        self.log_n
+    }
+
+    fn derive_key(&self, password: &[u8], salt: &[u8]) -> Result<Zeroizing<[u8; 32]>, BackupError> {
+        let params = scrypt::Params::new(self.log_n, self.r, self.p)
+            .map_err(|_| BackupError::InvalidParams)?;
+        let mut key = Zeroizing::new([0u8; 32]);
+        scrypt::scrypt(password, salt, &params, &mut key[..])
+            .map_err(|_| BackupError::InvalidParams)?;
+        Ok(key)
+    }
+}
+
+impl Default for BackupParams {
+    /// the work factor recommended by `age`, about a second on a laptop
+    fn default() -> Self {
+        BackupParams {
+            log_n: 18,
+            r: 8,
+            p: 1,
+        }
+    }
+}
+
+/// The encrypted backup of the secret key of a committee member
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemberSecretKeyBackup(Box<[u8; BACKUP_SIZE]>);
+
+impl MemberSecretKeyBackup {
+    /// Encrypt the secret key with the password
+    pub fn encrypt<R: RngCore + CryptoRng>(
+        rng: &mut R,
+        secret_key: &MemberSecretKey,
+        password: &[u8],
+        params: BackupParams,
+    ) -> Result<Self, BackupError> {
+        let mut salt = [0u8; SALT_SIZE];
+        rng.fill_bytes(&mut salt);
+        let mut nonce = [0u8; NONCE_SIZE];
+        rng.fill_bytes(&mut nonce);
+
+        let mut bytes = Box::new([0u8; BACKUP_SIZE]);
+        let header = [
+            &BACKUP_MAGIC[..],
+            &[BACKUP_VERSION, params.log_n][..],
+            &params.r.to_be_bytes()[..],
+            &params.p.to_be_bytes()[..],
+            &salt[..],
+            &nonce[..],
+            &secret_key.to_public().to_bytes()[..],
+        ]
+        .concat();
+        bytes[..HEADER_SIZE].copy_from_slice(&header);
+
+        let key = params.derive_key(password, &salt)?;
+        let plain = Zeroizing::new(secret_key.to_bytes());
+        let mut context = ChaCha20Poly1305::new(&key[..], &nonce, &header);
+        let (encrypted, tag) = bytes[HEADER_SIZE..].split_at_mut(SECRET_KEY_SIZE);
+        context.encrypt(&plain[..], encrypted, tag);
+        Ok(MemberSecretKeyBackup(bytes))
+    }
+
+    /// Check the header of the backup, the secret key is only checked on
+    /// decryption
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BackupError> {
+        let bytes: [u8; BACKUP_SIZE] = bytes.try_into().map_err(|_| BackupError::InvalidSize {
+            expected: BACKUP_SIZE,
+            actual: bytes.len(),
+        })?;
+        let backup = MemberSecretKeyBackup(Box::new(bytes));
+        if backup.0[..BACKUP_MAGIC.len()] != BACKUP_MAGIC {
+            return Err(BackupError::InvalidMagic);
+        }
+        let version = backup.0[BACKUP_MAGIC.len()];
+        if version != BACKUP_VERSION {
+            return Err(BackupError::UnsupportedVersion(version));
+        }
+        backup.params()?;
+        backup.public_key()?;
+        Ok(backup)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+
+    fn field(&self, offset: usize, size: usize) -> &[u8] {
+        &self.0[offset..offset + size]
+    }
+
+    pub fn params(&self) -> Result<BackupParams, BackupError> {
+        let offset = BACKUP_MAGIC.len() + 1;
+        let r = self.field(offset + 1, 4).try_into().unwrap();
+        let p = self.field(offset + 5, 4).try_into().unwrap();
+        BackupParams::new(self.0[offset], u32::from_be_bytes(r), u32::from_be_bytes(p))
+    }
+
+    /// The public key of the member whose secret key is backed up
+    pub fn public_key(&self) -> Result<MemberPublicKey, BackupError> {
+        let offset = HEADER_SIZE - MemberPublicKey::BYTES_LEN;
+        MemberPublicKey::from_bytes(self.field(offset, MemberPublicKey::BYTES_LEN))
+            .ok_or(BackupError::InvalidPublicKey)
+    }
+
+    /// Decrypt the secret key with the password
+    pub fn decrypt(&self, password: &[u8]) -> Result<MemberSecretKey, BackupError> {
+        let salt_offset = BACKUP_MAGIC.len() + 1 + PARAMS_SIZE;
+        let salt = self.field(salt_offset, SALT_SIZE);
+        let nonce = self.field(salt_offset + SALT_SIZE, NONCE_SIZE);
+        let header = self.field(0, HEADER_SIZE);
+        let encrypted = self.field(HEADER_SIZE, SECRET_KEY_SIZE);
+        let tag = self.field(HEADER_SIZE + SECRET_KEY_SIZE, TAG_SIZE);
+
+        let key = self.params()?.derive_key(password, salt)?;
+        let mut plain = Zeroizing::new([0u8; SECRET_KEY_SIZE]);
+        let mut context = ChaCha20Poly1305::new(&key[..], nonce, header);
+        if !context.decrypt(encrypted, &mut plain[..], tag) {
+            return Err(BackupError::DecryptionFailed);
+        }
+        let secret_key =
+            MemberSecretKey::from_bytes(&plain[..]).ok_or(BackupError::InvalidSecretKey)?;
+        if secret_key.to_public() != self.public_key()? {
+            return Err(BackupError::PublicKeyMismatch);
+        }
+        Ok(secret_key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{committee::MemberCommunicationKey, Crs, MemberState};
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+
+    fn member_secret_key(rng: &mut ChaCha20Rng) -> MemberSecretKey {
+        let h = Crs::from_hash(b"member secret key backup");
+        let mc = [MemberCommunicationKey::new(rng).to_public()];
+        MemberState::new(rng, 1, &h, &mc, 0).secret_key().clone()
+    }
+
+    fn fast_params() -> BackupParams {
+        BackupParams::new(4, 8, 1).unwrap()
+    }
+
+    #[test]
+    fn backup_roundtrip() {
+        let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+        let secret_key = member_secret_key(&mut rng);
+        let backup =
+            MemberSecretKeyBackup::encrypt(&mut rng, &secret_key, b"password", fast_params())
+                .unwrap();
+
+        let backup = MemberSecretKeyBackup::from_bytes(backup.as_bytes()).unwrap();
+        assert_eq!(backup.params(), Ok(fast_params()));
+        assert_eq!(backup.public_key(), Ok(secret_key.to_public()));
+        let decrypted = backup.decrypt(b"password").unwrap();
+        assert_eq!(decrypted.to_bytes(), secret_key.to_bytes());
+
+        assert_eq!(
+            backup.decrypt(b"wrong password").map(|_| ()),
+            Err(BackupError::DecryptionFailed)
+        );
+    }
+
+    #[test]
+    fn excessive_work_factor_is_rejected() {
+        assert!(BackupParams::new(MAX_LOG_N, 8, 1).is_ok());
+        assert_eq!(
+            BackupParams::new(MAX_LOG_N + 1, 8, 1),
+            Err(BackupError::InvalidParams)
+        );
+        assert_eq!(BackupParams::new(4, 8, 2), Err(BackupError::InvalidParams));
+
+        let mut rng = ChaCha20Rng::from_seed([2u8; 32]);
+        let secret_key = member_secret_key(&mut rng);
+        let backup =
+            MemberSecretKeyBackup::encrypt(&mut rng, &secret_key, b"password", fast_params())
+                .unwrap();
+
+        // the parameters are checked before deriving anything from them
+        let log_n_offset = BACKUP_MAGIC.len() + 1;
+        let mut bytes = backup.as_bytes().to_vec();
+        bytes[log_n_offset] = 60;
+        assert_eq!(
+            MemberSecretKeyBackup::from_bytes(&bytes).map(|_| ()),
+            Err(BackupError::InvalidParams)
+        );
+        let mut tampered = backup.clone();
+        tampered.0[log_n_offset + 1..log_n_offset + 5].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert_eq!(
+            tampered.decrypt(b"password").map(|_| ()),
+            Err(BackupError::InvalidParams)
+        );
+    }
+
+    #[test]
+    fn tampered_backup_is_rejected() {
+        let mut rng = ChaCha20Rng::from_seed([1u8; 32]);
+        let secret_key = member_secret_key(&mut rng);
+        let backup =
+            MemberSecretKeyBackup::encrypt(&mut rng, &secret_key, b"password", fast_params())
+                .unwrap();
+
+        // the public key is authenticated
+        let other = member_secret_key(&mut rng).to_public().to_bytes();
+        let mut bytes = backup.as_bytes().to_vec();
+        bytes[HEADER_SIZE - MemberPublicKey::BYTES_LEN..HEADER_SIZE].copy_from_slice(&other);
+        let tampered = MemberSecretKeyBackup::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            tampered.decrypt(b"password").map(|_| ()),
+            Err(BackupError::DecryptionFailed)
+        );
+
+        let mut bytes = backup.as_bytes().to_vec();
+        bytes[BACKUP_MAGIC.len()] = BACKUP_VERSION + 1;
+        assert_eq!(
+            MemberSecretKeyBackup::from_bytes(&bytes).map(|_| ()),
+            Err(BackupError::UnsupportedVersion(BACKUP_VERSION + 1))
+        );
+        assert_eq!(
+            MemberSecretKeyBackup::from_bytes(&bytes[1..]).map(|_| ()),
+            Err(BackupError::InvalidSize {
+                expected: BACKUP_SIZE,
+                actual: BACKUP_SIZE - 1
+            })
+        );
+    }
+}
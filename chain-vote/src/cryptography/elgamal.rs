@@ -127,6 +127,15 @@ impl PublicKey {
         self.encrypt_point_with_r(&(&GroupElement::generator() * message), randomness)
     }
 
+    /// Re-randomize a `ciphertext` by adding an encryption of zero with the given
+    /// `randomness`, the plaintext is unchanged.
+    pub(crate) fn reencrypt(&self, ciphertext: &Ciphertext, randomness: &Scalar) -> Ciphertext {
+        Ciphertext {
+            e1: &ciphertext.e1 + &(&GroupElement::generator() * randomness),
+            e2: &ciphertext.e2 + &(&self.pk * randomness),
+        }
+    }
+
     /// Given a `message` passed as bytes, encrypt it using hybrid encryption.
     pub(crate) fn hybrid_encrypt<R>(&self, message: &[u8], rng: &mut R) -> HybridCiphertext
     where
@@ -0,0 +1,359 @@
+//! Non-interactive Zero Knowledge proof of correct shuffle of encrypted
+//! ballots, using the cut-and-choose technique of Sako and Kilian.
+//!
+//! The statement is two lists of encrypted ballots, `inputs` and `outputs`,
+//! and the witness a permutation `pi` with the re-encryption randomness `r`
+//! such that:
+//!
+//! `outputs[j] = inputs[pi(j)] + Enc(0; r[j])`
+//!
+//! For every round, the prover shuffles the inputs again into a shadow
+//! list, with a fresh permutation `sigma` and randomness `s`. Depending on
+//! the challenge bit of the round, it then opens either the shuffle from
+//! the inputs to the shadow list (`sigma`, `s`), or the one from the shadow
+//! list to the outputs (`sigma^-1 . pi`, `r - s`). Each opening alone
+//! reveals nothing about `pi`, and a prover which does not know a valid
+//! shuffle can answer at most one of the two challenges of every round, so
+//! it is caught with probability `1 - 2^-ROUNDS`.
+//!
+//! The challenge bits are derived from the statement and all the shadow
+//! lists. The size of the proof is linear in the number of rounds, the
+//! number of ballots and the number of options.
+use crate::cryptography::{Ciphertext, PublicKey};
+use crate::Scalar;
+use cryptoxide::blake2b::Blake2b;
+use cryptoxide::digest::Digest;
+use rand::seq::SliceRandom;
+use rand::{CryptoRng, RngCore};
+use rayon::prelude::*;
+
+/// A list of encrypted ballots, each with one ciphertext per option
+type Ballots = [Vec<Ciphertext>];
+
+const PERMUTATION_INDEX_LEN: usize = 4;
+
+/// Shuffle of a list of ballots into another one: `target[j]` is
+/// `source[permutation[j]]` re-encrypted with `randomness[j]`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Opening {
+    permutation: Vec<usize>,
+    randomness: Vec<Vec<Scalar>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Round {
+    shadow: Vec<Vec<Ciphertext>>,
+    opening: Opening,
+}
+
+/// Proof of correct shuffle.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Zkp {
+    rounds: Vec<Round>,
+}
+
+impl Opening {
+    fn shuffle(
+        pk: &PublicKey,
+        source: &Ballots,
+        permutation: &[usize],
+        randomness: &[Vec<Scalar>],
+    ) -> Vec<Vec<Ciphertext>> {
+        permutation
+            .iter()
+            .zip(randomness)
+            .map(|(i, r)| {
+                source[*i]
+                    .iter()
+                    .zip(r)
+                    .map(|(c, r)| pk.reencrypt(c, r))
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn verify(&self, pk: &PublicKey, source: &Ballots, target: &Ballots) -> bool {
+        is_permutation(&self.permutation, source.len())
+            && self.randomness.len() == target.len()
+            && Self::shuffle(pk, source, &self.permutation, &self.randomness) == target
+    }
+}
+
+impl Zkp {
+    /// Number of rounds of the proof, a proof of an invalid shuffle is
+    /// accepted with probability `2^-ROUNDS`
+    pub const ROUNDS: usize = 128;
+
+    /// Generate a proof that `outputs[j]` is `inputs[permutation[j]]`
+    /// re-encrypted with `randomness[j]`
+    pub fn generate<R>(
+        rng: &mut R,
+        pk: &PublicKey,
+        inputs: &Ballots,
+        outputs: &Ballots,
+        permutation: &[usize],
+        randomness: &[Vec<Scalar>],
+    ) -> Self
+    where
+        R: CryptoRng + RngCore,
+    {
+        let options = inputs.first().map_or(0, Vec::len);
+        let shadows: Vec<Opening> = (0..Self::ROUNDS)
+            .map(|_| {
+                let mut permutation: Vec<usize> = (0..inputs.len()).collect();
+                permutation.shuffle(rng);
+                let randomness = random_scalars(rng, inputs.len(), options);
+                Opening {
+                    permutation,
+                    randomness,
+                }
+            })
+            .collect();
+        let shadow_ballots: Vec<Vec<Vec<Ciphertext>>> = shadows
+            .par_iter()
+            .map(|opening| Opening::shuffle(pk, inputs, &opening.permutation, &opening.randomness))
+            .collect();
+
+        let challenge = challenge(pk, inputs, outputs, &shadow_ballots);
+        let rounds = shadows
+            .into_iter()
+            .zip(shadow_ballots)
+            .enumerate()
+            .map(|(round, (shadow_opening, shadow))| {
+                let opening = if challenge_bit(&challenge, round) {
+                    let mut inverse = vec![0; shadow_opening.permutation.len()];
+                    for (i, j) in shadow_opening.permutation.iter().enumerate() {
+                        inverse[*j] = i;
+                    }
+                    // shadow[to_shadow[j]] is re-encrypted into outputs[j]
+                    let to_shadow: Vec<usize> = permutation.iter().map(|j| inverse[*j]).collect();
+                    let randomness = to_shadow
+                        .iter()
+                        .zip(randomness)
+                        .map(|(i, r)| {
+                            r.iter()
+                                .zip(&shadow_opening.randomness[*i])
+                                .map(|(r, s)| r - s)
+                                .collect()
+                        })
+                        .collect();
+                    Opening {
+                        permutation: to_shadow,
+                        randomness,
+                    }
+                } else {
+                    shadow_opening
+                };
+                Round { shadow, opening }
+            })
+            .collect();
+
+        Zkp { rounds }
+    }
+
+    /// Verify that `outputs` is a shuffle of `inputs`
+    pub fn verify(&self, pk: &PublicKey, inputs: &Ballots, outputs: &Ballots) -> bool {
+        if self.rounds.len() != Self::ROUNDS
+            || inputs.len() != outputs.len()
+            || self
+                .rounds
+                .iter()
+                .any(|round| round.shadow.len() != inputs.len())
+        {
+            return false;
+        }
+        let shadows = self.rounds.iter().map(|round| &round.shadow);
+        let challenge = challenge(pk, inputs, outputs, shadows);
+        self.rounds
+            .par_iter()
+            .enumerate()
+            .all(|(round_index, round)| {
+                if challenge_bit(&challenge, round_index) {
+                    round.opening.verify(pk, &round.shadow, outputs)
+                } else {
+                    round.opening.verify(pk, inputs, &round.shadow)
+                }
+            })
+    }
+
+    /// Size of the byte representation of a proof for the given number of
+    /// ballots and options
+    pub fn bytes_len(ballots: usize, options: usize) -> usize {
+        let ballot_len =
+            options * (Ciphertext::BYTES_LEN + Scalar::BYTES_LEN) + PERMUTATION_INDEX_LEN;
+        ballot_len
+            .checked_mul(ballots)
+            .and_then(|round_len| round_len.checked_mul(Self::ROUNDS))
+            .expect("integer overflow")
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        for round in &self.rounds {
+            for ciphertext in round.shadow.iter().flatten() {
+                out.extend_from_slice(&ciphertext.to_bytes());
+            }
+            for index in &round.opening.permutation {
+                out.extend_from_slice(&(*index as u32).to_be_bytes());
+            }
+            for scalar in round.opening.randomness.iter().flatten() {
+                out.extend_from_slice(&scalar.to_bytes());
+            }
+        }
+        out
+    }
+
+    pub fn from_bytes(slice: &[u8], ballots: usize, options: usize) -> Option<Self> {
+        if slice.len() != Self::bytes_len(ballots, options) {
+            return None;
+        }
+        let mut rest = slice;
+        let mut take = |len: usize| {
+            let (chunk, tail) = rest.split_at(len);
+            rest = tail;
+            chunk
+        };
+        let mut rounds = Vec::with_capacity(Self::ROUNDS);
+        for _ in 0..Self::ROUNDS {
+            let mut shadow = Vec::with_capacity(ballots);
+            for _ in 0..ballots {
+                let mut ballot = Vec::with_capacity(options);
+                for _ in 0..options {
+                    ballot.push(Ciphertext::from_bytes(take(Ciphertext::BYTES_LEN))?);
+                }
+                shadow.push(ballot);
+            }
+            let mut permutation = Vec::with_capacity(ballots);
+            for _ in 0..ballots {
+                let index = take(PERMUTATION_INDEX_LEN).try_into().unwrap();
+                permutation.push(u32::from_be_bytes(index) as usize);
+            }
+            let mut randomness = Vec::with_capacity(ballots);
+            for _ in 0..ballots {
+                let mut ballot = Vec::with_capacity(options);
+                for _ in 0..options {
+                    ballot.push(Scalar::from_bytes(take(Scalar::BYTES_LEN))?);
+                }
+                randomness.push(ballot);
+            }
+            rounds.push(Round {
+                shadow,
+                opening: Opening {
+                    permutation,
+                    randomness,
+                },
+            });
+        }
+        Some(Zkp { rounds })
+    }
+}
+
+fn random_scalars<R>(rng: &mut R, ballots: usize, options: usize) -> Vec<Vec<Scalar>>
+where
+    R: CryptoRng + RngCore,
+{
+    (0..ballots)
+        .map(|_| (0..options).map(|_| Scalar::random(rng)).collect())
+        .collect()
+}
+
+fn is_permutation(permutation: &[usize], len: usize) -> bool {
+    let mut seen = vec![false; len];
+    permutation.len() == len
+        && permutation
+            .iter()
+            .all(|i| *i < len && !std::mem::replace(&mut seen[*i], true))
+}
+
+fn challenge<'a, I>(pk: &PublicKey, inputs: &Ballots, outputs: &Ballots, shadows: I) -> [u8; 64]
+where
+    I: IntoIterator<Item = &'a Vec<Vec<Ciphertext>>>,
+{
+    let mut ctx = Blake2b::new(64);
+    ctx.input(&pk.to_bytes());
+    for ciphertext in inputs.iter().chain(outputs).flatten() {
+        ctx.input(&ciphertext.to_bytes());
+    }
+    for shadow in shadows {
+        for ciphertext in shadow.iter().flatten() {
+            ctx.input(&ciphertext.to_bytes());
+        }
+    }
+    let mut challenge = [0u8; 64];
+    ctx.result(&mut challenge);
+    challenge
+}
+
+fn challenge_bit(challenge: &[u8; 64], round: usize) -> bool {
+    challenge[round / 8] & (1 << (round % 8)) != 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cryptography::Keypair;
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+
+    type Statement = (
+        Vec<Vec<Ciphertext>>,
+        Vec<Vec<Ciphertext>>,
+        Vec<usize>,
+        Vec<Vec<Scalar>>,
+    );
+
+    fn statement<R: CryptoRng + RngCore>(rng: &mut R, pk: &PublicKey) -> Statement {
+        let inputs: Vec<Vec<Ciphertext>> = (0..4u64)
+            .map(|i| {
+                vec![
+                    pk.encrypt(&Scalar::from_u64(i), rng),
+                    pk.encrypt(&Scalar::one(), rng),
+                ]
+            })
+            .collect();
+        let permutation = vec![2, 0, 3, 1];
+        let randomness = random_scalars(rng, 4, 2);
+        let outputs = Opening::shuffle(pk, &inputs, &permutation, &randomness);
+        (inputs, outputs, permutation, randomness)
+    }
+
+    #[test]
+    fn it_works() {
+        let mut r = ChaCha20Rng::from_seed([0u8; 32]);
+        let keypair = Keypair::generate(&mut r);
+        let pk = &keypair.public_key;
+        let (inputs, outputs, permutation, randomness) = statement(&mut r, pk);
+
+        let proof = Zkp::generate(&mut r, pk, &inputs, &outputs, &permutation, &randomness);
+        assert!(proof.verify(pk, &inputs, &outputs));
+    }
+
+    #[test]
+    fn altered_outputs_are_rejected() {
+        let mut r = ChaCha20Rng::from_seed([0u8; 32]);
+        let keypair = Keypair::generate(&mut r);
+        let pk = &keypair.public_key;
+        let (inputs, mut outputs, permutation, randomness) = statement(&mut r, pk);
+
+        let proof = Zkp::generate(&mut r, pk, &inputs, &outputs, &permutation, &randomness);
+        outputs[0][0] = pk.encrypt(&Scalar::from_u64(7), &mut r);
+        assert!(!proof.verify(pk, &inputs, &outputs));
+        assert!(!proof.verify(pk, &inputs[..3], &outputs[..3]));
+    }
+
+    #[test]
+    fn serialisation() {
+        let mut r = ChaCha20Rng::from_seed([0u8; 32]);
+        let keypair = Keypair::generate(&mut r);
+        let pk = &keypair.public_key;
+        let (inputs, outputs, permutation, randomness) = statement(&mut r, pk);
+
+        let proof = Zkp::generate(&mut r, pk, &inputs, &outputs, &permutation, &randomness);
+        let bytes = proof.to_bytes();
+        assert_eq!(bytes.len(), Zkp::bytes_len(4, 2));
+        let deserialised = Zkp::from_bytes(&bytes, 4, 2).unwrap();
+        assert_eq!(deserialised, proof);
+        assert!(deserialised.verify(pk, &inputs, &outputs));
+        assert!(Zkp::from_bytes(&bytes, 4, 3).is_none());
+    }
+}
@@ -233,6 +233,15 @@ impl Zkp {
         mega_check == GroupElement::zero()
     }
 
+    /// Size of the byte representation of a proof with the given number of
+    /// bits, the leading number of bits included
+    pub fn bytes_len(bits: usize) -> usize {
+        (Announcement::BYTES_LEN + Ciphertext::BYTES_LEN + ResponseRandomness::BYTES_LEN)
+            .checked_mul(bits)
+            .and_then(|len| len.checked_add(1 + Scalar::BYTES_LEN))
+            .expect("integer overflow")
+    }
+
     /// Try to generate a `Proof` from a buffer
     pub fn from_buffer(buf: &mut ReadBuf) -> Result<Self, ReadError> {
         let bits = buf.get_u8()? as usize;
@@ -240,28 +249,26 @@ impl Zkp {
         for _ in 0..bits {
             let elem_buf = buf.get_slice(Announcement::BYTES_LEN)?;
             let iba = Announcement::from_bytes(elem_buf)
-                .ok_or_else(|| ReadError::StructureInvalid("Invalid IBA component".to_string()))?;
+                .ok_or_else(|| ReadError::StructureInvalid("Invalid IBA component".into()))?;
             ibas.push(iba);
         }
         let mut bs = Vec::with_capacity(bits);
         for _ in 0..bits {
             let elem_buf = buf.get_slice(Ciphertext::BYTES_LEN)?;
-            let ciphertext = Ciphertext::from_bytes(elem_buf).ok_or_else(|| {
-                ReadError::StructureInvalid("Invalid encoded ciphertext".to_string())
-            })?;
+            let ciphertext = Ciphertext::from_bytes(elem_buf)
+                .ok_or_else(|| ReadError::StructureInvalid("Invalid encoded ciphertext".into()))?;
             bs.push(ciphertext);
         }
         let mut zwvs = Vec::with_capacity(bits);
         for _ in 0..bits {
             let elem_buf = buf.get_slice(ResponseRandomness::BYTES_LEN)?;
             let zwv = ResponseRandomness::from_bytes(elem_buf)
-                .ok_or_else(|| ReadError::StructureInvalid("Invalid ZWV component".to_string()))?;
+                .ok_or_else(|| ReadError::StructureInvalid("Invalid ZWV component".into()))?;
             zwvs.push(zwv);
         }
         let r_buf = buf.get_slice(Scalar::BYTES_LEN)?;
-        let r = Scalar::from_bytes(r_buf).ok_or_else(|| {
-            ReadError::StructureInvalid("Invalid Proof encoded R scalar".to_string())
-        })?;
+        let r = Scalar::from_bytes(r_buf)
+            .ok_or_else(|| ReadError::StructureInvalid("Invalid Proof encoded R scalar".into()))?;
 
         Ok(Self::from_parts(ibas, bs, zwvs, r))
     }
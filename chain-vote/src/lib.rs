@@ -1,9 +1,11 @@
 #[macro_use]
 mod macros;
+pub mod backup;
 pub mod committee;
 mod cryptography;
 mod encrypted_vote;
 mod math;
+pub mod mixing;
 pub mod tally;
 
 // re-export under a debug module
@@ -27,8 +29,10 @@ const CURVE_HRP: &str = "ristretto255";
 pub use math::babystep::BabyStepsTable as TallyOptimizationTable;
 
 pub use crate::{
+    backup::{BackupError, BackupParams, MemberSecretKeyBackup},
     committee::{ElectionPublicKey, MemberCommunicationKey, MemberPublicKey, MemberState},
     cryptography::Ciphertext, //todo: why this?
     encrypted_vote::{Ballot, BallotVerificationError, EncryptedVote, ProofOfCorrectVote, Vote},
+    mixing::{BallotMix, MixRound, MixingError},
     tally::{Crs, EncryptedTally, Tally, TallyDecryptShare},
 };
@@ -0,0 +1,299 @@
+//! Optional mixing phase of the private tally.
+//!
+//! Instead of being aggregated as soon as they are submitted, the ballots can
+//! be collected in a [`BallotMix`] and mixed by the committee members in
+//! turns before the aggregation. Each member re-encrypts all the ballots with
+//! fresh randomness and shuffles them, publishing a [`MixRound`] with a proof
+//! that its output is a shuffle of its input. Once a single honest member has
+//! mixed the ballots, nobody can link a mixed ballot to its voter.
+//!
+//! The weight of a ballot is applied when the ballot is added to the mix, so
+//! the [`EncryptedTally`] computed from the mixed ballots is the same as the
+//! one computed without mixing, and is decrypted the same way.
+//!
+//! The mixing is standalone: the private tally of the ledger aggregates the
+//! ballots as they are submitted and never builds nor checks a mixing round.
+//! The proof of a round is large: with its 128 cut-and-choose rounds, it
+//! takes `128 * ballots * (options * 96 + 4)` bytes with the ristretto255
+//! backend, 12,800 bytes per ballot of an election with a single option.
+
+use crate::{
+    cryptography::{Ciphertext, CorrectShuffleZkp},
+    encrypted_vote::{Ballot, EncryptedVote},
+    tally::{ElectionFingerprint, EncryptedTally},
+    Crs, ElectionPublicKey, Scalar,
+};
+use rand::seq::SliceRandom;
+use rand_core::{CryptoRng, RngCore};
+
+/// The weighted ballots of an election, to be mixed before the aggregation
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BallotMix {
+    options: usize,
+    ballots: Vec<EncryptedVote>,
+    fingerprint: ElectionFingerprint,
+}
+
+/// A mixing round performed by a committee member: the shuffled ballots and
+/// the proof of correct shuffle
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MixRound {
+    ballots: Vec<EncryptedVote>,
+    proof: CorrectShuffleZkp,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum MixingError {
+    #[error("expected {expected} mixed ballots, {actual} were given")]
+    BallotCountMismatch { expected: usize, actual: usize },
+    #[error("a mixed ballot does not have the {0} options of the election")]
+    OptionsMismatch(usize),
+    #[error("Invalid proof of correct shuffle")]
+    InvalidProof,
+}
+
+impl BallotMix {
+    /// Initialise an empty mix for an election with N different options
+    pub fn new(options: usize, election_pk: &ElectionPublicKey, crs: &Crs) -> Self {
+        BallotMix {
+            options,
+            ballots: Vec::new(),
+            fingerprint: (election_pk, crs).into(),
+        }
+    }
+
+    /// Add a submitted `ballot` with a specific `weight` to the mix. Same as
+    /// for [`EncryptedTally::add`], the ballot needs to be verified for the
+    /// same election and to have the same number of options, otherwise an
+    /// assert will trigger.
+    pub fn add(&mut self, ballot: &Ballot, weight: u64) {
+        assert_eq!(ballot.vote().len(), self.options);
+        assert_eq!(ballot.fingerprint(), &self.fingerprint);
+        self.ballots
+            .push(ballot.vote().iter().map(|c| c * weight).collect());
+    }
+
+    pub fn options(&self) -> usize {
+        self.options
+    }
+
+    /// The weighted ballots, in the order of the last mixing round
+    pub fn ballots(&self) -> &[EncryptedVote] {
+        &self.ballots
+    }
+
+    pub(crate) fn fingerprint(&self) -> &ElectionFingerprint {
+        &self.fingerprint
+    }
+
+    /// Re-encrypt and shuffle the ballots, proving the shuffle is correct
+    pub fn mix<R: RngCore + CryptoRng>(
+        &self,
+        rng: &mut R,
+        election_pk: &ElectionPublicKey,
+    ) -> MixRound {
+        let mut permutation: Vec<usize> = (0..self.ballots.len()).collect();
+        permutation.shuffle(rng);
+        let randomness: Vec<Vec<Scalar>> = (0..self.ballots.len())
+            .map(|_| (0..self.options).map(|_| Scalar::random(rng)).collect())
+            .collect();
+        let ballots = permutation
+            .iter()
+            .zip(&randomness)
+            .map(|(i, r)| {
+                self.ballots[*i]
+                    .iter()
+                    .zip(r)
+                    .map(|(c, r)| election_pk.as_raw().reencrypt(c, r))
+                    .collect()
+            })
+            .collect::<Vec<_>>();
+        let proof = CorrectShuffleZkp::generate(
+            rng,
+            election_pk.as_raw(),
+            &self.ballots,
+            &ballots,
+            &permutation,
+            &randomness,
+        );
+        MixRound { ballots, proof }
+    }
+
+    /// Verify that the `round` is a correct mix of the ballots
+    pub fn verify(
+        &self,
+        round: &MixRound,
+        election_pk: &ElectionPublicKey,
+    ) -> Result<(), MixingError> {
+        if round.ballots.len() != self.ballots.len() {
+            return Err(MixingError::BallotCountMismatch {
+                expected: self.ballots.len(),
+                actual: round.ballots.len(),
+            });
+        }
+        if round
+            .ballots
+            .iter()
+            .any(|ballot| ballot.len() != self.options)
+        {
+            return Err(MixingError::OptionsMismatch(self.options));
+        }
+        if !round
+            .proof
+            .verify(election_pk.as_raw(), &self.ballots, &round.ballots)
+        {
+            return Err(MixingError::InvalidProof);
+        }
+        Ok(())
+    }
+
+    /// Verify the `round` and return the mix with its shuffled ballots, ready
+    /// for the next round or the aggregation
+    pub fn apply(
+        &self,
+        round: &MixRound,
+        election_pk: &ElectionPublicKey,
+    ) -> Result<Self, MixingError> {
+        self.verify(round, election_pk)?;
+        Ok(BallotMix {
+            options: self.options,
+            ballots: round.ballots.clone(),
+            fingerprint: self.fingerprint,
+        })
+    }
+}
+
+impl MixRound {
+    /// The shuffled ballots
+    pub fn ballots(&self) -> &[EncryptedVote] {
+        &self.ballots
+    }
+
+    /// Size of the byte representation of a mixing round for the given
+    /// number of ballots and options
+    pub fn bytes_len(ballots: usize, options: usize) -> usize {
+        Ciphertext::BYTES_LEN
+            .checked_mul(options)
+            .and_then(|ballot_len| ballot_len.checked_mul(ballots))
+            .and_then(|len| len.checked_add(CorrectShuffleZkp::bytes_len(ballots, options)))
+            .expect("integer overflow")
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        for ciphertext in self.ballots.iter().flatten() {
+            out.extend_from_slice(&ciphertext.to_bytes());
+        }
+        out.extend_from_slice(&self.proof.to_bytes());
+        out
+    }
+
+    /// Parse a mixing round of a mix of `ballots` ballots with `options`
+    /// options each
+    pub fn from_bytes(bytes: &[u8], ballots: usize, options: usize) -> Option<Self> {
+        if bytes.len() != Self::bytes_len(ballots, options) {
+            return None;
+        }
+        let (ciphertexts, proof) = bytes.split_at(ballots * options * Ciphertext::BYTES_LEN);
+        let mut ciphertexts = ciphertexts
+            .chunks(Ciphertext::BYTES_LEN)
+            .map(Ciphertext::from_bytes);
+        let mixed = (0..ballots)
+            .map(|_| ciphertexts.by_ref().take(options).collect())
+            .collect::<Option<Vec<_>>>()?;
+        let proof = CorrectShuffleZkp::from_bytes(proof, ballots, options)?;
+        Some(MixRound {
+            ballots: mixed,
+            proof,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        committee::{MemberCommunicationKey, MemberState},
+        encrypted_vote::Vote,
+        TallyOptimizationTable,
+    };
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+
+    #[test]
+    fn mixed_ballots_tally() {
+        let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+        let h = Crs::from_hash(b"mixed ballots tally");
+
+        let mc = [
+            MemberCommunicationKey::new(&mut rng).to_public(),
+            MemberCommunicationKey::new(&mut rng).to_public(),
+        ];
+        let m1 = MemberState::new(&mut rng, 2, &h, &mc, 0);
+        let m2 = MemberState::new(&mut rng, 2, &h, &mc, 1);
+        let participants = vec![m1.public_key(), m2.public_key()];
+        let ek = ElectionPublicKey::from_participants(&participants);
+
+        let vote_options = 3;
+        let mut mix = BallotMix::new(vote_options, &ek, &h);
+        for (option, weight) in [(0, 6), (1, 5), (0, 4), (2, 1)] {
+            let (vote, proof) =
+                ek.encrypt_and_prove_vote(&mut rng, &h, Vote::new(vote_options, option));
+            let ballot = Ballot::try_from_vote_and_proof(vote, &proof, &h, &ek).unwrap();
+            mix.add(&ballot, weight);
+        }
+
+        // every member mixes the output of the previous one
+        for _ in &participants {
+            let round = mix.mix(&mut rng, &ek);
+            let bytes = round.to_bytes();
+            assert_eq!(bytes.len(), MixRound::bytes_len(4, vote_options));
+            let round = MixRound::from_bytes(&bytes, 4, vote_options).unwrap();
+            mix = mix.apply(&round, &ek).unwrap();
+        }
+
+        let encrypted_tally = EncryptedTally::from_mix(&mix);
+        let shares = vec![
+            encrypted_tally.partial_decrypt(&mut rng, m1.secret_key()),
+            encrypted_tally.partial_decrypt(&mut rng, m2.secret_key()),
+        ];
+        let table = TallyOptimizationTable::generate_with_balance(16, 1);
+        let tally = encrypted_tally
+            .validate_partial_decryptions(&participants, &shares)
+            .unwrap()
+            .decrypt_tally(16, &table)
+            .unwrap();
+        assert_eq!(tally.votes, vec![10, 5, 1]);
+    }
+
+    #[test]
+    fn tampered_round_is_rejected() {
+        let mut rng = ChaCha20Rng::from_seed([1u8; 32]);
+        let h = Crs::from_hash(b"tampered round");
+
+        let mc = [MemberCommunicationKey::new(&mut rng).to_public()];
+        let m1 = MemberState::new(&mut rng, 1, &h, &mc, 0);
+        let ek = ElectionPublicKey::from_participants(&[m1.public_key()]);
+
+        let mut mix = BallotMix::new(2, &ek, &h);
+        for option in [0, 1, 1] {
+            let (vote, proof) = ek.encrypt_and_prove_vote(&mut rng, &h, Vote::new(2, option));
+            let ballot = Ballot::try_from_vote_and_proof(vote, &proof, &h, &ek).unwrap();
+            mix.add(&ballot, 1);
+        }
+
+        let mut round = mix.mix(&mut rng, &ek);
+        // replace a ballot with a copy of another one
+        round.ballots[0] = round.ballots[1].clone();
+        assert_eq!(mix.apply(&round, &ek), Err(MixingError::InvalidProof));
+
+        round.ballots.pop();
+        assert_eq!(
+            mix.verify(&round, &ek),
+            Err(MixingError::BallotCountMismatch {
+                expected: 3,
+                actual: 2
+            })
+        );
+    }
+}
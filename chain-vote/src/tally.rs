@@ -3,6 +3,7 @@ use crate::{
     cryptography::{Ciphertext, CorrectShareGenerationZkp},
     encrypted_vote::Ballot,
     math::babystep::baby_step_giant_step,
+    mixing::BallotMix,
     TallyOptimizationTable,
 };
 
@@ -125,6 +126,21 @@ impl EncryptedTally {
         }
     }
 
+    /// Aggregate the ballots of a mix, after the optional mixing phase. The
+    /// weights of the ballots were applied when they were added to the mix.
+    pub fn from_mix(mix: &BallotMix) -> Self {
+        let mut r = vec![Ciphertext::zero(); mix.options()];
+        for ballot in mix.ballots() {
+            for (ri, ci) in r.iter_mut().zip(ballot.iter()) {
+                *ri = &*ri + ci;
+            }
+        }
+        EncryptedTally {
+            r,
+            fingerprint: *mix.fingerprint(),
+        }
+    }
+
     /// Given a single committee member's `secret_key`, returns a partial decryption of
     /// the `EncryptedTally`
     pub fn partial_decrypt<R: RngCore + CryptoRng>(
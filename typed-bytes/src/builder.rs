@@ -35,6 +35,16 @@ impl<T> ByteBuilder<T> {
         }
     }
 
+    /// Number of bytes written in the builder so far
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Check if nothing has been written in the builder yet
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
     /// Append an u8 in the builder
     pub fn u8(self, v: u8) -> Self {
         let mut buf = self.buffer;